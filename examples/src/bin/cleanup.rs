@@ -0,0 +1,53 @@
+//! Remove leftovers of failed operations from a store.
+//!
+//! Usage: `cleanup <uri> [--dry-run] [--older-than-hours <hours>]`
+
+use std::{sync::Arc, time::Duration};
+
+use objstore::{ObjStore as _, ObjStoreBuilder, maintenance::CleanupOptions};
+
+#[tokio::main]
+async fn main() {
+    let builder = ObjStoreBuilder::new()
+        .with_provider(Arc::new(objstore_memory::MemoryProvider::new()))
+        .with_provider(Arc::new(objstore_fs::FsProvider::new()))
+        .with_provider(Arc::new(objstore_s3_light::S3LightProvider::new()));
+
+    let mut uri = None;
+    let mut options = CleanupOptions::new();
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--dry-run" => options = options.with_dry_run(true),
+            "--older-than-hours" => {
+                let hours: u64 = args
+                    .next()
+                    .and_then(|value| value.parse().ok())
+                    .expect("--older-than-hours requires a number");
+                options = options.with_older_than(Duration::from_secs(hours * 60 * 60));
+            }
+            _ => uri = Some(arg),
+        }
+    }
+    let uri = uri.expect("usage: cleanup <uri> [--dry-run] [--older-than-hours <hours>]");
+
+    let store = builder
+        .build(&uri)
+        .expect("Failed to create object store from URI");
+    let dry_run = options.dry_run();
+    let report = store.cleanup(options).await.expect("cleanup failed");
+
+    let verb = if dry_run { "Would remove" } else { "Removed" };
+    for item in &report.removed {
+        println!("{verb} {:?}: {}", item.kind, item.key);
+    }
+    for (item, err) in &report.failed {
+        eprintln!("Failed to remove {:?} {}: {err}", item.kind, item.key);
+    }
+    println!(
+        "{verb} {} leftovers ({} bytes), {} failed",
+        report.removed.len(),
+        report.removed_bytes(),
+        report.failed.len()
+    );
+}
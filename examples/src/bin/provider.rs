@@ -1,13 +1,9 @@
-use std::sync::Arc;
-
 use objstore::{ObjStoreBuilder, ObjStoreExt};
+use objstore_all::ObjStoreBuilderExt as _;
 
 #[tokio::main]
 async fn main() {
-    let builder = ObjStoreBuilder::new()
-        .with_provider(Arc::new(objstore_memory::MemoryProvider::new()))
-        .with_provider(Arc::new(objstore_fs::FsProvider::new()))
-        .with_provider(Arc::new(objstore_s3_light::S3LightProvider::new()));
+    let builder = ObjStoreBuilder::new().with_default_providers();
 
     // let uri = "memory://";
     // let uri = "fs:///tmp/my_store";
@@ -0,0 +1,417 @@
+//! Snapshots of a prefix into another store, and restoring them.
+//!
+//! A backup store contains content-addressed blobs and one manifest per
+//! backup, which maps the backed up keys to blobs:
+//!
+//! * `manifests/<id>.json`: the [`BackupManifest`]
+//! * `blobs/<aa>/<sha256>`: object data, keyed by the hex sha256 hash
+//!
+//! Blobs are shared between backups, so unchanged objects are only stored
+//! once. Use a [`PrefixObjStore`](crate::wrapper::prefix::PrefixObjStore) to
+//! place backups below a prefix of the backup store.
+//!
+//! See [`backup`] and [`restore`].
+
+use std::collections::HashMap;
+
+use bytes::Bytes;
+use futures::{StreamExt as _, TryStreamExt as _};
+use sha2::{Digest as _, Sha256};
+use time::OffsetDateTime;
+
+use crate::{ListArgs, ObjStore, ObjStoreError, ObjectMeta, Operation, Put, Result};
+
+const MANIFEST_PREFIX: &str = "manifests/";
+const MANIFEST_SUFFIX: &str = ".json";
+const BLOB_PREFIX: &str = "blobs/";
+
+/// Describes the objects of one backup.
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[non_exhaustive]
+pub struct BackupManifest {
+    /// Format version of the manifest.
+    pub version: u32,
+    /// Backup id, ids of later backups sort after earlier ones.
+    pub id: String,
+    /// The backed up prefix of the source store.
+    pub prefix: String,
+    /// Creation time as a unix timestamp in seconds.
+    pub created_at: i64,
+    /// Backed up objects, ordered by key.
+    pub entries: Vec<ManifestEntry>,
+}
+
+impl BackupManifest {
+    pub const VERSION: u32 = 1;
+
+    /// Total size of all objects in the backup.
+    pub fn total_size(&self) -> u64 {
+        self.entries.iter().map(|entry| entry.size).sum()
+    }
+}
+
+/// A backed up object.
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[non_exhaustive]
+pub struct ManifestEntry {
+    /// Key relative to [`BackupManifest::prefix`].
+    pub key: String,
+    /// Hex sha256 hash of the data, which identifies the blob.
+    pub blob: String,
+    pub size: u64,
+    /// Etag of the source object, used to detect unchanged objects.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub etag: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mime_type: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cache_control: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub content_disposition: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub content_encoding: Option<String>,
+}
+
+impl ManifestEntry {
+    fn new(key: String, blob: String, meta: &ObjectMeta, size: u64) -> Self {
+        Self {
+            key,
+            blob,
+            size,
+            etag: meta.etag.clone(),
+            mime_type: meta.mime_type.clone(),
+            cache_control: meta.cache_control.clone(),
+            content_disposition: meta.content_disposition.clone(),
+            content_encoding: meta.content_encoding.clone(),
+        }
+    }
+
+    /// Whether the object is unchanged since the entry was created.
+    ///
+    /// Requires an etag, since sizes alone are not reliable.
+    fn matches(&self, meta: &ObjectMeta) -> bool {
+        self.etag.is_some() && self.etag == meta.etag && Some(self.size) == meta.size
+    }
+}
+
+/// Options for [`backup`].
+#[derive(Clone, Debug)]
+pub struct BackupOptions {
+    concurrency: usize,
+    incremental: bool,
+}
+
+impl Default for BackupOptions {
+    fn default() -> Self {
+        Self {
+            concurrency: Self::DEFAULT_CONCURRENCY,
+            incremental: true,
+        }
+    }
+}
+
+impl BackupOptions {
+    pub const DEFAULT_CONCURRENCY: usize = 8;
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Maximum number of objects transferred at the same time.
+    pub fn concurrency(&self) -> usize {
+        self.concurrency
+    }
+
+    /// Set the maximum number of concurrent transfers. Values of `0` are ignored.
+    pub fn with_concurrency(mut self, concurrency: usize) -> Self {
+        if concurrency > 0 {
+            self.concurrency = concurrency;
+        }
+        self
+    }
+
+    /// Whether unchanged objects are taken from the latest backup.
+    pub fn incremental(&self) -> bool {
+        self.incremental
+    }
+
+    /// Reuse the blobs of objects that are unchanged since the latest backup,
+    /// without reading them. Enabled by default.
+    ///
+    /// Objects are unchanged if their etag and size match the latest backup.
+    /// When disabled, all objects are read, but existing blobs are still not
+    /// uploaded again.
+    pub fn with_incremental(mut self, incremental: bool) -> Self {
+        self.incremental = incremental;
+        self
+    }
+}
+
+/// Result of a [`backup`].
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub struct BackupReport {
+    /// The manifest of the new backup.
+    pub manifest: BackupManifest,
+    /// Number of blobs that were uploaded.
+    pub uploaded: u64,
+    /// Total size of the uploaded blobs.
+    pub uploaded_bytes: u64,
+    /// Number of objects that reused an existing blob.
+    pub reused: u64,
+}
+
+/// Options for [`restore`].
+#[derive(Clone, Debug)]
+pub struct RestoreOptions {
+    concurrency: usize,
+}
+
+impl Default for RestoreOptions {
+    fn default() -> Self {
+        Self {
+            concurrency: Self::DEFAULT_CONCURRENCY,
+        }
+    }
+}
+
+impl RestoreOptions {
+    pub const DEFAULT_CONCURRENCY: usize = 8;
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Maximum number of objects restored at the same time.
+    pub fn concurrency(&self) -> usize {
+        self.concurrency
+    }
+
+    /// Set the maximum number of concurrent restores. Values of `0` are ignored.
+    pub fn with_concurrency(mut self, concurrency: usize) -> Self {
+        if concurrency > 0 {
+            self.concurrency = concurrency;
+        }
+        self
+    }
+}
+
+/// Result of a [`restore`].
+#[derive(Clone, Debug, Default)]
+#[non_exhaustive]
+pub struct RestoreReport {
+    /// Number of restored objects.
+    pub restored: u64,
+    /// Total size of the restored objects.
+    pub restored_bytes: u64,
+}
+
+/// Back up all objects below `prefix` in `source` into `target`.
+///
+/// Objects are read into memory one at a time per concurrent transfer, and
+/// stored as blobs unless a blob with the same content already exists.
+/// The manifest is written last, so an interrupted backup does not show up
+/// in [`list_backups`].
+pub async fn backup<S, T>(
+    source: &S,
+    prefix: &str,
+    target: &T,
+    options: BackupOptions,
+) -> Result<BackupReport>
+where
+    S: ObjStore + ?Sized,
+    T: ObjStore + ?Sized,
+{
+    let previous = match (options.incremental, latest_backup(target).await?) {
+        (true, Some(id)) => Some(load_manifest(target, &id).await?),
+        _ => None,
+    };
+    // Only backups of the same prefix describe the same objects.
+    let previous = previous
+        .filter(|manifest| manifest.prefix == prefix)
+        .map(|manifest| manifest.entries)
+        .unwrap_or_default()
+        .into_iter()
+        .map(|entry| (entry.key.clone(), entry))
+        .collect::<HashMap<_, _>>();
+    let previous = &previous;
+
+    let mut results = source
+        .list_items_stream(ListArgs::new().with_prefix(prefix))
+        .map_ok(|meta| async move {
+            let key = meta
+                .key
+                .strip_prefix(prefix)
+                .unwrap_or(&meta.key)
+                .to_string();
+            if let Some(entry) = previous.get(&key).filter(|entry| entry.matches(&meta)) {
+                return Ok(Some((entry.clone(), None)));
+            }
+
+            // The object may have been deleted since it was listed.
+            let Some(data) = source.get(&meta.key).await? else {
+                return Ok(None);
+            };
+            let size = data.len() as u64;
+            let blob = format!("{:x}", Sha256::digest(&data));
+            let blob_key = blob_key(&blob);
+            let uploaded = if target.exists(&blob_key).await? {
+                None
+            } else {
+                target.send_put(Put::new(blob_key, data)).await?;
+                Some(size)
+            };
+            Ok::<_, ObjStoreError>(Some((ManifestEntry::new(key, blob, &meta, size), uploaded)))
+        })
+        .try_buffer_unordered(options.concurrency);
+
+    let mut entries = Vec::new();
+    let mut uploaded = 0;
+    let mut uploaded_bytes = 0;
+    let mut reused = 0;
+    while let Some(result) = results.try_next().await? {
+        let Some((entry, upload)) = result else {
+            continue;
+        };
+        match upload {
+            Some(size) => {
+                uploaded += 1;
+                uploaded_bytes += size;
+            }
+            None => reused += 1,
+        }
+        entries.push(entry);
+    }
+    drop(results);
+    entries.sort_by(|a, b| a.key.cmp(&b.key));
+
+    let now = OffsetDateTime::now_utc();
+    let manifest = BackupManifest {
+        version: BackupManifest::VERSION,
+        id: format!("{:020}", now.unix_timestamp_nanos()),
+        prefix: prefix.to_string(),
+        created_at: now.unix_timestamp(),
+        entries,
+    };
+    let data = serde_json::to_vec(&manifest).map_err(|source| ObjStoreError::Internal {
+        message: "could not serialize backup manifest".to_string(),
+        source: Some(source.into()),
+    })?;
+    let mut put = Put::new(manifest_key(&manifest.id), Bytes::from(data));
+    put.mime_type = Some("application/json".to_string());
+    target.send_put(put).await?;
+
+    Ok(BackupReport {
+        manifest,
+        uploaded,
+        uploaded_bytes,
+        reused,
+    })
+}
+
+/// Ids of all backups in `target`, oldest first.
+pub async fn list_backups<T>(target: &T) -> Result<Vec<String>>
+where
+    T: ObjStore + ?Sized,
+{
+    let mut ids = target
+        .list_all_keys(MANIFEST_PREFIX)
+        .await?
+        .into_iter()
+        .filter_map(|key| {
+            key.strip_prefix(MANIFEST_PREFIX)?
+                .strip_suffix(MANIFEST_SUFFIX)
+                .map(str::to_string)
+        })
+        .collect::<Vec<_>>();
+    ids.sort();
+    Ok(ids)
+}
+
+/// Id of the most recent backup in `target`.
+pub async fn latest_backup<T>(target: &T) -> Result<Option<String>>
+where
+    T: ObjStore + ?Sized,
+{
+    Ok(list_backups(target).await?.pop())
+}
+
+/// Load the manifest of the backup with the given id.
+pub async fn load_manifest<T>(target: &T, id: &str) -> Result<BackupManifest>
+where
+    T: ObjStore + ?Sized,
+{
+    let key = manifest_key(id);
+    let data = target
+        .get(&key)
+        .await?
+        .ok_or_else(|| ObjStoreError::object_not_found(&key))?;
+    let jd = &mut serde_json::Deserializer::from_slice(&data);
+    serde_path_to_error::deserialize(jd).map_err(|source| ObjStoreError::ContentDeserialization {
+        key,
+        format: "json".to_string(),
+        source: Some(Box::new(source)),
+    })
+}
+
+/// Restore the backup with the given id from `target` into `dest`, below `prefix`.
+///
+/// Objects are written to `prefix` followed by their key relative to the
+/// backed up prefix, replacing existing objects. Objects not in the backup
+/// are left untouched.
+///
+/// The content of each blob is verified against its hash before writing.
+pub async fn restore<T, D>(
+    target: &T,
+    id: &str,
+    dest: &D,
+    prefix: &str,
+    options: RestoreOptions,
+) -> Result<RestoreReport>
+where
+    T: ObjStore + ?Sized,
+    D: ObjStore + ?Sized,
+{
+    let manifest = load_manifest(target, id).await?;
+
+    let mut results = futures::stream::iter(manifest.entries)
+        .map(|entry| async move {
+            let blob_key = blob_key(&entry.blob);
+            let data = target
+                .get(&blob_key)
+                .await?
+                .ok_or_else(|| ObjStoreError::object_not_found(&blob_key))?;
+            if format!("{:x}", Sha256::digest(&data)) != entry.blob {
+                return Err(ObjStoreError::Response {
+                    operation: Operation::Get,
+                    source: Some(format!("sha256 hash mismatch for blob '{blob_key}'").into()),
+                });
+            }
+
+            let size = data.len() as u64;
+            let mut put = Put::new(format!("{prefix}{}", entry.key), data);
+            put.mime_type = entry.mime_type;
+            put.cache_control = entry.cache_control;
+            put.content_disposition = entry.content_disposition;
+            put.content_encoding = entry.content_encoding;
+            dest.send_put(put).await?;
+            Ok(size)
+        })
+        .buffer_unordered(options.concurrency);
+
+    let mut report = RestoreReport::default();
+    while let Some(size) = results.try_next().await? {
+        report.restored += 1;
+        report.restored_bytes += size;
+    }
+    Ok(report)
+}
+
+fn manifest_key(id: &str) -> String {
+    format!("{MANIFEST_PREFIX}{id}{MANIFEST_SUFFIX}")
+}
+
+fn blob_key(hash: &str) -> String {
+    let dir = hash.get(..2).unwrap_or(hash);
+    format!("{BLOB_PREFIX}{dir}/{hash}")
+}
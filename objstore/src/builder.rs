@@ -1,10 +1,22 @@
-use std::sync::Arc;
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
 
-use crate::{ObjStoreError, ObjStoreProvider, Result, store::DynObjStore};
+use crate::{
+    EnvSecretResolver, FileSecretResolver, HttpClientConfig, ObjStoreError, ObjStoreProvider,
+    Result, SecretResolver, store::DynObjStore,
+};
 
+/// Builds stores from URIs, using the registered providers.
+///
+/// Clones share the cache of [`Self::build_cached`].
 #[derive(Clone, Debug)]
 pub struct ObjStoreBuilder {
     providers: Vec<Arc<dyn ObjStoreProvider>>,
+    http_client: Option<HttpClientConfig>,
+    secret_resolvers: Vec<Arc<dyn SecretResolver>>,
+    cache: Arc<Mutex<HashMap<String, DynObjStore>>>,
 }
 
 impl Default for ObjStoreBuilder {
@@ -17,6 +29,9 @@ impl ObjStoreBuilder {
     pub fn new() -> Self {
         Self {
             providers: Vec::new(),
+            http_client: None,
+            secret_resolvers: Vec::new(),
+            cache: Arc::new(Mutex::new(HashMap::new())),
         }
     }
 
@@ -29,17 +44,107 @@ impl ObjStoreBuilder {
         self
     }
 
+    /// HTTP client settings passed to the providers.
+    pub fn http_client(&self) -> Option<&HttpClientConfig> {
+        self.http_client.as_ref()
+    }
+
+    /// Use `config` for the HTTP clients of all stores built afterwards.
+    ///
+    /// See [`ObjStoreProvider::build_with_http_client`].
+    /// Stores that were already cached by [`Self::build_cached`] keep their
+    /// clients, see [`Self::clear_cache`].
+    pub fn with_http_client(mut self, config: HttpClientConfig) -> Self {
+        self.http_client = Some(config);
+        self
+    }
+
+    /// Resolve secret references with `resolver` before building stores.
+    ///
+    /// See [`SecretResolver`]. A resolver for an already registered scheme
+    /// replaces the previous one.
+    pub fn with_secret_resolver(mut self, resolver: Arc<dyn SecretResolver>) -> Self {
+        self.secret_resolvers
+            .retain(|existing| existing.scheme() != resolver.scheme());
+        self.secret_resolvers.push(resolver);
+        self
+    }
+
+    /// Resolve `env:` and `file:` secret references.
+    ///
+    /// Only use this for trusted URIs, since references can read any
+    /// environment variable or file accessible to the process.
+    pub fn with_default_secret_resolvers(self) -> Self {
+        self.with_secret_resolver(Arc::new(EnvSecretResolver))
+            .with_secret_resolver(Arc::new(FileSecretResolver))
+    }
+
+    /// Build a new store for `uri`.
     pub fn build(&self, uri: &str) -> Result<DynObjStore> {
-        let url = url::Url::parse(uri).map_err(|source| ObjStoreError::InvalidConfig {
-            message: format!("invalid URL: {uri}"),
-            source: Some(source.into()),
-        })?;
+        self.build_url(&parse_uri(uri)?)
+    }
+
+    /// Build a store for `uri`, or return the store previously built for it.
+    ///
+    /// Stores are cached by the normalized URI, so repeated calls share the
+    /// same instance, including HTTP clients and connection pools.
+    /// Failed builds are not cached.
+    ///
+    /// See [`Self::invalidate`] and [`Self::clear_cache`].
+    pub fn build_cached(&self, uri: &str) -> Result<DynObjStore> {
+        let url = parse_uri(uri)?;
+        if let Some(store) = self.cache().get(url.as_str()) {
+            return Ok(store.clone());
+        }
+
+        // Built outside the lock, so a concurrent build for the same URI may win.
+        let store = self.build_url(&url)?;
+        let store = self.cache().entry(url.into()).or_insert(store).clone();
+        Ok(store)
+    }
+
+    /// Remove the cached store for `uri`.
+    ///
+    /// Returns `true` if a store was cached.
+    pub fn invalidate(&self, uri: &str) -> bool {
+        match parse_uri(uri) {
+            Ok(url) => self.cache().remove(url.as_str()).is_some(),
+            Err(_) => false,
+        }
+    }
 
+    /// Remove all cached stores.
+    pub fn clear_cache(&self) {
+        self.cache().clear();
+    }
+
+    /// Build a store, after resolving secret references in `url`.
+    ///
+    /// Stores are cached by the unresolved URI, so resolved secrets never
+    /// end up in cache keys.
+    fn build_url(&self, url: &url::Url) -> Result<DynObjStore> {
+        let url = crate::secret::resolve_url(url, &self.secret_resolvers)?;
+        let url = url.as_ref();
         for provider in &self.providers {
             if provider.url_scheme() == url.scheme() {
-                return provider.build(&url);
+                return match &self.http_client {
+                    Some(http) => provider.build_with_http_client(url, http),
+                    None => provider.build(url),
+                };
             }
         }
         Err(ObjStoreError::provider_not_found(url.scheme()))
     }
+
+    fn cache(&self) -> std::sync::MutexGuard<'_, HashMap<String, DynObjStore>> {
+        // The cache is always left consistent, so poisoning can be ignored.
+        self.cache.lock().unwrap_or_else(|err| err.into_inner())
+    }
+}
+
+fn parse_uri(uri: &str) -> Result<url::Url> {
+    url::Url::parse(uri).map_err(|source| ObjStoreError::InvalidConfig {
+        message: format!("invalid URL: {}", crate::redact_uri(uri)),
+        source: Some(source.into()),
+    })
 }
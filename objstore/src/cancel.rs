@@ -0,0 +1,116 @@
+//! Cooperative cancellation of long-running operations.
+
+use std::{
+    pin::Pin,
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicBool, Ordering},
+    },
+    task::{Context, Poll, Waker},
+};
+
+use futures::future::{self, Either};
+
+use crate::{ObjStoreError, Operation, Result};
+
+/// A token that allows cancelling long-running operations.
+///
+/// Clones share the same state: cancelling one clone cancels all of them.
+///
+/// Operations that accept a token stop at the next opportunity once the token
+/// is cancelled, and fail with [`ObjStoreError::Cancelled`].
+#[derive(Clone, Default)]
+pub struct CancellationToken {
+    inner: Arc<Inner>,
+}
+
+#[derive(Default)]
+struct Inner {
+    cancelled: AtomicBool,
+    wakers: Mutex<Vec<Waker>>,
+}
+
+impl std::fmt::Debug for CancellationToken {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CancellationToken")
+            .field("cancelled", &self.is_cancelled())
+            .finish()
+    }
+}
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Cancel the token, and wake up all tasks waiting for cancellation.
+    pub fn cancel(&self) {
+        if !self.inner.cancelled.swap(true, Ordering::SeqCst) {
+            let wakers = std::mem::take(&mut *self.inner.wakers.lock().unwrap());
+            for waker in wakers {
+                waker.wake();
+            }
+        }
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.inner.cancelled.load(Ordering::SeqCst)
+    }
+
+    /// Return [`ObjStoreError::Cancelled`] if the token was cancelled.
+    pub fn check(&self, operation: Operation) -> Result<()> {
+        if self.is_cancelled() {
+            Err(ObjStoreError::cancelled(operation))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Wait until the token is cancelled.
+    pub fn cancelled(&self) -> WaitForCancellation<'_> {
+        WaitForCancellation { token: self }
+    }
+
+    /// Run `fut` to completion, unless the token is cancelled first.
+    ///
+    /// On cancellation, `fut` is dropped and [`ObjStoreError::Cancelled`] is returned.
+    pub async fn run_until_cancelled<T, F>(&self, operation: Operation, fut: F) -> Result<T>
+    where
+        F: Future<Output = Result<T>>,
+    {
+        self.check(operation)?;
+        let fut = std::pin::pin!(fut);
+        match future::select(fut, self.cancelled()).await {
+            Either::Left((res, _)) => res,
+            Either::Right(((), _)) => Err(ObjStoreError::cancelled(operation)),
+        }
+    }
+}
+
+/// Future returned by [`CancellationToken::cancelled`].
+#[derive(Debug)]
+pub struct WaitForCancellation<'a> {
+    token: &'a CancellationToken,
+}
+
+impl Future for WaitForCancellation<'_> {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        if self.token.is_cancelled() {
+            return Poll::Ready(());
+        }
+        {
+            let mut wakers = self.token.inner.wakers.lock().unwrap();
+            if !wakers.iter().any(|waker| waker.will_wake(cx.waker())) {
+                wakers.push(cx.waker().clone());
+            }
+        }
+        // Re-check to avoid missing a cancellation that raced with the registration.
+        if self.token.is_cancelled() {
+            Poll::Ready(())
+        } else {
+            Poll::Pending
+        }
+    }
+}
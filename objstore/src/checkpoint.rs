@@ -0,0 +1,205 @@
+//! Resumable listings.
+//!
+//! See [`ScanCheckpoint`] and [`crate::ObjStoreExt::list_checkpointed`].
+
+use futures::{Future, StreamExt as _, TryStreamExt as _, stream, stream::BoxStream};
+
+use crate::{
+    KeyPage, ListArgs, ObjStore, ObjStoreError, ObjStoreExt as _, ObjectMetaPage, Operation, Result,
+};
+
+/// Serializable progress of a paginated listing.
+///
+/// Long running jobs over large stores (sync, usage, garbage collection)
+/// can persist the checkpoint, for example in the store itself with
+/// [`Self::save`], and resume the listing after a restart instead of
+/// starting over.
+///
+/// Checkpoints are emitted by [`crate::ObjStoreExt::list_checkpointed`] and
+/// [`crate::ObjStoreExt::list_keys_checkpointed`] after the pages they cover,
+/// so a resumed job sees every item at least once.
+#[derive(Clone, Debug, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[non_exhaustive]
+pub struct ScanCheckpoint {
+    /// Prefix of the listing.
+    ///
+    /// Resuming with different list arguments fails.
+    pub prefix: Option<String>,
+    /// Cursor of the next page, `None` before the first page and when done.
+    pub cursor: Option<String>,
+    /// Number of pages listed so far.
+    pub pages: u64,
+    /// Number of items listed so far.
+    pub items: u64,
+    /// Whether the listing is complete.
+    pub done: bool,
+}
+
+impl ScanCheckpoint {
+    /// A checkpoint at the start of a listing with `args`.
+    pub fn new(args: &ListArgs) -> Self {
+        Self {
+            prefix: args.prefix().map(str::to_owned),
+            cursor: args.cursor().map(str::to_owned),
+            ..Self::default()
+        }
+    }
+
+    /// `args` with the cursor set to continue after this checkpoint.
+    pub fn resume_args(&self, args: ListArgs) -> ListArgs {
+        match &self.cursor {
+            Some(cursor) => args.with_cursor(cursor.clone()),
+            None => args,
+        }
+    }
+
+    /// Load a checkpoint stored as JSON with [`Self::save`].
+    pub async fn load<S>(store: &S, key: &str) -> Result<Option<Self>>
+    where
+        S: ObjStore,
+    {
+        store.get_json(key).await
+    }
+
+    /// Store the checkpoint as JSON.
+    pub async fn save<S>(&self, store: &S, key: &str) -> Result<()>
+    where
+        S: ObjStore,
+    {
+        store.put(key).json(self).await?;
+        Ok(())
+    }
+}
+
+/// An item of a checkpointed listing.
+#[derive(Clone, Debug)]
+pub enum ScanEvent<P> {
+    /// A listed page.
+    Page(P),
+    /// Progress up to and including the previous page.
+    Checkpoint(ScanCheckpoint),
+}
+
+/// Stream returned by [`crate::ObjStoreExt::list_checkpointed`].
+pub type ScanStream<'a, P> = BoxStream<'a, Result<ScanEvent<P>>>;
+
+trait ScanPage {
+    fn len(&self) -> usize;
+    fn next_cursor(&self) -> Option<&str>;
+}
+
+impl ScanPage for ObjectMetaPage {
+    fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    fn next_cursor(&self) -> Option<&str> {
+        self.next_cursor.as_deref()
+    }
+}
+
+impl ScanPage for KeyPage {
+    fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    fn next_cursor(&self) -> Option<&str> {
+        self.next_cursor.as_deref()
+    }
+}
+
+pub(crate) fn list_checkpointed<S>(
+    store: &S,
+    args: ListArgs,
+    checkpoint: Option<ScanCheckpoint>,
+    every: usize,
+) -> ScanStream<'_, ObjectMetaPage>
+where
+    S: ObjStore + ?Sized,
+{
+    scan(args, checkpoint, every, Operation::List, move |args| {
+        store.list(args)
+    })
+}
+
+pub(crate) fn list_keys_checkpointed<S>(
+    store: &S,
+    args: ListArgs,
+    checkpoint: Option<ScanCheckpoint>,
+    every: usize,
+) -> ScanStream<'_, KeyPage>
+where
+    S: ObjStore + ?Sized,
+{
+    scan(args, checkpoint, every, Operation::ListKeys, move |args| {
+        store.list_keys(args)
+    })
+}
+
+fn scan<'a, P, F, Fut>(
+    args: ListArgs,
+    checkpoint: Option<ScanCheckpoint>,
+    every: usize,
+    operation: Operation,
+    fetch: F,
+) -> ScanStream<'a, P>
+where
+    P: ScanPage + Send + 'a,
+    F: Fn(ListArgs) -> Fut + Clone + Send + 'a,
+    Fut: Future<Output = Result<P>> + Send + 'a,
+{
+    let checkpoint = match checkpoint {
+        None => ScanCheckpoint::new(&args),
+        Some(checkpoint) if checkpoint.prefix.as_deref() != args.prefix() => {
+            let err = ObjStoreError::InvalidRequest {
+                message: format!(
+                    "checkpoint prefix {:?} does not match the listed prefix {:?}",
+                    checkpoint.prefix,
+                    args.prefix()
+                ),
+                source: None,
+            };
+            return stream::once(async { Err(err) }).boxed();
+        }
+        Some(checkpoint) if checkpoint.done => return stream::empty().boxed(),
+        Some(checkpoint) => checkpoint,
+    };
+    let every = every.max(1) as u64;
+    let args = checkpoint.resume_args(args);
+
+    stream::try_unfold(Some((args, checkpoint)), move |state| {
+        let fetch = fetch.clone();
+        async move {
+            let Some((args, mut checkpoint)) = state else {
+                return Ok(None);
+            };
+            let page = match args.cancellation() {
+                Some(token) => {
+                    token
+                        .run_until_cancelled(operation, fetch(args.clone()))
+                        .await?
+                }
+                None => fetch(args.clone()).await?,
+            };
+
+            let next = match page.next_cursor() {
+                Some(cursor) if Some(cursor) != args.cursor() => Some(cursor.to_owned()),
+                _ => None,
+            };
+            checkpoint.pages += 1;
+            checkpoint.items += page.len() as u64;
+            checkpoint.cursor = next.clone();
+            checkpoint.done = next.is_none();
+
+            let mut events = vec![ScanEvent::Page(page)];
+            if checkpoint.done || checkpoint.pages.is_multiple_of(every) {
+                events.push(ScanEvent::Checkpoint(checkpoint.clone()));
+            }
+            let state = next.map(|cursor| (args.with_cursor(cursor), checkpoint));
+            Ok::<_, ObjStoreError>(Some((events, state)))
+        }
+    })
+    .map_ok(|events| stream::iter(events.into_iter().map(Ok)))
+    .try_flatten()
+    .boxed()
+}
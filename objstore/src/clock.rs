@@ -0,0 +1,63 @@
+//! Time sources for backends that assign timestamps themselves.
+//!
+//! See [`Clock`].
+
+use std::sync::{Arc, Mutex};
+
+use time::OffsetDateTime;
+
+/// Source of the current time.
+///
+/// Backends that set [`crate::ObjectMeta::created_at`] and
+/// [`crate::ObjectMeta::updated_at`] themselves (like the memory store) can
+/// be given a [`ManualClock`] to make timestamps deterministic in tests.
+pub trait Clock: Send + Sync + std::fmt::Debug {
+    fn now(&self) -> OffsetDateTime;
+}
+
+/// The system clock, in UTC.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> OffsetDateTime {
+        OffsetDateTime::now_utc()
+    }
+}
+
+/// A clock that only moves when it is set or advanced.
+///
+/// Clones share the same time.
+#[derive(Clone, Debug)]
+pub struct ManualClock {
+    now: Arc<Mutex<OffsetDateTime>>,
+}
+
+impl ManualClock {
+    pub fn new(now: OffsetDateTime) -> Self {
+        Self {
+            now: Arc::new(Mutex::new(now)),
+        }
+    }
+
+    pub fn set(&self, now: OffsetDateTime) {
+        *self.now.lock().unwrap() = now;
+    }
+
+    pub fn advance(&self, by: std::time::Duration) {
+        *self.now.lock().unwrap() += by;
+    }
+}
+
+impl Default for ManualClock {
+    /// A clock at the Unix epoch.
+    fn default() -> Self {
+        Self::new(OffsetDateTime::UNIX_EPOCH)
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> OffsetDateTime {
+        *self.now.lock().unwrap()
+    }
+}
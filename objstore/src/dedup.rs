@@ -0,0 +1,62 @@
+//! Content comparison for [`crate::PutBuilder::skip_if_unchanged`].
+
+use sha2::{Digest as _, Sha256};
+
+use crate::ObjectMeta;
+
+/// Whether `data` is known to equal the content of the object described by
+/// `meta`.
+///
+/// Uses the sha256 or md5 hash of the metadata if available, and falls back
+/// to etags that encode a hash (`sha256:<hex>`, or the plain md5 etags of S3
+/// single part uploads). Returns `false` if the content can not be compared.
+pub(crate) fn content_matches(meta: &ObjectMeta, data: &[u8]) -> bool {
+    if meta.size.is_some_and(|size| size != data.len() as u64) {
+        return false;
+    }
+    if let Some(hash) = &meta.hash_sha256 {
+        return Sha256::digest(data).as_slice() == hash;
+    }
+    if let Some(hash) = &meta.hash_md5 {
+        return md5::compute(data).0 == *hash;
+    }
+
+    let Some(etag) = meta.etag.as_deref().map(|etag| etag.trim_matches('"')) else {
+        return false;
+    };
+    if let Some(hex) = etag.strip_prefix("sha256:") {
+        return hex.eq_ignore_ascii_case(&format!("{:x}", Sha256::digest(data)));
+    }
+    if etag.len() == 32 && etag.chars().all(|c| c.is_ascii_hexdigit()) {
+        return etag.eq_ignore_ascii_case(&format!("{:x}", md5::compute(data)));
+    }
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_content_matches() {
+        let data = b"hello";
+        let mut meta = ObjectMeta::new("a".to_string());
+        assert!(!content_matches(&meta, data));
+
+        meta.etag = Some("\"5d41402abc4b2a76b9719d911017c592\"".to_string());
+        assert!(content_matches(&meta, data));
+        assert!(!content_matches(&meta, b"world"));
+
+        meta.etag = Some(format!("sha256:{:x}", Sha256::digest(data)));
+        assert!(content_matches(&meta, data));
+
+        meta.size = Some(4);
+        assert!(!content_matches(&meta, data));
+
+        let mut meta = ObjectMeta::new("a".to_string());
+        meta.hash_sha256 = Some(Sha256::digest(b"world").into());
+        // Hashes take precedence over etags.
+        meta.etag = Some(format!("sha256:{:x}", Sha256::digest(data)));
+        assert!(!content_matches(&meta, data));
+    }
+}
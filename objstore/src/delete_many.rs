@@ -0,0 +1,62 @@
+//! Bulk deletion of many objects.
+//!
+//! See [`crate::ObjStoreExt::delete_many`].
+
+use futures::{StreamExt as _, stream};
+
+use crate::{ObjStore, Result};
+
+/// Stream of `(key, result)` pairs, as returned by [`crate::ObjStoreExt::delete_many`].
+pub type DeleteManyStream<'a> = futures::stream::BoxStream<'a, (String, Result<()>)>;
+
+/// Options for [`crate::ObjStoreExt::delete_many`].
+#[derive(Clone, Debug)]
+pub struct DeleteManyOptions {
+    concurrency: usize,
+}
+
+impl Default for DeleteManyOptions {
+    fn default() -> Self {
+        Self {
+            concurrency: Self::DEFAULT_CONCURRENCY,
+        }
+    }
+}
+
+impl DeleteManyOptions {
+    pub const DEFAULT_CONCURRENCY: usize = 8;
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Maximum number of concurrent delete requests.
+    pub fn concurrency(&self) -> usize {
+        self.concurrency
+    }
+
+    /// Set the maximum number of concurrent delete requests. Values of `0` are ignored.
+    pub fn with_concurrency(mut self, concurrency: usize) -> Self {
+        if concurrency > 0 {
+            self.concurrency = concurrency;
+        }
+        self
+    }
+}
+
+pub(crate) fn delete_many<S>(
+    store: &S,
+    keys: Vec<String>,
+    options: DeleteManyOptions,
+) -> DeleteManyStream<'_>
+where
+    S: ObjStore + ?Sized,
+{
+    stream::iter(keys)
+        .map(move |key| async move {
+            let res = store.delete(&key).await;
+            (key, res)
+        })
+        .buffer_unordered(options.concurrency)
+        .boxed()
+}
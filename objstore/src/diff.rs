@@ -0,0 +1,261 @@
+//! Comparing the objects of two stores, or of two prefixes in one store.
+//!
+//! See [`diff`] for a streaming comparison, and [`diff_report`] to collect
+//! the differences.
+
+use futures::{StreamExt as _, TryStreamExt as _, stream, stream::BoxStream};
+
+use crate::{ListArgs, ObjStore, ObjectMeta, Result, SortOrder};
+
+/// A difference between the two sides of a [`diff`].
+///
+/// Keys are relative to the compared prefixes.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DiffEntry {
+    /// The object only exists in `b`.
+    Added { key: String, b: ObjectMeta },
+    /// The object only exists in `a`.
+    Removed { key: String, a: ObjectMeta },
+    /// The object exists on both sides, but differs.
+    Changed {
+        key: String,
+        a: Box<ObjectMeta>,
+        b: Box<ObjectMeta>,
+    },
+}
+
+impl DiffEntry {
+    /// The key, relative to the compared prefixes.
+    pub fn key(&self) -> &str {
+        match self {
+            Self::Added { key, .. } | Self::Removed { key, .. } | Self::Changed { key, .. } => key,
+        }
+    }
+}
+
+/// Stream of differences, as returned by [`diff`].
+///
+/// Entries are yielded in ascending key order.
+pub type DiffStream<'a> = BoxStream<'a, Result<DiffEntry>>;
+
+/// Options for [`diff`].
+#[derive(Clone, Debug, Default)]
+pub struct DiffOptions {
+    prefix_a: String,
+    prefix_b: String,
+    compare_etags: bool,
+}
+
+impl DiffOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Prefix to compare in store `a`.
+    pub fn prefix_a(&self) -> &str {
+        &self.prefix_a
+    }
+
+    pub fn with_prefix_a(mut self, prefix: impl Into<String>) -> Self {
+        self.prefix_a = prefix.into();
+        self
+    }
+
+    /// Prefix to compare in store `b`.
+    pub fn prefix_b(&self) -> &str {
+        &self.prefix_b
+    }
+
+    pub fn with_prefix_b(mut self, prefix: impl Into<String>) -> Self {
+        self.prefix_b = prefix.into();
+        self
+    }
+
+    /// Whether etags are compared if no hashes are available.
+    pub fn compare_etags(&self) -> bool {
+        self.compare_etags
+    }
+
+    /// Compare etags if no hashes are available on both sides.
+    ///
+    /// Disabled by default, because etags are backend specific: only enable
+    /// this when both sides use the same backend.
+    pub fn with_compare_etags(mut self, compare_etags: bool) -> Self {
+        self.compare_etags = compare_etags;
+        self
+    }
+
+    /// Whether two objects with the same key differ.
+    ///
+    /// Objects differ if their sizes differ, or the strongest hash known on
+    /// both sides differs. Unknown values are treated as equal.
+    pub fn differs(&self, a: &ObjectMeta, b: &ObjectMeta) -> bool {
+        if let (Some(a), Some(b)) = (a.size, b.size)
+            && a != b
+        {
+            return true;
+        }
+        if let (Some(a), Some(b)) = (a.hash_sha256, b.hash_sha256) {
+            return a != b;
+        }
+        if let (Some(a), Some(b)) = (a.hash_md5, b.hash_md5) {
+            return a != b;
+        }
+        if self.compare_etags
+            && let (Some(a), Some(b)) = (&a.etag, &b.etag)
+        {
+            return a != b;
+        }
+        false
+    }
+}
+
+/// Summary of all differences, see [`diff_report`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct DiffReport {
+    /// Objects only in `b`.
+    pub added: Vec<DiffEntry>,
+    /// Objects only in `a`.
+    pub removed: Vec<DiffEntry>,
+    /// Objects on both sides that differ.
+    pub changed: Vec<DiffEntry>,
+}
+
+impl DiffReport {
+    /// Whether both sides are equal.
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+
+    fn push(&mut self, entry: DiffEntry) {
+        match entry {
+            DiffEntry::Added { .. } => self.added.push(entry),
+            DiffEntry::Removed { .. } => self.removed.push(entry),
+            DiffEntry::Changed { .. } => self.changed.push(entry),
+        }
+    }
+}
+
+/// Compare the objects below [`DiffOptions::prefix_a`] in `a` with the
+/// objects below [`DiffOptions::prefix_b`] in `b`, by relative key.
+///
+/// Both listings are streamed and merged if the stores list in key order
+/// (see [`ObjStore::supports_sort`]). Otherwise the listing of that side is
+/// loaded into memory and sorted first.
+///
+/// To compare two prefixes of one store, pass the store as both `a` and `b`.
+pub fn diff<'a, A, B>(a: &'a A, b: &'a B, options: DiffOptions) -> DiffStream<'a>
+where
+    A: ObjStore + ?Sized,
+    B: ObjStore + ?Sized,
+{
+    let a = Side::new(a, options.prefix_a.clone());
+    let b = Side::new(b, options.prefix_b.clone());
+
+    stream::try_unfold((a, b, options), |(mut a, mut b, options)| async move {
+        loop {
+            a.fill().await?;
+            b.fill().await?;
+            let order = match (&a.next, &b.next) {
+                (None, None) => return Ok(None),
+                (Some(_), None) => std::cmp::Ordering::Less,
+                (None, Some(_)) => std::cmp::Ordering::Greater,
+                (Some((key_a, _)), Some((key_b, _))) => key_a.cmp(key_b),
+            };
+            let entry = match order {
+                std::cmp::Ordering::Less => {
+                    let (key, a) = a.next.take().expect("checked above");
+                    DiffEntry::Removed { key, a }
+                }
+                std::cmp::Ordering::Greater => {
+                    let (key, b) = b.next.take().expect("checked above");
+                    DiffEntry::Added { key, b }
+                }
+                std::cmp::Ordering::Equal => {
+                    let (key, meta_a) = a.next.take().expect("checked above");
+                    let (_, meta_b) = b.next.take().expect("checked above");
+                    if !options.differs(&meta_a, &meta_b) {
+                        continue;
+                    }
+                    DiffEntry::Changed {
+                        key,
+                        a: Box::new(meta_a),
+                        b: Box::new(meta_b),
+                    }
+                }
+            };
+            return Ok(Some((entry, (a, b, options))));
+        }
+    })
+    .boxed()
+}
+
+/// Compare two stores or prefixes like [`diff`], and collect all differences.
+pub async fn diff_report<A, B>(a: &A, b: &B, options: DiffOptions) -> Result<DiffReport>
+where
+    A: ObjStore + ?Sized,
+    B: ObjStore + ?Sized,
+{
+    let mut report = DiffReport::default();
+    let mut entries = diff(a, b, options);
+    while let Some(entry) = entries.try_next().await? {
+        report.push(entry);
+    }
+    Ok(report)
+}
+
+/// One side of a diff, with the next unprocessed object.
+struct Side<'a> {
+    items: BoxStream<'a, Result<ObjectMeta>>,
+    prefix: String,
+    next: Option<(String, ObjectMeta)>,
+    done: bool,
+}
+
+impl<'a> Side<'a> {
+    fn new<S>(store: &'a S, prefix: String) -> Self
+    where
+        S: ObjStore + ?Sized,
+    {
+        let args = ListArgs::new().with_prefix(prefix.clone());
+        let items = if store.supports_sort(SortOrder::KeyAscending) {
+            store.list_items_stream(args.with_sort(SortOrder::KeyAscending))
+        } else {
+            stream::once(async move {
+                let mut items = store
+                    .list_items_stream(args)
+                    .try_collect::<Vec<_>>()
+                    .await?;
+                items.sort_by(|a, b| a.key.cmp(&b.key));
+                Ok::<_, crate::ObjStoreError>(stream::iter(items).map(Ok))
+            })
+            .try_flatten()
+            .boxed()
+        };
+        Self {
+            items,
+            prefix,
+            next: None,
+            done: false,
+        }
+    }
+
+    async fn fill(&mut self) -> Result<()> {
+        if self.next.is_some() || self.done {
+            return Ok(());
+        }
+        match self.items.try_next().await? {
+            Some(meta) => {
+                let key = meta
+                    .key
+                    .strip_prefix(&self.prefix)
+                    .unwrap_or(&meta.key)
+                    .to_string();
+                self.next = Some((key, meta));
+            }
+            None => self.done = true,
+        }
+        Ok(())
+    }
+}
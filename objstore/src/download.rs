@@ -0,0 +1,332 @@
+//! Parallel, range-based downloads.
+//!
+//! See [`crate::ObjStoreExt::download_parallel`].
+
+use std::ops::Range;
+#[cfg(feature = "backoff")]
+use std::time::Duration;
+
+use bytes::Bytes;
+use futures::{
+    AsyncWrite, AsyncWriteExt as _, StreamExt as _, TryStreamExt as _,
+    stream::{self},
+};
+
+use crate::{ObjStore, ObjStoreError, ObjectMeta, Operation, ProgressCallback, Resource, Result};
+
+/// Options for [`crate::ObjStoreExt::download_parallel`].
+#[derive(Clone, Debug)]
+pub struct ParallelDownloadOptions {
+    chunk_size: u64,
+    concurrency: usize,
+    max_retries: u32,
+    progress: Option<ProgressCallback>,
+    #[cfg(feature = "rate-limit")]
+    rate_limit: Option<u64>,
+    #[cfg(feature = "backoff")]
+    retry_backoff: Duration,
+}
+
+impl Default for ParallelDownloadOptions {
+    fn default() -> Self {
+        Self {
+            chunk_size: Self::DEFAULT_CHUNK_SIZE,
+            concurrency: Self::DEFAULT_CONCURRENCY,
+            max_retries: Self::DEFAULT_MAX_RETRIES,
+            progress: None,
+            #[cfg(feature = "rate-limit")]
+            rate_limit: None,
+            #[cfg(feature = "backoff")]
+            retry_backoff: Self::DEFAULT_RETRY_BACKOFF,
+        }
+    }
+}
+
+impl ParallelDownloadOptions {
+    pub const DEFAULT_CHUNK_SIZE: u64 = 8 * 1024 * 1024;
+    pub const DEFAULT_CONCURRENCY: usize = 4;
+    pub const DEFAULT_MAX_RETRIES: u32 = 3;
+    #[cfg(feature = "backoff")]
+    pub const DEFAULT_RETRY_BACKOFF: Duration = Duration::from_millis(100);
+    #[cfg(feature = "backoff")]
+    const MAX_RETRY_BACKOFF: Duration = Duration::from_secs(10);
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Size of each downloaded range in bytes.
+    pub fn chunk_size(&self) -> u64 {
+        self.chunk_size
+    }
+
+    /// Set the range size. Values of `0` are ignored.
+    pub fn with_chunk_size(mut self, chunk_size: u64) -> Self {
+        if chunk_size > 0 {
+            self.chunk_size = chunk_size;
+        }
+        self
+    }
+
+    /// Maximum number of concurrently downloaded ranges.
+    pub fn concurrency(&self) -> usize {
+        self.concurrency
+    }
+
+    /// Set the maximum number of concurrent range requests. Values of `0` are ignored.
+    pub fn with_concurrency(mut self, concurrency: usize) -> Self {
+        if concurrency > 0 {
+            self.concurrency = concurrency;
+        }
+        self
+    }
+
+    /// How often a single range is retried after a transient error.
+    ///
+    /// With the `backoff` feature, retries are delayed by
+    /// [`Self::retry_backoff`], doubling after every attempt. Otherwise
+    /// ranges are retried immediately.
+    pub fn max_retries(&self) -> u32 {
+        self.max_retries
+    }
+
+    pub fn with_max_retries(mut self, max_retries: u32) -> Self {
+        self.max_retries = max_retries;
+        self
+    }
+
+    /// Callback that receives the download progress.
+    pub fn progress(&self) -> Option<&ProgressCallback> {
+        self.progress.as_ref()
+    }
+
+    /// Report the progress after each chunk was written.
+    pub fn with_progress(mut self, callback: ProgressCallback) -> Self {
+        self.progress = Some(callback);
+        self
+    }
+
+    /// Delay before the first retry of a range.
+    #[cfg(feature = "backoff")]
+    pub fn retry_backoff(&self) -> Duration {
+        self.retry_backoff
+    }
+
+    /// Set the delay before the first retry of a range.
+    ///
+    /// The delay doubles after every failed attempt, up to 10 seconds.
+    #[cfg(feature = "backoff")]
+    pub fn with_retry_backoff(mut self, backoff: Duration) -> Self {
+        self.retry_backoff = backoff;
+        self
+    }
+
+    /// Retry delay after `attempt` failed attempts.
+    #[cfg(feature = "backoff")]
+    fn backoff(&self, attempt: u32) -> Duration {
+        self.retry_backoff
+            .saturating_mul(1 << attempt.saturating_sub(1).min(16))
+            .min(Self::MAX_RETRY_BACKOFF)
+    }
+
+    /// Bandwidth limit in bytes per second.
+    #[cfg(feature = "rate-limit")]
+    pub fn rate_limit(&self) -> Option<u64> {
+        self.rate_limit
+    }
+
+    /// Limit the download bandwidth to `bytes_per_sec`, across all ranges.
+    /// Values of `0` are ignored.
+    ///
+    /// Ranges are requested no faster than the limit allows, so up to
+    /// [`Self::concurrency`] ranges may be in flight at once.
+    #[cfg(feature = "rate-limit")]
+    pub fn with_rate_limit(mut self, bytes_per_sec: u64) -> Self {
+        if bytes_per_sec > 0 {
+            self.rate_limit = Some(bytes_per_sec);
+        }
+        self
+    }
+}
+
+pub(crate) async fn download_parallel<S, W>(
+    store: &S,
+    key: &str,
+    writer: &mut W,
+    opts: ParallelDownloadOptions,
+) -> Result<Option<ObjectMeta>>
+where
+    S: ObjStore + ?Sized,
+    W: AsyncWrite + Unpin,
+{
+    let Some(meta) = store.meta(key).await? else {
+        return Ok(None);
+    };
+    let size = match meta.size {
+        Some(size) if size > opts.chunk_size => size,
+        // Unknown or small sizes are not worth splitting up.
+        _ => return download_sequential(store, key, writer, &opts).await,
+    };
+
+    // The first range doubles as a probe for range support.
+    let first = match get_range_with_retry(store, key, 0..opts.chunk_size, &opts).await {
+        Ok(data) => data,
+        Err(ObjStoreError::Unsupported { .. }) => {
+            return download_sequential(store, key, writer, &opts).await;
+        }
+        Err(err) => return Err(err),
+    };
+    write_all(writer, &first).await?;
+    let mut written = first.len() as u64;
+    report(&opts, written, Some(size));
+
+    let ranges = (opts.chunk_size..size)
+        .step_by(opts.chunk_size as usize)
+        .map(|start| start..(start + opts.chunk_size).min(size));
+    #[cfg(feature = "rate-limit")]
+    let start = tokio::time::Instant::now();
+    let mut chunks = stream::iter(ranges)
+        .map(|range| async {
+            #[cfg(feature = "rate-limit")]
+            if let Some(bytes_per_sec) = opts.rate_limit {
+                let due = crate::io::RateLimiter::due(range.start, bytes_per_sec);
+                tokio::time::sleep_until(start + due).await;
+            }
+            get_range_with_retry(store, key, range, &opts).await
+        })
+        .buffered(opts.concurrency);
+    while let Some(chunk) = chunks.next().await {
+        let chunk = chunk?;
+        write_all(writer, &chunk).await?;
+        written += chunk.len() as u64;
+        report(&opts, written, Some(size));
+    }
+    // Checked once all ranges were read, so the download fails instead of
+    // silently mixing ranges of different object versions.
+    check_etag(store, key, meta.etag.as_deref()).await?;
+    writer
+        .flush()
+        .await
+        .map_err(|err| io_error(Operation::GetRange, err))?;
+
+    Ok(Some(meta))
+}
+
+async fn get_range_with_retry<S>(
+    store: &S,
+    key: &str,
+    range: Range<u64>,
+    opts: &ParallelDownloadOptions,
+) -> Result<Bytes>
+where
+    S: ObjStore + ?Sized,
+{
+    let expected_len = range.end - range.start;
+    let mut attempt = 0;
+    loop {
+        match store.get_range(key, range.clone()).await {
+            Ok(Some(data)) if data.len() as u64 == expected_len => return Ok(data),
+            Ok(Some(data)) => {
+                return Err(ObjStoreError::Response {
+                    operation: Operation::GetRange,
+                    source: Some(
+                        format!(
+                            "expected {expected_len} bytes for range {}..{} of '{key}', got {}",
+                            range.start,
+                            range.end,
+                            data.len()
+                        )
+                        .into(),
+                    ),
+                });
+            }
+            // The object was removed while the download was in progress.
+            Ok(None) => return Err(ObjStoreError::object_not_found(key)),
+            Err(err) if err.is_transient() && attempt < opts.max_retries => {
+                attempt += 1;
+                #[cfg(feature = "backoff")]
+                tokio::time::sleep(opts.backoff(attempt)).await;
+            }
+            Err(err) => return Err(err),
+        }
+    }
+}
+
+/// Fail if the object changed since the download started.
+///
+/// Checked after reading the last range, so any change before that read is
+/// detected.
+async fn check_etag<S>(store: &S, key: &str, etag: Option<&str>) -> Result<()>
+where
+    S: ObjStore + ?Sized,
+{
+    let Some(etag) = etag else {
+        return Ok(());
+    };
+    let Some(meta) = store.meta(key).await? else {
+        return Err(ObjStoreError::object_not_found(key));
+    };
+    if meta.etag.as_deref() == Some(etag) {
+        return Ok(());
+    }
+    Err(ObjStoreError::PreconditionFailed {
+        operation: Operation::GetRange,
+        resource: Some(Resource::Object {
+            key: key.to_string(),
+        }),
+        source: Some(format!("'{key}' changed during the download").into()),
+    })
+}
+
+async fn download_sequential<S, W>(
+    store: &S,
+    key: &str,
+    writer: &mut W,
+    opts: &ParallelDownloadOptions,
+) -> Result<Option<ObjectMeta>>
+where
+    S: ObjStore + ?Sized,
+    W: AsyncWrite + Unpin,
+{
+    let Some((meta, mut stream)) = store.get_stream_with_meta(key).await? else {
+        return Ok(None);
+    };
+    #[cfg(feature = "rate-limit")]
+    if let Some(bytes_per_sec) = opts.rate_limit {
+        stream = crate::io::limit_stream(stream, bytes_per_sec);
+    }
+    let mut written = 0;
+    while let Some(chunk) = stream.try_next().await? {
+        write_all(writer, &chunk).await?;
+        written += chunk.len() as u64;
+        report(opts, written, meta.size);
+    }
+    writer
+        .flush()
+        .await
+        .map_err(|err| io_error(Operation::GetStream, err))?;
+    Ok(Some(meta))
+}
+
+fn report(opts: &ParallelDownloadOptions, written: u64, total: Option<u64>) {
+    if let Some(progress) = &opts.progress {
+        progress.report(written, total);
+    }
+}
+
+async fn write_all<W>(writer: &mut W, data: &[u8]) -> Result<()>
+where
+    W: AsyncWrite + Unpin,
+{
+    writer
+        .write_all(data)
+        .await
+        .map_err(|err| io_error(Operation::Get, err))
+}
+
+fn io_error(operation: Operation, source: std::io::Error) -> ObjStoreError {
+    ObjStoreError::Io {
+        operation,
+        source: Some(source.into()),
+    }
+}
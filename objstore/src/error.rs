@@ -85,6 +85,11 @@ pub enum ObjStoreError {
         message: String,
         source: Option<BoxError>,
     },
+    /// The operation was cancelled via a [`crate::CancellationToken`].
+    Cancelled {
+        operation: Operation,
+        source: Option<BoxError>,
+    },
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -96,7 +101,9 @@ pub enum Operation {
     Meta,
     Get,
     GetStream,
+    GetRange,
     Put,
+    Append,
     Copy,
     Delete,
     DeletePrefix,
@@ -104,6 +111,7 @@ pub enum Operation {
     ListKeys,
     GenerateDownloadUrl,
     GenerateUploadUrl,
+    Watch,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -148,6 +156,13 @@ impl ObjStoreError {
         }
     }
 
+    pub fn cancelled(operation: Operation) -> Self {
+        Self::Cancelled {
+            operation,
+            source: None,
+        }
+    }
+
     pub fn unsupported(operation: Operation) -> Self {
         Self::Unsupported {
             operation,
@@ -168,6 +183,21 @@ impl ObjStoreError {
         }
     }
 
+    /// Whether the error is likely transient, and the operation may succeed when retried.
+    ///
+    /// I/O, timeout and dispatch failures as well as backend errors with a
+    /// 5xx (or unknown) status code are considered transient.
+    pub fn is_transient(&self) -> bool {
+        match self {
+            Self::Io { .. }
+            | Self::Timeout { .. }
+            | Self::Dispatch { .. }
+            | Self::Response { .. } => true,
+            Self::Backend { details, .. } => details.status.is_none_or(|status| status >= 500),
+            _ => false,
+        }
+    }
+
     pub fn with_source(mut self, source: impl Into<BoxError>) -> Self {
         let source = Some(source.into());
         match &mut self {
@@ -188,6 +218,7 @@ impl ObjStoreError {
             | Self::Response { source: field, .. }
             | Self::Backend { source: field, .. }
             | Self::Internal { source: field, .. }
+            | Self::Cancelled { source: field, .. }
             | Self::ProviderNotFound { source: field, .. } => *field = source,
         }
         self
@@ -253,6 +284,7 @@ impl fmt::Display for ObjStoreError {
             Self::Internal { message, .. } => {
                 write!(f, "internal objstore invariant violated: {message}")
             }
+            Self::Cancelled { operation, .. } => write!(f, "cancelled while {operation}"),
         }
     }
 }
@@ -277,6 +309,7 @@ impl StdError for ObjStoreError {
             | Self::Response { source, .. }
             | Self::Backend { source, .. }
             | Self::Internal { source, .. }
+            | Self::Cancelled { source, .. }
             | Self::ProviderNotFound { source, .. } => source.as_deref().map(|source| source as _),
         }
     }
@@ -291,7 +324,9 @@ impl fmt::Display for Operation {
             Self::Meta => "get metadata",
             Self::Get => "get object",
             Self::GetStream => "stream object",
+            Self::GetRange => "get object range",
             Self::Put => "put object",
+            Self::Append => "append to object",
             Self::Copy => "copy object",
             Self::Delete => "delete object",
             Self::DeletePrefix => "delete prefix",
@@ -299,6 +334,7 @@ impl fmt::Display for Operation {
             Self::ListKeys => "list keys",
             Self::GenerateDownloadUrl => "generate download URL",
             Self::GenerateUploadUrl => "generate upload URL",
+            Self::Watch => "watch prefix",
         };
         f.write_str(label)
     }
@@ -0,0 +1,35 @@
+//! Change notifications.
+//!
+//! See [`crate::ObjStore::watch`].
+
+use crate::Result;
+
+/// Stream of change events returned by [`crate::ObjStore::watch`].
+pub type EventStream = futures::stream::BoxStream<'static, Result<ObjectEvent>>;
+
+/// A change to an object.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ObjectEvent {
+    /// A new object was created.
+    Created { key: String },
+    /// An existing object was overwritten.
+    Updated { key: String },
+    /// An object was deleted.
+    Deleted { key: String },
+}
+
+impl ObjectEvent {
+    /// The key of the changed object.
+    pub fn key(&self) -> &str {
+        match self {
+            Self::Created { key } | Self::Updated { key } | Self::Deleted { key } => key,
+        }
+    }
+
+    pub(crate) fn key_mut(&mut self) -> &mut String {
+        match self {
+            Self::Created { key } | Self::Updated { key } | Self::Deleted { key } => key,
+        }
+    }
+}
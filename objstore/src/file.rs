@@ -0,0 +1,145 @@
+//! Transfers between a store and the local filesystem.
+//!
+//! See [`crate::ObjStoreExt::get_to_file`] and [`crate::ObjStoreExt::put_from_file`].
+
+use std::{
+    path::Path,
+    sync::{Arc, Mutex},
+};
+
+use bytes::{Bytes, BytesMut};
+use futures::{StreamExt as _, TryStreamExt as _, stream};
+use sha2::{Digest as _, Sha256};
+use tokio::io::{AsyncReadExt as _, AsyncWriteExt as _};
+
+use crate::{
+    DataSource, ObjStore, ObjStoreError, ObjectMeta, Operation, Put, Result, SizedValueStream,
+};
+
+/// Size of the chunks read from local files.
+const READ_CHUNK_SIZE: usize = 64 * 1024;
+
+pub(crate) async fn get_to_file<S>(store: &S, key: &str, path: &Path) -> Result<Option<ObjectMeta>>
+where
+    S: ObjStore + ?Sized,
+{
+    let Some((meta, mut stream)) = store.get_stream_with_meta(key).await? else {
+        return Ok(None);
+    };
+
+    let mut file = tokio::fs::File::create(path)
+        .await
+        .map_err(|err| io_error(Operation::GetStream, err))?;
+
+    let res = async {
+        let mut hasher = Sha256::new();
+        let mut size = 0u64;
+        while let Some(chunk) = stream.try_next().await? {
+            hasher.update(&chunk);
+            size += chunk.len() as u64;
+            file.write_all(&chunk)
+                .await
+                .map_err(|err| io_error(Operation::GetStream, err))?;
+        }
+        file.flush()
+            .await
+            .map_err(|err| io_error(Operation::GetStream, err))?;
+        verify(Operation::GetStream, &meta, size, hasher.finalize().into())
+    }
+    .await;
+
+    if let Err(err) = res {
+        // Do not leave partial or corrupt files behind.
+        drop(file);
+        tokio::fs::remove_file(path).await.ok();
+        return Err(err);
+    }
+
+    Ok(Some(meta))
+}
+
+pub(crate) async fn put_from_file<S>(store: &S, key: &str, path: &Path) -> Result<ObjectMeta>
+where
+    S: ObjStore + ?Sized,
+{
+    let file = tokio::fs::File::open(path)
+        .await
+        .map_err(|err| io_error(Operation::Put, err))?;
+    let size = file
+        .metadata()
+        .await
+        .map_err(|err| io_error(Operation::Put, err))?
+        .len();
+
+    let hasher = Arc::new(Mutex::new(Sha256::new()));
+    let stream_hasher = hasher.clone();
+    let stream = stream::try_unfold(file, |mut file| async move {
+        let mut buf = BytesMut::zeroed(READ_CHUNK_SIZE);
+        let n = file
+            .read(&mut buf)
+            .await
+            .map_err(|err| io_error(Operation::Put, err))?;
+        if n == 0 {
+            return Ok(None);
+        }
+        buf.truncate(n);
+        Ok(Some((buf.freeze(), file)))
+    })
+    .inspect_ok(move |chunk: &Bytes| stream_hasher.lock().unwrap().update(chunk))
+    .boxed();
+
+    let mut put = Put::new(
+        key.to_string(),
+        DataSource::Stream(SizedValueStream::new(stream, size)),
+    );
+    put.mime_type = mime_guess::from_path(path)
+        .first()
+        .map(|mime| mime.essence_str().to_string());
+
+    let meta = store.send_put(put).await?;
+
+    let hash = std::mem::take(&mut *hasher.lock().unwrap())
+        .finalize()
+        .into();
+    verify(Operation::Put, &meta, size, hash)?;
+
+    Ok(meta)
+}
+
+/// Compare the transferred size and hash against the object metadata, if the
+/// backend provides them.
+fn verify(operation: Operation, meta: &ObjectMeta, size: u64, hash: [u8; 32]) -> Result<()> {
+    if let Some(expected) = meta.size
+        && expected != size
+    {
+        return Err(integrity_error(
+            operation,
+            meta,
+            format!("size mismatch: expected {expected} bytes, transferred {size}"),
+        ));
+    }
+    if let Some(expected) = meta.hash_sha256
+        && expected != hash
+    {
+        return Err(integrity_error(
+            operation,
+            meta,
+            "sha256 hash mismatch".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+fn integrity_error(operation: Operation, meta: &ObjectMeta, message: String) -> ObjStoreError {
+    ObjStoreError::Response {
+        operation,
+        source: Some(format!("{message} for '{}'", meta.key).into()),
+    }
+}
+
+fn io_error(operation: Operation, source: std::io::Error) -> ObjStoreError {
+    ObjStoreError::Io {
+        operation,
+        source: Some(source.into()),
+    }
+}
@@ -0,0 +1,40 @@
+//! Bulk retrieval of many objects.
+//!
+//! See [`crate::ObjStore::get_many`].
+
+use bytes::Bytes;
+
+use crate::Result;
+
+/// Stream of `(key, result)` pairs, as returned by [`crate::ObjStore::get_many`].
+pub type GetManyStream<'a> = futures::stream::BoxStream<'a, (String, Result<Option<Bytes>>)>;
+
+/// Options for [`crate::ObjStore::get_many`].
+#[derive(Clone, Debug, Default)]
+pub struct GetManyOptions {
+    concurrency: Option<usize>,
+}
+
+impl GetManyOptions {
+    /// Concurrency used by the default [`crate::ObjStore::get_many`] implementation.
+    pub const DEFAULT_CONCURRENCY: usize = 8;
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Maximum number of concurrent requests.
+    ///
+    /// `None` uses the backend default.
+    pub fn concurrency(&self) -> Option<usize> {
+        self.concurrency
+    }
+
+    /// Set the maximum number of concurrent requests. Values of `0` are ignored.
+    pub fn with_concurrency(mut self, concurrency: usize) -> Self {
+        if concurrency > 0 {
+            self.concurrency = Some(concurrency);
+        }
+        self
+    }
+}
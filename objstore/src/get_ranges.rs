@@ -0,0 +1,257 @@
+//! Coalesced reads of many byte ranges.
+//!
+//! See [`RangePlan`] and [`crate::ObjStoreExt::get_ranges`].
+
+use std::ops::Range;
+
+use bytes::Bytes;
+use futures::{StreamExt as _, TryStreamExt as _, stream};
+
+use crate::{ObjStore, Result};
+
+/// Options for [`crate::ObjStoreExt::get_ranges`].
+#[derive(Clone, Debug)]
+pub struct GetRangesOptions {
+    max_gap: u64,
+    max_read_size: u64,
+    concurrency: usize,
+}
+
+impl Default for GetRangesOptions {
+    fn default() -> Self {
+        Self {
+            max_gap: Self::DEFAULT_MAX_GAP,
+            max_read_size: Self::DEFAULT_MAX_READ_SIZE,
+            concurrency: Self::DEFAULT_CONCURRENCY,
+        }
+    }
+}
+
+impl GetRangesOptions {
+    pub const DEFAULT_MAX_GAP: u64 = 1024 * 1024;
+    pub const DEFAULT_MAX_READ_SIZE: u64 = 16 * 1024 * 1024;
+    pub const DEFAULT_CONCURRENCY: usize = 8;
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Maximum number of unrequested bytes between two ranges that are
+    /// still merged into one read.
+    pub fn max_gap(&self) -> u64 {
+        self.max_gap
+    }
+
+    /// Set the maximum gap. `0` only merges adjacent and overlapping ranges.
+    pub fn with_max_gap(mut self, bytes: u64) -> Self {
+        self.max_gap = bytes;
+        self
+    }
+
+    /// Maximum size of a merged read.
+    ///
+    /// Single requested ranges larger than this are read as they are.
+    pub fn max_read_size(&self) -> u64 {
+        self.max_read_size
+    }
+
+    /// Set the maximum size of a merged read. A size of `0` is ignored.
+    pub fn with_max_read_size(mut self, bytes: u64) -> Self {
+        if bytes > 0 {
+            self.max_read_size = bytes;
+        }
+        self
+    }
+
+    /// Maximum number of concurrent range reads.
+    pub fn concurrency(&self) -> usize {
+        self.concurrency
+    }
+
+    /// Set the maximum number of concurrent range reads. Values of `0` are ignored.
+    pub fn with_concurrency(mut self, concurrency: usize) -> Self {
+        if concurrency > 0 {
+            self.concurrency = concurrency;
+        }
+        self
+    }
+}
+
+/// A single backend range read covering one or more requested ranges.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PlannedRead {
+    pub key: String,
+    pub range: Range<u64>,
+    /// Indices of the requests served by this read, with their ranges.
+    pub parts: Vec<(usize, Range<u64>)>,
+}
+
+/// Plan for reading many `(key, range)` requests with fewer range reads.
+///
+/// Requests for the same key are sorted, and ranges that overlap or are
+/// separated by at most [`GetRangesOptions::max_gap`] bytes are merged,
+/// as long as the merged read stays within
+/// [`GetRangesOptions::max_read_size`].
+///
+/// Useful for formats like Parquet or zip, where readers fetch many small,
+/// scattered pieces of a file.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RangePlan {
+    reads: Vec<PlannedRead>,
+    requests: usize,
+}
+
+impl RangePlan {
+    pub fn new(requests: &[(String, Range<u64>)], options: &GetRangesOptions) -> Self {
+        let mut order = (0..requests.len()).collect::<Vec<_>>();
+        order.sort_by(|a, b| {
+            let (a_key, a_range) = &requests[*a];
+            let (b_key, b_range) = &requests[*b];
+            a_key
+                .cmp(b_key)
+                .then(a_range.start.cmp(&b_range.start))
+                .then(a_range.end.cmp(&b_range.end))
+        });
+
+        let mut reads: Vec<PlannedRead> = Vec::new();
+        for index in order {
+            let (key, range) = &requests[index];
+            // Empty and inverted ranges are served as empty slices.
+            let range = range.start..range.end.max(range.start);
+
+            if let Some(read) = reads.last_mut()
+                && read.key == *key
+                && range.start <= read.range.end.saturating_add(options.max_gap)
+                && range.end.max(read.range.end) - read.range.start <= options.max_read_size
+            {
+                read.range.end = read.range.end.max(range.end);
+                read.parts.push((index, range));
+                continue;
+            }
+            reads.push(PlannedRead {
+                key: key.clone(),
+                range: range.clone(),
+                parts: vec![(index, range)],
+            });
+        }
+
+        Self {
+            reads,
+            requests: requests.len(),
+        }
+    }
+
+    /// The backend reads, ordered by key and offset.
+    pub fn reads(&self) -> &[PlannedRead] {
+        &self.reads
+    }
+
+    /// Read all ranges, returning the slices in request order.
+    ///
+    /// Slices follow [`ObjStore::get_range`]: they are truncated at the end
+    /// of the object, and `None` if the object does not exist.
+    pub async fn execute<S>(&self, store: &S, concurrency: usize) -> Result<Vec<Option<Bytes>>>
+    where
+        S: ObjStore + ?Sized,
+    {
+        let mut out = vec![None; self.requests];
+        let reads = self
+            .reads
+            .iter()
+            .enumerate()
+            .map(|(index, read)| (index, read.key.clone(), read.range.clone()))
+            .collect::<Vec<_>>();
+        let mut results = stream::iter(reads)
+            .map(|(index, key, range)| async move {
+                let data = store.get_range(&key, range).await?;
+                Ok::<_, crate::ObjStoreError>((index, data))
+            })
+            .buffer_unordered(concurrency.max(1));
+
+        while let Some((index, data)) = results.try_next().await? {
+            let Some(data) = data else {
+                continue;
+            };
+            let read = &self.reads[index];
+            let len = data.len() as u64;
+            for (index, range) in &read.parts {
+                let start = (range.start - read.range.start).min(len);
+                let end = (range.end - read.range.start).clamp(start, len);
+                out[*index] = Some(data.slice(start as usize..end as usize));
+            }
+        }
+        Ok(out)
+    }
+}
+
+pub(crate) async fn get_ranges<S>(
+    store: &S,
+    requests: Vec<(String, Range<u64>)>,
+    options: GetRangesOptions,
+) -> Result<Vec<Option<Bytes>>>
+where
+    S: ObjStore + ?Sized,
+{
+    RangePlan::new(&requests, &options)
+        .execute(store, options.concurrency)
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn requests(items: &[(&str, Range<u64>)]) -> Vec<(String, Range<u64>)> {
+        items
+            .iter()
+            .map(|(key, range)| (key.to_string(), range.clone()))
+            .collect()
+    }
+
+    #[test]
+    fn test_plan_merges_nearby_ranges_per_key() {
+        let requests = requests(&[
+            ("a", 100..110),
+            ("b", 0..10),
+            ("a", 0..10),
+            ("a", 15..20),
+            ("a", 5..12),
+        ]);
+        let plan = RangePlan::new(&requests, &GetRangesOptions::new().with_max_gap(5));
+        assert_eq!(
+            plan.reads(),
+            &[
+                PlannedRead {
+                    key: "a".to_string(),
+                    range: 0..20,
+                    parts: vec![(2, 0..10), (4, 5..12), (3, 15..20)],
+                },
+                PlannedRead {
+                    key: "a".to_string(),
+                    range: 100..110,
+                    parts: vec![(0, 100..110)],
+                },
+                PlannedRead {
+                    key: "b".to_string(),
+                    range: 0..10,
+                    parts: vec![(1, 0..10)],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_plan_respects_max_read_size() {
+        let requests = requests(&[("a", 0..10), ("a", 10..20), ("a", 20..50)]);
+        let options = GetRangesOptions::new()
+            .with_max_gap(0)
+            .with_max_read_size(20);
+        let plan = RangePlan::new(&requests, &options);
+        let ranges = plan
+            .reads()
+            .iter()
+            .map(|read| read.range.clone())
+            .collect::<Vec<_>>();
+        assert_eq!(ranges, vec![0..20, 20..50]);
+    }
+}
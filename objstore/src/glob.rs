@@ -0,0 +1,290 @@
+//! Minimal glob pattern matching for object keys.
+
+use crate::{ObjStoreError, Result};
+
+/// A glob pattern for matching object keys.
+///
+/// Supported syntax:
+///
+/// * `?` matches any single character except `/`
+/// * `*` matches any sequence of characters except `/`
+/// * `**` matches any sequence of characters, including `/`
+///   (`**/` also matches nothing, so `a/**/b` matches `a/b`)
+/// * `[abc]`, `[a-z]` and `[!a-z]` match character classes
+/// * `\` escapes the next character
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Glob {
+    pattern: String,
+    tokens: Vec<Token>,
+    literal_prefix: String,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Token {
+    Literal(char),
+    AnyChar,
+    Star,
+    DoubleStar,
+    /// `**/`
+    AnyDirs,
+    Class {
+        negated: bool,
+        ranges: Vec<(char, char)>,
+    },
+}
+
+impl Glob {
+    pub fn new(pattern: impl Into<String>) -> Result<Self> {
+        let pattern = pattern.into();
+        let tokens = parse(&pattern)?;
+        let literal_prefix = tokens
+            .iter()
+            .map_while(|token| match token {
+                Token::Literal(c) => Some(*c),
+                _ => None,
+            })
+            .collect();
+        Ok(Self {
+            pattern,
+            tokens,
+            literal_prefix,
+        })
+    }
+
+    pub fn pattern(&self) -> &str {
+        &self.pattern
+    }
+
+    /// The literal prefix of the pattern, up to the first wildcard.
+    ///
+    /// Suitable as a list prefix for the backend.
+    pub fn literal_prefix(&self) -> &str {
+        &self.literal_prefix
+    }
+
+    /// A pattern matching keys that start with the literal `prefix`,
+    /// followed by a match of this pattern.
+    pub fn with_prefix(&self, prefix: &str) -> Self {
+        let mut pattern = String::with_capacity(prefix.len() + self.pattern.len());
+        for c in prefix.chars() {
+            if matches!(c, '?' | '*' | '[' | '\\') {
+                pattern.push('\\');
+            }
+            pattern.push(c);
+        }
+        pattern.push_str(&self.pattern);
+
+        let tokens = prefix
+            .chars()
+            .map(Token::Literal)
+            .chain(self.tokens.iter().cloned())
+            .collect();
+        Self {
+            pattern,
+            tokens,
+            literal_prefix: format!("{prefix}{}", self.literal_prefix),
+        }
+    }
+
+    /// Check if the full `key` matches the pattern.
+    pub fn matches(&self, key: &str) -> bool {
+        let chars = key.chars().collect::<Vec<_>>();
+        match_tokens(&self.tokens, &chars)
+    }
+}
+
+impl std::fmt::Display for Glob {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.pattern)
+    }
+}
+
+impl std::str::FromStr for Glob {
+    type Err = ObjStoreError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Self::new(s)
+    }
+}
+
+fn invalid_pattern(pattern: &str, message: &str) -> ObjStoreError {
+    ObjStoreError::InvalidRequest {
+        message: format!("invalid glob pattern '{pattern}': {message}"),
+        source: None,
+    }
+}
+
+fn parse(pattern: &str) -> Result<Vec<Token>> {
+    let mut tokens = Vec::new();
+    let mut chars = pattern.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        let token = match c {
+            '\\' => match chars.next() {
+                Some(c) => Token::Literal(c),
+                None => return Err(invalid_pattern(pattern, "trailing escape")),
+            },
+            '?' => Token::AnyChar,
+            '*' if chars.peek() == Some(&'*') => {
+                chars.next();
+                if chars.peek() == Some(&'/') {
+                    chars.next();
+                    Token::AnyDirs
+                } else {
+                    Token::DoubleStar
+                }
+            }
+            '*' => Token::Star,
+            '[' => {
+                let negated = chars.next_if(|c| *c == '!' || *c == '^').is_some();
+                let mut ranges = Vec::new();
+                loop {
+                    let start = match chars.next() {
+                        Some(']') if !ranges.is_empty() => break,
+                        Some('\\') => chars.next(),
+                        other => other,
+                    };
+                    let Some(start) = start else {
+                        return Err(invalid_pattern(pattern, "unterminated character class"));
+                    };
+                    let end = if chars.peek() == Some(&'-') {
+                        chars.next();
+                        match chars.next() {
+                            Some(']') => {
+                                // A trailing '-' is a literal.
+                                ranges.push((start, start));
+                                ranges.push(('-', '-'));
+                                break;
+                            }
+                            Some(end) => end,
+                            None => {
+                                return Err(invalid_pattern(
+                                    pattern,
+                                    "unterminated character class",
+                                ));
+                            }
+                        }
+                    } else {
+                        start
+                    };
+                    if end < start {
+                        return Err(invalid_pattern(pattern, "invalid character range"));
+                    }
+                    ranges.push((start, end));
+                }
+                Token::Class { negated, ranges }
+            }
+            c => Token::Literal(c),
+        };
+        tokens.push(token);
+    }
+
+    Ok(tokens)
+}
+
+fn match_tokens(tokens: &[Token], chars: &[char]) -> bool {
+    // Dynamic programming over the tokens from back to front: `next[i]` is
+    // whether the tokens after the current one match `chars[i..]`. This keeps
+    // matching linear in the key length for every token, instead of
+    // backtracking exponentially on repeated wildcards.
+    let len = chars.len();
+    let mut next = vec![false; len + 1];
+    next[len] = true;
+    for token in tokens.iter().rev() {
+        let mut current = vec![false; len + 1];
+        // Whether the following tokens match after a '/' at or after `index`.
+        let mut after_slash = false;
+        for index in (0..=len).rev() {
+            let c = chars.get(index);
+            current[index] = match token {
+                Token::Literal(literal) => c == Some(literal) && next[index + 1],
+                Token::AnyChar => c.is_some_and(|c| *c != '/') && next[index + 1],
+                Token::Class { negated, ranges } => {
+                    c.is_some_and(|c| {
+                        *c != '/'
+                            && ranges
+                                .iter()
+                                .any(|(start, end)| (*start..=*end).contains(c))
+                                != *negated
+                    }) && next[index + 1]
+                }
+                Token::Star => next[index] || (c.is_some_and(|c| *c != '/') && current[index + 1]),
+                Token::DoubleStar => next[index] || (c.is_some() && current[index + 1]),
+                Token::AnyDirs => {
+                    after_slash |= c == Some(&'/') && next[index + 1];
+                    next[index] || after_slash
+                }
+            };
+        }
+        next = current;
+    }
+    next[0]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_glob_with_prefix_escapes_prefix() {
+        let glob = Glob::new("*.txt").unwrap().with_prefix("a[1]*/");
+        assert_eq!(glob.pattern(), "a\\[1]\\*/*.txt");
+        assert_eq!(glob.literal_prefix(), "a[1]*/");
+        assert!(glob.matches("a[1]*/b.txt"));
+        assert!(!glob.matches("a1x/b.txt"));
+        assert_eq!(Glob::new(glob.pattern()).unwrap(), glob);
+    }
+
+    #[test]
+    fn test_glob_matches() {
+        let glob = Glob::new("logs/2024-*/app-*.json").unwrap();
+        assert_eq!(glob.literal_prefix(), "logs/2024-");
+        assert!(glob.matches("logs/2024-01/app-1.json"));
+        assert!(!glob.matches("logs/2024-01/nested/app-1.json"));
+        assert!(!glob.matches("logs/2024-01/app-1.jsonl"));
+        assert!(!glob.matches("logs/2023-01/app-1.json"));
+
+        let glob = Glob::new("a/**/b?.txt").unwrap();
+        assert!(glob.matches("a/b1.txt"));
+        assert!(glob.matches("a/x/y/b2.txt"));
+        assert!(!glob.matches("a/x/b/.txt"));
+
+        let glob = Glob::new("data/**").unwrap();
+        assert!(glob.matches("data/a/b/c"));
+        assert!(!glob.matches("other/a"));
+
+        let glob = Glob::new("img-[0-9][!a].png").unwrap();
+        assert!(glob.matches("img-1b.png"));
+        assert!(!glob.matches("img-1a.png"));
+        assert!(!glob.matches("img-xb.png"));
+
+        let glob = Glob::new(r"literal\*.txt").unwrap();
+        assert_eq!(glob.literal_prefix(), "literal*.txt");
+        assert!(glob.matches("literal*.txt"));
+        assert!(!glob.matches("literalx.txt"));
+    }
+
+    #[test]
+    fn test_glob_repeated_wildcards() {
+        let key = "a".repeat(200);
+        let glob = Glob::new("*a".repeat(20)).unwrap();
+        assert!(glob.matches(&key));
+        let glob = Glob::new(format!("{}b", "*a".repeat(20))).unwrap();
+        assert!(!glob.matches(&key));
+        let glob = Glob::new(format!("{}b", "**a".repeat(20))).unwrap();
+        assert!(!glob.matches(&key));
+
+        let glob = Glob::new("**/*/x").unwrap();
+        assert!(glob.matches("a/x"));
+        assert!(glob.matches("a/b/c/x"));
+        assert!(!glob.matches("x"));
+        assert!(!glob.matches("a/b/c/y"));
+    }
+
+    #[test]
+    fn test_glob_invalid() {
+        assert!(Glob::new("a[bc").is_err());
+        assert!(Glob::new("a\\").is_err());
+        assert!(Glob::new("[z-a]").is_err());
+    }
+}
@@ -0,0 +1,60 @@
+//! gzip encoding (RFC 1952).
+//!
+//! Used by [`crate::PutBuilder::gzip`] and [`crate::ObjStoreExt::get_json`].
+
+use std::io::{Read as _, Write as _};
+
+use flate2::{Compression, read::GzDecoder, write::GzEncoder};
+
+const MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+/// Maximum size of decompressed data, to protect against decompression bombs.
+pub(crate) const MAX_DECOMPRESSED_SIZE: u64 = 256 * 1024 * 1024;
+
+/// Whether `data` starts with the gzip magic bytes.
+pub(crate) fn is_gzip(data: &[u8]) -> bool {
+    data.starts_with(&MAGIC)
+}
+
+pub(crate) fn compress(data: &[u8]) -> Vec<u8> {
+    let mut encoder = GzEncoder::new(
+        Vec::with_capacity(data.len() / 2 + 18),
+        Compression::default(),
+    );
+    // Writing to a Vec can't fail.
+    encoder
+        .write_all(data)
+        .expect("gzip encoding into a Vec failed");
+    encoder.finish().expect("gzip encoding into a Vec failed")
+}
+
+/// Decompress `data`, failing if the output exceeds `limit` bytes.
+pub(crate) fn decompress(data: &[u8], limit: u64) -> Result<Vec<u8>, String> {
+    let mut out = Vec::new();
+    GzDecoder::new(data)
+        .take(limit.saturating_add(1))
+        .read_to_end(&mut out)
+        .map_err(|err| format!("invalid gzip data: {err}"))?;
+    if out.len() as u64 > limit {
+        return Err(format!("decompressed data exceeds {limit} bytes"));
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decompress_limit() {
+        let data = compress(&[0u8; 1000]);
+        assert_eq!(decompress(&data, 1000).unwrap().len(), 1000);
+        let err = decompress(&data, 999).unwrap_err();
+        assert_eq!(err, "decompressed data exceeds 999 bytes");
+
+        let mut corrupt = data.clone();
+        let len = corrupt.len();
+        corrupt[len - 5] ^= 1;
+        assert!(decompress(&corrupt, 1000).is_err());
+    }
+}
@@ -0,0 +1,170 @@
+//! Incremental content hashing of streamed data.
+//!
+//! See [`HashingStream`].
+
+use std::{
+    pin::Pin,
+    sync::{Arc, Mutex, MutexGuard},
+    task::{Context, Poll},
+};
+
+use bytes::Bytes;
+use futures::Stream;
+use sha2::{Digest as _, Sha256};
+
+use crate::{ObjectMeta, Result, ValueStream};
+
+/// Hashes of an object's content.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct ContentHashes {
+    /// Number of hashed bytes.
+    pub size: u64,
+    pub md5: [u8; 16],
+    pub sha256: [u8; 32],
+}
+
+impl ContentHashes {
+    /// Hash in-memory data.
+    pub fn of(data: &[u8]) -> Self {
+        let mut hasher = Hasher::default();
+        hasher.update(data);
+        hasher.finish()
+    }
+
+    /// Set the hashes of `meta` that are not populated yet.
+    ///
+    /// Hashes reported by the backend are kept.
+    pub fn apply_to(&self, meta: &mut ObjectMeta) {
+        meta.hash_md5.get_or_insert(self.md5);
+        meta.hash_sha256.get_or_insert(self.sha256);
+    }
+}
+
+#[derive(Clone, Default)]
+struct Hasher {
+    size: u64,
+    md5: md5::Context,
+    sha256: Sha256,
+}
+
+impl Hasher {
+    fn update(&mut self, data: &[u8]) {
+        self.size += data.len() as u64;
+        self.md5.consume(data);
+        self.sha256.update(data);
+    }
+
+    fn finish(&self) -> ContentHashes {
+        let state = self.clone();
+        ContentHashes {
+            size: state.size,
+            md5: state.md5.finalize().0,
+            sha256: state.sha256.finalize().into(),
+        }
+    }
+}
+
+/// Stream adapter that computes md5 and sha256 hashes of the chunks passing
+/// through it.
+///
+/// Backends wrap the data of streamed puts with it, so the returned metadata
+/// carries hashes without reading the data a second time. Since the stream is
+/// usually handed to the code doing the upload, the hashes are read through a
+/// [`HashHandle`] once the stream was consumed.
+pub struct HashingStream {
+    inner: ValueStream,
+    hasher: Arc<Mutex<Hasher>>,
+}
+
+impl HashingStream {
+    pub fn new(inner: ValueStream) -> Self {
+        Self {
+            inner,
+            hasher: Arc::default(),
+        }
+    }
+
+    /// Handle for reading the hashes after the stream was moved.
+    pub fn handle(&self) -> HashHandle {
+        HashHandle(self.hasher.clone())
+    }
+
+    /// Hashes of the data yielded so far.
+    pub fn hashes(&self) -> ContentHashes {
+        lock(&self.hasher).finish()
+    }
+}
+
+impl std::fmt::Debug for HashingStream {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("HashingStream").finish_non_exhaustive()
+    }
+}
+
+impl Stream for HashingStream {
+    type Item = Result<Bytes>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let poll = self.inner.as_mut().poll_next(cx);
+        if let Poll::Ready(Some(Ok(chunk))) = &poll {
+            lock(&self.hasher).update(chunk);
+        }
+        poll
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+/// Reads the hashes of a [`HashingStream`], see [`HashingStream::handle`].
+#[derive(Clone)]
+pub struct HashHandle(Arc<Mutex<Hasher>>);
+
+impl std::fmt::Debug for HashHandle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("HashHandle").finish_non_exhaustive()
+    }
+}
+
+impl HashHandle {
+    /// Hashes of the data yielded by the stream so far.
+    pub fn hashes(&self) -> ContentHashes {
+        lock(&self.0).finish()
+    }
+}
+
+fn lock(hasher: &Mutex<Hasher>) -> MutexGuard<'_, Hasher> {
+    hasher
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::{StreamExt as _, TryStreamExt as _, stream};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_hashing_stream_matches_one_shot_hash() {
+        let chunks = ["hello", " ", "world"].map(|chunk| Ok(Bytes::from_static(chunk.as_bytes())));
+        let stream = HashingStream::new(stream::iter(chunks).boxed());
+        let handle = stream.handle();
+        let chunks = stream.try_collect::<Vec<_>>().await.unwrap();
+        let data = chunks.concat();
+
+        let hashes = handle.hashes();
+        assert_eq!(hashes, ContentHashes::of(b"hello world"));
+        assert_eq!(hashes.size, 11);
+        assert_eq!(hashes.md5, md5::compute(&data).0);
+        assert_eq!(hashes.sha256, <[u8; 32]>::from(Sha256::digest(&data)));
+
+        let mut meta = ObjectMeta::new("a".to_string());
+        meta.hash_md5 = Some([0; 16]);
+        hashes.apply_to(&mut meta);
+        assert_eq!(meta.hash_md5, Some([0; 16]));
+        assert_eq!(meta.hash_sha256, Some(hashes.sha256));
+    }
+}
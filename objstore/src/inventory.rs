@@ -0,0 +1,494 @@
+//! Inventory exports: snapshots of a listing stored as manifest files.
+//!
+//! Similar to S3 Inventory, [`export`] writes the full listing of a prefix
+//! into CSV or NDJSON files in a target store, so consumers can process the
+//! object list without listing the live store. An export consists of:
+//!
+//! * `inventories/<id>/manifest.json`: the [`InventoryManifest`]
+//! * `inventories/<id>/data-<n>.<csv|ndjson>`: the listed objects, split into
+//!   files of at most [`InventoryOptions::max_file_size`] bytes
+//!
+//! Use [`read`] to stream the entries of an export, and [`latest_inventory`]
+//! to find the most recent one.
+
+use bytes::Bytes;
+use futures::{StreamExt as _, TryStreamExt as _, stream, stream::BoxStream};
+use time::OffsetDateTime;
+
+use crate::{ListArgs, ObjStore, ObjStoreError, ObjectMeta, Put, Result};
+
+const INVENTORY_PREFIX: &str = "inventories/";
+const MANIFEST_FILE: &str = "manifest.json";
+const CSV_HEADER: &str = "key,size,updated_at,etag";
+
+/// File format of inventory data files.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[non_exhaustive]
+pub enum InventoryFormat {
+    /// Comma separated values with a header row, quoted as in RFC 4180.
+    #[default]
+    Csv,
+    /// One JSON encoded [`InventoryEntry`] per line.
+    Ndjson,
+}
+
+impl InventoryFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::Csv => "csv",
+            Self::Ndjson => "ndjson",
+        }
+    }
+
+    pub fn mime_type(&self) -> &'static str {
+        match self {
+            Self::Csv => "text/csv",
+            Self::Ndjson => crate::ndjson::NDJSON_MIME_TYPE,
+        }
+    }
+}
+
+/// An object in an inventory.
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[non_exhaustive]
+pub struct InventoryEntry {
+    pub key: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub size: Option<u64>,
+    /// Modification time as a unix timestamp in seconds.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub updated_at: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub etag: Option<String>,
+}
+
+impl InventoryEntry {
+    fn from_meta(meta: ObjectMeta) -> Self {
+        Self {
+            size: meta.size,
+            updated_at: meta.updated_at.map(OffsetDateTime::unix_timestamp),
+            etag: meta.etag,
+            key: meta.key,
+        }
+    }
+}
+
+/// A data file of an inventory.
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[non_exhaustive]
+pub struct InventoryFile {
+    /// Key of the file in the target store.
+    pub key: String,
+    /// Number of entries in the file.
+    pub entries: u64,
+    /// Size of the file in bytes.
+    pub size: u64,
+}
+
+/// Describes one inventory export.
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[non_exhaustive]
+pub struct InventoryManifest {
+    /// Format version of the manifest.
+    pub version: u32,
+    /// Inventory id, ids of later exports sort after earlier ones.
+    pub id: String,
+    /// The listed prefix of the source store.
+    pub prefix: String,
+    pub format: InventoryFormat,
+    /// Creation time as a unix timestamp in seconds.
+    pub created_at: i64,
+    /// Data files, in key order of their entries.
+    pub files: Vec<InventoryFile>,
+    /// Number of listed objects.
+    pub objects: u64,
+    /// Total size of the listed objects with a known size.
+    pub total_size: u64,
+}
+
+impl InventoryManifest {
+    pub const VERSION: u32 = 1;
+}
+
+/// Options for [`export`].
+#[derive(Clone, Debug)]
+pub struct InventoryOptions {
+    format: InventoryFormat,
+    max_file_size: u64,
+}
+
+impl Default for InventoryOptions {
+    fn default() -> Self {
+        Self {
+            format: InventoryFormat::default(),
+            max_file_size: Self::DEFAULT_MAX_FILE_SIZE,
+        }
+    }
+}
+
+impl InventoryOptions {
+    pub const DEFAULT_MAX_FILE_SIZE: u64 = 64 * 1024 * 1024;
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn format(&self) -> InventoryFormat {
+        self.format
+    }
+
+    pub fn with_format(mut self, format: InventoryFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// Size after which a new data file is started.
+    pub fn max_file_size(&self) -> u64 {
+        self.max_file_size
+    }
+
+    /// Set the maximum size of data files. A size of `0` is ignored.
+    ///
+    /// Files can be slightly larger, since entries are not split.
+    pub fn with_max_file_size(mut self, bytes: u64) -> Self {
+        if bytes > 0 {
+            self.max_file_size = bytes;
+        }
+        self
+    }
+}
+
+/// Write an inventory of all objects below `prefix` in `source` into `target`.
+///
+/// The listing is streamed, so only one data file is held in memory at a
+/// time. The manifest is written last, so an interrupted export does not show
+/// up in [`list_inventories`].
+pub async fn export<S, T>(
+    source: &S,
+    prefix: &str,
+    target: &T,
+    options: InventoryOptions,
+) -> Result<InventoryManifest>
+where
+    S: ObjStore + ?Sized,
+    T: ObjStore + ?Sized,
+{
+    let now = OffsetDateTime::now_utc();
+    let mut manifest = InventoryManifest {
+        version: InventoryManifest::VERSION,
+        id: format!("{:020}", now.unix_timestamp_nanos()),
+        prefix: prefix.to_string(),
+        format: options.format,
+        created_at: now.unix_timestamp(),
+        files: Vec::new(),
+        objects: 0,
+        total_size: 0,
+    };
+
+    let mut writer = FileWriter::new(options.format);
+    let mut items = source.list_items_stream(ListArgs::new().with_prefix(prefix));
+    while let Some(meta) = items.try_next().await? {
+        manifest.objects += 1;
+        manifest.total_size += meta.size.unwrap_or_default();
+        writer.push(&InventoryEntry::from_meta(meta))?;
+        if writer.data.len() as u64 >= options.max_file_size {
+            let file = writer.finish(target, &manifest).await?;
+            manifest.files.push(file);
+        }
+    }
+    if writer.entries > 0 {
+        let file = writer.finish(target, &manifest).await?;
+        manifest.files.push(file);
+    }
+
+    let data = serde_json::to_vec(&manifest).map_err(|source| ObjStoreError::Internal {
+        message: "could not serialize inventory manifest".to_string(),
+        source: Some(source.into()),
+    })?;
+    let mut put = Put::new(manifest_key(&manifest.id), Bytes::from(data));
+    put.mime_type = Some("application/json".to_string());
+    target.send_put(put).await?;
+
+    Ok(manifest)
+}
+
+/// Ids of all inventories in `target`, oldest first.
+pub async fn list_inventories<T>(target: &T) -> Result<Vec<String>>
+where
+    T: ObjStore + ?Sized,
+{
+    let mut ids = target
+        .list_all_keys(INVENTORY_PREFIX)
+        .await?
+        .into_iter()
+        .filter_map(|key| {
+            let (id, file) = key.strip_prefix(INVENTORY_PREFIX)?.split_once('/')?;
+            (file == MANIFEST_FILE).then(|| id.to_string())
+        })
+        .collect::<Vec<_>>();
+    ids.sort();
+    Ok(ids)
+}
+
+/// Id of the most recent inventory in `target`.
+pub async fn latest_inventory<T>(target: &T) -> Result<Option<String>>
+where
+    T: ObjStore + ?Sized,
+{
+    Ok(list_inventories(target).await?.pop())
+}
+
+/// Load the manifest of the inventory with the given id.
+pub async fn load_manifest<T>(target: &T, id: &str) -> Result<InventoryManifest>
+where
+    T: ObjStore + ?Sized,
+{
+    let key = manifest_key(id);
+    let data = target
+        .get(&key)
+        .await?
+        .ok_or_else(|| ObjStoreError::object_not_found(&key))?;
+    let jd = &mut serde_json::Deserializer::from_slice(&data);
+    serde_path_to_error::deserialize(jd).map_err(|source| ObjStoreError::ContentDeserialization {
+        key,
+        format: "json".to_string(),
+        source: Some(Box::new(source)),
+    })
+}
+
+/// Stream the entries of an inventory, in key order.
+///
+/// Data files are read one at a time.
+pub fn read<'a, T>(
+    target: &'a T,
+    manifest: &'a InventoryManifest,
+) -> BoxStream<'a, Result<InventoryEntry>>
+where
+    T: ObjStore + ?Sized,
+{
+    stream::iter(&manifest.files)
+        .then(move |file| async move {
+            let data = target
+                .get(&file.key)
+                .await?
+                .ok_or_else(|| ObjStoreError::object_not_found(&file.key))?;
+            let entries = parse_file(&file.key, manifest.format, &data)?;
+            Ok::<_, ObjStoreError>(stream::iter(entries.into_iter().map(Ok)))
+        })
+        .try_flatten()
+        .boxed()
+}
+
+fn manifest_key(id: &str) -> String {
+    format!("{INVENTORY_PREFIX}{id}/{MANIFEST_FILE}")
+}
+
+/// Buffers the entries of the current data file.
+struct FileWriter {
+    format: InventoryFormat,
+    data: Vec<u8>,
+    entries: u64,
+    index: usize,
+}
+
+impl FileWriter {
+    fn new(format: InventoryFormat) -> Self {
+        Self {
+            format,
+            data: Vec::new(),
+            entries: 0,
+            index: 0,
+        }
+    }
+
+    fn push(&mut self, entry: &InventoryEntry) -> Result<()> {
+        match self.format {
+            InventoryFormat::Csv => {
+                if self.entries == 0 {
+                    self.data.extend_from_slice(CSV_HEADER.as_bytes());
+                    self.data.push(b'\n');
+                }
+                let fields = [
+                    entry.key.clone(),
+                    entry.size.map(|size| size.to_string()).unwrap_or_default(),
+                    entry
+                        .updated_at
+                        .map(|at| at.to_string())
+                        .unwrap_or_default(),
+                    entry.etag.clone().unwrap_or_default(),
+                ];
+                for (index, field) in fields.iter().enumerate() {
+                    if index > 0 {
+                        self.data.push(b',');
+                    }
+                    write_csv_field(&mut self.data, field);
+                }
+            }
+            InventoryFormat::Ndjson => {
+                serde_json::to_writer(&mut self.data, entry).map_err(|source| {
+                    ObjStoreError::Internal {
+                        message: "could not serialize inventory entry".to_string(),
+                        source: Some(source.into()),
+                    }
+                })?;
+            }
+        }
+        self.data.push(b'\n');
+        self.entries += 1;
+        Ok(())
+    }
+
+    /// Upload the buffered entries and start a new file.
+    async fn finish<T>(&mut self, target: &T, manifest: &InventoryManifest) -> Result<InventoryFile>
+    where
+        T: ObjStore + ?Sized,
+    {
+        let key = format!(
+            "{INVENTORY_PREFIX}{}/data-{:05}.{}",
+            manifest.id,
+            self.index,
+            self.format.extension()
+        );
+        let data = Bytes::from(std::mem::take(&mut self.data));
+        let file = InventoryFile {
+            key: key.clone(),
+            entries: std::mem::take(&mut self.entries),
+            size: data.len() as u64,
+        };
+        let mut put = Put::new(key, data);
+        put.mime_type = Some(self.format.mime_type().to_string());
+        target.send_put(put).await?;
+        self.index += 1;
+        Ok(file)
+    }
+}
+
+fn write_csv_field(out: &mut Vec<u8>, field: &str) {
+    if field.contains([',', '"', '\n', '\r']) {
+        out.push(b'"');
+        out.extend_from_slice(field.replace('"', "\"\"").as_bytes());
+        out.push(b'"');
+    } else {
+        out.extend_from_slice(field.as_bytes());
+    }
+}
+
+fn parse_file(key: &str, format: InventoryFormat, data: &[u8]) -> Result<Vec<InventoryEntry>> {
+    let invalid = |message: String| ObjStoreError::ContentDeserialization {
+        key: key.to_string(),
+        format: format.extension().to_string(),
+        source: Some(message.into()),
+    };
+
+    match format {
+        InventoryFormat::Ndjson => data
+            .split(|byte| *byte == b'\n')
+            .filter(|line| !line.is_empty())
+            .map(|line| serde_json::from_slice(line).map_err(|err| invalid(err.to_string())))
+            .collect(),
+        InventoryFormat::Csv => {
+            let text = std::str::from_utf8(data).map_err(|err| invalid(err.to_string()))?;
+            let mut rows = parse_csv(text)
+                .map_err(|message| invalid(message.to_string()))?
+                .into_iter();
+            if rows
+                .next()
+                .is_some_and(|header| header.join(",") != CSV_HEADER)
+            {
+                return Err(invalid("unexpected csv header".to_string()));
+            }
+            rows.map(|row| {
+                let [key, size, updated_at, etag] = <[String; 4]>::try_from(row)
+                    .map_err(|row| invalid(format!("expected 4 fields, got {}", row.len())))?;
+                Ok(InventoryEntry {
+                    key,
+                    size: parse_optional(&size)
+                        .map_err(|err| invalid(format!("invalid size: {err}")))?,
+                    updated_at: parse_optional(&updated_at)
+                        .map_err(|err| invalid(format!("invalid updated_at: {err}")))?,
+                    etag: (!etag.is_empty()).then_some(etag),
+                })
+            })
+            .collect()
+        }
+    }
+}
+
+/// Parse an optional number, where an empty field means `None`.
+fn parse_optional<T: std::str::FromStr>(value: &str) -> std::result::Result<Option<T>, T::Err> {
+    (!value.is_empty()).then(|| value.parse()).transpose()
+}
+
+/// Parse RFC 4180 style CSV, where quoted fields can contain newlines.
+fn parse_csv(text: &str) -> std::result::Result<Vec<Vec<String>>, &'static str> {
+    let mut rows = Vec::new();
+    let mut row = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match (quoted, c) {
+            (true, '"') if chars.peek() == Some(&'"') => {
+                chars.next();
+                field.push('"');
+            }
+            (true, '"') => quoted = false,
+            (true, c) => field.push(c),
+            (false, '"') if field.is_empty() => quoted = true,
+            (false, ',') => row.push(std::mem::take(&mut field)),
+            (false, '\n') => {
+                row.push(std::mem::take(&mut field));
+                rows.push(std::mem::take(&mut row));
+            }
+            (false, '\r') if chars.peek() == Some(&'\n') => {}
+            (false, c) => field.push(c),
+        }
+    }
+    if quoted {
+        return Err("unterminated quoted field");
+    }
+    if !field.is_empty() || !row.is_empty() {
+        row.push(field);
+        rows.push(row);
+    }
+    Ok(rows)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_csv_roundtrip() {
+        let entries = vec![
+            InventoryEntry {
+                key: "plain".to_string(),
+                size: Some(1),
+                updated_at: Some(1_700_000_000),
+                etag: Some("\"abc\"".to_string()),
+            },
+            InventoryEntry {
+                key: "with,comma and\nnewline".to_string(),
+                size: None,
+                updated_at: None,
+                etag: None,
+            },
+        ];
+        let mut writer = FileWriter::new(InventoryFormat::Csv);
+        for entry in &entries {
+            writer.push(entry).unwrap();
+        }
+        let parsed = parse_file("f", InventoryFormat::Csv, &writer.data).unwrap();
+        assert_eq!(parsed, entries);
+    }
+
+    #[test]
+    fn test_parse_csv_rejects_unterminated_quotes() {
+        assert!(parse_csv("\"a,b\n").is_err());
+        assert_eq!(
+            parse_csv("a,\"b\"\"c\"\r\n").unwrap(),
+            vec![vec!["a", "b\"c"]]
+        );
+    }
+}
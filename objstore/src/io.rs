@@ -0,0 +1,74 @@
+//! Byte stream utilities.
+//!
+//! See [`limit_stream`].
+
+use std::time::Duration;
+
+use bytes::Bytes;
+use futures::{StreamExt as _, TryStreamExt as _, stream};
+use tokio::time::Instant;
+
+use crate::ValueStream;
+
+/// Limit the throughput of `stream` to `bytes_per_sec`.
+///
+/// Chunks are split into pieces of at most a tenth of a second worth of
+/// data, and delayed so the average rate since the first chunk stays below
+/// the limit. A limit of `0` returns the stream unchanged.
+///
+/// Limiting the stream that is passed to (or returned from) a store caps the
+/// bandwidth of that single transfer. See also
+/// [`crate::SizedValueStream::with_rate_limit`].
+pub fn limit_stream(stream: ValueStream, bytes_per_sec: u64) -> ValueStream {
+    if bytes_per_sec == 0 {
+        return stream;
+    }
+    let max_piece = (bytes_per_sec / 10).max(1) as usize;
+
+    let state = (stream, Bytes::new(), RateLimiter::new(bytes_per_sec));
+    stream::try_unfold(
+        state,
+        move |(mut stream, mut pending, mut limiter)| async move {
+            while pending.is_empty() {
+                match stream.try_next().await? {
+                    Some(chunk) => pending = chunk,
+                    None => return Ok(None),
+                }
+            }
+            let piece = pending.split_to(pending.len().min(max_piece));
+            limiter.acquire(piece.len() as u64).await;
+            Ok(Some((piece, (stream, pending, limiter))))
+        },
+    )
+    .boxed()
+}
+
+/// Paces transfers to a fixed average rate.
+#[derive(Debug)]
+pub(crate) struct RateLimiter {
+    bytes_per_sec: u64,
+    start: Option<Instant>,
+    transferred: u64,
+}
+
+impl RateLimiter {
+    pub(crate) fn new(bytes_per_sec: u64) -> Self {
+        Self {
+            bytes_per_sec,
+            start: None,
+            transferred: 0,
+        }
+    }
+
+    /// The delay after `start` at which `offset` bytes may have been transferred.
+    pub(crate) fn due(offset: u64, bytes_per_sec: u64) -> Duration {
+        Duration::from_secs_f64(offset as f64 / bytes_per_sec as f64)
+    }
+
+    /// Wait until `len` more bytes may be transferred.
+    pub(crate) async fn acquire(&mut self, len: u64) {
+        let start = *self.start.get_or_insert_with(Instant::now);
+        tokio::time::sleep_until(start + Self::due(self.transferred, self.bytes_per_sec)).await;
+        self.transferred += len;
+    }
+}
@@ -0,0 +1,136 @@
+//! Object keys that are not necessarily valid UTF-8.
+//!
+//! See [`ObjectKey`].
+
+use std::borrow::Cow;
+
+use crate::{ObjStoreError, Result};
+
+/// An object key as raw bytes.
+///
+/// Most of the [`crate::ObjStore`] API uses `&str` keys, which covers the
+/// vast majority of use cases. Some backends (e.g. raw S3 or the filesystem)
+/// allow keys that are not valid UTF-8 though. Those can be addressed with
+/// the `*_raw` methods, like [`crate::ObjStore::get_raw`].
+///
+/// Converting from `&str` and `String` is lossless, so existing keys can be
+/// migrated with `.into()`.
+///
+/// The [`Display`](std::fmt::Display) form is the key itself for valid UTF-8,
+/// with invalid bytes rendered as `\xNN` escapes otherwise.
+/// It is meant for logs and UIs, and can not be parsed back into a key.
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ObjectKey(Vec<u8>);
+
+impl ObjectKey {
+    pub fn from_bytes(bytes: impl Into<Vec<u8>>) -> Self {
+        Self(bytes.into())
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.0
+    }
+
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.0
+    }
+
+    /// The key as a string, if it is valid UTF-8.
+    pub fn as_str(&self) -> Option<&str> {
+        std::str::from_utf8(&self.0).ok()
+    }
+
+    pub fn is_utf8(&self) -> bool {
+        self.as_str().is_some()
+    }
+
+    /// Convert into a string, returning the key unchanged if it is not valid UTF-8.
+    pub fn into_string(self) -> Result<String, Self> {
+        String::from_utf8(self.0).map_err(|err| Self(err.into_bytes()))
+    }
+
+    /// The display form of the key.
+    ///
+    /// Borrows the key if it is valid UTF-8.
+    pub fn display(&self) -> Cow<'_, str> {
+        match self.as_str() {
+            Some(key) => Cow::Borrowed(key),
+            None => Cow::Owned(self.to_string()),
+        }
+    }
+}
+
+/// Convert a key for use with the `&str` based API.
+///
+/// Used by the default implementations of the `*_raw` methods.
+pub(crate) fn utf8_key(key: &ObjectKey) -> Result<&str> {
+    key.as_str().ok_or_else(|| ObjStoreError::InvalidRequest {
+        message: format!("key is not valid UTF-8 and not supported by this store: '{key}'"),
+        source: None,
+    })
+}
+
+impl std::fmt::Display for ObjectKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for chunk in self.0.utf8_chunks() {
+            f.write_str(chunk.valid())?;
+            for byte in chunk.invalid() {
+                write!(f, "\\x{byte:02X}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl std::fmt::Debug for ObjectKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "ObjectKey({:?})", self.display())
+    }
+}
+
+impl From<&str> for ObjectKey {
+    fn from(key: &str) -> Self {
+        Self(key.as_bytes().to_vec())
+    }
+}
+
+impl From<String> for ObjectKey {
+    fn from(key: String) -> Self {
+        Self(key.into_bytes())
+    }
+}
+
+impl From<Vec<u8>> for ObjectKey {
+    fn from(key: Vec<u8>) -> Self {
+        Self(key)
+    }
+}
+
+impl From<&[u8]> for ObjectKey {
+    fn from(key: &[u8]) -> Self {
+        Self(key.to_vec())
+    }
+}
+
+impl AsRef<[u8]> for ObjectKey {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_object_key_display() {
+        let key = ObjectKey::from("dir/file.txt");
+        assert_eq!(key.as_str(), Some("dir/file.txt"));
+        assert_eq!(key.to_string(), "dir/file.txt");
+
+        let key = ObjectKey::from_bytes(b"dir/\xff\xfe.txt".to_vec());
+        assert_eq!(key.as_str(), None);
+        assert_eq!(key.to_string(), "dir/\\xFF\\xFE.txt");
+        assert_eq!(key.clone().into_string(), Err(key));
+    }
+}
@@ -0,0 +1,309 @@
+//! Validated `/`-separated object keys.
+//!
+//! See [`KeyPath`].
+
+use crate::{ObjStoreError, Result};
+
+/// A normalized, `/`-separated object key.
+///
+/// Object stores have no real directories, but keys are conventionally
+/// structured as paths. Building them with `format!("{prefix}/{name}")` is
+/// easy to get wrong: an empty prefix produces a leading slash, a prefix with
+/// a trailing slash produces `//`, and user supplied names can contain `..`.
+///
+/// A `KeyPath` never contains empty, `.` or `..` segments, and never starts
+/// or ends with a slash. The empty path is the root.
+///
+/// ```
+/// use objstore::KeyPath;
+///
+/// let dir = KeyPath::parse("/data//2024/").unwrap();
+/// assert_eq!(dir.as_str(), "data/2024");
+///
+/// let file = dir.join("log.txt").unwrap();
+/// assert_eq!(file.as_str(), "data/2024/log.txt");
+/// assert!(file.starts_with(&dir));
+/// assert!(dir.join("..").is_err());
+/// ```
+#[derive(Clone, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct KeyPath(String);
+
+impl KeyPath {
+    pub const SEPARATOR: char = '/';
+
+    /// The empty root path.
+    pub fn root() -> Self {
+        Self(String::new())
+    }
+
+    /// Parse a key.
+    ///
+    /// Leading, trailing and repeated slashes are removed.
+    /// Fails with [`ObjStoreError::InvalidRequest`] if a segment is `.` or `..`.
+    pub fn parse(path: &str) -> Result<Self> {
+        let mut out = Self::root();
+        for segment in path.split(Self::SEPARATOR).filter(|s| !s.is_empty()) {
+            out.push(segment)?;
+        }
+        Ok(out)
+    }
+
+    /// Append a single segment.
+    ///
+    /// Fails with [`ObjStoreError::InvalidRequest`] if the segment is empty,
+    /// `.` or `..`, or contains a slash.
+    pub fn push(&mut self, segment: &str) -> Result<()> {
+        validate_segment(segment)?;
+        if !self.0.is_empty() {
+            self.0.push(Self::SEPARATOR);
+        }
+        self.0.push_str(segment);
+        Ok(())
+    }
+
+    /// A new path with `segment` appended. See [`Self::push`].
+    pub fn join(&self, segment: &str) -> Result<Self> {
+        let mut out = self.clone();
+        out.push(segment)?;
+        Ok(out)
+    }
+
+    /// A new path with all segments of `other` appended.
+    pub fn join_path(&self, other: &KeyPath) -> Self {
+        match (self.is_root(), other.is_root()) {
+            (_, true) => self.clone(),
+            (true, false) => other.clone(),
+            (false, false) => Self(format!("{}/{}", self.0, other.0)),
+        }
+    }
+
+    pub fn is_root(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    pub fn into_string(self) -> String {
+        self.0
+    }
+
+    /// The key as a listing prefix: the path with a trailing slash, or the
+    /// empty string for the root.
+    pub fn as_prefix(&self) -> String {
+        if self.is_root() {
+            String::new()
+        } else {
+            format!("{}/", self.0)
+        }
+    }
+
+    pub fn segments(&self) -> impl DoubleEndedIterator<Item = &str> {
+        self.0.split(Self::SEPARATOR).filter(|s| !s.is_empty())
+    }
+
+    /// The last segment, or `None` for the root.
+    pub fn name(&self) -> Option<&str> {
+        self.segments().next_back()
+    }
+
+    /// The path without its last segment, or `None` for the root.
+    pub fn parent(&self) -> Option<Self> {
+        if self.is_root() {
+            return None;
+        }
+        let parent = match self.0.rfind(Self::SEPARATOR) {
+            Some(idx) => &self.0[..idx],
+            None => "",
+        };
+        Some(Self(parent.to_string()))
+    }
+
+    /// Whether `prefix` is an ancestor of this path or equal to it.
+    ///
+    /// Only whole segments match: `a/bc` does not start with `a/b`.
+    pub fn starts_with(&self, prefix: &KeyPath) -> bool {
+        self.strip_prefix(prefix).is_some()
+    }
+
+    /// The remainder of this path below `prefix`.
+    ///
+    /// Returns `None` if `prefix` is not an ancestor of this path, and the
+    /// root if both are equal.
+    pub fn strip_prefix(&self, prefix: &KeyPath) -> Option<Self> {
+        if prefix.is_root() {
+            return Some(self.clone());
+        }
+        let rest = self.0.strip_prefix(prefix.as_str())?;
+        if rest.is_empty() {
+            Some(Self::root())
+        } else {
+            rest.strip_prefix(Self::SEPARATOR)
+                .map(|rest| Self(rest.to_string()))
+        }
+    }
+}
+
+/// Validate an object key before it is written.
+///
+/// Backends disagree about unusual keys: the filesystem maps `a//b` and
+/// `a/b` to the same file and can not store `a/` at all, while S3 stores
+/// both literally. To keep stores interchangeable, all built-in backends
+/// reject writes of keys that are:
+///
+/// * empty
+/// * start or end with a slash
+/// * contain an empty (`a//b`), `.` or `..` segment
+///
+/// Such keys are not rewritten, since silently changing a key would make
+/// it unreachable under the name the caller used. Use [`KeyPath::parse`]
+/// to normalize user input instead.
+///
+/// Reads, deletes and listings are not restricted, so keys created by other
+/// tools (like S3 "folder" markers) stay accessible.
+///
+/// Fails with [`ObjStoreError::InvalidRequest`].
+pub fn validate_key(key: &str) -> Result<()> {
+    if key.is_empty() {
+        return Err(ObjStoreError::InvalidRequest {
+            message: "empty key".to_string(),
+            source: None,
+        });
+    }
+    key.split(KeyPath::SEPARATOR)
+        .try_for_each(validate_segment)
+        .map_err(|_| ObjStoreError::InvalidRequest {
+            message: format!(
+                "invalid key '{key}': keys must not start or end with a slash, \
+                 or contain empty, '.' or '..' segments"
+            ),
+            source: None,
+        })
+}
+
+fn validate_segment(segment: &str) -> Result<()> {
+    let problem = if segment.is_empty() {
+        "empty key segment"
+    } else if segment == "." || segment == ".." {
+        "relative key segment"
+    } else if segment.contains(KeyPath::SEPARATOR) {
+        "key segment contains a slash"
+    } else {
+        return Ok(());
+    };
+    Err(ObjStoreError::InvalidRequest {
+        message: format!("{problem}: '{segment}'"),
+        source: None,
+    })
+}
+
+impl std::fmt::Display for KeyPath {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::fmt::Debug for KeyPath {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "KeyPath({:?})", self.0)
+    }
+}
+
+impl std::str::FromStr for KeyPath {
+    type Err = ObjStoreError;
+
+    fn from_str(path: &str) -> Result<Self> {
+        Self::parse(path)
+    }
+}
+
+impl TryFrom<&str> for KeyPath {
+    type Error = ObjStoreError;
+
+    fn try_from(path: &str) -> Result<Self> {
+        Self::parse(path)
+    }
+}
+
+impl From<KeyPath> for String {
+    fn from(path: KeyPath) -> Self {
+        path.0
+    }
+}
+
+impl AsRef<str> for KeyPath {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_key_path_parse_normalizes() {
+        assert_eq!(KeyPath::parse("").unwrap(), KeyPath::root());
+        assert_eq!(KeyPath::parse("/").unwrap(), KeyPath::root());
+        assert_eq!(KeyPath::parse("//a///b/").unwrap().as_str(), "a/b");
+        assert!(KeyPath::parse("a/../b").is_err());
+        assert!(KeyPath::parse("./a").is_err());
+        // Dots inside names are fine.
+        assert_eq!(KeyPath::parse("a/..b/.c").unwrap().as_str(), "a/..b/.c");
+    }
+
+    #[test]
+    fn test_validate_key() {
+        for key in ["a", "a/b", "a/..b/.c", "dir/file.txt", "a b/ü"] {
+            assert!(validate_key(key).is_ok(), "{key}");
+        }
+        for key in ["", "/", "/a", "a/", "a//b", "./a", "a/./b", "a/../b", ".."] {
+            assert!(
+                matches!(validate_key(key), Err(ObjStoreError::InvalidRequest { .. })),
+                "{key}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_key_path_join() {
+        let root = KeyPath::root();
+        assert_eq!(root.join("a").unwrap().as_str(), "a");
+        assert_eq!(root.join("a").unwrap().join("b").unwrap().as_str(), "a/b");
+        assert!(root.join("").is_err());
+        assert!(root.join("..").is_err());
+        assert!(root.join("a/b").is_err());
+
+        let a = KeyPath::parse("a").unwrap();
+        let bc = KeyPath::parse("b/c").unwrap();
+        assert_eq!(a.join_path(&bc).as_str(), "a/b/c");
+        assert_eq!(root.join_path(&bc), bc);
+        assert_eq!(a.join_path(&root), a);
+    }
+
+    #[test]
+    fn test_key_path_relationships() {
+        let path = KeyPath::parse("a/b/c").unwrap();
+        assert_eq!(path.name(), Some("c"));
+        assert_eq!(path.parent().unwrap().as_str(), "a/b");
+        assert_eq!(
+            path.parent().unwrap().parent().unwrap().parent(),
+            Some(KeyPath::root())
+        );
+        assert_eq!(KeyPath::root().parent(), None);
+        assert_eq!(KeyPath::root().name(), None);
+        assert_eq!(path.segments().collect::<Vec<_>>(), ["a", "b", "c"]);
+
+        let ab = KeyPath::parse("a/b").unwrap();
+        assert!(path.starts_with(&ab));
+        assert!(path.starts_with(&KeyPath::root()));
+        assert!(!KeyPath::parse("a/bc").unwrap().starts_with(&ab));
+        assert_eq!(path.strip_prefix(&ab).unwrap().as_str(), "c");
+        assert_eq!(path.strip_prefix(&path), Some(KeyPath::root()));
+        assert_eq!(ab.strip_prefix(&path), None);
+
+        assert_eq!(ab.as_prefix(), "a/b/");
+        assert_eq!(KeyPath::root().as_prefix(), "");
+    }
+}
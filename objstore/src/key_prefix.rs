@@ -0,0 +1,176 @@
+//! Mapping keys below a fixed prefix.
+//!
+//! See [`PrefixMapper`].
+
+use std::borrow::Cow;
+
+use crate::ObjectKey;
+
+/// Maps keys between a scoped key space and the full keys below a fixed
+/// prefix.
+///
+/// Shared by backends with a configurable path prefix and by
+/// [`crate::wrapper::prefix::PrefixObjStore`], so all of them treat slashes
+/// the same way:
+///
+/// * The prefix is normalized to have no leading `/` and a single trailing
+///   `/`, so `"tenant"` and `"/tenant/"` are equivalent.
+/// * Leading `/` of scoped keys are removed when joining, even with an empty
+///   prefix, so `"/a"` and `"a"` address the same object.
+/// * Leading `/` after a non-empty prefix are removed when stripping.
+///
+/// Applying a mapper to a key that already has the prefix adds it again,
+/// so keys must be mapped exactly once in each direction.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PrefixMapper {
+    prefix: String,
+}
+
+impl PrefixMapper {
+    pub fn new(prefix: impl AsRef<str>) -> Self {
+        let prefix = prefix.as_ref().trim_matches('/');
+        Self {
+            prefix: if prefix.is_empty() {
+                String::new()
+            } else {
+                format!("{prefix}/")
+            },
+        }
+    }
+
+    /// The normalized prefix, with a trailing `/` unless empty.
+    pub fn prefix(&self) -> &str {
+        &self.prefix
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.prefix.is_empty()
+    }
+
+    /// The full key for the scoped `key`.
+    pub fn apply<'a>(&self, key: &'a str) -> Cow<'a, str> {
+        let key = key.trim_start_matches('/');
+        if self.prefix.is_empty() {
+            Cow::Borrowed(key)
+        } else {
+            Cow::Owned(format!("{}{key}", self.prefix))
+        }
+    }
+
+    /// Variant of [`Self::apply`] for keys that are not valid UTF-8.
+    pub fn apply_raw(&self, key: &ObjectKey) -> ObjectKey {
+        match key.as_str() {
+            Some(key) => self.apply(key).into_owned().into(),
+            None => ObjectKey::from_bytes([self.prefix.as_bytes(), key.as_bytes()].concat()),
+        }
+    }
+
+    /// The full list prefix for an optional scoped list prefix.
+    ///
+    /// Listing without a prefix lists the whole scope.
+    pub fn apply_list_prefix(&self, prefix: Option<&str>) -> Option<String> {
+        match prefix {
+            Some(prefix) => Some(self.apply(prefix).into_owned()),
+            None if !self.prefix.is_empty() => Some(self.prefix.clone()),
+            None => None,
+        }
+    }
+
+    /// The scoped key for a full `key`, or `None` if it is outside the prefix.
+    pub fn strip<'a>(&self, key: &'a str) -> Option<&'a str> {
+        if self.prefix.is_empty() {
+            return Some(key);
+        }
+        key.strip_prefix(&self.prefix)
+            .map(|suffix| suffix.trim_start_matches('/'))
+    }
+
+    /// Variant of [`Self::strip`] for keys that are not valid UTF-8.
+    pub fn strip_raw(&self, key: &ObjectKey) -> Option<ObjectKey> {
+        let suffix = key.as_bytes().strip_prefix(self.prefix.as_bytes())?;
+        let start = if self.prefix.is_empty() {
+            0
+        } else {
+            suffix.iter().take_while(|byte| **byte == b'/').count()
+        };
+        Some(ObjectKey::from_bytes(&suffix[start..]))
+    }
+
+    /// Owned variant of [`Self::strip`], returning keys outside the prefix
+    /// unchanged as the error.
+    pub fn strip_owned(&self, key: String) -> Result<String, String> {
+        match self.strip(&key) {
+            Some(suffix) if suffix.len() == key.len() => Ok(key),
+            Some(suffix) => Ok(suffix.to_owned()),
+            None => Err(key),
+        }
+    }
+
+    /// Variant of [`Self::strip_owned`] that keeps keys outside the prefix
+    /// unchanged, for error messages and other best-effort mappings.
+    pub fn strip_lossy(&self, key: String) -> String {
+        match self.strip_owned(key) {
+            Ok(key) | Err(key) => key,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_prefix_is_normalized() {
+        for prefix in ["tenant", "/tenant", "tenant/", "//tenant//"] {
+            assert_eq!(PrefixMapper::new(prefix).prefix(), "tenant/");
+        }
+        assert_eq!(PrefixMapper::new("a/b/").prefix(), "a/b/");
+        assert!(PrefixMapper::new("/").is_empty());
+        assert!(PrefixMapper::new("").is_empty());
+    }
+
+    #[test]
+    fn test_apply_and_strip() {
+        let mapper = PrefixMapper::new("tenant");
+        assert_eq!(mapper.apply("a/b.txt"), "tenant/a/b.txt");
+        assert_eq!(mapper.apply("//a"), "tenant/a");
+        assert_eq!(mapper.apply(""), "tenant/");
+        assert_eq!(
+            mapper.apply_raw(&ObjectKey::from_bytes(b"\xff".to_vec())),
+            ObjectKey::from_bytes(b"tenant/\xff".to_vec())
+        );
+
+        assert_eq!(mapper.strip("tenant/a/b.txt"), Some("a/b.txt"));
+        assert_eq!(mapper.strip("tenant//a"), Some("a"));
+        assert_eq!(mapper.strip("tenant/"), Some(""));
+        assert_eq!(mapper.strip("tenantx/a"), None);
+        assert_eq!(
+            mapper.strip_raw(&ObjectKey::from_bytes(b"tenant//\xff".to_vec())),
+            Some(ObjectKey::from_bytes(b"\xff".to_vec()))
+        );
+        assert_eq!(mapper.strip_raw(&ObjectKey::from("other/a")), None);
+        assert_eq!(mapper.strip("other/a"), None);
+        assert_eq!(
+            mapper.strip_owned("other/a".to_string()),
+            Err("other/a".to_string())
+        );
+        assert_eq!(mapper.strip_lossy("other/a".to_string()), "other/a");
+        assert_eq!(mapper.strip_lossy("tenant/a".to_string()), "a");
+
+        assert_eq!(mapper.apply_list_prefix(None).as_deref(), Some("tenant/"));
+        assert_eq!(
+            mapper.apply_list_prefix(Some("/dir")).as_deref(),
+            Some("tenant/dir")
+        );
+    }
+
+    #[test]
+    fn test_empty_prefix() {
+        let mapper = PrefixMapper::default();
+        assert_eq!(mapper.apply("/a"), "a");
+        assert!(matches!(mapper.apply("a"), Cow::Borrowed("a")));
+        assert_eq!(mapper.strip("/a"), Some("/a"));
+        assert_eq!(mapper.strip_owned("a".to_string()), Ok("a".to_string()));
+        assert_eq!(mapper.apply_list_prefix(None), None);
+    }
+}
@@ -2,17 +2,89 @@
 //!
 //! See the [`ObjStore`] trait.
 
+#[cfg(feature = "backup")]
+pub mod backup;
 mod builder;
+mod cancel;
+mod checkpoint;
+mod clock;
+#[cfg(feature = "dedup")]
+mod dedup;
+mod delete_many;
+pub mod diff;
+mod download;
 mod error;
+mod event;
+#[cfg(feature = "fs")]
+mod file;
+mod get_many;
+mod get_ranges;
+mod glob;
+#[cfg(feature = "gzip")]
+mod gzip;
+#[cfg(feature = "hashing")]
+mod hashing;
+mod http;
+pub mod inventory;
+#[cfg(feature = "rate-limit")]
+pub mod io;
+mod key;
+mod key_path;
+mod key_prefix;
+pub mod lifecycle;
+mod list_parallel;
+pub mod maintenance;
+#[cfg(feature = "serde")]
+mod meta_serde;
+mod mime;
+mod ndjson;
+mod pool;
+mod prefix_copy;
+mod progress;
 mod provider;
+mod redact;
+mod request;
+mod secret;
+#[cfg(feature = "signed-url")]
+mod signed_url;
 mod store;
+mod text;
 mod types;
+mod update;
 pub mod wrapper;
 
 pub use self::{
     builder::ObjStoreBuilder,
+    cancel::{CancellationToken, WaitForCancellation},
+    checkpoint::{ScanCheckpoint, ScanEvent, ScanStream},
+    clock::{Clock, ManualClock, SystemClock},
+    delete_many::{DeleteManyOptions, DeleteManyStream},
+    download::ParallelDownloadOptions,
     error::{BackendError, BoxError, ObjStoreError, Operation, Resource, Result},
+    event::{EventStream, ObjectEvent},
+    get_many::{GetManyOptions, GetManyStream},
+    get_ranges::{GetRangesOptions, PlannedRead, RangePlan},
+    glob::Glob,
+    http::HttpClientConfig,
+    key::ObjectKey,
+    key_path::{KeyPath, validate_key},
+    key_prefix::PrefixMapper,
+    list_parallel::{ListPartitions, PartitionStrategy},
+    mime::MimeInference,
+    ndjson::NdjsonStream,
+    pool::BytesPool,
+    prefix_copy::{FailurePolicy, PrefixCopyOptions, PrefixCopyProgress, PrefixCopyReport},
+    progress::{Progress, ProgressCallback},
     provider::ObjStoreProvider,
+    redact::{REDACTED, redact_uri, redact_url},
+    request::RequestOptions,
+    secret::{EnvSecretResolver, FileSecretResolver, SecretResolver},
     store::{DynObjStore, ObjStore, ObjStoreExt},
+    text::TextDecoding,
     types::*,
 };
+
+#[cfg(feature = "hashing")]
+pub use self::hashing::{ContentHashes, HashHandle, HashingStream};
+#[cfg(feature = "signed-url")]
+pub use self::signed_url::UrlSigner;
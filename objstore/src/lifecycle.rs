@@ -0,0 +1,264 @@
+//! Lifecycle rules for backends without native lifecycle management.
+//!
+//! Rules declare what happens to objects once they reach a certain age, similar
+//! to S3 lifecycle configurations: they can be deleted, moved to another
+//! store, or (for incomplete uploads) aborted. [`apply`] executes the rules
+//! once, and [`dry_run`] reports what would be done without changing anything.
+//!
+//! Scheduling is left to the caller, for example a periodic task.
+
+use std::time::Duration;
+
+use futures::TryStreamExt as _;
+use time::OffsetDateTime;
+
+use crate::{
+    DynObjStore, ListArgs, ObjStore, ObjStoreError, Result, maintenance::CleanupOptions,
+    prefix_copy::transfer_object,
+};
+
+/// What a [`LifecycleRule`] does with matching objects.
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub enum LifecycleAction {
+    /// Delete the objects.
+    Expire,
+    /// Move the objects to `target`, below `prefix`.
+    ///
+    /// Objects are streamed to the target with their metadata, and deleted
+    /// from the source once the copy succeeded.
+    Transition { target: DynObjStore, prefix: String },
+    /// Remove incomplete multipart uploads and temporary files of interrupted
+    /// writes, see [`ObjStore::cleanup`].
+    AbortStaleUploads,
+}
+
+/// A lifecycle rule, applied with [`apply`].
+#[derive(Clone, Debug)]
+pub struct LifecycleRule {
+    prefix: String,
+    after: Duration,
+    action: LifecycleAction,
+}
+
+impl LifecycleRule {
+    /// Delete objects below `prefix` once they were not modified for `after`.
+    pub fn expire(prefix: impl Into<String>, after: Duration) -> Self {
+        Self {
+            prefix: prefix.into(),
+            after,
+            action: LifecycleAction::Expire,
+        }
+    }
+
+    /// Move objects below `prefix` to `target` once they were not modified
+    /// for `after`.
+    ///
+    /// Objects keep their key, prefixed with `target_prefix`.
+    pub fn transition(
+        prefix: impl Into<String>,
+        after: Duration,
+        target: DynObjStore,
+        target_prefix: impl Into<String>,
+    ) -> Self {
+        Self {
+            prefix: prefix.into(),
+            after,
+            action: LifecycleAction::Transition {
+                target,
+                prefix: target_prefix.into(),
+            },
+        }
+    }
+
+    /// Remove uploads that were started more than `after` ago and never
+    /// completed.
+    ///
+    /// Backends track uploads store-wide, so this rule has no prefix.
+    pub fn abort_stale_uploads(after: Duration) -> Self {
+        Self {
+            prefix: String::new(),
+            after,
+            action: LifecycleAction::AbortStaleUploads,
+        }
+    }
+
+    /// Prefix of the objects the rule applies to.
+    pub fn prefix(&self) -> &str {
+        &self.prefix
+    }
+
+    /// Minimum age of the objects the rule applies to.
+    pub fn after(&self) -> Duration {
+        self.after
+    }
+
+    pub fn action(&self) -> &LifecycleAction {
+        &self.action
+    }
+
+    /// Whether an object with the given modification time is old enough.
+    ///
+    /// Objects without a known time are never affected.
+    fn is_due(&self, time: Option<OffsetDateTime>, now: OffsetDateTime) -> bool {
+        time.is_some_and(|time| time + self.after <= now)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum LifecycleEventKind {
+    /// The object was deleted.
+    Expired,
+    /// The object was moved to another store.
+    Transitioned,
+    /// An incomplete upload or temporary file was removed.
+    UploadAborted,
+}
+
+/// An action taken (or planned, in a dry run) by [`apply`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct LifecycleEvent {
+    /// Index of the rule in the list passed to [`apply`].
+    pub rule: usize,
+    pub kind: LifecycleEventKind,
+    /// The key of the object (or the path of the upload).
+    pub key: String,
+    /// The key in the target store, for transitions.
+    pub target_key: Option<String>,
+    pub size: Option<u64>,
+    pub updated_at: Option<OffsetDateTime>,
+}
+
+/// Result of [`apply`] and [`dry_run`].
+#[derive(Debug, Default)]
+#[non_exhaustive]
+pub struct LifecycleReport {
+    /// Whether the report was created by [`dry_run`].
+    pub dry_run: bool,
+    /// Actions that were taken, or would be taken in a dry run.
+    pub applied: Vec<LifecycleEvent>,
+    /// Actions that failed, with the error.
+    pub failed: Vec<(LifecycleEvent, ObjStoreError)>,
+}
+
+impl LifecycleReport {
+    fn record(&mut self, event: LifecycleEvent, result: Result<()>) {
+        match result {
+            Ok(()) => self.applied.push(event),
+            Err(err) => self.failed.push((event, err)),
+        }
+    }
+
+    /// Number of applied actions of the given kind.
+    pub fn count(&self, kind: LifecycleEventKind) -> usize {
+        self.applied
+            .iter()
+            .filter(|event| event.kind == kind)
+            .count()
+    }
+
+    /// Total size of the objects affected by applied actions, where known.
+    pub fn applied_bytes(&self) -> u64 {
+        self.applied.iter().filter_map(|event| event.size).sum()
+    }
+}
+
+/// Apply lifecycle rules to `store`.
+///
+/// Rules are applied in order, so an object that was expired or moved by an
+/// earlier rule is not seen by later ones. Failures of individual objects are
+/// reported in [`LifecycleReport::failed`]; listing errors abort the run.
+pub async fn apply<S>(store: &S, rules: &[LifecycleRule]) -> Result<LifecycleReport>
+where
+    S: ObjStore + ?Sized,
+{
+    run(store, rules, false).await
+}
+
+/// Report what [`apply`] would do, without changing anything.
+///
+/// Since nothing is removed, an object can show up for multiple rules.
+pub async fn dry_run<S>(store: &S, rules: &[LifecycleRule]) -> Result<LifecycleReport>
+where
+    S: ObjStore + ?Sized,
+{
+    run(store, rules, true).await
+}
+
+async fn run<S>(store: &S, rules: &[LifecycleRule], dry_run: bool) -> Result<LifecycleReport>
+where
+    S: ObjStore + ?Sized,
+{
+    let mut report = LifecycleReport {
+        dry_run,
+        ..Default::default()
+    };
+    let now = OffsetDateTime::now_utc();
+
+    for (index, rule) in rules.iter().enumerate() {
+        if let LifecycleAction::AbortStaleUploads = rule.action {
+            let options = CleanupOptions::new()
+                .with_older_than(rule.after)
+                .with_dry_run(dry_run)
+                .without_trash_prefix();
+            let cleanup = store.cleanup(options).await?;
+            let event = |leftover: crate::maintenance::Leftover| LifecycleEvent {
+                rule: index,
+                kind: LifecycleEventKind::UploadAborted,
+                key: leftover.key,
+                target_key: None,
+                size: leftover.size,
+                updated_at: leftover.created_at,
+            };
+            for leftover in cleanup.removed {
+                report.record(event(leftover), Ok(()));
+            }
+            for (leftover, err) in cleanup.failed {
+                report.record(event(leftover), Err(err));
+            }
+            continue;
+        }
+
+        // Collect first, so deletions don't interfere with listing.
+        let due = store
+            .list_items_stream(ListArgs::new().with_prefix(&rule.prefix))
+            .try_filter(|meta| std::future::ready(rule.is_due(meta.updated_at, now)))
+            .try_collect::<Vec<_>>()
+            .await?;
+        for meta in due {
+            let (kind, target_key) = match &rule.action {
+                LifecycleAction::Transition { prefix, .. } => (
+                    LifecycleEventKind::Transitioned,
+                    Some(format!("{prefix}{}", meta.key)),
+                ),
+                _ => (LifecycleEventKind::Expired, None),
+            };
+            let result = if dry_run {
+                Ok(())
+            } else {
+                match &rule.action {
+                    LifecycleAction::Transition { target, .. } => {
+                        let target_key = target_key.as_deref().unwrap_or_default();
+                        match transfer_object(store, target.as_ref(), &meta.key, target_key).await {
+                            Ok(()) => store.delete(&meta.key).await,
+                            Err(err) => Err(err),
+                        }
+                    }
+                    _ => store.delete(&meta.key).await,
+                }
+            };
+            let event = LifecycleEvent {
+                rule: index,
+                kind,
+                key: meta.key,
+                target_key,
+                size: meta.size,
+                updated_at: meta.updated_at,
+            };
+            report.record(event, result);
+        }
+    }
+    Ok(report)
+}
@@ -0,0 +1,330 @@
+//! Concurrent listings over partitioned key spaces.
+//!
+//! See [`crate::ObjStoreExt::list_parallel`].
+
+use std::{
+    collections::VecDeque,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use futures::{Stream, StreamExt as _, TryStreamExt as _, stream, stream::BoxStream};
+
+use crate::{ListArgs, ObjStore, ObjectMeta, Result, SortOrder, store::list_pages};
+
+/// Characters used to split the key space with [`PartitionStrategy::FirstByte`],
+/// in byte order.
+const FIRST_BYTE_ALPHABET: &[u8] =
+    b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz";
+
+/// How [`crate::ObjStoreExt::list_parallel`] splits the key space.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PartitionStrategy {
+    /// Split by the first character after the list prefix.
+    ///
+    /// Partition boundaries are spread evenly over `0-9`, `A-Z` and `a-z`,
+    /// which works well for random or hex/base64 encoded keys.
+    /// Does not require an additional request.
+    FirstByte,
+    /// Split at the common prefixes of a delimiter listing.
+    ///
+    /// The common prefixes are listed first, and the partition boundaries are
+    /// spread evenly over them. Works well for hierarchical key spaces with
+    /// many similarly sized "directories".
+    Delimiter(String),
+}
+
+/// Options for [`crate::ObjStoreExt::list_parallel`].
+#[derive(Clone, Debug)]
+pub struct ListPartitions {
+    count: usize,
+    strategy: PartitionStrategy,
+    max_buffered_pages: usize,
+}
+
+impl Default for ListPartitions {
+    fn default() -> Self {
+        Self {
+            count: Self::DEFAULT_COUNT,
+            strategy: PartitionStrategy::FirstByte,
+            max_buffered_pages: Self::DEFAULT_MAX_BUFFERED_PAGES,
+        }
+    }
+}
+
+impl ListPartitions {
+    pub const DEFAULT_COUNT: usize = 8;
+    pub const DEFAULT_MAX_BUFFERED_PAGES: usize = 8;
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Split by the first character after the prefix into `count` partitions.
+    pub fn first_byte(count: usize) -> Self {
+        Self::new().with_count(count)
+    }
+
+    /// Split at the common prefixes for `delimiter` into `count` partitions.
+    pub fn delimiter(count: usize, delimiter: impl Into<String>) -> Self {
+        Self::new()
+            .with_count(count)
+            .with_strategy(PartitionStrategy::Delimiter(delimiter.into()))
+    }
+
+    /// Maximum number of partitions, which are all listed concurrently.
+    ///
+    /// Fewer partitions are used if the key space can not be split further.
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    /// Set the number of partitions. Values of `0` are ignored.
+    pub fn with_count(mut self, count: usize) -> Self {
+        if count > 0 {
+            self.count = count;
+        }
+        self
+    }
+
+    pub fn strategy(&self) -> &PartitionStrategy {
+        &self.strategy
+    }
+
+    pub fn with_strategy(mut self, strategy: PartitionStrategy) -> Self {
+        self.strategy = strategy;
+        self
+    }
+
+    /// Number of pages each partition lists ahead of the consumer.
+    ///
+    /// Partitions after the one currently consumed stop listing once this
+    /// many pages are buffered, which bounds memory usage.
+    pub fn max_buffered_pages(&self) -> usize {
+        self.max_buffered_pages
+    }
+
+    /// Set the number of buffered pages per partition. Values of `0` are ignored.
+    pub fn with_max_buffered_pages(mut self, pages: usize) -> Self {
+        if pages > 0 {
+            self.max_buffered_pages = pages;
+        }
+        self
+    }
+}
+
+pub(crate) fn list_parallel<'a, S>(
+    store: &'a S,
+    args: ListArgs,
+    partitions: ListPartitions,
+) -> BoxStream<'a, Result<ObjectMeta>>
+where
+    S: ObjStore + ?Sized,
+{
+    // Partitions stop listing once they pass their upper bound, which
+    // requires the native order to be by key. Delimiter listings, cursors
+    // and custom orders can not be split into key ranges.
+    let splittable = store.supports_sort(SortOrder::KeyAscending)
+        && args.delimiter().is_none()
+        && args.cursor().is_none()
+        && args
+            .sort()
+            .is_none_or(|sort| sort == SortOrder::KeyAscending);
+    if !splittable || partitions.count == 1 {
+        return store.list_items_stream(args);
+    }
+
+    stream::once(async move {
+        let boundaries = boundaries(store, &args, &partitions).await;
+        boundaries.map(|boundaries| {
+            ParallelList::new(store, &args, boundaries, partitions.max_buffered_pages)
+        })
+    })
+    .try_flatten()
+    .boxed()
+}
+
+/// Compute the sorted, exclusive upper bounds of all partitions but the last.
+async fn boundaries<S>(
+    store: &S,
+    args: &ListArgs,
+    partitions: &ListPartitions,
+) -> Result<Vec<String>>
+where
+    S: ObjStore + ?Sized,
+{
+    let prefix = args.prefix().unwrap_or_default();
+    let candidates = match &partitions.strategy {
+        PartitionStrategy::FirstByte => FIRST_BYTE_ALPHABET
+            .iter()
+            .map(|byte| format!("{prefix}{}", *byte as char))
+            .collect(),
+        PartitionStrategy::Delimiter(delimiter) => {
+            let mut list_args = ListArgs::new()
+                .with_prefix(prefix)
+                .with_delimiter(delimiter.clone());
+            if let Some(token) = args.cancellation() {
+                list_args = list_args.with_cancellation(token.clone());
+            }
+            let mut prefixes = Vec::new();
+            let mut pages = list_pages(store, list_args);
+            while let Some(page) = pages.try_next().await? {
+                prefixes.extend(page.prefixes.unwrap_or_default());
+            }
+            prefixes.sort();
+            prefixes
+        }
+    };
+    Ok(split(candidates, partitions.count, args.start_after()))
+}
+
+/// Pick up to `count - 1` evenly spread boundaries from sorted `candidates`.
+///
+/// Partition `i` covers the keys in `(boundaries[i - 1], boundaries[i]]`.
+/// Every key below a candidate prefix sorts after the candidate itself, so
+/// a boundary never splits the keys of a prefix.
+fn split(candidates: Vec<String>, count: usize, start_after: Option<&str>) -> Vec<String> {
+    let candidates: Vec<_> = candidates
+        .into_iter()
+        .filter(|candidate| start_after.is_none_or(|start| candidate.as_str() > start))
+        .collect();
+    let mut boundaries: Vec<String> = (1..count)
+        .map(|index| index * candidates.len() / count)
+        .filter(|index| *index > 0)
+        .map(|index| candidates[index].clone())
+        .collect();
+    boundaries.dedup();
+    boundaries
+}
+
+/// A single key range of a parallel listing.
+struct Partition<'a> {
+    /// `None` once the listing is exhausted, failed or passed `upper`.
+    pages: Option<BoxStream<'a, Result<Vec<ObjectMeta>>>>,
+    upper: Option<String>,
+    buffered: VecDeque<Result<Vec<ObjectMeta>>>,
+}
+
+impl Partition<'_> {
+    /// Buffer pages until `max` pages are ready or the listing is pending.
+    fn fill(&mut self, cx: &mut Context<'_>, max: usize) {
+        while self.buffered.len() < max {
+            let Some(pages) = &mut self.pages else {
+                return;
+            };
+            match pages.poll_next_unpin(cx) {
+                Poll::Ready(Some(Ok(mut items))) => {
+                    if let Some(upper) = &self.upper {
+                        let len = items.len();
+                        items.retain(|meta| meta.key.as_str() <= upper.as_str());
+                        if items.len() < len {
+                            self.pages = None;
+                        }
+                    }
+                    self.buffered.push_back(Ok(items));
+                }
+                Poll::Ready(Some(Err(err))) => {
+                    self.pages = None;
+                    self.buffered.push_back(Err(err));
+                }
+                Poll::Ready(None) => self.pages = None,
+                Poll::Pending => return,
+            }
+        }
+    }
+}
+
+/// Lists all partitions concurrently and yields their items in partition order.
+struct ParallelList<'a> {
+    partitions: VecDeque<Partition<'a>>,
+    current: std::vec::IntoIter<ObjectMeta>,
+    max_buffered_pages: usize,
+}
+
+impl<'a> ParallelList<'a> {
+    fn new<S>(
+        store: &'a S,
+        args: &ListArgs,
+        boundaries: Vec<String>,
+        max_buffered_pages: usize,
+    ) -> Self
+    where
+        S: ObjStore + ?Sized,
+    {
+        let lowers = std::iter::once(None).chain(boundaries.iter().cloned().map(Some));
+        let uppers = boundaries
+            .iter()
+            .cloned()
+            .map(Some)
+            .chain(std::iter::once(None));
+        let partitions = lowers
+            .zip(uppers)
+            .map(|(lower, upper)| {
+                let args = match lower {
+                    Some(lower) => args.clone().with_start_after(lower),
+                    None => args.clone(),
+                };
+                Partition {
+                    pages: Some(list_pages(store, args).map_ok(|page| page.items).boxed()),
+                    upper,
+                    buffered: VecDeque::new(),
+                }
+            })
+            .collect();
+        Self {
+            partitions,
+            current: Vec::new().into_iter(),
+            max_buffered_pages,
+        }
+    }
+}
+
+impl Stream for ParallelList<'_> {
+    type Item = Result<ObjectMeta>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = &mut *self;
+        loop {
+            if let Some(item) = this.current.next() {
+                return Poll::Ready(Some(Ok(item)));
+            }
+            for partition in &mut this.partitions {
+                partition.fill(cx, this.max_buffered_pages);
+            }
+            let Some(partition) = this.partitions.front_mut() else {
+                return Poll::Ready(None);
+            };
+            match partition.buffered.pop_front() {
+                Some(Ok(items)) => this.current = items.into_iter(),
+                Some(Err(err)) => {
+                    this.partitions.clear();
+                    return Poll::Ready(Some(Err(err)));
+                }
+                None if partition.pages.is_none() => {
+                    this.partitions.pop_front();
+                }
+                // All pending partitions were polled by `fill`, so the waker is registered.
+                None => return Poll::Pending,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn strings(values: &[&str]) -> Vec<String> {
+        values.iter().map(|value| value.to_string()).collect()
+    }
+
+    #[test]
+    fn split_spreads_boundaries_evenly() {
+        let candidates = strings(&["a/", "b/", "c/", "d/", "e/", "f/"]);
+        assert_eq!(split(candidates.clone(), 3, None), ["c/", "e/"]);
+        assert_eq!(split(candidates.clone(), 1, None), Vec::<String>::new());
+        assert_eq!(split(candidates.clone(), 10, None).len(), 5);
+        assert_eq!(split(candidates, 2, Some("c/")), ["e/"]);
+        assert!(split(Vec::new(), 4, None).is_empty());
+    }
+}
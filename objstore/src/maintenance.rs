@@ -0,0 +1,184 @@
+//! Removing leftovers of failed or abandoned operations.
+//!
+//! See [`crate::ObjStore::cleanup`].
+
+use std::time::Duration;
+
+use futures::TryStreamExt as _;
+use time::OffsetDateTime;
+
+use crate::{ListArgs, ObjStore, ObjStoreError, Result};
+
+/// Options for [`crate::ObjStore::cleanup`].
+#[derive(Clone, Debug)]
+pub struct CleanupOptions {
+    older_than: Duration,
+    dry_run: bool,
+    trash_prefix: Option<String>,
+}
+
+impl Default for CleanupOptions {
+    fn default() -> Self {
+        Self {
+            older_than: Self::DEFAULT_OLDER_THAN,
+            dry_run: false,
+            trash_prefix: Some(Self::DEFAULT_TRASH_PREFIX.to_string()),
+        }
+    }
+}
+
+impl CleanupOptions {
+    pub const DEFAULT_OLDER_THAN: Duration = Duration::from_secs(24 * 60 * 60);
+    pub const DEFAULT_TRASH_PREFIX: &'static str = ".trash/";
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Minimum age of leftovers to remove.
+    pub fn older_than(&self) -> Duration {
+        self.older_than
+    }
+
+    /// Only remove leftovers older than `age`.
+    ///
+    /// Younger leftovers may still belong to running operations.
+    pub fn with_older_than(mut self, age: Duration) -> Self {
+        self.older_than = age;
+        self
+    }
+
+    pub fn dry_run(&self) -> bool {
+        self.dry_run
+    }
+
+    /// Only report leftovers, without removing them.
+    pub fn with_dry_run(mut self, dry_run: bool) -> Self {
+        self.dry_run = dry_run;
+        self
+    }
+
+    /// Prefix holding soft-deleted objects.
+    pub fn trash_prefix(&self) -> Option<&str> {
+        self.trash_prefix.as_deref()
+    }
+
+    /// Set the prefix holding soft-deleted objects.
+    pub fn with_trash_prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.trash_prefix = Some(prefix.into());
+        self
+    }
+
+    /// Keep soft-deleted objects.
+    pub fn without_trash_prefix(mut self) -> Self {
+        self.trash_prefix = None;
+        self
+    }
+
+    /// Whether a leftover with the given time is old enough to be removed.
+    ///
+    /// Leftovers without a known time are never removed.
+    pub fn is_stale(&self, time: Option<OffsetDateTime>) -> bool {
+        time.is_some_and(|time| time + self.older_than <= OffsetDateTime::now_utc())
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum LeftoverKind {
+    /// An incomplete multipart upload.
+    MultipartUpload,
+    /// A temporary file of an interrupted write.
+    TempFile,
+    /// A soft-deleted object below [`CleanupOptions::trash_prefix`].
+    Trash,
+}
+
+/// A leftover found by [`crate::ObjStore::cleanup`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Leftover {
+    pub kind: LeftoverKind,
+    /// The key (or file path) of the leftover.
+    pub key: String,
+    pub size: Option<u64>,
+    pub created_at: Option<OffsetDateTime>,
+}
+
+impl Leftover {
+    pub fn new(
+        kind: LeftoverKind,
+        key: impl Into<String>,
+        size: Option<u64>,
+        created_at: Option<OffsetDateTime>,
+    ) -> Self {
+        Self {
+            kind,
+            key: key.into(),
+            size,
+            created_at,
+        }
+    }
+}
+
+/// Result of [`crate::ObjStore::cleanup`].
+#[derive(Debug, Default)]
+#[non_exhaustive]
+pub struct CleanupReport {
+    /// Removed leftovers, or leftovers that would be removed in a dry run.
+    pub removed: Vec<Leftover>,
+    /// Leftovers that could not be removed, with the error.
+    pub failed: Vec<(Leftover, ObjStoreError)>,
+}
+
+impl CleanupReport {
+    /// Record the result of removing a leftover.
+    pub fn record(&mut self, leftover: Leftover, result: Result<()>) {
+        match result {
+            Ok(()) => self.removed.push(leftover),
+            Err(err) => self.failed.push((leftover, err)),
+        }
+    }
+
+    /// Add the results of another cleanup.
+    pub fn merge(&mut self, other: CleanupReport) {
+        self.removed.extend(other.removed);
+        self.failed.extend(other.failed);
+    }
+
+    /// Total size of the removed leftovers, where known.
+    pub fn removed_bytes(&self) -> u64 {
+        self.removed.iter().filter_map(|item| item.size).sum()
+    }
+}
+
+/// Remove stale objects below [`CleanupOptions::trash_prefix`].
+///
+/// This is the default implementation of [`crate::ObjStore::cleanup`], which
+/// backends call in addition to removing their own leftovers.
+pub async fn cleanup_trash<S>(store: &S, options: &CleanupOptions) -> Result<CleanupReport>
+where
+    S: ObjStore + ?Sized,
+{
+    let mut report = CleanupReport::default();
+    let Some(prefix) = options.trash_prefix() else {
+        return Ok(report);
+    };
+
+    // Collect first, so deletions don't interfere with listing.
+    let stale = store
+        .list_items_stream(ListArgs::new().with_prefix(prefix))
+        .try_filter(|meta| std::future::ready(options.is_stale(meta.updated_at)))
+        .try_collect::<Vec<_>>()
+        .await?;
+    for meta in stale {
+        let result = if options.dry_run {
+            Ok(())
+        } else {
+            store.delete(&meta.key).await
+        };
+        let leftover = Leftover::new(LeftoverKind::Trash, meta.key, meta.size, meta.updated_at);
+        report.record(leftover, result);
+    }
+    Ok(report)
+}
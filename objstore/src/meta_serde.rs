@@ -0,0 +1,335 @@
+//! Serde support for [`ObjectMeta`].
+//!
+//! The serialized form is a stable, versioned layout:
+//!
+//! ```json
+//! {
+//!   "version": 2,
+//!   "key": "dir/file.txt",
+//!   "etag": "\"abc\"",
+//!   "size": 42,
+//!   "created_at": "2024-01-02T03:04:05Z",
+//!   "updated_at": "2024-01-02T03:04:05Z",
+//!   "hash_md5": "d41d8cd98f00b204e9800998ecf8427e",
+//!   "hash_sha256": "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855",
+//!   "mime_type": "text/plain",
+//!   "cache_control": "max-age=3600",
+//!   "content_disposition": "attachment",
+//!   "content_encoding": "gzip",
+//!   "expires_at": "2024-01-03T03:04:05Z",
+//!   "encryption": {"scheme": "aws:kms", "key_id": "key-arn"},
+//!   "attributes": {
+//!     "storage_class": "STANDARD",
+//!     "version_id": "v1",
+//!     "owner": "owner-id",
+//!     "custom": {"logfs.chunk_size": 1024}
+//!   }
+//! }
+//! ```
+//!
+//! * Timestamps are RFC3339 strings.
+//! * Hashes are lowercase hex strings.
+//! * Absent optional values are omitted, and `attributes` is omitted when empty.
+//! * `version` is written on serialization. Unknown versions are rejected.
+//! * Version 1 had a free-form `extra` map instead of `attributes`, which is
+//!   read into the custom attributes. Without a version, both are accepted.
+
+use std::collections::{BTreeMap, HashMap};
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer, de::Error as _};
+use time::{OffsetDateTime, format_description::well_known::Rfc3339};
+
+use crate::{Attributes, Encryption, EncryptionScheme, ObjectMeta};
+
+/// Current version of the serialized [`ObjectMeta`] layout.
+const WIRE_VERSION: u32 = 2;
+
+/// Layout version with a free-form `extra` map instead of `attributes`.
+const WIRE_VERSION_EXTRA: u32 = 1;
+
+#[derive(Serialize, Deserialize)]
+struct WireEncryption {
+    scheme: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    key_id: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Default)]
+struct WireAttributes {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    storage_class: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    version_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    owner: Option<String>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    custom: BTreeMap<String, serde_json::Value>,
+}
+
+#[derive(Serialize)]
+struct WireRef<'a> {
+    version: u32,
+    key: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    etag: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    size: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    created_at: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    updated_at: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    hash_md5: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    hash_sha256: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    mime_type: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    cache_control: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    content_disposition: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    content_encoding: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    expires_at: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    encryption: Option<WireEncryption>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    attributes: Option<WireAttributes>,
+}
+
+#[derive(Deserialize)]
+struct Wire {
+    #[serde(default)]
+    version: Option<u32>,
+    key: String,
+    #[serde(default)]
+    etag: Option<String>,
+    #[serde(default)]
+    size: Option<u64>,
+    #[serde(default)]
+    created_at: Option<String>,
+    #[serde(default)]
+    updated_at: Option<String>,
+    #[serde(default)]
+    hash_md5: Option<String>,
+    #[serde(default)]
+    hash_sha256: Option<String>,
+    #[serde(default)]
+    mime_type: Option<String>,
+    #[serde(default)]
+    cache_control: Option<String>,
+    #[serde(default)]
+    content_disposition: Option<String>,
+    #[serde(default)]
+    content_encoding: Option<String>,
+    #[serde(default)]
+    expires_at: Option<String>,
+    #[serde(default)]
+    encryption: Option<WireEncryption>,
+    #[serde(default)]
+    attributes: Option<WireAttributes>,
+    #[serde(default)]
+    extra: HashMap<String, serde_json::Value>,
+}
+
+impl Serialize for ObjectMeta {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let format_ts = |ts: &OffsetDateTime| {
+            ts.format(&Rfc3339)
+                .map_err(|err| serde::ser::Error::custom(format!("invalid timestamp: {err}")))
+        };
+
+        WireRef {
+            version: WIRE_VERSION,
+            key: &self.key,
+            etag: self.etag.as_deref(),
+            size: self.size,
+            created_at: self.created_at.as_ref().map(format_ts).transpose()?,
+            updated_at: self.updated_at.as_ref().map(format_ts).transpose()?,
+            hash_md5: self.hash_md5.as_ref().map(|hash| to_hex(hash)),
+            hash_sha256: self.hash_sha256.as_ref().map(|hash| to_hex(hash)),
+            mime_type: self.mime_type.as_deref(),
+            cache_control: self.cache_control.as_deref(),
+            content_disposition: self.content_disposition.as_deref(),
+            content_encoding: self.content_encoding.as_deref(),
+            expires_at: self.expires_at.as_ref().map(format_ts).transpose()?,
+            encryption: self.encryption.as_ref().map(|encryption| WireEncryption {
+                scheme: encryption.scheme.as_str().to_string(),
+                key_id: encryption.key_id.clone(),
+            }),
+            attributes: (!self.attributes.is_empty()).then(|| WireAttributes {
+                storage_class: self.attributes.storage_class.clone(),
+                version_id: self.attributes.version_id.clone(),
+                owner: self.attributes.owner.clone(),
+                custom: self.attributes.custom.clone(),
+            }),
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for ObjectMeta {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let wire = Wire::deserialize(deserializer)?;
+
+        let attributes = match wire.version {
+            Some(WIRE_VERSION) => wire.attributes.unwrap_or_default(),
+            Some(WIRE_VERSION_EXTRA) => WireAttributes {
+                custom: wire.extra.into_iter().collect(),
+                ..WireAttributes::default()
+            },
+            None => {
+                let mut attributes = wire.attributes.unwrap_or_default();
+                attributes.custom.extend(wire.extra);
+                attributes
+            }
+            Some(version) => {
+                return Err(D::Error::custom(format!(
+                    "unsupported ObjectMeta version {version}"
+                )));
+            }
+        };
+
+        let parse_ts = |value: String| {
+            OffsetDateTime::parse(&value, &Rfc3339)
+                .map_err(|err| D::Error::custom(format!("invalid timestamp '{value}': {err}")))
+        };
+
+        Ok(ObjectMeta {
+            key: wire.key,
+            etag: wire.etag,
+            size: wire.size,
+            created_at: wire.created_at.map(parse_ts).transpose()?,
+            updated_at: wire.updated_at.map(parse_ts).transpose()?,
+            hash_md5: wire
+                .hash_md5
+                .map(|value| from_hex(&value).map_err(D::Error::custom))
+                .transpose()?,
+            hash_sha256: wire
+                .hash_sha256
+                .map(|value| from_hex(&value).map_err(D::Error::custom))
+                .transpose()?,
+            mime_type: wire.mime_type,
+            cache_control: wire.cache_control,
+            content_disposition: wire.content_disposition,
+            content_encoding: wire.content_encoding,
+            expires_at: wire.expires_at.map(parse_ts).transpose()?,
+            encryption: wire.encryption.map(|encryption| Encryption {
+                scheme: EncryptionScheme::parse(&encryption.scheme),
+                key_id: encryption.key_id,
+            }),
+            attributes: Attributes {
+                storage_class: attributes.storage_class,
+                version_id: attributes.version_id,
+                owner: attributes.owner,
+                custom: attributes.custom,
+            },
+        })
+    }
+}
+
+fn to_hex(data: &[u8]) -> String {
+    use std::fmt::Write as _;
+
+    data.iter()
+        .fold(String::with_capacity(data.len() * 2), |mut out, byte| {
+            let _ = write!(out, "{byte:02x}");
+            out
+        })
+}
+
+fn from_hex<const N: usize>(value: &str) -> Result<[u8; N], String> {
+    if value.len() != N * 2 || !value.is_ascii() {
+        return Err(format!("invalid hex hash '{value}': expected {N} bytes"));
+    }
+    let mut out = [0u8; N];
+    for (index, byte) in out.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(&value[index * 2..index * 2 + 2], 16)
+            .map_err(|err| format!("invalid hex hash '{value}': {err}"))?;
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> ObjectMeta {
+        let mut meta = ObjectMeta::new("dir/file.txt".to_string());
+        meta.etag = Some("\"abc\"".to_string());
+        meta.size = Some(42);
+        meta.created_at = Some(OffsetDateTime::from_unix_timestamp(1_704_164_645).unwrap());
+        meta.updated_at = meta.created_at;
+        meta.expires_at = Some(OffsetDateTime::from_unix_timestamp(1_704_251_045).unwrap());
+        meta.hash_md5 = Some([0xab; 16]);
+        meta.hash_sha256 = Some([0x01; 32]);
+        meta.mime_type = Some("text/plain".to_string());
+        meta.cache_control = Some("max-age=3600".to_string());
+        meta.encryption = Some(Encryption::new(EncryptionScheme::Kms).with_key_id("key-arn"));
+        meta.attributes.storage_class = Some("STANDARD".to_string());
+        meta.attributes.set_custom("app.custom", "value");
+        meta
+    }
+
+    #[test]
+    fn test_object_meta_wire_format() {
+        let value = serde_json::to_value(sample()).unwrap();
+        assert_eq!(
+            value,
+            serde_json::json!({
+                "version": 2,
+                "key": "dir/file.txt",
+                "etag": "\"abc\"",
+                "size": 42,
+                "created_at": "2024-01-02T03:04:05Z",
+                "updated_at": "2024-01-02T03:04:05Z",
+                "hash_md5": "ab".repeat(16),
+                "hash_sha256": "01".repeat(32),
+                "mime_type": "text/plain",
+                "cache_control": "max-age=3600",
+                "expires_at": "2024-01-03T03:04:05Z",
+                "encryption": {"scheme": "aws:kms", "key_id": "key-arn"},
+                "attributes": {
+                    "storage_class": "STANDARD",
+                    "custom": {"app.custom": "value"},
+                },
+            })
+        );
+
+        let minimal = serde_json::to_value(ObjectMeta::new("a".to_string())).unwrap();
+        assert_eq!(minimal, serde_json::json!({"version": 2, "key": "a"}));
+    }
+
+    #[test]
+    fn test_object_meta_roundtrip() {
+        let meta = sample();
+        let json = serde_json::to_string(&meta).unwrap();
+        let parsed: ObjectMeta = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed, meta);
+
+        let parsed: ObjectMeta = serde_json::from_str(r#"{"key": "a"}"#).unwrap();
+        assert_eq!(parsed, ObjectMeta::new("a".to_string()));
+    }
+
+    #[test]
+    fn test_object_meta_reads_version_1_extra() {
+        let parsed: ObjectMeta =
+            serde_json::from_str(r#"{"version": 1, "key": "a", "extra": {"chunk_size": 1}}"#)
+                .unwrap();
+        assert_eq!(
+            parsed.attributes.custom("chunk_size"),
+            Some(&serde_json::json!(1))
+        );
+    }
+
+    #[test]
+    fn test_object_meta_rejects_invalid() {
+        assert!(serde_json::from_str::<ObjectMeta>(r#"{"version": 3, "key": "a"}"#).is_err());
+        assert!(serde_json::from_str::<ObjectMeta>(r#"{"key": "a", "hash_md5": "zz"}"#).is_err());
+        assert!(
+            serde_json::from_str::<ObjectMeta>(r#"{"key": "a", "created_at": "yesterday"}"#)
+                .is_err()
+        );
+    }
+}
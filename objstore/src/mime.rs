@@ -0,0 +1,118 @@
+//! MIME type inference.
+
+/// How to infer the MIME type of uploaded data if none is set explicitly.
+///
+/// Used by the put builder returned from [`crate::ObjStoreExt::put`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum MimeInference {
+    /// Do not infer a MIME type.
+    #[default]
+    Disabled,
+    /// Guess from the extension of the key.
+    Extension,
+    /// Guess from the leading bytes of the content.
+    Content,
+    /// Guess from the key extension, falling back to the content.
+    Auto,
+}
+
+impl MimeInference {
+    /// Infer the MIME type for `key`.
+    ///
+    /// `head` should contain the leading bytes of the content, if available.
+    pub fn infer(self, key: &str, head: Option<&[u8]>) -> Option<String> {
+        match self {
+            Self::Disabled => None,
+            Self::Extension => mime_from_extension(key),
+            Self::Content => head.and_then(mime_from_content),
+            Self::Auto => mime_from_extension(key).or_else(|| head.and_then(mime_from_content)),
+        }
+    }
+
+    pub(crate) fn uses_content(self) -> bool {
+        matches!(self, Self::Content | Self::Auto)
+    }
+}
+
+/// Guess a MIME type from the extension of a key or path.
+pub(crate) fn mime_from_extension(key: &str) -> Option<String> {
+    mime_guess::from_path(key)
+        .first()
+        .map(|mime| mime.essence_str().to_string())
+}
+
+/// Guess a MIME type from well-known signatures in the leading bytes of the content.
+pub(crate) fn mime_from_content(head: &[u8]) -> Option<String> {
+    const SIGNATURES: &[(&[u8], &str)] = &[
+        (b"\x89PNG\r\n\x1a\n", "image/png"),
+        (b"\xff\xd8\xff", "image/jpeg"),
+        (b"GIF87a", "image/gif"),
+        (b"GIF89a", "image/gif"),
+        (b"%PDF-", "application/pdf"),
+        (b"PK\x03\x04", "application/zip"),
+        (b"\x1f\x8b", "application/gzip"),
+        (b"\x00asm", "application/wasm"),
+        (b"<?xml", "application/xml"),
+    ];
+
+    for (signature, mime) in SIGNATURES {
+        if head.starts_with(signature) {
+            return Some(mime.to_string());
+        }
+    }
+    if head.len() >= 12 && &head[0..4] == b"RIFF" && &head[8..12] == b"WEBP" {
+        return Some("image/webp".to_string());
+    }
+
+    let text = head.trim_ascii_start();
+    let prefix = &text[..text.len().min(14)];
+    if prefix.eq_ignore_ascii_case(b"<!doctype html") || prefix.starts_with(b"<html") {
+        return Some("text/html".to_string());
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mime_inference() {
+        let png = b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR";
+
+        assert_eq!(MimeInference::Disabled.infer("a.png", Some(png)), None);
+        assert_eq!(
+            MimeInference::Extension.infer("dir/a.png", None).as_deref(),
+            Some("image/png")
+        );
+        assert_eq!(MimeInference::Extension.infer("a", Some(png)), None);
+        assert_eq!(
+            MimeInference::Content.infer("a.txt", Some(png)).as_deref(),
+            Some("image/png")
+        );
+        assert_eq!(
+            MimeInference::Auto.infer("a.json", Some(png)).as_deref(),
+            Some("application/json")
+        );
+        assert_eq!(
+            MimeInference::Auto.infer("a", Some(png)).as_deref(),
+            Some("image/png")
+        );
+        assert_eq!(MimeInference::Auto.infer("a", Some(b"hello")), None);
+    }
+
+    #[test]
+    fn test_mime_from_content() {
+        assert_eq!(
+            mime_from_content(b"RIFF\0\0\0\0WEBPVP8 ").as_deref(),
+            Some("image/webp")
+        );
+        assert_eq!(
+            mime_from_content(b"\n  <!DOCTYPE html><html>").as_deref(),
+            Some("text/html")
+        );
+        assert_eq!(mime_from_content(b""), None);
+    }
+}
@@ -0,0 +1,92 @@
+//! Streaming newline-delimited JSON.
+//!
+//! See [`crate::ObjStoreExt::get_ndjson`] and [`crate::ObjStoreExt::put_ndjson`].
+
+use bytes::{Bytes, BytesMut};
+use futures::{Stream, StreamExt as _, TryStreamExt as _, stream, stream::BoxStream};
+
+use crate::{ObjStore, ObjStoreError, ObjectMeta, Put, Result, SizedValueStream};
+
+/// MIME type of newline-delimited JSON.
+pub(crate) const NDJSON_MIME_TYPE: &str = "application/x-ndjson";
+
+/// Stream of deserialized lines, as returned by [`crate::ObjStoreExt::get_ndjson`].
+pub type NdjsonStream<T> = BoxStream<'static, Result<T>>;
+
+pub(crate) async fn get_ndjson<S, T>(store: &S, key: &str) -> Result<Option<NdjsonStream<T>>>
+where
+    S: ObjStore + ?Sized,
+    T: serde::de::DeserializeOwned + Send + 'static,
+{
+    let Some(chunks) = store.get_stream(key).await? else {
+        return Ok(None);
+    };
+    let key = key.to_string();
+
+    // `scanned` is the length of the buffer prefix known to contain no
+    // newline, so long lines split across many chunks are only scanned once.
+    let lines = stream::try_unfold(
+        (chunks, BytesMut::new(), 0, false),
+        |(mut chunks, mut buffer, mut scanned, mut done)| async move {
+            loop {
+                if let Some(pos) = buffer[scanned..].iter().position(|b| *b == b'\n') {
+                    let line = buffer.split_to(scanned + pos + 1).freeze();
+                    return Ok(Some((line, (chunks, buffer, 0, done))));
+                }
+                scanned = buffer.len();
+                if done {
+                    // The last line may not be terminated.
+                    if buffer.is_empty() {
+                        return Ok(None);
+                    }
+                    let line = buffer.split().freeze();
+                    return Ok(Some((line, (chunks, buffer, 0, done))));
+                }
+                match chunks.try_next().await? {
+                    Some(chunk) => buffer.extend_from_slice(&chunk),
+                    None => done = true,
+                }
+            }
+        },
+    );
+
+    let items = lines
+        .try_filter(|line| std::future::ready(!line.trim_ascii().is_empty()))
+        .and_then(move |line| std::future::ready(deserialize_line(&key, &line)));
+    Ok(Some(items.boxed()))
+}
+
+fn deserialize_line<T>(key: &str, line: &Bytes) -> Result<T>
+where
+    T: serde::de::DeserializeOwned,
+{
+    let jd = &mut serde_json::Deserializer::from_slice(line);
+    serde_path_to_error::deserialize(jd).map_err(|source| ObjStoreError::ContentDeserialization {
+        key: key.to_string(),
+        format: "ndjson".to_string(),
+        source: Some(Box::new(source)),
+    })
+}
+
+pub(crate) async fn put_ndjson<S, T, I>(store: &S, key: &str, items: I) -> Result<ObjectMeta>
+where
+    S: ObjStore + ?Sized,
+    T: serde::Serialize,
+    I: Stream<Item = T> + Send + 'static,
+{
+    let lines = items
+        .map(|item| {
+            let mut line =
+                serde_json::to_vec(&item).map_err(|source| ObjStoreError::InvalidRequest {
+                    message: "could not serialize NDJSON line for put".to_string(),
+                    source: Some(source.into()),
+                })?;
+            line.push(b'\n');
+            Ok(Bytes::from(line))
+        })
+        .boxed();
+
+    let mut put = Put::new(key, SizedValueStream::new_without_size(lines));
+    put.mime_type = Some(NDJSON_MIME_TYPE.to_string());
+    store.send_put(put).await
+}
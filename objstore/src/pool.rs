@@ -0,0 +1,264 @@
+//! Buffer reuse for producers of byte streams.
+//!
+//! See [`BytesPool`].
+
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use bytes::BytesMut;
+use futures::{StreamExt as _, stream};
+
+use crate::ValueStream;
+
+/// Pool of reusable buffers, shared by the streams of a store.
+///
+/// Streams produced by the pool yield chunks of (at most) the configured
+/// chunk size, which are split off larger buffers. Once the consumer dropped
+/// the chunks of a buffer, its memory is reused for later chunks and streams
+/// instead of being allocated again. This reduces allocator churn on
+/// high-throughput transfers.
+///
+/// Cloning is cheap, clones share the same buffers.
+#[derive(Clone, Debug)]
+pub struct BytesPool {
+    chunk_size: usize,
+    max_buffers: usize,
+    buffers: Arc<Mutex<Vec<BytesMut>>>,
+}
+
+impl Default for BytesPool {
+    fn default() -> Self {
+        Self::new(Self::DEFAULT_CHUNK_SIZE)
+    }
+}
+
+impl BytesPool {
+    pub const DEFAULT_CHUNK_SIZE: usize = 64 * 1024;
+    pub const DEFAULT_MAX_BUFFERS: usize = 32;
+
+    /// Number of chunks that fit into one buffer.
+    const CHUNKS_PER_BUFFER: usize = 4;
+
+    /// Create a pool producing chunks of `chunk_size` bytes.
+    ///
+    /// A chunk size of `0` is ignored.
+    pub fn new(chunk_size: usize) -> Self {
+        Self {
+            chunk_size: if chunk_size > 0 {
+                chunk_size
+            } else {
+                Self::DEFAULT_CHUNK_SIZE
+            },
+            max_buffers: Self::DEFAULT_MAX_BUFFERS,
+            buffers: Arc::default(),
+        }
+    }
+
+    /// Target size of produced chunks.
+    pub fn chunk_size(&self) -> usize {
+        self.chunk_size
+    }
+
+    /// Maximum number of idle buffers kept for reuse.
+    pub fn max_buffers(&self) -> usize {
+        self.max_buffers
+    }
+
+    /// Set the maximum number of idle buffers kept for reuse.
+    pub fn with_max_buffers(mut self, max: usize) -> Self {
+        self.max_buffers = max;
+        self
+    }
+
+    /// Number of idle buffers in the pool.
+    pub fn idle(&self) -> usize {
+        self.lock().len()
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Vec<BytesMut>> {
+        self.buffers
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Take an empty buffer with room for at least one chunk.
+    pub fn get(&self) -> BytesMut {
+        let mut buffers = self.lock();
+        while let Some(mut buffer) = buffers.pop() {
+            // Fails while chunks split off the buffer are still alive.
+            if buffer.try_reclaim(self.chunk_size) {
+                return buffer;
+            }
+        }
+        drop(buffers);
+        BytesMut::with_capacity(self.chunk_size * Self::CHUNKS_PER_BUFFER)
+    }
+
+    /// Return a buffer to the pool.
+    pub fn put(&self, mut buffer: BytesMut) {
+        buffer.clear();
+        let mut buffers = self.lock();
+        if buffers.len() < self.max_buffers {
+            buffers.push(buffer);
+        }
+    }
+
+    /// Coalesce the chunks of `stream` into chunks of the target size.
+    ///
+    /// Chunks that are at least as large as the target size are passed through
+    /// without copying.
+    pub fn rechunk(&self, stream: ValueStream) -> ValueStream {
+        let state = (stream, Pooled::new(self.clone()));
+        stream::try_unfold(state, |(mut stream, mut buffer)| async move {
+            let chunk_size = buffer.pool.chunk_size;
+            loop {
+                if buffer.len() >= chunk_size {
+                    let chunk = buffer.split_to(chunk_size).freeze();
+                    return Ok(Some((chunk, (stream, buffer))));
+                }
+                match stream.next().await {
+                    Some(Ok(chunk)) if buffer.is_empty() && chunk.len() >= chunk_size => {
+                        return Ok(Some((chunk, (stream, buffer))));
+                    }
+                    Some(Ok(chunk)) => buffer.extend_from_slice(&chunk),
+                    Some(Err(err)) => return Err(err),
+                    None if buffer.is_empty() => return Ok(None),
+                    None => {
+                        let chunk = buffer.split().freeze();
+                        return Ok(Some((chunk, (stream, buffer))));
+                    }
+                }
+            }
+        })
+        .boxed()
+    }
+
+    /// Stream the contents of `reader` in chunks of the target size.
+    #[cfg(feature = "fs")]
+    pub fn reader_stream<R>(&self, reader: R) -> ValueStream
+    where
+        R: tokio::io::AsyncRead + Unpin + Send + 'static,
+    {
+        use bytes::BufMut as _;
+        use tokio::io::AsyncReadExt as _;
+
+        let state = (reader, Pooled::new(self.clone()));
+        stream::try_unfold(state, |(mut reader, mut buffer)| async move {
+            let chunk_size = buffer.pool.chunk_size;
+            if buffer.capacity() - buffer.len() < chunk_size {
+                buffer.reserve(chunk_size);
+            }
+            let read = reader
+                .read_buf(&mut (&mut *buffer).limit(chunk_size))
+                .await
+                .map_err(|source| crate::ObjStoreError::Io {
+                    operation: crate::Operation::GetStream,
+                    source: Some(source.into()),
+                })?;
+            if read == 0 {
+                return Ok(None);
+            }
+            let chunk = buffer.split().freeze();
+            Ok(Some((chunk, (reader, buffer))))
+        })
+        .boxed()
+    }
+}
+
+/// A buffer that is returned to its pool when dropped.
+struct Pooled {
+    pool: BytesPool,
+    buffer: BytesMut,
+}
+
+impl Pooled {
+    fn new(pool: BytesPool) -> Self {
+        let buffer = pool.get();
+        Self { pool, buffer }
+    }
+}
+
+impl std::ops::Deref for Pooled {
+    type Target = BytesMut;
+
+    fn deref(&self) -> &BytesMut {
+        &self.buffer
+    }
+}
+
+impl std::ops::DerefMut for Pooled {
+    fn deref_mut(&mut self) -> &mut BytesMut {
+        &mut self.buffer
+    }
+}
+
+impl Drop for Pooled {
+    fn drop(&mut self) {
+        self.pool.put(std::mem::take(&mut self.buffer));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bytes::Bytes;
+    use futures::TryStreamExt as _;
+
+    use super::*;
+
+    fn chunks(sizes: &[usize]) -> ValueStream {
+        let chunks = sizes
+            .iter()
+            .map(|size| Ok(Bytes::from(vec![b'x'; *size])))
+            .collect::<Vec<_>>();
+        stream::iter(chunks).boxed()
+    }
+
+    #[tokio::test]
+    async fn test_rechunk() {
+        let pool = BytesPool::new(4);
+        for (input, expected) in [
+            (&[1, 2, 3, 8, 1][..], &[4, 4, 4, 3][..]),
+            (&[8, 1, 1], &[8, 2]),
+            (&[], &[]),
+        ] {
+            let out = pool
+                .rechunk(chunks(input))
+                .try_collect::<Vec<_>>()
+                .await
+                .unwrap();
+            let sizes = out.iter().map(Bytes::len).collect::<Vec<_>>();
+            assert_eq!(sizes, expected, "{input:?}");
+        }
+        assert_eq!(pool.idle(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_buffers_are_reused() {
+        let pool = BytesPool::new(4);
+        let out = pool
+            .rechunk(chunks(&[4, 2, 2]))
+            .try_collect::<Vec<_>>()
+            .await
+            .unwrap();
+        let start = out[1].as_ptr() as usize;
+        drop(out);
+
+        let buffer = pool.get();
+        assert_eq!(pool.idle(), 0);
+        let ptr = buffer.as_ptr() as usize;
+        assert!((start..start + 4 * BytesPool::CHUNKS_PER_BUFFER).contains(&ptr));
+    }
+
+    #[cfg(feature = "fs")]
+    #[tokio::test]
+    async fn test_reader_stream() {
+        let data = (0..=255u8).cycle().take(1000).collect::<Vec<_>>();
+        let out = BytesPool::new(300)
+            .reader_stream(std::io::Cursor::new(data.clone()))
+            .try_collect::<Vec<_>>()
+            .await
+            .unwrap();
+        let sizes = out.iter().map(Bytes::len).collect::<Vec<_>>();
+        assert_eq!(sizes, [300, 300, 300, 100]);
+        assert_eq!(out.concat(), data);
+    }
+}
@@ -0,0 +1,340 @@
+//! Copying and moving all objects below a prefix.
+//!
+//! See [`crate::ObjStoreExt::copy_prefix`] and [`crate::ObjStoreExt::move_prefix`],
+//! and [`crate::ObjStoreExt::copy_prefix_to`] and [`crate::ObjStoreExt::move_prefix_to`]
+//! for transfers between stores.
+
+use std::sync::Arc;
+
+use futures::{StreamExt as _, TryStreamExt as _, stream};
+
+use crate::{Copy, ListArgs, ObjStore, ObjStoreError, Put, Result, SizedValueStream};
+
+/// How prefix operations handle failures of individual objects.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum FailurePolicy {
+    /// Stop at the first failure and undo the changes made so far.
+    ///
+    /// Copied objects are deleted again, unless the destination key already
+    /// existed before the copy. Such objects stay overwritten, so they are not
+    /// lost. For moves, source objects are only deleted once all copies
+    /// succeeded.
+    #[default]
+    AllOrNothing,
+    /// Continue after failures, and report them in [`PrefixCopyReport::failed`].
+    ///
+    /// For moves, only successfully copied source objects are deleted.
+    BestEffort,
+}
+
+/// Progress of a prefix copy or move, passed to the
+/// [progress callback](PrefixCopyOptions::with_progress).
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub struct PrefixCopyProgress {
+    /// Key of the source object that was just processed.
+    pub key: String,
+    /// Number of objects copied so far.
+    pub copied: u64,
+    /// Number of objects that failed so far.
+    pub failed: u64,
+}
+
+type ProgressFn = Arc<dyn Fn(&PrefixCopyProgress) + Send + Sync>;
+
+/// Options for [`crate::ObjStoreExt::copy_prefix`],
+/// [`crate::ObjStoreExt::move_prefix`] and their cross-store variants.
+#[derive(Clone)]
+pub struct PrefixCopyOptions {
+    concurrency: usize,
+    policy: FailurePolicy,
+    progress: Option<ProgressFn>,
+}
+
+impl Default for PrefixCopyOptions {
+    fn default() -> Self {
+        Self {
+            concurrency: Self::DEFAULT_CONCURRENCY,
+            policy: FailurePolicy::default(),
+            progress: None,
+        }
+    }
+}
+
+impl std::fmt::Debug for PrefixCopyOptions {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PrefixCopyOptions")
+            .field("concurrency", &self.concurrency)
+            .field("policy", &self.policy)
+            .field("progress", &self.progress.is_some())
+            .finish()
+    }
+}
+
+impl PrefixCopyOptions {
+    pub const DEFAULT_CONCURRENCY: usize = 8;
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Maximum number of concurrent copy requests (or transfers between stores).
+    pub fn concurrency(&self) -> usize {
+        self.concurrency
+    }
+
+    /// Set the maximum number of concurrent copy requests. Values of `0` are ignored.
+    pub fn with_concurrency(mut self, concurrency: usize) -> Self {
+        if concurrency > 0 {
+            self.concurrency = concurrency;
+        }
+        self
+    }
+
+    pub fn policy(&self) -> FailurePolicy {
+        self.policy
+    }
+
+    pub fn with_policy(mut self, policy: FailurePolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Set a callback that is invoked after each object was processed.
+    pub fn with_progress(
+        mut self,
+        progress: impl Fn(&PrefixCopyProgress) + Send + Sync + 'static,
+    ) -> Self {
+        self.progress = Some(Arc::new(progress));
+        self
+    }
+}
+
+/// Result of a prefix copy or move.
+#[derive(Debug, Default)]
+#[non_exhaustive]
+pub struct PrefixCopyReport {
+    /// Number of copied (or moved) objects.
+    pub copied: u64,
+    /// Source keys that could not be copied or deleted, with the error.
+    ///
+    /// Always empty with [`FailurePolicy::AllOrNothing`].
+    pub failed: Vec<(String, ObjStoreError)>,
+}
+
+pub(crate) async fn copy_prefix<S>(
+    store: &S,
+    src_prefix: &str,
+    dest_prefix: &str,
+    options: PrefixCopyOptions,
+    delete_source: bool,
+) -> Result<PrefixCopyReport>
+where
+    S: ObjStore + ?Sized,
+{
+    copy_prefix_between::<S, S>(store, None, src_prefix, dest_prefix, options, delete_source).await
+}
+
+pub(crate) async fn copy_prefix_to<S, D>(
+    store: &S,
+    dest: &D,
+    src_prefix: &str,
+    dest_prefix: &str,
+    options: PrefixCopyOptions,
+    delete_source: bool,
+) -> Result<PrefixCopyReport>
+where
+    S: ObjStore + ?Sized,
+    D: ObjStore + ?Sized,
+{
+    copy_prefix_between(
+        store,
+        Some(dest),
+        src_prefix,
+        dest_prefix,
+        options,
+        delete_source,
+    )
+    .await
+}
+
+/// Copy a prefix within `store`, or to `dest` if set.
+async fn copy_prefix_between<S, D>(
+    store: &S,
+    dest: Option<&D>,
+    src_prefix: &str,
+    dest_prefix: &str,
+    options: PrefixCopyOptions,
+    delete_source: bool,
+) -> Result<PrefixCopyReport>
+where
+    S: ObjStore + ?Sized,
+    D: ObjStore + ?Sized,
+{
+    let overlaps = dest_prefix.starts_with(src_prefix) || src_prefix.starts_with(dest_prefix);
+    if dest.is_none() && overlaps {
+        return Err(ObjStoreError::InvalidRequest {
+            message: format!(
+                "source prefix '{src_prefix}' and destination prefix '{dest_prefix}' must not overlap"
+            ),
+            source: None,
+        });
+    }
+
+    let policy = options.policy;
+    let mut copies = store
+        .list_keys_items_stream(ListArgs::new().with_prefix(src_prefix))
+        .map_ok(|src_key| async move {
+            let suffix = src_key.strip_prefix(src_prefix).unwrap_or(&src_key);
+            let dest_key = format!("{dest_prefix}{suffix}");
+            let res = copy_object(store, dest, policy, &src_key, &dest_key).await;
+            Ok::<_, ObjStoreError>((src_key, dest_key, res))
+        })
+        .try_buffer_unordered(options.concurrency);
+
+    let mut report = PrefixCopyReport::default();
+    let mut copied = Vec::new();
+    let mut failure = None;
+    while let Some(next) = copies.next().await {
+        // Listing errors abort regardless of the policy.
+        let (src_key, dest_key, res) = match next {
+            Ok(item) => item,
+            Err(err) => {
+                failure = Some(err);
+                break;
+            }
+        };
+        match res {
+            Ok(created) => {
+                report.copied += 1;
+                copied.push((src_key.clone(), dest_key, created));
+            }
+            Err(err) if options.policy == FailurePolicy::BestEffort => {
+                report.failed.push((src_key.clone(), err));
+            }
+            Err(err) => {
+                failure = Some(err);
+                break;
+            }
+        }
+        if let Some(progress) = &options.progress {
+            progress(&PrefixCopyProgress {
+                key: src_key,
+                copied: report.copied,
+                failed: report.failed.len() as u64,
+            });
+        }
+    }
+    drop(copies);
+
+    if let Some(err) = failure {
+        if options.policy == FailurePolicy::AllOrNothing {
+            // Best-effort rollback, the original error is more relevant.
+            let dest_keys = copied
+                .into_iter()
+                .filter(|(_, _, created)| *created)
+                .map(|(_, dest_key, _)| dest_key);
+            match dest {
+                Some(dest) => delete_keys(dest, dest_keys, options.concurrency).await,
+                None => delete_keys(store, dest_keys, options.concurrency).await,
+            };
+        }
+        return Err(err);
+    }
+
+    if delete_source {
+        let src_keys = copied.into_iter().map(|(src_key, _, _)| src_key);
+        for (key, res) in delete_keys(store, src_keys, options.concurrency).await {
+            match res {
+                Ok(()) => {}
+                Err(err) if options.policy == FailurePolicy::BestEffort => {
+                    report.copied -= 1;
+                    report.failed.push((key, err));
+                }
+                Err(err) => return Err(err),
+            }
+        }
+    }
+
+    Ok(report)
+}
+
+/// Copy a single object within `store`, or to `dest` if set.
+///
+/// Returns whether the destination object was newly created. This is only
+/// checked for [`FailurePolicy::AllOrNothing`], which must not roll back
+/// objects that existed before.
+async fn copy_object<S, D>(
+    store: &S,
+    dest: Option<&D>,
+    policy: FailurePolicy,
+    src_key: &str,
+    dest_key: &str,
+) -> Result<bool>
+where
+    S: ObjStore + ?Sized,
+    D: ObjStore + ?Sized,
+{
+    let existed = match (policy, dest) {
+        (FailurePolicy::BestEffort, _) => false,
+        (FailurePolicy::AllOrNothing, Some(dest)) => dest.exists(dest_key).await?,
+        (FailurePolicy::AllOrNothing, None) => store.exists(dest_key).await?,
+    };
+    match dest {
+        Some(dest) => transfer_object(store, dest, src_key, dest_key).await?,
+        None => {
+            store.send_copy(Copy::new(src_key, dest_key)).await?;
+        }
+    }
+    Ok(!existed)
+}
+
+/// Stream a single object from `src` to `dest`, keeping its metadata.
+pub(crate) async fn transfer_object<S, D>(
+    src: &S,
+    dest: &D,
+    src_key: &str,
+    dest_key: &str,
+) -> Result<()>
+where
+    S: ObjStore + ?Sized,
+    D: ObjStore + ?Sized,
+{
+    let (meta, stream) = src
+        .get_stream_with_meta(src_key)
+        .await?
+        .ok_or_else(|| ObjStoreError::object_not_found(src_key))?;
+
+    let stream = match meta.size {
+        Some(size) => SizedValueStream::new(stream, size),
+        None => SizedValueStream::new_without_size(stream),
+    };
+    let mut put = Put::new(dest_key, stream);
+    put.mime_type = meta.mime_type;
+    put.cache_control = meta.cache_control;
+    put.content_disposition = meta.content_disposition;
+    put.content_encoding = meta.content_encoding;
+    put.expires_at = meta.expires_at;
+    put.modified_at = meta.updated_at;
+    dest.send_put(put).await?;
+    Ok(())
+}
+
+async fn delete_keys<S>(
+    store: &S,
+    keys: impl Iterator<Item = String>,
+    concurrency: usize,
+) -> Vec<(String, Result<()>)>
+where
+    S: ObjStore + ?Sized,
+{
+    stream::iter(keys)
+        .map(|key| async move {
+            let res = store.delete(&key).await;
+            (key, res)
+        })
+        .buffer_unordered(concurrency)
+        .collect()
+        .await
+}
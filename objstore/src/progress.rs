@@ -0,0 +1,60 @@
+//! Transfer progress reporting.
+//!
+//! See [`ProgressCallback`].
+
+use std::sync::Arc;
+
+use futures::StreamExt as _;
+
+use crate::ValueStream;
+
+/// Progress of a single transfer.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Progress {
+    /// Number of bytes transferred so far.
+    pub transferred: u64,
+    /// Total number of bytes, if known.
+    pub total: Option<u64>,
+}
+
+/// Callback that receives the [`Progress`] of a transfer.
+///
+/// Can be set on puts and copies ([`crate::ObjStoreExt::put`],
+/// [`crate::ObjStoreExt::copy`]) and on downloads
+/// ([`crate::ParallelDownloadOptions::with_progress`]).
+///
+/// Streamed transfers report progress after each chunk. Transfers of
+/// in-memory data and server-side copies report once on completion.
+#[derive(Clone)]
+pub struct ProgressCallback(Arc<dyn Fn(Progress) + Send + Sync>);
+
+impl ProgressCallback {
+    pub fn new(f: impl Fn(Progress) + Send + Sync + 'static) -> Self {
+        Self(Arc::new(f))
+    }
+
+    /// Invoke the callback.
+    pub fn report(&self, transferred: u64, total: Option<u64>) {
+        (self.0)(Progress { transferred, total })
+    }
+
+    /// Wrap a byte stream to report the progress of each chunk that passes through.
+    pub fn track(self, stream: ValueStream, total: Option<u64>) -> ValueStream {
+        stream
+            .scan(0u64, move |transferred, chunk| {
+                if let Ok(chunk) = &chunk {
+                    *transferred += chunk.len() as u64;
+                    self.report(*transferred, total);
+                }
+                std::future::ready(Some(chunk))
+            })
+            .boxed()
+    }
+}
+
+impl std::fmt::Debug for ProgressCallback {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("ProgressCallback")
+    }
+}
@@ -1,4 +1,4 @@
-use crate::{Result, store::DynObjStore};
+use crate::{HttpClientConfig, Result, store::DynObjStore};
 
 /// A provider/builder for an object store backend.
 ///
@@ -41,4 +41,20 @@ pub trait ObjStoreProvider: Send + Sync + std::fmt::Debug {
     /// * `memory://`
     /// * `s3://<access_key>:<secret_key>@<url>/bucket`
     fn build(&self, url: &url::Url) -> Result<DynObjStore>;
+
+    /// Build a new [`crate::ObjStore`] from a generic URI, with custom HTTP
+    /// client settings.
+    ///
+    /// Used by the [`crate::ObjStoreBuilder`] if
+    /// [`crate::ObjStoreBuilder::with_http_client`] was set.
+    /// Providers of HTTP based backends should override this. The default
+    /// implementation ignores `http` and calls [`Self::build`].
+    fn build_with_http_client(
+        &self,
+        url: &url::Url,
+        http: &HttpClientConfig,
+    ) -> Result<DynObjStore> {
+        let _ = http;
+        self.build(url)
+    }
 }
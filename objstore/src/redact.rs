@@ -0,0 +1,114 @@
+//! Removal of credentials from URLs, for logs and [`crate::ObjStore::safe_uri`].
+
+use url::Url;
+
+/// Replacement for redacted values.
+pub const REDACTED: &str = "REDACTED";
+
+/// Query parameter names (lowercase) that hold secrets.
+const SENSITIVE_PARAMS: &[&str] = &[
+    "key",
+    "access_key",
+    "secret_key",
+    "api_key",
+    "apikey",
+    "pass",
+    "sig",
+];
+
+/// Fragments of query parameter names (lowercase) that hold secrets.
+const SENSITIVE_PARAM_PARTS: &[&str] = &["token", "secret", "password", "signature", "credential"];
+
+pub(crate) fn is_sensitive_param(name: &str) -> bool {
+    let name = name.to_ascii_lowercase();
+    SENSITIVE_PARAMS.contains(&name.as_str())
+        || SENSITIVE_PARAM_PARTS.iter().any(|part| name.contains(part))
+}
+
+/// Return a copy of `url` without credentials.
+///
+/// The username and password are removed, and values of query parameters
+/// that usually hold secrets (tokens, passwords, signatures, ...) are replaced
+/// with [`REDACTED`]. Query values that are URLs have their credentials removed.
+pub fn redact_url(url: &Url) -> Url {
+    let mut url = url.clone();
+    // Both only fail for URLs that cannot have credentials.
+    let _ = url.set_username("");
+    let _ = url.set_password(None);
+
+    if url.query().is_some() {
+        let pairs = url
+            .query_pairs()
+            .map(|(name, value)| {
+                let value = if is_sensitive_param(&name) {
+                    REDACTED.into()
+                } else {
+                    redact_nested_url(value)
+                };
+                (name.into_owned(), value.into_owned())
+            })
+            .collect::<Vec<_>>();
+        url.query_pairs_mut().clear().extend_pairs(pairs);
+    }
+    url
+}
+
+/// Remove credentials from query values that are URLs themselves, like proxies.
+fn redact_nested_url(value: std::borrow::Cow<'_, str>) -> std::borrow::Cow<'_, str> {
+    match Url::parse(&value) {
+        Ok(url) if !url.username().is_empty() || url.password().is_some() => {
+            redact_url(&url).to_string().into()
+        }
+        _ => value,
+    }
+}
+
+/// Like [`redact_url`], for URIs that may not be valid URLs.
+///
+/// Invalid URIs are reduced to their scheme.
+pub fn redact_uri(uri: &str) -> String {
+    match Url::parse(uri) {
+        Ok(url) => redact_url(&url).to_string(),
+        Err(_) => match uri.split_once("://") {
+            Some((scheme, _)) => format!("{scheme}://{REDACTED}"),
+            None => REDACTED.to_string(),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_redact_url() {
+        let url = Url::parse(
+            "s3://AKID:s3cr3t@host:9000/bucket?style=path&token=t0k&X-Amz-Signature=abc&region=eu",
+        )
+        .unwrap();
+        let redacted = redact_url(&url).to_string();
+        assert_eq!(
+            redacted,
+            "s3://host:9000/bucket?style=path&token=REDACTED&X-Amz-Signature=REDACTED&region=eu"
+        );
+
+        let url = Url::parse("file:///tmp/store").unwrap();
+        assert_eq!(redact_url(&url), url);
+
+        let url =
+            Url::parse("s3://host/bucket?proxy=http%3A%2F%2Fuser%3Apw%40proxy%3A3128").unwrap();
+        let redacted = redact_url(&url);
+        let proxy = redacted.query_pairs().next().unwrap().1.into_owned();
+        assert_eq!(proxy, "http://proxy:3128/");
+    }
+
+    #[test]
+    fn test_redact_uri() {
+        assert_eq!(
+            redact_uri("ftp://user:pw@host/path?password=pw"),
+            "ftp://host/path?password=REDACTED"
+        );
+        assert_eq!(redact_uri("s3://user:pw@[bad/bucket"), "s3://REDACTED");
+        assert_eq!(redact_uri("not a url"), "REDACTED");
+    }
+}
@@ -0,0 +1,85 @@
+//! Per-request options.
+//!
+//! See [`RequestOptions`].
+
+#[cfg(feature = "timeout")]
+use std::time::Duration;
+
+use crate::{CancellationToken, Operation, Result};
+
+/// Options for a single request, accepted by the `*_opts` methods of
+/// [`crate::ObjStore`], like [`crate::ObjStore::get_opts`].
+///
+/// The default implementations of these methods apply the timeout and the
+/// cancellation token around the plain method (like
+/// [`crate::ObjStore::get`]).
+///
+/// Wrappers that do not override the `*_opts` methods apply the timeout and
+/// cancellation around their own implementation.
+#[derive(Clone, Debug, Default)]
+pub struct RequestOptions {
+    #[cfg(feature = "timeout")]
+    timeout: Option<Duration>,
+    cancellation: Option<CancellationToken>,
+}
+
+impl RequestOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Maximum duration of the request.
+    #[cfg(feature = "timeout")]
+    pub fn timeout(&self) -> Option<Duration> {
+        self.timeout
+    }
+
+    /// Fail with [`crate::ObjStoreError::Timeout`] if the request does not
+    /// complete within `timeout`.
+    ///
+    /// For streaming reads, this only covers opening the stream, not
+    /// consuming it.
+    #[cfg(feature = "timeout")]
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    pub fn cancellation(&self) -> Option<&CancellationToken> {
+        self.cancellation.as_ref()
+    }
+
+    /// Abort the request with [`crate::ObjStoreError::Cancelled`] once `token`
+    /// is cancelled.
+    pub fn with_cancellation(mut self, token: CancellationToken) -> Self {
+        self.cancellation = Some(token);
+        self
+    }
+
+    /// Run `fut`, applying the timeout and cancellation token.
+    ///
+    /// Used by the default implementations of the `*_opts` methods.
+    pub async fn run<T, F>(&self, operation: Operation, fut: F) -> Result<T>
+    where
+        F: Future<Output = Result<T>>,
+    {
+        let fut = async {
+            match &self.cancellation {
+                Some(token) => token.run_until_cancelled(operation, fut).await,
+                None => fut.await,
+            }
+        };
+        #[cfg(feature = "timeout")]
+        if let Some(timeout) = self.timeout {
+            return tokio::time::timeout(timeout, fut)
+                .await
+                .unwrap_or_else(|_| {
+                    Err(crate::ObjStoreError::Timeout {
+                        operation,
+                        source: Some(format!("no response within {timeout:?}").into()),
+                    })
+                });
+        }
+        fut.await
+    }
+}
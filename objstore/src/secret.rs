@@ -0,0 +1,230 @@
+//! Secret references in store URIs.
+//!
+//! See [`SecretResolver`].
+
+use std::borrow::Cow;
+
+use url::Url;
+
+use crate::{ObjStoreError, Result};
+
+/// Resolves secret references in store URIs, like `env:AWS_SECRET`.
+///
+/// Registered on an [`crate::ObjStoreBuilder`] with
+/// [`crate::ObjStoreBuilder::with_secret_resolver`]. Before a store is built,
+/// the username, password and credential query values (like `token` or
+/// `secret_key`) of its URI are checked for references of the form
+/// `<scheme>:<reference>`, and references with the scheme of a registered
+/// resolver are replaced with the resolved secret. Other query values are
+/// never resolved, so they can't be used to read arbitrary files or
+/// environment variables.
+/// Persisted URIs can so refer to credentials instead of embedding them:
+///
+/// ```text
+/// s3://AKIA...:env%3AAWS_SECRET_ACCESS_KEY@host/bucket?style=path&token=file%3A/run/secrets/token
+/// ```
+///
+/// [`EnvSecretResolver`] and [`FileSecretResolver`] are built in. Other
+/// sources, like the OS keychain, can be added by implementing this trait.
+pub trait SecretResolver: Send + Sync + std::fmt::Debug {
+    /// The reference scheme handled by this resolver, like `env`.
+    fn scheme(&self) -> &str;
+
+    /// Resolve a reference, without the scheme and `:` separator.
+    fn resolve(&self, reference: &str) -> Result<String>;
+}
+
+/// Resolves `env:<NAME>` references to the value of an environment variable.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct EnvSecretResolver;
+
+impl SecretResolver for EnvSecretResolver {
+    fn scheme(&self) -> &str {
+        "env"
+    }
+
+    fn resolve(&self, reference: &str) -> Result<String> {
+        std::env::var(reference).map_err(|source| ObjStoreError::InvalidConfig {
+            message: format!("could not read secret from environment variable '{reference}'"),
+            source: Some(source.into()),
+        })
+    }
+}
+
+/// Resolves `file:<path>` references to the contents of a file.
+///
+/// A trailing line break is removed, so files written by `echo` work.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct FileSecretResolver;
+
+impl SecretResolver for FileSecretResolver {
+    fn scheme(&self) -> &str {
+        "file"
+    }
+
+    fn resolve(&self, reference: &str) -> Result<String> {
+        let content =
+            std::fs::read_to_string(reference).map_err(|source| ObjStoreError::InvalidConfig {
+                message: format!("could not read secret from file '{reference}'"),
+                source: Some(source.into()),
+            })?;
+        let len = content.trim_end_matches(['\r', '\n']).len();
+        Ok(content[..len].to_string())
+    }
+}
+
+/// Replace all secret references in the credentials of `url`, including
+/// credential query parameters.
+///
+/// Returns `url` unchanged if it contains no references.
+pub(crate) fn resolve_url<'a>(
+    url: &'a Url,
+    resolvers: &[std::sync::Arc<dyn SecretResolver>],
+) -> Result<Cow<'a, Url>> {
+    if resolvers.is_empty() {
+        return Ok(Cow::Borrowed(url));
+    }
+
+    let resolve = |value: &str| -> Result<Option<String>> {
+        let Some((scheme, reference)) = value.split_once(':') else {
+            return Ok(None);
+        };
+        resolvers
+            .iter()
+            .find(|resolver| resolver.scheme() == scheme)
+            .map(|resolver| resolver.resolve(reference))
+            .transpose()
+    };
+    let decode = |value: &str| {
+        percent_encoding::percent_decode_str(value)
+            .decode_utf8_lossy()
+            .into_owned()
+    };
+    let invalid = |part: &str| ObjStoreError::InvalidConfig {
+        message: format!("could not set resolved secret as URL {part}"),
+        source: None,
+    };
+
+    let mut resolved = url.clone();
+    let mut changed = false;
+
+    if let Some(username) = resolve(&decode(url.username()))? {
+        resolved
+            .set_username(&username)
+            .map_err(|_| invalid("username"))?;
+        changed = true;
+    }
+    if let Some(password) = url.password()
+        && let Some(password) = resolve(&decode(password))?
+    {
+        resolved
+            .set_password(Some(&password))
+            .map_err(|_| invalid("password"))?;
+        changed = true;
+    }
+
+    if url.query().is_some() {
+        let mut pairs = Vec::new();
+        let mut query_changed = false;
+        for (name, value) in url.query_pairs() {
+            let secret = if crate::redact::is_sensitive_param(&name) {
+                resolve(&value)?
+            } else {
+                None
+            };
+            let value = match secret {
+                Some(secret) => {
+                    query_changed = true;
+                    secret
+                }
+                None => value.into_owned(),
+            };
+            pairs.push((name.into_owned(), value));
+        }
+        if query_changed {
+            resolved.query_pairs_mut().clear().extend_pairs(pairs);
+            changed = true;
+        }
+    }
+
+    Ok(if changed {
+        Cow::Owned(resolved)
+    } else {
+        Cow::Borrowed(url)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+
+    #[derive(Debug)]
+    struct Static;
+
+    impl SecretResolver for Static {
+        fn scheme(&self) -> &str {
+            "static"
+        }
+
+        fn resolve(&self, reference: &str) -> Result<String> {
+            Ok(format!("{reference}:s3cr3t/@"))
+        }
+    }
+
+    #[test]
+    fn test_resolve_url() {
+        let resolvers: Vec<Arc<dyn SecretResolver>> = vec![Arc::new(Static)];
+
+        let url = Url::parse(
+            "s3://static%3Akey:static%3Apw@host/bucket?style=path&token=static:t&path=static:p",
+        )
+        .unwrap();
+        let resolved = resolve_url(&url, &resolvers).unwrap();
+        assert_eq!(decode(resolved.username()), "key:s3cr3t/@", "{resolved}");
+        assert_eq!(decode(resolved.password().unwrap()), "pw:s3cr3t/@");
+        let query = resolved
+            .query_pairs()
+            .collect::<std::collections::HashMap<_, _>>();
+        assert_eq!(query["token"], "t:s3cr3t/@");
+        assert_eq!(query["style"], "path");
+        // Only credential parameters are resolved.
+        assert_eq!(query["path"], "static:p");
+
+        // URLs without references, and without resolvers, are unchanged.
+        let url = Url::parse("s3://key:pw@host/bucket?style=path").unwrap();
+        assert!(matches!(
+            resolve_url(&url, &resolvers),
+            Ok(Cow::Borrowed(_))
+        ));
+        let url = Url::parse("s3://key:env%3AX@host/bucket").unwrap();
+        assert!(matches!(resolve_url(&url, &[]), Ok(Cow::Borrowed(_))));
+    }
+
+    #[test]
+    fn test_env_and_file_resolvers() {
+        let path = std::env::var("PATH").unwrap();
+        assert_eq!(EnvSecretResolver.resolve("PATH").unwrap(), path);
+        let err = EnvSecretResolver
+            .resolve("OBJSTORE_TEST_MISSING_SECRET")
+            .unwrap_err();
+        assert!(matches!(err, ObjStoreError::InvalidConfig { .. }));
+
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("secret");
+        std::fs::write(&file, "s3cr3t\n").unwrap();
+        assert_eq!(
+            FileSecretResolver.resolve(file.to_str().unwrap()).unwrap(),
+            "s3cr3t"
+        );
+        assert!(FileSecretResolver.resolve("/nonexistent/secret").is_err());
+    }
+
+    fn decode(value: &str) -> String {
+        percent_encoding::percent_decode_str(value)
+            .decode_utf8()
+            .unwrap()
+            .into_owned()
+    }
+}
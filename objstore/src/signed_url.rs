@@ -0,0 +1,291 @@
+//! HMAC-signed download URLs for backends without native presigned URLs.
+//!
+//! See [`UrlSigner`].
+
+use std::{sync::Arc, time::Duration};
+
+use sha2::{Digest as _, Sha256};
+use time::OffsetDateTime;
+use url::Url;
+
+use crate::{DownloadUrlArgs, ObjStoreError, Operation, Resource, Result};
+
+const EXPIRES_PARAM: &str = "expires";
+const SIGNATURE_PARAM: &str = "signature";
+
+/// Signs and verifies download URLs with HMAC-SHA256.
+///
+/// Lets backends without presigned URLs (filesystem, memory, logfs) return
+/// links from [`crate::ObjStore::generate_download_url`], which are served by
+/// a gateway that holds the same secret (see the `objstore_gateway` crate).
+///
+/// URLs have the form `{base_url}/{key}?expires={unix_ts}&signature={hex}`,
+/// plus the requested response overrides (`response-content-type`, ...),
+/// which are covered by the signature.
+#[derive(Clone)]
+pub struct UrlSigner {
+    base_url: Url,
+    secret: Arc<[u8]>,
+}
+
+impl std::fmt::Debug for UrlSigner {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("UrlSigner")
+            .field("base_url", &self.base_url)
+            .finish_non_exhaustive()
+    }
+}
+
+impl UrlSigner {
+    /// Create a signer for URLs below `base_url`.
+    ///
+    /// Fails if the base URL can not hold a path, or the secret is empty.
+    pub fn new(base_url: Url, secret: impl AsRef<[u8]>) -> Result<Self> {
+        if base_url.cannot_be_a_base() {
+            return Err(ObjStoreError::InvalidConfig {
+                message: format!("invalid base URL for signed URLs: '{base_url}'"),
+                source: None,
+            });
+        }
+        if secret.as_ref().is_empty() {
+            return Err(ObjStoreError::InvalidConfig {
+                message: "secret for signed URLs must not be empty".to_string(),
+                source: None,
+            });
+        }
+        Ok(Self {
+            base_url,
+            secret: secret.as_ref().into(),
+        })
+    }
+
+    pub fn base_url(&self) -> &Url {
+        &self.base_url
+    }
+
+    /// Generate a signed URL for the given download.
+    pub fn sign(&self, args: &DownloadUrlArgs) -> Url {
+        let expires = (OffsetDateTime::now_utc() + args.valid_for).unix_timestamp();
+
+        let mut url = self.base_url.clone();
+        url.path_segments_mut()
+            .expect("base URL is validated in UrlSigner::new")
+            .pop_if_empty()
+            .extend(args.key.split('/'));
+
+        let overrides = overrides(args);
+        let signature = self.signature(&args.key, expires, &overrides);
+        let mut query = url.query_pairs_mut();
+        query.append_pair(EXPIRES_PARAM, &expires.to_string());
+        for (name, value) in overrides {
+            query.append_pair(name, value);
+        }
+        query.append_pair(SIGNATURE_PARAM, &signature);
+        drop(query);
+        url
+    }
+
+    /// Verify a signed URL.
+    ///
+    /// Only the path and query of `url` are checked, so URLs rebuilt from
+    /// incoming requests can be passed without knowing the public host.
+    ///
+    /// Returns the download arguments encoded in the URL, with
+    /// [`DownloadUrlArgs::valid_for`] set to the remaining validity.
+    pub fn verify(&self, url: &Url) -> Result<DownloadUrlArgs> {
+        let key = self.key_from_path(url)?;
+
+        let mut args = DownloadUrlArgs::new(key, Duration::ZERO);
+        let mut expires = None;
+        let mut signature = None;
+        for (name, value) in url.query_pairs() {
+            let value = value.into_owned();
+            match name.as_ref() {
+                EXPIRES_PARAM => expires = value.parse::<i64>().ok(),
+                SIGNATURE_PARAM => signature = Some(value),
+                "response-content-type" => args.response_content_type = Some(value),
+                "response-content-disposition" => args.response_content_disposition = Some(value),
+                "response-content-encoding" => args.response_content_encoding = Some(value),
+                "response-content-language" => args.response_content_language = Some(value),
+                "response-cache-control" => args.response_cache_control = Some(value),
+                _ => {}
+            }
+        }
+        let (Some(expires), Some(signature)) = (expires, signature) else {
+            return Err(ObjStoreError::InvalidRequest {
+                message: "signed URL is missing the expiry or signature".to_string(),
+                source: None,
+            });
+        };
+
+        let expected = self.signature(&args.key, expires, &overrides(&args));
+        if !constant_time_eq(expected.as_bytes(), signature.as_bytes()) {
+            return Err(denied(&args.key, "invalid signature"));
+        }
+
+        let remaining = expires - OffsetDateTime::now_utc().unix_timestamp();
+        if remaining <= 0 {
+            return Err(denied(&args.key, "signed URL has expired"));
+        }
+        args.valid_for = Duration::from_secs(remaining as u64);
+        Ok(args)
+    }
+
+    /// Extract the object key from the path below the base URL.
+    fn key_from_path(&self, url: &Url) -> Result<String> {
+        let invalid = || ObjStoreError::InvalidRequest {
+            message: format!("URL is not below the signed URL base: '{}'", url.path()),
+            source: None,
+        };
+
+        let base = self.base_url.path().trim_end_matches('/');
+        let path = url.path().strip_prefix(base).ok_or_else(invalid)?;
+        let path = path.strip_prefix('/').ok_or_else(invalid)?;
+        let key = path
+            .split('/')
+            .map(|segment| percent_encoding::percent_decode_str(segment).decode_utf8())
+            .collect::<std::result::Result<Vec<_>, _>>()
+            .map_err(|_| invalid())?
+            .join("/");
+        if key.is_empty() {
+            return Err(invalid());
+        }
+        Ok(key)
+    }
+
+    fn signature(&self, key: &str, expires: i64, overrides: &[(&str, &str)]) -> String {
+        let mut message = format!("{key}\n{expires}");
+        for (name, value) in overrides {
+            message.push_str(&format!("\n{name}={value}"));
+        }
+        hmac_sha256(&self.secret, message.as_bytes())
+            .iter()
+            .map(|byte| format!("{byte:02x}"))
+            .collect()
+    }
+}
+
+/// The response overrides of a download, in signing order.
+fn overrides(args: &DownloadUrlArgs) -> Vec<(&'static str, &str)> {
+    [
+        ("response-content-type", &args.response_content_type),
+        (
+            "response-content-disposition",
+            &args.response_content_disposition,
+        ),
+        ("response-content-encoding", &args.response_content_encoding),
+        ("response-content-language", &args.response_content_language),
+        ("response-cache-control", &args.response_cache_control),
+    ]
+    .into_iter()
+    .filter_map(|(name, value)| value.as_deref().map(|value| (name, value)))
+    .collect()
+}
+
+fn denied(key: &str, message: &str) -> ObjStoreError {
+    ObjStoreError::PermissionDenied {
+        operation: Operation::Get,
+        resource: Some(Resource::Object {
+            key: key.to_owned(),
+        }),
+        source: Some(message.into()),
+    }
+}
+
+/// HMAC-SHA256 as specified in RFC 2104.
+fn hmac_sha256(key: &[u8], message: &[u8]) -> [u8; 32] {
+    const BLOCK_SIZE: usize = 64;
+
+    let mut block = [0u8; BLOCK_SIZE];
+    if key.len() > BLOCK_SIZE {
+        block[..32].copy_from_slice(&Sha256::digest(key));
+    } else {
+        block[..key.len()].copy_from_slice(key);
+    }
+
+    let inner = Sha256::new()
+        .chain_update(block.map(|byte| byte ^ 0x36))
+        .chain_update(message)
+        .finalize();
+    Sha256::new()
+        .chain_update(block.map(|byte| byte ^ 0x5c))
+        .chain_update(inner)
+        .finalize()
+        .into()
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |acc, (a, b)| acc | (a ^ b)) == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn signer() -> UrlSigner {
+        UrlSigner::new("https://files.example.com/dl/".parse().unwrap(), "secret").unwrap()
+    }
+
+    #[test]
+    fn test_hmac_sha256_rfc4231() {
+        let mac = hmac_sha256(b"Jefe", b"what do ya want for nothing?");
+        let hex = mac.iter().map(|b| format!("{b:02x}")).collect::<String>();
+        assert_eq!(
+            hex,
+            "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+    }
+
+    #[test]
+    fn test_sign_and_verify() {
+        let signer = signer();
+        let mut args = DownloadUrlArgs::new("dir/a file.txt", Duration::from_secs(60));
+        args.response_content_disposition = Some("attachment".to_string());
+
+        let url = signer.sign(&args);
+        assert!(
+            url.as_str()
+                .starts_with("https://files.example.com/dl/dir/a%20file.txt?")
+        );
+
+        let verified = signer.verify(&url).unwrap();
+        assert_eq!(verified.key, "dir/a file.txt");
+        assert_eq!(
+            verified.response_content_disposition.as_deref(),
+            Some("attachment")
+        );
+        assert!(verified.valid_for <= Duration::from_secs(60));
+    }
+
+    #[test]
+    fn test_verify_rejects_tampering() {
+        let signer = signer();
+        let url = signer.sign(&DownloadUrlArgs::new("a", Duration::from_secs(60)));
+
+        let mut other_key = url.clone();
+        other_key.set_path("/dl/b");
+        assert!(matches!(
+            signer.verify(&other_key),
+            Err(ObjStoreError::PermissionDenied { .. })
+        ));
+
+        let mut extra_override = url.clone();
+        extra_override
+            .query_pairs_mut()
+            .append_pair("response-content-type", "text/html");
+        assert!(matches!(
+            signer.verify(&extra_override),
+            Err(ObjStoreError::PermissionDenied { .. })
+        ));
+
+        let other_secret =
+            UrlSigner::new("https://files.example.com/dl/".parse().unwrap(), "other").unwrap();
+        assert!(other_secret.verify(&url).is_err());
+
+        let expired = signer.sign(&DownloadUrlArgs::new("a", Duration::ZERO));
+        assert!(matches!(
+            signer.verify(&expired),
+            Err(ObjStoreError::PermissionDenied { .. })
+        ));
+    }
+}
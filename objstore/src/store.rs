@@ -1,13 +1,26 @@
-use std::sync::Arc;
+use std::{
+    ops::Range,
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+};
 
 use bytes::Bytes;
 
 use crate::{
-    Conditions, Copy, DataSource, DownloadUrlArgs, KeyPage, KeyStream, ListArgs, MetaStream,
-    ObjStoreError, ObjectMeta, ObjectMetaPage, Put, Result, SizedValueStream, UploadUrlArgs,
-    ValueStream,
+    CancellationToken, Conditions, Copy, CopyMetadata, DataSource, DeleteManyOptions,
+    DeleteManyStream, DownloadUrlArgs, EventStream, GetManyOptions, GetManyStream,
+    GetRangesOptions, Glob, HealthReport, KeyPage, KeyStream, ListArgs, ListPartitions, MetaStream,
+    MimeInference, NdjsonStream, ObjStoreError, ObjectKey, ObjectMeta, ObjectMetaPage,
+    ObjectOrPrefix, Operation, ParallelDownloadOptions, PrefixCopyOptions, PrefixCopyReport,
+    ProgressCallback, Put, PutResult, RequestOptions, Result, ScanCheckpoint, ScanStream,
+    SizedValueStream, SortOrder, TextDecoding, UploadUrlArgs, Usage, ValueStream,
+    key::utf8_key,
+    maintenance::{CleanupOptions, CleanupReport},
+    wrapper::prefix::PrefixObjStore,
 };
-use futures::{TryStreamExt as _, stream};
+use futures::{AsyncWrite, Stream, StreamExt as _, TryStreamExt as _, stream, stream::BoxStream};
+use time::OffsetDateTime;
 
 /// Abstraction for a generic key-value store.
 #[async_trait::async_trait]
@@ -34,12 +47,74 @@ pub trait ObjStore: Send + Sync + std::fmt::Debug {
     /// May perform upstream service requests to validate connectivity and credentials.
     async fn healthcheck(&self) -> Result<()>;
 
+    /// Check the health of the store, returning detailed diagnostics.
+    ///
+    /// Fails if [`Self::healthcheck`] fails. Problems that leave the store
+    /// partially usable are reported as [`HealthReport::warnings`].
+    ///
+    /// The default implementation times [`Self::healthcheck`] and probes
+    /// listing permissions.
+    async fn healthcheck_report(&self) -> Result<HealthReport> {
+        let start = std::time::Instant::now();
+        self.healthcheck().await?;
+        Ok(HealthReport::new(start.elapsed()).probe_list(self).await)
+    }
+
     /// Get metadata for a given key.
     async fn meta(&self, key: &str) -> Result<Option<ObjectMeta>>;
 
+    /// Check if an object exists for the given key.
+    ///
+    /// The default implementation uses [`Self::meta`].
+    /// Backends should override this if a cheaper check is available.
+    async fn exists(&self, key: &str) -> Result<bool> {
+        Ok(self.meta(key).await?.is_some())
+    }
+
     /// Get the value for a given key.
     async fn get(&self, key: &str) -> Result<Option<Bytes>>;
 
+    /// Variant of [`Self::meta`] for keys that may not be valid UTF-8.
+    ///
+    /// The key of the returned metadata is the display form of the key.
+    ///
+    /// The default implementation delegates to [`Self::meta`], and fails with
+    /// [`ObjStoreError::InvalidRequest`] for keys that are not valid UTF-8.
+    async fn meta_raw(&self, key: &ObjectKey) -> Result<Option<ObjectMeta>> {
+        self.meta(utf8_key(key)?).await
+    }
+
+    /// Variant of [`Self::get`] for keys that may not be valid UTF-8.
+    ///
+    /// See [`Self::meta_raw`].
+    async fn get_raw(&self, key: &ObjectKey) -> Result<Option<Bytes>> {
+        self.get(utf8_key(key)?).await
+    }
+
+    /// Variant of [`Self::delete`] for keys that may not be valid UTF-8.
+    ///
+    /// See [`Self::meta_raw`].
+    async fn delete_raw(&self, key: &ObjectKey) -> Result<()> {
+        self.delete(utf8_key(key)?).await
+    }
+
+    /// Variant of [`Self::send_put`] for keys that may not be valid UTF-8.
+    ///
+    /// See [`Self::meta_raw`].
+    async fn put_raw(&self, key: &ObjectKey, data: Bytes) -> Result<ObjectMeta> {
+        self.send_put(Put::new(utf8_key(key)?, data)).await
+    }
+
+    /// Variant of [`Self::list_all_keys`] for keys that may not be valid UTF-8.
+    ///
+    /// The default implementation delegates to [`Self::list_all_keys`], and
+    /// fails with [`ObjStoreError::InvalidRequest`] for prefixes that are not
+    /// valid UTF-8.
+    async fn list_all_keys_raw(&self, prefix: &ObjectKey) -> Result<Vec<ObjectKey>> {
+        let keys = self.list_all_keys(utf8_key(prefix)?).await?;
+        Ok(keys.into_iter().map(ObjectKey::from).collect())
+    }
+
     async fn get_stream(&self, key: &str) -> Result<Option<ValueStream>>;
 
     /// Get both the value and metadata for a given key.
@@ -47,6 +122,40 @@ pub trait ObjStore: Send + Sync + std::fmt::Debug {
 
     async fn get_stream_with_meta(&self, key: &str) -> Result<Option<(ObjectMeta, ValueStream)>>;
 
+    /// Get a byte range of the value for a given key.
+    ///
+    /// The end of the range is clamped to the object size, so the returned
+    /// value may be shorter than requested. A range starting at or after the
+    /// end of the object yields an empty value.
+    ///
+    /// NOTE: Must return [`ObjStoreError::Unsupported`] if the store does not
+    /// support range reads, which is what the default implementation does.
+    async fn get_range(&self, key: &str, range: Range<u64>) -> Result<Option<Bytes>> {
+        let _ = (key, range);
+        Err(ObjStoreError::unsupported(Operation::GetRange))
+    }
+
+    /// Fetch many objects concurrently.
+    ///
+    /// Yields a `(key, result)` pair for every requested key, in completion
+    /// order rather than request order. A failure for one key does not abort
+    /// the others.
+    ///
+    /// The default implementation issues concurrent [`Self::get`] calls.
+    /// Backends should override this if a cheaper method is available.
+    fn get_many<'a>(&'a self, keys: Vec<String>, options: GetManyOptions) -> GetManyStream<'a> {
+        let concurrency = options
+            .concurrency()
+            .unwrap_or(GetManyOptions::DEFAULT_CONCURRENCY);
+        stream::iter(keys)
+            .map(move |key| async move {
+                let res = self.get(&key).await;
+                (key, res)
+            })
+            .buffer_unordered(concurrency)
+            .boxed()
+    }
+
     /// Generate a download URL for a given key.
     ///
     /// NOTE: Must return `Ok(None)` if the store does not support download URLs!
@@ -59,8 +168,36 @@ pub trait ObjStore: Send + Sync + std::fmt::Debug {
     async fn generate_upload_url(&self, args: UploadUrlArgs) -> Result<Option<url::Url>>;
 
     /// Store a value under a given key.
+    ///
+    /// Existing objects are replaced, unless prevented by the
+    /// [`Put::conditions`].
     async fn send_put(&self, put: Put) -> Result<ObjectMeta>;
 
+    /// Like [`Self::send_put`], but also reports whether the put created a
+    /// new object or replaced an existing one.
+    ///
+    /// The default implementation checks for an existing object before the
+    /// put, so the result can be wrong if the key is written concurrently.
+    /// Backends that know atomically should override this.
+    async fn send_put_result(&self, put: Put) -> Result<PutResult> {
+        let existed = self.exists(&put.key).await?;
+        let meta = self.send_put(put).await?;
+        Ok(PutResult::new(meta, !existed))
+    }
+
+    /// Append data to the end of an object, creating it if it does not exist.
+    ///
+    /// The metadata of an existing object (content type, expiration, ...) is kept.
+    ///
+    /// The default implementation emulates appends by reading the whole
+    /// object and writing it back with an `if_match` condition on its etag,
+    /// retrying on conflicts. Objects without an etag are written back
+    /// unconditionally, so concurrent appends can be lost.
+    /// Backends that can append natively should override this.
+    async fn append(&self, key: &str, data: Bytes) -> Result<ObjectMeta> {
+        append_emulated(self, key, data).await
+    }
+
     /// Copy an existing object to a new key.
     ///
     /// May apply server-side copy optimizations and respects `Conditions`.
@@ -95,7 +232,14 @@ pub trait ObjStore: Send + Sync + std::fmt::Debug {
         let init = Some(args.clone());
         let page_stream = stream::try_unfold(init, move |state| async move {
             if let Some(args) = state {
-                let page = self.list_keys(args.clone()).await?;
+                let page = match args.cancellation() {
+                    Some(token) => {
+                        token
+                            .run_until_cancelled(Operation::ListKeys, self.list_keys(args.clone()))
+                            .await?
+                    }
+                    None => self.list_keys(args.clone()).await?,
+                };
                 let next = page
                     .next_cursor
                     .as_ref()
@@ -108,12 +252,39 @@ pub trait ObjStore: Send + Sync + std::fmt::Debug {
         Box::pin(page_stream)
     }
 
+    /// Streaming variant of [`Self::list_keys`] that yields individual keys
+    /// instead of pages.
+    ///
+    /// The next page is fetched while the keys of the current page are consumed.
+    fn list_keys_items_stream<'a>(&'a self, args: ListArgs) -> BoxStream<'a, Result<String>> {
+        PrefetchItems::new(self.list_keys_stream(args).map_ok(|page| page.items)).boxed()
+    }
+
     /// List metadata for a given key.
     ///
     /// The arguments allow for prefix filtering, pagination, and limiting
     /// the number of results.
+    ///
+    /// A requested [`SortOrder`] is only applied if [`Self::supports_sort`]
+    /// returns `true` for it.
     async fn list(&self, args: ListArgs) -> Result<ObjectMetaPage>;
 
+    /// Whether [`Self::list`] can natively list in the given order.
+    ///
+    /// Defaults to only supporting [`SortOrder::KeyAscending`].
+    fn supports_sort(&self, order: SortOrder) -> bool {
+        order == SortOrder::KeyAscending
+    }
+
+    /// Whether [`Self::send_put`] enforces the etag [`Conditions`] of a put
+    /// (`if_match` and `if_none_match`).
+    ///
+    /// Stores that return `false` may ignore them, so compare-and-swap
+    /// updates are only safe if this returns `true`.
+    fn supports_conditions(&self) -> bool {
+        false
+    }
+
     /// Streaming variant of [`Self::list`]: pages through [`Self::list`] and yields each metadata page (`ObjectMetaPage`).
     ///
     /// This default method repeatedly calls `list` to page through all results lazily.
@@ -127,7 +298,14 @@ pub trait ObjStore: Send + Sync + std::fmt::Debug {
             let store = store.clone();
             async move {
                 if let Some(args) = state {
-                    let page = store.list(args.clone()).await?;
+                    let page = match args.cancellation() {
+                        Some(token) => {
+                            token
+                                .run_until_cancelled(Operation::List, store.list(args.clone()))
+                                .await?
+                        }
+                        None => store.list(args.clone()).await?,
+                    };
                     let next = page
                         .next_cursor
                         .as_ref()
@@ -141,32 +319,194 @@ pub trait ObjStore: Send + Sync + std::fmt::Debug {
         Box::pin(page_stream)
     }
 
+    /// Streaming variant of [`Self::list`] that yields individual objects
+    /// instead of pages.
+    ///
+    /// The next page is fetched while the items of the current page are
+    /// consumed. At most one page is fetched ahead, so slow consumers do not
+    /// cause unbounded buffering.
+    fn list_items_stream<'a>(&'a self, args: ListArgs) -> BoxStream<'a, Result<ObjectMeta>> {
+        PrefetchItems::new(list_pages(self, args).map_ok(|page| page.items)).boxed()
+    }
+
+    /// Streaming variant of [`Self::list`] that yields objects and common
+    /// prefixes as [`ObjectOrPrefix`] entries, in key order within each page.
+    ///
+    /// Only yields prefixes if the arguments have a delimiter, see
+    /// [`ListArgs::with_delimiter`].
+    fn list_entries_stream<'a>(&'a self, args: ListArgs) -> BoxStream<'a, Result<ObjectOrPrefix>> {
+        PrefetchItems::new(list_pages(self, args).map_ok(ObjectMetaPage::into_entries)).boxed()
+    }
+
+    /// Compute the number of objects and their total size for a prefix.
+    ///
+    /// The default implementation pages through [`Self::list`] and aggregates
+    /// the results without buffering them.
+    /// Backends should override this if a cheaper method is available.
+    async fn usage(&self, prefix: &str) -> Result<Usage> {
+        let mut usage = Usage::default();
+        // Pages may be empty but still have a cursor, for example if all of
+        // their objects were filtered out, so all pages are followed.
+        let mut pages = list_pages(self, ListArgs::new().with_prefix(prefix));
+        while let Some(page) = pages.try_next().await? {
+            for meta in &page.items {
+                usage.objects += 1;
+                usage.bytes += meta.size.unwrap_or_default();
+            }
+        }
+        Ok(usage)
+    }
+
+    /// Watch for changes to objects with the given prefix.
+    ///
+    /// Returns a stream of [`crate::ObjectEvent`]s for changes made after the call.
+    /// Delivery is best-effort: depending on the backend, events may be
+    /// coalesced, and a single write can be reported more than once.
+    ///
+    /// NOTE: Must return [`ObjStoreError::Unsupported`] if the store does not
+    /// support change notifications.
+    /// See `PollingWatchObjStore` (behind the `watch` feature) for a fallback.
+    async fn watch(&self, prefix: &str) -> Result<EventStream> {
+        let _ = prefix;
+        Err(ObjStoreError::unsupported(Operation::Watch))
+    }
+
     /// Purge all keys in the store.
     async fn purge_all(&self) -> Result<()> {
         self.delete_prefix("").await
     }
 
-    /// Get a JSON value from the store.
-    async fn get_json<T: serde::de::DeserializeOwned>(&self, key: &str) -> Result<Option<T>>
-    where
-        Self: Sized,
-    {
-        match self.get(key).await {
-            Ok(Some(data)) => {
-                let jd = &mut serde_json::Deserializer::from_slice(&data);
-                let out = serde_path_to_error::deserialize(jd).map_err(|source| {
-                    ObjStoreError::ContentDeserialization {
-                        key: key.to_string(),
-                        format: "json".to_string(),
-                        source: Some(Box::new(source)),
-                    }
-                })?;
+    /// Remove leftovers of failed or abandoned operations.
+    ///
+    /// The default implementation removes stale objects below
+    /// [`CleanupOptions::trash_prefix`]. Backends extend it with their own
+    /// leftovers, like incomplete multipart uploads or temporary files.
+    async fn cleanup(&self, options: CleanupOptions) -> Result<CleanupReport> {
+        crate::maintenance::cleanup_trash(self, &options).await
+    }
 
-                Ok(Some(out))
-            }
-            Ok(None) => Ok(None),
-            Err(e) => Err(e),
-        }
+    // Variants of the operations above with per-request options.
+
+    /// Variant of [`Self::meta`] with per-request [`RequestOptions`].
+    ///
+    /// The default implementations of all `*_opts` methods apply the timeout
+    /// and [`RequestOptions::cancellation`] around the plain method.
+    async fn meta_opts(&self, key: &str, options: RequestOptions) -> Result<Option<ObjectMeta>> {
+        options.run(Operation::Meta, self.meta(key)).await
+    }
+
+    /// Variant of [`Self::get`] with per-request [`RequestOptions`].
+    ///
+    /// See [`Self::meta_opts`].
+    async fn get_opts(&self, key: &str, options: RequestOptions) -> Result<Option<Bytes>> {
+        options.run(Operation::Get, self.get(key)).await
+    }
+
+    /// Variant of [`Self::get_stream`] with per-request [`RequestOptions`].
+    ///
+    /// See [`Self::meta_opts`].
+    async fn get_stream_opts(
+        &self,
+        key: &str,
+        options: RequestOptions,
+    ) -> Result<Option<ValueStream>> {
+        options
+            .run(Operation::GetStream, self.get_stream(key))
+            .await
+    }
+
+    /// Variant of [`Self::get_with_meta`] with per-request [`RequestOptions`].
+    ///
+    /// See [`Self::meta_opts`].
+    async fn get_with_meta_opts(
+        &self,
+        key: &str,
+        options: RequestOptions,
+    ) -> Result<Option<(Bytes, ObjectMeta)>> {
+        options.run(Operation::Get, self.get_with_meta(key)).await
+    }
+
+    /// Variant of [`Self::get_stream_with_meta`] with per-request [`RequestOptions`].
+    ///
+    /// See [`Self::meta_opts`].
+    async fn get_stream_with_meta_opts(
+        &self,
+        key: &str,
+        options: RequestOptions,
+    ) -> Result<Option<(ObjectMeta, ValueStream)>> {
+        options
+            .run(Operation::GetStream, self.get_stream_with_meta(key))
+            .await
+    }
+
+    /// Variant of [`Self::get_range`] with per-request [`RequestOptions`].
+    ///
+    /// See [`Self::meta_opts`].
+    async fn get_range_opts(
+        &self,
+        key: &str,
+        range: Range<u64>,
+        options: RequestOptions,
+    ) -> Result<Option<Bytes>> {
+        options
+            .run(Operation::GetRange, self.get_range(key, range))
+            .await
+    }
+
+    /// Variant of [`Self::send_put`] with per-request [`RequestOptions`].
+    ///
+    /// See [`Self::meta_opts`].
+    async fn send_put_opts(&self, put: Put, options: RequestOptions) -> Result<ObjectMeta> {
+        options.run(Operation::Put, self.send_put(put)).await
+    }
+
+    /// Variant of [`Self::append`] with per-request [`RequestOptions`].
+    ///
+    /// See [`Self::meta_opts`].
+    async fn append_opts(
+        &self,
+        key: &str,
+        data: Bytes,
+        options: RequestOptions,
+    ) -> Result<ObjectMeta> {
+        options.run(Operation::Append, self.append(key, data)).await
+    }
+
+    /// Variant of [`Self::send_copy`] with per-request [`RequestOptions`].
+    ///
+    /// See [`Self::meta_opts`].
+    async fn send_copy_opts(&self, copy: Copy, options: RequestOptions) -> Result<ObjectMeta> {
+        options.run(Operation::Copy, self.send_copy(copy)).await
+    }
+
+    /// Variant of [`Self::delete`] with per-request [`RequestOptions`].
+    ///
+    /// See [`Self::meta_opts`].
+    async fn delete_opts(&self, key: &str, options: RequestOptions) -> Result<()> {
+        options.run(Operation::Delete, self.delete(key)).await
+    }
+
+    /// Variant of [`Self::delete_prefix`] with per-request [`RequestOptions`].
+    ///
+    /// See [`Self::meta_opts`].
+    async fn delete_prefix_opts(&self, prefix: &str, options: RequestOptions) -> Result<()> {
+        options
+            .run(Operation::DeletePrefix, self.delete_prefix(prefix))
+            .await
+    }
+
+    /// Variant of [`Self::list`] with per-request [`RequestOptions`].
+    ///
+    /// See [`Self::meta_opts`].
+    async fn list_opts(&self, args: ListArgs, options: RequestOptions) -> Result<ObjectMetaPage> {
+        options.run(Operation::List, self.list(args)).await
+    }
+
+    /// Variant of [`Self::list_keys`] with per-request [`RequestOptions`].
+    ///
+    /// See [`Self::meta_opts`].
+    async fn list_keys_opts(&self, args: ListArgs, options: RequestOptions) -> Result<KeyPage> {
+        options.run(Operation::ListKeys, self.list_keys(args)).await
     }
 }
 
@@ -184,10 +524,18 @@ impl<K: ObjStore> ObjStore for Arc<K> {
         self.as_ref().healthcheck().await
     }
 
+    async fn healthcheck_report(&self) -> Result<HealthReport> {
+        self.as_ref().healthcheck_report().await
+    }
+
     async fn meta(&self, key: &str) -> Result<Option<ObjectMeta>> {
         self.as_ref().meta(key).await
     }
 
+    async fn exists(&self, key: &str) -> Result<bool> {
+        self.as_ref().exists(key).await
+    }
+
     async fn get(&self, key: &str) -> Result<Option<Bytes>> {
         self.as_ref().get(key).await
     }
@@ -204,6 +552,34 @@ impl<K: ObjStore> ObjStore for Arc<K> {
         self.as_ref().get_stream_with_meta(key).await
     }
 
+    async fn get_range(&self, key: &str, range: Range<u64>) -> Result<Option<Bytes>> {
+        self.as_ref().get_range(key, range).await
+    }
+
+    async fn meta_raw(&self, key: &ObjectKey) -> Result<Option<ObjectMeta>> {
+        self.as_ref().meta_raw(key).await
+    }
+
+    async fn get_raw(&self, key: &ObjectKey) -> Result<Option<Bytes>> {
+        self.as_ref().get_raw(key).await
+    }
+
+    async fn delete_raw(&self, key: &ObjectKey) -> Result<()> {
+        self.as_ref().delete_raw(key).await
+    }
+
+    async fn put_raw(&self, key: &ObjectKey, data: Bytes) -> Result<ObjectMeta> {
+        self.as_ref().put_raw(key, data).await
+    }
+
+    async fn list_all_keys_raw(&self, prefix: &ObjectKey) -> Result<Vec<ObjectKey>> {
+        self.as_ref().list_all_keys_raw(prefix).await
+    }
+
+    fn get_many<'a>(&'a self, keys: Vec<String>, options: GetManyOptions) -> GetManyStream<'a> {
+        self.as_ref().get_many(keys, options)
+    }
+
     async fn generate_download_url(&self, args: DownloadUrlArgs) -> Result<Option<url::Url>> {
         self.as_ref().generate_download_url(args).await
     }
@@ -215,6 +591,14 @@ impl<K: ObjStore> ObjStore for Arc<K> {
     async fn send_put(&self, put: Put) -> Result<ObjectMeta> {
         self.as_ref().send_put(put).await
     }
+
+    async fn send_put_result(&self, put: Put) -> Result<PutResult> {
+        self.as_ref().send_put_result(put).await
+    }
+
+    async fn append(&self, key: &str, data: Bytes) -> Result<ObjectMeta> {
+        self.as_ref().append(key, data).await
+    }
     async fn send_copy(&self, copy: Copy) -> Result<ObjectMeta> {
         self.as_ref().send_copy(copy).await
     }
@@ -227,6 +611,26 @@ impl<K: ObjStore> ObjStore for Arc<K> {
         self.as_ref().delete_prefix(prefix).await
     }
 
+    async fn usage(&self, prefix: &str) -> Result<Usage> {
+        self.as_ref().usage(prefix).await
+    }
+
+    async fn cleanup(&self, options: CleanupOptions) -> Result<CleanupReport> {
+        self.as_ref().cleanup(options).await
+    }
+
+    fn supports_sort(&self, order: SortOrder) -> bool {
+        self.as_ref().supports_sort(order)
+    }
+
+    fn supports_conditions(&self) -> bool {
+        self.as_ref().supports_conditions()
+    }
+
+    async fn watch(&self, prefix: &str) -> Result<EventStream> {
+        self.as_ref().watch(prefix).await
+    }
+
     async fn list(&self, args: ListArgs) -> Result<ObjectMetaPage> {
         self.as_ref().list(args).await
     }
@@ -234,6 +638,80 @@ impl<K: ObjStore> ObjStore for Arc<K> {
     async fn list_keys(&self, args: ListArgs) -> Result<KeyPage> {
         self.as_ref().list_keys(args).await
     }
+
+    async fn meta_opts(&self, key: &str, options: RequestOptions) -> Result<Option<ObjectMeta>> {
+        self.as_ref().meta_opts(key, options).await
+    }
+
+    async fn get_opts(&self, key: &str, options: RequestOptions) -> Result<Option<Bytes>> {
+        self.as_ref().get_opts(key, options).await
+    }
+
+    async fn get_stream_opts(
+        &self,
+        key: &str,
+        options: RequestOptions,
+    ) -> Result<Option<ValueStream>> {
+        self.as_ref().get_stream_opts(key, options).await
+    }
+
+    async fn get_with_meta_opts(
+        &self,
+        key: &str,
+        options: RequestOptions,
+    ) -> Result<Option<(Bytes, ObjectMeta)>> {
+        self.as_ref().get_with_meta_opts(key, options).await
+    }
+
+    async fn get_stream_with_meta_opts(
+        &self,
+        key: &str,
+        options: RequestOptions,
+    ) -> Result<Option<(ObjectMeta, ValueStream)>> {
+        self.as_ref().get_stream_with_meta_opts(key, options).await
+    }
+
+    async fn get_range_opts(
+        &self,
+        key: &str,
+        range: Range<u64>,
+        options: RequestOptions,
+    ) -> Result<Option<Bytes>> {
+        self.as_ref().get_range_opts(key, range, options).await
+    }
+
+    async fn send_put_opts(&self, put: Put, options: RequestOptions) -> Result<ObjectMeta> {
+        self.as_ref().send_put_opts(put, options).await
+    }
+
+    async fn append_opts(
+        &self,
+        key: &str,
+        data: Bytes,
+        options: RequestOptions,
+    ) -> Result<ObjectMeta> {
+        self.as_ref().append_opts(key, data, options).await
+    }
+
+    async fn send_copy_opts(&self, copy: Copy, options: RequestOptions) -> Result<ObjectMeta> {
+        self.as_ref().send_copy_opts(copy, options).await
+    }
+
+    async fn delete_opts(&self, key: &str, options: RequestOptions) -> Result<()> {
+        self.as_ref().delete_opts(key, options).await
+    }
+
+    async fn delete_prefix_opts(&self, prefix: &str, options: RequestOptions) -> Result<()> {
+        self.as_ref().delete_prefix_opts(prefix, options).await
+    }
+
+    async fn list_opts(&self, args: ListArgs, options: RequestOptions) -> Result<ObjectMetaPage> {
+        self.as_ref().list_opts(args, options).await
+    }
+
+    async fn list_keys_opts(&self, args: ListArgs, options: RequestOptions) -> Result<KeyPage> {
+        self.as_ref().list_keys_opts(args, options).await
+    }
 }
 
 pub type DynObjStore = Arc<dyn ObjStore>;
@@ -252,10 +730,18 @@ impl ObjStore for DynObjStore {
         self.as_ref().healthcheck().await
     }
 
+    async fn healthcheck_report(&self) -> Result<HealthReport> {
+        self.as_ref().healthcheck_report().await
+    }
+
     async fn meta(&self, key: &str) -> Result<Option<ObjectMeta>> {
         self.as_ref().meta(key).await
     }
 
+    async fn exists(&self, key: &str) -> Result<bool> {
+        self.as_ref().exists(key).await
+    }
+
     async fn get(&self, key: &str) -> Result<Option<Bytes>> {
         self.as_ref().get(key).await
     }
@@ -272,6 +758,34 @@ impl ObjStore for DynObjStore {
         self.as_ref().get_stream_with_meta(key).await
     }
 
+    async fn get_range(&self, key: &str, range: Range<u64>) -> Result<Option<Bytes>> {
+        self.as_ref().get_range(key, range).await
+    }
+
+    async fn meta_raw(&self, key: &ObjectKey) -> Result<Option<ObjectMeta>> {
+        self.as_ref().meta_raw(key).await
+    }
+
+    async fn get_raw(&self, key: &ObjectKey) -> Result<Option<Bytes>> {
+        self.as_ref().get_raw(key).await
+    }
+
+    async fn delete_raw(&self, key: &ObjectKey) -> Result<()> {
+        self.as_ref().delete_raw(key).await
+    }
+
+    async fn put_raw(&self, key: &ObjectKey, data: Bytes) -> Result<ObjectMeta> {
+        self.as_ref().put_raw(key, data).await
+    }
+
+    async fn list_all_keys_raw(&self, prefix: &ObjectKey) -> Result<Vec<ObjectKey>> {
+        self.as_ref().list_all_keys_raw(prefix).await
+    }
+
+    fn get_many<'a>(&'a self, keys: Vec<String>, options: GetManyOptions) -> GetManyStream<'a> {
+        self.as_ref().get_many(keys, options)
+    }
+
     async fn generate_download_url(&self, args: DownloadUrlArgs) -> Result<Option<url::Url>> {
         self.as_ref().generate_download_url(args).await
     }
@@ -283,6 +797,14 @@ impl ObjStore for DynObjStore {
     async fn send_put(&self, put: Put) -> Result<ObjectMeta> {
         self.as_ref().send_put(put).await
     }
+
+    async fn send_put_result(&self, put: Put) -> Result<PutResult> {
+        self.as_ref().send_put_result(put).await
+    }
+
+    async fn append(&self, key: &str, data: Bytes) -> Result<ObjectMeta> {
+        self.as_ref().append(key, data).await
+    }
     async fn send_copy(&self, copy: Copy) -> Result<ObjectMeta> {
         self.as_ref().send_copy(copy).await
     }
@@ -303,23 +825,98 @@ impl ObjStore for DynObjStore {
         self.as_ref().delete_prefix(prefix).await
     }
 
-    async fn get_json<T: serde::de::DeserializeOwned>(&self, key: &str) -> Result<Option<T>> {
-        match self.get(key).await {
-            Ok(Some(data)) => {
-                let jd = &mut serde_json::Deserializer::from_slice(&data);
-                let out = serde_path_to_error::deserialize(jd).map_err(|source| {
-                    ObjStoreError::ContentDeserialization {
-                        key: key.to_string(),
-                        format: "json".to_string(),
-                        source: Some(Box::new(source)),
-                    }
-                })?;
+    async fn usage(&self, prefix: &str) -> Result<Usage> {
+        self.as_ref().usage(prefix).await
+    }
 
-                Ok(Some(out))
-            }
-            Ok(None) => Ok(None),
-            Err(e) => Err(e),
-        }
+    async fn cleanup(&self, options: CleanupOptions) -> Result<CleanupReport> {
+        self.as_ref().cleanup(options).await
+    }
+
+    fn supports_sort(&self, order: SortOrder) -> bool {
+        self.as_ref().supports_sort(order)
+    }
+
+    fn supports_conditions(&self) -> bool {
+        self.as_ref().supports_conditions()
+    }
+
+    async fn watch(&self, prefix: &str) -> Result<EventStream> {
+        self.as_ref().watch(prefix).await
+    }
+
+    async fn meta_opts(&self, key: &str, options: RequestOptions) -> Result<Option<ObjectMeta>> {
+        self.as_ref().meta_opts(key, options).await
+    }
+
+    async fn get_opts(&self, key: &str, options: RequestOptions) -> Result<Option<Bytes>> {
+        self.as_ref().get_opts(key, options).await
+    }
+
+    async fn get_stream_opts(
+        &self,
+        key: &str,
+        options: RequestOptions,
+    ) -> Result<Option<ValueStream>> {
+        self.as_ref().get_stream_opts(key, options).await
+    }
+
+    async fn get_with_meta_opts(
+        &self,
+        key: &str,
+        options: RequestOptions,
+    ) -> Result<Option<(Bytes, ObjectMeta)>> {
+        self.as_ref().get_with_meta_opts(key, options).await
+    }
+
+    async fn get_stream_with_meta_opts(
+        &self,
+        key: &str,
+        options: RequestOptions,
+    ) -> Result<Option<(ObjectMeta, ValueStream)>> {
+        self.as_ref().get_stream_with_meta_opts(key, options).await
+    }
+
+    async fn get_range_opts(
+        &self,
+        key: &str,
+        range: Range<u64>,
+        options: RequestOptions,
+    ) -> Result<Option<Bytes>> {
+        self.as_ref().get_range_opts(key, range, options).await
+    }
+
+    async fn send_put_opts(&self, put: Put, options: RequestOptions) -> Result<ObjectMeta> {
+        self.as_ref().send_put_opts(put, options).await
+    }
+
+    async fn append_opts(
+        &self,
+        key: &str,
+        data: Bytes,
+        options: RequestOptions,
+    ) -> Result<ObjectMeta> {
+        self.as_ref().append_opts(key, data, options).await
+    }
+
+    async fn send_copy_opts(&self, copy: Copy, options: RequestOptions) -> Result<ObjectMeta> {
+        self.as_ref().send_copy_opts(copy, options).await
+    }
+
+    async fn delete_opts(&self, key: &str, options: RequestOptions) -> Result<()> {
+        self.as_ref().delete_opts(key, options).await
+    }
+
+    async fn delete_prefix_opts(&self, prefix: &str, options: RequestOptions) -> Result<()> {
+        self.as_ref().delete_prefix_opts(prefix, options).await
+    }
+
+    async fn list_opts(&self, args: ListArgs, options: RequestOptions) -> Result<ObjectMetaPage> {
+        self.as_ref().list_opts(args, options).await
+    }
+
+    async fn list_keys_opts(&self, args: ListArgs, options: RequestOptions) -> Result<KeyPage> {
+        self.as_ref().list_keys_opts(args, options).await
     }
 }
 
@@ -329,6 +926,20 @@ pub struct PutBuilder<'a, S> {
     conditions: Conditions,
     /// Specifies the MIME type of the data.
     mime_type: Option<String>,
+    mime_inference: MimeInference,
+    cache_control: Option<String>,
+    content_disposition: Option<String>,
+    content_encoding: Option<String>,
+    cancellation: Option<CancellationToken>,
+    progress: Option<ProgressCallback>,
+    expires_at: Option<OffsetDateTime>,
+    modified_at: Option<OffsetDateTime>,
+    #[cfg(feature = "rate-limit")]
+    rate_limit: Option<u64>,
+    #[cfg(feature = "gzip")]
+    gzip: bool,
+    #[cfg(feature = "dedup")]
+    skip_if_unchanged: bool,
 }
 
 impl<'a, S: ObjStore> PutBuilder<'a, S>
@@ -336,26 +947,292 @@ where
     S: ObjStore,
 {
     pub fn build(self, data: impl Into<DataSource>) -> Put {
-        let mut put = Put::new(self.key, data.into());
+        let mut data = data.into();
+        let mime_type = self.mime_type.or_else(|| {
+            let head = match &data {
+                DataSource::Data(bytes) => Some(bytes.as_ref()),
+                DataSource::Stream(_) | DataSource::Factory(_) => None,
+            };
+            self.mime_inference.infer(&self.key, head)
+        });
+        #[cfg(feature = "rate-limit")]
+        if let Some(bytes_per_sec) = self.rate_limit {
+            data = data.map_stream(move |stream| stream.with_rate_limit(bytes_per_sec));
+        }
+        if let Some(token) = self.cancellation.clone() {
+            data = data.map_stream(move |stream| stream.with_cancellation(token.clone()));
+        }
+        if let Some(progress) = self.progress.clone() {
+            data = data.map_stream(move |stream| stream.with_progress(progress.clone()));
+        }
+
+        let mut put = Put::new(self.key, data);
         put.conditions = self.conditions;
-        put.mime_type = self.mime_type;
+        put.mime_type = mime_type;
+        put.cache_control = self.cache_control;
+        put.content_disposition = self.content_disposition;
+        put.content_encoding = self.content_encoding;
+        put.expires_at = self.expires_at;
+        put.modified_at = self.modified_at;
         put
     }
 
+    /// Set the conditions for the put.
+    pub fn conditions(mut self, conditions: Conditions) -> Self {
+        self.conditions = conditions;
+        self
+    }
+
+    /// Allow cancelling the upload via `token`.
+    ///
+    /// Streamed uploads are aborted by the backend once the token is cancelled.
+    pub fn cancellation(mut self, token: CancellationToken) -> Self {
+        self.cancellation = Some(token);
+        self
+    }
+
+    /// Report upload progress to `callback`.
+    ///
+    /// Streams report each chunk as it is consumed by the backend, in-memory
+    /// data is reported once the put completed.
+    pub fn progress(mut self, callback: ProgressCallback) -> Self {
+        self.progress = Some(callback);
+        self
+    }
+
+    /// Limit the upload bandwidth to `bytes_per_sec`. Values of `0` are ignored.
+    ///
+    /// In-memory data is streamed when it is sent, [`Self::build`] only
+    /// limits streams. See [`crate::io::limit_stream`].
+    #[cfg(feature = "rate-limit")]
+    pub fn rate_limit(mut self, bytes_per_sec: u64) -> Self {
+        if bytes_per_sec > 0 {
+            self.rate_limit = Some(bytes_per_sec);
+        }
+        self
+    }
+
+    /// Set the MIME type of the data.
+    pub fn mime_type(mut self, mime_type: impl Into<String>) -> Self {
+        self.mime_type = Some(mime_type.into());
+        self
+    }
+
+    /// Infer the MIME type if none is set explicitly.
+    ///
+    /// Guesses from the key extension, falling back to sniffing the leading
+    /// bytes of the content (see [`MimeInference::Auto`]).
+    pub fn infer_mime_type(self) -> Self {
+        self.mime_inference(MimeInference::Auto)
+    }
+
+    /// Set how the MIME type is inferred if none is set explicitly.
+    pub fn mime_inference(mut self, mode: MimeInference) -> Self {
+        self.mime_inference = mode;
+        self
+    }
+
+    /// Let the object expire at the given time.
+    ///
+    /// See [`Put::expires_at`] for backend support.
+    pub fn expires_at(mut self, time: OffsetDateTime) -> Self {
+        self.expires_at = Some(time);
+        self
+    }
+
+    /// Let the object expire after the given duration.
+    pub fn expires_in(self, duration: std::time::Duration) -> Self {
+        self.expires_at(OffsetDateTime::now_utc() + duration)
+    }
+
+    /// Record `time` as the modification time of the object.
+    ///
+    /// See [`Put::modified_at`] for backend support.
+    pub fn modified_at(mut self, time: OffsetDateTime) -> Self {
+        self.modified_at = Some(time);
+        self
+    }
+
+    /// Set the `Cache-Control` header stored with the object.
+    pub fn cache_control(mut self, value: impl Into<String>) -> Self {
+        self.cache_control = Some(value.into());
+        self
+    }
+
+    /// Set the `Content-Disposition` header stored with the object.
+    pub fn content_disposition(mut self, value: impl Into<String>) -> Self {
+        self.content_disposition = Some(value.into());
+        self
+    }
+
+    /// Set the `Content-Encoding` header stored with the object.
+    ///
+    /// The data is not encoded by the store, it must already be encoded accordingly.
+    pub fn content_encoding(mut self, value: impl Into<String>) -> Self {
+        self.content_encoding = Some(value.into());
+        self
+    }
+
+    /// Compress the payload of [`Self::json`] and [`Self::json_pretty`] with
+    /// gzip, and set the content encoding to `gzip`.
+    ///
+    /// [`ObjStoreExt::get_json`] decompresses such values transparently.
+    #[cfg(feature = "gzip")]
+    pub fn gzip(mut self) -> Self {
+        self.gzip = true;
+        self
+    }
+
+    /// Skip the upload if the object already has the same content.
+    ///
+    /// The hash of the payload is compared to the hash or etag returned by
+    /// [`ObjStore::meta`], and the existing metadata is returned if they
+    /// match. Costs an additional request for every put, and only applies to
+    /// in-memory data without [conditions](Self::conditions). Streams, and
+    /// objects whose content can not be compared, are always uploaded.
+    #[cfg(feature = "dedup")]
+    pub fn skip_if_unchanged(mut self) -> Self {
+        self.skip_if_unchanged = true;
+        self
+    }
+
+    /// Store `data` as compact JSON.
+    ///
+    /// The MIME type defaults to `application/json`.
     pub async fn json<T: serde::Serialize>(self, data: &T) -> Result<ObjectMeta> {
-        let data = serde_json::to_vec(data).map_err(|source| ObjStoreError::InvalidRequest {
-            message: "could not serialize JSON data for put".to_string(),
-            source: Some(source.into()),
-        })?;
-        let store = self.store;
-        let put = self.build(DataSource::Data(Bytes::from(data)));
-        store.send_put(put).await
+        let data = serde_json::to_vec(data).map_err(json_serialize_error)?;
+        self.send_json(data).await
+    }
+
+    /// Store `data` as indented JSON, see [`Self::json`].
+    pub async fn json_pretty<T: serde::Serialize>(self, data: &T) -> Result<ObjectMeta> {
+        let data = serde_json::to_vec_pretty(data).map_err(json_serialize_error)?;
+        self.send_json(data).await
+    }
+
+    async fn send_json(mut self, data: Vec<u8>) -> Result<ObjectMeta> {
+        if self.mime_type.is_none() {
+            self.mime_type = Some("application/json".to_string());
+        }
+        #[cfg(feature = "gzip")]
+        let data = if self.gzip {
+            self.content_encoding = Some("gzip".to_string());
+            crate::gzip::compress(&data)
+        } else {
+            data
+        };
+        self.send(DataSource::Data(Bytes::from(data))).await
     }
 
     pub async fn send(self, data: impl Into<DataSource>) -> Result<ObjectMeta> {
+        self.send_with(data, |store, put| store.send_put(put), |meta| meta)
+            .await
+    }
+
+    /// Like [`Self::send`], but also reports whether the put created a new
+    /// object. See [`ObjStore::send_put_result`].
+    pub async fn send_with_result(self, data: impl Into<DataSource>) -> Result<PutResult> {
+        self.send_with(
+            data,
+            |store, put| store.send_put_result(put),
+            |meta| PutResult::new(meta, false),
+        )
+        .await
+    }
+
+    async fn send_with<T, F, Fut>(
+        mut self,
+        data: impl Into<DataSource>,
+        send: F,
+        #[cfg_attr(not(feature = "dedup"), allow(unused_variables))] unchanged: fn(ObjectMeta) -> T,
+    ) -> Result<T>
+    where
+        F: FnOnce(&'a S, Put) -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        let data = match data.into() {
+            // Peek at the first chunk of streams for content sniffing.
+            DataSource::Stream(stream)
+                if self.mime_type.is_none() && self.mime_inference.uses_content() =>
+            {
+                let size = stream.size();
+                let mut inner = stream.into_stream();
+                let head = inner.try_next().await?;
+                self.mime_type = self.mime_inference.infer(&self.key, head.as_deref());
+
+                let inner = stream::iter(head.map(Ok)).chain(inner).boxed();
+                let stream = match size {
+                    Some(size) => SizedValueStream::new(inner, size),
+                    None => SizedValueStream::new_without_size(inner),
+                };
+                DataSource::Stream(stream)
+            }
+            // Factories can simply open another stream for sniffing.
+            DataSource::Factory(factory)
+                if self.mime_type.is_none() && self.mime_inference.uses_content() =>
+            {
+                let head = factory().into_stream().try_next().await?;
+                self.mime_type = self.mime_inference.infer(&self.key, head.as_deref());
+                DataSource::Factory(factory)
+            }
+            data => data,
+        };
+
+        // Compare the original bytes, before they are wrapped for sending.
+        #[cfg(feature = "dedup")]
+        if self.skip_if_unchanged
+            && self.conditions.is_empty()
+            && let DataSource::Data(bytes) = &data
+            && let Some(meta) = self.store.meta(&self.key).await?
+            && crate::dedup::content_matches(&meta, bytes)
+        {
+            return Ok(unchanged(meta));
+        }
+
         let store = self.store;
-        let put = self.build(data);
-        store.send_put(put).await
+        let token = self.cancellation.clone();
+        let progress = self.progress.clone();
+        #[cfg(feature = "rate-limit")]
+        let rate_limit = self.rate_limit;
+        #[cfg_attr(not(feature = "rate-limit"), allow(unused_mut))]
+        let mut put = self.build(data);
+
+        // Streams report progress themselves.
+        let progress = match &put.data {
+            DataSource::Data(bytes) => progress.map(|p| (p, bytes.len() as u64)),
+            DataSource::Stream(_) | DataSource::Factory(_) => None,
+        };
+        let in_memory = matches!(put.data, DataSource::Data(_));
+        // In-memory data is only streamed once it is sent, so the checks
+        // above and the progress still see the original bytes.
+        #[cfg(feature = "rate-limit")]
+        if let Some(bytes_per_sec) = rate_limit
+            && let DataSource::Data(bytes) = &put.data
+        {
+            let bytes = bytes.clone();
+            put.data = DataSource::factory(move || {
+                DataSource::Data(bytes.clone())
+                    .into_stream()
+                    .with_rate_limit(bytes_per_sec)
+            });
+        }
+        let result = match token {
+            // Streams are cancelled via the stream itself, so backends can clean up.
+            Some(token) if in_memory => {
+                token
+                    .run_until_cancelled(Operation::Put, send(store, put))
+                    .await
+            }
+            Some(token) => {
+                token.check(Operation::Put)?;
+                send(store, put).await
+            }
+            None => send(store, put).await,
+        }?;
+        if let Some((progress, size)) = progress {
+            progress.report(size, Some(size));
+        }
+        Ok(result)
     }
 
     pub async fn text(self, text: impl Into<String>) -> Result<ObjectMeta> {
@@ -370,6 +1247,21 @@ where
     pub async fn stream(self, stream: SizedValueStream) -> Result<ObjectMeta> {
         self.send(DataSource::Stream(stream)).await
     }
+
+    /// Send a replayable stream, see [`DataSource::factory`].
+    pub async fn stream_factory<F>(self, factory: F) -> Result<ObjectMeta>
+    where
+        F: Fn() -> SizedValueStream + Send + Sync + 'static,
+    {
+        self.send(DataSource::factory(factory)).await
+    }
+}
+
+fn json_serialize_error(source: serde_json::Error) -> ObjStoreError {
+    ObjStoreError::InvalidRequest {
+        message: "could not serialize JSON data for put".to_string(),
+        source: Some(source.into()),
+    }
 }
 
 /// Builder for a copy request from one key to another, respecting conditions.
@@ -378,24 +1270,60 @@ pub struct CopyBuilder<'a, S> {
     src: String,
     dest: String,
     conditions: Conditions,
+    metadata: Option<CopyMetadata>,
+    progress: Option<ProgressCallback>,
 }
 
 impl<'a, S: ObjStore> CopyBuilder<'a, S>
 where
     S: ObjStore,
 {
+    /// Replace the destination metadata instead of copying it from the source.
+    ///
+    /// See [`CopyMetadata`].
+    pub fn metadata(mut self, metadata: CopyMetadata) -> Self {
+        self.metadata = Some(metadata);
+        self
+    }
+
+    /// Set the MIME type of the destination.
+    ///
+    /// This replaces the destination metadata (see [`Self::metadata`]), so
+    /// other metadata of the source is not copied.
+    pub fn mime_type(mut self, mime_type: impl Into<String>) -> Self {
+        self.metadata = Some(
+            self.metadata
+                .take()
+                .unwrap_or_default()
+                .with_mime_type(mime_type),
+        );
+        self
+    }
+
+    /// Report progress to `callback`.
+    ///
+    /// Copies are usually performed by the backend, so progress is reported
+    /// once on completion, with the size of the destination object.
+    pub fn progress(mut self, callback: ProgressCallback) -> Self {
+        self.progress = Some(callback);
+        self
+    }
+
     /// Construct the underlying `Copy` request.
     pub fn build(&self) -> Copy {
         let mut copy = Copy::new(self.src.clone(), self.dest.clone());
         copy.conditions = self.conditions.clone();
+        copy.metadata = self.metadata.clone();
         copy
     }
 
     /// Execute the copy request.
     pub async fn send(self) -> Result<ObjectMeta> {
-        let mut copy = Copy::new(self.src.clone(), self.dest.clone());
-        copy.conditions = self.conditions.clone();
-        self.store.send_copy(copy).await
+        let meta = self.store.send_copy(self.build()).await?;
+        if let Some(progress) = &self.progress {
+            progress.report(meta.size.unwrap_or(0), meta.size);
+        }
+        Ok(meta)
     }
 }
 
@@ -409,9 +1337,38 @@ where
             key: key.to_string(),
             conditions: Conditions::default(),
             mime_type: None,
+            mime_inference: MimeInference::Disabled,
+            cache_control: None,
+            content_disposition: None,
+            content_encoding: None,
+            cancellation: None,
+            progress: None,
+            expires_at: None,
+            modified_at: None,
+            #[cfg(feature = "dedup")]
+            skip_if_unchanged: false,
+            #[cfg(feature = "rate-limit")]
+            rate_limit: None,
+            #[cfg(feature = "gzip")]
+            gzip: false,
         }
     }
 
+    /// Scope the store to the keys below `prefix`.
+    ///
+    /// All operations of the returned handle, including listing and
+    /// [`ObjStore::delete_prefix`], are confined to the prefix, and the prefix
+    /// is stripped from returned keys. Useful to give each tenant or test its
+    /// own namespace. See [`PrefixObjStore`].
+    ///
+    /// The handle holds a clone of the store, which shares the connection.
+    fn scoped(&self, prefix: &str) -> PrefixObjStore<Self>
+    where
+        Self: Clone,
+    {
+        PrefixObjStore::new(prefix, self.clone())
+    }
+
     /// Begin a copy operation from `src` to `dest`, allows setting conditions.
     fn copy(&self, src: &str, dest: &str) -> CopyBuilder<'_, Self> {
         CopyBuilder {
@@ -419,8 +1376,549 @@ where
             src: src.to_string(),
             dest: dest.to_string(),
             conditions: Conditions::default(),
+            metadata: None,
+            progress: None,
+        }
+    }
+
+    /// List objects in the requested [`ListArgs::sort`] order.
+    ///
+    /// If the store supports the order natively (see [`ObjStore::supports_sort`]),
+    /// pages are fetched until [`ListArgs::limit`] objects were collected.
+    /// Otherwise all objects matching the arguments are loaded and sorted
+    /// client-side, which requires buffering the full listing in memory.
+    ///
+    /// The limit applies to the total number of returned objects.
+    /// Without a sort order, the native order is used.
+    fn list_sorted<'a>(
+        &'a self,
+        args: ListArgs,
+    ) -> impl Future<Output = Result<Vec<ObjectMeta>>> + Send + 'a {
+        async move {
+            let limit = args.limit().map(|limit| limit as usize);
+            let sort = args.sort().filter(|sort| !self.supports_sort(*sort));
+
+            let mut pages = list_pages(self, args);
+            let mut items = Vec::new();
+            while let Some(page) = pages.try_next().await? {
+                items.extend(page.items);
+                if sort.is_none() && limit.is_some_and(|limit| items.len() >= limit) {
+                    break;
+                }
+            }
+
+            if let Some(sort) = sort {
+                items.sort_by(|a, b| sort.compare(a, b));
+            }
+            if let Some(limit) = limit {
+                items.truncate(limit);
+            }
+            Ok(items)
         }
     }
+
+    /// List all objects with keys matching a glob pattern.
+    ///
+    /// The literal prefix of the pattern (up to the first wildcard) is used as
+    /// the list prefix, and the pattern is applied with
+    /// [`ListArgs::with_include`].
+    /// See [`Glob`] for the supported syntax.
+    ///
+    /// An invalid pattern is returned as the first stream item.
+    fn list_glob<'a>(&'a self, pattern: &str) -> BoxStream<'a, Result<ObjectMeta>> {
+        let glob = match Glob::new(pattern) {
+            Ok(glob) => glob,
+            Err(err) => return stream::once(async move { Err(err) }).boxed(),
+        };
+        let args = ListArgs::new()
+            .with_prefix(glob.literal_prefix())
+            .with_include(glob);
+        self.list_items_stream(args)
+    }
+
+    /// Copy all objects below `src_prefix` to the same relative keys below `dest_prefix`.
+    ///
+    /// The listing is streamed, and objects are copied with bounded concurrency.
+    /// Failure handling is controlled by [`PrefixCopyOptions::with_policy`].
+    ///
+    /// The prefixes must not overlap.
+    fn copy_prefix<'a>(
+        &'a self,
+        src_prefix: &'a str,
+        dest_prefix: &'a str,
+        options: PrefixCopyOptions,
+    ) -> impl Future<Output = Result<PrefixCopyReport>> + Send + 'a {
+        crate::prefix_copy::copy_prefix(self, src_prefix, dest_prefix, options, false)
+    }
+
+    /// Move all objects below `src_prefix` to the same relative keys below `dest_prefix`.
+    ///
+    /// Objects are copied as with [`Self::copy_prefix`], and the source objects
+    /// are deleted afterwards.
+    fn move_prefix<'a>(
+        &'a self,
+        src_prefix: &'a str,
+        dest_prefix: &'a str,
+        options: PrefixCopyOptions,
+    ) -> impl Future<Output = Result<PrefixCopyReport>> + Send + 'a {
+        crate::prefix_copy::copy_prefix(self, src_prefix, dest_prefix, options, true)
+    }
+
+    /// Copy all objects below `src_prefix` to the same relative keys below
+    /// `dest_prefix` in another store.
+    ///
+    /// Objects are streamed from this store into `dest`, keeping their
+    /// metadata. Concurrency and failure handling work as for
+    /// [`Self::copy_prefix`]. The prefixes may overlap.
+    fn copy_prefix_to<'a, D>(
+        &'a self,
+        src_prefix: &'a str,
+        dest: &'a D,
+        dest_prefix: &'a str,
+        options: PrefixCopyOptions,
+    ) -> impl Future<Output = Result<PrefixCopyReport>> + Send + 'a
+    where
+        D: ObjStore + ?Sized,
+    {
+        crate::prefix_copy::copy_prefix_to(self, dest, src_prefix, dest_prefix, options, false)
+    }
+
+    /// Move all objects below `src_prefix` to the same relative keys below
+    /// `dest_prefix` in another store.
+    ///
+    /// Objects are transferred as with [`Self::copy_prefix_to`], and the source
+    /// objects are deleted afterwards.
+    fn move_prefix_to<'a, D>(
+        &'a self,
+        src_prefix: &'a str,
+        dest: &'a D,
+        dest_prefix: &'a str,
+        options: PrefixCopyOptions,
+    ) -> impl Future<Output = Result<PrefixCopyReport>> + Send + 'a
+    where
+        D: ObjStore + ?Sized,
+    {
+        crate::prefix_copy::copy_prefix_to(self, dest, src_prefix, dest_prefix, options, true)
+    }
+
+    /// Delete many objects, issuing concurrent [`ObjStore::delete`] calls.
+    ///
+    /// Yields a `(key, result)` pair for each key, in completion order.
+    /// Failures of individual keys do not stop the remaining deletions.
+    fn delete_many<'a>(
+        &'a self,
+        keys: Vec<String>,
+        options: DeleteManyOptions,
+    ) -> DeleteManyStream<'a> {
+        crate::delete_many::delete_many(self, keys, options)
+    }
+
+    /// Read many `(key, range)` pairs, merging nearby ranges of the same
+    /// key into fewer [`ObjStore::get_range`] calls.
+    ///
+    /// Returns the slices in request order. See [`crate::RangePlan`].
+    fn get_ranges<'a>(
+        &'a self,
+        requests: Vec<(String, Range<u64>)>,
+        options: GetRangesOptions,
+    ) -> impl Future<Output = Result<Vec<Option<Bytes>>>> + Send + 'a {
+        crate::get_ranges::get_ranges(self, requests, options)
+    }
+
+    /// Delete all keys with a given prefix, unless `token` is cancelled first.
+    ///
+    /// On cancellation the in-flight deletion is dropped and
+    /// [`ObjStoreError::Cancelled`] is returned. Keys deleted up to that point
+    /// stay deleted.
+    fn delete_prefix_cancellable<'a>(
+        &'a self,
+        prefix: &'a str,
+        token: &'a CancellationToken,
+    ) -> impl Future<Output = Result<()>> + Send + 'a {
+        token.run_until_cancelled(Operation::DeletePrefix, self.delete_prefix(prefix))
+    }
+
+    /// Download an object into `writer`, fetching multiple byte ranges concurrently.
+    ///
+    /// The object is split into ranges of [`ParallelDownloadOptions::chunk_size`],
+    /// which are fetched via [`ObjStore::get_range`] and written in order.
+    /// Each range is retried on transient errors.
+    ///
+    /// Fails with [`ObjStoreError::PreconditionFailed`] if the etag of the
+    /// object changed during the download, in which case `writer` may contain
+    /// data of both versions.
+    ///
+    /// Falls back to a single streaming download if the store does not support
+    /// range reads, or if the object is not larger than a single chunk.
+    ///
+    /// Returns `Ok(None)` if the object does not exist.
+    ///
+    /// Tokio users can adapt a `tokio::fs::File` with `tokio_util::compat`.
+    fn download_parallel<'a, W>(
+        &'a self,
+        key: &'a str,
+        writer: &'a mut W,
+        opts: ParallelDownloadOptions,
+    ) -> impl Future<Output = Result<Option<ObjectMeta>>> + Send + 'a
+    where
+        W: AsyncWrite + Unpin + Send,
+    {
+        crate::download::download_parallel(self, key, writer, opts)
+    }
+
+    /// Atomically update a JSON object with optimistic concurrency control.
+    ///
+    /// Loads the current value (`None` if the object does not exist), applies `f`
+    /// and writes the result back, conditional on the object not having changed
+    /// in the meantime (via its etag, or "if not exists" for new objects).
+    /// If another writer wins the race, the whole cycle is repeated, up to 10 times,
+    /// so `f` may be called multiple times.
+    ///
+    /// Backends that ignore put conditions provide no atomicity guarantees.
+    /// Fails with [`ObjStoreError::Unsupported`] if the existing object has no etag.
+    fn update_json<'a, T, F>(
+        &'a self,
+        key: &'a str,
+        f: F,
+    ) -> impl Future<Output = Result<ObjectMeta>> + Send + 'a
+    where
+        T: serde::Serialize + serde::de::DeserializeOwned + Send + 'a,
+        F: FnMut(Option<T>) -> T + Send + 'a,
+    {
+        crate::update::update_json(self, key, f)
+    }
+
+    /// Get a JSON value from the store.
+    ///
+    /// Values compressed with gzip (see [`PutBuilder::gzip`]) are decompressed
+    /// transparently, up to a decompressed size of 256 MiB.
+    fn get_json<'a, T>(
+        &'a self,
+        key: &'a str,
+    ) -> impl Future<Output = Result<Option<T>>> + Send + 'a
+    where
+        T: serde::de::DeserializeOwned + 'a,
+    {
+        get_json(self, key)
+    }
+
+    /// Get a value as UTF-8 text.
+    ///
+    /// A leading byte order mark is removed. Fails with
+    /// [`ObjStoreError::ContentDeserialization`] if the value is not valid UTF-8.
+    /// See [`Self::get_text_with`] for other decodings.
+    fn get_text<'a>(
+        &'a self,
+        key: &'a str,
+    ) -> impl Future<Output = Result<Option<String>>> + Send + 'a {
+        crate::text::get_text(self, key, TextDecoding::Strict)
+    }
+
+    /// Get a value as text, decoded according to `decoding`.
+    fn get_text_with<'a>(
+        &'a self,
+        key: &'a str,
+        decoding: TextDecoding,
+    ) -> impl Future<Output = Result<Option<String>>> + Send + 'a {
+        crate::text::get_text(self, key, decoding)
+    }
+
+    /// Stream all objects matching `args`, listing multiple key ranges concurrently.
+    ///
+    /// The key space below the prefix is split into ranges according to
+    /// `partitions` (see [`crate::PartitionStrategy`]). All ranges are listed at the
+    /// same time and their items are yielded in key order, so full scans of
+    /// large stores are bound by throughput instead of per-page round trips.
+    /// Each range buffers at most [`ListPartitions::max_buffered_pages`] pages.
+    ///
+    /// Falls back to [`ObjStore::list_items_stream`] if the arguments have a
+    /// delimiter, cursor or non-key sort order, or if the store does not list
+    /// in key order.
+    fn list_parallel(
+        &self,
+        args: ListArgs,
+        partitions: ListPartitions,
+    ) -> BoxStream<'_, Result<ObjectMeta>> {
+        crate::list_parallel::list_parallel(self, args, partitions)
+    }
+
+    /// Page through [`ObjStore::list`], emitting a [`ScanCheckpoint`] after
+    /// every `every` pages and after the last page.
+    ///
+    /// Pass a previously emitted checkpoint to resume the listing after it.
+    /// Its prefix must match the prefix of `args`.
+    fn list_checkpointed(
+        &self,
+        args: ListArgs,
+        checkpoint: Option<ScanCheckpoint>,
+        every: usize,
+    ) -> ScanStream<'_, ObjectMetaPage> {
+        crate::checkpoint::list_checkpointed(self, args, checkpoint, every)
+    }
+
+    /// Variant of [`Self::list_checkpointed`] for [`ObjStore::list_keys`].
+    fn list_keys_checkpointed(
+        &self,
+        args: ListArgs,
+        checkpoint: Option<ScanCheckpoint>,
+        every: usize,
+    ) -> ScanStream<'_, KeyPage> {
+        crate::checkpoint::list_keys_checkpointed(self, args, checkpoint, every)
+    }
+
+    /// Stream the lines of a newline-delimited JSON object, deserialized as `T`.
+    ///
+    /// The object is streamed rather than loaded into memory. Empty lines are
+    /// skipped, and lines that can not be deserialized yield an
+    /// [`ObjStoreError::ContentDeserialization`] error.
+    ///
+    /// Returns `Ok(None)` if the object does not exist.
+    fn get_ndjson<'a, T>(
+        &'a self,
+        key: &'a str,
+    ) -> impl Future<Output = Result<Option<NdjsonStream<T>>>> + Send + 'a
+    where
+        T: serde::de::DeserializeOwned + Send + 'static,
+    {
+        crate::ndjson::get_ndjson(self, key)
+    }
+
+    /// Store a stream of items as newline-delimited JSON, one item per line.
+    ///
+    /// Items are serialized while the stream is uploaded, without buffering
+    /// the whole object. The MIME type is set to `application/x-ndjson`.
+    fn put_ndjson<'a, T, I>(
+        &'a self,
+        key: &'a str,
+        items: I,
+    ) -> impl Future<Output = Result<ObjectMeta>> + Send + 'a
+    where
+        T: serde::Serialize + 'a,
+        I: Stream<Item = T> + Send + 'static,
+    {
+        crate::ndjson::put_ndjson(self, key, items)
+    }
+
+    /// Download an object into a local file, streaming the data without
+    /// buffering the full object in memory.
+    ///
+    /// The file is created or truncated.
+    /// After the transfer the written size and SHA-256 hash are verified
+    /// against the object metadata, if the backend provides them.
+    /// The file is removed again if the transfer or verification fails.
+    ///
+    /// Returns `Ok(None)` if the object does not exist.
+    #[cfg(feature = "fs")]
+    fn get_to_file<'a>(
+        &'a self,
+        key: &'a str,
+        path: impl AsRef<std::path::Path> + Send + 'a,
+    ) -> impl Future<Output = Result<Option<ObjectMeta>>> + Send + 'a {
+        async move { crate::file::get_to_file(self, key, path.as_ref()).await }
+    }
+
+    /// Upload a local file, streaming the data without buffering the full
+    /// file in memory.
+    ///
+    /// The MIME type is derived from the file extension.
+    /// After the upload the size and SHA-256 hash of the file are verified
+    /// against the returned metadata, if the backend provides them.
+    #[cfg(feature = "fs")]
+    fn put_from_file<'a>(
+        &'a self,
+        key: &'a str,
+        path: impl AsRef<std::path::Path> + Send + 'a,
+    ) -> impl Future<Output = Result<ObjectMeta>> + Send + 'a {
+        async move { crate::file::put_from_file(self, key, path.as_ref()).await }
+    }
 }
 
 impl<S: ObjStore> ObjStoreExt for S {}
+
+pub(crate) async fn get_json<S, T>(store: &S, key: &str) -> Result<Option<T>>
+where
+    S: ObjStore + ?Sized,
+    T: serde::de::DeserializeOwned,
+{
+    match store.get(key).await? {
+        Some(data) => Ok(Some(deserialize_json(key, &data)?)),
+        None => Ok(None),
+    }
+}
+
+/// Deserialize a JSON value, decompressing gzip data first.
+pub(crate) fn deserialize_json<T>(key: &str, data: &[u8]) -> Result<T>
+where
+    T: serde::de::DeserializeOwned,
+{
+    #[cfg(feature = "gzip")]
+    let decompressed;
+    #[cfg(feature = "gzip")]
+    let data = if crate::gzip::is_gzip(data) {
+        decompressed = crate::gzip::decompress(data, crate::gzip::MAX_DECOMPRESSED_SIZE).map_err(
+            |source| ObjStoreError::ContentDeserialization {
+                key: key.to_string(),
+                format: "gzip".to_string(),
+                source: Some(source.into()),
+            },
+        )?;
+        &decompressed[..]
+    } else {
+        data
+    };
+
+    let jd = &mut serde_json::Deserializer::from_slice(data);
+    serde_path_to_error::deserialize(jd).map_err(|source| ObjStoreError::ContentDeserialization {
+        key: key.to_string(),
+        format: "json".to_string(),
+        source: Some(Box::new(source)),
+    })
+}
+
+/// Read-modify-write implementation of [`ObjStore::append`].
+pub(crate) async fn append_emulated<S>(store: &S, key: &str, data: Bytes) -> Result<ObjectMeta>
+where
+    S: ObjStore + ?Sized,
+{
+    /// Number of attempts before giving up on conflicting writes.
+    const MAX_ATTEMPTS: usize = 10;
+
+    let mut attempt = 1;
+    loop {
+        let put = match store.get_with_meta(key).await? {
+            Some((existing, meta)) => {
+                let mut value = bytes::BytesMut::with_capacity(existing.len() + data.len());
+                value.extend_from_slice(&existing);
+                value.extend_from_slice(&data);
+
+                let mut put = Put::new(key, value.freeze());
+                put.mime_type = meta.mime_type;
+                put.cache_control = meta.cache_control;
+                put.content_disposition = meta.content_disposition;
+                put.content_encoding = meta.content_encoding;
+                put.expires_at = meta.expires_at;
+                if let Some(etag) = meta.etag {
+                    put.conditions = Conditions::new().if_match_tags([etag]);
+                }
+                put
+            }
+            None => {
+                let mut put = Put::new(key, data.clone());
+                put.conditions = Conditions::new().if_not_exists();
+                put
+            }
+        };
+
+        match store.send_put(put).await {
+            Err(ObjStoreError::PreconditionFailed { .. }) if attempt < MAX_ATTEMPTS => {
+                attempt += 1;
+            }
+            Err(ObjStoreError::PreconditionFailed {
+                resource, source, ..
+            }) => {
+                return Err(ObjStoreError::PreconditionFailed {
+                    operation: Operation::Append,
+                    resource,
+                    source,
+                });
+            }
+            res => return res,
+        }
+    }
+}
+
+/// Page through [`ObjStore::list`], following the cursors.
+///
+/// In contrast to [`ObjStore::list_stream`], this does not require the
+/// store to be `Clone + 'static`.
+pub(crate) fn list_pages<'a, S>(
+    store: &'a S,
+    args: ListArgs,
+) -> BoxStream<'a, Result<ObjectMetaPage>>
+where
+    S: ObjStore + ?Sized,
+{
+    stream::try_unfold(Some(args), move |state| async move {
+        let Some(args) = state else {
+            return Ok(None);
+        };
+        let page = match args.cancellation() {
+            Some(token) => {
+                token
+                    .run_until_cancelled(Operation::List, store.list(args.clone()))
+                    .await?
+            }
+            None => store.list(args.clone()).await?,
+        };
+        let next = match &page.next_cursor {
+            Some(cursor) if Some(cursor.as_str()) != args.cursor() => {
+                Some(args.with_cursor(cursor.clone()))
+            }
+            _ => None,
+        };
+        Ok(Some((page, next)))
+    })
+    .boxed()
+}
+
+/// Flattens a stream of pages into their items, fetching the next page while
+/// the items of the current one are consumed.
+///
+/// At most one page is buffered ahead of the consumer.
+struct PrefetchItems<'a, T> {
+    /// `None` once the page stream is exhausted or failed.
+    pages: Option<BoxStream<'a, Result<Vec<T>>>>,
+    current: std::vec::IntoIter<T>,
+    next: Option<Result<Vec<T>>>,
+}
+
+impl<'a, T> PrefetchItems<'a, T> {
+    fn new(pages: impl Stream<Item = Result<Vec<T>>> + Send + 'a) -> Self {
+        Self {
+            pages: Some(pages.boxed()),
+            current: Vec::new().into_iter(),
+            next: None,
+        }
+    }
+
+    /// Drive the fetch of the next page, unless one is already buffered.
+    fn prefetch(&mut self, cx: &mut Context<'_>) {
+        if self.next.is_some() {
+            return;
+        }
+        if let Some(pages) = &mut self.pages {
+            match pages.poll_next_unpin(cx) {
+                Poll::Ready(Some(page)) => self.next = Some(page),
+                Poll::Ready(None) => self.pages = None,
+                Poll::Pending => {}
+            }
+        }
+    }
+}
+
+impl<T: Unpin> Stream for PrefetchItems<'_, T> {
+    type Item = Result<T>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = &mut *self;
+        this.prefetch(cx);
+        loop {
+            if let Some(item) = this.current.next() {
+                return Poll::Ready(Some(Ok(item)));
+            }
+            match this.next.take() {
+                Some(Ok(page)) => {
+                    this.current = page.into_iter();
+                    this.prefetch(cx);
+                }
+                Some(Err(err)) => {
+                    this.pages = None;
+                    return Poll::Ready(Some(Err(err)));
+                }
+                // The page stream was polled by `prefetch`, so the waker is registered.
+                None if this.pages.is_some() => return Poll::Pending,
+                None => return Poll::Ready(None),
+            }
+        }
+    }
+}
@@ -0,0 +1,82 @@
+//! Decoding objects as text.
+//!
+//! See [`crate::ObjStoreExt::get_text`].
+
+use crate::{BoxError, ObjStore, ObjStoreError, Result};
+
+const UTF8_BOM: &[u8] = &[0xef, 0xbb, 0xbf];
+const UTF16_LE_BOM: &[u8] = &[0xff, 0xfe];
+const UTF16_BE_BOM: &[u8] = &[0xfe, 0xff];
+
+/// How [`crate::ObjStoreExt::get_text_with`] decodes values.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum TextDecoding {
+    /// Require valid UTF-8.
+    #[default]
+    Strict,
+    /// Decode as UTF-8, replacing invalid sequences with `U+FFFD`.
+    Lossy,
+    /// Detect the encoding: UTF-16 (with a byte order mark) or UTF-8,
+    /// falling back to ISO-8859-1 (Latin-1) for anything else.
+    Detect,
+}
+
+impl TextDecoding {
+    /// Decode `data`. A leading byte order mark is removed.
+    ///
+    /// Fails with [`ObjStoreError::ContentDeserialization`] if the data is not
+    /// valid in the selected encoding.
+    pub fn decode(self, key: &str, data: &[u8]) -> Result<String> {
+        let utf8 = data.strip_prefix(UTF8_BOM).unwrap_or(data);
+        match self {
+            Self::Strict => String::from_utf8(utf8.to_vec())
+                .map_err(|source| decode_error(key, "utf-8", source)),
+            Self::Lossy => Ok(String::from_utf8_lossy(utf8).into_owned()),
+            Self::Detect => {
+                if let Some(data) = data.strip_prefix(UTF16_LE_BOM) {
+                    decode_utf16(key, data, u16::from_le_bytes)
+                } else if let Some(data) = data.strip_prefix(UTF16_BE_BOM) {
+                    decode_utf16(key, data, u16::from_be_bytes)
+                } else {
+                    match std::str::from_utf8(utf8) {
+                        Ok(text) => Ok(text.to_string()),
+                        Err(_) => Ok(data.iter().map(|b| char::from(*b)).collect()),
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn decode_utf16(key: &str, data: &[u8], unit: fn([u8; 2]) -> u16) -> Result<String> {
+    if !data.len().is_multiple_of(2) {
+        return Err(decode_error(key, "utf-16", "odd number of bytes"));
+    }
+    let units = data.chunks_exact(2).map(|pair| unit([pair[0], pair[1]]));
+    char::decode_utf16(units)
+        .collect::<std::result::Result<String, _>>()
+        .map_err(|source| decode_error(key, "utf-16", source))
+}
+
+fn decode_error(key: &str, format: &str, source: impl Into<BoxError>) -> ObjStoreError {
+    ObjStoreError::ContentDeserialization {
+        key: key.to_string(),
+        format: format.to_string(),
+        source: Some(source.into()),
+    }
+}
+
+pub(crate) async fn get_text<S>(
+    store: &S,
+    key: &str,
+    decoding: TextDecoding,
+) -> Result<Option<String>>
+where
+    S: ObjStore + ?Sized,
+{
+    match store.get(key).await? {
+        Some(data) => decoding.decode(key, &data).map(Some),
+        None => Ok(None),
+    }
+}
@@ -1,9 +1,17 @@
-use std::collections::HashMap;
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::Arc,
+};
 
 use bytes::Bytes;
+use futures::{
+    StreamExt as _,
+    future::{self, Either},
+    stream,
+};
 use time::OffsetDateTime;
 
-use crate::Result;
+use crate::{CancellationToken, Glob, ObjStoreError, Operation, ProgressCallback, Result};
 
 /// Byte stream.
 pub type ValueStream = futures::stream::BoxStream<'static, Result<Bytes>>;
@@ -49,6 +57,47 @@ impl SizedValueStream {
     pub fn into_stream(self) -> ValueStream {
         self.stream
     }
+
+    /// Abort the stream with [`ObjStoreError::Cancelled`] once `token` is cancelled.
+    ///
+    /// Backends treat this like any other stream error, and abort the upload.
+    pub fn with_cancellation(self, token: CancellationToken) -> Self {
+        let stream = stream::unfold(Some((self.stream, token)), |state| async move {
+            let (mut stream, token) = state?;
+            let next = match future::select(stream.next(), token.cancelled()).await {
+                Either::Left((next, _)) => next,
+                Either::Right(((), _)) => Some(Err(ObjStoreError::cancelled(Operation::Put))),
+            };
+            match next {
+                Some(Ok(chunk)) => Some((Ok(chunk), Some((stream, token)))),
+                // Stop after the first error.
+                Some(Err(err)) => Some((Err(err), None)),
+                None => None,
+            }
+        })
+        .boxed();
+        Self {
+            stream,
+            size: self.size,
+        }
+    }
+
+    /// Report the progress of each chunk to `callback`, with the size as total.
+    pub fn with_progress(self, callback: ProgressCallback) -> Self {
+        Self {
+            stream: callback.track(self.stream, self.size),
+            size: self.size,
+        }
+    }
+
+    /// Limit the throughput to `bytes_per_sec`, see [`crate::io::limit_stream`].
+    #[cfg(feature = "rate-limit")]
+    pub fn with_rate_limit(self, bytes_per_sec: u64) -> Self {
+        Self {
+            stream: crate::io::limit_stream(self.stream, bytes_per_sec),
+            size: self.size,
+        }
+    }
 }
 
 impl std::fmt::Debug for SizedValueStream {
@@ -66,10 +115,24 @@ pub type KeyStream<'a> = futures::stream::BoxStream<'a, Result<KeyPage>>;
 pub type MetaStream = futures::stream::BoxStream<'static, Result<ObjectMetaPage>>;
 
 /// Object metadata.
+///
+/// With the `serde` feature enabled, implements `Serialize` and `Deserialize`
+/// with a stable, versioned layout (hex hashes, RFC3339 timestamps).
 #[derive(Clone, Debug, PartialEq, Eq)]
 #[non_exhaustive]
 pub struct ObjectMeta {
     pub key: String,
+    /// Opaque version tag of the object.
+    ///
+    /// Etags are stable across reads (`meta`, `get_with_meta`, listings)
+    /// and change whenever the content changes, so they can be used for
+    /// [`Conditions::if_match_tags`] and change detection.
+    ///
+    /// They are not content hashes: the format is backend specific (S3
+    /// returns quoted MD5s or multipart digests, the memory store a prefixed
+    /// SHA-256), rewriting identical content may or may not change them, and
+    /// they are not comparable across stores. Use [`Self::hash_sha256`] or
+    /// [`Self::hash_md5`] to compare content.
     pub etag: Option<String>,
     pub size: Option<u64>,
     pub created_at: Option<OffsetDateTime>,
@@ -78,8 +141,26 @@ pub struct ObjectMeta {
     pub hash_sha256: Option<[u8; 32]>,
     /// Optional MIME content type of the object.
     pub mime_type: Option<String>,
+    /// Optional `Cache-Control` header value of the object.
+    pub cache_control: Option<String>,
+    /// Optional `Content-Disposition` header value of the object.
+    pub content_disposition: Option<String>,
+    /// Optional `Content-Encoding` header value of the object.
+    pub content_encoding: Option<String>,
 
-    pub extra: HashMap<String, serde_json::Value>,
+    /// Time after which the object expires, and is removed.
+    ///
+    /// See [`Put::expires_at`].
+    pub expires_at: Option<OffsetDateTime>,
+
+    /// Encryption of the object at rest.
+    ///
+    /// `None` if the object is not encrypted, or if the backend does not
+    /// report it (e.g. S3 listings).
+    pub encryption: Option<Encryption>,
+
+    /// Additional, mostly backend-specific attributes.
+    pub attributes: Attributes,
 }
 
 impl ObjectMeta {
@@ -93,7 +174,12 @@ impl ObjectMeta {
             hash_md5: None,
             hash_sha256: None,
             mime_type: None,
-            extra: HashMap::new(),
+            cache_control: None,
+            content_disposition: None,
+            content_encoding: None,
+            expires_at: None,
+            encryption: None,
+            attributes: Attributes::default(),
         }
     }
 
@@ -141,26 +227,344 @@ impl ObjectMeta {
     }
 }
 
+/// Additional object attributes.
+///
+/// Attributes that several backends share have dedicated fields.
+/// Anything else goes into the custom namespace (see [`Self::set_custom`]).
+///
+/// HTTP content headers like `Content-Encoding` are regular [`ObjectMeta`]
+/// fields.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Attributes {
+    /// Storage class or tier of the object (e.g. `STANDARD` or `GLACIER` on S3).
+    pub storage_class: Option<String>,
+    /// Version identifier, for backends with object versioning.
+    pub version_id: Option<String>,
+    /// Identifier of the object owner.
+    pub owner: Option<String>,
+
+    pub(crate) custom: BTreeMap<String, serde_json::Value>,
+}
+
+impl Attributes {
+    /// Whether no attribute is set.
+    pub fn is_empty(&self) -> bool {
+        self.storage_class.is_none()
+            && self.version_id.is_none()
+            && self.owner.is_none()
+            && self.custom.is_empty()
+    }
+
+    /// Get a custom attribute.
+    pub fn custom(&self, key: &str) -> Option<&serde_json::Value> {
+        self.custom.get(key)
+    }
+
+    /// Set a custom attribute, returning the previous value.
+    ///
+    /// Keys should be namespaced with the backend or application name,
+    /// for example `logfs.chunk_size`, to avoid collisions.
+    pub fn set_custom(
+        &mut self,
+        key: impl Into<String>,
+        value: impl Into<serde_json::Value>,
+    ) -> Option<serde_json::Value> {
+        self.custom.insert(key.into(), value.into())
+    }
+
+    /// Remove a custom attribute, returning its value.
+    pub fn remove_custom(&mut self, key: &str) -> Option<serde_json::Value> {
+        self.custom.remove(key)
+    }
+
+    /// Iterate over all custom attributes, ordered by key.
+    pub fn custom_iter(&self) -> impl Iterator<Item = (&str, &serde_json::Value)> {
+        self.custom.iter().map(|(key, value)| (key.as_str(), value))
+    }
+}
+
+/// Encryption of an object at rest, see [`ObjectMeta::encryption`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Encryption {
+    pub scheme: EncryptionScheme,
+    /// Identifier of the encryption key, like a KMS key ARN or the MD5
+    /// digest of a customer-provided key.
+    pub key_id: Option<String>,
+}
+
+impl Encryption {
+    pub fn new(scheme: EncryptionScheme) -> Self {
+        Self {
+            scheme,
+            key_id: None,
+        }
+    }
+
+    pub fn with_key_id(mut self, key_id: impl Into<String>) -> Self {
+        self.key_id = Some(key_id.into());
+        self
+    }
+}
+
+/// Encryption scheme of an object.
+///
+/// Uses the S3 names as the string form (see [`Self::as_str`]).
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum EncryptionScheme {
+    /// Server-side encryption with keys managed by the service (SSE-S3, `AES256`).
+    ServiceManaged,
+    /// Server-side encryption with keys from a key management service (SSE-KMS, `aws:kms`).
+    Kms,
+    /// Dual-layer server-side encryption with KMS keys (DSSE-KMS, `aws:kms:dsse`).
+    KmsDualLayer,
+    /// Server-side encryption with customer-provided keys (SSE-C).
+    CustomerProvided,
+    /// Encrypted by the client before upload.
+    ClientSide,
+    /// Any other scheme reported by a backend.
+    Other(String),
+}
+
+impl EncryptionScheme {
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::ServiceManaged => "AES256",
+            Self::Kms => "aws:kms",
+            Self::KmsDualLayer => "aws:kms:dsse",
+            Self::CustomerProvided => "SSE-C",
+            Self::ClientSide => "client-side",
+            Self::Other(value) => value,
+        }
+    }
+
+    /// Parse the string form, as returned by [`Self::as_str`].
+    ///
+    /// Unknown values are preserved as [`Self::Other`].
+    pub fn parse(value: &str) -> Self {
+        match value {
+            "AES256" => Self::ServiceManaged,
+            "aws:kms" => Self::Kms,
+            "aws:kms:dsse" => Self::KmsDualLayer,
+            "SSE-C" => Self::CustomerProvided,
+            "client-side" => Self::ClientSide,
+            other => Self::Other(other.to_string()),
+        }
+    }
+}
+
+impl std::fmt::Display for EncryptionScheme {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct ObjectMetaPage {
     pub items: Vec<ObjectMeta>,
     pub next_cursor: Option<String>,
 
+    /// Common prefixes of a delimiter listing (see [`ListArgs::with_delimiter`]).
+    ///
+    /// Each prefix is the full key prefix, including the trailing delimiter.
+    /// Use [`Self::into_entries`] to get objects and prefixes in key order.
     pub prefixes: Option<Vec<String>>,
 }
 
+impl ObjectMetaPage {
+    /// Build a page from mixed entries, splitting them into
+    /// [`Self::items`] and [`Self::prefixes`].
+    pub fn from_entries(entries: Vec<ObjectOrPrefix>, next_cursor: Option<String>) -> Self {
+        let mut items = Vec::new();
+        let mut prefixes = Vec::new();
+        for entry in entries {
+            match entry {
+                ObjectOrPrefix::Object(meta) => items.push(*meta),
+                ObjectOrPrefix::Prefix(prefix) => prefixes.push(prefix),
+            }
+        }
+        Self {
+            items,
+            next_cursor,
+            prefixes: (!prefixes.is_empty()).then_some(prefixes),
+        }
+    }
+
+    /// The objects and prefixes of the page, merged in key order.
+    pub fn into_entries(self) -> Vec<ObjectOrPrefix> {
+        let mut entries: Vec<_> = self
+            .items
+            .into_iter()
+            .map(|meta| ObjectOrPrefix::Object(Box::new(meta)))
+            .chain(
+                self.prefixes
+                    .unwrap_or_default()
+                    .into_iter()
+                    .map(ObjectOrPrefix::Prefix),
+            )
+            .collect();
+        entries.sort_by(|a, b| a.key().cmp(b.key()));
+        entries
+    }
+}
+
+/// An entry of a delimiter listing: either an object, or a common prefix
+/// that groups the objects below it, like a directory.
+///
+/// See [`crate::ObjStore::list_entries_stream`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ObjectOrPrefix {
+    Object(Box<ObjectMeta>),
+    /// The full key prefix, including the trailing delimiter.
+    Prefix(String),
+}
+
+impl ObjectOrPrefix {
+    /// The object key, or the prefix.
+    pub fn key(&self) -> &str {
+        match self {
+            Self::Object(meta) => meta.key(),
+            Self::Prefix(prefix) => prefix,
+        }
+    }
+
+    pub fn is_prefix(&self) -> bool {
+        matches!(self, Self::Prefix(_))
+    }
+
+    pub fn as_object(&self) -> Option<&ObjectMeta> {
+        match self {
+            Self::Object(meta) => Some(meta),
+            Self::Prefix(_) => None,
+        }
+    }
+
+    pub fn into_object(self) -> Option<ObjectMeta> {
+        match self {
+            Self::Object(meta) => Some(*meta),
+            Self::Prefix(_) => None,
+        }
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct KeyPage {
     pub items: Vec<String>,
     pub next_cursor: Option<String>,
 }
 
+/// Aggregated storage usage, as returned by [`crate::ObjStore::usage`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Usage {
+    /// Number of objects.
+    pub objects: u64,
+    /// Total size of all objects in bytes.
+    ///
+    /// Objects with an unknown size are not included.
+    pub bytes: u64,
+}
+
+/// Detailed health information, as returned by
+/// [`crate::ObjStore::healthcheck_report`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct HealthReport {
+    /// Round-trip latency of the healthcheck request.
+    pub latency: std::time::Duration,
+    /// Server or backend version information, if reported by the backend.
+    pub server: Option<String>,
+    /// Region of the backend, if known.
+    pub region: Option<String>,
+    /// Soft problems that do not make the store unusable,
+    /// e.g. missing permissions for some operations.
+    pub warnings: Vec<String>,
+}
+
+impl HealthReport {
+    pub fn new(latency: std::time::Duration) -> Self {
+        Self {
+            latency,
+            server: None,
+            region: None,
+            warnings: Vec::new(),
+        }
+    }
+
+    /// Whether any warnings were reported.
+    pub fn has_warnings(&self) -> bool {
+        !self.warnings.is_empty()
+    }
+
+    /// Check that listing objects works, recording a warning if it fails.
+    ///
+    /// Used by the default [`crate::ObjStore::healthcheck_report`], and
+    /// available for backends that override it.
+    pub async fn probe_list<S>(mut self, store: &S) -> Self
+    where
+        S: crate::ObjStore + ?Sized,
+    {
+        if let Err(err) = store.list_keys(ListArgs::new().with_limit(1)).await {
+            self.warnings.push(format!("listing objects failed: {err}"));
+        }
+        self
+    }
+}
+
+/// Sort order for listings.
+///
+/// Without an explicit sort order, backends list in their native order, which
+/// is ascending by key for all bundled backends except the filesystem store.
+///
+/// Backends only apply orders they report via [`crate::ObjStore::supports_sort`].
+/// [`crate::ObjStoreExt::list_sorted`] falls back to sorting client-side.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum SortOrder {
+    KeyAscending,
+    KeyDescending,
+    /// Oldest first, by `updated_at`.
+    ModifiedAscending,
+    /// Newest first, by `updated_at`.
+    ModifiedDescending,
+    SizeAscending,
+    SizeDescending,
+}
+
+impl SortOrder {
+    /// Compare two objects according to this order.
+    ///
+    /// Ties (and unknown values) are ordered by key.
+    pub fn compare(&self, a: &ObjectMeta, b: &ObjectMeta) -> std::cmp::Ordering {
+        let by_key = a.key.cmp(&b.key);
+        match self {
+            Self::KeyAscending => by_key,
+            Self::KeyDescending => by_key.reverse(),
+            Self::ModifiedAscending => a.updated_at.cmp(&b.updated_at).then(by_key),
+            Self::ModifiedDescending => b.updated_at.cmp(&a.updated_at).then(by_key),
+            Self::SizeAscending => a.size.cmp(&b.size).then(by_key),
+            Self::SizeDescending => b.size.cmp(&a.size).then(by_key),
+        }
+    }
+}
+
 #[derive(Clone, Debug, Default)]
 pub struct ListArgs {
     prefix: Option<String>,
     limit: Option<u64>,
     cursor: Option<String>,
     delimiter: Option<String>,
+    cancellation: Option<CancellationToken>,
+    min_size: Option<u64>,
+    max_size: Option<u64>,
+    modified_after: Option<OffsetDateTime>,
+    modified_before: Option<OffsetDateTime>,
+    sort: Option<SortOrder>,
+    start_after: Option<String>,
+    fetch_owner: bool,
+    include: Vec<Glob>,
+    exclude: Vec<Glob>,
 }
 
 impl ListArgs {
@@ -237,11 +641,287 @@ impl ListArgs {
         self.cursor = cursor;
         self
     }
+
+    /// Token for cancelling streaming listings.
+    ///
+    /// Checked by [`crate::ObjStore::list_keys_stream`] and
+    /// [`crate::ObjStore::list_stream`] before and while fetching each page.
+    pub fn cancellation(&self) -> Option<&CancellationToken> {
+        self.cancellation.as_ref()
+    }
+
+    pub fn with_cancellation(mut self, token: CancellationToken) -> Self {
+        self.cancellation = Some(token);
+        self
+    }
+
+    /// Only list objects with at least this size in bytes.
+    pub fn min_size(&self) -> Option<u64> {
+        self.min_size
+    }
+
+    pub fn with_min_size(mut self, size: u64) -> Self {
+        self.min_size = Some(size);
+        self
+    }
+
+    /// Only list objects with at most this size in bytes.
+    pub fn max_size(&self) -> Option<u64> {
+        self.max_size
+    }
+
+    pub fn with_max_size(mut self, size: u64) -> Self {
+        self.max_size = Some(size);
+        self
+    }
+
+    /// Only list objects last modified after this time.
+    pub fn modified_after(&self) -> Option<OffsetDateTime> {
+        self.modified_after
+    }
+
+    pub fn with_modified_after(mut self, time: OffsetDateTime) -> Self {
+        self.modified_after = Some(time);
+        self
+    }
+
+    /// Only list objects last modified before this time.
+    pub fn modified_before(&self) -> Option<OffsetDateTime> {
+        self.modified_before
+    }
+
+    pub fn with_modified_before(mut self, time: OffsetDateTime) -> Self {
+        self.modified_before = Some(time);
+        self
+    }
+
+    /// Requested sort order.
+    pub fn sort(&self) -> Option<SortOrder> {
+        self.sort
+    }
+
+    pub fn with_sort(mut self, sort: SortOrder) -> Self {
+        self.sort = Some(sort);
+        self
+    }
+
+    /// Only list keys that sort after this key.
+    ///
+    /// Unlike a cursor, this does not require a previous listing, so a
+    /// lexicographic scan can be resumed from any known key.
+    /// Only meaningful for ascending listings.
+    pub fn start_after(&self) -> Option<&str> {
+        self.start_after.as_deref()
+    }
+
+    pub fn with_start_after(mut self, key: impl Into<String>) -> Self {
+        let key = key.into();
+        self.start_after = if key.is_empty() { None } else { Some(key) };
+        self
+    }
+
+    /// Request the owner of listed objects ([`Attributes::owner`]).
+    ///
+    /// Backends that report owners without extra cost may ignore this.
+    pub fn fetch_owner(&self) -> bool {
+        self.fetch_owner
+    }
+
+    pub fn with_fetch_owner(mut self, fetch: bool) -> Self {
+        self.fetch_owner = fetch;
+        self
+    }
+
+    /// Patterns of which a key must match at least one to be listed.
+    ///
+    /// Patterns match the full key. An empty list includes all keys.
+    pub fn include(&self) -> &[Glob] {
+        &self.include
+    }
+
+    /// Only list keys matching `glob`, or any other included pattern.
+    pub fn with_include(mut self, glob: Glob) -> Self {
+        self.include.push(glob);
+        self
+    }
+
+    /// Patterns of which a key must match none to be listed.
+    pub fn exclude(&self) -> &[Glob] {
+        &self.exclude
+    }
+
+    /// Do not list keys matching `glob`.
+    pub fn with_exclude(mut self, glob: Glob) -> Self {
+        self.exclude.push(glob);
+        self
+    }
+
+    /// Replace all include and exclude patterns, for wrappers that
+    /// translate keys.
+    pub(crate) fn map_patterns(&mut self, f: impl Fn(&Glob) -> Glob) {
+        self.include = self.include.iter().map(&f).collect();
+        self.exclude = self.exclude.iter().map(&f).collect();
+    }
+
+    /// The most specific prefix of all listed keys.
+    ///
+    /// The longer of [`Self::prefix`] and the common literal prefix of the
+    /// [`Self::include`] patterns. Backends can list this prefix instead of
+    /// [`Self::prefix`] when no delimiter is set, since no other key can
+    /// match.
+    pub fn effective_prefix(&self) -> Option<&str> {
+        let prefix = self.prefix();
+        let Some((first, rest)) = self.include.split_first() else {
+            return prefix;
+        };
+        let mut common = first.literal_prefix();
+        for glob in rest {
+            let other = glob.literal_prefix();
+            let len = common
+                .char_indices()
+                .zip(other.chars())
+                .find(|((_, a), b)| a != b)
+                .map_or(common.len().min(other.len()), |((index, _), _)| index);
+            common = &common[..len];
+        }
+        match prefix {
+            Some(prefix) if !common.starts_with(prefix) => Some(prefix),
+            _ if common.is_empty() => prefix,
+            _ => Some(common),
+        }
+    }
+
+    /// Whether any size, modification time or key pattern filters are set.
+    pub fn has_filters(&self) -> bool {
+        self.min_size.is_some()
+            || self.max_size.is_some()
+            || self.modified_after.is_some()
+            || self.modified_before.is_some()
+            || !self.include.is_empty()
+            || !self.exclude.is_empty()
+    }
+
+    /// Check if an object passes the size, modification time and key
+    /// pattern filters, and sorts after [`Self::start_after`].
+    ///
+    /// Objects with an unknown size or modification time never match a
+    /// filter on that value.
+    ///
+    /// Backends apply this to listed objects, unless they can push the
+    /// filters down to the storage service.
+    pub fn matches(&self, meta: &ObjectMeta) -> bool {
+        fn check<T: std::marker::Copy>(
+            filter: Option<T>,
+            value: Option<T>,
+            f: impl Fn(T, T) -> bool,
+        ) -> bool {
+            match (filter, value) {
+                (None, _) => true,
+                (Some(filter), Some(value)) => f(value, filter),
+                (Some(_), None) => false,
+            }
+        }
+
+        let after = self
+            .start_after
+            .as_deref()
+            .is_none_or(|start| meta.key.as_str() > start);
+
+        let included =
+            self.include.is_empty() || self.include.iter().any(|glob| glob.matches(&meta.key));
+        let excluded = self.exclude.iter().any(|glob| glob.matches(&meta.key));
+
+        after
+            && included
+            && !excluded
+            && check(self.min_size, meta.size, |size, min| size >= min)
+            && check(self.max_size, meta.size, |size, max| size <= max)
+            && check(self.modified_after, meta.updated_at, |at, after| at > after)
+            && check(self.modified_before, meta.updated_at, |at, before| {
+                at < before
+            })
+    }
 }
 
+/// Creates a new stream over the same data on every call.
+pub type StreamFactory = Arc<dyn Fn() -> SizedValueStream + Send + Sync>;
+
 pub enum DataSource {
     Data(Bytes),
+    /// A stream that can only be sent once.
     Stream(SizedValueStream),
+    /// A stream that can be recreated, so a failed put can be sent again.
+    ///
+    /// See [`DataSource::factory`].
+    Factory(StreamFactory),
+}
+
+impl DataSource {
+    /// Create a replayable data source from a function that opens the data.
+    ///
+    /// The function is called for every attempt to send the data, and must
+    /// return the same bytes each time. It should be cheap, with the actual
+    /// work (like opening a file) deferred until the stream is polled.
+    pub fn factory<F>(factory: F) -> Self
+    where
+        F: Fn() -> SizedValueStream + Send + Sync + 'static,
+    {
+        Self::Factory(Arc::new(factory))
+    }
+
+    /// Whether the data can be sent more than once.
+    pub fn is_replayable(&self) -> bool {
+        !matches!(self, Self::Stream(_))
+    }
+
+    /// Clone the data source, if it is replayable.
+    pub fn try_clone(&self) -> Option<Self> {
+        match self {
+            Self::Data(bytes) => Some(Self::Data(bytes.clone())),
+            Self::Stream(_) => None,
+            Self::Factory(factory) => Some(Self::Factory(factory.clone())),
+        }
+    }
+
+    /// The known data length, if available.
+    ///
+    /// For factories this creates (but does not poll) a stream.
+    pub fn size(&self) -> Option<u64> {
+        match self {
+            Self::Data(bytes) => Some(bytes.len() as u64),
+            Self::Stream(stream) => stream.size(),
+            Self::Factory(factory) => factory().size(),
+        }
+    }
+
+    /// Convert the data source into a single stream.
+    ///
+    /// Useful for backends that handle everything except in-memory data as
+    /// a stream.
+    pub fn into_stream(self) -> SizedValueStream {
+        match self {
+            Self::Data(bytes) => {
+                let size = bytes.len() as u64;
+                SizedValueStream::new(stream::once(future::ready(Ok(bytes))).boxed(), size)
+            }
+            Self::Stream(stream) => stream,
+            Self::Factory(factory) => factory(),
+        }
+    }
+
+    /// Apply `f` to the stream, or to every stream created by a factory.
+    ///
+    /// In-memory data is returned unchanged.
+    pub(crate) fn map_stream<F>(self, f: F) -> Self
+    where
+        F: Fn(SizedValueStream) -> SizedValueStream + Send + Sync + 'static,
+    {
+        match self {
+            Self::Data(bytes) => Self::Data(bytes),
+            Self::Stream(stream) => Self::Stream(f(stream)),
+            Self::Factory(factory) => Self::Factory(Arc::new(move || f(factory()))),
+        }
+    }
 }
 
 impl std::fmt::Debug for DataSource {
@@ -249,6 +929,7 @@ impl std::fmt::Debug for DataSource {
         match self {
             Self::Data(_) => f.write_str("DataSource::Data(...)"),
             Self::Stream(v) => f.debug_tuple("Stream").field(&v.size()).finish(),
+            Self::Factory(_) => f.write_str("DataSource::Factory(...)"),
         }
     }
 }
@@ -324,6 +1005,14 @@ impl Conditions {
         Self::default()
     }
 
+    /// Whether no condition is set.
+    pub fn is_empty(&self) -> bool {
+        self.if_match.is_none()
+            && self.if_none_match.is_none()
+            && self.if_modified_since.is_none()
+            && self.if_unmodified_since.is_none()
+    }
+
     pub fn if_not_exists(mut self) -> Self {
         self.if_none_match = Some(MatchValue::Any);
         self
@@ -414,6 +1103,45 @@ pub struct Put {
     pub conditions: Conditions,
     /// Optional MIME type to associate with the object.
     pub mime_type: Option<String>,
+    /// Optional `Cache-Control` header to store with the object.
+    pub cache_control: Option<String>,
+    /// Optional `Content-Disposition` header to store with the object.
+    pub content_disposition: Option<String>,
+    /// Optional `Content-Encoding` header to store with the object.
+    ///
+    /// Only describes the data, which is stored as-is.
+    pub content_encoding: Option<String>,
+    /// Time after which the object expires.
+    ///
+    /// Backends with native support (e.g. the memory store) stop returning
+    /// expired objects and remove them. Others only record the time in
+    /// [`ObjectMeta::expires_at`] if they can, and rely on
+    /// [`ExpiringObjStore`](crate::wrapper::expiry::ExpiringObjStore) to
+    /// remove expired objects.
+    pub expires_at: Option<OffsetDateTime>,
+    /// Modification time to record for the object, instead of the time of
+    /// the write.
+    ///
+    /// Used to keep [`ObjectMeta::updated_at`] when copying objects between
+    /// stores. Only honored by backends that can set it (e.g. the fs store
+    /// with mtime preservation enabled), and ignored otherwise.
+    pub modified_at: Option<OffsetDateTime>,
+}
+
+/// Result of [`crate::ObjStore::send_put_result`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct PutResult {
+    pub meta: ObjectMeta,
+    /// `true` if the put created a new object, `false` if it replaced an
+    /// existing one.
+    pub created: bool,
+}
+
+impl PutResult {
+    pub fn new(meta: ObjectMeta, created: bool) -> Self {
+        Self { meta, created }
+    }
 }
 
 /// Request to copy an object from one key to another.
@@ -426,6 +1154,10 @@ pub struct Copy {
     pub target_key: String,
     /// Conditions to apply to the copy operation.
     pub conditions: Conditions,
+    /// Metadata for the destination.
+    ///
+    /// `None` copies the metadata of the source object.
+    pub metadata: Option<CopyMetadata>,
     // TODO: add source/target bucket support?
 }
 
@@ -436,10 +1168,62 @@ impl Copy {
             source_key: src.into(),
             target_key: dest.into(),
             conditions: Conditions::default(),
+            metadata: None,
         }
     }
 }
 
+/// Replacement metadata for the destination of a [`Copy`].
+///
+/// Replaces all metadata of the source object: fields left as `None` are
+/// cleared on the destination rather than inherited.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct CopyMetadata {
+    /// MIME content type.
+    pub mime_type: Option<String>,
+    /// `Cache-Control` header value.
+    pub cache_control: Option<String>,
+    /// `Content-Disposition` header value.
+    pub content_disposition: Option<String>,
+    /// `Content-Encoding` header value.
+    pub content_encoding: Option<String>,
+}
+
+impl CopyMetadata {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_mime_type(mut self, mime_type: impl Into<String>) -> Self {
+        self.mime_type = Some(mime_type.into());
+        self
+    }
+
+    pub fn with_cache_control(mut self, cache_control: impl Into<String>) -> Self {
+        self.cache_control = Some(cache_control.into());
+        self
+    }
+
+    pub fn with_content_disposition(mut self, content_disposition: impl Into<String>) -> Self {
+        self.content_disposition = Some(content_disposition.into());
+        self
+    }
+
+    pub fn with_content_encoding(mut self, content_encoding: impl Into<String>) -> Self {
+        self.content_encoding = Some(content_encoding.into());
+        self
+    }
+
+    /// Apply the metadata to the metadata of a copied object.
+    pub fn apply(&self, meta: &mut ObjectMeta) {
+        meta.mime_type = self.mime_type.clone();
+        meta.cache_control = self.cache_control.clone();
+        meta.content_disposition = self.content_disposition.clone();
+        meta.content_encoding = self.content_encoding.clone();
+    }
+}
+
 impl Put {
     pub fn new(key: impl Into<String>, data: impl Into<DataSource>) -> Self {
         Self {
@@ -447,8 +1231,31 @@ impl Put {
             data: data.into(),
             conditions: Conditions::default(),
             mime_type: None,
+            cache_control: None,
+            content_disposition: None,
+            content_encoding: None,
+            expires_at: None,
+            modified_at: None,
         }
     }
+
+    /// Clone the request, so it can be sent again.
+    ///
+    /// Returns `None` if the data is a one-shot stream, see
+    /// [`DataSource::is_replayable`].
+    pub fn try_clone(&self) -> Option<Self> {
+        Some(Self {
+            key: self.key.clone(),
+            data: self.data.try_clone()?,
+            conditions: self.conditions.clone(),
+            mime_type: self.mime_type.clone(),
+            cache_control: self.cache_control.clone(),
+            content_disposition: self.content_disposition.clone(),
+            content_encoding: self.content_encoding.clone(),
+            expires_at: self.expires_at,
+            modified_at: self.modified_at,
+        })
+    }
 }
 
 /// Arguments for generating a download URL for an object.
@@ -516,7 +1323,86 @@ impl UploadUrlArgs {
 
 #[cfg(test)]
 mod tests {
-    use super::{Conditions, MatchValue};
+    use time::OffsetDateTime;
+
+    use super::{Conditions, ListArgs, MatchValue, ObjectMeta};
+
+    #[test]
+    fn list_args_filters_match_size_and_mtime() {
+        let now = OffsetDateTime::now_utc();
+        let mut meta = ObjectMeta::new("key".to_string());
+        meta.size = Some(100);
+        meta.updated_at = Some(now);
+
+        assert!(ListArgs::new().matches(&meta));
+        assert!(ListArgs::new().with_min_size(100).matches(&meta));
+        assert!(!ListArgs::new().with_min_size(101).matches(&meta));
+        assert!(ListArgs::new().with_max_size(100).matches(&meta));
+        assert!(!ListArgs::new().with_max_size(99).matches(&meta));
+
+        let hour = time::Duration::hours(1);
+        assert!(
+            ListArgs::new()
+                .with_modified_after(now - hour)
+                .matches(&meta)
+        );
+        assert!(!ListArgs::new().with_modified_after(now).matches(&meta));
+        assert!(
+            ListArgs::new()
+                .with_modified_before(now + hour)
+                .matches(&meta)
+        );
+        assert!(!ListArgs::new().with_modified_before(now).matches(&meta));
+
+        let unknown = ObjectMeta::new("unknown".to_string());
+        assert!(!ListArgs::new().with_min_size(0).matches(&unknown));
+        assert!(!ListArgs::new().with_modified_before(now).matches(&unknown));
+    }
+
+    #[test]
+    fn list_args_key_patterns() {
+        let glob = |pattern: &str| crate::Glob::new(pattern).unwrap();
+        let meta = |key: &str| ObjectMeta::new(key.to_string());
+
+        let args = ListArgs::new()
+            .with_include(glob("logs/**/*.json"))
+            .with_include(glob("logs/*.txt"))
+            .with_exclude(glob("**/tmp-*"));
+        assert!(args.has_filters());
+        assert!(args.matches(&meta("logs/a/b.json")));
+        assert!(args.matches(&meta("logs/a.txt")));
+        assert!(!args.matches(&meta("logs/a/b.txt")));
+        assert!(!args.matches(&meta("logs/a/tmp-b.json")));
+        assert!(!args.matches(&meta("other/a.json")));
+        assert_eq!(args.effective_prefix(), Some("logs/"));
+
+        let args = ListArgs::new().with_exclude(glob("*.tmp"));
+        assert!(args.matches(&meta("a/b.tmp")));
+        assert!(!args.matches(&meta("b.tmp")));
+        assert_eq!(args.effective_prefix(), None);
+
+        let args = ListArgs::new()
+            .with_prefix("data/")
+            .with_include(glob("data/2024-*"));
+        assert_eq!(args.effective_prefix(), Some("data/2024-"));
+        let args = ListArgs::new()
+            .with_prefix("data/")
+            .with_include(glob("*.json"));
+        assert_eq!(args.effective_prefix(), Some("data/"));
+        let args = ListArgs::new()
+            .with_prefix("data/2024")
+            .with_include(glob("data/**"));
+        assert_eq!(args.effective_prefix(), Some("data/2024"));
+    }
+
+    #[test]
+    fn list_args_start_after_matches_later_keys() {
+        let meta = ObjectMeta::new("b".to_string());
+        assert!(ListArgs::new().with_start_after("a").matches(&meta));
+        assert!(!ListArgs::new().with_start_after("b").matches(&meta));
+        assert!(!ListArgs::new().with_start_after("c").matches(&meta));
+        assert!(ListArgs::new().with_start_after("").matches(&meta));
+    }
 
     #[test]
     fn if_not_exists_sets_if_none_match_any() {
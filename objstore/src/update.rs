@@ -0,0 +1,59 @@
+//! Optimistic read-modify-write updates.
+//!
+//! See [`crate::ObjStoreExt::update_json`].
+
+use bytes::Bytes;
+
+use crate::{Conditions, DataSource, ObjStore, ObjStoreError, ObjectMeta, Operation, Put, Result};
+
+/// Maximum number of attempts for [`crate::ObjStoreExt::update_json`].
+pub(crate) const UPDATE_MAX_ATTEMPTS: u32 = 10;
+
+pub(crate) async fn update_json<S, T, F>(store: &S, key: &str, mut f: F) -> Result<ObjectMeta>
+where
+    S: ObjStore + ?Sized,
+    T: serde::Serialize + serde::de::DeserializeOwned,
+    F: FnMut(Option<T>) -> T,
+{
+    let mut attempt = 1;
+    loop {
+        let (current, conditions) = match store.get_with_meta(key).await? {
+            Some((data, meta)) => {
+                let Some(etag) = meta.etag else {
+                    return Err(ObjStoreError::Unsupported {
+                        operation: Operation::Put,
+                        source: Some(
+                            format!("object '{key}' has no etag, conditional update not possible")
+                                .into(),
+                        ),
+                    });
+                };
+                let value = crate::store::deserialize_json(key, &data)?;
+                (Some(value), Conditions::new().if_match_tags([etag]))
+            }
+            None => (None, Conditions::new().if_not_exists()),
+        };
+
+        let new_value = f(current);
+        let data =
+            serde_json::to_vec(&new_value).map_err(|source| ObjStoreError::InvalidRequest {
+                message: "could not serialize JSON data for put".to_string(),
+                source: Some(source.into()),
+            })?;
+
+        let mut put = Put::new(key.to_string(), DataSource::Data(Bytes::from(data)));
+        put.conditions = conditions;
+        put.mime_type = Some("application/json".to_string());
+
+        match store.send_put(put).await {
+            Ok(meta) => return Ok(meta),
+            // Another writer modified (or created) the object in the meantime.
+            Err(ObjStoreError::PreconditionFailed { .. } | ObjStoreError::AlreadyExists { .. })
+                if attempt < UPDATE_MAX_ATTEMPTS =>
+            {
+                attempt += 1;
+            }
+            Err(err) => return Err(err),
+        }
+    }
+}
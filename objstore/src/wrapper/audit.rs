@@ -0,0 +1,603 @@
+//! Audit trail of all mutations made through a store.
+//!
+//! See [`AuditedObjStore`] and [`verify_audit_log`].
+
+use std::{
+    sync::{Arc, Mutex, MutexGuard},
+    time::Duration,
+};
+
+use bytes::Bytes;
+use futures::TryStreamExt as _;
+use sha2::{Digest as _, Sha256};
+use time::OffsetDateTime;
+
+use crate::{
+    Copy, DownloadUrlArgs, DynObjStore, EventStream, GetManyOptions, GetManyStream, HealthReport,
+    KeyPage, ListArgs, ObjStore, ObjStoreError, ObjectKey, ObjectMeta, ObjectMetaPage, Put,
+    PutResult, Result, SortOrder, UploadUrlArgs, Usage, ValueStream,
+    maintenance::{CleanupOptions, CleanupReport},
+    store::list_pages,
+};
+
+/// Kind of mutation recorded in an [`AuditRecord`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[non_exhaustive]
+pub enum AuditOp {
+    Put,
+    Append,
+    Copy,
+    Delete,
+    DeletePrefix,
+}
+
+/// A single mutation, stored as one line of an NDJSON audit batch.
+///
+/// Records form a hash chain: [`Self::hash`] covers all other fields,
+/// including the hash of the previous record, so removed or modified records
+/// are detected by [`verify_audit_log`].
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[non_exhaustive]
+pub struct AuditRecord {
+    /// Position in the chain, starting at `0` for every wrapper instance.
+    pub seq: u64,
+    /// Time of the mutation as a unix timestamp in milliseconds.
+    pub time: i64,
+    /// Writer identity, see [`AuditOptions::with_actor`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub actor: Option<String>,
+    pub op: AuditOp,
+    /// The changed key, or the prefix for [`AuditOp::DeletePrefix`].
+    pub key: String,
+    /// Source key of a copy.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub size: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub etag: Option<String>,
+    /// Hex sha256 hash of the previous record, empty for the first record.
+    pub prev_hash: String,
+    /// Hex sha256 hash of this record.
+    pub hash: String,
+}
+
+impl AuditRecord {
+    /// Compute the chain hash of the record, ignoring [`Self::hash`].
+    pub fn compute_hash(&self) -> String {
+        let mut record = self.clone();
+        record.hash = String::new();
+        let data = serde_json::to_vec(&record).expect("audit records always serialize");
+        format!("{:x}", Sha256::digest(&data))
+    }
+}
+
+/// Options for [`AuditedObjStore`].
+#[derive(Clone, Debug)]
+pub struct AuditOptions {
+    prefix: String,
+    actor: Option<String>,
+    max_batch_records: usize,
+}
+
+impl Default for AuditOptions {
+    fn default() -> Self {
+        Self {
+            prefix: Self::DEFAULT_PREFIX.to_string(),
+            actor: None,
+            max_batch_records: Self::DEFAULT_MAX_BATCH_RECORDS,
+        }
+    }
+}
+
+impl AuditOptions {
+    pub const DEFAULT_PREFIX: &str = "_audit/";
+    pub const DEFAULT_MAX_BATCH_RECORDS: usize = 100;
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Prefix below which audit batches are written.
+    pub fn prefix(&self) -> &str {
+        &self.prefix
+    }
+
+    /// Set the audit prefix. A trailing `/` is added if missing, and an
+    /// empty prefix is ignored.
+    pub fn with_prefix(mut self, prefix: impl Into<String>) -> Self {
+        let mut prefix = prefix.into();
+        if !prefix.is_empty() {
+            if !prefix.ends_with('/') {
+                prefix.push('/');
+            }
+            self.prefix = prefix;
+        }
+        self
+    }
+
+    /// Writer identity stored in every record.
+    pub fn actor(&self) -> Option<&str> {
+        self.actor.as_deref()
+    }
+
+    /// Set the writer identity, like a user or service name.
+    pub fn with_actor(mut self, actor: impl Into<String>) -> Self {
+        self.actor = Some(actor.into());
+        self
+    }
+
+    /// Number of buffered records that triggers writing a batch.
+    pub fn max_batch_records(&self) -> usize {
+        self.max_batch_records
+    }
+
+    /// Set the number of records per batch. Values of `0` are ignored.
+    pub fn with_max_batch_records(mut self, records: usize) -> Self {
+        if records > 0 {
+            self.max_batch_records = records;
+        }
+        self
+    }
+}
+
+#[derive(Debug)]
+struct AuditState {
+    /// Records not yet written, ordered by sequence number.
+    pending: Vec<AuditRecord>,
+    next_seq: u64,
+    last_hash: String,
+}
+
+/// Wrapper that records every mutation in an append-only audit log.
+///
+/// Successful puts, appends, copies and deletes are recorded as
+/// [`AuditRecord`]s. Records are buffered and written in batches of
+/// [`AuditOptions::max_batch_records`], so auditing does not double the
+/// number of requests. Call [`Self::flush`] to write buffered records, for
+/// example before shutting down, or spawn [`Self::run_flusher`].
+///
+/// Every batch is an NDJSON object at
+/// `<prefix><session>/<first seq>.ndjson`, where the session is the creation
+/// time of the wrapper in nanoseconds, and each session forms its own hash
+/// chain.
+///
+/// The log is written to the wrapped store by default, or to a separate
+/// store (see [`Self::with_audit_store`]). When written to the wrapped store,
+/// mutations below the audit prefix are rejected.
+#[derive(Debug)]
+pub struct AuditedObjStore<S> {
+    inner: Arc<S>,
+    audit: DynObjStore,
+    separate: bool,
+    options: AuditOptions,
+    session: String,
+    state: Arc<Mutex<AuditState>>,
+}
+
+impl<S> Clone for AuditedObjStore<S> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            audit: self.audit.clone(),
+            separate: self.separate,
+            options: self.options.clone(),
+            session: self.session.clone(),
+            state: self.state.clone(),
+        }
+    }
+}
+
+impl<S> AuditedObjStore<S>
+where
+    S: ObjStore + Send + Sync + 'static,
+{
+    /// Default interval between flushes for [`Self::run_flusher`].
+    pub const DEFAULT_INTERVAL: Duration = Duration::from_secs(10);
+
+    /// Audit mutations of `inner`, writing the log below the audit prefix of
+    /// `inner` itself.
+    pub fn new(inner: S, options: AuditOptions) -> Self {
+        let inner = Arc::new(inner);
+        Self::build(inner.clone(), inner, false, options)
+    }
+
+    /// Audit mutations of `inner`, writing the log to `audit`.
+    pub fn with_audit_store(inner: S, audit: DynObjStore, options: AuditOptions) -> Self {
+        Self::build(Arc::new(inner), audit, true, options)
+    }
+
+    fn build(inner: Arc<S>, audit: DynObjStore, separate: bool, options: AuditOptions) -> Self {
+        let session = OffsetDateTime::now_utc().unix_timestamp_nanos();
+        Self {
+            inner,
+            audit,
+            separate,
+            options,
+            session: format!("{session:020}"),
+            state: Arc::new(Mutex::new(AuditState {
+                pending: Vec::new(),
+                next_seq: 0,
+                last_hash: String::new(),
+            })),
+        }
+    }
+
+    pub fn inner(&self) -> &S {
+        &self.inner
+    }
+
+    pub fn options(&self) -> &AuditOptions {
+        &self.options
+    }
+
+    /// Number of records not yet written.
+    pub fn pending(&self) -> usize {
+        self.state().pending.len()
+    }
+
+    /// Write all buffered records.
+    ///
+    /// Records of failed batches stay buffered and are retried by the next
+    /// flush.
+    pub async fn flush(&self) -> Result<()> {
+        let records = std::mem::take(&mut self.state().pending);
+        let mut batches = records
+            .chunks(self.options.max_batch_records)
+            .map(<[AuditRecord]>::to_vec)
+            .collect::<Vec<_>>()
+            .into_iter();
+
+        while let Some(batch) = batches.next() {
+            if let Err(err) = self.write_batch(&batch).await {
+                let mut failed = batch;
+                failed.extend(batches.flatten());
+                let mut state = self.state();
+                failed.append(&mut state.pending);
+                state.pending = failed;
+                return Err(err);
+            }
+        }
+        Ok(())
+    }
+
+    /// Run [`Self::flush`] every `interval`, forever.
+    ///
+    /// Meant to be spawned as a background task. Flush errors are logged
+    /// (with the `tracing` feature) and retried on the next tick.
+    pub async fn run_flusher(self, interval: Duration) {
+        loop {
+            tokio::time::sleep(interval).await;
+            #[cfg_attr(not(feature = "tracing"), allow(unused_variables))]
+            if let Err(err) = self.flush().await {
+                #[cfg(feature = "tracing")]
+                tracing::warn!(error = %err, "writing audit records failed");
+            }
+        }
+    }
+
+    async fn write_batch(&self, batch: &[AuditRecord]) -> Result<()> {
+        let Some(first) = batch.first() else {
+            return Ok(());
+        };
+        let mut data = Vec::new();
+        for record in batch {
+            serde_json::to_writer(&mut data, record).map_err(|err| ObjStoreError::Internal {
+                message: "could not serialize audit record".to_string(),
+                source: Some(err.into()),
+            })?;
+            data.push(b'\n');
+        }
+
+        let key = format!(
+            "{}{}/{:020}.ndjson",
+            self.options.prefix, self.session, first.seq
+        );
+        let mut put = Put::new(key, Bytes::from(data));
+        put.mime_type = Some("application/x-ndjson".to_string());
+        self.audit.send_put(put).await?;
+        Ok(())
+    }
+
+    fn state(&self) -> MutexGuard<'_, AuditState> {
+        // The state is always left consistent, so poisoning can be ignored.
+        self.state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Reject writes to the audit log through the wrapper.
+    fn check_key(&self, key: &str) -> Result<()> {
+        if !self.separate && key.starts_with(&self.options.prefix) {
+            return Err(ObjStoreError::InvalidRequest {
+                message: format!("key '{key}' is inside the audit prefix"),
+                source: None,
+            });
+        }
+        Ok(())
+    }
+
+    fn check_prefix(&self, prefix: &str) -> Result<()> {
+        if !self.separate && self.options.prefix.starts_with(prefix) {
+            return Err(ObjStoreError::InvalidRequest {
+                message: format!("prefix '{prefix}' contains the audit prefix"),
+                source: None,
+            });
+        }
+        self.check_key(prefix)
+    }
+
+    async fn record(
+        &self,
+        op: AuditOp,
+        key: String,
+        source: Option<String>,
+        meta: Option<&ObjectMeta>,
+    ) {
+        let full = {
+            let mut state = self.state();
+            let mut record = AuditRecord {
+                seq: state.next_seq,
+                time: (OffsetDateTime::now_utc().unix_timestamp_nanos() / 1_000_000) as i64,
+                actor: self.options.actor.clone(),
+                op,
+                key,
+                source,
+                size: meta.and_then(|meta| meta.size),
+                etag: meta.and_then(|meta| meta.etag.clone()),
+                prev_hash: std::mem::take(&mut state.last_hash),
+                hash: String::new(),
+            };
+            record.hash = record.compute_hash();
+            state.next_seq += 1;
+            state.last_hash = record.hash.clone();
+            state.pending.push(record);
+            state.pending.len() >= self.options.max_batch_records
+        };
+
+        #[cfg_attr(not(feature = "tracing"), allow(unused_variables))]
+        if full && let Err(err) = self.flush().await {
+            // The mutation already succeeded, the records are retried later.
+            #[cfg(feature = "tracing")]
+            tracing::warn!(error = %err, "writing audit records failed");
+        }
+    }
+}
+
+/// Read and verify the audit log below `prefix` of `store`.
+///
+/// Checks that every session is a complete hash chain, starting at sequence
+/// number `0`. Fails with [`ObjStoreError::InvalidMetadata`] if a record was
+/// modified or removed.
+///
+/// Returns all records, ordered by session and sequence number.
+pub async fn verify_audit_log<S>(store: &S, prefix: &str) -> Result<Vec<AuditRecord>>
+where
+    S: ObjStore + ?Sized,
+{
+    let mut keys = Vec::new();
+    let mut pages = list_pages(store, ListArgs::new().with_prefix(prefix));
+    while let Some(page) = pages.try_next().await? {
+        keys.extend(page.items.into_iter().map(|meta| meta.key));
+    }
+    keys.sort();
+
+    let mut records = Vec::new();
+    let mut session = None;
+    let mut prev: Option<AuditRecord> = None;
+    for key in keys {
+        let key_session = key.rsplit_once('/').map(|(session, _)| session.to_string());
+        if key_session != session {
+            session = key_session;
+            prev = None;
+        }
+
+        let data = store
+            .get(&key)
+            .await?
+            .ok_or_else(|| ObjStoreError::object_not_found(&key))?;
+        for line in data.split(|byte| *byte == b'\n') {
+            if line.is_empty() {
+                continue;
+            }
+            let record: AuditRecord = serde_json::from_slice(line).map_err(|err| {
+                ObjStoreError::ContentDeserialization {
+                    key: key.clone(),
+                    format: "ndjson".to_string(),
+                    source: Some(err.into()),
+                }
+            })?;
+
+            let (seq, prev_hash) = match &prev {
+                Some(prev) => (prev.seq + 1, prev.hash.as_str()),
+                None => (0, ""),
+            };
+            let message = if record.seq != seq {
+                Some(format!("expected audit record {seq}, found {}", record.seq))
+            } else if record.prev_hash != prev_hash {
+                Some(format!(
+                    "audit record {seq} does not follow its predecessor"
+                ))
+            } else if record.hash != record.compute_hash() {
+                Some(format!("audit record {seq} was modified"))
+            } else {
+                None
+            };
+            if let Some(message) = message {
+                return Err(ObjStoreError::InvalidMetadata {
+                    key,
+                    message,
+                    source: None,
+                });
+            }
+
+            prev = Some(record.clone());
+            records.push(record);
+        }
+    }
+    Ok(records)
+}
+
+#[async_trait::async_trait]
+impl<S> ObjStore for AuditedObjStore<S>
+where
+    S: ObjStore + Send + Sync + 'static,
+{
+    fn kind(&self) -> &str {
+        self.inner.kind()
+    }
+
+    fn safe_uri(&self) -> &url::Url {
+        self.inner.safe_uri()
+    }
+
+    async fn healthcheck(&self) -> Result<()> {
+        self.inner.healthcheck().await
+    }
+
+    async fn healthcheck_report(&self) -> Result<HealthReport> {
+        self.inner.healthcheck_report().await
+    }
+
+    async fn meta(&self, key: &str) -> Result<Option<ObjectMeta>> {
+        self.inner.meta(key).await
+    }
+
+    async fn exists(&self, key: &str) -> Result<bool> {
+        self.inner.exists(key).await
+    }
+
+    async fn meta_raw(&self, key: &ObjectKey) -> Result<Option<ObjectMeta>> {
+        self.inner.meta_raw(key).await
+    }
+
+    async fn get_raw(&self, key: &ObjectKey) -> Result<Option<Bytes>> {
+        self.inner.get_raw(key).await
+    }
+
+    async fn delete_raw(&self, key: &ObjectKey) -> Result<()> {
+        let display = key.display().into_owned();
+        self.check_key(&display)?;
+        self.inner.delete_raw(key).await?;
+        self.record(AuditOp::Delete, display, None, None).await;
+        Ok(())
+    }
+
+    async fn get(&self, key: &str) -> Result<Option<Bytes>> {
+        self.inner.get(key).await
+    }
+
+    async fn get_stream(&self, key: &str) -> Result<Option<ValueStream>> {
+        self.inner.get_stream(key).await
+    }
+
+    async fn get_with_meta(&self, key: &str) -> Result<Option<(Bytes, ObjectMeta)>> {
+        self.inner.get_with_meta(key).await
+    }
+
+    async fn get_stream_with_meta(&self, key: &str) -> Result<Option<(ObjectMeta, ValueStream)>> {
+        self.inner.get_stream_with_meta(key).await
+    }
+
+    async fn get_range(&self, key: &str, range: std::ops::Range<u64>) -> Result<Option<Bytes>> {
+        self.inner.get_range(key, range).await
+    }
+
+    fn get_many<'a>(&'a self, keys: Vec<String>, options: GetManyOptions) -> GetManyStream<'a> {
+        self.inner.get_many(keys, options)
+    }
+
+    async fn generate_download_url(&self, args: DownloadUrlArgs) -> Result<Option<url::Url>> {
+        self.inner.generate_download_url(args).await
+    }
+
+    async fn generate_upload_url(&self, args: UploadUrlArgs) -> Result<Option<url::Url>> {
+        // Uploads via URL bypass the wrapper and could not be audited.
+        let _ = args;
+        Ok(None)
+    }
+
+    async fn send_put(&self, put: Put) -> Result<ObjectMeta> {
+        self.check_key(&put.key)?;
+        let meta = self.inner.send_put(put).await?;
+        self.record(AuditOp::Put, meta.key.clone(), None, Some(&meta))
+            .await;
+        Ok(meta)
+    }
+
+    async fn send_put_result(&self, put: Put) -> Result<PutResult> {
+        self.check_key(&put.key)?;
+        let result = self.inner.send_put_result(put).await?;
+        self.record(
+            AuditOp::Put,
+            result.meta.key.clone(),
+            None,
+            Some(&result.meta),
+        )
+        .await;
+        Ok(result)
+    }
+
+    async fn append(&self, key: &str, data: Bytes) -> Result<ObjectMeta> {
+        self.check_key(key)?;
+        let meta = self.inner.append(key, data).await?;
+        self.record(AuditOp::Append, key.to_string(), None, Some(&meta))
+            .await;
+        Ok(meta)
+    }
+
+    async fn send_copy(&self, copy: Copy) -> Result<ObjectMeta> {
+        self.check_key(&copy.target_key)?;
+        let source = copy.source_key.clone();
+        let target = copy.target_key.clone();
+        let meta = self.inner.send_copy(copy).await?;
+        self.record(AuditOp::Copy, target, Some(source), Some(&meta))
+            .await;
+        Ok(meta)
+    }
+
+    async fn delete(&self, key: &str) -> Result<()> {
+        self.check_key(key)?;
+        self.inner.delete(key).await?;
+        self.record(AuditOp::Delete, key.to_string(), None, None)
+            .await;
+        Ok(())
+    }
+
+    async fn delete_prefix(&self, prefix: &str) -> Result<()> {
+        self.check_prefix(prefix)?;
+        self.inner.delete_prefix(prefix).await?;
+        self.record(AuditOp::DeletePrefix, prefix.to_string(), None, None)
+            .await;
+        Ok(())
+    }
+
+    async fn usage(&self, prefix: &str) -> Result<Usage> {
+        self.inner.usage(prefix).await
+    }
+
+    async fn cleanup(&self, options: CleanupOptions) -> Result<CleanupReport> {
+        self.inner.cleanup(options).await
+    }
+
+    fn supports_sort(&self, order: SortOrder) -> bool {
+        self.inner.supports_sort(order)
+    }
+
+    fn supports_conditions(&self) -> bool {
+        self.inner.supports_conditions()
+    }
+
+    async fn watch(&self, prefix: &str) -> Result<EventStream> {
+        self.inner.watch(prefix).await
+    }
+
+    async fn list(&self, args: ListArgs) -> Result<ObjectMetaPage> {
+        self.inner.list(args).await
+    }
+
+    async fn list_keys(&self, args: ListArgs) -> Result<KeyPage> {
+        self.inner.list_keys(args).await
+    }
+}
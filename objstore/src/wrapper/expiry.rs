@@ -0,0 +1,403 @@
+use std::{
+    collections::BTreeMap,
+    sync::{Arc, Mutex, MutexGuard},
+    time::Duration,
+};
+
+use bytes::Bytes;
+use futures::{StreamExt as _, TryStreamExt as _};
+use time::OffsetDateTime;
+
+use crate::{
+    Copy, DownloadUrlArgs, EventStream, GetManyOptions, GetManyStream, HealthReport, KeyPage,
+    ListArgs, ObjStore, ObjStoreError, ObjectKey, ObjectMeta, ObjectMetaPage, Put, PutResult,
+    Result, SortOrder, UploadUrlArgs, Usage, ValueStream,
+    maintenance::{CleanupOptions, CleanupReport},
+    store::list_pages,
+};
+
+/// Wrapper that emulates [object expiration](Put::expires_at) for backends
+/// without native support.
+///
+/// Expiration times of objects written through the wrapper are kept in
+/// memory. Expired objects are hidden from reads right away, and removed from
+/// the wrapped store by [`Self::sweep`], usually called periodically via
+/// [`Self::run_sweeper`].
+///
+/// Objects whose [`ObjectMeta::expires_at`] is reported in listings are also
+/// hidden from listings and removed by sweeps, which covers objects written
+/// before the wrapper was created. S3 listings do not include user metadata,
+/// so there only expiration times recorded by this wrapper are swept.
+#[derive(Debug)]
+pub struct ExpiringObjStore<S> {
+    inner: Arc<S>,
+    deadlines: Arc<Mutex<BTreeMap<String, Deadline>>>,
+}
+
+/// Expiration time recorded for an object written through the wrapper.
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Deadline {
+    at: OffsetDateTime,
+    /// Etag of the written object, used to detect rewrites that bypassed
+    /// the wrapper.
+    etag: Option<String>,
+}
+
+impl<S> Clone for ExpiringObjStore<S> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            deadlines: self.deadlines.clone(),
+        }
+    }
+}
+
+impl<S> ExpiringObjStore<S> {
+    /// Default interval between sweeps for [`Self::run_sweeper`].
+    pub const DEFAULT_INTERVAL: Duration = Duration::from_secs(60);
+
+    pub fn new(inner: S) -> Self {
+        Self {
+            inner: Arc::new(inner),
+            deadlines: Arc::new(Mutex::new(BTreeMap::new())),
+        }
+    }
+
+    pub fn inner(&self) -> &S {
+        &self.inner
+    }
+
+    fn deadlines(&self) -> MutexGuard<'_, BTreeMap<String, Deadline>> {
+        // The map is always left consistent, so poisoning can be ignored.
+        self.deadlines
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn is_expired(&self, key: &str) -> bool {
+        self.deadlines()
+            .get(key)
+            .is_some_and(|deadline| deadline.at <= OffsetDateTime::now_utc())
+    }
+
+    fn forget(&self, key: &str) {
+        self.deadlines().remove(key);
+    }
+
+    /// Track the deadline of a put, or forget it if the put has none.
+    fn track_put(&self, key: String, expires_at: Option<OffsetDateTime>, meta: &mut ObjectMeta) {
+        match expires_at {
+            Some(expires_at) => {
+                let deadline = Deadline {
+                    at: expires_at,
+                    etag: meta.etag.clone(),
+                };
+                self.deadlines().insert(key, deadline);
+                meta.expires_at.get_or_insert(expires_at);
+            }
+            None => self.forget(&key),
+        }
+    }
+
+    fn forget_prefix(&self, prefix: &str) {
+        self.deadlines().retain(|key, _| !key.starts_with(prefix));
+    }
+}
+
+fn meta_expired(meta: &ObjectMeta, now: OffsetDateTime) -> bool {
+    meta.expires_at.is_some_and(|expires_at| expires_at <= now)
+}
+
+impl<S> ExpiringObjStore<S>
+where
+    S: ObjStore + Send + Sync + 'static,
+{
+    /// Delete all expired objects from the wrapped store.
+    ///
+    /// Lists the whole store to find objects with an expired
+    /// [`ObjectMeta::expires_at`], in addition to the expiration times
+    /// recorded by this wrapper.
+    ///
+    /// The expiration of each object is checked again right before it is
+    /// deleted, so objects rewritten during the sweep are kept.
+    ///
+    /// Returns the number of deleted objects.
+    pub async fn sweep(&self) -> Result<u64> {
+        let now = OffsetDateTime::now_utc();
+        let mut expired = self
+            .deadlines()
+            .iter()
+            .filter(|(_, deadline)| deadline.at <= now)
+            .map(|(key, deadline)| (key.clone(), Some(deadline.clone())))
+            .collect::<BTreeMap<_, _>>();
+
+        let mut pages = list_pages(&*self.inner, ListArgs::new());
+        while let Some(page) = pages.try_next().await? {
+            for meta in page.items {
+                if meta_expired(&meta, now) {
+                    expired.entry(meta.key).or_insert(None);
+                }
+            }
+        }
+
+        let mut deleted = 0;
+        for (key, deadline) in expired {
+            if self.still_expired(&key, deadline.as_ref()).await? {
+                self.inner.delete(&key).await?;
+                deleted += 1;
+            }
+            let mut deadlines = self.deadlines();
+            // Keep the entry if the key was rewritten during the sweep.
+            if deadlines.get(&key) == deadline.as_ref() {
+                deadlines.remove(&key);
+            }
+        }
+        Ok(deleted)
+    }
+
+    /// Check that `key` is still expired right before deleting it.
+    ///
+    /// `deadline` is the deadline recorded by this wrapper when the sweep
+    /// started, or `None` if the object was found by its listed metadata.
+    async fn still_expired(&self, key: &str, deadline: Option<&Deadline>) -> Result<bool> {
+        // Rewritten through the wrapper, which updated or removed the deadline.
+        if self.deadlines().get(key) != deadline {
+            return Ok(false);
+        }
+        let Some(meta) = self.inner.meta(key).await? else {
+            // Already gone, or hidden by native expiration of the wrapped
+            // store, which may still hold the data.
+            return Ok(deadline.is_some());
+        };
+        Ok(match deadline {
+            // Rewritten directly in the wrapped store if the etag changed.
+            Some(deadline) => match (&deadline.etag, &meta.etag) {
+                (Some(recorded), Some(current)) => recorded == current,
+                _ => true,
+            },
+            None => meta_expired(&meta, OffsetDateTime::now_utc()),
+        })
+    }
+
+    /// Run [`Self::sweep`] every `interval`, forever.
+    ///
+    /// Meant to be spawned as a background task. Sweep errors are logged
+    /// (with the `tracing` feature) and retried on the next tick.
+    pub async fn run_sweeper(self, interval: Duration) {
+        loop {
+            tokio::time::sleep(interval).await;
+            #[cfg_attr(not(feature = "tracing"), allow(unused_variables))]
+            if let Err(err) = self.sweep().await {
+                #[cfg(feature = "tracing")]
+                tracing::warn!(error = %err, "sweeping expired objects failed");
+            }
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl<S> ObjStore for ExpiringObjStore<S>
+where
+    S: ObjStore + Send + Sync + 'static,
+{
+    fn kind(&self) -> &str {
+        self.inner.kind()
+    }
+
+    fn safe_uri(&self) -> &url::Url {
+        self.inner.safe_uri()
+    }
+
+    async fn healthcheck(&self) -> Result<()> {
+        self.inner.healthcheck().await
+    }
+
+    async fn healthcheck_report(&self) -> Result<HealthReport> {
+        self.inner.healthcheck_report().await
+    }
+
+    async fn meta(&self, key: &str) -> Result<Option<ObjectMeta>> {
+        if self.is_expired(key) {
+            return Ok(None);
+        }
+        self.inner.meta(key).await
+    }
+
+    async fn exists(&self, key: &str) -> Result<bool> {
+        if self.is_expired(key) {
+            return Ok(false);
+        }
+        self.inner.exists(key).await
+    }
+
+    async fn meta_raw(&self, key: &ObjectKey) -> Result<Option<ObjectMeta>> {
+        if key.as_str().is_some_and(|key| self.is_expired(key)) {
+            return Ok(None);
+        }
+        self.inner.meta_raw(key).await
+    }
+
+    async fn get_raw(&self, key: &ObjectKey) -> Result<Option<Bytes>> {
+        if key.as_str().is_some_and(|key| self.is_expired(key)) {
+            return Ok(None);
+        }
+        self.inner.get_raw(key).await
+    }
+
+    async fn delete_raw(&self, key: &ObjectKey) -> Result<()> {
+        if let Some(key) = key.as_str() {
+            self.forget(key);
+        }
+        self.inner.delete_raw(key).await
+    }
+
+    async fn get(&self, key: &str) -> Result<Option<Bytes>> {
+        if self.is_expired(key) {
+            return Ok(None);
+        }
+        self.inner.get(key).await
+    }
+
+    async fn get_stream(&self, key: &str) -> Result<Option<ValueStream>> {
+        if self.is_expired(key) {
+            return Ok(None);
+        }
+        self.inner.get_stream(key).await
+    }
+
+    async fn get_with_meta(&self, key: &str) -> Result<Option<(Bytes, ObjectMeta)>> {
+        if self.is_expired(key) {
+            return Ok(None);
+        }
+        self.inner.get_with_meta(key).await
+    }
+
+    async fn get_stream_with_meta(&self, key: &str) -> Result<Option<(ObjectMeta, ValueStream)>> {
+        if self.is_expired(key) {
+            return Ok(None);
+        }
+        self.inner.get_stream_with_meta(key).await
+    }
+
+    async fn get_range(&self, key: &str, range: std::ops::Range<u64>) -> Result<Option<Bytes>> {
+        if self.is_expired(key) {
+            return Ok(None);
+        }
+        self.inner.get_range(key, range).await
+    }
+
+    fn get_many<'a>(&'a self, keys: Vec<String>, options: GetManyOptions) -> GetManyStream<'a> {
+        self.inner
+            .get_many(keys, options)
+            .map(|(key, res)| {
+                let res = match res {
+                    Ok(Some(_)) if self.is_expired(&key) => Ok(None),
+                    other => other,
+                };
+                (key, res)
+            })
+            .boxed()
+    }
+
+    async fn generate_download_url(&self, args: DownloadUrlArgs) -> Result<Option<url::Url>> {
+        self.inner.generate_download_url(args).await
+    }
+
+    async fn generate_upload_url(&self, args: UploadUrlArgs) -> Result<Option<url::Url>> {
+        self.inner.generate_upload_url(args).await
+    }
+
+    async fn send_put(&self, put: Put) -> Result<ObjectMeta> {
+        let key = put.key.clone();
+        let expires_at = put.expires_at;
+        let mut meta = self.inner.send_put(put).await?;
+        self.track_put(key, expires_at, &mut meta);
+        Ok(meta)
+    }
+
+    async fn send_put_result(&self, put: Put) -> Result<PutResult> {
+        let key = put.key.clone();
+        let expires_at = put.expires_at;
+        let mut result = self.inner.send_put_result(put).await?;
+        self.track_put(key, expires_at, &mut result.meta);
+        Ok(result)
+    }
+
+    async fn append(&self, key: &str, data: Bytes) -> Result<ObjectMeta> {
+        if self.is_expired(key) {
+            // Start over instead of appending to the expired object.
+            self.delete(key).await?;
+        }
+        self.inner.append(key, data).await
+    }
+
+    async fn send_copy(&self, copy: Copy) -> Result<ObjectMeta> {
+        if self.is_expired(&copy.source_key) {
+            return Err(ObjStoreError::object_not_found(copy.source_key));
+        }
+        let deadline = self.deadlines().get(&copy.source_key).map(|d| d.at);
+        let target_key = copy.target_key.clone();
+        let meta = self.inner.send_copy(copy).await?;
+        match deadline {
+            Some(at) => {
+                let deadline = Deadline {
+                    at,
+                    etag: meta.etag.clone(),
+                };
+                self.deadlines().insert(target_key, deadline);
+            }
+            None => self.forget(&target_key),
+        }
+        Ok(meta)
+    }
+
+    async fn delete(&self, key: &str) -> Result<()> {
+        self.forget(key);
+        self.inner.delete(key).await
+    }
+
+    async fn delete_prefix(&self, prefix: &str) -> Result<()> {
+        self.forget_prefix(prefix);
+        self.inner.delete_prefix(prefix).await
+    }
+
+    async fn usage(&self, prefix: &str) -> Result<Usage> {
+        self.inner.usage(prefix).await
+    }
+
+    async fn cleanup(&self, options: CleanupOptions) -> Result<CleanupReport> {
+        self.inner.cleanup(options).await
+    }
+
+    fn supports_sort(&self, order: SortOrder) -> bool {
+        self.inner.supports_sort(order)
+    }
+
+    fn supports_conditions(&self) -> bool {
+        self.inner.supports_conditions()
+    }
+
+    async fn watch(&self, prefix: &str) -> Result<EventStream> {
+        self.inner.watch(prefix).await
+    }
+
+    async fn list(&self, args: ListArgs) -> Result<ObjectMetaPage> {
+        let mut page = self.inner.list(args).await?;
+        let now = OffsetDateTime::now_utc();
+        let deadlines = self.deadlines();
+        page.items.retain(|meta| {
+            let deadline = deadlines.get(meta.key()).map(|d| d.at).or(meta.expires_at);
+            deadline.is_none_or(|deadline| deadline > now)
+        });
+        Ok(page)
+    }
+
+    async fn list_keys(&self, args: ListArgs) -> Result<KeyPage> {
+        let mut page = self.inner.list_keys(args).await?;
+        let now = OffsetDateTime::now_utc();
+        let deadlines = self.deadlines();
+        page.items
+            .retain(|key| deadlines.get(key).is_none_or(|deadline| deadline.at > now));
+        Ok(page)
+    }
+}
@@ -0,0 +1,297 @@
+//! Write-once-read-many (WORM) semantics for object stores.
+//!
+//! See [`LockedObjStore`].
+
+use std::time::Duration;
+
+use bytes::Bytes;
+use futures::TryStreamExt as _;
+use time::OffsetDateTime;
+
+use crate::{
+    Copy, DownloadUrlArgs, EventStream, GetManyOptions, GetManyStream, HealthReport, KeyPage,
+    ListArgs, ObjStore, ObjStoreError, ObjectKey, ObjectMeta, ObjectMetaPage, Operation, Put,
+    PutResult, Resource, Result, SortOrder, UploadUrlArgs, Usage, ValueStream,
+    maintenance::{CleanupOptions, CleanupReport},
+};
+
+/// How long an object is protected, see [`LockedObjStore::retention`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Retention {
+    /// The object can be changed again after the given time.
+    Until(OffsetDateTime),
+    /// The object can never be changed.
+    Forever,
+}
+
+#[derive(Clone, Debug)]
+struct LockRule {
+    prefix: String,
+    retention: Option<Duration>,
+}
+
+/// Wrapper that makes objects below configured prefixes immutable.
+///
+/// Once an object below a locked prefix is written, overwrites (put, append
+/// and copies onto it) and deletes are rejected with
+/// [`ObjStoreError::PermissionDenied`] until its retention expires. The
+/// retention starts at the modification time of the object, so no extra state
+/// is needed and the lock survives restarts; objects without a known
+/// modification time are locked forever. If multiple prefixes match a key, the
+/// longest one applies.
+///
+/// [`ObjStore::delete_prefix`] fails without deleting anything if any object
+/// below the prefix is locked. Upload URLs are not generated for locked
+/// prefixes, since uploads via URL would bypass the checks.
+///
+/// The checks are not atomic with the writes, and only apply to writes
+/// through the wrapper. They protect against mistakes of the application, not
+/// against other writers of the backend.
+#[derive(Clone, Debug)]
+pub struct LockedObjStore<S> {
+    inner: S,
+    rules: Vec<LockRule>,
+}
+
+impl<S> LockedObjStore<S> {
+    pub fn new(inner: S) -> Self {
+        Self {
+            inner,
+            rules: Vec::new(),
+        }
+    }
+
+    /// Lock objects below `prefix` for `retention` after they were written.
+    ///
+    /// A retention of `None` locks objects forever.
+    pub fn with_lock(mut self, prefix: impl Into<String>, retention: Option<Duration>) -> Self {
+        let prefix = prefix.into();
+        self.rules.retain(|rule| rule.prefix != prefix);
+        self.rules.push(LockRule { prefix, retention });
+        self
+    }
+
+    pub fn inner(&self) -> &S {
+        &self.inner
+    }
+
+    /// The rule with the longest prefix matching `key`.
+    fn rule(&self, key: &[u8]) -> Option<&LockRule> {
+        self.rules
+            .iter()
+            .filter(|rule| key.starts_with(rule.prefix.as_bytes()))
+            .max_by_key(|rule| rule.prefix.len())
+    }
+
+    /// Whether a lock applies to some key below `prefix`.
+    fn overlaps(&self, prefix: &str) -> bool {
+        self.rules
+            .iter()
+            .any(|rule| rule.prefix.starts_with(prefix) || prefix.starts_with(&rule.prefix))
+    }
+
+    fn retention_of(&self, meta: &ObjectMeta, now: OffsetDateTime) -> Option<Retention> {
+        let rule = self.rule(meta.key.as_bytes())?;
+        let (Some(retention), Some(updated_at)) = (rule.retention, meta.updated_at) else {
+            return Some(Retention::Forever);
+        };
+        let until = updated_at + retention;
+        (until > now).then_some(Retention::Until(until))
+    }
+}
+
+fn locked(operation: Operation, key: &str, retention: Retention) -> ObjStoreError {
+    let message = match retention {
+        Retention::Until(until) => format!("object '{key}' is locked until {until}"),
+        Retention::Forever => format!("object '{key}' is locked"),
+    };
+    ObjStoreError::PermissionDenied {
+        operation,
+        resource: Some(Resource::Object {
+            key: key.to_string(),
+        }),
+        source: Some(message.into()),
+    }
+}
+
+impl<S> LockedObjStore<S>
+where
+    S: ObjStore + Send + Sync,
+{
+    /// The remaining retention of the object at `key`.
+    ///
+    /// Returns `None` if the object can be changed, because it does not
+    /// exist, is not below a locked prefix, or its retention expired.
+    pub async fn retention(&self, key: &str) -> Result<Option<Retention>> {
+        if self.rule(key.as_bytes()).is_none() {
+            return Ok(None);
+        }
+        let Some(meta) = self.inner.meta(key).await? else {
+            return Ok(None);
+        };
+        Ok(self.retention_of(&meta, OffsetDateTime::now_utc()))
+    }
+
+    async fn check(&self, operation: Operation, key: &str) -> Result<()> {
+        match self.retention(key).await? {
+            Some(retention) => Err(locked(operation, key, retention)),
+            None => Ok(()),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl<S> ObjStore for LockedObjStore<S>
+where
+    S: ObjStore + Send + Sync,
+{
+    fn kind(&self) -> &str {
+        self.inner.kind()
+    }
+
+    fn safe_uri(&self) -> &url::Url {
+        self.inner.safe_uri()
+    }
+
+    async fn healthcheck(&self) -> Result<()> {
+        self.inner.healthcheck().await
+    }
+
+    async fn healthcheck_report(&self) -> Result<HealthReport> {
+        self.inner.healthcheck_report().await
+    }
+
+    async fn meta(&self, key: &str) -> Result<Option<ObjectMeta>> {
+        self.inner.meta(key).await
+    }
+
+    async fn exists(&self, key: &str) -> Result<bool> {
+        self.inner.exists(key).await
+    }
+
+    async fn get(&self, key: &str) -> Result<Option<Bytes>> {
+        self.inner.get(key).await
+    }
+
+    async fn meta_raw(&self, key: &ObjectKey) -> Result<Option<ObjectMeta>> {
+        self.inner.meta_raw(key).await
+    }
+
+    async fn get_raw(&self, key: &ObjectKey) -> Result<Option<Bytes>> {
+        self.inner.get_raw(key).await
+    }
+
+    async fn delete_raw(&self, key: &ObjectKey) -> Result<()> {
+        if let Some(key) = key.as_str() {
+            return self.delete(key).await;
+        }
+        if self.rule(key.as_bytes()).is_some()
+            && let Some(meta) = self.inner.meta_raw(key).await?
+            && let Some(retention) = self.retention_of(&meta, OffsetDateTime::now_utc())
+        {
+            return Err(locked(Operation::Delete, &meta.key, retention));
+        }
+        self.inner.delete_raw(key).await
+    }
+
+    async fn get_stream(&self, key: &str) -> Result<Option<ValueStream>> {
+        self.inner.get_stream(key).await
+    }
+
+    async fn get_with_meta(&self, key: &str) -> Result<Option<(Bytes, ObjectMeta)>> {
+        self.inner.get_with_meta(key).await
+    }
+
+    async fn get_stream_with_meta(&self, key: &str) -> Result<Option<(ObjectMeta, ValueStream)>> {
+        self.inner.get_stream_with_meta(key).await
+    }
+
+    async fn get_range(&self, key: &str, range: std::ops::Range<u64>) -> Result<Option<Bytes>> {
+        self.inner.get_range(key, range).await
+    }
+
+    fn get_many<'a>(&'a self, keys: Vec<String>, options: GetManyOptions) -> GetManyStream<'a> {
+        self.inner.get_many(keys, options)
+    }
+
+    async fn generate_download_url(&self, args: DownloadUrlArgs) -> Result<Option<url::Url>> {
+        self.inner.generate_download_url(args).await
+    }
+
+    async fn generate_upload_url(&self, args: UploadUrlArgs) -> Result<Option<url::Url>> {
+        // Uploads via URL bypass the lock checks.
+        if self.rule(args.key.as_bytes()).is_some() {
+            return Ok(None);
+        }
+        self.inner.generate_upload_url(args).await
+    }
+
+    async fn send_put(&self, put: Put) -> Result<ObjectMeta> {
+        self.check(Operation::Put, &put.key).await?;
+        self.inner.send_put(put).await
+    }
+
+    async fn send_put_result(&self, put: Put) -> Result<PutResult> {
+        self.check(Operation::Put, &put.key).await?;
+        self.inner.send_put_result(put).await
+    }
+
+    async fn append(&self, key: &str, data: Bytes) -> Result<ObjectMeta> {
+        self.check(Operation::Append, key).await?;
+        self.inner.append(key, data).await
+    }
+
+    async fn send_copy(&self, copy: Copy) -> Result<ObjectMeta> {
+        self.check(Operation::Copy, &copy.target_key).await?;
+        self.inner.send_copy(copy).await
+    }
+
+    async fn delete(&self, key: &str) -> Result<()> {
+        self.check(Operation::Delete, key).await?;
+        self.inner.delete(key).await
+    }
+
+    async fn delete_prefix(&self, prefix: &str) -> Result<()> {
+        if self.overlaps(prefix) {
+            let now = OffsetDateTime::now_utc();
+            let mut items = self
+                .inner
+                .list_items_stream(ListArgs::new().with_prefix(prefix));
+            while let Some(meta) = items.try_next().await? {
+                if let Some(retention) = self.retention_of(&meta, now) {
+                    return Err(locked(Operation::DeletePrefix, &meta.key, retention));
+                }
+            }
+        }
+        self.inner.delete_prefix(prefix).await
+    }
+
+    async fn usage(&self, prefix: &str) -> Result<Usage> {
+        self.inner.usage(prefix).await
+    }
+
+    async fn cleanup(&self, options: CleanupOptions) -> Result<CleanupReport> {
+        self.inner.cleanup(options).await
+    }
+
+    fn supports_sort(&self, order: SortOrder) -> bool {
+        self.inner.supports_sort(order)
+    }
+
+    fn supports_conditions(&self) -> bool {
+        self.inner.supports_conditions()
+    }
+
+    async fn watch(&self, prefix: &str) -> Result<EventStream> {
+        self.inner.watch(prefix).await
+    }
+
+    async fn list(&self, args: ListArgs) -> Result<ObjectMetaPage> {
+        self.inner.list(args).await
+    }
+
+    async fn list_keys(&self, args: ListArgs) -> Result<KeyPage> {
+        self.inner.list_keys(args).await
+    }
+}
@@ -1,4 +1,16 @@
+#[cfg(feature = "audit")]
+pub mod audit;
+#[cfg(feature = "expiry")]
+pub mod expiry;
+pub mod lock;
+pub mod policy;
+#[cfg(feature = "watch")]
+pub mod poll;
 pub mod prefix;
+pub mod shard;
+#[cfg(feature = "tiered")]
+pub mod tiered;
+pub mod versioned;
 
 #[cfg(feature = "tracing")]
 pub mod trace;
@@ -0,0 +1,453 @@
+//! Limits and safety rails for object stores.
+//!
+//! See [`PolicyObjStore`].
+
+use bytes::Bytes;
+use futures::StreamExt as _;
+
+use crate::{
+    Copy, DownloadUrlArgs, EventStream, GetManyOptions, GetManyStream, Glob, HealthReport, KeyPage,
+    ListArgs, ObjStore, ObjStoreError, ObjectKey, ObjectMeta, ObjectMetaPage, Operation, Put,
+    PutResult, Resource, Result, SizedValueStream, SortOrder, UploadUrlArgs, Usage, ValueStream,
+    maintenance::{CleanupOptions, CleanupReport},
+};
+
+/// Characters allowed in keys written through a [`PolicyObjStore`].
+#[derive(Clone, Copy, Debug, Default)]
+#[non_exhaustive]
+pub enum KeyCharset {
+    /// Any key accepted by the backend.
+    #[default]
+    Any,
+    /// ASCII letters and digits, `/` and the special characters `!-_.*'()`.
+    ///
+    /// These are safe to use with all backends and in URLs without escaping.
+    Safe,
+    /// Keys for which the function accepts every character.
+    Custom(fn(char) -> bool),
+}
+
+impl KeyCharset {
+    /// Whether all characters of `key` are allowed.
+    pub fn allows(&self, key: &str) -> bool {
+        key.chars().all(|c| self.allows_char(c))
+    }
+
+    /// Whether the character `c` is allowed.
+    pub fn allows_char(&self, c: char) -> bool {
+        match self {
+            Self::Any => true,
+            Self::Safe => c.is_ascii_alphanumeric() || "/!-_.*'()".contains(c),
+            Self::Custom(allowed) => allowed(c),
+        }
+    }
+}
+
+/// Limits enforced by a [`PolicyObjStore`].
+///
+/// The default policy forbids deleting all objects with
+/// [`ObjStore::purge_all`] or an empty [`ObjStore::delete_prefix`], and caps
+/// list pages at [`Self::DEFAULT_MAX_LIST_PAGE_SIZE`] items.
+/// Use [`Self::unrestricted`] to start without any limits.
+#[derive(Clone, Debug)]
+pub struct StorePolicy {
+    max_object_size: Option<u64>,
+    max_list_page_size: Option<u64>,
+    allow_delete_all: bool,
+    key_charset: KeyCharset,
+    max_key_length: Option<usize>,
+    key_patterns: Vec<Glob>,
+}
+
+impl Default for StorePolicy {
+    fn default() -> Self {
+        Self {
+            max_object_size: None,
+            max_list_page_size: Some(Self::DEFAULT_MAX_LIST_PAGE_SIZE),
+            allow_delete_all: false,
+            key_charset: KeyCharset::Any,
+            max_key_length: None,
+            key_patterns: Vec::new(),
+        }
+    }
+}
+
+impl StorePolicy {
+    pub const DEFAULT_MAX_LIST_PAGE_SIZE: u64 = 1_000;
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A policy that allows everything.
+    pub fn unrestricted() -> Self {
+        Self {
+            max_object_size: None,
+            max_list_page_size: None,
+            allow_delete_all: true,
+            key_charset: KeyCharset::Any,
+            max_key_length: None,
+            key_patterns: Vec::new(),
+        }
+    }
+
+    pub fn max_object_size(&self) -> Option<u64> {
+        self.max_object_size
+    }
+
+    /// Reject writes of objects larger than `bytes`.
+    ///
+    /// Data of unknown size is checked while it is streamed, so the write
+    /// fails once the limit is exceeded.
+    /// A limit of `0` is ignored.
+    pub fn with_max_object_size(mut self, bytes: u64) -> Self {
+        if bytes > 0 {
+            self.max_object_size = Some(bytes);
+        }
+        self
+    }
+
+    pub fn max_list_page_size(&self) -> Option<u64> {
+        self.max_list_page_size
+    }
+
+    /// Cap the number of items requested per list page.
+    ///
+    /// Larger or missing limits in [`ListArgs`] are lowered to this value.
+    /// A size of `0` is ignored.
+    pub fn with_max_list_page_size(mut self, size: u64) -> Self {
+        if size > 0 {
+            self.max_list_page_size = Some(size);
+        }
+        self
+    }
+
+    pub fn allow_delete_all(&self) -> bool {
+        self.allow_delete_all
+    }
+
+    /// Allow [`ObjStore::purge_all`] and [`ObjStore::delete_prefix`] with an
+    /// empty prefix.
+    pub fn with_allow_delete_all(mut self, allow: bool) -> Self {
+        self.allow_delete_all = allow;
+        self
+    }
+
+    pub fn key_charset(&self) -> KeyCharset {
+        self.key_charset
+    }
+
+    /// Restrict the characters of keys that are written.
+    ///
+    /// Existing objects with other keys can still be read and deleted.
+    pub fn with_key_charset(mut self, charset: KeyCharset) -> Self {
+        self.key_charset = charset;
+        self
+    }
+
+    pub fn max_key_length(&self) -> Option<usize> {
+        self.max_key_length
+    }
+
+    /// Reject writes to keys longer than `bytes` (in UTF-8).
+    ///
+    /// A length of `0` is ignored.
+    pub fn with_max_key_length(mut self, bytes: usize) -> Self {
+        if bytes > 0 {
+            self.max_key_length = Some(bytes);
+        }
+        self
+    }
+
+    pub fn key_patterns(&self) -> &[Glob] {
+        &self.key_patterns
+    }
+
+    /// Only allow writes to keys matching `pattern`, like `tenants/*/**`.
+    ///
+    /// Can be called multiple times, keys must match at least one pattern.
+    pub fn with_key_pattern(mut self, pattern: Glob) -> Self {
+        self.key_patterns.push(pattern);
+        self
+    }
+
+    /// Check that `key` follows the naming rules of the policy.
+    pub fn check_key(&self, key: &str) -> Result<()> {
+        let message = if let Some(max) = self.max_key_length
+            && key.len() > max
+        {
+            format!(
+                "key '{key}' is {} bytes long, the store policy allows at most {max}",
+                key.len()
+            )
+        } else if let Some((index, c)) = key
+            .char_indices()
+            .find(|(_, c)| !self.key_charset.allows_char(*c))
+        {
+            format!(
+                "key '{key}' contains the character {c:?} at byte {index}, which is forbidden by the store policy"
+            )
+        } else if !self.key_patterns.is_empty()
+            && !self.key_patterns.iter().any(|glob| glob.matches(key))
+        {
+            let patterns = self
+                .key_patterns
+                .iter()
+                .map(|glob| format!("'{}'", glob.pattern()))
+                .collect::<Vec<_>>()
+                .join(", ");
+            format!(
+                "key '{key}' does not match any allowed pattern of the store policy: {patterns}"
+            )
+        } else {
+            return Ok(());
+        };
+        Err(ObjStoreError::InvalidRequest {
+            message,
+            source: None,
+        })
+    }
+
+    fn check_size(&self, key: &str, size: u64) -> Result<()> {
+        match self.max_object_size {
+            Some(max) if size > max => Err(too_large(key, max)),
+            _ => Ok(()),
+        }
+    }
+
+    fn check_delete_prefix(&self, operation: Operation, prefix: &str) -> Result<()> {
+        if self.allow_delete_all || !prefix.trim_matches('/').is_empty() {
+            return Ok(());
+        }
+        Err(ObjStoreError::PermissionDenied {
+            operation,
+            resource: Some(Resource::Store),
+            source: Some("deleting all objects is forbidden by the store policy".into()),
+        })
+    }
+
+    fn limit_list_args(&self, mut args: ListArgs) -> ListArgs {
+        if let Some(max) = self.max_list_page_size
+            && args.limit().is_none_or(|limit| limit > max)
+        {
+            args.set_limit(max);
+        }
+        args
+    }
+}
+
+fn too_large(key: &str, max: u64) -> ObjStoreError {
+    ObjStoreError::InvalidRequest {
+        message: format!("object '{key}' exceeds the maximum size of {max} bytes"),
+        source: None,
+    }
+}
+
+/// Fail the stream once more than `max` bytes were read.
+fn limit_stream_size(stream: SizedValueStream, key: String, max: u64) -> SizedValueStream {
+    let mut total = 0u64;
+    let stream = stream.into_stream().map(move |chunk| {
+        let chunk = chunk?;
+        total += chunk.len() as u64;
+        if total > max {
+            Err(too_large(&key, max))
+        } else {
+            Ok(chunk)
+        }
+    });
+    SizedValueStream::new_without_size(stream.boxed())
+}
+
+/// Wrapper that enforces a [`StorePolicy`] on all operations.
+///
+/// Meant for frontends like UIs or servers that pass user input to a store,
+/// to prevent accidents like deleting a whole bucket, and to enforce key
+/// naming conventions across all writers.
+#[derive(Clone, Debug)]
+pub struct PolicyObjStore<S> {
+    inner: S,
+    policy: StorePolicy,
+}
+
+impl<S> PolicyObjStore<S> {
+    pub fn new(inner: S, policy: StorePolicy) -> Self {
+        Self { inner, policy }
+    }
+
+    pub fn inner(&self) -> &S {
+        &self.inner
+    }
+
+    pub fn policy(&self) -> &StorePolicy {
+        &self.policy
+    }
+
+    /// Check the key and size of a put.
+    ///
+    /// Streams of unknown size are limited to the maximum object size.
+    fn check_put(&self, mut put: Put) -> Result<Put> {
+        self.policy.check_key(&put.key)?;
+        if let Some(max) = self.policy.max_object_size {
+            match put.data.size() {
+                Some(size) => self.policy.check_size(&put.key, size)?,
+                None => {
+                    let key = put.key.clone();
+                    put.data = put
+                        .data
+                        .map_stream(move |stream| limit_stream_size(stream, key.clone(), max));
+                }
+            }
+        }
+        Ok(put)
+    }
+}
+
+#[async_trait::async_trait]
+impl<S> ObjStore for PolicyObjStore<S>
+where
+    S: ObjStore + Send + Sync,
+{
+    fn kind(&self) -> &str {
+        self.inner.kind()
+    }
+
+    fn safe_uri(&self) -> &url::Url {
+        self.inner.safe_uri()
+    }
+
+    async fn healthcheck(&self) -> Result<()> {
+        self.inner.healthcheck().await
+    }
+
+    async fn healthcheck_report(&self) -> Result<HealthReport> {
+        self.inner.healthcheck_report().await
+    }
+
+    async fn meta(&self, key: &str) -> Result<Option<ObjectMeta>> {
+        self.inner.meta(key).await
+    }
+
+    async fn exists(&self, key: &str) -> Result<bool> {
+        self.inner.exists(key).await
+    }
+
+    async fn get(&self, key: &str) -> Result<Option<Bytes>> {
+        self.inner.get(key).await
+    }
+
+    async fn meta_raw(&self, key: &ObjectKey) -> Result<Option<ObjectMeta>> {
+        self.inner.meta_raw(key).await
+    }
+
+    async fn get_raw(&self, key: &ObjectKey) -> Result<Option<Bytes>> {
+        self.inner.get_raw(key).await
+    }
+
+    async fn delete_raw(&self, key: &ObjectKey) -> Result<()> {
+        self.inner.delete_raw(key).await
+    }
+
+    async fn get_stream(&self, key: &str) -> Result<Option<ValueStream>> {
+        self.inner.get_stream(key).await
+    }
+
+    async fn get_with_meta(&self, key: &str) -> Result<Option<(Bytes, ObjectMeta)>> {
+        self.inner.get_with_meta(key).await
+    }
+
+    async fn get_stream_with_meta(&self, key: &str) -> Result<Option<(ObjectMeta, ValueStream)>> {
+        self.inner.get_stream_with_meta(key).await
+    }
+
+    async fn get_range(&self, key: &str, range: std::ops::Range<u64>) -> Result<Option<Bytes>> {
+        self.inner.get_range(key, range).await
+    }
+
+    fn get_many<'a>(&'a self, keys: Vec<String>, options: GetManyOptions) -> GetManyStream<'a> {
+        self.inner.get_many(keys, options)
+    }
+
+    async fn generate_download_url(&self, args: DownloadUrlArgs) -> Result<Option<url::Url>> {
+        self.inner.generate_download_url(args).await
+    }
+
+    async fn generate_upload_url(&self, args: UploadUrlArgs) -> Result<Option<url::Url>> {
+        self.policy.check_key(&args.key)?;
+        self.inner.generate_upload_url(args).await
+    }
+
+    async fn send_put(&self, put: Put) -> Result<ObjectMeta> {
+        let put = self.check_put(put)?;
+        self.inner.send_put(put).await
+    }
+
+    async fn send_put_result(&self, put: Put) -> Result<PutResult> {
+        let put = self.check_put(put)?;
+        self.inner.send_put_result(put).await
+    }
+
+    async fn append(&self, key: &str, data: Bytes) -> Result<ObjectMeta> {
+        self.policy.check_key(key)?;
+        if self.policy.max_object_size.is_some() {
+            let existing = self
+                .inner
+                .meta(key)
+                .await?
+                .and_then(|meta| meta.size)
+                .unwrap_or_default();
+            self.policy.check_size(key, existing + data.len() as u64)?;
+        }
+        self.inner.append(key, data).await
+    }
+
+    async fn send_copy(&self, copy: Copy) -> Result<ObjectMeta> {
+        self.policy.check_key(&copy.target_key)?;
+        self.inner.send_copy(copy).await
+    }
+
+    async fn delete(&self, key: &str) -> Result<()> {
+        self.inner.delete(key).await
+    }
+
+    async fn delete_prefix(&self, prefix: &str) -> Result<()> {
+        self.policy
+            .check_delete_prefix(Operation::DeletePrefix, prefix)?;
+        self.inner.delete_prefix(prefix).await
+    }
+
+    async fn purge_all(&self) -> Result<()> {
+        self.policy
+            .check_delete_prefix(Operation::DeletePrefix, "")?;
+        self.inner.purge_all().await
+    }
+
+    async fn usage(&self, prefix: &str) -> Result<Usage> {
+        self.inner.usage(prefix).await
+    }
+
+    async fn cleanup(&self, options: CleanupOptions) -> Result<CleanupReport> {
+        self.inner.cleanup(options).await
+    }
+
+    fn supports_sort(&self, order: SortOrder) -> bool {
+        self.inner.supports_sort(order)
+    }
+
+    fn supports_conditions(&self) -> bool {
+        self.inner.supports_conditions()
+    }
+
+    async fn watch(&self, prefix: &str) -> Result<EventStream> {
+        self.inner.watch(prefix).await
+    }
+
+    async fn list(&self, args: ListArgs) -> Result<ObjectMetaPage> {
+        self.inner.list(self.policy.limit_list_args(args)).await
+    }
+
+    async fn list_keys(&self, args: ListArgs) -> Result<KeyPage> {
+        self.inner
+            .list_keys(self.policy.limit_list_args(args))
+            .await
+    }
+}
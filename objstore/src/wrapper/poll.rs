@@ -0,0 +1,321 @@
+use std::{
+    collections::{BTreeMap, VecDeque},
+    sync::Arc,
+    time::Duration,
+};
+
+use bytes::Bytes;
+use futures::{StreamExt as _, TryStreamExt as _, stream};
+use time::OffsetDateTime;
+
+use crate::{
+    Copy, DownloadUrlArgs, EventStream, GetManyOptions, GetManyStream, HealthReport, KeyPage,
+    ListArgs, ObjStore, ObjectEvent, ObjectKey, ObjectMeta, ObjectMetaPage, Put, PutResult, Result,
+    SortOrder, UploadUrlArgs, Usage, ValueStream,
+    maintenance::{CleanupOptions, CleanupReport},
+    store::list_pages,
+};
+
+/// Wrapper that emulates [`ObjStore::watch`] by periodically listing the
+/// watched prefix and comparing the results.
+///
+/// Useful for backends without native change notifications.
+/// Changes are detected by comparing the etag, size and modification time of
+/// objects, so multiple writes to the same key within one interval are
+/// reported as a single event.
+/// All other operations are forwarded to the wrapped store.
+#[derive(Debug)]
+pub struct PollingWatchObjStore<S> {
+    inner: Arc<S>,
+    interval: Duration,
+}
+
+impl<S> Clone for PollingWatchObjStore<S> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            interval: self.interval,
+        }
+    }
+}
+
+impl<S> PollingWatchObjStore<S> {
+    /// Default interval between listings.
+    pub const DEFAULT_INTERVAL: Duration = Duration::from_secs(10);
+
+    /// Creates a new polling wrapper with the [default interval](Self::DEFAULT_INTERVAL).
+    pub fn new(inner: S) -> Self {
+        Self {
+            inner: Arc::new(inner),
+            interval: Self::DEFAULT_INTERVAL,
+        }
+    }
+
+    /// Set the interval between listings.
+    pub fn with_interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// The interval between listings.
+    pub fn interval(&self) -> Duration {
+        self.interval
+    }
+}
+
+/// Attributes compared to detect changes to an object.
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Fingerprint {
+    etag: Option<String>,
+    size: Option<u64>,
+    updated_at: Option<OffsetDateTime>,
+}
+
+type Snapshot = BTreeMap<String, Fingerprint>;
+
+async fn snapshot<S>(store: &S, prefix: &str) -> Result<Snapshot>
+where
+    S: ObjStore + ?Sized,
+{
+    list_pages(store, ListArgs::new().with_prefix(prefix))
+        .try_fold(Snapshot::new(), |mut snapshot, page| async move {
+            for meta in page.items {
+                let fingerprint = Fingerprint {
+                    etag: meta.etag,
+                    size: meta.size,
+                    updated_at: meta.updated_at,
+                };
+                snapshot.insert(meta.key, fingerprint);
+            }
+            Ok(snapshot)
+        })
+        .await
+}
+
+fn diff(old: &Snapshot, new: &Snapshot) -> VecDeque<ObjectEvent> {
+    let mut events = VecDeque::new();
+    for (key, fingerprint) in new {
+        match old.get(key) {
+            None => events.push_back(ObjectEvent::Created { key: key.clone() }),
+            Some(previous) if previous != fingerprint => {
+                events.push_back(ObjectEvent::Updated { key: key.clone() })
+            }
+            Some(_) => {}
+        }
+    }
+    for key in old.keys().filter(|key| !new.contains_key(*key)) {
+        events.push_back(ObjectEvent::Deleted { key: key.clone() });
+    }
+    events
+}
+
+struct PollState<S> {
+    store: Arc<S>,
+    prefix: String,
+    interval: Duration,
+    snapshot: Snapshot,
+    pending: VecDeque<ObjectEvent>,
+}
+
+#[async_trait::async_trait]
+impl<S> ObjStore for PollingWatchObjStore<S>
+where
+    S: ObjStore + Send + Sync + 'static,
+{
+    fn kind(&self) -> &str {
+        self.inner.kind()
+    }
+
+    fn safe_uri(&self) -> &url::Url {
+        self.inner.safe_uri()
+    }
+
+    async fn healthcheck(&self) -> Result<()> {
+        self.inner.healthcheck().await
+    }
+
+    async fn healthcheck_report(&self) -> Result<HealthReport> {
+        self.inner.healthcheck_report().await
+    }
+
+    async fn meta(&self, key: &str) -> Result<Option<ObjectMeta>> {
+        self.inner.meta(key).await
+    }
+
+    async fn exists(&self, key: &str) -> Result<bool> {
+        self.inner.exists(key).await
+    }
+
+    async fn meta_raw(&self, key: &ObjectKey) -> Result<Option<ObjectMeta>> {
+        self.inner.meta_raw(key).await
+    }
+
+    async fn get_raw(&self, key: &ObjectKey) -> Result<Option<Bytes>> {
+        self.inner.get_raw(key).await
+    }
+
+    async fn delete_raw(&self, key: &ObjectKey) -> Result<()> {
+        self.inner.delete_raw(key).await
+    }
+
+    async fn put_raw(&self, key: &ObjectKey, data: Bytes) -> Result<ObjectMeta> {
+        self.inner.put_raw(key, data).await
+    }
+
+    async fn list_all_keys_raw(&self, prefix: &ObjectKey) -> Result<Vec<ObjectKey>> {
+        self.inner.list_all_keys_raw(prefix).await
+    }
+
+    async fn get(&self, key: &str) -> Result<Option<Bytes>> {
+        self.inner.get(key).await
+    }
+
+    async fn get_stream(&self, key: &str) -> Result<Option<ValueStream>> {
+        self.inner.get_stream(key).await
+    }
+
+    async fn get_with_meta(&self, key: &str) -> Result<Option<(Bytes, ObjectMeta)>> {
+        self.inner.get_with_meta(key).await
+    }
+
+    async fn get_stream_with_meta(&self, key: &str) -> Result<Option<(ObjectMeta, ValueStream)>> {
+        self.inner.get_stream_with_meta(key).await
+    }
+
+    async fn get_range(&self, key: &str, range: std::ops::Range<u64>) -> Result<Option<Bytes>> {
+        self.inner.get_range(key, range).await
+    }
+
+    fn get_many<'a>(&'a self, keys: Vec<String>, options: GetManyOptions) -> GetManyStream<'a> {
+        self.inner.get_many(keys, options)
+    }
+
+    async fn generate_download_url(&self, args: DownloadUrlArgs) -> Result<Option<url::Url>> {
+        self.inner.generate_download_url(args).await
+    }
+
+    async fn generate_upload_url(&self, args: UploadUrlArgs) -> Result<Option<url::Url>> {
+        self.inner.generate_upload_url(args).await
+    }
+
+    async fn send_put(&self, put: Put) -> Result<ObjectMeta> {
+        self.inner.send_put(put).await
+    }
+
+    async fn send_put_result(&self, put: Put) -> Result<PutResult> {
+        self.inner.send_put_result(put).await
+    }
+
+    async fn append(&self, key: &str, data: Bytes) -> Result<ObjectMeta> {
+        self.inner.append(key, data).await
+    }
+
+    async fn send_copy(&self, copy: Copy) -> Result<ObjectMeta> {
+        self.inner.send_copy(copy).await
+    }
+
+    async fn delete(&self, key: &str) -> Result<()> {
+        self.inner.delete(key).await
+    }
+
+    async fn delete_prefix(&self, prefix: &str) -> Result<()> {
+        self.inner.delete_prefix(prefix).await
+    }
+
+    async fn usage(&self, prefix: &str) -> Result<Usage> {
+        self.inner.usage(prefix).await
+    }
+
+    async fn cleanup(&self, options: CleanupOptions) -> Result<CleanupReport> {
+        self.inner.cleanup(options).await
+    }
+
+    fn supports_sort(&self, order: SortOrder) -> bool {
+        self.inner.supports_sort(order)
+    }
+
+    fn supports_conditions(&self) -> bool {
+        self.inner.supports_conditions()
+    }
+
+    async fn watch(&self, prefix: &str) -> Result<EventStream> {
+        // Take the initial snapshot eagerly, so changes made after this call
+        // returns are reported, and listing errors surface immediately.
+        let state = PollState {
+            snapshot: snapshot(&*self.inner, prefix).await?,
+            store: self.inner.clone(),
+            prefix: prefix.to_owned(),
+            interval: self.interval,
+            pending: VecDeque::new(),
+        };
+
+        let stream = stream::unfold(state, |mut state| async move {
+            loop {
+                if let Some(event) = state.pending.pop_front() {
+                    return Some((Ok(event), state));
+                }
+                tokio::time::sleep(state.interval).await;
+                match snapshot(&*state.store, &state.prefix).await {
+                    Ok(current) => {
+                        state.pending = diff(&state.snapshot, &current);
+                        state.snapshot = current;
+                    }
+                    // Keep the previous snapshot and retry on the next tick.
+                    Err(err) => return Some((Err(err), state)),
+                }
+            }
+        });
+        Ok(stream.boxed())
+    }
+
+    async fn list(&self, args: ListArgs) -> Result<ObjectMetaPage> {
+        self.inner.list(args).await
+    }
+
+    async fn list_keys(&self, args: ListArgs) -> Result<KeyPage> {
+        self.inner.list_keys(args).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fingerprint(etag: &str) -> Fingerprint {
+        Fingerprint {
+            etag: Some(etag.to_string()),
+            size: None,
+            updated_at: None,
+        }
+    }
+
+    #[test]
+    fn test_diff() {
+        let old = Snapshot::from([
+            ("a".to_string(), fingerprint("1")),
+            ("b".to_string(), fingerprint("1")),
+            ("c".to_string(), fingerprint("1")),
+        ]);
+        let new = Snapshot::from([
+            ("b".to_string(), fingerprint("2")),
+            ("c".to_string(), fingerprint("1")),
+            ("d".to_string(), fingerprint("1")),
+        ]);
+
+        let events = diff(&old, &new);
+        assert_eq!(
+            events,
+            vec![
+                ObjectEvent::Updated {
+                    key: "b".to_string()
+                },
+                ObjectEvent::Created {
+                    key: "d".to_string()
+                },
+                ObjectEvent::Deleted {
+                    key: "a".to_string()
+                },
+            ]
+        );
+    }
+}
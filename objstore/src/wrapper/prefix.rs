@@ -1,15 +1,21 @@
+use std::collections::{HashMap, VecDeque};
+
 use bytes::Bytes;
-use futures::TryStreamExt as _;
+use futures::{StreamExt as _, TryStreamExt as _};
 
 use crate::{
-    Copy, DownloadUrlArgs, KeyPage, ListArgs, ObjStore, ObjStoreError, ObjectMeta, ObjectMetaPage,
-    Put, Resource, Result, UploadUrlArgs, ValueStream,
+    Copy, DownloadUrlArgs, EventStream, GetManyOptions, GetManyStream, HealthReport, KeyPage,
+    ListArgs, ObjStore, ObjStoreError, ObjectKey, ObjectMeta, ObjectMetaPage, PrefixMapper, Put,
+    PutResult, Resource, Result, SortOrder, UploadUrlArgs, Usage, ValueStream,
+    maintenance::{CleanupOptions, CleanupReport},
 };
 
 /// Wrapper that scopes all object store operations to a fixed key prefix.
+///
+/// Keys are mapped with a [`PrefixMapper`].
 #[derive(Clone, Debug)]
 pub struct PrefixObjStore<S> {
-    prefix: String,
+    mapper: PrefixMapper,
     inner: S,
 }
 
@@ -20,38 +26,32 @@ impl<S> PrefixObjStore<S> {
     /// separators, so `"tenant-a"` and `"/tenant-a/"` behave the same.
     pub fn new(prefix: impl AsRef<str>, inner: S) -> Self {
         Self {
-            prefix: normalize_prefix(prefix.as_ref()),
+            mapper: PrefixMapper::new(prefix),
             inner,
         }
     }
 
-    fn trim_joined_suffix<'a>(&self, key: &'a str) -> &'a str {
-        if self.prefix.is_empty() {
-            key
-        } else {
-            key.trim_start_matches('/')
-        }
+    /// The normalized prefix, with a trailing `/` unless empty.
+    pub fn prefix(&self) -> &str {
+        self.mapper.prefix()
+    }
+
+    pub fn inner(&self) -> &S {
+        &self.inner
+    }
+
+    fn prepend_prefix_raw(&self, key: &ObjectKey) -> ObjectKey {
+        self.mapper.apply_raw(key)
     }
 
     fn prepend_prefix(&self, key: &str) -> String {
-        let key = self.trim_joined_suffix(key);
-
-        if self.prefix.is_empty() {
-            key.to_owned()
-        } else if key.is_empty() {
-            self.prefix.clone()
-        } else {
-            format!("{}{}", self.prefix, key)
-        }
+        self.mapper.apply(key).into_owned()
     }
 
     fn strip_prefix(&self, key: &str) -> Result<String> {
-        if self.prefix.is_empty() {
-            return Ok(key.to_owned());
-        }
-
-        key.strip_prefix(&self.prefix)
-            .map(|suffix| suffix.trim_start_matches('/').to_owned())
+        self.mapper
+            .strip(key)
+            .map(str::to_owned)
             .ok_or_else(|| ObjStoreError::Internal {
                 message: format!("wrapped store returned key outside prefix: {key}"),
                 source: None,
@@ -59,14 +59,7 @@ impl<S> PrefixObjStore<S> {
     }
 
     fn strip_prefix_owned(&self, key: String) -> std::result::Result<String, String> {
-        if self.prefix.is_empty() {
-            return Ok(key);
-        }
-
-        match key.strip_prefix(&self.prefix) {
-            Some(suffix) => Ok(suffix.trim_start_matches('/').to_owned()),
-            None => Err(key),
-        }
+        self.mapper.strip_owned(key)
     }
 
     fn map_resource(&self, resource: Resource) -> Resource {
@@ -84,10 +77,7 @@ impl<S> PrefixObjStore<S> {
     }
 
     fn map_key_lossy(&self, key: String) -> String {
-        match self.strip_prefix_owned(key) {
-            Ok(key) => key,
-            Err(key) => key,
-        }
+        self.mapper.strip_lossy(key)
     }
 
     fn map_error(&self, err: ObjStoreError) -> ObjStoreError {
@@ -167,15 +157,17 @@ impl<S> PrefixObjStore<S> {
     }
 
     fn map_list_args(&self, mut args: ListArgs) -> ListArgs {
-        match args.prefix().map(str::to_owned) {
-            Some(prefix) => args.set_prefix(self.prepend_prefix(&prefix)),
-            None if !self.prefix.is_empty() => args.set_prefix(self.prefix.clone()),
-            None => {}
+        if let Some(prefix) = self.mapper.apply_list_prefix(args.prefix()) {
+            args.set_prefix(prefix);
         }
 
         if let Some(cursor) = args.cursor().map(str::to_owned) {
             args = args.with_cursor(self.prepend_prefix(&cursor));
         }
+        if let Some(start_after) = args.start_after().map(str::to_owned) {
+            args = args.with_start_after(self.prepend_prefix(&start_after));
+        }
+        args.map_patterns(|glob| glob.with_prefix(self.mapper.prefix()));
 
         args
     }
@@ -227,23 +219,13 @@ impl<S> PrefixObjStore<S> {
 
     fn map_stream_errors(&self, stream: ValueStream) -> ValueStream {
         let prefix = PrefixObjStore {
-            prefix: self.prefix.clone(),
+            mapper: self.mapper.clone(),
             inner: (),
         };
         Box::pin(stream.map_err(move |err| prefix.map_error(err)))
     }
 }
 
-fn normalize_prefix(prefix: &str) -> String {
-    let prefix = prefix.trim_matches('/');
-
-    if prefix.is_empty() {
-        String::new()
-    } else {
-        format!("{prefix}/")
-    }
-}
-
 #[async_trait::async_trait]
 impl<S> ObjStore for PrefixObjStore<S>
 where
@@ -264,6 +246,13 @@ where
             .map_err(|err| self.map_error(err))
     }
 
+    async fn healthcheck_report(&self) -> Result<HealthReport> {
+        self.inner
+            .healthcheck_report()
+            .await
+            .map_err(|err| self.map_error(err))
+    }
+
     async fn meta(&self, key: &str) -> Result<Option<ObjectMeta>> {
         self.inner
             .meta(&self.prepend_prefix(key))
@@ -273,6 +262,13 @@ where
             .transpose()
     }
 
+    async fn exists(&self, key: &str) -> Result<bool> {
+        self.inner
+            .exists(&self.prepend_prefix(key))
+            .await
+            .map_err(|err| self.map_error(err))
+    }
+
     async fn get(&self, key: &str) -> Result<Option<Bytes>> {
         self.inner
             .get(&self.prepend_prefix(key))
@@ -280,6 +276,50 @@ where
             .map_err(|err| self.map_error(err))
     }
 
+    async fn meta_raw(&self, key: &ObjectKey) -> Result<Option<ObjectMeta>> {
+        self.inner
+            .meta_raw(&self.prepend_prefix_raw(key))
+            .await
+            .map_err(|err| self.map_error(err))?
+            .map(|meta| self.map_meta(meta))
+            .transpose()
+    }
+
+    async fn get_raw(&self, key: &ObjectKey) -> Result<Option<Bytes>> {
+        self.inner
+            .get_raw(&self.prepend_prefix_raw(key))
+            .await
+            .map_err(|err| self.map_error(err))
+    }
+
+    async fn delete_raw(&self, key: &ObjectKey) -> Result<()> {
+        self.inner
+            .delete_raw(&self.prepend_prefix_raw(key))
+            .await
+            .map_err(|err| self.map_error(err))
+    }
+
+    async fn put_raw(&self, key: &ObjectKey, data: Bytes) -> Result<ObjectMeta> {
+        let meta = self
+            .inner
+            .put_raw(&self.prepend_prefix_raw(key), data)
+            .await
+            .map_err(|err| self.map_error(err))?;
+        self.map_meta(meta)
+    }
+
+    async fn list_all_keys_raw(&self, prefix: &ObjectKey) -> Result<Vec<ObjectKey>> {
+        let keys = self
+            .inner
+            .list_all_keys_raw(&self.prepend_prefix_raw(prefix))
+            .await
+            .map_err(|err| self.map_error(err))?;
+        Ok(keys
+            .into_iter()
+            .filter_map(|key| self.mapper.strip_raw(&key))
+            .collect())
+    }
+
     async fn get_stream(&self, key: &str) -> Result<Option<ValueStream>> {
         self.inner
             .get_stream(&self.prepend_prefix(key))
@@ -307,6 +347,38 @@ where
             .transpose()
     }
 
+    async fn get_range(&self, key: &str, range: std::ops::Range<u64>) -> Result<Option<Bytes>> {
+        self.inner
+            .get_range(&self.prepend_prefix(key), range)
+            .await
+            .map_err(|err| self.map_error(err))
+    }
+
+    fn get_many<'a>(&'a self, keys: Vec<String>, options: GetManyOptions) -> GetManyStream<'a> {
+        // Map back to the requested keys, which may differ from the stripped
+        // keys in leading slashes. Every requested key is fetched, including
+        // duplicates, and each result is matched to the next pending request.
+        let mut pending = HashMap::<String, VecDeque<String>>::new();
+        let keys = keys
+            .into_iter()
+            .map(|key| {
+                let mapped = self.prepend_prefix(&key);
+                pending.entry(mapped.clone()).or_default().push_back(key);
+                mapped
+            })
+            .collect();
+        self.inner
+            .get_many(keys, options)
+            .map(move |(key, res)| {
+                let key = match pending.get_mut(&key).and_then(VecDeque::pop_front) {
+                    Some(requested) => requested,
+                    None => self.map_key_lossy(key),
+                };
+                (key, res.map_err(|err| self.map_error(err)))
+            })
+            .boxed()
+    }
+
     async fn generate_download_url(&self, mut args: DownloadUrlArgs) -> Result<Option<url::Url>> {
         args.key = self.prepend_prefix(&args.key);
         self.inner
@@ -333,6 +405,25 @@ where
         self.map_meta(meta)
     }
 
+    async fn send_put_result(&self, mut put: Put) -> Result<PutResult> {
+        put.key = self.prepend_prefix(&put.key);
+        let result = self
+            .inner
+            .send_put_result(put)
+            .await
+            .map_err(|err| self.map_error(err))?;
+        Ok(PutResult::new(self.map_meta(result.meta)?, result.created))
+    }
+
+    async fn append(&self, key: &str, data: Bytes) -> Result<ObjectMeta> {
+        let meta = self
+            .inner
+            .append(&self.prepend_prefix(key), data)
+            .await
+            .map_err(|err| self.map_error(err))?;
+        self.map_meta(meta)
+    }
+
     async fn send_copy(&self, mut copy: Copy) -> Result<ObjectMeta> {
         copy.source_key = self.prepend_prefix(&copy.source_key);
         copy.target_key = self.prepend_prefix(&copy.target_key);
@@ -358,6 +449,45 @@ where
             .map_err(|err| self.map_error(err))
     }
 
+    async fn usage(&self, prefix: &str) -> Result<Usage> {
+        self.inner
+            .usage(&self.prepend_prefix(prefix))
+            .await
+            .map_err(|err| self.map_error(err))
+    }
+
+    async fn cleanup(&self, options: CleanupOptions) -> Result<CleanupReport> {
+        // Backend leftovers like multipart uploads are not scoped to the
+        // prefix, so only the trash below the prefix is cleaned up.
+        crate::maintenance::cleanup_trash(self, &options).await
+    }
+
+    fn supports_sort(&self, order: SortOrder) -> bool {
+        self.inner.supports_sort(order)
+    }
+
+    fn supports_conditions(&self) -> bool {
+        self.inner.supports_conditions()
+    }
+
+    async fn watch(&self, prefix: &str) -> Result<EventStream> {
+        let stream = self
+            .inner
+            .watch(&self.prepend_prefix(prefix))
+            .await
+            .map_err(|err| self.map_error(err))?;
+        let prefix = PrefixObjStore {
+            mapper: self.mapper.clone(),
+            inner: (),
+        };
+        Ok(Box::pin(stream.map(move |event| {
+            let mut event = event.map_err(|err| prefix.map_error(err))?;
+            let key = event.key_mut();
+            *key = prefix.strip_prefix(key)?;
+            Ok(event)
+        })))
+    }
+
     async fn list(&self, args: ListArgs) -> Result<ObjectMetaPage> {
         let page = self
             .inner
@@ -0,0 +1,573 @@
+use std::collections::{HashMap, VecDeque};
+
+use bytes::Bytes;
+use futures::{StreamExt as _, TryStreamExt as _};
+
+use crate::{
+    Copy, DownloadUrlArgs, EventStream, GetManyOptions, GetManyStream, HealthReport, KeyPage,
+    ListArgs, ObjStore, ObjStoreError, ObjectKey, ObjectMeta, ObjectMetaPage, ObjectOrPrefix, Put,
+    PutResult, Resource, Result, SortOrder, UploadUrlArgs, Usage, ValueStream,
+    maintenance::{CleanupOptions, CleanupReport},
+    store::list_pages,
+};
+
+/// Options for [`ShardedObjStore`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ShardOptions {
+    levels: usize,
+    width: usize,
+}
+
+impl Default for ShardOptions {
+    fn default() -> Self {
+        Self {
+            levels: Self::DEFAULT_LEVELS,
+            width: Self::DEFAULT_WIDTH,
+        }
+    }
+}
+
+impl ShardOptions {
+    pub const DEFAULT_LEVELS: usize = 2;
+    pub const DEFAULT_WIDTH: usize = 2;
+    /// Maximum number of hex characters of all shard levels combined.
+    pub const MAX_DIGITS: usize = 16;
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Number of shard directories in front of every key.
+    pub fn levels(&self) -> usize {
+        self.levels
+    }
+
+    /// Set the number of shard levels. Values of `0` are ignored.
+    pub fn with_levels(mut self, levels: usize) -> Self {
+        if levels > 0 {
+            self.levels = levels.min(Self::MAX_DIGITS / self.width);
+        }
+        self
+    }
+
+    /// Number of hex characters per shard level.
+    ///
+    /// Each level splits the keys into `16^width` directories.
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Set the number of hex characters per level. Values of `0` are ignored.
+    pub fn with_width(mut self, width: usize) -> Self {
+        if width > 0 {
+            self.width = width.min(Self::MAX_DIGITS);
+            self.levels = self.levels.min(Self::MAX_DIGITS / self.width);
+        }
+        self
+    }
+}
+
+/// Wrapper that spreads keys over hash-derived shard prefixes.
+///
+/// Every key is stored below `levels` directories derived from a hash of the
+/// key, so `photos/cat.jpg` becomes e.g. `3f/a2/photos/cat.jpg`. This avoids
+/// request rate limits on hot S3 prefixes and huge single directories on file
+/// systems. The shard is stripped again from returned keys, so consumers only
+/// see the logical keys.
+///
+/// The hash is stable across versions and platforms, but changing the
+/// [`ShardOptions`] changes the location of every key.
+///
+/// Logical prefixes are spread over all shards, so listings, prefix deletes,
+/// usage and watches scan the whole wrapped store and filter the results.
+/// Every listed page requires a full scan, so avoid paging through large
+/// stores. Objects outside of the shard layout are ignored.
+#[derive(Clone, Debug)]
+pub struct ShardedObjStore<S> {
+    inner: S,
+    options: ShardOptions,
+}
+
+impl<S> ShardedObjStore<S> {
+    pub fn new(inner: S, options: ShardOptions) -> Self {
+        Self { inner, options }
+    }
+
+    pub fn inner(&self) -> &S {
+        &self.inner
+    }
+
+    pub fn options(&self) -> &ShardOptions {
+        &self.options
+    }
+
+    /// The key in the wrapped store for a logical key.
+    pub fn shard_key(&self, key: &str) -> String {
+        let mut out = self.shard_dirs(key.as_bytes());
+        out.push_str(key);
+        out
+    }
+
+    /// The shard directories for a key, like `ab/cd/`.
+    fn shard_dirs(&self, key: &[u8]) -> String {
+        let hash = format!("{:016x}", fnv1a(key));
+        let mut out = String::with_capacity(key.len() + self.options.levels * 3);
+        for level in 0..self.options.levels {
+            let start = level * self.options.width;
+            out.push_str(&hash[start..start + self.options.width]);
+            out.push('/');
+        }
+        out
+    }
+
+    /// The logical key for a key of the wrapped store.
+    ///
+    /// Returns `None` for keys outside of the shard layout, or in the wrong
+    /// shard.
+    pub fn logical_key<'a>(&self, key: &'a str) -> Option<&'a str> {
+        let len = self.options.levels * (self.options.width + 1);
+        let logical = key.get(len..)?;
+        (self.shard_key(logical) == key).then_some(logical)
+    }
+
+    fn shard_raw(&self, key: &ObjectKey) -> ObjectKey {
+        match key.as_str() {
+            Some(key) => self.shard_key(key).into(),
+            None => {
+                let mut out = self.shard_dirs(key.as_bytes()).into_bytes();
+                out.extend_from_slice(key.as_bytes());
+                ObjectKey::from_bytes(out)
+            }
+        }
+    }
+
+    fn map_key_lossy(&self, key: String) -> String {
+        match self.logical_key(&key) {
+            Some(logical) => logical.to_owned(),
+            None => key,
+        }
+    }
+
+    fn map_resource(&self, resource: Resource) -> Resource {
+        match resource {
+            Resource::Object { key } => Resource::Object {
+                key: self.map_key_lossy(key),
+            },
+            resource => resource,
+        }
+    }
+
+    fn map_error(&self, err: ObjStoreError) -> ObjStoreError {
+        match err {
+            ObjStoreError::ObjectNotFound { key, source } => ObjStoreError::ObjectNotFound {
+                key: self.map_key_lossy(key),
+                source,
+            },
+            ObjStoreError::AlreadyExists { resource, source } => ObjStoreError::AlreadyExists {
+                resource: self.map_resource(resource),
+                source,
+            },
+            ObjStoreError::PreconditionFailed {
+                operation,
+                resource,
+                source,
+            } => ObjStoreError::PreconditionFailed {
+                operation,
+                resource: resource.map(|resource| self.map_resource(resource)),
+                source,
+            },
+            ObjStoreError::InvalidMetadata {
+                key,
+                message,
+                source,
+            } => ObjStoreError::InvalidMetadata {
+                key: self.map_key_lossy(key),
+                message,
+                source,
+            },
+            ObjStoreError::ContentDeserialization {
+                key,
+                format,
+                source,
+            } => ObjStoreError::ContentDeserialization {
+                key: self.map_key_lossy(key),
+                format,
+                source,
+            },
+            err => err,
+        }
+    }
+
+    fn map_meta(&self, mut meta: ObjectMeta) -> ObjectMeta {
+        meta.key = self.map_key_lossy(meta.key);
+        meta
+    }
+}
+
+impl<S> ShardedObjStore<S>
+where
+    S: ObjStore + Send + Sync,
+{
+    /// All objects below the logical `prefix`, sorted by logical key.
+    async fn scan(&self, prefix: &str, args: &ListArgs) -> Result<Vec<ObjectMeta>> {
+        let mut inner_args = ListArgs::new();
+        if let Some(token) = args.cancellation() {
+            inner_args = inner_args.with_cancellation(token.clone());
+        }
+
+        let mut items = Vec::new();
+        let mut pages = list_pages(&self.inner, inner_args);
+        while let Some(page) = pages.try_next().await.map_err(|err| self.map_error(err))? {
+            for mut meta in page.items {
+                let Some(key) = self.logical_key(&meta.key) else {
+                    continue;
+                };
+                if key.starts_with(prefix) {
+                    meta.key = key.to_owned();
+                    items.push(meta);
+                }
+            }
+        }
+        items.sort_by(|a, b| a.key.cmp(&b.key));
+        Ok(items)
+    }
+}
+
+/// 64 bit FNV-1a, which is stable across platforms and versions.
+fn fnv1a(data: &[u8]) -> u64 {
+    data.iter().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(0x0000_0100_0000_01b3)
+    })
+}
+
+fn common_prefix<'a>(key: &'a str, prefix: &str, delimiter: &str) -> Option<&'a str> {
+    let rest = key.strip_prefix(prefix)?;
+    let idx = rest.find(delimiter)?;
+    Some(&key[..prefix.len() + idx + delimiter.len()])
+}
+
+#[async_trait::async_trait]
+impl<S> ObjStore for ShardedObjStore<S>
+where
+    S: ObjStore + Send + Sync,
+{
+    fn kind(&self) -> &str {
+        self.inner.kind()
+    }
+
+    fn safe_uri(&self) -> &url::Url {
+        self.inner.safe_uri()
+    }
+
+    async fn healthcheck(&self) -> Result<()> {
+        self.inner.healthcheck().await
+    }
+
+    async fn healthcheck_report(&self) -> Result<HealthReport> {
+        self.inner.healthcheck_report().await
+    }
+
+    async fn meta(&self, key: &str) -> Result<Option<ObjectMeta>> {
+        let meta = self
+            .inner
+            .meta(&self.shard_key(key))
+            .await
+            .map_err(|err| self.map_error(err))?;
+        Ok(meta.map(|meta| self.map_meta(meta)))
+    }
+
+    async fn exists(&self, key: &str) -> Result<bool> {
+        self.inner
+            .exists(&self.shard_key(key))
+            .await
+            .map_err(|err| self.map_error(err))
+    }
+
+    async fn get(&self, key: &str) -> Result<Option<Bytes>> {
+        self.inner
+            .get(&self.shard_key(key))
+            .await
+            .map_err(|err| self.map_error(err))
+    }
+
+    async fn meta_raw(&self, key: &ObjectKey) -> Result<Option<ObjectMeta>> {
+        let meta = self
+            .inner
+            .meta_raw(&self.shard_raw(key))
+            .await
+            .map_err(|err| self.map_error(err))?;
+        Ok(meta.map(|meta| self.map_meta(meta)))
+    }
+
+    async fn get_raw(&self, key: &ObjectKey) -> Result<Option<Bytes>> {
+        self.inner
+            .get_raw(&self.shard_raw(key))
+            .await
+            .map_err(|err| self.map_error(err))
+    }
+
+    async fn delete_raw(&self, key: &ObjectKey) -> Result<()> {
+        self.inner
+            .delete_raw(&self.shard_raw(key))
+            .await
+            .map_err(|err| self.map_error(err))
+    }
+
+    async fn get_stream(&self, key: &str) -> Result<Option<ValueStream>> {
+        self.inner
+            .get_stream(&self.shard_key(key))
+            .await
+            .map_err(|err| self.map_error(err))
+    }
+
+    async fn get_with_meta(&self, key: &str) -> Result<Option<(Bytes, ObjectMeta)>> {
+        let res = self
+            .inner
+            .get_with_meta(&self.shard_key(key))
+            .await
+            .map_err(|err| self.map_error(err))?;
+        Ok(res.map(|(value, meta)| (value, self.map_meta(meta))))
+    }
+
+    async fn get_stream_with_meta(&self, key: &str) -> Result<Option<(ObjectMeta, ValueStream)>> {
+        let res = self
+            .inner
+            .get_stream_with_meta(&self.shard_key(key))
+            .await
+            .map_err(|err| self.map_error(err))?;
+        Ok(res.map(|(meta, stream)| (self.map_meta(meta), stream)))
+    }
+
+    async fn get_range(&self, key: &str, range: std::ops::Range<u64>) -> Result<Option<Bytes>> {
+        self.inner
+            .get_range(&self.shard_key(key), range)
+            .await
+            .map_err(|err| self.map_error(err))
+    }
+
+    fn get_many<'a>(&'a self, keys: Vec<String>, options: GetManyOptions) -> GetManyStream<'a> {
+        // Every requested key is fetched, including duplicates, and each result
+        // is matched to the next pending request for its sharded key.
+        let mut pending = HashMap::<String, VecDeque<String>>::new();
+        let keys = keys
+            .into_iter()
+            .map(|key| {
+                let mapped = self.shard_key(&key);
+                pending.entry(mapped.clone()).or_default().push_back(key);
+                mapped
+            })
+            .collect();
+        self.inner
+            .get_many(keys, options)
+            .map(move |(key, res)| {
+                let key = match pending.get_mut(&key).and_then(VecDeque::pop_front) {
+                    Some(requested) => requested,
+                    None => self.map_key_lossy(key),
+                };
+                (key, res.map_err(|err| self.map_error(err)))
+            })
+            .boxed()
+    }
+
+    async fn generate_download_url(&self, mut args: DownloadUrlArgs) -> Result<Option<url::Url>> {
+        args.key = self.shard_key(&args.key);
+        self.inner
+            .generate_download_url(args)
+            .await
+            .map_err(|err| self.map_error(err))
+    }
+
+    async fn generate_upload_url(&self, mut args: UploadUrlArgs) -> Result<Option<url::Url>> {
+        args.key = self.shard_key(&args.key);
+        self.inner
+            .generate_upload_url(args)
+            .await
+            .map_err(|err| self.map_error(err))
+    }
+
+    async fn send_put(&self, mut put: Put) -> Result<ObjectMeta> {
+        put.key = self.shard_key(&put.key);
+        let meta = self
+            .inner
+            .send_put(put)
+            .await
+            .map_err(|err| self.map_error(err))?;
+        Ok(self.map_meta(meta))
+    }
+
+    async fn send_put_result(&self, mut put: Put) -> Result<PutResult> {
+        put.key = self.shard_key(&put.key);
+        let result = self
+            .inner
+            .send_put_result(put)
+            .await
+            .map_err(|err| self.map_error(err))?;
+        Ok(PutResult::new(self.map_meta(result.meta), result.created))
+    }
+
+    async fn append(&self, key: &str, data: Bytes) -> Result<ObjectMeta> {
+        let meta = self
+            .inner
+            .append(&self.shard_key(key), data)
+            .await
+            .map_err(|err| self.map_error(err))?;
+        Ok(self.map_meta(meta))
+    }
+
+    async fn send_copy(&self, mut copy: Copy) -> Result<ObjectMeta> {
+        copy.source_key = self.shard_key(&copy.source_key);
+        copy.target_key = self.shard_key(&copy.target_key);
+        let meta = self
+            .inner
+            .send_copy(copy)
+            .await
+            .map_err(|err| self.map_error(err))?;
+        Ok(self.map_meta(meta))
+    }
+
+    async fn delete(&self, key: &str) -> Result<()> {
+        self.inner
+            .delete(&self.shard_key(key))
+            .await
+            .map_err(|err| self.map_error(err))
+    }
+
+    async fn delete_prefix(&self, prefix: &str) -> Result<()> {
+        for meta in self.scan(prefix, &ListArgs::new()).await? {
+            self.delete(&meta.key).await?;
+        }
+        Ok(())
+    }
+
+    async fn usage(&self, prefix: &str) -> Result<Usage> {
+        let items = self.scan(prefix, &ListArgs::new()).await?;
+        Ok(Usage {
+            objects: items.len() as u64,
+            bytes: items.iter().filter_map(|meta| meta.size).sum(),
+        })
+    }
+
+    async fn cleanup(&self, options: CleanupOptions) -> Result<CleanupReport> {
+        // Trash keys are sharded like all other keys.
+        crate::maintenance::cleanup_trash(self, &options).await
+    }
+
+    fn supports_sort(&self, order: SortOrder) -> bool {
+        order == SortOrder::KeyAscending
+    }
+
+    fn supports_conditions(&self) -> bool {
+        self.inner.supports_conditions()
+    }
+
+    async fn watch(&self, prefix: &str) -> Result<EventStream> {
+        let stream = self
+            .inner
+            .watch("")
+            .await
+            .map_err(|err| self.map_error(err))?;
+        let shards = ShardedObjStore {
+            inner: (),
+            options: self.options,
+        };
+        let prefix = prefix.to_owned();
+        Ok(Box::pin(stream.filter_map(move |event| {
+            let event = match event {
+                Ok(mut event) => {
+                    let key = event.key_mut();
+                    match shards.logical_key(key) {
+                        Some(logical) if logical.starts_with(&prefix) => {
+                            *key = logical.to_owned();
+                            Some(Ok(event))
+                        }
+                        _ => None,
+                    }
+                }
+                Err(err) => Some(Err(shards.map_error(err))),
+            };
+            futures::future::ready(event)
+        })))
+    }
+
+    async fn list(&self, args: ListArgs) -> Result<ObjectMetaPage> {
+        let prefix = args.prefix().unwrap_or_default();
+        let delimiter = args.delimiter();
+        let limit = args.limit().unwrap_or(1_000) as usize;
+        let cursor = args.cursor();
+        // A cursor pointing into a common prefix means the prefix was
+        // already returned.
+        let cursor_prefix = cursor
+            .zip(delimiter)
+            .and_then(|(cursor, delim)| common_prefix(cursor, prefix, delim));
+
+        let mut entries = Vec::new();
+        let mut exhausted = true;
+        for meta in self.scan(prefix, &args).await? {
+            if cursor.is_some_and(|cursor| meta.key.as_str() <= cursor) {
+                continue;
+            }
+            if entries.len() >= limit {
+                exhausted = false;
+                break;
+            }
+            if let Some(common) =
+                delimiter.and_then(|delim| common_prefix(&meta.key, prefix, delim))
+            {
+                let seen = entries
+                    .last()
+                    .is_some_and(|entry: &ObjectOrPrefix| entry.key() == common);
+                if !seen && cursor_prefix != Some(common) {
+                    entries.push(ObjectOrPrefix::Prefix(common.to_owned()));
+                }
+            } else if args.matches(&meta) {
+                entries.push(ObjectOrPrefix::Object(Box::new(meta)));
+            }
+        }
+
+        let next_cursor = if exhausted {
+            None
+        } else {
+            entries.last().map(|entry| entry.key().to_owned())
+        };
+        Ok(ObjectMetaPage::from_entries(entries, next_cursor))
+    }
+
+    async fn list_keys(&self, args: ListArgs) -> Result<KeyPage> {
+        let page = self.list(args).await?;
+        Ok(KeyPage {
+            items: page.items.into_iter().map(|meta| meta.key).collect(),
+            next_cursor: page.next_cursor,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shard_key_roundtrip() {
+        let store = ShardedObjStore::new((), ShardOptions::new());
+        let key = store.shard_key("photos/cat.jpg");
+        assert_eq!(key.len(), "ab/cd/photos/cat.jpg".len());
+        assert!(key.ends_with("/photos/cat.jpg"));
+        assert_eq!(store.logical_key(&key), Some("photos/cat.jpg"));
+        assert_eq!(store.logical_key("photos/cat.jpg"), None);
+        assert_eq!(store.logical_key("00/00/photos/cat.jpg"), None);
+    }
+
+    #[test]
+    fn test_shard_key_is_stable() {
+        let store = ShardedObjStore::new((), ShardOptions::new().with_levels(1).with_width(4));
+        assert_eq!(fnv1a(b"a"), 0xaf63_dc4c_8601_ec8c);
+        assert_eq!(store.shard_key("a"), "af63/a");
+    }
+
+    #[test]
+    fn test_options_limit_digits() {
+        let options = ShardOptions::new().with_width(8).with_levels(4);
+        assert_eq!((options.levels(), options.width()), (2, 8));
+        let options = ShardOptions::new().with_levels(0).with_width(0);
+        assert_eq!(options, ShardOptions::default());
+    }
+}
@@ -0,0 +1,602 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex, MutexGuard},
+    time::Duration,
+};
+
+use bytes::Bytes;
+use futures::TryStreamExt as _;
+use time::OffsetDateTime;
+
+use crate::{
+    Copy, DownloadUrlArgs, EventStream, HealthReport, KeyPage, ListArgs, ObjStore, ObjectKey,
+    ObjectMeta, ObjectMetaPage, Put, PutResult, Result, SortOrder, UploadUrlArgs, Usage,
+    ValueStream,
+    maintenance::{CleanupOptions, CleanupReport},
+    store::list_pages,
+};
+
+/// How [`TieredObjStore`] handles writes.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum WritePolicy {
+    /// Write to the slow tier, then cache the object in the fast tier.
+    #[default]
+    WriteThrough,
+    /// Only write to the fast tier.
+    ///
+    /// Objects are copied to the slow tier by [`TieredObjStore::flush`],
+    /// before they are evicted, and before operations that need them there
+    /// (like listing).
+    WriteBack,
+}
+
+/// Options for [`TieredObjStore`].
+#[derive(Clone, Debug)]
+pub struct TieredOptions {
+    write_policy: WritePolicy,
+    max_object_size: u64,
+    max_age: Option<Duration>,
+    max_fast_size: Option<u64>,
+}
+
+impl Default for TieredOptions {
+    fn default() -> Self {
+        Self {
+            write_policy: WritePolicy::default(),
+            max_object_size: Self::DEFAULT_MAX_OBJECT_SIZE,
+            max_age: None,
+            max_fast_size: None,
+        }
+    }
+}
+
+impl TieredOptions {
+    pub const DEFAULT_MAX_OBJECT_SIZE: u64 = 16 * 1024 * 1024;
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn write_policy(&self) -> WritePolicy {
+        self.write_policy
+    }
+
+    pub fn with_write_policy(mut self, policy: WritePolicy) -> Self {
+        self.write_policy = policy;
+        self
+    }
+
+    /// Maximum size of objects cached in the fast tier.
+    pub fn max_object_size(&self) -> u64 {
+        self.max_object_size
+    }
+
+    /// Set the maximum size of objects cached in the fast tier.
+    ///
+    /// Larger objects are read from and written to the slow tier directly.
+    pub fn with_max_object_size(mut self, size: u64) -> Self {
+        self.max_object_size = size;
+        self
+    }
+
+    /// Time after the last access at which objects are evicted.
+    pub fn max_age(&self) -> Option<Duration> {
+        self.max_age
+    }
+
+    /// Evict objects from the fast tier once they were not accessed for `age`.
+    pub fn with_max_age(mut self, age: Duration) -> Self {
+        self.max_age = Some(age);
+        self
+    }
+
+    /// Maximum total size of the fast tier.
+    pub fn max_fast_size(&self) -> Option<u64> {
+        self.max_fast_size
+    }
+
+    /// Evict the least recently used objects once the fast tier holds more
+    /// than `size` bytes.
+    pub fn with_max_fast_size(mut self, size: u64) -> Self {
+        self.max_fast_size = Some(size);
+        self
+    }
+}
+
+#[derive(Debug, Default)]
+struct TierState {
+    /// Keys only written to the fast tier, with their write generation.
+    dirty: HashMap<String, u64>,
+    generation: u64,
+    /// Last access through the wrapper.
+    accessed: HashMap<String, OffsetDateTime>,
+}
+
+/// Wrapper that combines a fast store (like memory or fs) with a slow store
+/// (like S3).
+///
+/// Reads check the fast tier first, and copy objects from the slow tier into
+/// the fast tier on a miss. Writes are handled according to the
+/// [`WritePolicy`]. Listings and usage are always served by the slow tier.
+///
+/// Objects are evicted from the fast tier by [`Self::demote`], according to
+/// [`TieredOptions::with_max_age`] and [`TieredOptions::with_max_fast_size`],
+/// usually called periodically via [`Self::run_maintenance`]. Access times
+/// are kept in memory, and fall back to the modification time in the fast
+/// tier.
+///
+/// Streaming reads are not cached.
+#[derive(Debug)]
+pub struct TieredObjStore<F, S> {
+    fast: Arc<F>,
+    slow: Arc<S>,
+    options: TieredOptions,
+    state: Arc<Mutex<TierState>>,
+}
+
+impl<F, S> Clone for TieredObjStore<F, S> {
+    fn clone(&self) -> Self {
+        Self {
+            fast: self.fast.clone(),
+            slow: self.slow.clone(),
+            options: self.options.clone(),
+            state: self.state.clone(),
+        }
+    }
+}
+
+impl<F, S> TieredObjStore<F, S> {
+    /// Default interval between runs for [`Self::run_maintenance`].
+    pub const DEFAULT_INTERVAL: Duration = Duration::from_secs(60);
+
+    pub fn new(fast: F, slow: S) -> Self {
+        Self {
+            fast: Arc::new(fast),
+            slow: Arc::new(slow),
+            options: TieredOptions::default(),
+            state: Arc::new(Mutex::new(TierState::default())),
+        }
+    }
+
+    pub fn with_options(mut self, options: TieredOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    pub fn options(&self) -> &TieredOptions {
+        &self.options
+    }
+
+    pub fn fast(&self) -> &F {
+        &self.fast
+    }
+
+    pub fn slow(&self) -> &S {
+        &self.slow
+    }
+
+    fn state(&self) -> MutexGuard<'_, TierState> {
+        // The state is always left consistent, so poisoning can be ignored.
+        self.state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn touch(&self, key: &str) {
+        self.state()
+            .accessed
+            .insert(key.to_string(), OffsetDateTime::now_utc());
+    }
+
+    fn mark_dirty(&self, key: &str) {
+        let mut state = self.state();
+        state.generation += 1;
+        let generation = state.generation;
+        state.dirty.insert(key.to_string(), generation);
+    }
+
+    fn forget(&self, key: &str) {
+        let mut state = self.state();
+        state.dirty.remove(key);
+        state.accessed.remove(key);
+    }
+
+    fn forget_prefix(&self, prefix: &str) {
+        let mut state = self.state();
+        state.dirty.retain(|key, _| !key.starts_with(prefix));
+        state.accessed.retain(|key, _| !key.starts_with(prefix));
+    }
+
+    fn has_dirty(&self) -> bool {
+        !self.state().dirty.is_empty()
+    }
+
+    fn cacheable(&self, size: Option<u64>) -> bool {
+        size.is_some_and(|size| size <= self.options.max_object_size)
+    }
+
+    /// Clone a write-through put for the fast tier, if it should be cached.
+    fn cache_copy(&self, put: &Put) -> Option<Put> {
+        // Streams can only be sent once, so they are not cached.
+        put.try_clone()
+            .filter(|put| self.cacheable(put.data.size()))
+    }
+}
+
+/// Build a put request that keeps the metadata of an existing object.
+fn put_with_meta(key: &str, data: Bytes, meta: &ObjectMeta) -> Put {
+    let mut put = Put::new(key, data);
+    put.mime_type = meta.mime_type.clone();
+    put.cache_control = meta.cache_control.clone();
+    put.content_disposition = meta.content_disposition.clone();
+    put.content_encoding = meta.content_encoding.clone();
+    put.expires_at = meta.expires_at;
+    put.modified_at = meta.updated_at;
+    put
+}
+
+/// Failures of the fast tier only affect caching, so they are logged
+/// (with the `tracing` feature) rather than returned.
+#[cfg_attr(not(feature = "tracing"), allow(unused_variables))]
+fn cache_error(key: &str, err: crate::ObjStoreError) {
+    #[cfg(feature = "tracing")]
+    tracing::warn!(key, error = %err, "updating the fast tier failed");
+}
+
+impl<F, S> TieredObjStore<F, S>
+where
+    F: ObjStore + Send + Sync + 'static,
+    S: ObjStore + Send + Sync + 'static,
+{
+    /// Copy an object read from the slow tier into the fast tier.
+    async fn promote(&self, key: &str, data: &Bytes, meta: &ObjectMeta) {
+        if !self.cacheable(Some(data.len() as u64)) {
+            return;
+        }
+        match self
+            .fast
+            .send_put(put_with_meta(key, data.clone(), meta))
+            .await
+        {
+            Ok(_) => self.touch(key),
+            Err(err) => cache_error(key, err),
+        }
+    }
+
+    /// Remove a possibly outdated object from the fast tier.
+    async fn invalidate(&self, key: &str) {
+        self.forget(key);
+        if let Err(err) = self.fast.delete(key).await {
+            cache_error(key, err);
+        }
+    }
+
+    /// Update the fast tier after a write-through put to the slow tier.
+    async fn cache_put(&self, key: &str, cached: Option<Put>) {
+        self.forget(key);
+        match cached {
+            Some(cached) => match self.fast.send_put(cached).await {
+                Ok(_) => self.touch(key),
+                Err(err) => {
+                    cache_error(key, err);
+                    self.invalidate(key).await;
+                }
+            },
+            None => self.invalidate(key).await,
+        }
+    }
+
+    /// Copy an object written with [`WritePolicy::WriteBack`] to the slow tier.
+    async fn flush_key(&self, key: &str) -> Result<bool> {
+        let Some(generation) = self.state().dirty.get(key).copied() else {
+            return Ok(false);
+        };
+        if let Some((data, meta)) = self.fast.get_with_meta(key).await? {
+            self.slow.send_put(put_with_meta(key, data, &meta)).await?;
+        }
+        let mut state = self.state();
+        // Keep the entry if the key was written again during the flush.
+        if state.dirty.get(key) == Some(&generation) {
+            state.dirty.remove(key);
+        }
+        Ok(true)
+    }
+
+    /// Copy all objects that were only written to the fast tier to the slow tier.
+    ///
+    /// Returns the number of flushed objects.
+    pub async fn flush(&self) -> Result<u64> {
+        let keys = self.state().dirty.keys().cloned().collect::<Vec<_>>();
+        let mut flushed = 0;
+        for key in keys {
+            if self.flush_key(&key).await? {
+                flushed += 1;
+            }
+        }
+        Ok(flushed)
+    }
+
+    /// Evict cold objects from the fast tier.
+    ///
+    /// Pending writes are flushed first, so evicted objects are always
+    /// available from the slow tier.
+    ///
+    /// Returns the number of evicted objects.
+    pub async fn demote(&self) -> Result<u64> {
+        if self.options.max_age.is_none() && self.options.max_fast_size.is_none() {
+            return Ok(0);
+        }
+        self.flush().await?;
+
+        let mut objects = Vec::new();
+        let mut pages = list_pages(&*self.fast, ListArgs::new());
+        while let Some(page) = pages.try_next().await? {
+            let state = self.state();
+            for meta in page.items {
+                if state.dirty.contains_key(&meta.key) {
+                    continue;
+                }
+                let accessed = state.accessed.get(&meta.key).copied().or(meta.updated_at);
+                objects.push((accessed, meta.size.unwrap_or_default(), meta.key));
+            }
+        }
+        // Least recently used first, unknown access times count as oldest.
+        objects.sort();
+
+        let mut evict = Vec::new();
+        if let Some(max_age) = self.options.max_age {
+            let cutoff = OffsetDateTime::now_utc() - max_age;
+            let fresh =
+                objects.partition_point(|(accessed, _, _)| accessed.is_none_or(|at| at <= cutoff));
+            evict.extend(objects.drain(..fresh).map(|(_, _, key)| key));
+        }
+        if let Some(max_size) = self.options.max_fast_size {
+            let mut total = objects.iter().map(|(_, size, _)| size).sum::<u64>();
+            let mut objects = objects.into_iter();
+            while total > max_size
+                && let Some((_, size, key)) = objects.next()
+            {
+                total -= size;
+                evict.push(key);
+            }
+        }
+
+        let mut evicted = 0;
+        for key in evict {
+            self.fast.delete(&key).await?;
+            self.state().accessed.remove(&key);
+            evicted += 1;
+        }
+        Ok(evicted)
+    }
+
+    /// Run [`Self::flush`] and [`Self::demote`] every `interval`, forever.
+    ///
+    /// Meant to be spawned as a background task. Errors are logged (with the
+    /// `tracing` feature) and retried on the next tick.
+    pub async fn run_maintenance(self, interval: Duration) {
+        loop {
+            tokio::time::sleep(interval).await;
+            let res = match self.flush().await {
+                Ok(_) => self.demote().await,
+                Err(err) => Err(err),
+            };
+            #[cfg_attr(not(feature = "tracing"), allow(unused_variables))]
+            if let Err(err) = res {
+                #[cfg(feature = "tracing")]
+                tracing::warn!(error = %err, "tiered store maintenance failed");
+            }
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl<F, S> ObjStore for TieredObjStore<F, S>
+where
+    F: ObjStore + Send + Sync + 'static,
+    S: ObjStore + Send + Sync + 'static,
+{
+    fn kind(&self) -> &str {
+        self.slow.kind()
+    }
+
+    fn safe_uri(&self) -> &url::Url {
+        self.slow.safe_uri()
+    }
+
+    async fn healthcheck(&self) -> Result<()> {
+        self.fast.healthcheck().await?;
+        self.slow.healthcheck().await
+    }
+
+    async fn healthcheck_report(&self) -> Result<HealthReport> {
+        self.fast.healthcheck().await?;
+        self.slow.healthcheck_report().await
+    }
+
+    async fn meta(&self, key: &str) -> Result<Option<ObjectMeta>> {
+        if let Some(meta) = self.fast.meta(key).await? {
+            self.touch(key);
+            return Ok(Some(meta));
+        }
+        self.slow.meta(key).await
+    }
+
+    async fn exists(&self, key: &str) -> Result<bool> {
+        Ok(self.fast.exists(key).await? || self.slow.exists(key).await?)
+    }
+
+    // Keys that are not valid UTF-8 are never cached.
+
+    async fn meta_raw(&self, key: &ObjectKey) -> Result<Option<ObjectMeta>> {
+        match key.as_str() {
+            Some(key) => self.meta(key).await,
+            None => self.slow.meta_raw(key).await,
+        }
+    }
+
+    async fn get_raw(&self, key: &ObjectKey) -> Result<Option<Bytes>> {
+        match key.as_str() {
+            Some(key) => self.get(key).await,
+            None => self.slow.get_raw(key).await,
+        }
+    }
+
+    async fn delete_raw(&self, key: &ObjectKey) -> Result<()> {
+        match key.as_str() {
+            Some(key) => self.delete(key).await,
+            None => self.slow.delete_raw(key).await,
+        }
+    }
+
+    async fn get(&self, key: &str) -> Result<Option<Bytes>> {
+        Ok(self.get_with_meta(key).await?.map(|(data, _)| data))
+    }
+
+    async fn get_stream(&self, key: &str) -> Result<Option<ValueStream>> {
+        if let Some(stream) = self.fast.get_stream(key).await? {
+            self.touch(key);
+            return Ok(Some(stream));
+        }
+        self.slow.get_stream(key).await
+    }
+
+    async fn get_with_meta(&self, key: &str) -> Result<Option<(Bytes, ObjectMeta)>> {
+        if let Some(value) = self.fast.get_with_meta(key).await? {
+            self.touch(key);
+            return Ok(Some(value));
+        }
+        let Some((data, meta)) = self.slow.get_with_meta(key).await? else {
+            return Ok(None);
+        };
+        self.promote(key, &data, &meta).await;
+        Ok(Some((data, meta)))
+    }
+
+    async fn get_stream_with_meta(&self, key: &str) -> Result<Option<(ObjectMeta, ValueStream)>> {
+        if let Some(value) = self.fast.get_stream_with_meta(key).await? {
+            self.touch(key);
+            return Ok(Some(value));
+        }
+        self.slow.get_stream_with_meta(key).await
+    }
+
+    async fn get_range(&self, key: &str, range: std::ops::Range<u64>) -> Result<Option<Bytes>> {
+        // The fast tier may not support range reads.
+        if let Ok(Some(data)) = self.fast.get_range(key, range.clone()).await {
+            self.touch(key);
+            return Ok(Some(data));
+        }
+        self.slow.get_range(key, range).await
+    }
+
+    async fn generate_download_url(&self, args: DownloadUrlArgs) -> Result<Option<url::Url>> {
+        self.flush_key(&args.key).await?;
+        self.slow.generate_download_url(args).await
+    }
+
+    async fn generate_upload_url(&self, args: UploadUrlArgs) -> Result<Option<url::Url>> {
+        // The upload bypasses the wrapper, so the cached object becomes stale.
+        self.invalidate(&args.key).await;
+        self.slow.generate_upload_url(args).await
+    }
+
+    async fn send_put(&self, put: Put) -> Result<ObjectMeta> {
+        let key = put.key.clone();
+        if self.options.write_policy == WritePolicy::WriteBack && self.cacheable(put.data.size()) {
+            let meta = self.fast.send_put(put).await?;
+            self.mark_dirty(&key);
+            self.touch(&key);
+            return Ok(meta);
+        }
+
+        let cached = self.cache_copy(&put);
+        let meta = self.slow.send_put(put).await?;
+        self.cache_put(&key, cached).await;
+        Ok(meta)
+    }
+
+    async fn send_put_result(&self, put: Put) -> Result<PutResult> {
+        if self.options.write_policy == WritePolicy::WriteBack && self.cacheable(put.data.size()) {
+            // The slow store is only written on flush, so it can't tell
+            // whether the object existed.
+            let existed = self.exists(&put.key).await?;
+            let meta = self.send_put(put).await?;
+            return Ok(PutResult::new(meta, !existed));
+        }
+
+        let key = put.key.clone();
+        let cached = self.cache_copy(&put);
+        let result = self.slow.send_put_result(put).await?;
+        self.cache_put(&key, cached).await;
+        Ok(result)
+    }
+
+    async fn append(&self, key: &str, data: Bytes) -> Result<ObjectMeta> {
+        self.flush_key(key).await?;
+        let meta = self.slow.append(key, data).await?;
+        self.invalidate(key).await;
+        Ok(meta)
+    }
+
+    async fn send_copy(&self, copy: Copy) -> Result<ObjectMeta> {
+        self.flush_key(&copy.source_key).await?;
+        let target_key = copy.target_key.clone();
+        let meta = self.slow.send_copy(copy).await?;
+        self.invalidate(&target_key).await;
+        Ok(meta)
+    }
+
+    async fn delete(&self, key: &str) -> Result<()> {
+        self.forget(key);
+        self.fast.delete(key).await?;
+        self.slow.delete(key).await
+    }
+
+    async fn delete_prefix(&self, prefix: &str) -> Result<()> {
+        self.forget_prefix(prefix);
+        self.fast.delete_prefix(prefix).await?;
+        self.slow.delete_prefix(prefix).await
+    }
+
+    async fn usage(&self, prefix: &str) -> Result<Usage> {
+        if self.has_dirty() {
+            self.flush().await?;
+        }
+        self.slow.usage(prefix).await
+    }
+
+    async fn cleanup(&self, options: CleanupOptions) -> Result<CleanupReport> {
+        let mut report = self.slow.cleanup(options.clone()).await?;
+        report.merge(self.fast.cleanup(options).await?);
+        Ok(report)
+    }
+
+    fn supports_sort(&self, order: SortOrder) -> bool {
+        self.slow.supports_sort(order)
+    }
+
+    fn supports_conditions(&self) -> bool {
+        // Write-back puts are only checked against the fast tier.
+        self.options.write_policy != WritePolicy::WriteBack && self.slow.supports_conditions()
+    }
+
+    async fn watch(&self, prefix: &str) -> Result<EventStream> {
+        self.slow.watch(prefix).await
+    }
+
+    async fn list(&self, args: ListArgs) -> Result<ObjectMetaPage> {
+        if self.has_dirty() {
+            self.flush().await?;
+        }
+        self.slow.list(args).await
+    }
+
+    async fn list_keys(&self, args: ListArgs) -> Result<KeyPage> {
+        if self.has_dirty() {
+            self.flush().await?;
+        }
+        self.slow.list_keys(args).await
+    }
+}
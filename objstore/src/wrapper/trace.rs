@@ -1,8 +1,14 @@
+use std::time::{Duration, Instant};
+
 use bytes::Bytes;
+use futures::{StreamExt as _, stream};
+use tracing::{Instrument as _, Level, Span, field};
 
 use crate::{
-    Copy, DownloadUrlArgs, KeyPage, ListArgs, ObjStore, ObjectMeta, ObjectMetaPage, Put, Result,
-    UploadUrlArgs, ValueStream,
+    Copy, DownloadUrlArgs, EventStream, GetManyOptions, GetManyStream, HealthReport, KeyPage,
+    ListArgs, ObjStore, ObjectKey, ObjectMeta, ObjectMetaPage, Put, PutResult, Result, SortOrder,
+    UploadUrlArgs, Usage, ValueStream,
+    maintenance::{CleanupOptions, CleanupReport},
 };
 
 /// Wrapper for an object stores that logs operations with the `tracing` crate.
@@ -12,10 +18,20 @@ use crate::{
 /// * All put/delete operations will be logged at the `TRACE` level on start of the operation
 ///   and at the `DEBUG` level on completion.
 /// * All errors will be logged at the `ERROR` level
+///
+/// Each operation runs in an `objstore` span with `store`, `op`, `key` (or
+/// prefix), `size` and `duration_ms` fields, created at the
+/// [configured level](Self::with_level). Streams returned by reads log a
+/// completion event with the transferred `bytes` and `duration_ms` once they
+/// are fully consumed.
+///
+/// Operations that take longer than the [slow threshold](Self::with_slow_threshold)
+/// are additionally logged at the [slow level](Self::with_slow_level).
 #[derive(Debug)]
 pub struct TracedObjStore<S> {
     name: String,
     inner: S,
+    config: TraceConfig,
 }
 
 impl<S> TracedObjStore<S> {
@@ -26,6 +42,207 @@ impl<S> TracedObjStore<S> {
         Self {
             name: name.into(),
             inner,
+            config: TraceConfig::default(),
+        }
+    }
+
+    /// Set the level of operation spans and stream completion events.
+    ///
+    /// Defaults to `TRACE`.
+    pub fn with_level(mut self, level: Level) -> Self {
+        self.config.level = level;
+        self
+    }
+
+    /// Log operations that take at least `threshold` at the slow level.
+    pub fn with_slow_threshold(mut self, threshold: Duration) -> Self {
+        self.config.slow_threshold = Some(threshold);
+        self
+    }
+
+    /// Set the level for slow operations.
+    ///
+    /// Defaults to `WARN`.
+    pub fn with_slow_level(mut self, level: Level) -> Self {
+        self.config.slow_level = level;
+        self
+    }
+
+    /// Only report slow operations: skip the operation spans and the
+    /// completion events of fast streams.
+    ///
+    /// The per-operation events listed above are not affected, and can be
+    /// filtered by level as usual.
+    pub fn with_only_slow(mut self, only_slow: bool) -> Self {
+        self.config.only_slow = only_slow;
+        self
+    }
+
+    /// Run `fut` in an operation span, and record its size and duration.
+    async fn observe<T, F>(
+        &self,
+        op: &'static str,
+        key: &str,
+        fut: F,
+        size: impl FnOnce(&T) -> Option<u64>,
+    ) -> Result<T>
+    where
+        F: Future<Output = Result<T>>,
+    {
+        let span = self.config.span(&self.name, op, key);
+        let start = Instant::now();
+        let res = fut.instrument(span.clone()).await;
+        let elapsed = start.elapsed();
+
+        let size = res.as_ref().ok().and_then(size);
+        span.record("duration_ms", elapsed.as_millis() as u64);
+        if let Some(size) = size {
+            span.record("size", size);
+        }
+        if self.config.is_slow(elapsed) {
+            self.config.slow(&self.name, op, key, size, elapsed);
+        }
+        res
+    }
+
+    /// Log the number of bytes and the duration once `stream` is consumed.
+    fn observe_stream(&self, op: &'static str, key: &str, stream: ValueStream) -> ValueStream {
+        let config = self.config;
+        let store = self.name.clone();
+        let key = key.to_string();
+        let start = Instant::now();
+        stream::unfold((stream, 0u64, false), move |(mut stream, bytes, done)| {
+            let (store, key) = (store.clone(), key.clone());
+            async move {
+                if done {
+                    return None;
+                }
+                match stream.next().await {
+                    Some(Ok(chunk)) => {
+                        let bytes = bytes + chunk.len() as u64;
+                        Some((Ok(chunk), (stream, bytes, false)))
+                    }
+                    // Failures are reported by the consumer.
+                    Some(Err(err)) => Some((Err(err), (stream, bytes, true))),
+                    None => {
+                        config.stream_complete(&store, op, &key, bytes, start.elapsed());
+                        None
+                    }
+                }
+            }
+        })
+        .boxed()
+    }
+}
+
+/// Like [`tracing::span!`], with a level that is only known at runtime.
+macro_rules! dyn_span {
+    ($level:expr, $($args:tt)+) => {
+        match $level {
+            Level::ERROR => tracing::span!(Level::ERROR, $($args)+),
+            Level::WARN => tracing::span!(Level::WARN, $($args)+),
+            Level::INFO => tracing::span!(Level::INFO, $($args)+),
+            Level::DEBUG => tracing::span!(Level::DEBUG, $($args)+),
+            _ => tracing::span!(Level::TRACE, $($args)+),
+        }
+    };
+}
+
+/// Like [`tracing::event!`], with a level that is only known at runtime.
+macro_rules! dyn_event {
+    ($level:expr, $($args:tt)+) => {
+        match $level {
+            Level::ERROR => tracing::event!(Level::ERROR, $($args)+),
+            Level::WARN => tracing::event!(Level::WARN, $($args)+),
+            Level::INFO => tracing::event!(Level::INFO, $($args)+),
+            Level::DEBUG => tracing::event!(Level::DEBUG, $($args)+),
+            _ => tracing::event!(Level::TRACE, $($args)+),
+        }
+    };
+}
+
+#[derive(Clone, Copy, Debug)]
+struct TraceConfig {
+    level: Level,
+    slow_threshold: Option<Duration>,
+    slow_level: Level,
+    only_slow: bool,
+}
+
+impl Default for TraceConfig {
+    fn default() -> Self {
+        Self {
+            level: Level::TRACE,
+            slow_threshold: None,
+            slow_level: Level::WARN,
+            only_slow: false,
+        }
+    }
+}
+
+impl TraceConfig {
+    fn is_slow(&self, elapsed: Duration) -> bool {
+        self.slow_threshold
+            .is_some_and(|threshold| elapsed >= threshold)
+    }
+
+    fn span(&self, store: &str, op: &'static str, key: &str) -> Span {
+        if self.only_slow {
+            return Span::none();
+        }
+        dyn_span!(
+            self.level,
+            "objstore",
+            store,
+            op,
+            key,
+            size = field::Empty,
+            duration_ms = field::Empty
+        )
+    }
+
+    fn slow(&self, store: &str, op: &'static str, key: &str, size: Option<u64>, elapsed: Duration) {
+        let duration_ms = elapsed.as_millis() as u64;
+        dyn_event!(
+            self.slow_level,
+            store,
+            op,
+            key,
+            size,
+            duration_ms,
+            "slow operation"
+        );
+    }
+
+    fn stream_complete(
+        &self,
+        store: &str,
+        op: &'static str,
+        key: &str,
+        bytes: u64,
+        elapsed: Duration,
+    ) {
+        let duration_ms = elapsed.as_millis() as u64;
+        if self.is_slow(elapsed) {
+            dyn_event!(
+                self.slow_level,
+                store,
+                op,
+                key,
+                bytes,
+                duration_ms,
+                "slow stream"
+            );
+        } else if !self.only_slow {
+            dyn_event!(
+                self.level,
+                store,
+                op,
+                key,
+                bytes,
+                duration_ms,
+                "stream::complete"
+            );
         }
     }
 }
@@ -45,7 +262,10 @@ where
 
     async fn healthcheck(&self) -> Result<()> {
         tracing::debug!("Performing healthcheck on object store: {}", self.kind());
-        match self.inner.healthcheck().await {
+        let res = self
+            .observe("healthcheck", "", self.inner.healthcheck(), |_| None)
+            .await;
+        match res {
             Ok(_) => {
                 tracing::debug!(store = &self.name, "healthcheck::ok");
                 Ok(())
@@ -57,8 +277,38 @@ where
         }
     }
 
+    async fn healthcheck_report(&self) -> Result<HealthReport> {
+        let res = self
+            .observe(
+                "healthcheck_report",
+                "",
+                self.inner.healthcheck_report(),
+                |_| None,
+            )
+            .await;
+        match res {
+            Ok(report) => {
+                if report.has_warnings() {
+                    tracing::warn!(store = &self.name, ?report, "healthcheck_report::warnings");
+                } else {
+                    tracing::debug!(store = &self.name, ?report, "healthcheck_report::ok");
+                }
+                Ok(report)
+            }
+            Err(e) => {
+                tracing::error!(store=&self.name, error=%e, "healthcheck_report::failed");
+                Err(e)
+            }
+        }
+    }
+
     async fn meta(&self, key: &str) -> Result<Option<ObjectMeta>> {
-        match self.inner.meta(key).await {
+        let res = self
+            .observe("meta", key, self.inner.meta(key), |meta| {
+                meta.as_ref().and_then(|meta| meta.size)
+            })
+            .await;
+        match res {
             Ok(meta) => {
                 tracing::trace!(store = &self.name, key, ?meta, "get_meta");
                 Ok(meta)
@@ -70,8 +320,29 @@ where
         }
     }
 
+    async fn exists(&self, key: &str) -> Result<bool> {
+        let res = self
+            .observe("exists", key, self.inner.exists(key), |_| None)
+            .await;
+        match res {
+            Ok(exists) => {
+                tracing::trace!(store = &self.name, key, exists, "exists::ok");
+                Ok(exists)
+            }
+            Err(e) => {
+                tracing::error!(store = &self.name, key, error=%e, "exists::failed");
+                Err(e)
+            }
+        }
+    }
+
     async fn get(&self, key: &str) -> Result<Option<Bytes>> {
-        match self.inner.get(key).await {
+        let res = self
+            .observe("get", key, self.inner.get(key), |value| {
+                value.as_ref().map(|value| value.len() as u64)
+            })
+            .await;
+        match res {
             Ok(Some(value)) => {
                 tracing::trace!(store = &self.name, key, "get::ok");
                 Ok(Some(value))
@@ -88,10 +359,13 @@ where
     }
 
     async fn get_stream(&self, key: &str) -> Result<Option<ValueStream>> {
-        match self.inner.get_stream(key).await {
+        let res = self
+            .observe("get_stream", key, self.inner.get_stream(key), |_| None)
+            .await;
+        match res {
             Ok(Some(value)) => {
                 tracing::trace!(store = &self.name, key, "get_stream::ok");
-                Ok(Some(value))
+                Ok(Some(self.observe_stream("get_stream", key, value)))
             }
             Ok(None) => {
                 tracing::trace!(store = &self.name, key, "get_stream::not_found");
@@ -105,7 +379,15 @@ where
     }
 
     async fn get_with_meta(&self, key: &str) -> Result<Option<(Bytes, ObjectMeta)>> {
-        match self.inner.get_with_meta(key).await {
+        let res = self
+            .observe(
+                "get_with_meta",
+                key,
+                self.inner.get_with_meta(key),
+                |value| value.as_ref().map(|(value, _)| value.len() as u64),
+            )
+            .await;
+        match res {
             Ok(Some((value, meta))) => {
                 tracing::trace!(store = &self.name, key, ?meta, "get_with_meta::ok");
                 Ok(Some((value, meta)))
@@ -122,9 +404,18 @@ where
     }
 
     async fn get_stream_with_meta(&self, key: &str) -> Result<Option<(ObjectMeta, ValueStream)>> {
-        match self.inner.get_stream_with_meta(key).await {
+        let res = self
+            .observe(
+                "get_stream_with_meta",
+                key,
+                self.inner.get_stream_with_meta(key),
+                |value| value.as_ref().and_then(|(meta, _)| meta.size),
+            )
+            .await;
+        match res {
             Ok(Some((meta, value))) => {
                 tracing::trace!(store = &self.name, key, ?meta, "get_stream_with_meta::ok");
+                let value = self.observe_stream("get_stream_with_meta", key, value);
                 Ok(Some((meta, value)))
             }
             Ok(None) => {
@@ -137,8 +428,56 @@ where
             }
         }
     }
+
+    async fn get_range(&self, key: &str, range: std::ops::Range<u64>) -> Result<Option<Bytes>> {
+        let (start, end) = (range.start, range.end);
+        let res = self
+            .observe(
+                "get_range",
+                key,
+                self.inner.get_range(key, range),
+                |value| value.as_ref().map(|value| value.len() as u64),
+            )
+            .await;
+        match res {
+            Ok(Some(value)) => {
+                tracing::trace!(store = &self.name, key, start, end, "get_range::ok");
+                Ok(Some(value))
+            }
+            Ok(None) => {
+                tracing::trace!(store = &self.name, key, start, end, "get_range::not_found");
+                Ok(None)
+            }
+            Err(e) => {
+                tracing::error!(store = &self.name, key, start, end, error=%e, "get_range::failed");
+                Err(e)
+            }
+        }
+    }
+
+    fn get_many<'a>(&'a self, keys: Vec<String>, options: GetManyOptions) -> GetManyStream<'a> {
+        tracing::trace!(store = &self.name, keys = keys.len(), "get_many");
+        self.inner
+            .get_many(keys, options)
+            .inspect(|(key, res)| {
+                if let Err(e) = res {
+                    tracing::error!(store = &self.name, key, error=%e, "get_many::failed");
+                }
+            })
+            .boxed()
+    }
+
     async fn generate_download_url(&self, args: DownloadUrlArgs) -> Result<Option<url::Url>> {
-        match self.inner.generate_download_url(args).await {
+        let key = args.key.clone();
+        let res = self
+            .observe(
+                "generate_download_url",
+                &key,
+                self.inner.generate_download_url(args),
+                |_| None,
+            )
+            .await;
+        match res {
             Ok(Some(url)) => {
                 tracing::trace!(store = &self.name, %url, "generate_download_url::ok");
                 Ok(Some(url))
@@ -158,7 +497,16 @@ where
     }
 
     async fn generate_upload_url(&self, args: UploadUrlArgs) -> Result<Option<url::Url>> {
-        match self.inner.generate_upload_url(args).await {
+        let key = args.key.clone();
+        let res = self
+            .observe(
+                "generate_upload_url",
+                &key,
+                self.inner.generate_upload_url(args),
+                |_| None,
+            )
+            .await;
+        match res {
             Ok(Some(url)) => {
                 tracing::trace!(store = &self.name, %url, "generate_upload_url::ok");
                 Ok(Some(url))
@@ -180,7 +528,10 @@ where
     async fn send_put(&self, put: Put) -> Result<ObjectMeta> {
         let key = put.key.clone();
         tracing::trace!(store = &self.name, key, "put::start");
-        match self.inner.send_put(put).await {
+        let res = self
+            .observe("send_put", &key, self.inner.send_put(put), |meta| meta.size)
+            .await;
+        match res {
             Ok(out) => {
                 tracing::debug!(store = &self.name, key, "put::ok");
                 Ok(out)
@@ -192,14 +543,63 @@ where
         }
     }
 
+    async fn send_put_result(&self, put: Put) -> Result<PutResult> {
+        let key = put.key.clone();
+        tracing::trace!(store = &self.name, key, "put::start");
+        let res = self
+            .observe(
+                "send_put_result",
+                &key,
+                self.inner.send_put_result(put),
+                |result| result.meta.size,
+            )
+            .await;
+        match res {
+            Ok(out) => {
+                tracing::debug!(store = &self.name, key, created = out.created, "put::ok");
+                Ok(out)
+            }
+            Err(e) => {
+                tracing::error!(store = &self.name, key, error=%e, "put::failed");
+                Err(e)
+            }
+        }
+    }
+
+    async fn append(&self, key: &str, data: Bytes) -> Result<ObjectMeta> {
+        let size = data.len();
+        tracing::trace!(store = &self.name, key, size, "append::start");
+        let res = self
+            .observe("append", key, self.inner.append(key, data), |meta| {
+                meta.size
+            })
+            .await;
+        match res {
+            Ok(out) => {
+                tracing::debug!(store = &self.name, key, size, "append::ok");
+                Ok(out)
+            }
+            Err(e) => {
+                tracing::error!(store = &self.name, key, error=%e, "append::failed");
+                Err(e)
+            }
+        }
+    }
+
     async fn send_copy(&self, copy: Copy) -> Result<ObjectMeta> {
+        let key = copy.target_key.clone();
         tracing::trace!(
             store = &self.name,
             src = &copy.source_key,
             dest = &copy.target_key,
             "copy::start"
         );
-        match self.inner.send_copy(copy).await {
+        let res = self
+            .observe("send_copy", &key, self.inner.send_copy(copy), |meta| {
+                meta.size
+            })
+            .await;
+        match res {
             Ok(out) => {
                 tracing::debug!(store = &self.name, "copy::ok");
                 Ok(out)
@@ -213,7 +613,10 @@ where
 
     async fn delete(&self, key: &str) -> Result<()> {
         tracing::trace!(store = &self.name, key, "delete::start");
-        match self.inner.delete(key).await {
+        let res = self
+            .observe("delete", key, self.inner.delete(key), |_| None)
+            .await;
+        match res {
             Ok(_) => {
                 tracing::debug!(store = &self.name, key, "delete::ok");
                 Ok(())
@@ -225,9 +628,116 @@ where
         }
     }
 
+    async fn meta_raw(&self, key: &ObjectKey) -> Result<Option<ObjectMeta>> {
+        let display = key.to_string();
+        let res = self
+            .observe("meta_raw", &display, self.inner.meta_raw(key), |meta| {
+                meta.as_ref().and_then(|meta| meta.size)
+            })
+            .await;
+        match res {
+            Ok(meta) => {
+                tracing::trace!(store = &self.name, %key, ?meta, "meta_raw::ok");
+                Ok(meta)
+            }
+            Err(e) => {
+                tracing::error!(store = &self.name, %key, error=%e, "meta_raw::failed");
+                Err(e)
+            }
+        }
+    }
+
+    async fn get_raw(&self, key: &ObjectKey) -> Result<Option<Bytes>> {
+        let display = key.to_string();
+        let res = self
+            .observe("get_raw", &display, self.inner.get_raw(key), |value| {
+                value.as_ref().map(|value| value.len() as u64)
+            })
+            .await;
+        match res {
+            Ok(value) => {
+                tracing::trace!(store = &self.name, %key, found = value.is_some(), "get_raw::ok");
+                Ok(value)
+            }
+            Err(e) => {
+                tracing::error!(store = &self.name, %key, error=%e, "get_raw::failed");
+                Err(e)
+            }
+        }
+    }
+
+    async fn delete_raw(&self, key: &ObjectKey) -> Result<()> {
+        let display = key.to_string();
+        tracing::trace!(store = &self.name, %key, "delete_raw::start");
+        let res = self
+            .observe("delete_raw", &display, self.inner.delete_raw(key), |_| None)
+            .await;
+        match res {
+            Ok(()) => {
+                tracing::debug!(store = &self.name, %key, "delete_raw::ok");
+                Ok(())
+            }
+            Err(e) => {
+                tracing::error!(store = &self.name, %key, error=%e, "delete_raw::failed");
+                Err(e)
+            }
+        }
+    }
+
+    async fn put_raw(&self, key: &ObjectKey, data: Bytes) -> Result<ObjectMeta> {
+        let display = key.to_string();
+        let size = data.len();
+        tracing::trace!(store = &self.name, %key, size, "put_raw::start");
+        let res = self
+            .observe("put_raw", &display, self.inner.put_raw(key, data), |meta| {
+                meta.size
+            })
+            .await;
+        match res {
+            Ok(meta) => {
+                tracing::debug!(store = &self.name, %key, "put_raw::ok");
+                Ok(meta)
+            }
+            Err(e) => {
+                tracing::error!(store = &self.name, %key, error=%e, "put_raw::failed");
+                Err(e)
+            }
+        }
+    }
+
+    async fn list_all_keys_raw(&self, prefix: &ObjectKey) -> Result<Vec<ObjectKey>> {
+        let display = prefix.to_string();
+        let res = self
+            .observe(
+                "list_all_keys_raw",
+                &display,
+                self.inner.list_all_keys_raw(prefix),
+                |_| None,
+            )
+            .await;
+        match res {
+            Ok(keys) => {
+                tracing::trace!(store = &self.name, %prefix, count = keys.len(), "list_all_keys_raw::ok");
+                Ok(keys)
+            }
+            Err(e) => {
+                tracing::error!(store = &self.name, %prefix, error=%e, "list_all_keys_raw::failed");
+                Err(e)
+            }
+        }
+    }
+
     async fn delete_prefix(&self, prefix: &str) -> Result<()> {
         tracing::trace!(store = &self.name, prefix, "delete_prefix::start");
-        match self.inner.delete_prefix(prefix).await {
+        let res = self
+            .observe(
+                "delete_prefix",
+                prefix,
+                self.inner.delete_prefix(prefix),
+                |_| None,
+            )
+            .await;
+        match res {
             Ok(_) => {
                 tracing::debug!(store = &self.name, prefix, "delete_prefix::ok");
                 Ok(())
@@ -239,8 +749,84 @@ where
         }
     }
 
+    async fn usage(&self, prefix: &str) -> Result<Usage> {
+        let res = self
+            .observe("usage", prefix, self.inner.usage(prefix), |usage| {
+                Some(usage.bytes)
+            })
+            .await;
+        match res {
+            Ok(usage) => {
+                tracing::trace!(
+                    store = &self.name,
+                    prefix,
+                    objects = usage.objects,
+                    bytes = usage.bytes,
+                    "usage::ok"
+                );
+                Ok(usage)
+            }
+            Err(e) => {
+                tracing::error!(store = &self.name, prefix, error=%e, "usage::failed");
+                Err(e)
+            }
+        }
+    }
+
+    async fn cleanup(&self, options: CleanupOptions) -> Result<CleanupReport> {
+        tracing::trace!(store = &self.name, ?options, "cleanup::start");
+        let res = self
+            .observe("cleanup", "", self.inner.cleanup(options), |report| {
+                Some(report.removed_bytes())
+            })
+            .await;
+        match res {
+            Ok(report) => {
+                tracing::debug!(
+                    store = &self.name,
+                    removed = report.removed.len(),
+                    failed = report.failed.len(),
+                    "cleanup::ok"
+                );
+                Ok(report)
+            }
+            Err(e) => {
+                tracing::error!(store = &self.name, error=%e, "cleanup::failed");
+                Err(e)
+            }
+        }
+    }
+
+    fn supports_sort(&self, order: SortOrder) -> bool {
+        self.inner.supports_sort(order)
+    }
+
+    fn supports_conditions(&self) -> bool {
+        self.inner.supports_conditions()
+    }
+
+    async fn watch(&self, prefix: &str) -> Result<EventStream> {
+        let res = self
+            .observe("watch", prefix, self.inner.watch(prefix), |_| None)
+            .await;
+        match res {
+            Ok(stream) => {
+                tracing::trace!(store = &self.name, prefix, "watch::ok");
+                Ok(stream)
+            }
+            Err(e) => {
+                tracing::error!(store = &self.name, prefix, error=%e, "watch::failed");
+                Err(e)
+            }
+        }
+    }
+
     async fn list(&self, args: ListArgs) -> Result<ObjectMetaPage> {
-        match self.inner.list(args).await {
+        let prefix = args.prefix().unwrap_or_default().to_string();
+        let res = self
+            .observe("list", &prefix, self.inner.list(args), |_| None)
+            .await;
+        match res {
             Ok(page) => {
                 tracing::trace!(store = &self.name, ?page, "list::ok");
                 Ok(page)
@@ -253,7 +839,11 @@ where
     }
 
     async fn list_keys(&self, args: ListArgs) -> Result<KeyPage> {
-        match self.inner.list_keys(args).await {
+        let prefix = args.prefix().unwrap_or_default().to_string();
+        let res = self
+            .observe("list_keys", &prefix, self.inner.list_keys(args), |_| None)
+            .await;
+        match res {
             Ok(page) => {
                 tracing::trace!(store = &self.name, ?page, "list_keys::ok");
                 Ok(page)
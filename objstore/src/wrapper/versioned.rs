@@ -0,0 +1,352 @@
+//! Object history for backends without native versioning.
+//!
+//! See [`VersionedObjStore`].
+
+use bytes::Bytes;
+use futures::{StreamExt as _, TryStreamExt as _};
+use time::OffsetDateTime;
+
+use crate::{
+    Copy, DownloadUrlArgs, EventStream, GetManyOptions, GetManyStream, HealthReport, KeyPage,
+    ListArgs, ObjStore, ObjStoreError, ObjectKey, ObjectMeta, ObjectMetaPage, Put, PutResult,
+    Result, SortOrder, UploadUrlArgs, Usage, ValueStream,
+    maintenance::{CleanupOptions, CleanupReport},
+    store::list_pages,
+};
+
+/// A previous version of an object, see [`VersionedObjStore::list_versions`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct ObjectVersion {
+    /// Identifier of the version, sortable by creation time.
+    pub id: String,
+    /// Time at which the version was replaced or deleted.
+    pub archived_at: Option<OffsetDateTime>,
+    /// Metadata of the stored version, with the key in the version area.
+    pub meta: ObjectMeta,
+}
+
+/// Wrapper that keeps the previous versions of overwritten and deleted
+/// objects.
+///
+/// Before an object is replaced by a put, copy or append, or deleted, it is
+/// copied to `<prefix><key>/<version id>`, where the version id is the current
+/// time in nanoseconds. Versions can be inspected with
+/// [`Self::list_versions`] and brought back with [`Self::restore_version`],
+/// which gives point-in-time recovery on backends without native versioning.
+///
+/// Keeping a version costs one server-side copy per mutation. Versions are
+/// kept until removed with [`Self::prune_versions`].
+///
+/// The version area is hidden from listings, unless the listed prefix is
+/// inside of it, and writes to it are rejected. Usage includes versions.
+/// Deletes of non-UTF-8 keys (see [`ObjStore::delete_raw`]) are not
+/// versioned.
+#[derive(Clone, Debug)]
+pub struct VersionedObjStore<S> {
+    inner: S,
+    prefix: String,
+}
+
+impl<S> VersionedObjStore<S> {
+    pub const DEFAULT_PREFIX: &str = "__versions/";
+
+    pub fn new(inner: S) -> Self {
+        Self {
+            inner,
+            prefix: Self::DEFAULT_PREFIX.to_string(),
+        }
+    }
+
+    /// Store versions below `prefix` instead of [`Self::DEFAULT_PREFIX`].
+    ///
+    /// A trailing `/` is added if missing, and an empty prefix is ignored.
+    pub fn with_prefix(mut self, prefix: impl Into<String>) -> Self {
+        let mut prefix = prefix.into();
+        if !prefix.is_empty() {
+            if !prefix.ends_with('/') {
+                prefix.push('/');
+            }
+            self.prefix = prefix;
+        }
+        self
+    }
+
+    pub fn inner(&self) -> &S {
+        &self.inner
+    }
+
+    /// Prefix of the version area.
+    pub fn prefix(&self) -> &str {
+        &self.prefix
+    }
+
+    fn versions_prefix(&self, key: &str) -> String {
+        format!("{}{key}/", self.prefix)
+    }
+
+    fn is_version_key(&self, key: &str) -> bool {
+        key.starts_with(&self.prefix)
+    }
+
+    fn check_key(&self, key: &str) -> Result<()> {
+        if self.is_version_key(key) {
+            return Err(ObjStoreError::InvalidRequest {
+                message: format!("key '{key}' is inside the version area"),
+                source: None,
+            });
+        }
+        Ok(())
+    }
+
+    /// Whether a listing with `args` must hide the version area.
+    fn hides_versions(&self, args: &ListArgs) -> bool {
+        !self.is_version_key(args.prefix().unwrap_or_default())
+    }
+}
+
+impl<S> VersionedObjStore<S>
+where
+    S: ObjStore + Send + Sync,
+{
+    /// Copy the current object at `key` into the version area, if it exists.
+    async fn preserve(&self, key: &str) -> Result<()> {
+        self.check_key(key)?;
+        let id = format!("{:020}", OffsetDateTime::now_utc().unix_timestamp_nanos());
+        let copy = Copy::new(key, format!("{}{id}", self.versions_prefix(key)));
+        match self.inner.send_copy(copy).await {
+            Ok(_) | Err(ObjStoreError::ObjectNotFound { .. }) => Ok(()),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// List the stored versions of `key`, oldest first.
+    pub async fn list_versions(&self, key: &str) -> Result<Vec<ObjectVersion>> {
+        let prefix = self.versions_prefix(key);
+        let mut versions = Vec::new();
+        let mut pages = list_pages(&self.inner, ListArgs::new().with_prefix(&prefix));
+        while let Some(page) = pages.try_next().await? {
+            for meta in page.items {
+                let Some(id) = meta.key.strip_prefix(&prefix) else {
+                    continue;
+                };
+                // Skip versions of keys below `key`.
+                if id.contains('/') {
+                    continue;
+                }
+                let archived_at = id
+                    .parse::<i128>()
+                    .ok()
+                    .and_then(|nanos| OffsetDateTime::from_unix_timestamp_nanos(nanos).ok());
+                versions.push(ObjectVersion {
+                    id: id.to_string(),
+                    archived_at,
+                    meta,
+                });
+            }
+        }
+        versions.sort_by(|a, b| a.id.cmp(&b.id));
+        Ok(versions)
+    }
+
+    /// Get the data of a stored version.
+    pub async fn get_version(&self, key: &str, id: &str) -> Result<Option<Bytes>> {
+        self.inner
+            .get(&format!("{}{id}", self.versions_prefix(key)))
+            .await
+    }
+
+    /// Replace the current object at `key` with a stored version.
+    ///
+    /// The current object is kept as a new version, so a restore can be
+    /// undone. Fails with [`ObjStoreError::ObjectNotFound`] if the version
+    /// does not exist.
+    pub async fn restore_version(&self, key: &str, id: &str) -> Result<ObjectMeta> {
+        let source = format!("{}{id}", self.versions_prefix(key));
+        if !self.inner.exists(&source).await? {
+            return Err(ObjStoreError::object_not_found(source));
+        }
+        self.preserve(key).await?;
+        self.inner.send_copy(Copy::new(source, key)).await
+    }
+
+    /// Delete all but the newest `keep` versions of `key`.
+    ///
+    /// Returns the number of deleted versions.
+    pub async fn prune_versions(&self, key: &str, keep: usize) -> Result<u64> {
+        let versions = self.list_versions(key).await?;
+        let excess = versions.len().saturating_sub(keep);
+        for version in &versions[..excess] {
+            self.inner.delete(&version.meta.key).await?;
+        }
+        Ok(excess as u64)
+    }
+}
+
+#[async_trait::async_trait]
+impl<S> ObjStore for VersionedObjStore<S>
+where
+    S: ObjStore + Send + Sync,
+{
+    fn kind(&self) -> &str {
+        self.inner.kind()
+    }
+
+    fn safe_uri(&self) -> &url::Url {
+        self.inner.safe_uri()
+    }
+
+    async fn healthcheck(&self) -> Result<()> {
+        self.inner.healthcheck().await
+    }
+
+    async fn healthcheck_report(&self) -> Result<HealthReport> {
+        self.inner.healthcheck_report().await
+    }
+
+    async fn meta(&self, key: &str) -> Result<Option<ObjectMeta>> {
+        self.inner.meta(key).await
+    }
+
+    async fn exists(&self, key: &str) -> Result<bool> {
+        self.inner.exists(key).await
+    }
+
+    async fn get(&self, key: &str) -> Result<Option<Bytes>> {
+        self.inner.get(key).await
+    }
+
+    async fn meta_raw(&self, key: &ObjectKey) -> Result<Option<ObjectMeta>> {
+        self.inner.meta_raw(key).await
+    }
+
+    async fn get_raw(&self, key: &ObjectKey) -> Result<Option<Bytes>> {
+        self.inner.get_raw(key).await
+    }
+
+    async fn delete_raw(&self, key: &ObjectKey) -> Result<()> {
+        match key.as_str() {
+            Some(key) => self.delete(key).await,
+            None => self.inner.delete_raw(key).await,
+        }
+    }
+
+    async fn get_stream(&self, key: &str) -> Result<Option<ValueStream>> {
+        self.inner.get_stream(key).await
+    }
+
+    async fn get_with_meta(&self, key: &str) -> Result<Option<(Bytes, ObjectMeta)>> {
+        self.inner.get_with_meta(key).await
+    }
+
+    async fn get_stream_with_meta(&self, key: &str) -> Result<Option<(ObjectMeta, ValueStream)>> {
+        self.inner.get_stream_with_meta(key).await
+    }
+
+    async fn get_range(&self, key: &str, range: std::ops::Range<u64>) -> Result<Option<Bytes>> {
+        self.inner.get_range(key, range).await
+    }
+
+    fn get_many<'a>(&'a self, keys: Vec<String>, options: GetManyOptions) -> GetManyStream<'a> {
+        self.inner.get_many(keys, options)
+    }
+
+    async fn generate_download_url(&self, args: DownloadUrlArgs) -> Result<Option<url::Url>> {
+        self.inner.generate_download_url(args).await
+    }
+
+    async fn generate_upload_url(&self, args: UploadUrlArgs) -> Result<Option<url::Url>> {
+        // Uploads via URL bypass the wrapper and would not be versioned.
+        let _ = args;
+        Ok(None)
+    }
+
+    async fn send_put(&self, put: Put) -> Result<ObjectMeta> {
+        self.preserve(&put.key).await?;
+        self.inner.send_put(put).await
+    }
+
+    async fn send_put_result(&self, put: Put) -> Result<PutResult> {
+        self.preserve(&put.key).await?;
+        self.inner.send_put_result(put).await
+    }
+
+    async fn append(&self, key: &str, data: Bytes) -> Result<ObjectMeta> {
+        self.preserve(key).await?;
+        self.inner.append(key, data).await
+    }
+
+    async fn send_copy(&self, copy: Copy) -> Result<ObjectMeta> {
+        self.preserve(&copy.target_key).await?;
+        self.inner.send_copy(copy).await
+    }
+
+    async fn delete(&self, key: &str) -> Result<()> {
+        self.preserve(key).await?;
+        self.inner.delete(key).await
+    }
+
+    async fn delete_prefix(&self, prefix: &str) -> Result<()> {
+        self.check_key(prefix)?;
+        let mut keys = Vec::new();
+        let mut pages = list_pages(self, ListArgs::new().with_prefix(prefix));
+        while let Some(page) = pages.try_next().await? {
+            keys.extend(page.items.into_iter().map(|meta| meta.key));
+        }
+        for key in keys {
+            self.delete(&key).await?;
+        }
+        Ok(())
+    }
+
+    async fn usage(&self, prefix: &str) -> Result<Usage> {
+        self.inner.usage(prefix).await
+    }
+
+    async fn cleanup(&self, options: CleanupOptions) -> Result<CleanupReport> {
+        self.inner.cleanup(options).await
+    }
+
+    fn supports_sort(&self, order: SortOrder) -> bool {
+        self.inner.supports_sort(order)
+    }
+
+    fn supports_conditions(&self) -> bool {
+        self.inner.supports_conditions()
+    }
+
+    async fn watch(&self, prefix: &str) -> Result<EventStream> {
+        let stream = self.inner.watch(prefix).await?;
+        if self.is_version_key(prefix) {
+            return Ok(stream);
+        }
+        let versions = self.prefix.clone();
+        Ok(Box::pin(stream.filter(move |event| {
+            let hidden = event
+                .as_ref()
+                .is_ok_and(|event| event.key().starts_with(&versions));
+            futures::future::ready(!hidden)
+        })))
+    }
+
+    async fn list(&self, args: ListArgs) -> Result<ObjectMetaPage> {
+        let hide = self.hides_versions(&args);
+        let mut page = self.inner.list(args).await?;
+        if hide {
+            page.items.retain(|meta| !self.is_version_key(&meta.key));
+            if let Some(prefixes) = &mut page.prefixes {
+                prefixes.retain(|prefix| !self.is_version_key(prefix));
+            }
+        }
+        Ok(page)
+    }
+
+    async fn list_keys(&self, args: ListArgs) -> Result<KeyPage> {
+        let hide = self.hides_versions(&args);
+        let mut page = self.inner.list_keys(args).await?;
+        if hide {
+            page.items.retain(|key| !self.is_version_key(key));
+        }
+        Ok(page)
+    }
+}
@@ -0,0 +1,111 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use bytes::Bytes;
+use objstore::{
+    Copy, DownloadUrlArgs, KeyPage, ListArgs, ObjStore, ObjStoreExt as _, ObjectMeta,
+    ObjectMetaPage, Put, Result, UploadUrlArgs, ValueStream,
+};
+use objstore_memory::MemoryObjStore;
+
+/// Store without native appends, which can inject a concurrent write.
+#[derive(Debug, Default)]
+struct EmulatedAppendStore {
+    inner: MemoryObjStore,
+    interfere: AtomicBool,
+}
+
+#[async_trait::async_trait]
+impl ObjStore for EmulatedAppendStore {
+    fn kind(&self) -> &str {
+        "emulated-append"
+    }
+
+    fn safe_uri(&self) -> &url::Url {
+        self.inner.safe_uri()
+    }
+
+    async fn healthcheck(&self) -> Result<()> {
+        Ok(())
+    }
+
+    async fn meta(&self, key: &str) -> Result<Option<ObjectMeta>> {
+        self.inner.meta(key).await
+    }
+
+    async fn get(&self, key: &str) -> Result<Option<Bytes>> {
+        self.inner.get(key).await
+    }
+
+    async fn get_stream(&self, key: &str) -> Result<Option<ValueStream>> {
+        self.inner.get_stream(key).await
+    }
+
+    async fn get_with_meta(&self, key: &str) -> Result<Option<(Bytes, ObjectMeta)>> {
+        self.inner.get_with_meta(key).await
+    }
+
+    async fn get_stream_with_meta(&self, key: &str) -> Result<Option<(ObjectMeta, ValueStream)>> {
+        self.inner.get_stream_with_meta(key).await
+    }
+
+    async fn generate_download_url(&self, args: DownloadUrlArgs) -> Result<Option<url::Url>> {
+        self.inner.generate_download_url(args).await
+    }
+
+    async fn generate_upload_url(&self, args: UploadUrlArgs) -> Result<Option<url::Url>> {
+        self.inner.generate_upload_url(args).await
+    }
+
+    async fn send_put(&self, put: Put) -> Result<ObjectMeta> {
+        if self.interfere.swap(false, Ordering::SeqCst) {
+            self.inner.append(&put.key, Bytes::from("x")).await?;
+        }
+        self.inner.send_put(put).await
+    }
+
+    async fn send_copy(&self, copy: Copy) -> Result<ObjectMeta> {
+        self.inner.send_copy(copy).await
+    }
+
+    async fn delete(&self, key: &str) -> Result<()> {
+        self.inner.delete(key).await
+    }
+
+    async fn delete_prefix(&self, prefix: &str) -> Result<()> {
+        self.inner.delete_prefix(prefix).await
+    }
+
+    async fn list(&self, args: ListArgs) -> Result<ObjectMetaPage> {
+        self.inner.list(args).await
+    }
+
+    async fn list_keys(&self, args: ListArgs) -> Result<KeyPage> {
+        self.inner.list_keys(args).await
+    }
+}
+
+#[tokio::test]
+async fn test_emulated_append_keeps_metadata() {
+    let store = EmulatedAppendStore::default();
+    store
+        .put("log")
+        .mime_type("text/plain")
+        .text("a")
+        .await
+        .unwrap();
+
+    let meta = store.append("log", Bytes::from("b")).await.unwrap();
+    assert_eq!(meta.size, Some(2));
+    assert_eq!(meta.mime_type.as_deref(), Some("text/plain"));
+    assert_eq!(store.get("log").await.unwrap().unwrap(), "ab");
+}
+
+#[tokio::test]
+async fn test_emulated_append_retries_on_conflict() {
+    let store = EmulatedAppendStore::default();
+    store.put("log").text("a").await.unwrap();
+
+    store.interfere.store(true, Ordering::SeqCst);
+    store.append("log", Bytes::from("b")).await.unwrap();
+    assert_eq!(store.get("log").await.unwrap().unwrap(), "axb");
+}
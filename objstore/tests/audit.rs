@@ -0,0 +1,96 @@
+use std::sync::Arc;
+
+use objstore::{
+    DynObjStore, ObjStore, ObjStoreError, ObjStoreExt as _,
+    wrapper::audit::{AuditOp, AuditOptions, AuditedObjStore, verify_audit_log},
+};
+use objstore_memory::MemoryObjStore;
+
+#[tokio::test]
+async fn test_audit_records_mutations_in_batches() {
+    let options = AuditOptions::new()
+        .with_actor("tester")
+        .with_max_batch_records(3);
+    let store = AuditedObjStore::new(MemoryObjStore::new(), options);
+
+    store.put("a").text("hello").await.unwrap();
+    store.copy("a", "b").send().await.unwrap();
+    assert_eq!(store.pending(), 2);
+    assert!(
+        store
+            .inner()
+            .list_all_keys("_audit/")
+            .await
+            .unwrap()
+            .is_empty()
+    );
+
+    // The third record fills the batch.
+    store.delete("a").await.unwrap();
+    assert_eq!(store.pending(), 0);
+    assert_eq!(
+        store.inner().list_all_keys("_audit/").await.unwrap().len(),
+        1
+    );
+
+    store.delete_prefix("b").await.unwrap();
+    store.flush().await.unwrap();
+    assert_eq!(
+        store.inner().list_all_keys("_audit/").await.unwrap().len(),
+        2
+    );
+
+    let records = verify_audit_log(store.inner(), "_audit/").await.unwrap();
+    let ops = records
+        .iter()
+        .map(|record| (record.op, record.key.as_str()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        ops,
+        vec![
+            (AuditOp::Put, "a"),
+            (AuditOp::Copy, "b"),
+            (AuditOp::Delete, "a"),
+            (AuditOp::DeletePrefix, "b"),
+        ]
+    );
+    assert_eq!(records[0].size, Some(5));
+    assert_eq!(records[0].actor.as_deref(), Some("tester"));
+    assert_eq!(records[1].source.as_deref(), Some("a"));
+}
+
+#[tokio::test]
+async fn test_audit_rejects_writes_to_audit_prefix() {
+    let store = AuditedObjStore::new(MemoryObjStore::new(), AuditOptions::new());
+    let err = store.put("_audit/x").text("x").await.unwrap_err();
+    assert!(matches!(err, ObjStoreError::InvalidRequest { .. }));
+    let err = store.delete_prefix("").await.unwrap_err();
+    assert!(matches!(err, ObjStoreError::InvalidRequest { .. }));
+    assert_eq!(store.pending(), 0);
+}
+
+#[tokio::test]
+async fn test_audit_to_separate_store_detects_tampering() {
+    let audit: DynObjStore = Arc::new(MemoryObjStore::new());
+    let store = AuditedObjStore::with_audit_store(
+        MemoryObjStore::new(),
+        audit.clone(),
+        AuditOptions::new(),
+    );
+    for key in ["a", "b", "c"] {
+        store.put(key).text(key).await.unwrap();
+    }
+    store.delete_prefix("").await.unwrap();
+    store.flush().await.unwrap();
+    assert_eq!(verify_audit_log(&audit, "_audit/").await.unwrap().len(), 4);
+
+    let key = audit.list_all_keys("_audit/").await.unwrap().remove(0);
+    let data = audit.get_text(&key).await.unwrap().unwrap();
+    audit
+        .put(&key)
+        .text(data.replacen("\"key\":\"b\"", "\"key\":\"x\"", 1))
+        .await
+        .unwrap();
+    let err = verify_audit_log(&audit, "_audit/").await.unwrap_err();
+    assert!(matches!(err, ObjStoreError::InvalidMetadata { .. }));
+}
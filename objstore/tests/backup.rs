@@ -0,0 +1,92 @@
+use objstore::{
+    ObjStore as _, ObjStoreExt as _,
+    backup::{BackupOptions, RestoreOptions, backup, latest_backup, list_backups, restore},
+};
+use objstore_memory::MemoryObjStore;
+
+#[tokio::test]
+async fn test_backup_and_restore() {
+    let source = MemoryObjStore::new();
+    source
+        .put("data/a.txt")
+        .mime_type("text/plain")
+        .text("hello")
+        .await
+        .unwrap();
+    source.put("data/b").text("hello").await.unwrap();
+    source.put("data/sub/c").text("world").await.unwrap();
+    source.put("other").text("ignored").await.unwrap();
+
+    let target = MemoryObjStore::new();
+    let report = backup(&source, "data/", &target, BackupOptions::new())
+        .await
+        .unwrap();
+    // a.txt and b share a blob.
+    assert_eq!(report.uploaded, 2);
+    assert_eq!(report.reused, 1);
+    let keys = report
+        .manifest
+        .entries
+        .iter()
+        .map(|entry| entry.key.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(keys, ["a.txt", "b", "sub/c"]);
+    assert_eq!(report.manifest.total_size(), 15);
+
+    let restored = MemoryObjStore::new();
+    let id = latest_backup(&target).await.unwrap().unwrap();
+    let report = restore(&target, &id, &restored, "copy/", RestoreOptions::new())
+        .await
+        .unwrap();
+    assert_eq!(report.restored, 3);
+    assert_eq!(
+        restored.list_all_keys("").await.unwrap(),
+        ["copy/a.txt", "copy/b", "copy/sub/c"]
+    );
+    assert_eq!(restored.get("copy/sub/c").await.unwrap().unwrap(), "world");
+    let meta = restored.meta("copy/a.txt").await.unwrap().unwrap();
+    assert_eq!(meta.mime_type.as_deref(), Some("text/plain"));
+}
+
+#[tokio::test]
+async fn test_incremental_backup_reuses_unchanged_objects() {
+    let source = MemoryObjStore::new();
+    source.put("a").text("one").await.unwrap();
+    source.put("b").text("two").await.unwrap();
+
+    let target = MemoryObjStore::new();
+    let first = backup(&source, "", &target, BackupOptions::new())
+        .await
+        .unwrap();
+    assert_eq!(first.uploaded, 2);
+
+    source.put("b").text("changed").await.unwrap();
+    source.put("c").text("three").await.unwrap();
+    source.delete("a").await.unwrap();
+    let second = backup(&source, "", &target, BackupOptions::new())
+        .await
+        .unwrap();
+    assert_eq!(second.uploaded, 2);
+    assert_eq!(second.reused, 0);
+    assert_eq!(second.manifest.entries.len(), 2);
+
+    let third = backup(&source, "", &target, BackupOptions::new())
+        .await
+        .unwrap();
+    assert_eq!(third.uploaded, 0);
+    assert_eq!(third.reused, 2);
+
+    let ids = list_backups(&target).await.unwrap();
+    assert_eq!(
+        ids,
+        [first.manifest.id, second.manifest.id, third.manifest.id]
+    );
+
+    // Older backups stay restorable.
+    let restored = MemoryObjStore::new();
+    restore(&target, &ids[0], &restored, "", RestoreOptions::new())
+        .await
+        .unwrap();
+    assert_eq!(restored.get("a").await.unwrap().unwrap(), "one");
+    assert_eq!(restored.get("b").await.unwrap().unwrap(), "two");
+}
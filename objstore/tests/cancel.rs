@@ -0,0 +1,74 @@
+use bytes::Bytes;
+use futures::{StreamExt as _, TryStreamExt as _};
+use objstore::{
+    CancellationToken, ListArgs, ObjStore as _, ObjStoreError, ObjStoreExt as _, SizedValueStream,
+    ValueStream,
+};
+use objstore_memory::MemoryObjStore;
+
+#[tokio::test]
+async fn test_list_keys_stream_cancelled() {
+    let store = MemoryObjStore::new();
+    for index in 0..5 {
+        store.put(&format!("a/{index}")).text("x").await.unwrap();
+    }
+
+    let token = CancellationToken::new();
+    let args = ListArgs::new()
+        .with_prefix("a/")
+        .with_limit(2)
+        .with_cancellation(token.clone());
+    let mut pages = store.list_keys_stream(args);
+
+    let first = pages.try_next().await.unwrap().unwrap();
+    assert_eq!(first.items.len(), 2);
+
+    token.cancel();
+    let err = pages.try_next().await.unwrap_err();
+    assert!(matches!(err, ObjStoreError::Cancelled { .. }));
+}
+
+#[tokio::test]
+async fn test_put_stream_cancelled() {
+    let store = MemoryObjStore::new();
+    let token = CancellationToken::new();
+
+    // Cancels the token once the first chunk was consumed, then stalls.
+    let canceller = token.clone();
+    let stream: ValueStream = futures::stream::once(async { Ok(Bytes::from_static(b"chunk")) })
+        .chain(futures::stream::once(async move {
+            canceller.cancel();
+            futures::future::pending().await
+        }))
+        .boxed();
+
+    let err = store
+        .put("upload")
+        .cancellation(token)
+        .stream(SizedValueStream::new_without_size(stream))
+        .await
+        .unwrap_err();
+    assert!(matches!(err, ObjStoreError::Cancelled { .. }));
+    assert!(!store.exists("upload").await.unwrap());
+}
+
+#[tokio::test]
+async fn test_delete_prefix_cancellable() {
+    let store = MemoryObjStore::new();
+    store.put("a/1").text("x").await.unwrap();
+
+    let token = CancellationToken::new();
+    token.cancel();
+    let err = store
+        .delete_prefix_cancellable("a/", &token)
+        .await
+        .unwrap_err();
+    assert!(matches!(err, ObjStoreError::Cancelled { .. }));
+    assert!(store.exists("a/1").await.unwrap());
+
+    store
+        .delete_prefix_cancellable("a/", &CancellationToken::new())
+        .await
+        .unwrap();
+    assert!(!store.exists("a/1").await.unwrap());
+}
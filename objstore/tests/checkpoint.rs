@@ -0,0 +1,90 @@
+use futures::TryStreamExt as _;
+use objstore::{ListArgs, ObjStoreError, ObjStoreExt as _, ScanCheckpoint, ScanEvent};
+use objstore_memory::MemoryObjStore;
+
+async fn store_with_items(count: usize) -> MemoryObjStore {
+    let store = MemoryObjStore::new();
+    for index in 0..count {
+        store
+            .put(&format!("items/{index}"))
+            .text("x")
+            .await
+            .unwrap();
+    }
+    store
+}
+
+#[tokio::test]
+async fn test_list_checkpointed_emits_checkpoints() {
+    let store = store_with_items(5).await;
+    let args = ListArgs::new().with_prefix("items/").with_limit(1);
+
+    let events = store
+        .list_checkpointed(args, None, 2)
+        .try_collect::<Vec<_>>()
+        .await
+        .unwrap();
+    let checkpoints = events
+        .iter()
+        .filter_map(|event| match event {
+            ScanEvent::Checkpoint(checkpoint) => Some(checkpoint.clone()),
+            ScanEvent::Page(_) => None,
+        })
+        .collect::<Vec<_>>();
+
+    // After pages 2 and 4, and after the final (empty) page.
+    assert_eq!(checkpoints.len(), 3);
+    assert_eq!(checkpoints[0].pages, 2);
+    assert_eq!(checkpoints[0].items, 2);
+    assert_eq!(checkpoints[0].cursor.as_deref(), Some("items/1"));
+    assert!(!checkpoints[0].done);
+    let last = checkpoints.last().unwrap();
+    assert!(last.done);
+    assert_eq!(last.items, 5);
+    assert!(matches!(events.last(), Some(ScanEvent::Checkpoint(_))));
+}
+
+#[tokio::test]
+async fn test_list_checkpointed_resume() {
+    let store = store_with_items(5).await;
+    let args = ListArgs::new().with_prefix("items/").with_limit(2);
+
+    // Stop after the first checkpoint, as if the job crashed.
+    let mut events = store.list_checkpointed(args.clone(), None, 1);
+    let checkpoint = loop {
+        if let ScanEvent::Checkpoint(checkpoint) = events.try_next().await.unwrap().unwrap() {
+            break checkpoint;
+        }
+    };
+    drop(events);
+    checkpoint.save(&store, "jobs/scan.json").await.unwrap();
+
+    let checkpoint = ScanCheckpoint::load(&store, "jobs/scan.json")
+        .await
+        .unwrap()
+        .unwrap();
+    let keys = store
+        .list_keys_checkpointed(args, Some(checkpoint), 1)
+        .try_filter_map(|event| async move {
+            Ok(match event {
+                ScanEvent::Page(page) => Some(page.items),
+                ScanEvent::Checkpoint(_) => None,
+            })
+        })
+        .try_concat()
+        .await
+        .unwrap();
+    assert_eq!(keys, ["items/2", "items/3", "items/4"]);
+}
+
+#[tokio::test]
+async fn test_list_checkpointed_prefix_mismatch() {
+    let store = store_with_items(1).await;
+    let checkpoint = ScanCheckpoint::new(&ListArgs::new().with_prefix("other/"));
+    let err = store
+        .list_checkpointed(ListArgs::new().with_prefix("items/"), Some(checkpoint), 1)
+        .try_collect::<Vec<_>>()
+        .await
+        .unwrap_err();
+    assert!(matches!(err, ObjStoreError::InvalidRequest { .. }));
+}
@@ -0,0 +1,65 @@
+use futures::StreamExt as _;
+use objstore::{Conditions, ObjStore as _, ObjStoreError, ObjStoreExt as _, ObjectEvent};
+use objstore_memory::MemoryObjStore;
+
+#[tokio::test]
+async fn test_skip_if_unchanged() {
+    let store = MemoryObjStore::new();
+    let first = store.put("a").text("hello").await.unwrap();
+    let mut events = store.watch("").await.unwrap();
+
+    let result = store
+        .put("a")
+        .skip_if_unchanged()
+        .send_with_result(bytes::Bytes::from_static(b"hello"))
+        .await
+        .unwrap();
+    assert!(!result.created);
+    assert_eq!(result.meta.updated_at, first.updated_at);
+
+    store
+        .put("a")
+        .skip_if_unchanged()
+        .text("changed")
+        .await
+        .unwrap();
+    // Only the changed content was written.
+    let event = events.next().await.unwrap().unwrap();
+    assert_eq!(event, ObjectEvent::Updated { key: "a".into() });
+    assert_eq!(store.get("a").await.unwrap().unwrap(), "changed");
+
+    // New objects are uploaded.
+    store.put("b").skip_if_unchanged().text("b").await.unwrap();
+    assert!(store.exists("b").await.unwrap());
+}
+
+#[tokio::test]
+async fn test_skip_if_unchanged_keeps_conditions() {
+    let store = MemoryObjStore::new();
+    store.put("a").text("hello").await.unwrap();
+    let err = store
+        .put("a")
+        .skip_if_unchanged()
+        .conditions(Conditions::new().if_not_exists())
+        .text("hello")
+        .await
+        .unwrap_err();
+    assert!(matches!(err, ObjStoreError::PreconditionFailed { .. }));
+}
+
+#[cfg(feature = "rate-limit")]
+#[tokio::test]
+async fn test_skip_if_unchanged_with_rate_limit() {
+    let store = MemoryObjStore::new();
+    let first = store.put("a").text("hello").await.unwrap();
+
+    let result = store
+        .put("a")
+        .skip_if_unchanged()
+        .rate_limit(1_000)
+        .send_with_result(bytes::Bytes::from_static(b"hello"))
+        .await
+        .unwrap();
+    assert!(!result.created);
+    assert_eq!(result.meta.updated_at, first.updated_at);
+}
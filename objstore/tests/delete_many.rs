@@ -0,0 +1,38 @@
+use futures::StreamExt as _;
+use objstore::{DeleteManyOptions, ObjStore as _, ObjStoreExt as _};
+use objstore_memory::MemoryObjStore;
+
+#[tokio::test]
+async fn test_delete_many() {
+    let store = MemoryObjStore::new();
+    let mut keys = Vec::new();
+    for index in 0..10 {
+        let key = format!("many/{index}");
+        store.put(&key).text(index.to_string()).await.unwrap();
+        keys.push(key);
+    }
+    store.put("other/keep").text("keep").await.unwrap();
+    keys.push("many/missing".to_string());
+
+    let mut results = store
+        .delete_many(keys.clone(), DeleteManyOptions::new().with_concurrency(3))
+        .collect::<Vec<_>>()
+        .await;
+    results.sort_by(|a, b| a.0.cmp(&b.0));
+
+    let mut expected = keys;
+    expected.sort();
+    assert_eq!(
+        results
+            .iter()
+            .map(|(key, _)| key.clone())
+            .collect::<Vec<_>>(),
+        expected
+    );
+    for (key, res) in results {
+        assert!(res.is_ok(), "deleting {key} failed: {res:?}");
+    }
+
+    assert!(store.list_all_keys("many/").await.unwrap().is_empty());
+    assert_eq!(store.list_all_keys("").await.unwrap(), vec!["other/keep"]);
+}
@@ -0,0 +1,75 @@
+use futures::TryStreamExt as _;
+use objstore::{
+    ObjStoreExt as _,
+    diff::{DiffEntry, DiffOptions, diff, diff_report},
+};
+use objstore_memory::MemoryObjStore;
+
+async fn store(items: &[(&str, &str)]) -> MemoryObjStore {
+    let store = MemoryObjStore::new();
+    for (key, value) in items {
+        store.put(key).text(*value).await.unwrap();
+    }
+    store
+}
+
+#[tokio::test]
+async fn test_diff_two_stores() {
+    let a = store(&[("a", "1"), ("b", "2"), ("c", "3"), ("e", "5")]).await;
+    let b = store(&[("b", "2"), ("c", "changed"), ("d", "4"), ("e", "6")]).await;
+
+    let entries = diff(&a, &b, DiffOptions::new())
+        .try_collect::<Vec<_>>()
+        .await
+        .unwrap();
+    let summary = entries
+        .iter()
+        .map(|entry| match entry {
+            DiffEntry::Added { key, .. } => format!("+{key}"),
+            DiffEntry::Removed { key, .. } => format!("-{key}"),
+            DiffEntry::Changed { key, .. } => format!("~{key}"),
+        })
+        .collect::<Vec<_>>();
+    // Same size, different hash.
+    assert_eq!(summary, ["-a", "~c", "+d", "~e"]);
+}
+
+#[tokio::test]
+async fn test_diff_prefixes_in_one_store() {
+    let store = store(&[
+        ("src/a", "1"),
+        ("src/b", "2"),
+        ("dest/a", "1"),
+        ("dest/c", "3"),
+    ])
+    .await;
+
+    let options = DiffOptions::new()
+        .with_prefix_a("src/")
+        .with_prefix_b("dest/");
+    let report = diff_report(&store, &store, options).await.unwrap();
+    assert_eq!(
+        report.added.iter().map(DiffEntry::key).collect::<Vec<_>>(),
+        ["c"]
+    );
+    assert_eq!(
+        report
+            .removed
+            .iter()
+            .map(DiffEntry::key)
+            .collect::<Vec<_>>(),
+        ["b"]
+    );
+    assert!(report.changed.is_empty());
+    assert!(!report.is_empty());
+
+    let options = DiffOptions::new()
+        .with_prefix_a("src/a")
+        .with_prefix_b("dest/a");
+    assert!(
+        diff_report(&store, &store, options)
+            .await
+            .unwrap()
+            .is_empty()
+    );
+}
@@ -0,0 +1,86 @@
+use bytes::Bytes;
+use objstore::{ObjStoreError, ObjStoreExt as _, ParallelDownloadOptions, ProgressCallback};
+use objstore_memory::MemoryObjStore;
+
+fn payload(len: usize) -> Bytes {
+    (0..len).map(|i| (i % 251) as u8).collect::<Vec<_>>().into()
+}
+
+#[tokio::test]
+async fn test_download_parallel_reassembles_ranges_in_order() {
+    let store = MemoryObjStore::new();
+    let value = payload(10_500);
+    store.put("large.bin").bytes(value.clone()).await.unwrap();
+
+    let mut out = Vec::new();
+    let opts = ParallelDownloadOptions::new()
+        .with_chunk_size(1_000)
+        .with_concurrency(3);
+    let meta = store
+        .download_parallel("large.bin", &mut out, opts)
+        .await
+        .unwrap()
+        .expect("object should exist");
+
+    assert_eq!(meta.size, Some(value.len() as u64));
+    assert_eq!(out, value);
+}
+
+#[tokio::test]
+async fn test_download_parallel_small_object() {
+    let store = MemoryObjStore::new();
+    store.put("small.txt").text("hello").await.unwrap();
+
+    let mut out = Vec::new();
+    store
+        .download_parallel("small.txt", &mut out, ParallelDownloadOptions::new())
+        .await
+        .unwrap()
+        .expect("object should exist");
+
+    assert_eq!(out, b"hello");
+}
+
+#[tokio::test]
+async fn test_download_parallel_missing_object() {
+    let store = MemoryObjStore::new();
+
+    let mut out = Vec::new();
+    let meta = store
+        .download_parallel("missing", &mut out, ParallelDownloadOptions::new())
+        .await
+        .unwrap();
+
+    assert!(meta.is_none());
+    assert!(out.is_empty());
+}
+
+#[tokio::test]
+async fn test_download_parallel_fails_if_object_changes() {
+    let store = MemoryObjStore::new();
+    let value = payload(3_000);
+    store.put("large.bin").bytes(value.clone()).await.unwrap();
+
+    // Overwrite the object once the first range was written.
+    let callback = ProgressCallback::new({
+        let store = store.clone();
+        move |_| {
+            let put = store.put("large.bin").bytes(vec![0u8; 3_000]);
+            futures::executor::block_on(put).unwrap();
+        }
+    });
+    let mut out = Vec::new();
+    let opts = ParallelDownloadOptions::new()
+        .with_chunk_size(1_000)
+        .with_concurrency(1)
+        .with_progress(callback);
+    let err = store
+        .download_parallel("large.bin", &mut out, opts)
+        .await
+        .unwrap_err();
+
+    // The change is detected once all ranges were read.
+    assert!(matches!(err, ObjStoreError::PreconditionFailed { .. }));
+    assert_eq!(out.len(), 3_000);
+    assert_eq!(out[..1_000], value[..1_000]);
+}
@@ -0,0 +1,36 @@
+use objstore::{ObjStore as _, ObjStoreExt as _};
+use objstore_memory::MemoryObjStore;
+
+#[tokio::test]
+async fn test_put_from_file_and_get_to_file() {
+    let dir = tempfile::tempdir().unwrap();
+    let store = MemoryObjStore::new();
+
+    let value = (0..200_000).map(|i| (i % 251) as u8).collect::<Vec<_>>();
+    let src = dir.path().join("data.json");
+    std::fs::write(&src, &value).unwrap();
+
+    let meta = store.put_from_file("data", &src).await.unwrap();
+    assert_eq!(meta.size, Some(value.len() as u64));
+    assert_eq!(store.get("data").await.unwrap().unwrap(), value);
+
+    let dest = dir.path().join("out.bin");
+    let meta = store
+        .get_to_file("data", &dest)
+        .await
+        .unwrap()
+        .expect("object should exist");
+    assert_eq!(meta.size, Some(value.len() as u64));
+    assert_eq!(std::fs::read(&dest).unwrap(), value);
+}
+
+#[tokio::test]
+async fn test_get_to_file_missing_object() {
+    let dir = tempfile::tempdir().unwrap();
+    let store = MemoryObjStore::new();
+
+    let dest = dir.path().join("missing.bin");
+    let meta = store.get_to_file("missing", &dest).await.unwrap();
+    assert!(meta.is_none());
+    assert!(!dest.exists());
+}
@@ -0,0 +1,37 @@
+use objstore::{GetRangesOptions, ObjStoreExt as _};
+use objstore_memory::MemoryObjStore;
+
+#[tokio::test]
+async fn test_get_ranges_returns_slices_in_request_order() {
+    let store = MemoryObjStore::new();
+    store.put("a").text("0123456789").await.unwrap();
+    store.put("b").text("abcdef").await.unwrap();
+
+    let requests = vec![
+        ("b".to_string(), 4..10),
+        ("a".to_string(), 6..8),
+        ("missing".to_string(), 0..4),
+        ("a".to_string(), 0..3),
+        ("a".to_string(), 2..5),
+        ("a".to_string(), 12..14),
+    ];
+    let slices = store
+        .get_ranges(requests, GetRangesOptions::new())
+        .await
+        .unwrap();
+    let slices = slices
+        .iter()
+        .map(|slice| slice.as_deref())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        slices,
+        vec![
+            Some(&b"ef"[..]),
+            Some(&b"67"[..]),
+            None,
+            Some(&b"012"[..]),
+            Some(&b"234"[..]),
+            Some(&b""[..]),
+        ]
+    );
+}
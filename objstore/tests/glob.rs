@@ -0,0 +1,78 @@
+use futures::TryStreamExt as _;
+use objstore::{
+    Glob, ListArgs, ObjStore as _, ObjStoreError, ObjStoreExt as _, wrapper::prefix::PrefixObjStore,
+};
+use objstore_memory::MemoryObjStore;
+
+#[tokio::test]
+async fn test_list_glob() {
+    let store = MemoryObjStore::new();
+    for key in [
+        "logs/2024-01/app-1.json",
+        "logs/2024-01/app-2.txt",
+        "logs/2024-02/app-3.json",
+        "logs/2024-02/nested/app-4.json",
+        "logs/2023-12/app-5.json",
+        "other/app-6.json",
+    ] {
+        store.put(key).text("{}").await.unwrap();
+    }
+
+    let keys = store
+        .list_glob("logs/2024-*/app-*.json")
+        .map_ok(|meta| meta.key)
+        .try_collect::<Vec<_>>()
+        .await
+        .unwrap();
+    assert_eq!(
+        keys,
+        vec!["logs/2024-01/app-1.json", "logs/2024-02/app-3.json"]
+    );
+
+    let keys = store
+        .list_glob("**/*.json")
+        .map_ok(|meta| meta.key)
+        .try_collect::<Vec<_>>()
+        .await
+        .unwrap();
+    assert_eq!(keys.len(), 5);
+}
+
+#[tokio::test]
+async fn test_list_glob_invalid_pattern() {
+    let store = MemoryObjStore::new();
+    let err = store
+        .list_glob("logs/[a")
+        .try_collect::<Vec<_>>()
+        .await
+        .unwrap_err();
+    assert!(matches!(err, ObjStoreError::InvalidRequest { .. }));
+}
+
+#[tokio::test]
+async fn test_list_include_exclude_patterns() {
+    let inner = MemoryObjStore::new();
+    let store = PrefixObjStore::new("tenant", inner.clone());
+    for key in ["a/1.json", "a/2.txt", "a/tmp-3.json", "b/4.json", "5.json"] {
+        store.put(key).text("{}").await.unwrap();
+    }
+    inner.put("other/6.json").text("{}").await.unwrap();
+
+    let args = ListArgs::new()
+        .with_include(Glob::new("**/*.json").unwrap())
+        .with_exclude(Glob::new("**/tmp-*").unwrap());
+    let keys = store
+        .list_keys_items_stream(args.clone())
+        .try_collect::<Vec<_>>()
+        .await
+        .unwrap();
+    assert_eq!(keys, vec!["5.json", "a/1.json", "b/4.json"]);
+
+    let keys = store
+        .list_items_stream(args.with_include(Glob::new("a/*").unwrap()))
+        .map_ok(|meta| meta.key)
+        .try_collect::<Vec<_>>()
+        .await
+        .unwrap();
+    assert_eq!(keys, vec!["5.json", "a/1.json", "a/2.txt", "b/4.json"]);
+}
@@ -0,0 +1,96 @@
+use futures::TryStreamExt as _;
+use objstore::{
+    ObjStore as _, ObjStoreExt as _,
+    inventory::{
+        InventoryFormat, InventoryOptions, export, latest_inventory, list_inventories,
+        load_manifest, read,
+    },
+};
+use objstore_memory::MemoryObjStore;
+
+async fn source() -> MemoryObjStore {
+    let source = MemoryObjStore::new();
+    for index in 0..10 {
+        source
+            .put(&format!("data/{index:02}"))
+            .text("hello")
+            .await
+            .unwrap();
+    }
+    source.put("data/a,\"b\"\nc").text("odd").await.unwrap();
+    source.put("other").text("ignored").await.unwrap();
+    source
+}
+
+#[tokio::test]
+async fn test_inventory_export_and_read() {
+    let source = source().await;
+    for format in [InventoryFormat::Csv, InventoryFormat::Ndjson] {
+        let target = MemoryObjStore::new();
+        let options = InventoryOptions::new()
+            .with_format(format)
+            .with_max_file_size(100);
+        let manifest = export(&source, "data/", &target, options).await.unwrap();
+        assert_eq!(manifest.objects, 11);
+        assert_eq!(manifest.total_size, 53);
+        assert!(manifest.files.len() > 1, "{format:?}");
+        for file in &manifest.files {
+            let meta = target.meta(&file.key).await.unwrap().unwrap();
+            assert_eq!(meta.size, Some(file.size));
+            assert_eq!(meta.mime_type.as_deref(), Some(format.mime_type()));
+        }
+
+        let id = latest_inventory(&target).await.unwrap().unwrap();
+        assert_eq!(id, manifest.id);
+        let loaded = load_manifest(&target, &id).await.unwrap();
+        assert_eq!(loaded, manifest);
+
+        let entries = read(&target, &loaded)
+            .try_collect::<Vec<_>>()
+            .await
+            .unwrap();
+        let keys = entries
+            .iter()
+            .map(|entry| entry.key.as_str())
+            .collect::<Vec<_>>();
+        let expected = source.list_all_keys("data/").await.unwrap();
+        assert_eq!(keys, expected);
+        let meta = source.meta("data/00").await.unwrap().unwrap();
+        assert_eq!(entries[0].size, Some(5));
+        assert_eq!(entries[0].etag, meta.etag);
+        assert_eq!(
+            entries[0].updated_at,
+            meta.updated_at.map(|at| at.unix_timestamp())
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_inventory_lists_completed_exports() {
+    let source = source().await;
+    let target = MemoryObjStore::new();
+    assert_eq!(latest_inventory(&target).await.unwrap(), None);
+
+    let first = export(&source, "data/", &target, InventoryOptions::new())
+        .await
+        .unwrap();
+    let second = export(&source, "missing/", &target, InventoryOptions::new())
+        .await
+        .unwrap();
+    assert_eq!(second.objects, 0);
+    assert!(second.files.is_empty());
+    assert_eq!(
+        list_inventories(&target).await.unwrap(),
+        [first.id.clone(), second.id.clone()]
+    );
+    assert_eq!(first.files.len(), 1);
+    assert!(first.files[0].key.ends_with("/data-00000.csv"));
+
+    // Data files without a manifest are incomplete exports.
+    target
+        .put("inventories/99999999999999999999/data-00000.csv")
+        .text("key,size,updated_at,etag\n")
+        .await
+        .unwrap();
+    assert_eq!(latest_inventory(&target).await.unwrap(), Some(second.id));
+}
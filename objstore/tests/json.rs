@@ -0,0 +1,79 @@
+use std::sync::Arc;
+
+use objstore::{DynObjStore, ObjStore, ObjStoreExt as _};
+use objstore_memory::MemoryObjStore;
+use serde_json::json;
+
+#[tokio::test]
+async fn test_put_json_sets_mime_type() {
+    let store = MemoryObjStore::new();
+    let meta = store.put("a").json(&json!({"a": 1})).await.unwrap();
+    assert_eq!(meta.mime_type.as_deref(), Some("application/json"));
+    assert_eq!(store.get("a").await.unwrap().unwrap(), r#"{"a":1}"#);
+
+    // An explicit MIME type wins.
+    let meta = store
+        .put("b")
+        .mime_type("application/vnd.test+json")
+        .json(&1)
+        .await
+        .unwrap();
+    assert_eq!(meta.mime_type.as_deref(), Some("application/vnd.test+json"));
+}
+
+#[tokio::test]
+async fn test_put_json_pretty() {
+    let store = MemoryObjStore::new();
+    store.put("a").json_pretty(&json!({"a": 1})).await.unwrap();
+    assert_eq!(store.get("a").await.unwrap().unwrap(), "{\n  \"a\": 1\n}");
+}
+
+#[cfg(feature = "gzip")]
+#[tokio::test]
+async fn test_put_json_gzip() {
+    let store = MemoryObjStore::new();
+    let value = json!({"items": vec!["value"; 100]});
+    let meta = store.put("a").gzip().json(&value).await.unwrap();
+    assert_eq!(meta.content_encoding.as_deref(), Some("gzip"));
+    assert_eq!(meta.mime_type.as_deref(), Some("application/json"));
+
+    let raw = store.get("a").await.unwrap().unwrap();
+    assert!(raw.starts_with(&[0x1f, 0x8b]));
+    assert!(raw.len() < serde_json::to_vec(&value).unwrap().len());
+
+    let read: serde_json::Value = store.get_json("a").await.unwrap().unwrap();
+    assert_eq!(read, value);
+}
+
+#[cfg(feature = "gzip")]
+#[tokio::test]
+async fn test_get_json_reads_external_gzip() {
+    let store = MemoryObjStore::new();
+    // Written by Python's gzip module, with a file name in the header.
+    let data = b"\x1f\x8b\x08\x08\x00\x00\x00\x00\x02\xffv.json\x00\xabVJT\xb22\xac\x05\x00\xaf\xac\x1bV\x07\x00\x00\x00";
+    store
+        .put("a")
+        .bytes(bytes::Bytes::from_static(data))
+        .await
+        .unwrap();
+
+    let read: serde_json::Value = store.get_json("a").await.unwrap().unwrap();
+    assert_eq!(read, json!({"a": 1}));
+}
+
+#[tokio::test]
+async fn test_get_json_on_arc_and_dyn_stores() {
+    async fn read<S: ObjStore>(store: &S) -> Option<serde_json::Value> {
+        store.get_json("a").await.unwrap()
+    }
+
+    let store = Arc::new(MemoryObjStore::new());
+    store.put("a").json(&json!({"a": 1})).await.unwrap();
+    let dyn_store: DynObjStore = store.clone();
+
+    assert_eq!(read(store.as_ref()).await, Some(json!({"a": 1})));
+    assert_eq!(read(&store).await, Some(json!({"a": 1})));
+    assert_eq!(read(&dyn_store).await, Some(json!({"a": 1})));
+    let missing: Option<u32> = dyn_store.get_json("missing").await.unwrap();
+    assert_eq!(missing, None);
+}
@@ -0,0 +1,89 @@
+use std::{sync::Arc, time::Duration};
+
+use objstore::{
+    DynObjStore, ObjStore as _, ObjStoreExt as _,
+    lifecycle::{LifecycleEventKind, LifecycleRule, apply, dry_run},
+};
+use objstore_memory::MemoryObjStore;
+
+const DAY: Duration = Duration::from_secs(24 * 60 * 60);
+
+async fn store() -> MemoryObjStore {
+    let store = MemoryObjStore::new();
+    store
+        .put("logs/a")
+        .mime_type("text/plain")
+        .text("aaa")
+        .await
+        .unwrap();
+    store.put("logs/b").text("bb").await.unwrap();
+    store.put("archive/c").text("c").await.unwrap();
+    store.put("keep/d").text("d").await.unwrap();
+    store
+}
+
+#[tokio::test]
+async fn test_lifecycle_expire_and_transition() {
+    let store = store().await;
+    let cold = Arc::new(MemoryObjStore::new());
+    let rules = [
+        LifecycleRule::expire("logs/", Duration::ZERO),
+        LifecycleRule::transition(
+            "archive/",
+            Duration::ZERO,
+            cold.clone() as DynObjStore,
+            "cold/",
+        ),
+        LifecycleRule::expire("keep/", DAY),
+        // Sees nothing, the earlier rules already removed the objects.
+        LifecycleRule::expire("", Duration::ZERO),
+    ];
+
+    let report = dry_run(&store, &rules[..3]).await.unwrap();
+    assert!(report.dry_run);
+    assert_eq!(report.count(LifecycleEventKind::Expired), 2);
+    assert_eq!(report.count(LifecycleEventKind::Transitioned), 1);
+    assert_eq!(report.applied_bytes(), 6);
+    assert_eq!(store.list_all_keys("").await.unwrap().len(), 4);
+    assert!(cold.list_all_keys("").await.unwrap().is_empty());
+
+    let report = apply(&store, &rules[..3]).await.unwrap();
+    assert!(report.failed.is_empty());
+    let events = report
+        .applied
+        .iter()
+        .map(|event| (event.rule, event.kind, event.key.as_str()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        events,
+        [
+            (0, LifecycleEventKind::Expired, "logs/a"),
+            (0, LifecycleEventKind::Expired, "logs/b"),
+            (1, LifecycleEventKind::Transitioned, "archive/c"),
+        ]
+    );
+    assert_eq!(
+        report.applied[2].target_key.as_deref(),
+        Some("cold/archive/c")
+    );
+
+    assert_eq!(store.list_all_keys("").await.unwrap(), ["keep/d"]);
+    assert_eq!(cold.get_text("cold/archive/c").await.unwrap().unwrap(), "c");
+
+    let report = apply(&store, &rules[3..]).await.unwrap();
+    assert_eq!(report.applied.len(), 1);
+    assert!(store.list_all_keys("").await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_lifecycle_abort_stale_uploads_keeps_objects() {
+    let store = store().await;
+    let rules = [LifecycleRule::abort_stale_uploads(Duration::ZERO)];
+    let report = apply(&store, &rules).await.unwrap();
+    assert!(report.failed.is_empty());
+    assert_eq!(
+        report.applied.len(),
+        report.count(LifecycleEventKind::UploadAborted)
+    );
+    assert_eq!(store.list_all_keys("").await.unwrap().len(), 4);
+}
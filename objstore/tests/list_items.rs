@@ -0,0 +1,117 @@
+use futures::TryStreamExt as _;
+use objstore::{ListArgs, ObjStore as _, ObjStoreExt as _, ObjectOrPrefix};
+use objstore_memory::MemoryObjStore;
+
+#[tokio::test]
+async fn test_list_items_stream_flattens_pages() {
+    let store = MemoryObjStore::new();
+    for index in 0..5 {
+        store
+            .put(&format!("items/{index}"))
+            .text("x")
+            .await
+            .unwrap();
+    }
+    store.put("other/0").text("x").await.unwrap();
+
+    let args = ListArgs::new().with_prefix("items/").with_limit(2);
+
+    let keys = store
+        .list_items_stream(args.clone())
+        .map_ok(|meta| meta.key)
+        .try_collect::<Vec<_>>()
+        .await
+        .unwrap();
+    assert_eq!(
+        keys,
+        vec!["items/0", "items/1", "items/2", "items/3", "items/4"]
+    );
+
+    let keys = store
+        .list_keys_items_stream(args)
+        .try_collect::<Vec<_>>()
+        .await
+        .unwrap();
+    assert_eq!(
+        keys,
+        vec!["items/0", "items/1", "items/2", "items/3", "items/4"]
+    );
+}
+
+#[tokio::test]
+async fn test_list_items_stream_empty() {
+    let store = MemoryObjStore::new();
+    let items = store
+        .list_items_stream(ListArgs::new())
+        .try_collect::<Vec<_>>()
+        .await
+        .unwrap();
+    assert!(items.is_empty());
+}
+
+#[tokio::test]
+async fn test_list_entries_stream_with_delimiter() {
+    let store = MemoryObjStore::new();
+    for key in ["a.txt", "dir/1", "dir/2", "dir/sub/3", "other/1", "z.txt"] {
+        store.put(key).text("x").await.unwrap();
+    }
+
+    // A small page size must not repeat prefixes across pages.
+    let args = ListArgs::new().with_delimiter('/').with_limit(1);
+    let entries = store
+        .list_entries_stream(args)
+        .try_collect::<Vec<_>>()
+        .await
+        .unwrap();
+    let keys = entries.iter().map(|e| e.key()).collect::<Vec<_>>();
+    assert_eq!(keys, vec!["a.txt", "dir/", "other/", "z.txt"]);
+    assert!(!entries[0].is_prefix());
+    assert!(entries[1].is_prefix());
+
+    let args = ListArgs::new().with_prefix("dir/").with_delimiter('/');
+    let entries = store
+        .list_entries_stream(args)
+        .map_ok(|entry| match entry {
+            ObjectOrPrefix::Object(meta) => meta.key,
+            ObjectOrPrefix::Prefix(prefix) => format!("prefix:{prefix}"),
+        })
+        .try_collect::<Vec<_>>()
+        .await
+        .unwrap();
+    assert_eq!(entries, vec!["dir/1", "dir/2", "prefix:dir/sub/"]);
+}
+
+#[tokio::test]
+async fn test_list_without_delimiter_has_no_prefixes() {
+    let store = MemoryObjStore::new();
+    store.put("dir/1").text("x").await.unwrap();
+
+    let page = store.list(ListArgs::new()).await.unwrap();
+    assert!(page.prefixes.is_none());
+    let entries = page.into_entries();
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0].as_object().unwrap().key, "dir/1");
+}
+
+#[tokio::test]
+async fn test_list_items_stream_start_after() {
+    let store = MemoryObjStore::new();
+    for index in 0..5 {
+        store
+            .put(&format!("items/{index}"))
+            .text("x")
+            .await
+            .unwrap();
+    }
+
+    let args = ListArgs::new()
+        .with_prefix("items/")
+        .with_start_after("items/1")
+        .with_limit(2);
+    let keys = store
+        .list_keys_items_stream(args)
+        .try_collect::<Vec<_>>()
+        .await
+        .unwrap();
+    assert_eq!(keys, vec!["items/2", "items/3", "items/4"]);
+}
@@ -0,0 +1,81 @@
+use futures::TryStreamExt as _;
+use objstore::{ListArgs, ListPartitions, ObjStore, ObjStoreExt as _};
+use objstore_memory::MemoryObjStore;
+
+async fn populate() -> MemoryObjStore {
+    let store = MemoryObjStore::new();
+    for dir in ["0", "4", "A", "b", "logs", "z", "~"] {
+        for index in 0..5 {
+            store
+                .put(&format!("{dir}/{index}"))
+                .text("x")
+                .await
+                .unwrap();
+        }
+    }
+    store.put("b").text("x").await.unwrap();
+    store.put("top").text("x").await.unwrap();
+    store
+}
+
+async fn keys(store: &MemoryObjStore, args: ListArgs, partitions: ListPartitions) -> Vec<String> {
+    store
+        .list_parallel(args, partitions)
+        .map_ok(|meta| meta.key)
+        .try_collect()
+        .await
+        .unwrap()
+}
+
+async fn sequential(store: &MemoryObjStore, args: ListArgs) -> Vec<String> {
+    store
+        .list_items_stream(args)
+        .map_ok(|meta| meta.key)
+        .try_collect()
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_list_parallel_matches_sequential_listing() {
+    let store = populate().await;
+
+    let all = sequential(&store, ListArgs::new()).await;
+    assert_eq!(all.len(), 37);
+    for count in [1, 2, 3, 8, 100] {
+        for partitions in [
+            ListPartitions::first_byte(count),
+            ListPartitions::delimiter(count, "/"),
+        ] {
+            let partitions = partitions.with_max_buffered_pages(1);
+            // Small pages force every partition to follow cursors.
+            let args = ListArgs::new().with_limit(2);
+            assert_eq!(keys(&store, args, partitions).await, all);
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_list_parallel_respects_args() {
+    let store = populate().await;
+
+    for partitions in [
+        ListPartitions::first_byte(4),
+        ListPartitions::delimiter(4, "/"),
+    ] {
+        let args = ListArgs::new().with_prefix("logs/");
+        assert_eq!(
+            keys(&store, args.clone(), partitions.clone()).await,
+            sequential(&store, args).await
+        );
+
+        let args = ListArgs::new().with_start_after("b/2").with_limit(3);
+        let listed = keys(&store, args.clone(), partitions.clone()).await;
+        assert_eq!(listed.first().map(String::as_str), Some("b/3"));
+        assert_eq!(listed, sequential(&store, args).await);
+
+        // Delimiter listings are not split.
+        let args = ListArgs::new().with_delimiter("/");
+        assert_eq!(keys(&store, args, partitions).await, ["b", "top"]);
+    }
+}
@@ -0,0 +1,63 @@
+use std::time::Duration;
+
+use objstore::{
+    Copy, ObjStore, ObjStoreError, ObjStoreExt as _,
+    wrapper::lock::{LockedObjStore, Retention},
+};
+use objstore_memory::MemoryObjStore;
+
+#[tokio::test]
+async fn test_locked_store_conformance() {
+    let store = LockedObjStore::new(MemoryObjStore::new()).with_lock("worm/", None);
+    objstore_test::test_objstore(&store).await;
+}
+
+#[tokio::test]
+async fn test_locked_store_rejects_changes() {
+    let store = LockedObjStore::new(MemoryObjStore::new())
+        .with_lock("backups/", None)
+        .with_lock("backups/tmp/", Some(Duration::ZERO))
+        .with_lock("logs/", Some(Duration::from_secs(3600)));
+
+    store.put("backups/a").text("v1").await.unwrap();
+    store.put("logs/a").text("log").await.unwrap();
+    store.put("free/a").text("free").await.unwrap();
+
+    assert_eq!(
+        store.retention("backups/a").await.unwrap(),
+        Some(Retention::Forever)
+    );
+    assert!(matches!(
+        store.retention("logs/a").await.unwrap(),
+        Some(Retention::Until(_))
+    ));
+    assert_eq!(store.retention("free/a").await.unwrap(), None);
+    assert_eq!(store.retention("backups/missing").await.unwrap(), None);
+
+    for key in ["backups/a", "logs/a"] {
+        let err = store.put(key).text("v2").await.unwrap_err();
+        assert!(
+            matches!(err, ObjStoreError::PermissionDenied { .. }),
+            "{err:?}"
+        );
+        assert!(store.append(key, "+".into()).await.is_err());
+        assert!(store.send_copy(Copy::new("free/a", key)).await.is_err());
+        assert!(store.delete(key).await.is_err());
+    }
+    assert!(store.delete_prefix("").await.is_err());
+    assert!(store.delete_prefix("logs/").await.is_err());
+    assert_eq!(store.get_text("backups/a").await.unwrap().unwrap(), "v1");
+    assert_eq!(store.get_text("logs/a").await.unwrap().unwrap(), "log");
+
+    // The longest prefix applies, and expired retentions allow changes.
+    store.put("backups/tmp/x").text("v1").await.unwrap();
+    store.put("backups/tmp/x").text("v2").await.unwrap();
+    store.delete_prefix("backups/tmp/").await.unwrap();
+
+    store.put("free/a").text("changed").await.unwrap();
+    store.delete_prefix("free/").await.unwrap();
+    assert_eq!(
+        store.list_all_keys("").await.unwrap(),
+        ["backups/a", "logs/a"]
+    );
+}
@@ -0,0 +1,52 @@
+use std::time::Duration;
+
+use objstore::{
+    ObjStore as _, ObjStoreExt as _,
+    maintenance::{CleanupOptions, LeftoverKind},
+    wrapper::prefix::PrefixObjStore,
+};
+use objstore_memory::MemoryObjStore;
+
+#[tokio::test]
+async fn test_cleanup_removes_stale_trash() {
+    let store = MemoryObjStore::new();
+    store.put(".trash/a").text("old").await.unwrap();
+    store.put("b").text("kept").await.unwrap();
+
+    // Not stale yet.
+    let report = store.cleanup(CleanupOptions::new()).await.unwrap();
+    assert!(report.removed.is_empty());
+
+    let options = CleanupOptions::new().with_older_than(Duration::ZERO);
+    let report = store
+        .cleanup(options.clone().with_dry_run(true))
+        .await
+        .unwrap();
+    assert_eq!(report.removed.len(), 1);
+    assert!(store.exists(".trash/a").await.unwrap());
+
+    let report = store.cleanup(options.clone()).await.unwrap();
+    assert_eq!(report.removed[0].kind, LeftoverKind::Trash);
+    assert_eq!(report.removed[0].key, ".trash/a");
+    assert_eq!(report.removed_bytes(), 3);
+    assert!(report.failed.is_empty());
+    assert_eq!(store.list_all_keys("").await.unwrap(), ["b"]);
+
+    store.put(".trash/c").text("old").await.unwrap();
+    let report = store.cleanup(options.without_trash_prefix()).await.unwrap();
+    assert!(report.removed.is_empty());
+}
+
+#[tokio::test]
+async fn test_cleanup_is_scoped_to_prefix() {
+    let inner = MemoryObjStore::new();
+    inner.put(".trash/outside").text("x").await.unwrap();
+    inner.put("tenant/.trash/inside").text("x").await.unwrap();
+
+    let store = PrefixObjStore::new("tenant", inner.clone());
+    let options = CleanupOptions::new().with_older_than(Duration::ZERO);
+    let report = store.cleanup(options).await.unwrap();
+    assert_eq!(report.removed.len(), 1);
+    assert_eq!(report.removed[0].key, ".trash/inside");
+    assert_eq!(inner.list_all_keys("").await.unwrap(), [".trash/outside"]);
+}
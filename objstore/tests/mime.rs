@@ -0,0 +1,52 @@
+use bytes::Bytes;
+use futures::StreamExt as _;
+use objstore::{MimeInference, ObjStore as _, ObjStoreExt as _, SizedValueStream};
+use objstore_memory::MemoryObjStore;
+
+const PNG: &[u8] = b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR";
+
+#[tokio::test]
+async fn test_put_infer_mime_type_from_extension() {
+    let store = MemoryObjStore::new();
+
+    let meta = store
+        .put("images/foo.png")
+        .infer_mime_type()
+        .bytes(Bytes::from_static(b"not really a png"))
+        .await
+        .unwrap();
+    assert_eq!(meta.mime_type.as_deref(), Some("image/png"));
+
+    let meta = store.put("plain.png").text("no inference").await.unwrap();
+    assert_eq!(meta.mime_type, None);
+
+    let meta = store
+        .put("explicit.png")
+        .mime_type("application/octet-stream")
+        .infer_mime_type()
+        .bytes(Bytes::from_static(PNG))
+        .await
+        .unwrap();
+    assert_eq!(meta.mime_type.as_deref(), Some("application/octet-stream"));
+}
+
+#[tokio::test]
+async fn test_put_infer_mime_type_from_stream_content() {
+    let store = MemoryObjStore::new();
+
+    let chunks = vec![Ok(Bytes::from_static(PNG)), Ok(Bytes::from_static(b"rest"))];
+    let stream = SizedValueStream::new(
+        futures::stream::iter(chunks).boxed(),
+        (PNG.len() + 4) as u64,
+    );
+    let meta = store
+        .put("upload")
+        .mime_inference(MimeInference::Content)
+        .stream(stream)
+        .await
+        .unwrap();
+    assert_eq!(meta.mime_type.as_deref(), Some("image/png"));
+
+    let data = store.get("upload").await.unwrap().unwrap();
+    assert_eq!(data, [PNG, b"rest"].concat());
+}
@@ -0,0 +1,95 @@
+use bytes::Bytes;
+use futures::{StreamExt as _, TryStreamExt as _, stream};
+use objstore::{ObjStore as _, ObjStoreError, ObjStoreExt as _, SizedValueStream};
+use objstore_memory::MemoryObjStore;
+
+#[derive(Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+struct Entry {
+    id: u32,
+    msg: String,
+}
+
+fn entry(id: u32, msg: &str) -> Entry {
+    Entry {
+        id,
+        msg: msg.to_string(),
+    }
+}
+
+#[tokio::test]
+async fn test_ndjson_roundtrip() {
+    let store = MemoryObjStore::new();
+    let items = stream::iter([entry(1, "a"), entry(2, "b")]);
+    let meta = store.put_ndjson("log.ndjson", items).await.unwrap();
+    assert_eq!(meta.mime_type.as_deref(), Some("application/x-ndjson"));
+
+    let data = store.get("log.ndjson").await.unwrap().unwrap();
+    assert_eq!(data, "{\"id\":1,\"msg\":\"a\"}\n{\"id\":2,\"msg\":\"b\"}\n");
+
+    let entries = store
+        .get_ndjson::<Entry>("log.ndjson")
+        .await
+        .unwrap()
+        .unwrap()
+        .try_collect::<Vec<_>>()
+        .await
+        .unwrap();
+    assert_eq!(entries, [entry(1, "a"), entry(2, "b")]);
+
+    assert!(
+        store
+            .get_ndjson::<Entry>("missing")
+            .await
+            .unwrap()
+            .is_none()
+    );
+}
+
+#[tokio::test]
+async fn test_ndjson_lines_split_across_chunks() {
+    let store = MemoryObjStore::new();
+    let chunks = [
+        "{\"id\":1,\"ms",
+        "g\":\"a\"}\r\n\n{\"id\":2,",
+        "\"msg\":\"b\"}",
+    ];
+    let size = chunks.iter().map(|chunk| chunk.len() as u64).sum();
+    let chunks = stream::iter(chunks.map(|chunk| Ok(Bytes::from(chunk)))).boxed();
+    store
+        .put("log.ndjson")
+        .stream(SizedValueStream::new(chunks, size))
+        .await
+        .unwrap();
+
+    let entries = store
+        .get_ndjson::<Entry>("log.ndjson")
+        .await
+        .unwrap()
+        .unwrap()
+        .try_collect::<Vec<_>>()
+        .await
+        .unwrap();
+    assert_eq!(entries, [entry(1, "a"), entry(2, "b")]);
+}
+
+#[tokio::test]
+async fn test_ndjson_invalid_line() {
+    let store = MemoryObjStore::new();
+    store
+        .put("log.ndjson")
+        .text("{\"id\":1,\"msg\":\"a\"}\n{\"id\":\"x\"}\n")
+        .await
+        .unwrap();
+
+    let mut entries = store
+        .get_ndjson::<Entry>("log.ndjson")
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(entries.try_next().await.unwrap(), Some(entry(1, "a")));
+    let err = entries.try_next().await.unwrap_err();
+    assert!(matches!(
+        err,
+        ObjStoreError::ContentDeserialization { ref format, .. } if format == "ndjson"
+    ));
+}
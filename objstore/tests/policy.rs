@@ -0,0 +1,120 @@
+use bytes::Bytes;
+use futures::{StreamExt as _, stream};
+use objstore::{
+    Glob, ListArgs, ObjStore as _, ObjStoreError, ObjStoreExt as _, Put, SizedValueStream,
+    wrapper::policy::{KeyCharset, PolicyObjStore, StorePolicy},
+};
+use objstore_memory::MemoryObjStore;
+
+#[tokio::test]
+async fn test_policy_forbids_deleting_everything() {
+    let store = PolicyObjStore::new(MemoryObjStore::new(), StorePolicy::default());
+    store.put("a/1").text("x").await.unwrap();
+    store.put("b/1").text("x").await.unwrap();
+
+    for res in [
+        store.delete_prefix("").await,
+        store.delete_prefix("/").await,
+        store.purge_all().await,
+    ] {
+        assert!(matches!(
+            res.unwrap_err(),
+            ObjStoreError::PermissionDenied { .. }
+        ));
+    }
+    assert_eq!(store.list_all_keys("").await.unwrap().len(), 2);
+
+    store.delete_prefix("a/").await.unwrap();
+    assert_eq!(store.list_all_keys("").await.unwrap(), ["b/1"]);
+
+    let store = PolicyObjStore::new(
+        store.inner().clone(),
+        StorePolicy::new().with_allow_delete_all(true),
+    );
+    store.purge_all().await.unwrap();
+    assert!(store.list_all_keys("").await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_policy_max_object_size() {
+    let policy = StorePolicy::new().with_max_object_size(4);
+    let store = PolicyObjStore::new(MemoryObjStore::new(), policy);
+
+    store.put("small").text("1234").await.unwrap();
+    let err = store.put("large").text("12345").await.unwrap_err();
+    assert!(matches!(err, ObjStoreError::InvalidRequest { .. }));
+    assert!(!store.exists("large").await.unwrap());
+    let err = store
+        .send_put_result(Put::new("large", Bytes::from_static(b"12345")))
+        .await
+        .unwrap_err();
+    assert!(matches!(err, ObjStoreError::InvalidRequest { .. }));
+    assert!(!store.exists("large").await.unwrap());
+
+    // Streams of unknown size fail while they are sent.
+    let chunks = stream::iter(["12", "34", "5"].map(|chunk| Ok(Bytes::from(chunk)))).boxed();
+    let res = store
+        .put("stream")
+        .stream(SizedValueStream::new_without_size(chunks))
+        .await;
+    assert!(res.is_err());
+    assert!(!store.exists("stream").await.unwrap());
+
+    let err = store
+        .append("small", Bytes::from_static(b"5"))
+        .await
+        .unwrap_err();
+    assert!(matches!(err, ObjStoreError::InvalidRequest { .. }));
+}
+
+#[tokio::test]
+async fn test_policy_key_charset_and_page_size() {
+    let policy = StorePolicy::new()
+        .with_key_charset(KeyCharset::Safe)
+        .with_max_list_page_size(2);
+    let store = PolicyObjStore::new(MemoryObjStore::new(), policy);
+
+    for key in ["a", "b", "c"] {
+        store.put(key).text("x").await.unwrap();
+    }
+    let err = store.put("with space").text("x").await.unwrap_err();
+    assert!(matches!(err, ObjStoreError::InvalidRequest { .. }));
+    let err = store.copy("a", "ä").send().await.unwrap_err();
+    assert!(matches!(err, ObjStoreError::InvalidRequest { .. }));
+
+    let page = store.list(ListArgs::new().with_limit(100)).await.unwrap();
+    assert_eq!(page.items.len(), 2);
+    // Pagination still reaches all objects.
+    assert_eq!(store.list_all_keys("").await.unwrap(), ["a", "b", "c"]);
+}
+
+#[tokio::test]
+async fn test_policy_key_length_and_patterns() {
+    let policy = StorePolicy::unrestricted()
+        .with_max_key_length(16)
+        .with_key_pattern(Glob::new("tenants/*/**").unwrap())
+        .with_key_pattern(Glob::new("shared/**").unwrap());
+    let store = PolicyObjStore::new(MemoryObjStore::new(), policy);
+
+    store.put("tenants/a/x").text("x").await.unwrap();
+    store.put("shared/y").text("y").await.unwrap();
+
+    let message = |err: ObjStoreError| match err {
+        ObjStoreError::InvalidRequest { message, .. } => message,
+        other => panic!("expected an invalid request, got {other:?}"),
+    };
+    let err = store.put("other/z").text("z").await.unwrap_err();
+    assert!(message(err).contains("'tenants/*/**', 'shared/**'"));
+    let err = store.put("tenants/a/too-long").text("z").await.unwrap_err();
+    assert!(message(err).contains("at most 16"));
+    let err = store.copy("shared/y", "other/y").send().await.unwrap_err();
+    assert!(matches!(err, ObjStoreError::InvalidRequest { .. }));
+    assert_eq!(
+        store.list_all_keys("").await.unwrap(),
+        ["shared/y", "tenants/a/x"]
+    );
+
+    let policy = StorePolicy::new().with_key_charset(KeyCharset::Safe);
+    let err = policy.check_key("a b").unwrap_err();
+    assert!(message(err).contains("' ' at byte 1"));
+}
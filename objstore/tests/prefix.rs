@@ -1,7 +1,9 @@
+use bytes::Bytes;
+use futures::StreamExt as _;
 use objstore::wrapper::prefix::PrefixObjStore;
 use objstore::{
-    DownloadUrlArgs, KeyPage, ListArgs, ObjStore, ObjStoreError, ObjStoreExt as _, ObjectMeta,
-    ObjectMetaPage, Put, Result, UploadUrlArgs, ValueStream,
+    DownloadUrlArgs, GetManyOptions, KeyPage, ListArgs, ObjStore, ObjStoreError, ObjStoreExt as _,
+    ObjectMeta, ObjectMetaPage, Put, Result, UploadUrlArgs, ValueStream,
 };
 use objstore_memory::MemoryObjStore;
 use std::sync::{Arc, Mutex};
@@ -137,6 +139,32 @@ async fn test_prefix_store_matches_objstore_contract() {
     objstore_test::test_objstore(&store).await;
 }
 
+#[tokio::test]
+async fn test_scoped_confines_operations() {
+    let inner = MemoryObjStore::new();
+    inner.put("outside.txt").bytes("outside").await.unwrap();
+
+    let tenant_a = inner.scoped("tenant-a");
+    let tenant_b = inner.scoped("/tenant-b/");
+    assert_eq!(tenant_b.prefix(), "tenant-b/");
+
+    tenant_a.put("file.txt").bytes("a").await.unwrap();
+    tenant_b.put("file.txt").bytes("b").await.unwrap();
+
+    assert_eq!(tenant_a.get("file.txt").await.unwrap().unwrap(), "a");
+    assert_eq!(tenant_b.list_all_keys("").await.unwrap(), vec!["file.txt"]);
+
+    tenant_a.delete_prefix("").await.unwrap();
+    assert!(tenant_a.list_all_keys("").await.unwrap().is_empty());
+    assert_eq!(tenant_b.get("file.txt").await.unwrap().unwrap(), "b");
+    assert!(inner.exists("outside.txt").await.unwrap());
+
+    // Scopes nest.
+    let nested = tenant_b.scoped("nested");
+    nested.put("deep.txt").bytes("deep").await.unwrap();
+    assert!(inner.exists("tenant-b/nested/deep.txt").await.unwrap());
+}
+
 #[tokio::test]
 async fn test_prefix_store_normalizes_constructor_prefix() {
     let inner = MemoryObjStore::new();
@@ -311,3 +339,52 @@ async fn test_prefix_store_strips_leading_slashes_when_joining_paths() {
             .is_none()
     );
 }
+
+#[tokio::test]
+async fn test_prefix_store_healthcheck_report_warns_on_list_failure() {
+    let store = PrefixObjStore::new("tenant", RecordingListStore::default());
+
+    let report = store.healthcheck_report().await.unwrap();
+    assert_eq!(report.warnings.len(), 1, "{report:?}");
+    assert!(report.warnings[0].starts_with("listing objects failed"));
+}
+
+#[tokio::test]
+async fn test_prefix_store_get_many_keeps_duplicate_keys() {
+    let store = PrefixObjStore::new("tenant", MemoryObjStore::new());
+    store.put("a").text("value").await.unwrap();
+
+    let keys = ["a", "/a", "a", "missing"].map(String::from).to_vec();
+    let mut results = store
+        .get_many(keys, GetManyOptions::new())
+        .map(|(key, res)| (key, res.unwrap()))
+        .collect::<Vec<_>>()
+        .await;
+    results.sort();
+    let value = Some(Bytes::from("value"));
+    assert_eq!(
+        results,
+        vec![
+            ("/a".to_string(), value.clone()),
+            ("a".to_string(), value.clone()),
+            ("a".to_string(), value),
+            ("missing".to_string(), None),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_usage_follows_empty_pages_with_cursor() {
+    // A page with only common prefixes, which repeats its own cursor.
+    let store = RecordingListStore::with_list_page(ObjectMetaPage {
+        items: Vec::new(),
+        next_cursor: Some("a/".to_string()),
+        prefixes: Some(vec!["a/".to_string()]),
+    });
+
+    let usage = store.usage("").await.unwrap();
+    assert_eq!(usage.objects, 0);
+    let args = store.args.lock().unwrap();
+    assert_eq!(args.len(), 2);
+    assert_eq!(args[1].cursor(), Some("a/"));
+}
@@ -0,0 +1,152 @@
+use std::sync::{
+    Arc,
+    atomic::{AtomicU64, Ordering},
+};
+
+use objstore::{
+    ObjStore as _, ObjStoreError, ObjStoreExt as _, PrefixCopyOptions,
+    wrapper::policy::{PolicyObjStore, StorePolicy},
+};
+use objstore_memory::MemoryObjStore;
+
+async fn seeded_store() -> MemoryObjStore {
+    let store = MemoryObjStore::new();
+    for key in ["src/a", "src/b", "src/nested/c", "other/d"] {
+        store.put(key).text(key).await.unwrap();
+    }
+    store
+}
+
+async fn keys(store: &MemoryObjStore, prefix: &str) -> Vec<String> {
+    let mut keys = store.list_all_keys(prefix).await.unwrap();
+    keys.sort();
+    keys
+}
+
+#[tokio::test]
+async fn test_copy_prefix() {
+    let store = seeded_store().await;
+
+    let calls = Arc::new(AtomicU64::new(0));
+    let options = PrefixCopyOptions::new().with_concurrency(2).with_progress({
+        let calls = calls.clone();
+        move |progress| {
+            assert_eq!(progress.failed, 0);
+            calls.fetch_add(1, Ordering::SeqCst);
+        }
+    });
+    let report = store.copy_prefix("src/", "dest/", options).await.unwrap();
+    assert_eq!(report.copied, 3);
+    assert!(report.failed.is_empty());
+    assert_eq!(calls.load(Ordering::SeqCst), 3);
+
+    assert_eq!(
+        keys(&store, "dest/").await,
+        vec!["dest/a", "dest/b", "dest/nested/c"]
+    );
+    assert_eq!(keys(&store, "src/").await.len(), 3);
+    assert_eq!(
+        store.get("dest/nested/c").await.unwrap().unwrap(),
+        "src/nested/c"
+    );
+}
+
+#[tokio::test]
+async fn test_move_prefix() {
+    let store = seeded_store().await;
+
+    let report = store
+        .move_prefix("src/", "moved/", PrefixCopyOptions::new())
+        .await
+        .unwrap();
+    assert_eq!(report.copied, 3);
+
+    assert!(keys(&store, "src/").await.is_empty());
+    assert_eq!(
+        keys(&store, "moved/").await,
+        vec!["moved/a", "moved/b", "moved/nested/c"]
+    );
+    assert_eq!(keys(&store, "other/").await, vec!["other/d"]);
+}
+
+#[tokio::test]
+async fn test_copy_prefix_rejects_overlap() {
+    let store = seeded_store().await;
+
+    for (src, dest) in [("src/", "src/sub/"), ("src/", ""), ("src/nested/", "src/")] {
+        let err = store
+            .copy_prefix(src, dest, PrefixCopyOptions::new())
+            .await
+            .unwrap_err();
+        assert!(matches!(err, ObjStoreError::InvalidRequest { .. }));
+    }
+}
+
+#[tokio::test]
+async fn test_copy_prefix_to_other_store() {
+    let store = seeded_store().await;
+    store
+        .put("src/typed")
+        .mime_type("application/json")
+        .text("{}")
+        .await
+        .unwrap();
+    let dest = MemoryObjStore::new();
+
+    // Prefixes may overlap across stores.
+    let report = store
+        .copy_prefix_to("src/", &dest, "src/copied/", PrefixCopyOptions::new())
+        .await
+        .unwrap();
+    assert_eq!(report.copied, 4);
+    assert_eq!(keys(&store, "src/").await.len(), 4);
+    assert_eq!(
+        keys(&dest, "").await,
+        vec![
+            "src/copied/a",
+            "src/copied/b",
+            "src/copied/nested/c",
+            "src/copied/typed"
+        ]
+    );
+    assert_eq!(
+        dest.get("src/copied/nested/c").await.unwrap().unwrap(),
+        "src/nested/c"
+    );
+    let meta = dest.meta("src/copied/typed").await.unwrap().unwrap();
+    assert_eq!(meta.mime_type.as_deref(), Some("application/json"));
+}
+
+#[tokio::test]
+async fn test_move_prefix_to_other_store() {
+    let store = seeded_store().await;
+    let dest = MemoryObjStore::new();
+
+    let report = store
+        .move_prefix_to("src/", &dest, "", PrefixCopyOptions::new())
+        .await
+        .unwrap();
+    assert_eq!(report.copied, 3);
+    assert!(keys(&store, "src/").await.is_empty());
+    assert_eq!(keys(&store, "").await, vec!["other/d"]);
+    assert_eq!(keys(&dest, "").await, vec!["a", "b", "nested/c"]);
+}
+
+#[tokio::test]
+async fn test_copy_prefix_rollback_keeps_existing_objects() {
+    let store = seeded_store().await;
+    let dest = MemoryObjStore::new();
+    dest.put("a").text("old").await.unwrap();
+    // "src/nested/c" exceeds the size limit, so the copy fails and is rolled back.
+    let dest = PolicyObjStore::new(dest, StorePolicy::new().with_max_object_size(5));
+
+    let options = PrefixCopyOptions::new().with_concurrency(1);
+    store
+        .copy_prefix_to("src/", &dest, "", options)
+        .await
+        .unwrap_err();
+
+    let inner = dest.inner();
+    assert_eq!(keys(inner, "").await, vec!["a"]);
+    assert_eq!(inner.get("a").await.unwrap().unwrap(), "src/a");
+}
@@ -0,0 +1,90 @@
+use std::sync::{Arc, Mutex};
+
+use bytes::Bytes;
+use futures::{StreamExt as _, stream};
+use objstore::{
+    ObjStoreExt as _, ParallelDownloadOptions, Progress, ProgressCallback, SizedValueStream,
+};
+use objstore_memory::MemoryObjStore;
+
+fn recorder() -> (ProgressCallback, Arc<Mutex<Vec<Progress>>>) {
+    let events = Arc::new(Mutex::new(Vec::new()));
+    let callback = ProgressCallback::new({
+        let events = events.clone();
+        move |progress| events.lock().unwrap().push(progress)
+    });
+    (callback, events)
+}
+
+fn transferred(events: &Mutex<Vec<Progress>>) -> Vec<(u64, Option<u64>)> {
+    events
+        .lock()
+        .unwrap()
+        .iter()
+        .map(|progress| (progress.transferred, progress.total))
+        .collect()
+}
+
+#[tokio::test]
+async fn test_put_stream_reports_chunks() {
+    let store = MemoryObjStore::new();
+    let (callback, events) = recorder();
+
+    let chunks = stream::iter(["ab", "cde", "f"].map(|chunk| Ok(Bytes::from(chunk)))).boxed();
+    store
+        .put("a")
+        .progress(callback)
+        .stream(SizedValueStream::new(chunks, 6))
+        .await
+        .unwrap();
+
+    assert_eq!(
+        transferred(&events),
+        vec![(2, Some(6)), (5, Some(6)), (6, Some(6))]
+    );
+}
+
+#[tokio::test]
+async fn test_put_bytes_and_copy_report_completion() {
+    let store = MemoryObjStore::new();
+
+    let (callback, events) = recorder();
+    store
+        .put("a")
+        .progress(callback)
+        .text("hello")
+        .await
+        .unwrap();
+    assert_eq!(transferred(&events), vec![(5, Some(5))]);
+
+    let (callback, events) = recorder();
+    store
+        .copy("a", "b")
+        .progress(callback)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(transferred(&events), vec![(5, Some(5))]);
+}
+
+#[tokio::test]
+async fn test_download_parallel_reports_progress() {
+    let store = MemoryObjStore::new();
+    store.put("a").bytes(vec![0u8; 2_500]).await.unwrap();
+
+    let (callback, events) = recorder();
+    let mut out = Vec::new();
+    let opts = ParallelDownloadOptions::new()
+        .with_chunk_size(1_000)
+        .with_progress(callback);
+    store.download_parallel("a", &mut out, opts).await.unwrap();
+
+    assert_eq!(
+        transferred(&events),
+        vec![
+            (1_000, Some(2_500)),
+            (2_000, Some(2_500)),
+            (2_500, Some(2_500))
+        ]
+    );
+}
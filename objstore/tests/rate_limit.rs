@@ -0,0 +1,92 @@
+use std::{
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use bytes::Bytes;
+use futures::{StreamExt as _, TryStreamExt as _, stream};
+use objstore::{
+    ObjStore as _, ObjStoreExt as _, ParallelDownloadOptions, ProgressCallback, io::limit_stream,
+};
+use objstore_memory::MemoryObjStore;
+
+fn payload(len: usize) -> Bytes {
+    (0..len).map(|i| (i % 251) as u8).collect::<Vec<_>>().into()
+}
+
+#[tokio::test]
+async fn test_limit_stream_paces_chunks() {
+    let value = payload(300);
+    let stream = stream::iter([Ok(value.clone())]).boxed();
+
+    let start = Instant::now();
+    let chunks = limit_stream(stream, 1_000)
+        .try_collect::<Vec<_>>()
+        .await
+        .unwrap();
+    // Split into pieces of 100 bytes, released at 0, 100 and 200ms.
+    assert!(start.elapsed() >= Duration::from_millis(200));
+    assert_eq!(chunks.len(), 3);
+    assert_eq!(chunks.concat(), value);
+}
+
+#[tokio::test]
+async fn test_put_rate_limit() {
+    let store = MemoryObjStore::new();
+    let value = payload(200);
+
+    let start = Instant::now();
+    store
+        .put("a")
+        .rate_limit(1_000)
+        .bytes(value.clone())
+        .await
+        .unwrap();
+    assert!(start.elapsed() >= Duration::from_millis(100));
+    assert_eq!(store.get("a").await.unwrap().unwrap(), value);
+}
+
+#[tokio::test]
+async fn test_put_rate_limit_reports_progress() {
+    let store = MemoryObjStore::new();
+    let events = Arc::new(Mutex::new(Vec::new()));
+    let callback = ProgressCallback::new({
+        let events = events.clone();
+        move |progress| {
+            events
+                .lock()
+                .unwrap()
+                .push((progress.transferred, progress.total))
+        }
+    });
+
+    store
+        .put("a")
+        .rate_limit(1_000)
+        .progress(callback)
+        .bytes(payload(200))
+        .await
+        .unwrap();
+    // In-memory data still reports completion once.
+    assert_eq!(*events.lock().unwrap(), vec![(200, Some(200))]);
+}
+
+#[tokio::test]
+async fn test_download_parallel_rate_limit() {
+    let store = MemoryObjStore::new();
+    let value = payload(300);
+    store.put("a").bytes(value.clone()).await.unwrap();
+
+    let opts = ParallelDownloadOptions::new()
+        .with_chunk_size(100)
+        .with_rate_limit(1_000);
+    let mut out = Vec::new();
+    let start = Instant::now();
+    store
+        .download_parallel("a", &mut out, opts)
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(start.elapsed() >= Duration::from_millis(200));
+    assert_eq!(out, value);
+}
@@ -0,0 +1,74 @@
+use std::sync::{
+    Arc,
+    atomic::{AtomicUsize, Ordering},
+};
+
+use bytes::Bytes;
+use futures::{StreamExt as _, stream};
+use objstore::{
+    DataSource, ObjStore as _, ObjStoreError, ObjStoreExt as _, Operation, Put, SizedValueStream,
+};
+use objstore_memory::MemoryObjStore;
+
+fn chunks(data: &'static [&'static str]) -> SizedValueStream {
+    let size = data.iter().map(|chunk| chunk.len() as u64).sum();
+    let stream = stream::iter(data.iter().map(|chunk| Ok(Bytes::from(*chunk)))).boxed();
+    SizedValueStream::new(stream, size)
+}
+
+#[tokio::test]
+async fn test_factory_put_can_be_resent() {
+    let store = MemoryObjStore::new();
+    let attempts = Arc::new(AtomicUsize::new(0));
+
+    let data = DataSource::factory({
+        let attempts = attempts.clone();
+        move || {
+            if attempts.fetch_add(1, Ordering::SeqCst) == 0 {
+                let stream = stream::iter([
+                    Ok(Bytes::from("par")),
+                    Err(ObjStoreError::cancelled(Operation::Put)),
+                ])
+                .boxed();
+                SizedValueStream::new(stream, 6)
+            } else {
+                chunks(&["par", "tial"])
+            }
+        }
+    });
+    assert!(data.is_replayable());
+
+    let put = Put::new("a", data);
+    let retry = put.try_clone().expect("factory puts are replayable");
+    store.send_put(put).await.unwrap_err();
+
+    store.send_put(retry).await.unwrap();
+    assert_eq!(attempts.load(Ordering::SeqCst), 2);
+    assert_eq!(store.get("a").await.unwrap().unwrap(), "partial");
+}
+
+#[tokio::test]
+async fn test_stream_put_is_not_replayable() {
+    let put = Put::new("a", chunks(&["abc"]));
+    assert!(!put.data.is_replayable());
+    assert!(put.try_clone().is_none());
+
+    let put = Put::new("a", Bytes::from("abc"));
+    let clone = put.try_clone().unwrap();
+    assert_eq!(clone.key, "a");
+    assert_eq!(clone.data.size(), Some(3));
+}
+
+#[tokio::test]
+async fn test_put_stream_factory() {
+    let store = MemoryObjStore::new();
+    let meta = store
+        .put("doc.txt")
+        .infer_mime_type()
+        .stream_factory(|| chunks(&["hello ", "world"]))
+        .await
+        .unwrap();
+    assert_eq!(meta.size, Some(11));
+    assert_eq!(meta.mime_type.as_deref(), Some("text/plain"));
+    assert_eq!(store.get("doc.txt").await.unwrap().unwrap(), "hello world");
+}
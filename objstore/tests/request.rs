@@ -0,0 +1,66 @@
+use std::sync::Arc;
+
+use objstore::{
+    CancellationToken, DynObjStore, ObjStore as _, ObjStoreError, ObjStoreExt as _, Operation,
+    RequestOptions,
+};
+use objstore_memory::MemoryObjStore;
+
+#[tokio::test]
+async fn test_opts_default_behaves_like_plain_method() {
+    let store: DynObjStore = Arc::new(MemoryObjStore::new());
+    store.put("a").text("value").await.unwrap();
+
+    let options = RequestOptions::new();
+
+    let value = store.get_opts("a", options.clone()).await.unwrap();
+    assert_eq!(value.unwrap(), "value");
+    store.delete_opts("a", options.clone()).await.unwrap();
+    assert!(store.meta_opts("a", options).await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn test_opts_cancellation() {
+    let store = MemoryObjStore::new();
+    let token = CancellationToken::new();
+    token.cancel();
+
+    let options = RequestOptions::new().with_cancellation(token);
+    let err = store.get_opts("a", options).await.unwrap_err();
+    assert!(matches!(
+        err,
+        ObjStoreError::Cancelled {
+            operation: Operation::Get,
+            ..
+        }
+    ));
+}
+
+#[cfg(feature = "timeout")]
+#[tokio::test]
+async fn test_opts_timeout() {
+    use std::time::Duration;
+
+    use bytes::Bytes;
+    use futures::{StreamExt as _, stream};
+    use objstore::{Put, SizedValueStream};
+
+    let store = MemoryObjStore::new();
+    let slow = stream::once(async {
+        tokio::time::sleep(Duration::from_secs(5)).await;
+        Ok(Bytes::from("late"))
+    })
+    .boxed();
+    let put = Put::new("a", SizedValueStream::new(slow, 4));
+
+    let options = RequestOptions::new().with_timeout(Duration::from_millis(10));
+    let err = store.send_put_opts(put, options).await.unwrap_err();
+    assert!(matches!(
+        err,
+        ObjStoreError::Timeout {
+            operation: Operation::Put,
+            ..
+        }
+    ));
+    assert!(!store.exists("a").await.unwrap());
+}
@@ -0,0 +1,90 @@
+use bytes::Bytes;
+use futures::StreamExt as _;
+use objstore::{
+    GetManyOptions, ListArgs, ObjStore, ObjStoreExt as _,
+    wrapper::shard::{ShardOptions, ShardedObjStore},
+};
+use objstore_memory::MemoryObjStore;
+
+#[tokio::test]
+async fn test_sharded_store_conformance() {
+    let store = ShardedObjStore::new(MemoryObjStore::new(), ShardOptions::new());
+    objstore_test::test_objstore(&store).await;
+}
+
+#[tokio::test]
+async fn test_sharded_keys_are_spread_and_listed_logically() {
+    let store = ShardedObjStore::new(MemoryObjStore::new(), ShardOptions::new());
+    let keys = ["a/1", "a/2", "a/b/3", "c"];
+    for key in keys {
+        store.put(key).text(key).await.unwrap();
+    }
+    // Foreign objects outside of the shard layout are ignored.
+    store.inner().put("a/foreign").text("x").await.unwrap();
+
+    let inner_keys = store.inner().list_all_keys("").await.unwrap();
+    assert!(inner_keys.contains(&store.shard_key("a/1")));
+    assert!(inner_keys.iter().all(|key| !key.starts_with("a/1")));
+
+    assert_eq!(store.list_all_keys("").await.unwrap(), keys);
+    assert_eq!(
+        store.list_all_keys("a/").await.unwrap(),
+        ["a/1", "a/2", "a/b/3"]
+    );
+    assert_eq!(store.get("a/b/3").await.unwrap().unwrap(), "a/b/3");
+    assert_eq!(store.meta("c").await.unwrap().unwrap().key, "c");
+
+    let page = store
+        .list(ListArgs::new().with_prefix("a/").with_delimiter("/"))
+        .await
+        .unwrap();
+    let items = page
+        .items
+        .iter()
+        .map(|meta| meta.key.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(items, ["a/1", "a/2"]);
+    assert_eq!(page.prefixes, Some(vec!["a/b/".to_string()]));
+
+    let page = store.list(ListArgs::new().with_limit(2)).await.unwrap();
+    assert_eq!(page.next_cursor.as_deref(), Some("a/2"));
+    let page = store
+        .list(ListArgs::new().with_limit(2).with_cursor("a/2"))
+        .await
+        .unwrap();
+    let items = page
+        .items
+        .iter()
+        .map(|meta| meta.key.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(items, ["a/b/3", "c"]);
+    assert_eq!(page.next_cursor, None);
+
+    assert_eq!(store.usage("a/").await.unwrap().objects, 3);
+    store.delete_prefix("a/").await.unwrap();
+    assert_eq!(store.list_all_keys("").await.unwrap(), ["c"]);
+    assert!(store.inner().exists("a/foreign").await.unwrap());
+}
+
+#[tokio::test]
+async fn test_sharded_get_many_keeps_duplicate_keys() {
+    let store = ShardedObjStore::new(MemoryObjStore::new(), ShardOptions::new());
+    store.put("a").text("value").await.unwrap();
+
+    let keys = ["a", "a", "missing"].map(String::from).to_vec();
+    let mut results = store
+        .get_many(keys, GetManyOptions::new())
+        .map(|(key, res)| (key, res.unwrap()))
+        .collect::<Vec<_>>()
+        .await;
+    results.sort();
+    let value = Some(Bytes::from("value"));
+    assert_eq!(
+        results,
+        vec![
+            ("a".to_string(), value.clone()),
+            ("a".to_string(), value),
+            ("missing".to_string(), None),
+        ]
+    );
+}
@@ -0,0 +1,68 @@
+use objstore::{ListArgs, ObjStore as _, ObjStoreExt as _, SortOrder};
+use objstore_memory::MemoryObjStore;
+
+async fn seeded_store() -> MemoryObjStore {
+    let store = MemoryObjStore::new();
+    for (key, value) in [("a", "xx"), ("b", "xxxx"), ("c", "x"), ("d", "xxx")] {
+        store.put(key).text(value).await.unwrap();
+    }
+    store
+}
+
+fn keys(items: Vec<objstore::ObjectMeta>) -> Vec<String> {
+    items.into_iter().map(|meta| meta.key).collect()
+}
+
+#[tokio::test]
+async fn test_list_sorted_native_descending() {
+    let store = seeded_store().await;
+    assert!(store.supports_sort(SortOrder::KeyDescending));
+
+    let items = store
+        .list_sorted(ListArgs::new().with_sort(SortOrder::KeyDescending))
+        .await
+        .unwrap();
+    assert_eq!(keys(items), vec!["d", "c", "b", "a"]);
+
+    // Paging in descending order continues below the cursor.
+    let page = store
+        .list(
+            ListArgs::new()
+                .with_sort(SortOrder::KeyDescending)
+                .with_limit(2),
+        )
+        .await
+        .unwrap();
+    assert_eq!(keys(page.items), vec!["d", "c"]);
+    let page = store
+        .list(
+            ListArgs::new()
+                .with_sort(SortOrder::KeyDescending)
+                .with_cursor(page.next_cursor.unwrap()),
+        )
+        .await
+        .unwrap();
+    assert_eq!(keys(page.items), vec!["b", "a"]);
+}
+
+#[tokio::test]
+async fn test_list_sorted_client_side() {
+    let store = seeded_store().await;
+    assert!(!store.supports_sort(SortOrder::SizeDescending));
+
+    let items = store
+        .list_sorted(
+            ListArgs::new()
+                .with_sort(SortOrder::SizeDescending)
+                .with_limit(3),
+        )
+        .await
+        .unwrap();
+    assert_eq!(keys(items), vec!["b", "d", "a"]);
+
+    let items = store
+        .list_sorted(ListArgs::new().with_sort(SortOrder::SizeAscending))
+        .await
+        .unwrap();
+    assert_eq!(keys(items), vec!["c", "a", "d", "b"]);
+}
@@ -0,0 +1,54 @@
+use bytes::Bytes;
+use objstore::{ObjStoreError, ObjStoreExt as _, TextDecoding};
+use objstore_memory::MemoryObjStore;
+
+#[tokio::test]
+async fn test_get_text() {
+    let store = MemoryObjStore::new();
+    store.put("a").text("grüße").await.unwrap();
+    assert_eq!(store.get_text("a").await.unwrap().unwrap(), "grüße");
+    assert!(store.get_text("missing").await.unwrap().is_none());
+
+    // The byte order mark is not part of the text.
+    store
+        .put("bom")
+        .bytes(Bytes::from_static(b"\xef\xbb\xbfkey=value"))
+        .await
+        .unwrap();
+    assert_eq!(store.get_text("bom").await.unwrap().unwrap(), "key=value");
+}
+
+#[tokio::test]
+async fn test_get_text_invalid_utf8() {
+    let store = MemoryObjStore::new();
+    store
+        .put("a")
+        .bytes(Bytes::from_static(b"caf\xe9"))
+        .await
+        .unwrap();
+
+    let err = store.get_text("a").await.unwrap_err();
+    assert!(matches!(
+        err,
+        ObjStoreError::ContentDeserialization { ref format, .. } if format == "utf-8"
+    ));
+
+    let text = store.get_text_with("a", TextDecoding::Lossy).await.unwrap();
+    assert_eq!(text.unwrap(), "caf\u{fffd}");
+
+    // Not UTF-8, so decoded as Latin-1.
+    let text = store
+        .get_text_with("a", TextDecoding::Detect)
+        .await
+        .unwrap();
+    assert_eq!(text.unwrap(), "café");
+}
+
+#[test]
+fn test_detect_utf16() {
+    let le = b"\xff\xfeh\x00i\x00";
+    let be = b"\xfe\xff\x00h\x00i";
+    assert_eq!(TextDecoding::Detect.decode("k", le).unwrap(), "hi");
+    assert_eq!(TextDecoding::Detect.decode("k", be).unwrap(), "hi");
+    assert!(TextDecoding::Detect.decode("k", b"\xff\xfeh").is_err());
+}
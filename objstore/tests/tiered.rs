@@ -0,0 +1,83 @@
+use bytes::Bytes;
+use objstore::{
+    ObjStore as _, ObjStoreExt as _, Put,
+    wrapper::tiered::{TieredObjStore, TieredOptions, WritePolicy},
+};
+use objstore_memory::MemoryObjStore;
+
+#[tokio::test]
+async fn test_tiered_read_promotes() {
+    let store = TieredObjStore::new(MemoryObjStore::new(), MemoryObjStore::new());
+    store.slow().put("a").text("slow").await.unwrap();
+    assert!(!store.fast().exists("a").await.unwrap());
+
+    assert_eq!(store.get("a").await.unwrap().unwrap(), "slow");
+    assert_eq!(store.fast().get("a").await.unwrap().unwrap(), "slow");
+    assert!(store.get("missing").await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn test_tiered_write_through() {
+    let store = TieredObjStore::new(MemoryObjStore::new(), MemoryObjStore::new());
+    store.put("a").text("v1").await.unwrap();
+    assert_eq!(store.fast().get("a").await.unwrap().unwrap(), "v1");
+    assert_eq!(store.slow().get("a").await.unwrap().unwrap(), "v1");
+
+    let result = store
+        .send_put_result(Put::new("a", Bytes::from("v2")))
+        .await
+        .unwrap();
+    assert!(!result.created);
+    assert_eq!(store.fast().get("a").await.unwrap().unwrap(), "v2");
+    assert_eq!(store.slow().get("a").await.unwrap().unwrap(), "v2");
+
+    // Objects above the size limit are not cached.
+    let store = store.with_options(TieredOptions::new().with_max_object_size(2));
+    store.put("a").text("long").await.unwrap();
+    assert!(!store.fast().exists("a").await.unwrap());
+    assert_eq!(store.get("a").await.unwrap().unwrap(), "long");
+
+    store.delete("a").await.unwrap();
+    assert!(!store.slow().exists("a").await.unwrap());
+}
+
+#[tokio::test]
+async fn test_tiered_write_back() {
+    let options = TieredOptions::new().with_write_policy(WritePolicy::WriteBack);
+    let store =
+        TieredObjStore::new(MemoryObjStore::new(), MemoryObjStore::new()).with_options(options);
+    store.put("a").text("a").await.unwrap();
+    store.put("b").text("b").await.unwrap();
+    assert!(!store.slow().exists("a").await.unwrap());
+    assert_eq!(store.get("a").await.unwrap().unwrap(), "a");
+
+    assert_eq!(store.flush().await.unwrap(), 2);
+    assert_eq!(store.slow().get("a").await.unwrap().unwrap(), "a");
+    assert_eq!(store.flush().await.unwrap(), 0);
+
+    // Listing flushes pending writes.
+    store.put("c").text("c").await.unwrap();
+    let keys = store.list_all_keys("").await.unwrap();
+    assert_eq!(keys, ["a", "b", "c"]);
+}
+
+#[tokio::test]
+async fn test_tiered_demote_by_size() {
+    let options = TieredOptions::new()
+        .with_write_policy(WritePolicy::WriteBack)
+        .with_max_fast_size(15);
+    let store =
+        TieredObjStore::new(MemoryObjStore::new(), MemoryObjStore::new()).with_options(options);
+    for key in ["a", "b", "c"] {
+        store.put(key).text("0123456789").await.unwrap();
+    }
+    store.get("a").await.unwrap().unwrap();
+
+    assert_eq!(store.demote().await.unwrap(), 2);
+    assert_eq!(store.fast().list_all_keys("").await.unwrap(), ["a"]);
+    assert_eq!(
+        store.slow().list_all_keys("").await.unwrap(),
+        ["a", "b", "c"]
+    );
+    assert_eq!(store.get("b").await.unwrap().unwrap(), "0123456789");
+}
@@ -0,0 +1,91 @@
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use futures::TryStreamExt as _;
+use objstore::{ObjStore as _, ObjStoreExt as _, wrapper::trace::TracedObjStore};
+use objstore_memory::MemoryObjStore;
+use tracing::{
+    Event, Level, Metadata, Subscriber,
+    field::{Field, Visit},
+    span,
+};
+
+/// Collects the messages of all events.
+#[derive(Clone, Default)]
+struct Collector(Arc<Mutex<Vec<String>>>);
+
+impl Collector {
+    fn messages(&self) -> Vec<String> {
+        self.0.lock().unwrap().clone()
+    }
+}
+
+struct MessageVisitor<'a>(&'a mut Option<String>);
+
+impl Visit for MessageVisitor<'_> {
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        if field.name() == "message" {
+            *self.0 = Some(format!("{value:?}"));
+        }
+    }
+}
+
+impl Subscriber for Collector {
+    fn enabled(&self, _: &Metadata<'_>) -> bool {
+        true
+    }
+
+    fn new_span(&self, _: &span::Attributes<'_>) -> span::Id {
+        span::Id::from_u64(1)
+    }
+
+    fn record(&self, _: &span::Id, _: &span::Record<'_>) {}
+
+    fn record_follows_from(&self, _: &span::Id, _: &span::Id) {}
+
+    fn event(&self, event: &Event<'_>) {
+        let mut message = None;
+        event.record(&mut MessageVisitor(&mut message));
+        if let Some(message) = message {
+            self.0
+                .lock()
+                .unwrap()
+                .push(format!("{} {message}", event.metadata().level()));
+        }
+    }
+
+    fn enter(&self, _: &span::Id) {}
+
+    fn exit(&self, _: &span::Id) {}
+}
+
+#[tokio::test]
+async fn test_traced_slow_operations_and_stream_completion() {
+    let collector = Collector::default();
+    let _guard = tracing::subscriber::set_default(collector.clone());
+
+    let store = TracedObjStore::new("test", MemoryObjStore::new())
+        .with_level(Level::DEBUG)
+        .with_slow_threshold(Duration::ZERO)
+        .with_slow_level(Level::INFO);
+    store.put("a").text("value").await.unwrap();
+    let messages = collector.messages();
+    assert!(messages.contains(&"INFO slow operation".to_string()));
+
+    let store = store.with_slow_threshold(Duration::from_secs(60));
+    let stream = store.get_stream("a").await.unwrap().unwrap();
+    assert!(
+        !collector
+            .messages()
+            .contains(&"DEBUG stream::complete".to_string())
+    );
+    let chunks = stream.try_collect::<Vec<_>>().await.unwrap();
+    assert_eq!(chunks.concat(), b"value");
+    assert!(
+        collector
+            .messages()
+            .contains(&"DEBUG stream::complete".to_string())
+    );
+}
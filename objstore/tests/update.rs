@@ -0,0 +1,75 @@
+use std::sync::Arc;
+
+use objstore::{Conditions, ObjStoreError, ObjStoreExt as _};
+use objstore_memory::MemoryObjStore;
+
+#[tokio::test]
+async fn test_update_json_creates_and_updates() {
+    let store = MemoryObjStore::new();
+
+    store
+        .update_json("counter.json", |value: Option<u64>| {
+            assert!(value.is_none());
+            1
+        })
+        .await
+        .unwrap();
+    store
+        .update_json("counter.json", |value: Option<u64>| value.unwrap() + 1)
+        .await
+        .unwrap();
+
+    let value: u64 = store.get_json("counter.json").await.unwrap().unwrap();
+    assert_eq!(value, 2);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_update_json_concurrent_writers() {
+    let store = Arc::new(MemoryObjStore::new());
+
+    let tasks = (0..4)
+        .map(|_| {
+            let store = store.clone();
+            tokio::spawn(async move {
+                for _ in 0..5 {
+                    store
+                        .update_json("counter.json", |value: Option<Vec<u32>>| {
+                            let mut value = value.unwrap_or_default();
+                            value.push(value.len() as u32);
+                            value
+                        })
+                        .await
+                        .unwrap();
+                }
+            })
+        })
+        .collect::<Vec<_>>();
+    for task in tasks {
+        task.await.unwrap();
+    }
+
+    let value: Vec<u32> = store.get_json("counter.json").await.unwrap().unwrap();
+    assert_eq!(value, (0..20).collect::<Vec<_>>());
+}
+
+#[tokio::test]
+async fn test_put_conditions_rejected() {
+    let store = MemoryObjStore::new();
+    store.put("key").text("a").await.unwrap();
+
+    let err = store
+        .put("key")
+        .conditions(Conditions::new().if_not_exists())
+        .text("b")
+        .await
+        .unwrap_err();
+    assert!(matches!(err, ObjStoreError::PreconditionFailed { .. }));
+
+    let err = store
+        .put("key")
+        .conditions(Conditions::new().if_match_tags(["wrong"]))
+        .text("b")
+        .await
+        .unwrap_err();
+    assert!(matches!(err, ObjStoreError::PreconditionFailed { .. }));
+}
@@ -0,0 +1,61 @@
+use objstore::{ObjStore, ObjStoreError, ObjStoreExt as _, wrapper::versioned::VersionedObjStore};
+use objstore_memory::MemoryObjStore;
+
+#[tokio::test]
+async fn test_versioned_store_conformance() {
+    let store = VersionedObjStore::new(MemoryObjStore::new());
+    objstore_test::test_objstore(&store).await;
+}
+
+#[tokio::test]
+async fn test_versioned_store_keeps_history() {
+    let store = VersionedObjStore::new(MemoryObjStore::new());
+    store.put("a").text("v1").await.unwrap();
+    assert!(store.list_versions("a").await.unwrap().is_empty());
+
+    store.put("a").text("v2").await.unwrap();
+    store.append("a", "+".into()).await.unwrap();
+    store.put("a/b").text("nested").await.unwrap();
+    store.delete("a").await.unwrap();
+
+    let versions = store.list_versions("a").await.unwrap();
+    let mut data = Vec::new();
+    for version in &versions {
+        assert!(version.archived_at.is_some());
+        data.push(store.get_version("a", &version.id).await.unwrap().unwrap());
+    }
+    assert_eq!(data, ["v1", "v2", "v2+"]);
+
+    // The version area is hidden and protected.
+    assert_eq!(store.list_all_keys("").await.unwrap(), ["a/b"]);
+    assert_eq!(
+        store
+            .inner()
+            .list_all_keys("__versions/a/")
+            .await
+            .unwrap()
+            .len(),
+        3
+    );
+    let err = store.put("__versions/a/1").text("x").await.unwrap_err();
+    assert!(matches!(err, ObjStoreError::InvalidRequest { .. }));
+
+    store.restore_version("a", &versions[1].id).await.unwrap();
+    assert_eq!(store.get("a").await.unwrap().unwrap(), "v2");
+    let err = store.restore_version("a", "missing").await.unwrap_err();
+    assert!(matches!(err, ObjStoreError::ObjectNotFound { .. }));
+
+    store.put("a").text("v3").await.unwrap();
+    assert_eq!(store.list_versions("a").await.unwrap().len(), 4);
+    assert_eq!(store.prune_versions("a", 1).await.unwrap(), 3);
+    let versions = store.list_versions("a").await.unwrap();
+    assert_eq!(versions.len(), 1);
+    assert_eq!(
+        store
+            .get_version("a", &versions[0].id)
+            .await
+            .unwrap()
+            .unwrap(),
+        "v2"
+    );
+}
@@ -0,0 +1,43 @@
+use std::time::Duration;
+
+use futures::StreamExt as _;
+use objstore::{ObjStore as _, ObjStoreExt as _, ObjectEvent, wrapper::poll::PollingWatchObjStore};
+use objstore_memory::MemoryObjStore;
+
+#[tokio::test]
+async fn test_polling_watch() {
+    let store =
+        PollingWatchObjStore::new(MemoryObjStore::new()).with_interval(Duration::from_millis(10));
+    store.put("watched/a").text("a").await.unwrap();
+    store.put("other/b").text("b").await.unwrap();
+
+    let mut events = store.watch("watched/").await.unwrap();
+
+    store.put("watched/c").text("c").await.unwrap();
+    store.put("other/d").text("d").await.unwrap();
+    let event = events.next().await.unwrap().unwrap();
+    assert_eq!(
+        event,
+        ObjectEvent::Created {
+            key: "watched/c".to_string()
+        }
+    );
+
+    store.put("watched/a").text("changed").await.unwrap();
+    let event = events.next().await.unwrap().unwrap();
+    assert_eq!(
+        event,
+        ObjectEvent::Updated {
+            key: "watched/a".to_string()
+        }
+    );
+
+    store.delete("watched/c").await.unwrap();
+    let event = events.next().await.unwrap().unwrap();
+    assert_eq!(
+        event,
+        ObjectEvent::Deleted {
+            key: "watched/c".to_string()
+        }
+    );
+}
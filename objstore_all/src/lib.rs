@@ -0,0 +1,59 @@
+//! All built-in objstore backends.
+//!
+//! Each backend is enabled with a feature (`fs`, `memory`, `s3` and
+//! `logfs`, all enabled by default), and re-exported under its crate name.
+//!
+//! ```
+//! use objstore_all::ObjStoreBuilderExt as _;
+//!
+//! let builder = objstore::ObjStoreBuilder::new().with_default_providers();
+//! let store = builder.build("memory://").unwrap();
+//! ```
+
+use std::sync::Arc;
+
+use objstore::{ObjStoreBuilder, ObjStoreProvider};
+
+pub use objstore;
+#[cfg(feature = "fs")]
+pub use objstore_fs;
+#[cfg(feature = "logfs")]
+pub use objstore_logfs;
+#[cfg(feature = "memory")]
+pub use objstore_memory;
+#[cfg(feature = "s3")]
+pub use objstore_s3_light;
+
+/// Providers of all enabled backends.
+#[allow(unused_mut, clippy::vec_init_then_push)]
+pub fn default_providers() -> Vec<Arc<dyn ObjStoreProvider>> {
+    let mut providers: Vec<Arc<dyn ObjStoreProvider>> = Vec::new();
+    #[cfg(feature = "fs")]
+    providers.push(Arc::new(objstore_fs::FsProvider::new()));
+    #[cfg(feature = "memory")]
+    providers.push(Arc::new(objstore_memory::MemoryProvider::new()));
+    #[cfg(feature = "s3")]
+    providers.push(Arc::new(objstore_s3_light::S3LightProvider::new()));
+    #[cfg(feature = "logfs")]
+    providers.push(Arc::new(objstore_logfs::LogFsProvider::new()));
+    providers
+}
+
+/// A builder with the providers of all enabled backends.
+pub fn builder() -> ObjStoreBuilder {
+    ObjStoreBuilder::new().with_default_providers()
+}
+
+/// Registers the built-in providers on an [`ObjStoreBuilder`].
+pub trait ObjStoreBuilderExt {
+    /// Add the providers of all enabled backends.
+    fn with_default_providers(self) -> Self;
+}
+
+impl ObjStoreBuilderExt for ObjStoreBuilder {
+    fn with_default_providers(self) -> Self {
+        default_providers()
+            .into_iter()
+            .fold(self, |builder, provider| builder.with_provider(provider))
+    }
+}
@@ -0,0 +1,25 @@
+use objstore::{ObjStore as _, ObjStoreExt as _};
+use objstore_all::ObjStoreBuilderExt as _;
+
+#[tokio::test]
+async fn test_default_providers_build_all_backends() {
+    let builder = objstore::ObjStoreBuilder::new().with_default_providers();
+
+    let store = builder.build("memory://").unwrap();
+    store.put("a").text("a").await.unwrap();
+    assert_eq!(store.get("a").await.unwrap().unwrap().as_ref(), b"a");
+
+    let dir = std::env::temp_dir().join(format!("objstore_all_{}", std::process::id()));
+    let store = objstore_all::builder()
+        .build(&format!("fs://{}", dir.display()))
+        .unwrap();
+    assert_eq!(store.kind(), objstore_fs::FsObjStore::KIND);
+    std::fs::remove_dir_all(&dir).ok();
+
+    let store = builder
+        .build("s3://key:secret@s3.example.com/bucket?style=path")
+        .unwrap();
+    assert_eq!(store.kind(), objstore_s3_light::S3ObjStore::KIND);
+
+    assert!(builder.build("unknown://").is_err());
+}
@@ -0,0 +1,397 @@
+//! TOML config documents with profiles, shared defaults and includes.
+//!
+//! See [`crate::FsConfigStore`] for the format.
+
+use std::{
+    collections::{BTreeMap, btree_map::Entry},
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context as _, bail};
+
+use crate::{
+    ConnectionConfig, ConnectionLoadError, LoadedConnection, LoadedConnections, WrapperConfig,
+};
+
+#[derive(serde::Deserialize, Debug, Default)]
+#[serde(deny_unknown_fields)]
+struct ConfigDocument {
+    #[serde(default)]
+    include: Vec<PathBuf>,
+    #[serde(default)]
+    defaults: Defaults,
+    #[serde(default)]
+    connections: BTreeMap<String, ConnectionEntry>,
+    #[serde(default)]
+    profiles: BTreeMap<String, Profile>,
+}
+
+impl ConfigDocument {
+    /// Merge `other` into `self`, with settings of `other` taking precedence.
+    fn merge(&mut self, other: Self) {
+        self.defaults.merge(other.defaults);
+        self.connections.extend(other.connections);
+        for (name, profile) in other.profiles {
+            match self.profiles.entry(name) {
+                Entry::Occupied(mut entry) => entry.get_mut().merge(profile),
+                Entry::Vacant(entry) => {
+                    entry.insert(profile);
+                }
+            }
+        }
+    }
+
+    fn resolve(self) -> LoadedConnections {
+        let mut cons = LoadedConnections::default();
+        for (name, entry) in self.connections {
+            let path = format!("connections.{name}");
+            cons.push(entry.resolve(path, None, name, &self.defaults.wrappers));
+        }
+        for (profile, config) in self.profiles {
+            let defaults = config.defaults.wrappers.or(&self.defaults.wrappers);
+            for (name, entry) in config.connections {
+                let path = format!("profiles.{profile}.connections.{name}");
+                cons.push(entry.resolve(path, Some(&profile), name, &defaults));
+            }
+        }
+        cons
+    }
+}
+
+#[derive(serde::Deserialize, Debug, Default)]
+#[serde(deny_unknown_fields)]
+struct Defaults {
+    #[serde(default)]
+    wrappers: WrapperConfig,
+}
+
+impl Defaults {
+    fn merge(&mut self, other: Self) {
+        self.wrappers = other.wrappers.or(&self.wrappers);
+    }
+}
+
+#[derive(serde::Deserialize, Debug, Default)]
+#[serde(deny_unknown_fields)]
+struct Profile {
+    #[serde(default)]
+    defaults: Defaults,
+    #[serde(default)]
+    connections: BTreeMap<String, ConnectionEntry>,
+}
+
+impl Profile {
+    fn merge(&mut self, other: Self) {
+        self.defaults.merge(other.defaults);
+        self.connections.extend(other.connections);
+    }
+}
+
+#[derive(serde::Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+struct ConnectionEntry {
+    uri: String,
+    #[serde(default)]
+    description: Option<String>,
+    #[serde(default)]
+    wrappers: WrapperConfig,
+    /// The file the connection was defined in.
+    #[serde(skip)]
+    source: PathBuf,
+}
+
+impl ConnectionEntry {
+    /// Apply `defaults` and validate the connection.
+    ///
+    /// `path` is the key path of the connection, used in error messages.
+    fn resolve(
+        self,
+        path: String,
+        profile: Option<&str>,
+        name: String,
+        defaults: &WrapperConfig,
+    ) -> Result<LoadedConnection, ConnectionLoadError> {
+        let fail = |error: String| ConnectionLoadError {
+            source: self.source.clone().into(),
+            error,
+            index: None,
+        };
+
+        if let Err(err) = url::Url::parse(&self.uri) {
+            return Err(fail(format!("{path}.uri: invalid URI: {err}")));
+        }
+        let own_valid = self.wrappers.validate().is_ok();
+        let wrappers = self.wrappers.clone().or(defaults);
+        if let Err((field, message)) = wrappers.validate() {
+            let inherited = if own_valid {
+                " (including defaults)"
+            } else {
+                ""
+            };
+            return Err(fail(format!(
+                "{path}.wrappers.{field}: {message}{inherited}"
+            )));
+        }
+
+        Ok(LoadedConnection {
+            source: Some(self.source.into()),
+            profile: profile.map(ToOwned::to_owned),
+            config: ConnectionConfig {
+                uri: self.uri,
+                name,
+                description: self.description,
+                wrappers,
+            },
+        })
+    }
+}
+
+/// Load the TOML document at `path`, including all files it includes.
+///
+/// Files that fail to load are reported in [`LoadedConnections::failed`],
+/// without affecting the connections of other files.
+pub(crate) fn load_document(path: &Path) -> LoadedConnections {
+    let mut loader = Loader::default();
+    let mut cons = loader
+        .load(path)
+        .map(ConfigDocument::resolve)
+        .unwrap_or_default();
+    cons.failed.splice(0..0, loader.failed);
+    cons
+}
+
+#[derive(Default)]
+struct Loader {
+    /// Canonical paths of the files currently being loaded.
+    stack: Vec<PathBuf>,
+    failed: Vec<ConnectionLoadError>,
+}
+
+impl Loader {
+    fn load(&mut self, path: &Path) -> Option<ConfigDocument> {
+        match self.try_load(path) {
+            Ok(document) => Some(document),
+            Err(err) => {
+                self.failed.push(ConnectionLoadError {
+                    source: path.to_owned().into(),
+                    error: format!("{err:#}"),
+                    index: None,
+                });
+                None
+            }
+        }
+    }
+
+    fn try_load(&mut self, path: &Path) -> Result<ConfigDocument, anyhow::Error> {
+        let canonical = path
+            .canonicalize()
+            .with_context(|| format!("Failed to read config file '{}'", path.display()))?;
+        if self.stack.contains(&canonical) {
+            bail!("Include cycle: '{}' includes itself", path.display());
+        }
+        let contents = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read config file '{}'", path.display()))?;
+        let mut document = parse_document(path, &contents)?;
+
+        self.stack.push(canonical);
+        let dir = path.parent().unwrap_or(Path::new(""));
+        let mut merged = ConfigDocument::default();
+        for include in std::mem::take(&mut document.include) {
+            if let Some(included) = self.load(&dir.join(include)) {
+                merged.merge(included);
+            }
+        }
+        self.stack.pop();
+
+        merged.merge(document);
+        Ok(merged)
+    }
+}
+
+fn parse_document(path: &Path, contents: &str) -> Result<ConfigDocument, anyhow::Error> {
+    let mut document =
+        toml::from_str::<ConfigDocument>(contents).context("Failed to parse TOML config")?;
+    let entries = document.connections.values_mut().chain(
+        document
+            .profiles
+            .values_mut()
+            .flat_map(|profile| profile.connections.values_mut()),
+    );
+    for entry in entries {
+        entry.source = path.to_owned();
+    }
+    Ok(document)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{CacheConfig, ConfigSource, RetryConfig};
+
+    use super::*;
+
+    fn write(dir: &Path, name: &str, contents: &str) -> PathBuf {
+        let path = dir.join(name);
+        std::fs::write(&path, contents).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_profiles_and_defaults() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = write(
+            dir.path(),
+            "config.toml",
+            r#"
+            [defaults.wrappers.retry]
+            max_attempts = 3
+            max_backoff_ms = 1000
+
+            [connections.scratch]
+            uri = "memory://scratch"
+
+            [profiles.prod.defaults.wrappers]
+            readonly = true
+            retry = { max_attempts = 5 }
+
+            [profiles.prod.connections.assets]
+            uri = "memory://assets"
+            description = "Static assets"
+            wrappers = { readonly = false, cache = { max_bytes = 1024 } }
+            "#,
+        );
+
+        let cons = load_document(&path);
+        assert!(cons.failed.is_empty(), "{:?}", cons.failed);
+        assert_eq!(cons.profiles(), ["prod"]);
+
+        let scratch = cons.get("scratch").unwrap();
+        assert_eq!(scratch.profile, None);
+        assert_eq!(scratch.source, Some(ConfigSource::File(path.clone())));
+        assert_eq!(
+            scratch.config.wrappers,
+            WrapperConfig {
+                retry: Some(RetryConfig {
+                    max_attempts: Some(3),
+                    max_backoff_ms: Some(1000),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            }
+        );
+
+        let assets = cons.get_in_profile("prod", "assets").unwrap();
+        assert_eq!(assets.config.description.as_deref(), Some("Static assets"));
+        assert_eq!(
+            assets.config.wrappers,
+            WrapperConfig {
+                readonly: Some(false),
+                retry: Some(RetryConfig {
+                    max_attempts: Some(5),
+                    max_backoff_ms: Some(1000),
+                    ..Default::default()
+                }),
+                cache: Some(CacheConfig {
+                    max_bytes: Some(1024),
+                    ttl_secs: None,
+                }),
+            }
+        );
+    }
+
+    #[test]
+    fn test_includes() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("shared")).unwrap();
+        let shared = write(
+            &dir.path().join("shared"),
+            "shared.toml",
+            r#"
+            include = ["../config.toml"]
+
+            [defaults.wrappers]
+            readonly = true
+
+            [profiles.dev.connections.local]
+            uri = "memory://shared"
+
+            [profiles.dev.connections.other]
+            uri = "memory://other"
+            "#,
+        );
+        let path = write(
+            dir.path(),
+            "config.toml",
+            r#"
+            include = ["shared/shared.toml", "missing.toml"]
+
+            [defaults.wrappers.cache]
+            ttl_secs = 60
+
+            [profiles.dev.connections.local]
+            uri = "memory://local"
+            "#,
+        );
+
+        let cons = load_document(&path);
+        let local = cons.get_in_profile("dev", "local").unwrap();
+        assert_eq!(local.config.uri, "memory://local");
+        assert_eq!(local.source, Some(ConfigSource::File(path.clone())));
+        assert_eq!(local.config.wrappers.readonly, Some(true));
+        assert!(local.config.wrappers.cache.is_some());
+
+        let other = cons.get_in_profile("dev", "other").unwrap();
+        assert_eq!(other.source, Some(ConfigSource::File(shared.clone())));
+
+        // The cycle back to config.toml and the missing include are reported.
+        assert_eq!(cons.failed.len(), 2, "{:?}", cons.failed);
+        assert!(cons.failed[0].error.contains("Include cycle"));
+        assert!(cons.failed[1].error.contains("missing.toml"));
+    }
+
+    #[test]
+    fn test_errors_point_to_fields() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = write(
+            dir.path(),
+            "config.toml",
+            r#"
+            [profiles.dev.connections.a]
+            uri = "memory://a"
+            wrappers = { retyr = { max_attempts = 3 } }
+            "#,
+        );
+        let cons = load_document(&path);
+        assert!(cons.connections.is_empty());
+        let error = &cons.failed[0].error;
+        assert!(error.contains("line 4"), "{error}");
+        assert!(error.contains("unknown field `retyr`"), "{error}");
+
+        let path = write(
+            dir.path(),
+            "config.toml",
+            r#"
+            [defaults.wrappers.retry]
+            max_attempts = 0
+
+            [connections.a]
+            uri = "memory://a"
+
+            [connections.b]
+            uri = "memory://b"
+            wrappers = { retry = { max_attempts = 1 } }
+
+            [connections.c]
+            uri = "not a uri"
+            "#,
+        );
+        let cons = load_document(&path);
+        assert_eq!(cons.connections.len(), 1);
+        assert_eq!(cons.connections[0].config.name, "b");
+        let errors: Vec<_> = cons.failed.iter().map(|e| e.error.as_str()).collect();
+        assert_eq!(
+            errors[0],
+            "connections.a.wrappers.retry.max_attempts: must be at least 1 (including defaults)"
+        );
+        assert!(errors[1].starts_with("connections.c.uri: invalid URI"));
+    }
+}
@@ -8,7 +8,37 @@ use crate::{
 
 const CONFIG_DIR_NAME: &str = "objstore";
 const CONNECTIONS_DIR_NAME: &str = "connections";
-
+const CONFIG_FILE_NAME: &str = "config.toml";
+
+/// Loads connections from a config directory.
+///
+/// Connections are loaded from:
+///
+/// * `config.toml` in the directory, a TOML document with profiles, shared
+///   defaults and includes.
+/// * Files in the `connections` subdirectory. JSON and YAML files contain a
+///   single connection or a list of connections, TOML files are documents
+///   like `config.toml`.
+///
+/// TOML documents look like this:
+///
+/// ```toml
+/// include = ["shared.toml"]
+///
+/// [defaults.wrappers.retry]
+/// max_attempts = 3
+///
+/// [profiles.prod.defaults.wrappers]
+/// readonly = true
+///
+/// [profiles.prod.connections.assets]
+/// uri = "s3://s3.amazonaws.com/assets"
+/// wrappers = { cache = { max_bytes = 104857600 } }
+/// ```
+///
+/// Wrapper settings of a connection are layered over the defaults of its
+/// profile and the document. Includes are relative to the including file,
+/// which overrides the settings of the files it includes.
 #[derive(Debug, Clone)]
 pub struct FsConfigStore {
     path: PathBuf,
@@ -37,26 +67,33 @@ impl FsConfigStore {
     }
 
     pub fn connections(&self) -> Result<LoadedConnections, anyhow::Error> {
+        let mut cons = LoadedConnections::default();
+
+        let config_file = self.path.join(CONFIG_FILE_NAME);
+        if config_file.is_file() {
+            cons.extend(crate::document::load_document(&config_file));
+        }
+
         let connections_dir = self.connections_dir();
 
         let reader = match std::fs::read_dir(connections_dir) {
             Ok(reader) => reader,
             Err(err) => {
                 if err.kind() == std::io::ErrorKind::NotFound {
-                    return Ok(LoadedConnections {
-                        connections: Vec::new(),
-                        failed: Vec::new(),
-                    });
+                    return Ok(cons);
                 }
                 bail!("Failed to read connections directory: {}", err);
             }
         };
 
-        let mut cons = LoadedConnections::default();
         for res in reader {
             let entry = res?;
             if entry.file_type()?.is_file() {
                 let path = entry.path();
+                if path.extension().is_some_and(|ext| ext == "toml") {
+                    cons.extend(crate::document::load_document(&path));
+                    continue;
+                }
                 let contents = std::fs::read_to_string(&path).with_context(|| {
                     format!("Failed to read connection file: '{}'", path.display())
                 })?;
@@ -114,6 +151,7 @@ impl FsConfigStore {
                         Ok(config) => {
                             cons.connections.push(LoadedConnection {
                                 source: Some(path.to_owned().into()),
+                                profile: None,
                                 config,
                             });
                         }
@@ -171,6 +209,7 @@ impl FsConfigStore {
 
         Ok(LoadedConnection {
             source: Some(file_path.into()),
+            profile: None,
             config: config.clone(),
         })
     }
@@ -1,5 +1,6 @@
 use std::{path::PathBuf, sync::Arc};
 
+mod document;
 mod fs;
 
 pub use self::fs::FsConfigStore;
@@ -9,6 +10,117 @@ pub struct ConnectionConfig {
     pub uri: String,
     pub name: String,
     pub description: Option<String>,
+    /// Wrappers to apply to the store built from [`Self::uri`].
+    #[serde(default, skip_serializing_if = "WrapperConfig::is_empty")]
+    pub wrappers: WrapperConfig,
+}
+
+/// Wrapper settings of a connection.
+///
+/// All settings are optional, so settings of a connection can be layered
+/// over shared defaults with [`Self::or`].
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct WrapperConfig {
+    /// Reject all writes.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub readonly: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retry: Option<RetryConfig>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cache: Option<CacheConfig>,
+}
+
+impl WrapperConfig {
+    pub fn is_empty(&self) -> bool {
+        self == &Self::default()
+    }
+
+    /// Fill settings missing in `self` from `defaults`.
+    pub fn or(self, defaults: &Self) -> Self {
+        Self {
+            readonly: self.readonly.or(defaults.readonly),
+            retry: merge_option(self.retry, &defaults.retry, RetryConfig::or),
+            cache: merge_option(self.cache, &defaults.cache, CacheConfig::or),
+        }
+    }
+
+    /// Check the settings, returning the path of the first invalid field
+    /// relative to the wrapper table and a message.
+    fn validate(&self) -> Result<(), (&'static str, &'static str)> {
+        if let Some(retry) = &self.retry {
+            if retry.max_attempts == Some(0) {
+                return Err(("retry.max_attempts", "must be at least 1"));
+            }
+            if let (Some(initial), Some(max)) = (retry.initial_backoff_ms, retry.max_backoff_ms)
+                && initial > max
+            {
+                return Err((
+                    "retry.initial_backoff_ms",
+                    "must not be greater than retry.max_backoff_ms",
+                ));
+            }
+        }
+        if let Some(cache) = &self.cache
+            && cache.max_bytes == Some(0)
+        {
+            return Err(("cache.max_bytes", "must be greater than 0"));
+        }
+        Ok(())
+    }
+}
+
+/// Retry failed requests.
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct RetryConfig {
+    /// Maximum number of attempts, including the first one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_attempts: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub initial_backoff_ms: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_backoff_ms: Option<u64>,
+}
+
+impl RetryConfig {
+    /// Fill settings missing in `self` from `defaults`.
+    pub fn or(self, defaults: &Self) -> Self {
+        Self {
+            max_attempts: self.max_attempts.or(defaults.max_attempts),
+            initial_backoff_ms: self.initial_backoff_ms.or(defaults.initial_backoff_ms),
+            max_backoff_ms: self.max_backoff_ms.or(defaults.max_backoff_ms),
+        }
+    }
+}
+
+/// Cache objects in memory.
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct CacheConfig {
+    /// Maximum total size of cached objects.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_bytes: Option<u64>,
+    /// Time after which cached objects are revalidated.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ttl_secs: Option<u64>,
+}
+
+impl CacheConfig {
+    /// Fill settings missing in `self` from `defaults`.
+    pub fn or(self, defaults: &Self) -> Self {
+        Self {
+            max_bytes: self.max_bytes.or(defaults.max_bytes),
+            ttl_secs: self.ttl_secs.or(defaults.ttl_secs),
+        }
+    }
+}
+
+fn merge_option<T: Clone>(value: Option<T>, defaults: &Option<T>, or: fn(T, &T) -> T) -> Option<T> {
+    match (value, defaults) {
+        (Some(value), Some(defaults)) => Some(or(value, defaults)),
+        (value, defaults) => value.or_else(|| defaults.clone()),
+    }
 }
 
 #[derive(serde::Serialize, serde::Deserialize, Clone, Debug, PartialEq, Eq)]
@@ -25,6 +137,9 @@ impl From<PathBuf> for ConfigSource {
 #[derive(serde::Serialize, serde::Deserialize, Clone, Debug, PartialEq, Eq)]
 pub struct LoadedConnection {
     pub source: Option<ConfigSource>,
+    /// The profile the connection was defined in, for TOML config documents.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub profile: Option<String>,
     pub config: ConnectionConfig,
 }
 
@@ -39,10 +154,29 @@ impl LoadedConnections {
         self.connections.iter().find(|c| c.config.name == name)
     }
 
+    /// Find a connection defined in `profile`.
+    pub fn get_in_profile(&self, profile: &str, name: &str) -> Option<&LoadedConnection> {
+        self.connections
+            .iter()
+            .find(|c| c.profile.as_deref() == Some(profile) && c.config.name == name)
+    }
+
+    /// Names of all profiles with loaded connections, in load order.
+    pub fn profiles(&self) -> Vec<&str> {
+        let mut profiles = Vec::new();
+        for profile in self.connections.iter().filter_map(|c| c.profile.as_deref()) {
+            if !profiles.contains(&profile) {
+                profiles.push(profile);
+            }
+        }
+        profiles
+    }
+
     pub fn new_one_loaded(source: impl Into<ConfigSource>, config: ConnectionConfig) -> Self {
         Self {
             connections: vec![LoadedConnection {
                 source: Some(source.into()),
+                profile: None,
                 config,
             }],
             failed: Vec::new(),
@@ -64,6 +198,13 @@ impl LoadedConnections {
         }
     }
 
+    fn push(&mut self, result: Result<LoadedConnection, ConnectionLoadError>) {
+        match result {
+            Ok(connection) => self.connections.push(connection),
+            Err(error) => self.failed.push(error),
+        }
+    }
+
     fn extend(&mut self, other: Self) {
         self.connections.extend(other.connections);
         self.failed.extend(other.failed);
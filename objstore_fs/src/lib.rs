@@ -1,21 +1,29 @@
+mod path_encoding;
 mod provider;
+#[cfg(feature = "watch")]
+mod watch;
 
 pub use self::provider::FsProvider;
 
 use std::{
     collections::HashSet,
     path::{Path, PathBuf},
-    sync::Arc,
+    sync::{
+        Arc,
+        atomic::{AtomicU64, Ordering},
+    },
 };
 
 use bytes::Bytes;
-use futures::{StreamExt as _, TryStreamExt as _};
+use futures::StreamExt as _;
 use time::OffsetDateTime;
-use tokio::io::{AsyncReadExt, AsyncWriteExt as _};
+use tokio::io::{AsyncReadExt, AsyncSeekExt as _, AsyncWriteExt as _};
 
 use objstore::{
-    Copy, DataSource, DownloadUrlArgs, KeyPage, ListArgs, ObjStore, ObjStoreError, ObjectMeta,
-    ObjectMetaPage, Operation, Put, Result, UploadUrlArgs, ValueStream,
+    BytesPool, ContentHashes, Copy, DataSource, DownloadUrlArgs, HashingStream, KeyPage, KeyPath,
+    ListArgs, ObjStore, ObjStoreError, ObjectKey, ObjectMeta, ObjectMetaPage, Operation, Put,
+    PutResult, Result, SortOrder, UploadUrlArgs, UrlSigner, ValueStream,
+    maintenance::{CleanupOptions, CleanupReport, Leftover, LeftoverKind},
 };
 use sha2::Digest;
 use url::Url;
@@ -23,23 +31,140 @@ use url::Url;
 #[derive(serde::Serialize, serde::Deserialize, Clone, Debug, PartialEq, Eq)]
 pub struct FsObjStoreConfig {
     path: PathBuf,
+    /// Set file modification times from [`Put::modified_at`] and copy
+    /// sources. See [`Self::with_preserve_mtime`].
+    #[serde(default)]
+    preserve_mtime: bool,
+    /// Size of the chunks yielded by streaming reads.
+    /// See [`Self::with_read_chunk_size`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    read_chunk_size: Option<usize>,
+    /// Minimum size of files that are memory mapped by `get`.
+    /// See [`Self::with_mmap_threshold`].
+    #[cfg(feature = "mmap")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    mmap_threshold: Option<u64>,
 }
 
 impl FsObjStoreConfig {
+    #[cfg(feature = "mmap")]
+    pub const DEFAULT_MMAP_THRESHOLD: u64 = 16 * 1024 * 1024;
+
     pub fn new(path: PathBuf) -> Self {
-        Self { path }
+        Self {
+            path,
+            preserve_mtime: false,
+            read_chunk_size: None,
+            #[cfg(feature = "mmap")]
+            mmap_threshold: None,
+        }
+    }
+
+    pub fn preserve_mtime(&self) -> bool {
+        self.preserve_mtime
+    }
+
+    /// Preserve modification times.
+    ///
+    /// When enabled, puts with [`Put::modified_at`] set the modification time
+    /// of the written file, and copies keep the modification time of the
+    /// source file. This keeps `updated_at` meaningful for newer-than change
+    /// detection when syncing with other stores.
+    ///
+    /// Only the modification time is set. Creation and change times are
+    /// managed by the operating system.
+    pub fn with_preserve_mtime(mut self, preserve_mtime: bool) -> Self {
+        self.preserve_mtime = preserve_mtime;
+        self
+    }
+
+    pub fn read_chunk_size(&self) -> usize {
+        self.read_chunk_size
+            .unwrap_or(objstore::BytesPool::DEFAULT_CHUNK_SIZE)
+    }
+
+    /// Set the size of the chunks yielded by streaming reads.
+    ///
+    /// Larger chunks reduce the per-chunk overhead of high-throughput
+    /// transfers, at the cost of memory. Defaults to
+    /// [`objstore::BytesPool::DEFAULT_CHUNK_SIZE`], a size of `0` is ignored.
+    pub fn with_read_chunk_size(mut self, size: usize) -> Self {
+        if size > 0 {
+            self.read_chunk_size = Some(size);
+        }
+        self
+    }
+
+    /// Minimum size of files that are memory mapped, if enabled.
+    #[cfg(feature = "mmap")]
+    pub fn mmap_threshold(&self) -> Option<u64> {
+        self.mmap_threshold
+    }
+
+    /// Memory map files of at least `bytes` in [`ObjStore::get`] and
+    /// [`ObjStore::get_with_meta`], instead of reading them in chunks.
+    ///
+    /// The mapped data is copied into the returned [`Bytes`] right away, so
+    /// the result never changes with the file, and the map is released before
+    /// returning. Smaller files are read as usual, since mapping has a fixed
+    /// cost. [`Self::DEFAULT_MMAP_THRESHOLD`] is a reasonable value. A
+    /// threshold of `0` is ignored.
+    ///
+    /// Page faults on mapped data block the reading thread, like synchronous
+    /// file IO.
+    #[cfg(feature = "mmap")]
+    pub fn with_mmap_threshold(mut self, bytes: u64) -> Self {
+        if bytes > 0 {
+            self.mmap_threshold = Some(bytes);
+        }
+        self
+    }
+
+    /// Build a URI for [`FsProvider`].
+    ///
+    /// The path must be absolute.
+    pub fn build_uri(&self) -> Result<String> {
+        let url = Url::from_file_path(&self.path).map_err(|_| ObjStoreError::InvalidConfig {
+            message: format!(
+                "invalid path '{}': path must be absolute",
+                self.path.display()
+            ),
+            source: None,
+        })?;
+        let path = url.as_str().trim_start_matches("file://");
+        let mut params = Vec::new();
+        if self.preserve_mtime {
+            params.push("preserve_mtime=true".to_string());
+        }
+        if let Some(size) = self.read_chunk_size {
+            params.push(format!("read_chunk_size={size}"));
+        }
+        #[cfg(feature = "mmap")]
+        if let Some(threshold) = self.mmap_threshold {
+            params.push(format!("mmap_threshold={threshold}"));
+        }
+        if params.is_empty() {
+            Ok(format!("fs://{path}"))
+        } else {
+            Ok(format!("fs://{path}?{}", params.join("&")))
+        }
     }
 }
 
 #[derive(Clone, Debug)]
 pub struct FsObjStore {
     state: Arc<State>,
+    url_signer: Option<UrlSigner>,
 }
 
 #[derive(Debug)]
 struct State {
     safe_uri: Url,
     root: PathBuf,
+    preserve_mtime: bool,
+    pool: BytesPool,
+    #[cfg(feature = "mmap")]
+    mmap_threshold: Option<u64>,
 }
 
 impl FsObjStore {
@@ -59,17 +184,217 @@ impl FsObjStore {
                 source: Some(source.into()),
             }
         })?;
+        let root = path_encoding::root_path(root);
 
         Ok(Self {
-            state: Arc::new(State { safe_uri, root }),
+            state: Arc::new(State {
+                safe_uri,
+                root,
+                preserve_mtime: config.preserve_mtime,
+                pool: BytesPool::new(config.read_chunk_size()),
+                #[cfg(feature = "mmap")]
+                mmap_threshold: config.mmap_threshold,
+            }),
+            url_signer: None,
         })
     }
 
+    /// Return signed download URLs from [`ObjStore::generate_download_url`].
+    ///
+    /// The URLs must be served by a gateway using the same signer.
+    pub fn with_url_signer(mut self, signer: UrlSigner) -> Self {
+        self.url_signer = Some(signer);
+        self
+    }
+
+    /// Read the whole file of `key`, with its file system metadata.
+    ///
+    /// Files of at least the mmap threshold are memory mapped instead of read,
+    /// see [`FsObjStoreConfig::with_mmap_threshold`].
+    async fn read_file(&self, key: &str) -> Result<Option<(Bytes, std::fs::Metadata)>> {
+        let mut f = match tokio::fs::File::open(self.key_path(key)).await {
+            Ok(f) => f,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(err) => return Err(io_error(Operation::Get, err)),
+        };
+        let fs_meta = match f.metadata().await {
+            Ok(meta) => meta,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(err) => return Err(io_error(Operation::Get, err)),
+        };
+
+        #[cfg(feature = "mmap")]
+        if self
+            .state
+            .mmap_threshold
+            .is_some_and(|threshold| fs_meta.len() >= threshold)
+        {
+            let file = f.into_std().await;
+            let data = map_file(&file).map_err(|err| io_error(Operation::Get, err))?;
+            return Ok(Some((data, fs_meta)));
+        }
+
+        let mut buf = Vec::with_capacity(fs_meta.len() as usize);
+        f.read_to_end(&mut buf)
+            .await
+            .map_err(|err| io_error(Operation::Get, err))?;
+        Ok(Some((buf.into(), fs_meta)))
+    }
+
+    /// Path for a key.
+    ///
+    /// See [`path_encoding`] for how keys are mapped on Windows.
     fn key_path(&self, key: &str) -> PathBuf {
-        self.state.root.join(key)
+        path_encoding::key_path(&self.state.root, key)
+    }
+
+    /// Path for a key that may not be valid UTF-8.
+    ///
+    /// Only Unix file names can hold arbitrary bytes.
+    ///
+    /// Keys are split into segments which are pushed one at a time, and
+    /// keys with a leading slash or empty, `.` or `..` segments are rejected,
+    /// so a key can never address a file outside the root.
+    fn raw_key_path(&self, key: &ObjectKey) -> Result<PathBuf> {
+        let valid = key
+            .as_bytes()
+            .split(|byte| *byte == b'/')
+            .all(|segment| !matches!(segment, b"" | b"." | b".."));
+        if !valid {
+            return Err(ObjStoreError::InvalidRequest {
+                message: format!(
+                    "invalid key '{key}': keys must not start or end with a slash, \
+                     or contain empty, '.' or '..' segments"
+                ),
+                source: None,
+            });
+        }
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::ffi::OsStrExt as _;
+            let mut path = self.state.root.clone();
+            for segment in key.as_bytes().split(|byte| *byte == b'/') {
+                path.push(std::ffi::OsStr::from_bytes(segment));
+            }
+            Ok(path)
+        }
+        #[cfg(not(unix))]
+        match key.as_str() {
+            Some(key) => Ok(self.key_path(key)),
+            None => Err(ObjStoreError::InvalidRequest {
+                message: format!("non UTF-8 keys are not supported on this platform: '{key}'"),
+                source: None,
+            }),
+        }
+    }
+}
+
+/// Suffix of temporary files.
+///
+/// Puts write to a temporary file next to the target, which is renamed once
+/// complete. Interrupted writes leave these behind, see [`ObjStore::cleanup`].
+const TEMP_SUFFIX: &str = ".objstore-tmp";
+
+fn temp_path(path: &Path) -> PathBuf {
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+    let id = COUNTER.fetch_add(1, Ordering::Relaxed);
+    path.with_file_name(format!(".{name}.{}-{id}{TEMP_SUFFIX}", std::process::id()))
+}
+
+/// Memory map a file, and copy its contents into owned [`Bytes`].
+#[cfg(feature = "mmap")]
+fn map_file(file: &std::fs::File) -> std::io::Result<Bytes> {
+    // SAFETY: the map only lives until its contents are copied, and is never
+    // handed out. The store does not truncate or modify files in place: puts
+    // and copies replace files by renaming, deletes only unlink them, and
+    // appends only grow them beyond the mapped length.
+    let map = unsafe { memmap2::Mmap::map(file)? };
+    Ok(Bytes::copy_from_slice(&map))
+}
+
+/// Write `data` to `path`, hashing it on the way.
+async fn write_file(path: &Path, data: DataSource) -> Result<ContentHashes> {
+    match data {
+        DataSource::Data(value) => {
+            tokio::fs::write(path, &value)
+                .await
+                .map_err(|err| io_error(Operation::Put, err))?;
+            Ok(ContentHashes::of(&value))
+        }
+        data => {
+            let mut stream = HashingStream::new(data.into_stream().into_stream());
+            let mut file = tokio::fs::File::create(path)
+                .await
+                .map_err(|err| io_error(Operation::Put, err))?;
+
+            while let Some(chunk) = stream.next().await {
+                file.write_all(&chunk?)
+                    .await
+                    .map_err(|err| io_error(Operation::Put, err))?;
+            }
+
+            file.sync_all()
+                .await
+                .map_err(|err| io_error(Operation::Put, err))?;
+            Ok(stream.hashes())
+        }
     }
 }
 
+/// Find all temporary files below `path`, with their key relative to the root.
+async fn find_temp_files(
+    path: &Path,
+    current_path: &KeyPath,
+    files: &mut Vec<(PathBuf, String, std::fs::Metadata)>,
+) -> Result<()> {
+    let f = async {
+        let mut iter = match tokio::fs::read_dir(path).await {
+            Ok(iter) => iter,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+            Err(err) => return Err(io_error(Operation::List, err)),
+        };
+        while let Some(entry) = iter
+            .next_entry()
+            .await
+            .map_err(|err| io_error(Operation::List, err))?
+        {
+            let meta = entry
+                .metadata()
+                .await
+                .map_err(|err| io_error(Operation::List, err))?;
+            let name = entry.file_name().to_string_lossy().to_string();
+            let key = current_path.join(&path_encoding::decode_name(&name))?;
+            if meta.is_dir() {
+                find_temp_files(&entry.path(), &key, files).await?;
+            } else if meta.is_file() && name.ends_with(TEMP_SUFFIX) {
+                files.push((entry.path(), key.into_string(), meta));
+            }
+        }
+        Ok(())
+    };
+
+    Box::pin(f).await
+}
+
+/// Set the modification time of the file at `path`.
+async fn set_modified(
+    path: &Path,
+    time: std::time::SystemTime,
+    operation: Operation,
+) -> Result<()> {
+    let file = tokio::fs::File::options()
+        .write(true)
+        .open(path)
+        .await
+        .map_err(|err| io_error(operation, err))?;
+    file.into_std()
+        .await
+        .set_modified(time)
+        .map_err(|err| io_error(operation, err))
+}
+
 fn meta_from_fs_meta(key: String, fs_meta: std::fs::Metadata) -> ObjectMeta {
     let mut meta = ObjectMeta::new(key);
     meta.size = Some(fs_meta.len());
@@ -91,7 +416,7 @@ async fn list_dir_rec(
     cursor: Option<&str>,
     limit: usize,
     prefix_filter: Option<&str>,
-    current_path: &str,
+    current_path: &KeyPath,
     items: &mut Vec<ObjectMeta>,
     directories: &mut Option<Vec<String>>,
 ) -> Result<Option<()>> {
@@ -111,7 +436,8 @@ async fn list_dir_rec(
                 .metadata()
                 .await
                 .map_err(|err| io_error(Operation::List, err))?;
-            let key = entry.file_name().to_string_lossy().to_string();
+            let name = entry.file_name();
+            let key = path_encoding::decode_name(&name.to_string_lossy()).into_owned();
 
             if let Some(prefix) = &prefix_filter
                 && !key.starts_with(prefix)
@@ -121,15 +447,14 @@ async fn list_dir_rec(
 
             if !meta.is_file() {
                 if meta.is_dir() {
+                    let cpath = current_path.join(&key)?;
+                    // Delimiter listings return directories as common prefixes
+                    // instead of descending into them.
                     if let Some(directories) = directories {
-                        directories.push(key.clone());
+                        directories.push(cpath.as_prefix());
+                        continue;
                     }
 
-                    let cpath = if current_path.is_empty() {
-                        key
-                    } else {
-                        format!("{current_path}/{key}")
-                    };
                     list_dir_rec(
                         &entry.path(),
                         cursor,
@@ -146,18 +471,18 @@ async fn list_dir_rec(
                 }
             }
 
+            if key.ends_with(TEMP_SUFFIX) {
+                continue;
+            }
+
             if let Some(cursor) = cursor
                 && (key.as_str() <= cursor || key.as_str() == cursor)
             {
                 continue;
             }
 
-            let full_key = if current_path.is_empty() {
-                key
-            } else {
-                format!("{current_path}/{key}")
-            };
-            items.push(meta_from_fs_meta(full_key, meta));
+            let full_key = current_path.join(&key)?;
+            items.push(meta_from_fs_meta(full_key.into_string(), meta));
 
             if items.len() >= limit {
                 break;
@@ -175,11 +500,11 @@ async fn list_dir(
     cursor: Option<&str>,
     limit: usize,
     prefix_filter: Option<&str>,
-    current_path: &str,
+    current_path: &KeyPath,
     flat: bool,
 ) -> Result<(Vec<ObjectMeta>, Option<Vec<String>>)> {
     let mut items = Vec::new();
-    let mut directories = if flat { None } else { Some(Vec::new()) };
+    let mut directories = flat.then(Vec::new);
     list_dir_rec(
         path,
         cursor,
@@ -201,8 +526,11 @@ async fn list_dir(
             false
         }
     });
+    if let Some(directories) = &mut directories {
+        directories.sort();
+    }
 
-    Ok((items, directories))
+    Ok((items, directories.filter(|dirs| !dirs.is_empty())))
 }
 
 #[async_trait::async_trait]
@@ -228,52 +556,141 @@ impl ObjStore for FsObjStore {
         }
     }
 
-    async fn get(&self, key: &str) -> Result<Option<Bytes>> {
-        let path = self.key_path(key);
-        let data = match tokio::fs::read(&path).await {
-            Ok(data) => Some(data.into()),
-            Err(err) if err.kind() == std::io::ErrorKind::NotFound => None,
-            Err(err) => return Err(io_error(Operation::Get, err)),
+    async fn meta_raw(&self, key: &ObjectKey) -> Result<Option<ObjectMeta>> {
+        let path = self.raw_key_path(key)?;
+        match tokio::fs::metadata(&path).await {
+            Ok(meta) => Ok(Some(meta_from_fs_meta(key.to_string(), meta))),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(io_error(Operation::Meta, err)),
+        }
+    }
+
+    async fn get_raw(&self, key: &ObjectKey) -> Result<Option<Bytes>> {
+        let path = self.raw_key_path(key)?;
+        match tokio::fs::read(&path).await {
+            Ok(data) => Ok(Some(data.into())),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(io_error(Operation::Get, err)),
+        }
+    }
+
+    async fn delete_raw(&self, key: &ObjectKey) -> Result<()> {
+        let path = self.raw_key_path(key)?;
+        tokio::fs::remove_file(&path)
+            .await
+            .map_err(|err| io_error(Operation::Delete, err))
+    }
+
+    async fn put_raw(&self, key: &ObjectKey, data: Bytes) -> Result<ObjectMeta> {
+        let path = self.raw_key_path(key)?;
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent)
+                .await
+                .map_err(|err| io_error(Operation::Put, err))?;
+        }
+
+        let temp = temp_path(&path);
+        let res = async {
+            let hashes = write_file(&temp, DataSource::Data(data)).await?;
+            tokio::fs::rename(&temp, &path)
+                .await
+                .map_err(|err| io_error(Operation::Put, err))?;
+            Ok::<_, ObjStoreError>(hashes)
+        }
+        .await;
+        if res.is_err() {
+            tokio::fs::remove_file(&temp).await.ok();
+        }
+        let hashes = res?;
+
+        let fs_meta = tokio::fs::metadata(&path)
+            .await
+            .map_err(|err| io_error(Operation::Put, err))?;
+        let mut meta = meta_from_fs_meta(key.to_string(), fs_meta);
+        hashes.apply_to(&mut meta);
+        Ok(meta)
+    }
+
+    #[cfg(unix)]
+    async fn list_all_keys_raw(&self, prefix: &ObjectKey) -> Result<Vec<ObjectKey>> {
+        use std::os::unix::ffi::OsStrExt as _;
+
+        // Start at the deepest directory fully covered by the prefix.
+        let prefix = prefix.as_bytes();
+        let dir_len = prefix
+            .iter()
+            .rposition(|byte| *byte == b'/')
+            .map_or(0, |pos| pos + 1);
+        let start = if dir_len == 0 {
+            self.state.root.clone()
+        } else {
+            self.raw_key_path(&ObjectKey::from_bytes(&prefix[..dir_len - 1]))?
         };
-        Ok(data)
+
+        let mut keys = Vec::new();
+        let mut dirs = vec![(start, prefix[..dir_len].to_vec())];
+        while let Some((dir, dir_key)) = dirs.pop() {
+            let mut iter = match tokio::fs::read_dir(&dir).await {
+                Ok(iter) => iter,
+                Err(err) if err.kind() == std::io::ErrorKind::NotFound => continue,
+                Err(err) => return Err(io_error(Operation::List, err)),
+            };
+            while let Some(entry) = iter
+                .next_entry()
+                .await
+                .map_err(|err| io_error(Operation::List, err))?
+            {
+                let file_type = entry
+                    .file_type()
+                    .await
+                    .map_err(|err| io_error(Operation::List, err))?;
+                let mut key = dir_key.clone();
+                key.extend_from_slice(entry.file_name().as_bytes());
+                if file_type.is_dir() {
+                    key.push(b'/');
+                    if key.starts_with(prefix) || prefix.starts_with(&key) {
+                        dirs.push((entry.path(), key));
+                    }
+                } else if file_type.is_file()
+                    && key.starts_with(prefix)
+                    && !key.ends_with(TEMP_SUFFIX.as_bytes())
+                {
+                    keys.push(ObjectKey::from_bytes(key));
+                }
+            }
+        }
+        keys.sort();
+        Ok(keys)
+    }
+
+    async fn exists(&self, key: &str) -> Result<bool> {
+        let path = self.key_path(key);
+        match tokio::fs::metadata(&path).await {
+            Ok(meta) => Ok(meta.is_file()),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(false),
+            Err(err) => Err(io_error(Operation::Meta, err)),
+        }
+    }
+
+    async fn get(&self, key: &str) -> Result<Option<Bytes>> {
+        Ok(self.read_file(key).await?.map(|(data, _)| data))
     }
 
     async fn get_stream(&self, key: &str) -> Result<Option<ValueStream>> {
         let path = self.key_path(key);
         match tokio::fs::File::open(&path).await {
-            Ok(file) => {
-                let stream = tokio_util::io::ReaderStream::new(file)
-                    .map_ok(Bytes::from)
-                    .map_err(|source| ObjStoreError::Io {
-                        operation: Operation::GetStream,
-                        source: Some(source.into()),
-                    })
-                    .boxed();
-                Ok(Some(stream))
-            }
+            Ok(file) => Ok(Some(self.state.pool.reader_stream(file))),
             Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(None),
             Err(err) => Err(io_error(Operation::GetStream, err)),
         }
     }
 
     async fn get_with_meta(&self, key: &str) -> Result<Option<(Bytes, ObjectMeta)>> {
-        let mut f = match tokio::fs::File::open(self.key_path(key)).await {
-            Ok(f) => f,
-            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(None),
-            Err(err) => return Err(io_error(Operation::Get, err)),
-        };
-        let fs_meta = match f.metadata().await {
-            Ok(meta) => meta,
-            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(None),
-            Err(err) => return Err(io_error(Operation::Get, err)),
+        let Some((data, fs_meta)) = self.read_file(key).await? else {
+            return Ok(None);
         };
-        let mut buf = Vec::with_capacity(fs_meta.len() as usize);
-        f.read_to_end(&mut buf)
-            .await
-            .map_err(|err| io_error(Operation::Get, err))?;
-
         let meta = meta_from_fs_meta(key.to_string(), fs_meta);
-        Ok(Some((buf.into(), meta)))
+        Ok(Some((data, meta)))
     }
 
     async fn get_stream_with_meta(&self, key: &str) -> Result<Option<(ObjectMeta, ValueStream)>> {
@@ -288,20 +705,32 @@ impl ObjStore for FsObjStore {
             Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(None),
             Err(err) => return Err(io_error(Operation::GetStream, err)),
         };
-        let stream = tokio_util::io::ReaderStream::new(f)
-            .map_ok(Bytes::from)
-            .map_err(|source| ObjStoreError::Io {
-                operation: Operation::GetStream,
-                source: Some(source.into()),
-            })
-            .boxed();
+        let stream = self.state.pool.reader_stream(f);
 
         let meta = meta_from_fs_meta(key.to_string(), fs_meta);
         Ok(Some((meta, stream)))
     }
 
-    async fn generate_download_url(&self, _args: DownloadUrlArgs) -> Result<Option<url::Url>> {
-        Ok(None)
+    async fn get_range(&self, key: &str, range: std::ops::Range<u64>) -> Result<Option<Bytes>> {
+        let mut f = match tokio::fs::File::open(self.key_path(key)).await {
+            Ok(f) => f,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(err) => return Err(io_error(Operation::GetRange, err)),
+        };
+        let len = range.end.saturating_sub(range.start);
+        f.seek(std::io::SeekFrom::Start(range.start))
+            .await
+            .map_err(|err| io_error(Operation::GetRange, err))?;
+        let mut buf = Vec::new();
+        f.take(len)
+            .read_to_end(&mut buf)
+            .await
+            .map_err(|err| io_error(Operation::GetRange, err))?;
+        Ok(Some(buf.into()))
+    }
+
+    async fn generate_download_url(&self, args: DownloadUrlArgs) -> Result<Option<url::Url>> {
+        Ok(self.url_signer.as_ref().map(|signer| signer.sign(&args)))
     }
 
     async fn generate_upload_url(&self, _args: UploadUrlArgs) -> Result<Option<url::Url>> {
@@ -309,6 +738,11 @@ impl ObjStore for FsObjStore {
     }
 
     async fn send_put(&self, put: Put) -> Result<ObjectMeta> {
+        Ok(self.send_put_result(put).await?.meta)
+    }
+
+    async fn send_put_result(&self, put: Put) -> Result<PutResult> {
+        objstore::validate_key(&put.key)?;
         let path = self.key_path(&put.key);
         if let Some(parent) = path.parent() {
             tokio::fs::create_dir_all(parent)
@@ -316,39 +750,72 @@ impl ObjStore for FsObjStore {
                 .map_err(|err| io_error(Operation::Put, err))?;
         }
 
-        match put.data {
-            DataSource::Data(value) => {
-                tokio::fs::write(&path, &value)
-                    .await
-                    .map_err(|err| io_error(Operation::Put, err))?;
-            }
-            DataSource::Stream(sized) => {
-                let mut stream = sized.into_stream();
-                let mut file = tokio::fs::File::create(&path)
-                    .await
-                    .map_err(|err| io_error(Operation::Put, err))?;
-
-                while let Some(chunk) = stream.next().await {
-                    file.write_all(&chunk?)
-                        .await
-                        .map_err(|err| io_error(Operation::Put, err))?;
-                }
-
-                file.sync_all()
-                    .await
-                    .map_err(|err| io_error(Operation::Put, err))?;
+        // Write to a temporary file first, so readers never see partial data.
+        let temp = temp_path(&path);
+        let modified_at = put
+            .modified_at
+            .filter(|_| self.state.preserve_mtime)
+            .map(std::time::SystemTime::from);
+        let res: Result<(bool, ContentHashes)> = async {
+            let hashes = write_file(&temp, put.data).await?;
+            if let Some(time) = modified_at {
+                set_modified(&temp, time, Operation::Put).await?;
             }
+            // Checked right before the rename, which replaces existing files.
+            let existed = tokio::fs::try_exists(&path)
+                .await
+                .map_err(|err| io_error(Operation::Put, err))?;
+            tokio::fs::rename(&temp, &path)
+                .await
+                .map_err(|err| io_error(Operation::Put, err))?;
+            Ok((!existed, hashes))
         }
+        .await;
+        if res.is_err() {
+            tokio::fs::remove_file(&temp).await.ok();
+        }
+        let (created, hashes) = res?;
 
         let fs_meta = tokio::fs::metadata(&path)
             .await
             .map_err(|err| io_error(Operation::Put, err))?;
-        let meta = meta_from_fs_meta(put.key, fs_meta);
+        let mut meta = meta_from_fs_meta(put.key, fs_meta);
+        hashes.apply_to(&mut meta);
 
-        Ok(meta)
+        Ok(PutResult::new(meta, created))
+    }
+
+    async fn append(&self, key: &str, data: Bytes) -> Result<ObjectMeta> {
+        objstore::validate_key(key)?;
+        let path = self.key_path(key);
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent)
+                .await
+                .map_err(|err| io_error(Operation::Append, err))?;
+        }
+
+        let mut file = tokio::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .await
+            .map_err(|err| io_error(Operation::Append, err))?;
+        file.write_all(&data)
+            .await
+            .map_err(|err| io_error(Operation::Append, err))?;
+        file.sync_all()
+            .await
+            .map_err(|err| io_error(Operation::Append, err))?;
+
+        let fs_meta = file
+            .metadata()
+            .await
+            .map_err(|err| io_error(Operation::Append, err))?;
+        Ok(meta_from_fs_meta(key.to_owned(), fs_meta))
     }
 
     async fn send_copy(&self, copy: Copy) -> Result<ObjectMeta> {
+        objstore::validate_key(&copy.target_key)?;
         let src_path = self.key_path(&copy.source_key);
         let dst_path = self.key_path(&copy.target_key);
         // If requested, ensure destination does not exist
@@ -360,14 +827,33 @@ impl ObjStore for FsObjStore {
                 .await
                 .map_err(|err| io_error(Operation::Copy, err))?;
         }
-        // Perform file copy
-        match tokio::fs::copy(&src_path, &dst_path).await {
-            Ok(_) => {}
-            Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
-                return Err(ObjStoreError::object_not_found(copy.source_key));
+        // Copy to a temporary file first, since copying onto the target would
+        // truncate it in place (which breaks readers, including memory maps).
+        let temp = temp_path(&dst_path);
+        let res: Result<()> = async {
+            match tokio::fs::copy(&src_path, &temp).await {
+                Ok(_) => {}
+                Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+                    return Err(ObjStoreError::object_not_found(copy.source_key.clone()));
+                }
+                Err(err) => return Err(io_error(Operation::Copy, err)),
+            }
+            if self.state.preserve_mtime {
+                let modified = tokio::fs::metadata(&src_path)
+                    .await
+                    .and_then(|meta| meta.modified())
+                    .map_err(|err| io_error(Operation::Copy, err))?;
+                set_modified(&temp, modified, Operation::Copy).await?;
             }
-            Err(err) => return Err(io_error(Operation::Copy, err)),
+            tokio::fs::rename(&temp, &dst_path)
+                .await
+                .map_err(|err| io_error(Operation::Copy, err))
+        }
+        .await;
+        if res.is_err() {
+            tokio::fs::remove_file(&temp).await.ok();
         }
+        res?;
         // Build metadata from filesystem and compute hash
         let fs_meta = tokio::fs::metadata(&dst_path)
             .await
@@ -395,13 +881,13 @@ impl ObjStore for FsObjStore {
 
         // Must compute the prefix as a parent directory.
 
-        let (path, key_path, prefix) = if let Some(prefix) = args.prefix() {
+        let (path, dir, prefix) = if let Some(prefix) = args.prefix() {
             match prefix.rsplit_once('/') {
-                Some((main, rest)) => (self.key_path(main), main, Some(rest)),
-                None => (self.state.root.clone(), "", Some(prefix)),
+                Some((main, rest)) => (self.key_path(main), KeyPath::parse(main)?, Some(rest)),
+                None => (self.state.root.clone(), KeyPath::root(), Some(prefix)),
             }
         } else {
-            (self.state.root.clone(), "", None)
+            (self.state.root.clone(), KeyPath::root(), None)
         };
 
         let flat = if let Some(delim) = args.delimiter() {
@@ -417,16 +903,29 @@ impl ObjStore for FsObjStore {
             false
         };
 
-        let (items, directories) =
-            list_dir(&path, args.cursor(), limit, prefix, key_path, flat).await?;
+        let (mut items, directories) =
+            list_dir(&path, args.cursor(), limit, prefix, &dir, flat).await?;
+
+        // The cursor must point at the last scanned item, so it is computed before filtering.
+        // Only full pages can be followed by more items.
+        let next_cursor = items
+            .last()
+            .filter(|_| items.len() >= limit)
+            .map(|item| item.key().to_owned());
+        items.retain(|item| args.matches(item));
 
         Ok(ObjectMetaPage {
-            next_cursor: items.last().map(|item| item.key().to_owned()),
+            next_cursor,
             items,
             prefixes: directories,
         })
     }
 
+    fn supports_sort(&self, _order: SortOrder) -> bool {
+        // Directories are listed in the order returned by the filesystem.
+        false
+    }
+
     async fn list_keys(&self, args: ListArgs) -> Result<KeyPage> {
         let meta_items = self.list(args).await?;
         let items = meta_items.items.into_iter().map(|item| item.key).collect();
@@ -469,10 +968,42 @@ impl ObjStore for FsObjStore {
             Err(err) => Err(io_error(Operation::DeletePrefix, err)),
         }
     }
+
+    async fn cleanup(&self, options: CleanupOptions) -> Result<CleanupReport> {
+        let mut files = Vec::new();
+        find_temp_files(&self.state.root, &KeyPath::root(), &mut files).await?;
+
+        let mut report = CleanupReport::default();
+        for (path, key, meta) in files {
+            let modified = meta.modified().ok().map(OffsetDateTime::from);
+            if !options.is_stale(modified) {
+                continue;
+            }
+            let result = if options.dry_run() {
+                Ok(())
+            } else {
+                tokio::fs::remove_file(&path)
+                    .await
+                    .map_err(|err| io_error(Operation::Delete, err))
+            };
+            let leftover = Leftover::new(LeftoverKind::TempFile, key, Some(meta.len()), modified);
+            report.record(leftover, result);
+        }
+
+        report.merge(objstore::maintenance::cleanup_trash(self, &options).await?);
+        Ok(report)
+    }
+
+    #[cfg(feature = "watch")]
+    async fn watch(&self, prefix: &str) -> Result<objstore::EventStream> {
+        watch::watch(&self.state.root, prefix)
+    }
 }
 
 #[cfg(test)]
 mod tests {
+    use futures::TryStreamExt as _;
+
     use super::*;
 
     #[tokio::test]
@@ -483,4 +1014,301 @@ mod tests {
 
         objstore_test::test_objstore(&store).await;
     }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_raw_keys() {
+        use objstore::ObjStoreExt as _;
+
+        let dir = tempfile::tempdir().unwrap();
+        let store = FsObjStore::new(FsObjStoreConfig::new(dir.path().to_owned())).unwrap();
+
+        let key = ObjectKey::from_bytes(b"raw-\xff.bin".to_vec());
+        let path = store.raw_key_path(&key).unwrap();
+        tokio::fs::write(&path, b"data").await.unwrap();
+
+        let meta = store.meta_raw(&key).await.unwrap().unwrap();
+        assert_eq!(meta.key, "raw-\\xFF.bin");
+        assert_eq!(meta.size, Some(4));
+        assert_eq!(store.get_raw(&key).await.unwrap().unwrap(), "data");
+
+        store.delete_raw(&key).await.unwrap();
+        assert!(store.get_raw(&key).await.unwrap().is_none());
+
+        let nested = ObjectKey::from_bytes(b"dir/\xfe/b.bin".to_vec());
+        let meta = store.put_raw(&nested, "nested".into()).await.unwrap();
+        assert_eq!(meta.key, "dir/\\xFE/b.bin");
+        assert_eq!(store.get_raw(&nested).await.unwrap().unwrap(), "nested");
+        store.put("dir/c.txt").text("c").await.unwrap();
+        store.put("other.txt").text("o").await.unwrap();
+        assert_eq!(
+            store
+                .list_all_keys_raw(&ObjectKey::from_bytes(b"dir/\xfe".to_vec()))
+                .await
+                .unwrap(),
+            std::slice::from_ref(&nested)
+        );
+        assert_eq!(
+            store
+                .list_all_keys_raw(&ObjectKey::from("dir/"))
+                .await
+                .unwrap(),
+            [ObjectKey::from("dir/c.txt"), nested]
+        );
+
+        // Keys can not escape the root.
+        for key in [
+            &b"/etc/passwd"[..],
+            b"../outside",
+            b"a/../../outside",
+            b"a//b",
+            b"./a",
+            b"",
+        ] {
+            let key = ObjectKey::from_bytes(key.to_vec());
+            assert!(store.raw_key_path(&key).is_err(), "{key}");
+            assert!(store.get_raw(&key).await.is_err(), "{key}");
+            assert!(store.delete_raw(&key).await.is_err(), "{key}");
+        }
+
+        // UTF-8 keys are interchangeable with the `&str` API.
+        store.put("a.txt").text("hello").await.unwrap();
+        let key = ObjectKey::from("a.txt");
+        assert_eq!(store.get_raw(&key).await.unwrap().unwrap(), "hello");
+    }
+
+    #[tokio::test]
+    async fn test_cleanup_removes_stale_temp_files() {
+        use objstore::ObjStoreExt as _;
+
+        let dir = tempfile::tempdir().unwrap();
+        let store = FsObjStore::new(FsObjStoreConfig::new(dir.path().to_owned())).unwrap();
+        store.put("a/b.txt").text("hello").await.unwrap();
+
+        // Leftover of an interrupted write.
+        let temp = temp_path(&store.key_path("a/b.txt"));
+        tokio::fs::write(&temp, b"partial").await.unwrap();
+        assert_eq!(store.list_all_keys("").await.unwrap(), ["a/b.txt"]);
+
+        let report = store.cleanup(CleanupOptions::new()).await.unwrap();
+        assert!(report.removed.is_empty());
+
+        let options = CleanupOptions::new().with_older_than(std::time::Duration::ZERO);
+        let report = store
+            .cleanup(options.clone().with_dry_run(true))
+            .await
+            .unwrap();
+        assert_eq!(report.removed.len(), 1);
+        assert!(temp.exists());
+
+        let report = store.cleanup(options).await.unwrap();
+        assert_eq!(report.removed[0].kind, LeftoverKind::TempFile);
+        assert_eq!(report.removed_bytes(), 7);
+        assert!(!temp.exists());
+        assert_eq!(store.get("a/b.txt").await.unwrap().unwrap(), "hello");
+    }
+
+    #[tokio::test]
+    async fn test_list_delimiter_returns_prefixes() {
+        use objstore::ObjStoreExt as _;
+
+        let dir = tempfile::tempdir().unwrap();
+        let store = FsObjStore::new(FsObjStoreConfig::new(dir.path().to_owned())).unwrap();
+        store.put("a.txt").text("x").await.unwrap();
+        store.put("dir/b.txt").text("x").await.unwrap();
+        store.put("dir/sub/c.txt").text("x").await.unwrap();
+
+        let page = store
+            .list(ListArgs::new().with_delimiter('/'))
+            .await
+            .unwrap();
+        let keys = page
+            .into_entries()
+            .into_iter()
+            .map(|entry| entry.key().to_owned())
+            .collect::<Vec<_>>();
+        assert_eq!(keys, ["a.txt", "dir/"]);
+
+        let page = store
+            .list(ListArgs::new().with_prefix("dir/").with_delimiter('/'))
+            .await
+            .unwrap();
+        assert_eq!(page.items[0].key, "dir/b.txt");
+        assert_eq!(page.prefixes.unwrap(), ["dir/sub/"]);
+    }
+
+    #[tokio::test]
+    async fn test_list_sorted_sorts_client_side() {
+        use objstore::ObjStoreExt as _;
+
+        let dir = tempfile::tempdir().unwrap();
+        let store = FsObjStore::new(FsObjStoreConfig::new(dir.path().to_owned())).unwrap();
+        assert!(!store.supports_sort(SortOrder::KeyAscending));
+        for key in ["c", "a", "d/e", "b"] {
+            store.put(key).text("x").await.unwrap();
+        }
+
+        let items = store
+            .list_sorted(ListArgs::new().with_sort(SortOrder::KeyAscending))
+            .await
+            .unwrap();
+        let keys = items.into_iter().map(|meta| meta.key).collect::<Vec<_>>();
+        assert_eq!(keys, ["a", "b", "c", "d/e"]);
+    }
+
+    #[tokio::test]
+    async fn test_preserve_mtime() {
+        use objstore::{ObjStoreExt as _, ObjStoreProvider as _};
+
+        let dir = tempfile::tempdir().unwrap();
+        let config = FsObjStoreConfig::new(dir.path().to_owned()).with_preserve_mtime(true);
+        let uri = config.build_uri().unwrap();
+        let store = FsProvider::new().build(&uri.parse().unwrap()).unwrap();
+
+        let time = OffsetDateTime::from_unix_timestamp(1_600_000_000).unwrap();
+        let meta = store
+            .put("a.txt")
+            .modified_at(time)
+            .text("a")
+            .await
+            .unwrap();
+        assert_eq!(meta.updated_at, Some(time));
+
+        let meta = store.copy("a.txt", "b.txt").send().await.unwrap();
+        assert_eq!(meta.updated_at, Some(time));
+        let meta = store.meta("b.txt").await.unwrap().unwrap();
+        assert_eq!(meta.updated_at, Some(time));
+
+        // Without the option, the time of the write is kept.
+        let store = FsObjStore::new(FsObjStoreConfig::new(dir.path().to_owned())).unwrap();
+        let meta = store
+            .put("c.txt")
+            .modified_at(time)
+            .text("c")
+            .await
+            .unwrap();
+        assert_ne!(meta.updated_at, Some(time));
+    }
+
+    #[tokio::test]
+    async fn test_streamed_put_carries_hashes() {
+        use objstore::{ContentHashes, ObjStoreExt as _, SizedValueStream};
+
+        let dir = tempfile::tempdir().unwrap();
+        let store = FsObjStore::new(FsObjStoreConfig::new(dir.path().to_owned())).unwrap();
+
+        let chunks = ["hello", " ", "world"].map(|chunk| Ok(Bytes::from_static(chunk.as_bytes())));
+        let stream = SizedValueStream::new_without_size(futures::stream::iter(chunks).boxed());
+        let meta = store.put("a").send(stream).await.unwrap();
+        let hashes = ContentHashes::of(b"hello world");
+        assert_eq!(meta.hash_md5, Some(hashes.md5));
+        assert_eq!(meta.hash_sha256, Some(hashes.sha256));
+
+        let meta = store.put("b").text("hello world").await.unwrap();
+        assert_eq!(meta.hash_sha256, Some(hashes.sha256));
+    }
+
+    #[cfg(windows)]
+    #[tokio::test]
+    async fn test_windows_special_and_long_keys() {
+        use objstore::ObjStoreExt as _;
+
+        let dir = tempfile::tempdir().unwrap();
+        let store = FsObjStore::new(FsObjStoreConfig::new(dir.path().to_owned())).unwrap();
+
+        let special = "dir:1/CON/what?.txt.";
+        store.put(special).text("special").await.unwrap();
+        assert_eq!(store.get(special).await.unwrap().unwrap(), "special");
+
+        // Well beyond MAX_PATH.
+        let long = format!("{}/{}.txt", "d".repeat(200), "f".repeat(200));
+        store.put(&long).text("long").await.unwrap();
+        assert_eq!(store.get(&long).await.unwrap().unwrap(), "long");
+
+        let mut keys = store.list_all_keys("").await.unwrap();
+        keys.sort();
+        assert_eq!(keys, [long.clone(), special.to_string()]);
+
+        store.delete(special).await.unwrap();
+        store.delete_prefix(&"d".repeat(200)).await.unwrap();
+        assert!(store.list_all_keys("").await.unwrap().is_empty());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_build_uri_roundtrip() {
+        use objstore::ObjStoreProvider as _;
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("my store");
+        let uri = FsObjStoreConfig::new(path.clone()).build_uri().unwrap();
+        assert!(uri.starts_with("fs:///"));
+
+        let store = FsProvider::new().build(&uri.parse().unwrap()).unwrap();
+        assert_eq!(store.safe_uri().to_file_path().unwrap(), path);
+
+        let uri = FsObjStoreConfig::new(path)
+            .with_preserve_mtime(true)
+            .with_read_chunk_size(1024)
+            .build_uri()
+            .unwrap();
+        assert!(
+            uri.ends_with("?preserve_mtime=true&read_chunk_size=1024"),
+            "{uri}"
+        );
+        FsProvider::new().build(&uri.parse().unwrap()).unwrap();
+    }
+
+    #[cfg(feature = "mmap")]
+    #[tokio::test]
+    async fn test_mmap_reads() {
+        use objstore::{ObjStoreExt as _, ObjStoreProvider as _};
+
+        let dir = tempfile::tempdir().unwrap();
+        let config = FsObjStoreConfig::new(dir.path().to_owned()).with_mmap_threshold(8);
+        let uri = config.build_uri().unwrap();
+        assert!(uri.ends_with("?mmap_threshold=8"), "{uri}");
+        FsProvider::new().build(&uri.parse().unwrap()).unwrap();
+
+        let store = FsObjStore::new(config).unwrap();
+        store.put("small").text("tiny").await.unwrap();
+        store.put("large").text("large enough").await.unwrap();
+        assert_eq!(store.get("small").await.unwrap().unwrap(), "tiny");
+        let mapped = store.get("large").await.unwrap().unwrap();
+        assert_eq!(mapped, "large enough");
+
+        // Returned data is owned, so later writes don't change it.
+        store.put("large").text("replaced").await.unwrap();
+        store.copy("small", "large").send().await.unwrap();
+        store
+            .append("large", Bytes::from_static(b"+"))
+            .await
+            .unwrap();
+        assert_eq!(mapped, "large enough");
+        let (data, meta) = store.get_with_meta("large").await.unwrap().unwrap();
+        assert_eq!(data, "tiny+");
+        assert_eq!(meta.size, Some(5));
+    }
+
+    #[tokio::test]
+    async fn test_read_chunk_size() {
+        use objstore::ObjStoreExt as _;
+
+        let dir = tempfile::tempdir().unwrap();
+        let config = FsObjStoreConfig::new(dir.path().to_owned()).with_read_chunk_size(4);
+        let store = FsObjStore::new(config).unwrap();
+        store.put("a").text("hello world").await.unwrap();
+
+        let chunks = store
+            .get_stream("a")
+            .await
+            .unwrap()
+            .unwrap()
+            .try_collect::<Vec<_>>()
+            .await
+            .unwrap();
+        let sizes = chunks.iter().map(Bytes::len).collect::<Vec<_>>();
+        assert_eq!(sizes, [4, 4, 3]);
+        assert_eq!(chunks.concat(), b"hello world");
+    }
 }
@@ -0,0 +1,200 @@
+//! Mapping between object keys and file system paths.
+//!
+//! On Unix, key segments are used as file names unchanged.
+//!
+//! Windows is more restrictive: NTFS file names can not contain `<>:"\|?*` or
+//! control characters, can not end with a dot or space, and device names like
+//! `CON` or `LPT1` (even with an extension) are reserved. Such characters are
+//! percent-encoded (`a:b` is stored as `a%3Ab`, `CON.txt` as `%43ON.txt`),
+//! together with `%` itself so the encoding is reversible.
+//! Paths are also given the `\\?\` prefix, which lifts the `MAX_PATH` limit
+//! of 260 characters.
+
+use std::{
+    borrow::Cow,
+    path::{Path, PathBuf},
+};
+
+/// The path of `key` below `root`.
+///
+/// Empty segments are skipped, so prefixes with a trailing slash map to
+/// their directory.
+pub(crate) fn key_path(root: &Path, key: &str) -> PathBuf {
+    let mut path = root.to_path_buf();
+    for segment in key.split('/').filter(|segment| !segment.is_empty()) {
+        path.push(encode_name(segment).as_ref());
+    }
+    path
+}
+
+/// Encode a key segment as a file name for the current platform.
+pub(crate) fn encode_name(segment: &str) -> Cow<'_, str> {
+    if cfg!(windows) {
+        encode_windows_name(segment)
+    } else {
+        Cow::Borrowed(segment)
+    }
+}
+
+/// Decode a file name into a key segment for the current platform.
+pub(crate) fn decode_name(name: &str) -> Cow<'_, str> {
+    if cfg!(windows) {
+        decode_windows_name(name)
+    } else {
+        Cow::Borrowed(name)
+    }
+}
+
+/// Prepare the store root for the current platform.
+///
+/// On Windows, the root is made absolute and converted to a `\\?\` path.
+pub(crate) fn root_path(root: PathBuf) -> PathBuf {
+    if cfg!(windows) {
+        let root = std::path::absolute(&root).unwrap_or(root);
+        match root.to_str() {
+            Some(path) => PathBuf::from(verbatim_path(path)),
+            // Non-Unicode roots can not be converted, keep them as they are.
+            None => root,
+        }
+    } else {
+        root
+    }
+}
+
+const WINDOWS_RESERVED_NAMES: &[&str] = &[
+    "CON", "PRN", "AUX", "NUL", "COM1", "COM2", "COM3", "COM4", "COM5", "COM6", "COM7", "COM8",
+    "COM9", "LPT1", "LPT2", "LPT3", "LPT4", "LPT5", "LPT6", "LPT7", "LPT8", "LPT9",
+];
+
+fn needs_windows_escape(c: char) -> bool {
+    matches!(c, '<' | '>' | ':' | '"' | '\\' | '|' | '?' | '*' | '%') || c.is_ascii_control()
+}
+
+fn is_reserved_windows_name(name: &str) -> bool {
+    let base = name.split('.').next().unwrap_or_default();
+    WINDOWS_RESERVED_NAMES
+        .iter()
+        .any(|reserved| base.eq_ignore_ascii_case(reserved))
+}
+
+fn push_escaped(out: &mut String, c: char) {
+    let mut buf = [0; 4];
+    for byte in c.encode_utf8(&mut buf).bytes() {
+        out.push_str(&format!("%{byte:02X}"));
+    }
+}
+
+fn encode_windows_name(segment: &str) -> Cow<'_, str> {
+    let reserved = is_reserved_windows_name(segment);
+    let trailing = segment.ends_with(['.', ' ']);
+    if !reserved && !trailing && !segment.chars().any(needs_windows_escape) {
+        return Cow::Borrowed(segment);
+    }
+
+    let last = segment.len() - segment.chars().next_back().map_or(0, char::len_utf8);
+    let mut out = String::with_capacity(segment.len() + 8);
+    for (index, c) in segment.char_indices() {
+        if needs_windows_escape(c) || (reserved && index == 0) || (trailing && index == last) {
+            push_escaped(&mut out, c);
+        } else {
+            out.push(c);
+        }
+    }
+    Cow::Owned(out)
+}
+
+fn decode_windows_name(name: &str) -> Cow<'_, str> {
+    if !name.contains('%') {
+        return Cow::Borrowed(name);
+    }
+
+    let bytes = name.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut index = 0;
+    while index < bytes.len() {
+        let decoded = (bytes[index] == b'%')
+            .then(|| name.get(index + 1..index + 3))
+            .flatten()
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+            // Slashes would change the key structure.
+            .filter(|byte| *byte != b'/');
+        match decoded {
+            Some(byte) => {
+                out.push(byte);
+                index += 3;
+            }
+            None => {
+                out.push(bytes[index]);
+                index += 1;
+            }
+        }
+    }
+    match String::from_utf8(out) {
+        Ok(decoded) => Cow::Owned(decoded),
+        // Not produced by the encoding, keep the name as it is.
+        Err(_) => Cow::Borrowed(name),
+    }
+}
+
+/// Convert an absolute Windows path to a `\\?\` path.
+fn verbatim_path(path: &str) -> String {
+    if path.starts_with(r"\\?\") {
+        path.to_string()
+    } else if let Some(unc) = path.strip_prefix(r"\\") {
+        format!(r"\\?\UNC\{unc}")
+    } else {
+        format!(r"\\?\{}", path.replace('/', r"\"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_windows_name_encoding_roundtrip() {
+        let cases = [
+            ("plain.txt", "plain.txt"),
+            ("a:b", "a%3Ab"),
+            ("what?*", "what%3F%2A"),
+            ("<tag>|\"q\"", "%3Ctag%3E%7C%22q%22"),
+            ("back\\slash", "back%5Cslash"),
+            ("100%", "100%25"),
+            ("tab\t", "tab%09"),
+            ("CON", "%43ON"),
+            ("con.txt", "%63on.txt"),
+            ("lpt9.tar.gz", "%6Cpt9.tar.gz"),
+            ("CONSOLE", "CONSOLE"),
+            ("trailing.", "trailing%2E"),
+            ("space ", "space%20"),
+            ("ü:", "ü%3A"),
+        ];
+        for (segment, encoded) in cases {
+            assert_eq!(encode_windows_name(segment), encoded, "encode {segment:?}");
+            assert_eq!(decode_windows_name(encoded), segment, "decode {encoded:?}");
+        }
+    }
+
+    #[test]
+    fn test_windows_name_decoding_keeps_foreign_names() {
+        assert_eq!(decode_windows_name("50%"), "50%");
+        assert_eq!(decode_windows_name("%zz"), "%zz");
+        assert_eq!(decode_windows_name("a%2Fb"), "a%2Fb");
+        assert_eq!(decode_windows_name("%FF"), "%FF");
+    }
+
+    #[test]
+    fn test_verbatim_path() {
+        assert_eq!(verbatim_path(r"C:\data\store"), r"\\?\C:\data\store");
+        assert_eq!(verbatim_path("C:/data/store"), r"\\?\C:\data\store");
+        assert_eq!(verbatim_path(r"\\server\share"), r"\\?\UNC\server\share");
+        assert_eq!(verbatim_path(r"\\?\C:\data"), r"\\?\C:\data");
+    }
+
+    #[test]
+    fn test_key_path_skips_empty_segments() {
+        let root = Path::new("root");
+        assert_eq!(key_path(root, "a/b/"), root.join("a").join("b"));
+        assert_eq!(key_path(root, ""), root);
+    }
+}
@@ -36,10 +36,52 @@ impl objstore::ObjStoreProvider for FsProvider {
             });
         }
 
-        let config = crate::FsObjStoreConfig {
-            path: url.path().into(),
-        };
+        // Go through a file URL to decode percent-encoded paths.
+        let path = url::Url::parse(&format!("file://{}", url.path()))
+            .ok()
+            .and_then(|file_url| file_url.to_file_path().ok())
+            .unwrap_or_else(|| url.path().into());
+        let mut config = crate::FsObjStoreConfig::new(path);
+        for (key, value) in url.query_pairs() {
+            config = match key.as_ref() {
+                "preserve_mtime" => config.with_preserve_mtime(parse_bool(&value)?),
+                "read_chunk_size" => config.with_read_chunk_size(parse_size(&value)?),
+                #[cfg(feature = "mmap")]
+                "mmap_threshold" => config.with_mmap_threshold(parse_size(&value)? as u64),
+                other => {
+                    return Err(ObjStoreError::InvalidConfig {
+                        message: format!(
+                            "unsupported fs query parameter '{other}': value '{value}'"
+                        ),
+                        source: None,
+                    });
+                }
+            };
+        }
         let store = crate::FsObjStore::new(config)?;
         Ok(Arc::new(store) as objstore::DynObjStore)
     }
 }
+
+fn parse_bool(value: &str) -> Result<bool> {
+    match value {
+        "1" | "true" | "on" | "yes" => Ok(true),
+        "0" | "false" | "off" | "no" => Ok(false),
+        other => Err(ObjStoreError::InvalidConfig {
+            message: format!(
+                "invalid bool value '{other}': expected one of [true,false,1,0,on,off,yes,no]"
+            ),
+            source: None,
+        }),
+    }
+}
+
+fn parse_size(value: &str) -> Result<usize> {
+    match value.parse::<usize>() {
+        Ok(size) if size > 0 => Ok(size),
+        _ => Err(ObjStoreError::InvalidConfig {
+            message: format!("invalid size '{value}': expected a positive number"),
+            source: None,
+        }),
+    }
+}
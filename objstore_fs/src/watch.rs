@@ -0,0 +1,131 @@
+//! Change notifications based on the `notify` crate.
+
+use std::{
+    collections::VecDeque,
+    path::{Path, PathBuf},
+};
+
+use futures::stream;
+use notify::{
+    EventKind, RecursiveMode, Watcher as _,
+    event::{CreateKind, ModifyKind, RemoveKind, RenameMode},
+};
+use objstore::{EventStream, ObjStoreError, ObjectEvent, Operation, Result};
+
+pub(crate) fn watch(root: &Path, prefix: &str) -> Result<EventStream> {
+    let (sender, receiver) = tokio::sync::mpsc::unbounded_channel();
+    let mut watcher = notify::recommended_watcher(move |res| {
+        sender.send(res).ok();
+    })
+    .map_err(watch_error)?;
+    watcher
+        .watch(root, RecursiveMode::Recursive)
+        .map_err(watch_error)?;
+
+    let root = root.to_owned();
+    let prefix = prefix.to_owned();
+    // The watcher is moved into the stream state, so it lives as long as the stream.
+    let stream = stream::unfold(
+        (watcher, receiver, VecDeque::new()),
+        move |(watcher, mut receiver, mut pending)| {
+            let root = root.clone();
+            let prefix = prefix.clone();
+            async move {
+                loop {
+                    if let Some(event) = pending.pop_front() {
+                        return Some((event, (watcher, receiver, pending)));
+                    }
+                    match receiver.recv().await? {
+                        Ok(event) => pending.extend(
+                            map_event(&root, event)
+                                .into_iter()
+                                .filter(|event| event.key().starts_with(&prefix))
+                                .map(Ok),
+                        ),
+                        Err(err) => pending.push_back(Err(watch_error(err))),
+                    }
+                }
+            }
+        },
+    );
+
+    Ok(Box::pin(stream))
+}
+
+/// Translate a filesystem event into object events.
+///
+/// Directory events are ignored.
+fn map_event(root: &Path, event: notify::Event) -> Vec<ObjectEvent> {
+    let created = |path: &PathBuf| {
+        path_key(root, path)
+            .filter(|_| path.is_file())
+            .map(|key| ObjectEvent::Created { key })
+    };
+    let updated = |path: &PathBuf| {
+        path_key(root, path)
+            .filter(|_| path.is_file())
+            .map(|key| ObjectEvent::Updated { key })
+    };
+    let deleted = |path: &PathBuf| path_key(root, path).map(|key| ObjectEvent::Deleted { key });
+
+    match event.kind {
+        EventKind::Create(CreateKind::Folder) => Vec::new(),
+        EventKind::Create(_) => event.paths.iter().filter_map(created).collect(),
+        EventKind::Modify(ModifyKind::Name(RenameMode::From)) => {
+            event.paths.iter().filter_map(deleted).collect()
+        }
+        EventKind::Modify(ModifyKind::Name(RenameMode::To)) => {
+            event.paths.iter().filter_map(created).collect()
+        }
+        EventKind::Modify(ModifyKind::Name(RenameMode::Both)) => match event.paths.as_slice() {
+            [from, to] => deleted(from).into_iter().chain(created(to)).collect(),
+            _ => Vec::new(),
+        },
+        EventKind::Modify(ModifyKind::Metadata(_)) => Vec::new(),
+        EventKind::Modify(_) => event.paths.iter().filter_map(updated).collect(),
+        EventKind::Remove(RemoveKind::Folder) => Vec::new(),
+        EventKind::Remove(_) => event.paths.iter().filter_map(deleted).collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Convert a path below the store root into an object key.
+fn path_key(root: &Path, path: &Path) -> Option<String> {
+    let relative = path.strip_prefix(root).ok()?;
+    let key = relative
+        .components()
+        .map(|component| {
+            crate::path_encoding::decode_name(&component.as_os_str().to_string_lossy()).into_owned()
+        })
+        .collect::<Vec<_>>()
+        .join("/");
+    // Temporary files of running writes are not objects.
+    (!key.is_empty() && !key.ends_with(crate::TEMP_SUFFIX)).then_some(key)
+}
+
+fn watch_error(source: notify::Error) -> ObjStoreError {
+    ObjStoreError::Io {
+        operation: Operation::Watch,
+        source: Some(source.into()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_path_key() {
+        let root = Path::new("/data/store");
+        assert_eq!(
+            path_key(root, Path::new("/data/store/a/b.txt")).as_deref(),
+            Some("a/b.txt")
+        );
+        assert_eq!(path_key(root, Path::new("/data/store")), None);
+        assert_eq!(path_key(root, Path::new("/other/a.txt")), None);
+        assert_eq!(
+            path_key(root, Path::new("/data/store/a/.b.txt.1-0.objstore-tmp")),
+            None
+        );
+    }
+}
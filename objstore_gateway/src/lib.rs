@@ -0,0 +1,208 @@
+//! HTTP gateway serving signed download URLs.
+//!
+//! Backends without native presigned URLs (filesystem, memory, logfs) can
+//! return URLs signed by an [`objstore::UrlSigner`] from
+//! [`ObjStore::generate_download_url`]. [`SignedUrlService`] verifies those
+//! URLs and streams the objects, so the links can be shared like presigned
+//! cloud storage URLs.
+//!
+//! The service is a plain [`tower_service::Service`], and can be mounted in
+//! axum with `Router::route_service`, or served with hyper directly.
+
+use std::{
+    convert::Infallible,
+    sync::Arc,
+    task::{Context, Poll},
+};
+
+use bytes::Bytes;
+use futures::{FutureExt as _, TryStreamExt as _, future::BoxFuture};
+use http::{HeaderValue, Method, StatusCode, header};
+use http_body::Frame;
+use http_body_util::{BodyExt as _, Empty, Full, StreamBody, combinators::UnsyncBoxBody};
+
+use objstore::{
+    DownloadUrlArgs, ObjStore, ObjStoreError, ObjectMeta, UrlSigner,
+    wrapper::policy::{PolicyObjStore, StorePolicy},
+};
+
+/// Response body of [`SignedUrlService`].
+pub type Body = UnsyncBoxBody<Bytes, ObjStoreError>;
+
+/// Tower service that verifies signed download URLs and streams the objects.
+///
+/// Must be served at the path of the signer's
+/// [base URL](UrlSigner::base_url). Only the path and query of requests are
+/// verified, so the service can run behind a proxy with a different host.
+///
+/// Responds with `403` for invalid or expired signatures, `404` for missing
+/// objects and `405` for methods other than `GET` and `HEAD`.
+///
+/// The store is accessed through a [`PolicyObjStore`], with the default
+/// [`StorePolicy`] unless configured with [`Self::with_policy`].
+#[derive(Debug)]
+pub struct SignedUrlService<S> {
+    store: Arc<PolicyObjStore<S>>,
+    signer: UrlSigner,
+}
+
+impl<S> Clone for SignedUrlService<S> {
+    fn clone(&self) -> Self {
+        Self {
+            store: self.store.clone(),
+            signer: self.signer.clone(),
+        }
+    }
+}
+
+impl<S> SignedUrlService<S>
+where
+    S: ObjStore + 'static,
+{
+    pub fn new(store: S, signer: UrlSigner) -> Self {
+        Self::with_policy(store, signer, StorePolicy::default())
+    }
+
+    pub fn with_policy(store: S, signer: UrlSigner, policy: StorePolicy) -> Self {
+        Self {
+            store: Arc::new(PolicyObjStore::new(store, policy)),
+            signer,
+        }
+    }
+
+    async fn handle(self, method: Method, uri: http::Uri) -> http::Response<Body> {
+        if method != Method::GET && method != Method::HEAD {
+            let mut res = text_response(StatusCode::METHOD_NOT_ALLOWED, "method not allowed");
+            res.headers_mut()
+                .insert(header::ALLOW, HeaderValue::from_static("GET, HEAD"));
+            return res;
+        }
+
+        let mut url = self.signer.base_url().clone();
+        url.set_path(uri.path());
+        url.set_query(uri.query());
+        let args = match self.signer.verify(&url) {
+            Ok(args) => args,
+            Err(err) => return error_response(&err),
+        };
+
+        let res = if method == Method::HEAD {
+            self.store
+                .meta(&args.key)
+                .await
+                .map(|meta| meta.map(|meta| (meta, empty_body())))
+        } else {
+            self.store
+                .get_stream_with_meta(&args.key)
+                .await
+                .map(|found| {
+                    found.map(|(meta, stream)| {
+                        let body = StreamBody::new(stream.map_ok(Frame::data));
+                        (meta, body.boxed_unsync())
+                    })
+                })
+        };
+        match res {
+            Ok(Some((meta, body))) => object_response(&args, &meta, body),
+            Ok(None) => error_response(&ObjStoreError::object_not_found(args.key)),
+            Err(err) => error_response(&err),
+        }
+    }
+}
+
+impl<S, B> tower_service::Service<http::Request<B>> for SignedUrlService<S>
+where
+    S: ObjStore + 'static,
+{
+    type Response = http::Response<Body>;
+    type Error = Infallible;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, req: http::Request<B>) -> Self::Future {
+        let this = self.clone();
+        let method = req.method().clone();
+        let uri = req.uri().clone();
+        async move { Ok(this.handle(method, uri).await) }.boxed()
+    }
+}
+
+fn object_response(args: &DownloadUrlArgs, meta: &ObjectMeta, body: Body) -> http::Response<Body> {
+    let mut res = http::Response::new(body);
+    let headers = res.headers_mut();
+
+    let content_length = meta.size.map(|size| size.to_string());
+    let etag = meta.etag.as_ref().map(|etag| format!("\"{etag}\""));
+    for (name, value) in [
+        (
+            header::CONTENT_TYPE,
+            args.response_content_type
+                .as_ref()
+                .or(meta.mime_type.as_ref()),
+        ),
+        (
+            header::CONTENT_DISPOSITION,
+            args.response_content_disposition
+                .as_ref()
+                .or(meta.content_disposition.as_ref()),
+        ),
+        (
+            header::CONTENT_ENCODING,
+            args.response_content_encoding
+                .as_ref()
+                .or(meta.content_encoding.as_ref()),
+        ),
+        (
+            header::CONTENT_LANGUAGE,
+            args.response_content_language.as_ref(),
+        ),
+        (
+            header::CACHE_CONTROL,
+            args.response_cache_control
+                .as_ref()
+                .or(meta.cache_control.as_ref()),
+        ),
+        (header::CONTENT_LENGTH, content_length.as_ref()),
+        (header::ETAG, etag.as_ref()),
+    ] {
+        // Values that are not valid header values are skipped.
+        if let Some(value) = value.and_then(|value| HeaderValue::from_str(value).ok()) {
+            headers.insert(name, value);
+        }
+    }
+    res
+}
+
+fn error_response(err: &ObjStoreError) -> http::Response<Body> {
+    match err {
+        ObjStoreError::ObjectNotFound { .. } => text_response(StatusCode::NOT_FOUND, "not found"),
+        ObjStoreError::PermissionDenied { .. } | ObjStoreError::Unauthenticated { .. } => {
+            text_response(StatusCode::FORBIDDEN, "invalid or expired signature")
+        }
+        ObjStoreError::InvalidRequest { .. } => {
+            text_response(StatusCode::BAD_REQUEST, "invalid signed URL")
+        }
+        // Do not leak backend details to clients.
+        _ => text_response(StatusCode::INTERNAL_SERVER_ERROR, "internal error"),
+    }
+}
+
+fn text_response(status: StatusCode, message: &'static str) -> http::Response<Body> {
+    let body = Full::new(Bytes::from_static(message.as_bytes()))
+        .map_err(|never| match never {})
+        .boxed_unsync();
+    let mut res = http::Response::new(body);
+    *res.status_mut() = status;
+    res.headers_mut().insert(
+        header::CONTENT_TYPE,
+        HeaderValue::from_static("text/plain; charset=utf-8"),
+    );
+    res
+}
+
+fn empty_body() -> Body {
+    Empty::new().map_err(|never| match never {}).boxed_unsync()
+}
@@ -0,0 +1,83 @@
+use std::time::Duration;
+
+use http::{Method, Request, StatusCode};
+use http_body_util::BodyExt as _;
+use objstore::{DownloadUrlArgs, ObjStore as _, ObjStoreExt as _, UrlSigner};
+use objstore_gateway::{Body, SignedUrlService};
+use objstore_memory::MemoryObjStore;
+use tower_service::Service as _;
+
+async fn setup() -> (MemoryObjStore, SignedUrlService<MemoryObjStore>) {
+    let signer = UrlSigner::new("http://localhost/files/".parse().unwrap(), "secret").unwrap();
+    let store = MemoryObjStore::new().with_url_signer(signer.clone());
+    store
+        .put("dir/hello.txt")
+        .mime_type("text/plain")
+        .text("hello")
+        .await
+        .unwrap();
+    let service = SignedUrlService::new(store.clone(), signer);
+    (store, service)
+}
+
+async fn request(
+    service: &mut SignedUrlService<MemoryObjStore>,
+    method: Method,
+    url: &url::Url,
+) -> http::Response<Body> {
+    let path = &url[url::Position::BeforePath..];
+    let req = Request::builder()
+        .method(method)
+        .uri(path)
+        .body(())
+        .unwrap();
+    service.call(req).await.unwrap()
+}
+
+async fn body_text(res: http::Response<Body>) -> String {
+    let bytes = res.into_body().collect().await.unwrap().to_bytes();
+    String::from_utf8(bytes.to_vec()).unwrap()
+}
+
+#[tokio::test]
+async fn test_serves_signed_url() {
+    let (store, mut service) = setup().await;
+
+    let mut args = DownloadUrlArgs::new("dir/hello.txt", Duration::from_secs(60));
+    args.response_content_disposition = Some("attachment; filename=\"hello.txt\"".to_string());
+    let url = store.generate_download_url(args).await.unwrap().unwrap();
+
+    let res = request(&mut service, Method::GET, &url).await;
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(res.headers()["content-type"], "text/plain");
+    assert_eq!(
+        res.headers()["content-disposition"],
+        "attachment; filename=\"hello.txt\""
+    );
+    assert_eq!(res.headers()["content-length"], "5");
+    assert_eq!(body_text(res).await, "hello");
+
+    let res = request(&mut service, Method::HEAD, &url).await;
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(res.headers()["content-length"], "5");
+    assert_eq!(body_text(res).await, "");
+}
+
+#[tokio::test]
+async fn test_rejects_invalid_requests() {
+    let (store, mut service) = setup().await;
+    let args = DownloadUrlArgs::new("dir/hello.txt", Duration::from_secs(60));
+    let url = store.generate_download_url(args).await.unwrap().unwrap();
+
+    let mut tampered = url.clone();
+    tampered.set_path("/files/dir/other.txt");
+    let res = request(&mut service, Method::GET, &tampered).await;
+    assert_eq!(res.status(), StatusCode::FORBIDDEN);
+
+    let res = request(&mut service, Method::DELETE, &url).await;
+    assert_eq!(res.status(), StatusCode::METHOD_NOT_ALLOWED);
+
+    store.delete("dir/hello.txt").await.unwrap();
+    let res = request(&mut service, Method::GET, &url).await;
+    assert_eq!(res.status(), StatusCode::NOT_FOUND);
+}
@@ -7,7 +7,7 @@ use serde::{Deserialize, Serialize};
 use url::Url;
 use zeroize::Zeroizing;
 
-#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq)]
 pub struct LogFsCryptoConfig {
     pub key: String,
     #[serde(with = "serde_bytes")]
@@ -15,6 +15,16 @@ pub struct LogFsCryptoConfig {
     pub iterations: NonZeroU32,
 }
 
+impl std::fmt::Debug for LogFsCryptoConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("LogFsCryptoConfig")
+            .field("key", &objstore::REDACTED)
+            .field("salt", &objstore::REDACTED)
+            .field("iterations", &self.iterations)
+            .finish()
+    }
+}
+
 impl LogFsCryptoConfig {
     pub fn into_crypto(self) -> CryptoConfig {
         CryptoConfig {
@@ -36,6 +46,13 @@ pub struct LogFsObjStoreConfig {
     #[serde(default)]
     pub readonly: bool,
     pub default_chunk_size: Option<u32>,
+    /// Size of the chunks yielded by streaming reads.
+    ///
+    /// By default, the chunks stored in the log are yielded as they are.
+    /// When set, they are coalesced into chunks of this size using a buffer
+    /// pool.
+    #[serde(default)]
+    pub read_chunk_size: Option<usize>,
     pub partial_index_write_interval: Option<u64>,
     pub full_index_write_interval: Option<u64>,
     pub crypto: Option<LogFsCryptoConfig>,
@@ -52,6 +69,7 @@ impl LogFsObjStoreConfig {
             offset: None,
             readonly: false,
             default_chunk_size: None,
+            read_chunk_size: None,
             partial_index_write_interval: None,
             full_index_write_interval: None,
             crypto: None,
@@ -83,6 +101,11 @@ impl LogFsObjStoreConfig {
         self
     }
 
+    pub fn with_read_chunk_size(mut self, chunk_size: Option<usize>) -> Self {
+        self.read_chunk_size = chunk_size.filter(|size| *size > 0);
+        self
+    }
+
     pub fn with_crypto(mut self, crypto: LogFsCryptoConfig) -> Self {
         self.crypto = Some(crypto);
         self
@@ -147,6 +170,48 @@ impl LogFsObjStoreConfig {
         })
     }
 
+    /// Build a URI that can be parsed back with [`Self::from_url`].
+    ///
+    /// NOTE: includes the crypto key, if configured.
+    pub fn build_uri(&self) -> Result<String> {
+        let mut url = self.safe_uri()?;
+        {
+            let mut pairs = url.query_pairs_mut();
+            for (name, enabled) in [
+                ("allow_create", self.allow_create),
+                ("readonly", self.readonly),
+                ("raw_mode", self.raw_mode),
+            ] {
+                if enabled {
+                    pairs.append_pair(name, "true");
+                }
+            }
+            for (name, value) in [
+                ("offset", self.offset),
+                ("chunk_size", self.default_chunk_size.map(u64::from)),
+                (
+                    "read_chunk_size",
+                    self.read_chunk_size.map(|size| size as u64),
+                ),
+                ("partial_index_interval", self.partial_index_write_interval),
+                ("full_index_interval", self.full_index_write_interval),
+            ] {
+                if let Some(value) = value {
+                    pairs.append_pair(name, &value.to_string());
+                }
+            }
+            if let Some(crypto) = &self.crypto {
+                let engine = base64::engine::general_purpose::STANDARD;
+                pairs.append_pair("crypto_key", &crypto.key);
+                pairs.append_pair("crypto_salt_b64", &engine.encode(&crypto.salt));
+                pairs.append_pair("crypto_iterations", &crypto.iterations.to_string());
+            }
+        }
+        let uri = url.to_string();
+        // Do not leave a dangling '?' if no options are set.
+        Ok(uri.strip_suffix('?').map(str::to_owned).unwrap_or(uri))
+    }
+
     pub fn from_url(url: &Url) -> Result<Self> {
         if url.scheme() != Self::URI_SCHEME {
             return Err(ObjStoreError::InvalidConfig {
@@ -204,6 +269,19 @@ impl LogFsObjStoreConfig {
                         }
                     })?)
                 }
+                "read_chunk_size" => {
+                    config.read_chunk_size = match value.parse::<usize>() {
+                        Ok(size) if size > 0 => Some(size),
+                        _ => {
+                            return Err(ObjStoreError::InvalidConfig {
+                                message: format!(
+                                    "invalid read chunk size '{value}': expected a positive number"
+                                ),
+                                source: None,
+                            });
+                        }
+                    }
+                }
                 "partial_index_interval" => {
                     config.partial_index_write_interval =
                         Some(value.parse::<u64>().map_err(|source| {
@@ -306,3 +384,29 @@ fn parse_bool(value: &str) -> Result<bool> {
         }),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build_uri_roundtrip() {
+        let config = LogFsObjStoreConfig::new(PathBuf::from("/tmp/store.log"));
+        assert_eq!(config.build_uri().unwrap(), "logfs:///tmp/store.log");
+
+        let config = config
+            .with_allow_create(true)
+            .with_default_chunk_size(Some(4096))
+            .with_read_chunk_size(Some(1024 * 1024))
+            .with_crypto(LogFsCryptoConfig {
+                key: "k&y".to_string(),
+                salt: b"salt".to_vec(),
+                iterations: NonZeroU32::new(10).unwrap(),
+            });
+        let url = config.build_uri().unwrap().parse::<Url>().unwrap();
+        assert_eq!(LogFsObjStoreConfig::from_url(&url).unwrap(), config);
+
+        let debug = format!("{config:?}");
+        assert!(!debug.contains("k&y"), "{debug}");
+    }
+}
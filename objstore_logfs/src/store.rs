@@ -1,4 +1,9 @@
-use std::{collections::BTreeSet, io::Write as _, sync::Arc};
+use std::{
+    collections::BTreeSet,
+    io::{Read as _, Write as _},
+    ops::Bound,
+    sync::Arc,
+};
 
 use bytes::Bytes;
 use futures::StreamExt;
@@ -10,8 +15,9 @@ use url::Url;
 use sha2::Digest;
 
 use objstore::{
-    BackendError, Copy, DataSource, DownloadUrlArgs, KeyPage, ListArgs, ObjStore, ObjStoreError,
-    ObjectMeta, ObjectMetaPage, Operation, Put, Result, UploadUrlArgs, ValueStream,
+    BackendError, BytesPool, Copy, DataSource, DownloadUrlArgs, KeyPage, ListArgs, ObjStore,
+    ObjStoreError, ObjectMeta, ObjectMetaPage, Operation, Put, Result, UploadUrlArgs, UrlSigner,
+    ValueStream,
 };
 
 use crate::LogFsObjStoreConfig;
@@ -19,11 +25,16 @@ use crate::LogFsObjStoreConfig;
 #[derive(Clone)]
 pub struct LogFsObjStore {
     state: Arc<State>,
+    url_signer: Option<UrlSigner>,
 }
 
 struct State {
     log: LogFs<Journal2>,
     safe_uri: Url,
+    /// Serializes appends, which read and rewrite the whole key.
+    append_lock: tokio::sync::Mutex<()>,
+    /// Coalesces read chunks, if a read chunk size is configured.
+    pool: Option<BytesPool>,
 }
 
 impl std::fmt::Debug for LogFsObjStore {
@@ -43,10 +54,24 @@ impl LogFsObjStore {
         let safe_uri = config.safe_uri()?;
 
         Ok(Self {
-            state: Arc::new(State { log, safe_uri }),
+            state: Arc::new(State {
+                log,
+                safe_uri,
+                append_lock: tokio::sync::Mutex::new(()),
+                pool: config.read_chunk_size.map(BytesPool::new),
+            }),
+            url_signer: None,
         })
     }
 
+    /// Return signed download URLs from [`ObjStore::generate_download_url`].
+    ///
+    /// The URLs must be served by a gateway using the same signer.
+    pub fn with_url_signer(mut self, signer: UrlSigner) -> Self {
+        self.url_signer = Some(signer);
+        self
+    }
+
     fn key_meta_to_object_meta(key: String, meta: KeyMeta) -> ObjectMeta {
         let mut obj = ObjectMeta::new(key);
         obj.size = Some(meta.size);
@@ -58,8 +83,7 @@ impl LogFsObjStore {
         obj.created_at = Some(now);
         obj.updated_at = Some(now);
         if let Some(chunk_size) = meta.chunk_size {
-            obj.extra
-                .insert("chunk_size".to_string(), serde_json::json!(chunk_size));
+            obj.attributes.set_custom("logfs.chunk_size", chunk_size);
         }
         obj
     }
@@ -90,23 +114,17 @@ impl LogFsObjStore {
     ) -> Result<(Vec<ObjectMeta>, Option<String>, Option<Vec<String>>)> {
         let prefix = args.prefix().map(|p| p.to_string()).unwrap_or_default();
         let limit = args.limit().unwrap_or(1_000) as usize;
-        let cursor = args.cursor().map(|c| c.to_string());
         let delimiter = args.delimiter().map(|d| d.to_string());
+        let after = args
+            .cursor()
+            .max(args.start_after())
+            .map(|after| after.to_string());
 
         self.with_log(move |log| {
-            let mut keys = if prefix.is_empty() {
-                log.paths_range(String::new()..)?
-            } else {
-                log.paths_range(prefix.clone()..)?
-            };
-
-            if let Some(cursor) = &cursor {
-                keys.retain(|key| key > cursor);
-            }
-
-            if !prefix.is_empty() {
-                keys.retain(|key| key.starts_with(&prefix));
-            }
+            // logfs only returns materialized ranges, so keys are loaded in
+            // windows of growing size around the cursor instead of loading
+            // all keys after it for every page.
+            let windows = key_windows(&prefix, after.as_deref());
 
             let mut truncated = false;
             let mut last_processed = None;
@@ -114,40 +132,44 @@ impl LogFsObjStore {
             let mut directories: BTreeSet<String> = BTreeSet::new();
             let mut processed = 0usize;
 
-            for key in keys.into_iter() {
-                processed += 1;
-                last_processed = Some(key.clone());
-
-                if let Some(delim) = delimiter.as_deref()
-                    && !delim.is_empty()
-                {
-                    let stripped = key
-                        .strip_prefix(&prefix)
-                        .map(|s| s.to_string())
-                        .unwrap_or_else(|| key.clone());
-                    if let Some(idx) = stripped.find(delim) {
-                        let dir = &stripped[..idx];
-                        let mut full = prefix.clone();
-                        full.push_str(dir);
-                        directories.insert(full);
-                        if processed >= limit {
-                            truncated = true;
-                            break;
+            'windows: for window in windows {
+                for key in log.paths_range(window)? {
+                    processed += 1;
+                    last_processed = Some(key.clone());
+
+                    if let Some(delim) = delimiter.as_deref()
+                        && !delim.is_empty()
+                    {
+                        let stripped = key
+                            .strip_prefix(&prefix)
+                            .map(|s| s.to_string())
+                            .unwrap_or_else(|| key.clone());
+                        if let Some(idx) = stripped.find(delim) {
+                            let dir = &stripped[..idx + delim.len()];
+                            let mut full = prefix.clone();
+                            full.push_str(dir);
+                            directories.insert(full);
+                            if processed >= limit {
+                                truncated = true;
+                                break 'windows;
+                            }
+                            continue;
                         }
-                        continue;
                     }
-                }
 
-                let key_meta = match log.get_meta(&key)? {
-                    Some(meta) => meta,
-                    None => continue,
-                };
-                let meta = Self::key_meta_to_object_meta(key.clone(), key_meta);
-                items.push(meta);
+                    let key_meta = match log.get_meta(&key)? {
+                        Some(meta) => meta,
+                        None => continue,
+                    };
+                    let meta = Self::key_meta_to_object_meta(key.clone(), key_meta);
+                    if args.matches(&meta) {
+                        items.push(meta);
+                    }
 
-                if processed >= limit {
-                    truncated = true;
-                    break;
+                    if processed >= limit {
+                        truncated = true;
+                        break 'windows;
+                    }
                 }
             }
 
@@ -159,6 +181,9 @@ impl LogFsObjStore {
 
             let next_cursor = if truncated {
                 last_processed
+            } else if args.has_filters() {
+                // Filtered out items might come after the last returned item.
+                None
             } else {
                 items.last().map(|item| item.key.clone())
             };
@@ -199,12 +224,15 @@ impl LogFsObjStore {
             source: Some(source.into()),
         })? {
             Ok(true) => {
-                let stream = futures::stream::unfold(rx, |mut rx| async {
+                let stream: ValueStream = Box::pin(futures::stream::unfold(rx, |mut rx| async {
                     rx.recv()
                         .await
                         .map(|item| (item.map_err(map_logfs_err), rx))
-                });
-                Ok(Some(Box::pin(stream)))
+                }));
+                match &self.state.pool {
+                    Some(pool) => Ok(Some(pool.rechunk(stream))),
+                    None => Ok(Some(stream)),
+                }
             }
             Ok(false) => Ok(None),
             Err(err) => Err(map_logfs_err(err)),
@@ -274,8 +302,26 @@ impl ObjStore for LogFsObjStore {
         Ok(None)
     }
 
-    async fn generate_download_url(&self, _args: DownloadUrlArgs) -> Result<Option<url::Url>> {
-        Ok(None)
+    async fn get_range(&self, key: &str, range: std::ops::Range<u64>) -> Result<Option<Bytes>> {
+        let key = key.to_string();
+        self.with_log(move |log| {
+            let mut reader = match log.get_reader(&key) {
+                Ok(reader) => reader,
+                Err(LogFsError::NotFound { .. }) => return Ok(None),
+                Err(err) => return Err(err),
+            };
+            std::io::copy(&mut reader.by_ref().take(range.start), &mut std::io::sink())?;
+            let mut buf = Vec::new();
+            reader
+                .take(range.end.saturating_sub(range.start))
+                .read_to_end(&mut buf)?;
+            Ok(Some(Bytes::from(buf)))
+        })
+        .await
+    }
+
+    async fn generate_download_url(&self, args: DownloadUrlArgs) -> Result<Option<url::Url>> {
+        Ok(self.url_signer.as_ref().map(|signer| signer.sign(&args)))
     }
 
     async fn generate_upload_url(&self, _args: UploadUrlArgs) -> Result<Option<url::Url>> {
@@ -283,6 +329,7 @@ impl ObjStore for LogFsObjStore {
     }
 
     async fn send_put(&self, put: Put) -> Result<ObjectMeta> {
+        objstore::validate_key(&put.key)?;
         let key = put.key.clone();
         match put.data {
             DataSource::Data(bytes) => {
@@ -296,8 +343,8 @@ impl ObjStore for LogFsObjStore {
                 })
                 .await
             }
-            DataSource::Stream(sized) => {
-                let mut stream = sized.into_stream();
+            data => {
+                let mut stream = data.into_stream().into_stream();
                 let log = self.state.log.clone();
                 let key_clone = key.clone();
                 let (tx, rx) = mpsc::channel::<Bytes>(8);
@@ -342,7 +389,24 @@ impl ObjStore for LogFsObjStore {
         }
     }
 
+    async fn append(&self, key: &str, data: Bytes) -> Result<ObjectMeta> {
+        objstore::validate_key(key)?;
+        let _guard = self.state.append_lock.lock().await;
+        let key = key.to_string();
+        self.with_log(move |log| {
+            let mut value = log.get(&key)?.unwrap_or_default();
+            value.extend_from_slice(&data);
+            log.insert(key.clone(), value)?;
+            let meta = log
+                .get_meta(&key)?
+                .ok_or_else(|| LogFsError::NotFound { path: key.clone() })?;
+            Ok(Self::key_meta_to_object_meta(key, meta))
+        })
+        .await
+    }
+
     async fn send_copy(&self, copy: Copy) -> Result<ObjectMeta> {
+        objstore::validate_key(&copy.target_key)?;
         self.with_log(move |log| {
             let data = log
                 .get(&copy.source_key)?
@@ -400,6 +464,61 @@ impl ObjStore for LogFsObjStore {
     }
 }
 
+/// A key range accepted by [`LogFs::paths_range`].
+type KeyRange = (Bound<String>, Bound<String>);
+
+/// Split the keys below `prefix` that sort after `after` into consecutive
+/// ranges of growing size.
+///
+/// The first range holds the keys starting with `after`, and each following
+/// range the keys sharing one character less with it, so a page that is
+/// filled from the first ranges never loads the rest of the key space.
+fn key_windows(prefix: &str, after: Option<&str>) -> Vec<KeyRange> {
+    let after = match after {
+        Some(after) if after.starts_with(prefix) => after,
+        // All keys below the prefix sort before `after`.
+        Some(after) if after > prefix => return Vec::new(),
+        _ => return vec![(Bound::Included(prefix.to_string()), prefix_end(prefix))],
+    };
+
+    let cuts = after
+        .char_indices()
+        .map(|(index, _)| index)
+        .chain([after.len()])
+        .filter(|index| *index >= prefix.len())
+        .rev();
+    let mut windows = Vec::new();
+    let mut lower = Bound::Excluded(after.to_string());
+    for cut in cuts {
+        let upper = prefix_end(&after[..cut]);
+        let next = match &upper {
+            Bound::Excluded(upper) => Bound::Included(upper.clone()),
+            _ => Bound::Unbounded,
+        };
+        let empty = matches!((&lower, &upper), (Bound::Included(l), Bound::Excluded(u)) if l == u);
+        if !empty {
+            windows.push((lower, upper));
+        }
+        if next == Bound::Unbounded {
+            break;
+        }
+        lower = next;
+    }
+    windows
+}
+
+/// The exclusive upper bound of all keys starting with `prefix`.
+fn prefix_end(prefix: &str) -> Bound<String> {
+    let mut end = prefix.to_string();
+    while let Some(last) = end.pop() {
+        if let Some(next) = (last as u32 + 1..=char::MAX as u32).find_map(char::from_u32) {
+            end.push(next);
+            return Bound::Excluded(end);
+        }
+    }
+    Bound::Unbounded
+}
+
 fn map_logfs_err(source: LogFsError) -> ObjStoreError {
     match source {
         LogFsError::NotFound { path } => ObjStoreError::ObjectNotFound {
@@ -439,9 +558,78 @@ mod tests {
             .with_allow_create(true)
             .with_crypto(crypto);
         let store = LogFsObjStore::new(config).unwrap();
+        objstore_test::assert_no_credentials(&store, &["hello123"]);
 
         let traced_store = TracedObjStore::new("logfs", store);
 
         objstore_test::test_objstore(&traced_store).await;
     }
+
+    #[test]
+    fn test_key_windows() {
+        let excluded = |key: &str| Bound::Excluded(key.to_string());
+        let included = |key: &str| Bound::Included(key.to_string());
+
+        assert_eq!(key_windows("a/", None), [(included("a/"), excluded("a0"))]);
+        assert_eq!(key_windows("", None), [(included(""), Bound::Unbounded)]);
+        assert!(key_windows("a/", Some("b")).is_empty());
+        assert_eq!(
+            key_windows("a/", Some("0")),
+            [(included("a/"), excluded("a0"))]
+        );
+        assert_eq!(
+            key_windows("a/", Some("a/bc")),
+            [
+                (excluded("a/bc"), excluded("a/bd")),
+                (included("a/bd"), excluded("a/c")),
+                (included("a/c"), excluded("a0")),
+            ]
+        );
+        assert_eq!(
+            key_windows("", Some("x")),
+            [
+                (excluded("x"), excluded("y")),
+                (included("y"), Bound::Unbounded)
+            ]
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_list_pages_across_windows() {
+        use objstore::ObjStoreExt as _;
+
+        let dir = tempfile::tempdir().unwrap();
+        let config = LogFsObjStoreConfig::new(dir.path().join("store.log")).with_allow_create(true);
+        let store = LogFsObjStore::new(config).unwrap();
+
+        let mut expected = Vec::new();
+        for a in 0..4 {
+            for b in 0..4 {
+                let key = format!("p/{a}/{b}");
+                store.put(&key).text("x").await.unwrap();
+                expected.push(key);
+            }
+        }
+        store.put("other").text("x").await.unwrap();
+
+        let mut keys = Vec::new();
+        let mut args = ListArgs::new().with_prefix("p/").with_limit(3);
+        loop {
+            let page = store.list(args.clone()).await.unwrap();
+            keys.extend(page.items.into_iter().map(|meta| meta.key));
+            match page.next_cursor {
+                Some(cursor) => args = args.with_cursor(cursor),
+                None => break,
+            }
+        }
+        assert_eq!(keys, expected);
+
+        let args = ListArgs::new()
+            .with_prefix("p/")
+            .with_start_after("p/2/3")
+            .with_limit(2);
+        let page = store.list(args).await.unwrap();
+        let keys: Vec<_> = page.items.into_iter().map(|meta| meta.key).collect();
+        assert_eq!(keys, ["p/3/0", "p/3/1"]);
+    }
 }
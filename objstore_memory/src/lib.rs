@@ -2,26 +2,46 @@ mod provider;
 
 pub use self::provider::MemoryProvider;
 
-use std::{collections::BTreeMap, sync::Arc};
+use std::{
+    collections::BTreeMap,
+    ops::Bound,
+    sync::{
+        Arc,
+        atomic::{AtomicU64, Ordering},
+    },
+    time::Duration,
+};
 
 use bytes::{Bytes, BytesMut};
-use futures::TryStreamExt as _;
+use futures::{FutureExt as _, StreamExt as _, TryStreamExt as _};
 use time::OffsetDateTime;
-use tokio::sync::RwLock;
+use tokio::sync::{RwLock, broadcast};
 
 use objstore::{
-    Copy, DataSource, DownloadUrlArgs, KeyPage, ListArgs, ObjStore, ObjStoreError, ObjectMeta,
-    ObjectMetaPage, Put, Result, UploadUrlArgs, ValueStream,
+    Clock, Conditions, Copy, DataSource, DownloadUrlArgs, EventStream, GetManyOptions,
+    GetManyStream, KeyPage, ListArgs, MatchValue, ObjStore, ObjStoreError, ObjectEvent, ObjectMeta,
+    ObjectMetaPage, ObjectOrPrefix, Operation, Put, PutResult, Resource, Result, SortOrder,
+    SystemClock, UploadUrlArgs, UrlSigner, Usage, ValueStream,
 };
 use url::Url;
 
 /// In-memory [`ObjStore`] implementation.
 ///
 /// Supports concurrent access.
+///
+/// Objects with an [expiration time](Put::expires_at) are hidden from reads
+/// once they expired, and removed by a timer task if a tokio runtime is
+/// available when they are written.
+///
+/// For tests, the store can be constrained with a capacity
+/// ([`Self::with_max_bytes`]) and a default TTL ([`Self::with_default_ttl`]),
+/// and made slow or flaky with [`Self::with_latency`] and
+/// [`Self::with_fail_rate`].
 #[derive(Clone)]
 pub struct MemoryObjStore {
     state: State,
     safe_uri: Url,
+    url_signer: Option<UrlSigner>,
 }
 
 impl std::fmt::Debug for MemoryObjStore {
@@ -36,23 +56,197 @@ struct Item {
     meta: ObjectMeta,
 }
 
+impl Item {
+    fn is_live(&self, now: OffsetDateTime) -> bool {
+        self.meta
+            .expires_at
+            .is_none_or(|expires_at| expires_at > now)
+    }
+}
+
+type Items = BTreeMap<String, Item>;
+
 #[derive(Clone)]
 struct State {
-    data: Arc<RwLock<BTreeMap<String, Item>>>,
+    data: Arc<RwLock<Items>>,
+    events: broadcast::Sender<ObjectEvent>,
+    clock: Arc<dyn Clock>,
+    max_bytes: Option<u64>,
+    default_ttl: Option<Duration>,
+    latency: Duration,
+    fail_rate: f64,
+    /// Random state for simulated failures.
+    rng: Arc<AtomicU64>,
+}
+
+impl State {
+    fn now(&self) -> OffsetDateTime {
+        self.clock.now()
+    }
+
+    /// Look up an item, ignoring expired ones.
+    fn live_item<'a>(&self, data: &'a Items, key: &str) -> Option<&'a Item> {
+        let now = self.now();
+        data.get(key).filter(|item| item.is_live(now))
+    }
+
+    /// Expiration of a new object without an explicit expiration time.
+    fn default_expiry(&self, now: OffsetDateTime) -> Option<OffsetDateTime> {
+        self.default_ttl.map(|ttl| now + ttl)
+    }
+
+    /// Ensure that replacing `key` with `size` bytes stays within the capacity.
+    fn check_capacity(&self, data: &Items, key: &str, size: u64) -> Result<()> {
+        let Some(max_bytes) = self.max_bytes else {
+            return Ok(());
+        };
+        let now = self.now();
+        let used = data
+            .iter()
+            .filter(|(existing, item)| existing.as_str() != key && item.is_live(now))
+            .map(|(_, item)| item.data.len() as u64)
+            .sum::<u64>();
+        if used + size > max_bytes {
+            return Err(ObjStoreError::InvalidRequest {
+                message: format!(
+                    "writing {size} bytes to '{key}' exceeds the memory store capacity of \
+                     {max_bytes} bytes"
+                ),
+                source: None,
+            });
+        }
+        Ok(())
+    }
+
+    /// Apply the simulated latency and failure rate to an operation.
+    async fn simulate(&self, operation: Operation) -> Result<()> {
+        if !self.latency.is_zero() {
+            tokio::time::sleep(self.latency).await;
+        }
+        if self.fail_rate > 0.0 && self.next_f64() < self.fail_rate {
+            return Err(simulated_failure(operation));
+        }
+        Ok(())
+    }
+
+    fn next_f64(&self) -> f64 {
+        // SplitMix64 over an atomic counter.
+        let mut z = self
+            .rng
+            .fetch_add(0x9e37_79b9_7f4a_7c15, Ordering::Relaxed)
+            .wrapping_add(0x9e37_79b9_7f4a_7c15);
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^= z >> 31;
+        (z >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    fn notify(&self, event: ObjectEvent) {
+        // Sending only fails if there are no watchers.
+        self.events.send(event).ok();
+    }
+
+    /// Spawn a task that removes the item once it expired.
+    ///
+    /// The task does not keep the store alive, and leaves the key alone if it
+    /// was overwritten in the meantime.
+    fn schedule_expiry(&self, key: String, expires_at: OffsetDateTime) {
+        let Ok(runtime) = tokio::runtime::Handle::try_current() else {
+            // Expired items are still hidden from reads.
+            return;
+        };
+        let data = Arc::downgrade(&self.data);
+        let events = self.events.downgrade();
+        let clock = self.clock.clone();
+        runtime.spawn(async move {
+            let delay = expires_at - clock.now();
+            tokio::time::sleep(delay.try_into().unwrap_or_default()).await;
+
+            let Some(data) = data.upgrade() else {
+                return;
+            };
+            let mut data = data.write().await;
+            if data
+                .get(&key)
+                .is_some_and(|item| item.meta.expires_at == Some(expires_at))
+            {
+                data.remove(&key);
+                if let Some(events) = events.upgrade() {
+                    events.send(ObjectEvent::Deleted { key }).ok();
+                }
+            }
+        });
+    }
 }
 
 impl MemoryObjStore {
     /// The kind of this object store (see [`ObjStore::kind`]).
     pub const KIND: &'static str = "objstore.memory";
 
+    /// Number of events buffered per watcher before it starts lagging.
+    const EVENT_CAPACITY: usize = 1024;
+
     pub fn new() -> Self {
         Self {
             safe_uri: Url::parse("memory://").expect("Invalid URL for MemoryObjStore"),
             state: State {
                 data: Arc::new(RwLock::new(BTreeMap::new())),
+                events: broadcast::channel(Self::EVENT_CAPACITY).0,
+                clock: Arc::new(SystemClock),
+                max_bytes: None,
+                default_ttl: None,
+                latency: Duration::ZERO,
+                fail_rate: 0.0,
+                rng: Arc::new(AtomicU64::new(random_seed())),
             },
+            url_signer: None,
         }
     }
+
+    /// Use `clock` for timestamps and expiration instead of the system clock.
+    ///
+    /// With a [`objstore::ManualClock`], `created_at` and `updated_at` are
+    /// deterministic, which allows exact assertions in tests.
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.state.clock = clock;
+        self
+    }
+
+    /// Limit the total size of all stored objects.
+    ///
+    /// Writes that would exceed the capacity fail with
+    /// [`ObjStoreError::InvalidRequest`].
+    pub fn with_max_bytes(mut self, max_bytes: u64) -> Self {
+        self.state.max_bytes = Some(max_bytes);
+        self
+    }
+
+    /// Expire new objects after `ttl`, unless the put sets an expiration time.
+    pub fn with_default_ttl(mut self, ttl: Duration) -> Self {
+        self.state.default_ttl = Some(ttl);
+        self
+    }
+
+    /// Delay every operation by `latency`.
+    pub fn with_latency(mut self, latency: Duration) -> Self {
+        self.state.latency = latency;
+        self
+    }
+
+    /// Fail the given fraction of operations (`0.0..=1.0`) with a transient
+    /// [`ObjStoreError::Io`] error.
+    pub fn with_fail_rate(mut self, fail_rate: f64) -> Self {
+        self.state.fail_rate = fail_rate.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Return signed download URLs from [`ObjStore::generate_download_url`].
+    ///
+    /// The URLs must be served by a gateway using the same signer.
+    pub fn with_url_signer(mut self, signer: UrlSigner) -> Self {
+        self.url_signer = Some(signer);
+        self
+    }
 }
 
 impl Default for MemoryObjStore {
@@ -76,25 +270,43 @@ impl ObjStore for MemoryObjStore {
     }
 
     async fn meta(&self, key: &str) -> Result<Option<ObjectMeta>> {
-        let meta = self
+        self.state.simulate(Operation::Meta).await?;
+        let data = self.state.data.read().await;
+        Ok(self
             .state
-            .data
-            .read()
-            .await
-            .get(key)
-            .map(|item| item.meta.clone());
-        Ok(meta)
+            .live_item(&data, key)
+            .map(|item| item.meta.clone()))
+    }
+
+    async fn exists(&self, key: &str) -> Result<bool> {
+        self.state.simulate(Operation::Meta).await?;
+        let data = self.state.data.read().await;
+        Ok(self.state.live_item(&data, key).is_some())
+    }
+
+    async fn usage(&self, prefix: &str) -> Result<Usage> {
+        self.state.simulate(Operation::List).await?;
+        let data = self.state.data.read().await;
+        let now = self.state.now();
+        let usage = data
+            .range(prefix.to_string()..)
+            .take_while(|(key, _)| key.starts_with(prefix))
+            .filter(|(_, item)| item.is_live(now))
+            .fold(Usage::default(), |mut usage, (_, item)| {
+                usage.objects += 1;
+                usage.bytes += item.data.len() as u64;
+                usage
+            });
+        Ok(usage)
     }
 
     async fn get(&self, key: &str) -> Result<Option<Bytes>> {
-        let bytes = self
+        self.state.simulate(Operation::Get).await?;
+        let data = self.state.data.read().await;
+        Ok(self
             .state
-            .data
-            .read()
-            .await
-            .get(key)
-            .map(|item| item.data.clone());
-        Ok(bytes)
+            .live_item(&data, key)
+            .map(|item| item.data.clone()))
     }
 
     async fn get_stream(&self, key: &str) -> Result<Option<ValueStream>> {
@@ -107,10 +319,12 @@ impl ObjStore for MemoryObjStore {
     }
 
     async fn get_with_meta(&self, key: &str) -> Result<Option<(Bytes, ObjectMeta)>> {
-        match self.state.data.read().await.get(key).cloned() {
-            Some(item) => Ok(Some((item.data, item.meta))),
-            None => Ok(None),
-        }
+        self.state.simulate(Operation::Get).await?;
+        let data = self.state.data.read().await;
+        Ok(self
+            .state
+            .live_item(&data, key)
+            .map(|item| (item.data.clone(), item.meta.clone())))
     }
 
     async fn get_stream_with_meta(&self, key: &str) -> Result<Option<(ObjectMeta, ValueStream)>> {
@@ -122,8 +336,46 @@ impl ObjStore for MemoryObjStore {
         }
     }
 
-    async fn generate_download_url(&self, _args: DownloadUrlArgs) -> Result<Option<url::Url>> {
-        Ok(None)
+    async fn get_range(&self, key: &str, range: std::ops::Range<u64>) -> Result<Option<Bytes>> {
+        let Some(value) = self.get(key).await? else {
+            return Ok(None);
+        };
+        let len = value.len() as u64;
+        let start = range.start.min(len);
+        let end = range.end.clamp(start, len);
+        Ok(Some(value.slice(start as usize..end as usize)))
+    }
+
+    fn get_many<'a>(&'a self, keys: Vec<String>, _options: GetManyOptions) -> GetManyStream<'a> {
+        // Serve all keys from a single read lock.
+        async move {
+            if self.state.simulate(Operation::Get).await.is_err() {
+                // Report the failure for every key.
+                let items = keys
+                    .into_iter()
+                    .map(|key| (key, Err(simulated_failure(Operation::Get))))
+                    .collect::<Vec<_>>();
+                return futures::stream::iter(items);
+            }
+            let data = self.state.data.read().await;
+            let items = keys
+                .into_iter()
+                .map(|key| {
+                    let value = self
+                        .state
+                        .live_item(&data, &key)
+                        .map(|item| item.data.clone());
+                    (key, Ok(value))
+                })
+                .collect::<Vec<_>>();
+            futures::stream::iter(items)
+        }
+        .flatten_stream()
+        .boxed()
+    }
+
+    async fn generate_download_url(&self, args: DownloadUrlArgs) -> Result<Option<url::Url>> {
+        Ok(self.url_signer.as_ref().map(|signer| signer.sign(&args)))
     }
 
     async fn generate_upload_url(&self, _args: UploadUrlArgs) -> Result<Option<url::Url>> {
@@ -131,12 +383,23 @@ impl ObjStore for MemoryObjStore {
     }
 
     async fn send_put(&self, put: Put) -> Result<ObjectMeta> {
+        Ok(self.send_put_result(put).await?.meta)
+    }
+
+    async fn send_put_result(&self, put: Put) -> Result<PutResult> {
         use sha2::Digest;
 
+        objstore::validate_key(&put.key)?;
+        self.state.simulate(Operation::Put).await?;
+
         let value = match put.data {
             DataSource::Data(bytes) => bytes,
-            DataSource::Stream(sized) => {
-                let data = sized.into_stream().try_collect::<BytesMut>().await?;
+            data => {
+                let data = data
+                    .into_stream()
+                    .into_stream()
+                    .try_collect::<BytesMut>()
+                    .await?;
                 data.freeze()
             }
         };
@@ -146,25 +409,107 @@ impl ObjStore for MemoryObjStore {
         // Use the sha256 hash as the etag.
         let etag = format!("sha256:{digest:x}");
 
-        let now = OffsetDateTime::now_utc();
+        let now = self.state.now();
         let mut meta = ObjectMeta::new(put.key.clone());
         meta.size = Some(value.len() as u64);
         meta.etag = Some(etag.clone());
         meta.created_at = Some(now);
         meta.updated_at = Some(now);
         meta.hash_sha256 = Some(digest.into());
+        meta.mime_type = put.mime_type;
+        meta.cache_control = put.cache_control;
+        meta.content_disposition = put.content_disposition;
+        meta.content_encoding = put.content_encoding;
+        meta.expires_at = put.expires_at.or_else(|| self.state.default_expiry(now));
+
+        let mut data = self.state.data.write().await;
+        let existing_etag = self
+            .state
+            .live_item(&data, &put.key)
+            .map(|item| item.meta.etag.as_deref());
+        if !conditions_match(&put.conditions, existing_etag) {
+            return Err(ObjStoreError::PreconditionFailed {
+                operation: Operation::Put,
+                resource: Some(Resource::Object { key: put.key }),
+                source: None,
+            });
+        }
+        self.state
+            .check_capacity(&data, &put.key, value.len() as u64)?;
+        let previous = data.insert(
+            put.key.clone(),
+            Item {
+                data: value,
+                meta: meta.clone(),
+            },
+        );
+        if let Some(expires_at) = meta.expires_at {
+            self.state.schedule_expiry(put.key.clone(), expires_at);
+        }
+        // Replaced items may have expired already.
+        let created = previous.is_none_or(|item| !item.is_live(now));
+        self.state.notify(if created {
+            ObjectEvent::Created { key: put.key }
+        } else {
+            ObjectEvent::Updated { key: put.key }
+        });
+        Ok(PutResult::new(meta, created))
+    }
 
-        self.state.data.write().await.insert(
-            put.key,
+    async fn append(&self, key: &str, data: Bytes) -> Result<ObjectMeta> {
+        use sha2::Digest;
+
+        objstore::validate_key(key)?;
+        self.state.simulate(Operation::Append).await?;
+
+        let mut items = self.state.data.write().await;
+        let now = self.state.now();
+        let (value, mut meta) = match self.state.live_item(&items, key) {
+            Some(item) => {
+                let mut value = BytesMut::with_capacity(item.data.len() + data.len());
+                value.extend_from_slice(&item.data);
+                value.extend_from_slice(&data);
+                (value.freeze(), item.meta.clone())
+            }
+            None => {
+                let mut meta = ObjectMeta::new(key.to_owned());
+                meta.created_at = Some(now);
+                meta.expires_at = self.state.default_expiry(now);
+                (data, meta)
+            }
+        };
+
+        let digest = sha2::Sha256::digest(&value);
+        meta.size = Some(value.len() as u64);
+        meta.etag = Some(format!("sha256:{digest:x}"));
+        meta.hash_sha256 = Some(digest.into());
+        meta.updated_at = Some(now);
+
+        self.state.check_capacity(&items, key, value.len() as u64)?;
+        let previous = items.insert(
+            key.to_owned(),
             Item {
                 data: value,
                 meta: meta.clone(),
             },
         );
+        if previous.is_none()
+            && let Some(expires_at) = meta.expires_at
+        {
+            self.state.schedule_expiry(key.to_owned(), expires_at);
+        }
+        let key = key.to_owned();
+        self.state.notify(match previous {
+            Some(_) => ObjectEvent::Updated { key },
+            None => ObjectEvent::Created { key },
+        });
         Ok(meta)
     }
 
     async fn send_copy(&self, copy: Copy) -> Result<ObjectMeta> {
+        objstore::validate_key(&copy.target_key)?;
+        self.state.simulate(Operation::Copy).await?;
+
         // Load source item
         let item = {
             let data_read = self.state.data.read().await;
@@ -172,64 +517,133 @@ impl ObjStore for MemoryObjStore {
 
             // TODO: support conditions
 
-            data_read
-                .get(&copy.source_key)
+            self.state
+                .live_item(&data_read, &copy.source_key)
                 .cloned()
                 .ok_or_else(|| ObjStoreError::object_not_found(copy.source_key.clone()))?
         };
         // Create new metadata for destination
         let mut meta = item.meta.clone();
         meta.key = copy.target_key.clone();
-        let now = OffsetDateTime::now_utc();
+        if let Some(metadata) = &copy.metadata {
+            metadata.apply(&mut meta);
+        }
+        let now = self.state.now();
         meta.created_at = Some(now);
         meta.updated_at = Some(now);
         // Insert copied data
-        self.state.data.write().await.insert(
+        let mut data = self.state.data.write().await;
+        self.state
+            .check_capacity(&data, &copy.target_key, item.data.len() as u64)?;
+        let previous = data.insert(
             copy.target_key.clone(),
             Item {
                 data: item.data,
                 meta: meta.clone(),
             },
         );
+        if let Some(expires_at) = meta.expires_at {
+            self.state
+                .schedule_expiry(copy.target_key.clone(), expires_at);
+        }
+        self.state.notify(match previous {
+            Some(_) => ObjectEvent::Updated {
+                key: copy.target_key,
+            },
+            None => ObjectEvent::Created {
+                key: copy.target_key,
+            },
+        });
         Ok(meta)
     }
 
     async fn delete(&self, key: &str) -> Result<()> {
-        self.state.data.write().await.remove(key);
+        self.state.simulate(Operation::Delete).await?;
+        if self.state.data.write().await.remove(key).is_some() {
+            self.state.notify(ObjectEvent::Deleted {
+                key: key.to_owned(),
+            });
+        }
         Ok(())
     }
 
     async fn list(&self, args: ListArgs) -> Result<ObjectMetaPage> {
+        self.state.simulate(Operation::List).await?;
         let data = self.state.data.read().await;
 
         let limit = args.limit().unwrap_or(1_000) as usize;
-
-        let prefix = args.prefix().unwrap_or_default().to_owned();
-
-        let items: Vec<ObjectMeta> = {
-            let iter = data
-                .range(prefix.clone()..)
-                .take_while(|(key, _value)| key.starts_with(&prefix));
-
-            if let Some(cursor) = args.cursor() {
-                let cursor = cursor.to_owned();
-                iter.skip_while(|(key, _value)| key <= &&cursor)
-                    .take(limit)
-                    .map(|(_key, item)| item.meta.clone())
-                    .collect()
+        let now = self.state.now();
+
+        let delimiter = args.delimiter();
+        // Common prefixes are relative to the requested prefix, so it can
+        // only be narrowed down without a delimiter.
+        let prefix = match delimiter {
+            Some(_) => args.prefix(),
+            None => args.effective_prefix(),
+        }
+        .unwrap_or_default()
+        .to_owned();
+
+        let iter: Box<dyn Iterator<Item = (&String, &Item)>> =
+            if args.sort() == Some(SortOrder::KeyDescending) {
+                let end = match args.cursor() {
+                    Some(cursor) => Bound::Excluded(cursor.to_owned()),
+                    None => Bound::Unbounded,
+                };
+                Box::new(
+                    data.range((Bound::Included(prefix.clone()), end))
+                        .rev()
+                        .filter(|(key, _item)| key.starts_with(&prefix)),
+                )
             } else {
-                iter.take(limit)
-                    .map(|(_key, item)| item.meta.clone())
-                    .collect()
+                let iter = data
+                    .range(prefix.clone()..)
+                    .take_while(|(key, _value)| key.starts_with(&prefix));
+                match args.cursor().max(args.start_after()) {
+                    Some(after) => {
+                        Box::new(iter.skip_while(move |(key, _value)| key.as_str() <= after))
+                    }
+                    None => Box::new(iter),
+                }
+            };
+
+        // A cursor pointing into a common prefix means the prefix was
+        // already returned.
+        let cursor_prefix = args
+            .cursor()
+            .zip(delimiter)
+            .and_then(|(cursor, delim)| common_prefix(cursor, &prefix, delim));
+
+        let mut entries = Vec::new();
+        for (key, item) in iter {
+            if entries.len() >= limit {
+                break;
             }
-        };
+            if !item.is_live(now) {
+                continue;
+            }
+            if let Some(common) = delimiter.and_then(|delim| common_prefix(key, &prefix, delim)) {
+                let seen = entries
+                    .last()
+                    .is_some_and(|entry: &ObjectOrPrefix| entry.key() == common);
+                if !seen && cursor_prefix != Some(common) {
+                    entries.push(ObjectOrPrefix::Prefix(common.to_owned()));
+                }
+            } else if args.matches(&item.meta) {
+                entries.push(ObjectOrPrefix::Object(Box::new(item.meta.clone())));
+            }
+        }
+
+        let next_cursor = entries.last().map(|entry| entry.key().to_owned());
+        Ok(ObjectMetaPage::from_entries(entries, next_cursor))
+    }
 
-        Ok(ObjectMetaPage {
-            next_cursor: items.last().map(|item| item.key().to_owned()),
-            // FIXME: implement args.delimiter() based prefix detection
-            prefixes: None,
-            items,
-        })
+    fn supports_sort(&self, order: SortOrder) -> bool {
+        matches!(order, SortOrder::KeyAscending | SortOrder::KeyDescending)
+    }
+
+    fn supports_conditions(&self) -> bool {
+        true
     }
 
     async fn list_keys(&self, args: ListArgs) -> Result<KeyPage> {
@@ -246,13 +660,95 @@ impl ObjStore for MemoryObjStore {
     }
 
     async fn delete_prefix(&self, prefix: &str) -> Result<()> {
-        self.state
-            .data
-            .write()
-            .await
-            .retain(|key, _value| !key.starts_with(prefix));
+        self.state.simulate(Operation::DeletePrefix).await?;
+        let mut data = self.state.data.write().await;
+        let keys = data
+            .range(prefix.to_owned()..)
+            .take_while(|(key, _value)| key.starts_with(prefix))
+            .map(|(key, _value)| key.clone())
+            .collect::<Vec<_>>();
+        for key in keys {
+            data.remove(&key);
+            self.state.notify(ObjectEvent::Deleted { key });
+        }
         Ok(())
     }
+
+    async fn watch(&self, prefix: &str) -> Result<EventStream> {
+        let prefix = prefix.to_owned();
+        let receiver = self.state.events.subscribe();
+        let stream = futures::stream::unfold(receiver, move |mut receiver| {
+            let prefix = prefix.clone();
+            async move {
+                loop {
+                    match receiver.recv().await {
+                        Ok(event) if event.key().starts_with(&prefix) => {
+                            return Some((Ok(event), receiver));
+                        }
+                        Ok(_) => {}
+                        Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                            let err = ObjStoreError::backend(
+                                "memory",
+                                Operation::Watch,
+                                format!("watcher lagged behind, {skipped} events were dropped"),
+                            );
+                            return Some((Err(err), receiver));
+                        }
+                        Err(broadcast::error::RecvError::Closed) => return None,
+                    }
+                }
+            }
+        });
+        Ok(Box::pin(stream))
+    }
+}
+
+/// The common prefix of `key` in a delimiter listing below `prefix`:
+/// everything up to and including the first delimiter after `prefix`.
+fn common_prefix<'a>(key: &'a str, prefix: &str, delimiter: &str) -> Option<&'a str> {
+    let rest = key.strip_prefix(prefix)?;
+    let idx = rest.find(delimiter)?;
+    Some(&key[..prefix.len() + idx + delimiter.len()])
+}
+
+/// Check the etag conditions of a put against the current object.
+///
+/// `existing` is `None` if the object does not exist, and `Some(etag)` otherwise.
+fn conditions_match(conditions: &Conditions, existing: Option<Option<&str>>) -> bool {
+    let matches = |value: &MatchValue, etag: Option<&str>| match value {
+        MatchValue::Any => true,
+        MatchValue::Tags(tags) => etag.is_some_and(|etag| tags.iter().any(|tag| tag == etag)),
+    };
+
+    if let Some(if_match) = &conditions.if_match {
+        match existing {
+            Some(etag) if matches(if_match, etag) => {}
+            _ => return false,
+        }
+    }
+    if let Some(if_none_match) = &conditions.if_none_match
+        && let Some(etag) = existing
+        && matches(if_none_match, etag)
+    {
+        return false;
+    }
+    true
+}
+
+fn simulated_failure(operation: Operation) -> ObjStoreError {
+    ObjStoreError::Io {
+        operation,
+        source: Some("simulated memory store failure".into()),
+    }
+}
+
+/// Seed for simulated failures, different for every store.
+fn random_seed() -> u64 {
+    use std::hash::{BuildHasher as _, Hasher as _};
+
+    std::collections::hash_map::RandomState::new()
+        .build_hasher()
+        .finish()
 }
 
 #[cfg(test)]
@@ -263,4 +759,46 @@ mod tests {
     async fn test_kv_memory() {
         objstore_test::test_objstore(&MemoryObjStore::new()).await;
     }
+
+    #[tokio::test]
+    async fn test_clock() {
+        let clock = objstore::ManualClock::default();
+        let store = MemoryObjStore::new().with_clock(Arc::new(clock.clone()));
+        objstore_test::test_clock_timestamps(&store, &clock, "clock").await;
+    }
+
+    #[tokio::test]
+    async fn test_seed_dataset() {
+        let spec = objstore_test::DatasetSpec {
+            seed: 7,
+            prefix: "data".to_string(),
+            ..Default::default()
+        };
+        let store = MemoryObjStore::new();
+        let manifest = objstore_test::seed_dataset(&store, &spec).await.unwrap();
+
+        // 1 + 3 + 9 directories with 4 objects each.
+        assert_eq!(manifest.len(), 52);
+        assert!(manifest.keys().all(|key| key.starts_with("data/")));
+        let leaf = manifest
+            .keys()
+            .filter(|key| key.starts_with("data/dir-002/dir-002/"))
+            .collect::<Vec<_>>();
+        assert_eq!(leaf.len(), 4);
+        assert!(manifest.get(leaf[0]).is_some());
+        manifest.assert_matches(&store).await;
+
+        let mut keys = store.list_all_keys("data/").await.unwrap();
+        keys.sort();
+        assert_eq!(keys, manifest.keys().map(String::from).collect::<Vec<_>>());
+
+        let again = objstore_test::generate_dataset(&spec);
+        assert_eq!(
+            again
+                .iter()
+                .map(|(key, _, _)| key.as_str())
+                .collect::<Vec<_>>(),
+            manifest.keys().collect::<Vec<_>>()
+        );
+    }
 }
@@ -1,9 +1,19 @@
-use std::sync::Arc;
+use std::{sync::Arc, time::Duration};
 
 use objstore::{ObjStoreError, ObjStoreProvider, Result};
 
 use crate::MemoryObjStore;
 
+/// Provider for `memory://` URIs.
+///
+/// Supported query parameters:
+///
+/// * `max_bytes`: capacity in bytes, see [`MemoryObjStore::with_max_bytes`]
+/// * `ttl`: default object TTL in seconds, see [`MemoryObjStore::with_default_ttl`]
+/// * `latency_ms`: latency of every operation, see [`MemoryObjStore::with_latency`]
+/// * `fail_rate`: fraction of failing operations, see [`MemoryObjStore::with_fail_rate`]
+///
+/// For example `memory://?max_bytes=1048576&latency_ms=20&fail_rate=0.1`.
 #[derive(Clone, Debug, Default)]
 pub struct MemoryProvider {
     _private: (),
@@ -38,7 +48,87 @@ impl ObjStoreProvider for MemoryProvider {
             });
         }
 
-        let store = crate::MemoryObjStore::new();
+        let mut store = crate::MemoryObjStore::new();
+        for (key, value) in url.query_pairs() {
+            store = match key.as_ref() {
+                "max_bytes" => store.with_max_bytes(parse_u64(&key, &value)?),
+                "ttl" => store.with_default_ttl(Duration::from_secs(parse_u64(&key, &value)?)),
+                "latency_ms" => store.with_latency(Duration::from_millis(parse_u64(&key, &value)?)),
+                "fail_rate" => match value.parse::<f64>() {
+                    Ok(rate) if (0.0..=1.0).contains(&rate) => store.with_fail_rate(rate),
+                    _ => {
+                        return Err(ObjStoreError::InvalidConfig {
+                            message: format!(
+                                "invalid fail_rate '{value}': expected a number between 0 and 1"
+                            ),
+                            source: None,
+                        });
+                    }
+                },
+                other => {
+                    return Err(ObjStoreError::InvalidConfig {
+                        message: format!(
+                            "unsupported memory query parameter '{other}': value '{value}'"
+                        ),
+                        source: None,
+                    });
+                }
+            };
+        }
         Ok(Arc::new(store) as objstore::DynObjStore)
     }
 }
+
+fn parse_u64(key: &str, value: &str) -> Result<u64> {
+    value
+        .parse::<u64>()
+        .map_err(|source| ObjStoreError::InvalidConfig {
+            message: format!("invalid {key} '{value}': expected u64"),
+            source: Some(source.into()),
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use objstore::ObjStoreExt as _;
+
+    use super::*;
+
+    fn build(uri: &str) -> Result<objstore::DynObjStore> {
+        MemoryProvider::new().build(&url::Url::parse(uri).unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_build_with_options() {
+        let store = build("memory://?max_bytes=4&ttl=60").unwrap();
+        let meta = store.put("a").text("abc").await.unwrap();
+        assert!(meta.expires_at.is_some());
+        assert!(matches!(
+            store.put("b").text("ab").await,
+            Err(ObjStoreError::InvalidRequest { .. })
+        ));
+        // Replacing an object only counts the new size.
+        store.put("a").text("abcd").await.unwrap();
+
+        let store = build("memory://?fail_rate=1").unwrap();
+        let err = store.put("a").text("a").await.unwrap_err();
+        assert!(err.is_transient());
+
+        build("memory://?latency_ms=1&fail_rate=0").unwrap();
+    }
+
+    #[test]
+    fn test_build_rejects_invalid_options() {
+        for uri in [
+            "memory://?max_bytes=lots",
+            "memory://?ttl=-1",
+            "memory://?fail_rate=1.5",
+            "memory://?unknown=1",
+        ] {
+            assert!(
+                matches!(build(uri), Err(ObjStoreError::InvalidConfig { .. })),
+                "{uri}"
+            );
+        }
+    }
+}
@@ -6,6 +6,10 @@ fn default_fetch_metadata_after_put() -> bool {
     true
 }
 
+fn default_delete_concurrency() -> usize {
+    S3ObjStoreConfig::DEFAULT_DELETE_CONCURRENCY
+}
+
 #[derive(serde::Serialize, serde::Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
 pub enum UrlStyle {
     /// Requests will use "path-style" url: i.e:
@@ -35,7 +39,7 @@ impl From<UrlStyle> for rusty_s3::UrlStyle {
     }
 }
 
-#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, PartialEq, Eq)]
+#[derive(serde::Serialize, serde::Deserialize, Clone, PartialEq, Eq)]
 pub struct S3ObjStoreConfig {
     pub url: Url,
     pub bucket: String,
@@ -43,6 +47,24 @@ pub struct S3ObjStoreConfig {
     pub path_style: UrlStyle,
     #[serde(default = "default_fetch_metadata_after_put")]
     pub fetch_metadata_after_put: bool,
+    /// Maximum number of concurrent object deletions in
+    /// [`objstore::ObjStore::delete_prefix`].
+    #[serde(default = "default_delete_concurrency")]
+    pub delete_concurrency: usize,
+    /// Size of the chunks yielded by streaming reads.
+    ///
+    /// By default, chunks are passed on as received from the connection.
+    /// When set, they are coalesced into chunks of this size using a buffer
+    /// pool, which reduces per-chunk overhead on high-throughput transfers.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub read_chunk_size: Option<usize>,
+    /// Proxy for all requests, like `http://proxy:3128` or
+    /// `socks5://proxy:1080` (requires the `socks` feature).
+    ///
+    /// Without a proxy, the `HTTPS_PROXY`, `HTTP_PROXY`, `ALL_PROXY` and
+    /// `NO_PROXY` environment variables are used.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub proxy: Option<Url>,
 
     pub key: String,
     pub secret: String,
@@ -52,15 +74,39 @@ pub struct S3ObjStoreConfig {
     pub path_prefix: Option<String>,
 }
 
+impl std::fmt::Debug for S3ObjStoreConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("S3ObjStoreConfig")
+            .field("url", &objstore::redact_url(&self.url))
+            .field("bucket", &self.bucket)
+            .field("region", &self.region)
+            .field("path_style", &self.path_style)
+            .field("fetch_metadata_after_put", &self.fetch_metadata_after_put)
+            .field("delete_concurrency", &self.delete_concurrency)
+            .field("read_chunk_size", &self.read_chunk_size)
+            .field("proxy", &self.proxy.as_ref().map(objstore::redact_url))
+            .field("key", &self.key)
+            .field("secret", &objstore::REDACTED)
+            .field("token", &self.token.as_ref().map(|_| objstore::REDACTED))
+            .field("path_prefix", &self.path_prefix)
+            .finish()
+    }
+}
+
 impl S3ObjStoreConfig {
     pub(crate) const URI_SCHEME: &'static str = "s3";
 
+    pub const DEFAULT_DELETE_CONCURRENCY: usize = 16;
+
     const QUERY_STYLE: &'static str = "style";
     const QUERY_REGION: &'static str = "region";
     const QUERY_PREFIX: &'static str = "prefix";
     const QUERY_TOKEN: &'static str = "token";
     const QUERY_FETCH_METADATA_AFTER_PUT: &'static str = "fetch_metadata_after_put";
     const QUERY_ENDPOINT_PATH: &'static str = "endpoint_path";
+    const QUERY_DELETE_CONCURRENCY: &'static str = "delete_concurrency";
+    const QUERY_READ_CHUNK_SIZE: &'static str = "read_chunk_size";
+    const QUERY_PROXY: &'static str = "proxy";
 
     pub fn validate(&self) -> Result<()> {
         if !(self.url.scheme() == "http" || self.url.scheme() == "https") {
@@ -90,6 +136,29 @@ impl S3ObjStoreConfig {
                 source: None,
             });
         }
+        if self.delete_concurrency == 0 {
+            return Err(ObjStoreError::InvalidConfig {
+                message: "delete concurrency must be at least 1".to_string(),
+                source: None,
+            });
+        }
+        if self.read_chunk_size == Some(0) {
+            return Err(ObjStoreError::InvalidConfig {
+                message: "read chunk size must be at least 1".to_string(),
+                source: None,
+            });
+        }
+        if let Some(proxy) = &self.proxy
+            && !matches!(proxy.scheme(), "http" | "https" | "socks5" | "socks5h")
+        {
+            return Err(ObjStoreError::InvalidConfig {
+                message: format!(
+                    "invalid proxy scheme: expected http, https, socks5 or socks5h, got '{}'",
+                    proxy.scheme()
+                ),
+                source: None,
+            });
+        }
 
         Ok(())
     }
@@ -143,6 +212,18 @@ impl S3ObjStoreConfig {
             if !self.fetch_metadata_after_put {
                 pairs.append_pair(Self::QUERY_FETCH_METADATA_AFTER_PUT, "false");
             }
+            if self.delete_concurrency != Self::DEFAULT_DELETE_CONCURRENCY {
+                pairs.append_pair(
+                    Self::QUERY_DELETE_CONCURRENCY,
+                    &self.delete_concurrency.to_string(),
+                );
+            }
+            if let Some(size) = self.read_chunk_size {
+                pairs.append_pair(Self::QUERY_READ_CHUNK_SIZE, &size.to_string());
+            }
+            if let Some(proxy) = &self.proxy {
+                pairs.append_pair(Self::QUERY_PROXY, proxy.as_str());
+            }
             if let Some(prefix) = &self.path_prefix {
                 pairs.append_pair(Self::QUERY_PREFIX, prefix);
             }
@@ -295,6 +376,49 @@ impl S3ObjStoreConfig {
             .transpose()?
             .unwrap_or(true);
 
+        let delete_concurrency = query_pairs
+            .iter()
+            .find(|(k, _)| k == Self::QUERY_DELETE_CONCURRENCY)
+            .map(|(_, v)| match v.parse::<usize>() {
+                Ok(value) if value > 0 => Ok(value),
+                _ => Err(ObjStoreError::InvalidConfig {
+                    message: format!(
+                        "invalid delete_concurrency: expected a positive number, got '{v}'"
+                    ),
+                    source: None,
+                }),
+            })
+            .transpose()?
+            .unwrap_or(Self::DEFAULT_DELETE_CONCURRENCY);
+
+        let read_chunk_size = query_pairs
+            .iter()
+            .find(|(k, _)| k == Self::QUERY_READ_CHUNK_SIZE)
+            .map(|(_, v)| match v.parse::<usize>() {
+                Ok(value) if value > 0 => Ok(value),
+                _ => Err(ObjStoreError::InvalidConfig {
+                    message: format!(
+                        "invalid read_chunk_size: expected a positive number, got '{v}'"
+                    ),
+                    source: None,
+                }),
+            })
+            .transpose()?;
+
+        let proxy = query_pairs
+            .iter()
+            .find(|(k, _)| k == Self::QUERY_PROXY)
+            .map(|(_, v)| v.as_ref())
+            .filter(|s| !s.is_empty())
+            .map(|v| {
+                v.parse::<Url>()
+                    .map_err(|source| ObjStoreError::InvalidConfig {
+                        message: "invalid proxy URL".to_string(),
+                        source: Some(source.into()),
+                    })
+            })
+            .transpose()?;
+
         let region = region.unwrap_or_else(|| "auto".to_string());
 
         let insecure = query_pairs.iter().any(|(k, _)| k == "insecure");
@@ -337,6 +461,9 @@ impl S3ObjStoreConfig {
             region,
             path_style,
             fetch_metadata_after_put,
+            delete_concurrency,
+            read_chunk_size,
+            proxy,
             key,
             secret,
             token,
@@ -366,6 +493,9 @@ mod tests {
                     region: "auto".to_string(),
                     path_style: UrlStyle::Path,
                     fetch_metadata_after_put: true,
+                    delete_concurrency: S3ObjStoreConfig::DEFAULT_DELETE_CONCURRENCY,
+                    read_chunk_size: None,
+                    proxy: None,
                     key: "user".to_string(),
                     secret: "pw".to_string(),
                     token: None,
@@ -383,6 +513,45 @@ mod tests {
             assert!(uri2_roundtrip.contains("fetch_metadata_after_put=false"));
         }
 
+        {
+            let uri = "s3://user:pw@host:9000/bucket?style=path&delete_concurrency=4";
+            let config = S3ObjStoreConfig::from_uri(uri).unwrap();
+            assert_eq!(config.delete_concurrency, 4);
+            let roundtrip = S3ObjStoreConfig::from_uri(&config.build_uri().unwrap()).unwrap();
+            assert_eq!(roundtrip, config);
+
+            let uri = "s3://user:pw@host:9000/bucket?style=path&delete_concurrency=0";
+            assert!(S3ObjStoreConfig::from_uri(uri).is_err());
+        }
+
+        {
+            let uri = "s3://user:pw@host:9000/bucket?style=path&read_chunk_size=1048576";
+            let config = S3ObjStoreConfig::from_uri(uri).unwrap();
+            assert_eq!(config.read_chunk_size, Some(1024 * 1024));
+            let roundtrip = S3ObjStoreConfig::from_uri(&config.build_uri().unwrap()).unwrap();
+            assert_eq!(roundtrip, config);
+
+            let uri = "s3://user:pw@host:9000/bucket?style=path&read_chunk_size=0";
+            assert!(S3ObjStoreConfig::from_uri(uri).is_err());
+        }
+
+        {
+            let uri = "s3://user:pw@host:9000/bucket?style=path&proxy=socks5h%3A%2F%2Fu%3Ap%40proxy%3A1080";
+            let config = S3ObjStoreConfig::from_uri(uri).unwrap();
+            assert_eq!(
+                config.proxy.as_ref().map(Url::as_str),
+                Some("socks5h://u:p@proxy:1080")
+            );
+            config.validate().unwrap();
+            let roundtrip = S3ObjStoreConfig::from_uri(&config.build_uri().unwrap()).unwrap();
+            assert_eq!(roundtrip, config);
+            assert!(!format!("{config:?}").contains("u:p@"));
+
+            let uri = "s3://user:pw@host:9000/bucket?style=path&proxy=ftp%3A%2F%2Fproxy";
+            let config = S3ObjStoreConfig::from_uri(uri).unwrap();
+            assert!(config.validate().is_err());
+        }
+
         {
             let json = r#"{
                 "url":"https://host:9000",
@@ -396,6 +565,10 @@ mod tests {
             }"#;
             let config: S3ObjStoreConfig = serde_json::from_str(json).unwrap();
             assert!(config.fetch_metadata_after_put);
+            assert_eq!(
+                config.delete_concurrency,
+                S3ObjStoreConfig::DEFAULT_DELETE_CONCURRENCY
+            );
         }
 
         {
@@ -405,6 +578,9 @@ mod tests {
                 region: "us-east-1".to_string(),
                 path_style: UrlStyle::VirtualHost,
                 fetch_metadata_after_put: false,
+                delete_concurrency: S3ObjStoreConfig::DEFAULT_DELETE_CONCURRENCY,
+                read_chunk_size: None,
+                proxy: None,
                 key: "user:name".to_string(),
                 secret: "pw/@:".to_string(),
                 token: Some("session/token".to_string()),
@@ -416,4 +592,15 @@ mod tests {
             assert_eq!(roundtrip, config);
         }
     }
+
+    #[test]
+    fn test_debug_redacts_credentials() {
+        let config = S3ObjStoreConfig::from_uri(
+            "s3://user:s3cr3t-pw@host:9000/bucket?style=path&token=t0k3n-value",
+        )
+        .unwrap();
+        let debug = format!("{config:?}");
+        assert!(!debug.contains("s3cr3t-pw"), "{debug}");
+        assert!(!debug.contains("t0k3n-value"), "{debug}");
+    }
 }
@@ -1,6 +1,6 @@
 use std::sync::Arc;
 
-use objstore::{ObjStoreError, Result};
+use objstore::{HttpClientConfig, ObjStoreError, Result};
 
 use crate::S3ObjStore;
 
@@ -27,13 +27,23 @@ impl objstore::ObjStoreProvider for S3LightProvider {
     }
 
     fn build(&self, url: &url::Url) -> Result<objstore::DynObjStore> {
-        let config = crate::S3ObjStoreConfig::from_uri(url.as_str()).map_err(|source| {
-            ObjStoreError::InvalidConfig {
-                message: "failed to parse S3 object store configuration from URI".to_string(),
-                source: Some(source.into()),
-            }
-        })?;
-        let store = crate::S3ObjStore::new(config)?;
+        let store = crate::S3ObjStore::new(parse_config(url)?)?;
         Ok(Arc::new(store) as objstore::DynObjStore)
     }
+
+    fn build_with_http_client(
+        &self,
+        url: &url::Url,
+        http: &HttpClientConfig,
+    ) -> Result<objstore::DynObjStore> {
+        let store = crate::S3ObjStore::new_with_http_client(parse_config(url)?, http)?;
+        Ok(Arc::new(store) as objstore::DynObjStore)
+    }
+}
+
+fn parse_config(url: &url::Url) -> Result<crate::S3ObjStoreConfig> {
+    crate::S3ObjStoreConfig::from_uri(url.as_str()).map_err(|source| ObjStoreError::InvalidConfig {
+        message: "failed to parse S3 object store configuration from URI".to_string(),
+        source: Some(source.into()),
+    })
 }
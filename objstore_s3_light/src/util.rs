@@ -4,7 +4,9 @@ use base64::Engine as _;
 use http::HeaderMap;
 #[cfg(test)]
 use objstore::BackendError;
-use objstore::{Conditions, ObjStoreError, ObjectMeta, Result};
+use objstore::{
+    Conditions, CopyMetadata, Encryption, EncryptionScheme, ObjStoreError, ObjectMeta, Put, Result,
+};
 use quick_xml::de::from_reader;
 use serde::Deserialize;
 use time::OffsetDateTime;
@@ -17,6 +19,115 @@ pub(crate) fn insert_signed_header<'a>(
     headers.insert(name.as_ref().to_ascii_lowercase(), value);
 }
 
+/// User metadata header storing [`Put::expires_at`] as a unix timestamp.
+///
+/// S3 has no per-object expiration, so the header is only informational.
+/// Expired objects must be removed by a bucket lifecycle rule or by
+/// [`objstore::wrapper::expiry::ExpiringObjStore`].
+pub(crate) const EXPIRES_AT_HEADER: &str = "x-amz-meta-objstore-expires-at";
+
+/// User metadata header storing the creation time as a unix timestamp.
+///
+/// S3 only reports the last modification time, so the creation time is
+/// written on the first put, and carried over from the existing object on
+/// overwrites and on copies that replace the metadata.
+/// Copies that keep the source metadata keep its creation time.
+/// Listings do not include user metadata, so listed objects have no
+/// creation time.
+pub(crate) const CREATED_AT_HEADER: &str = "x-amz-meta-objstore-created-at";
+
+/// The current time, truncated to the second precision of
+/// [`CREATED_AT_HEADER`].
+pub(crate) fn created_at_now() -> OffsetDateTime {
+    let now = OffsetDateTime::now_utc();
+    now.replace_nanosecond(0).unwrap_or(now)
+}
+
+/// Parse a unix timestamp user metadata header.
+///
+/// Any client can write user metadata, so invalid values are logged and
+/// ignored instead of failing the read.
+fn parse_timestamp_header(key: &str, headers: &HeaderMap, name: &str) -> Option<OffsetDateTime> {
+    let value = headers.get(name)?;
+    let timestamp = value
+        .to_str()
+        .ok()
+        .and_then(|raw| raw.parse::<i64>().ok())
+        .and_then(|ts| OffsetDateTime::from_unix_timestamp(ts).ok());
+    if timestamp.is_none() {
+        tracing::warn!(
+            key,
+            header = name,
+            value = %String::from_utf8_lossy(value.as_bytes()),
+            "ignoring invalid timestamp metadata header"
+        );
+    }
+    timestamp
+}
+
+/// Insert the [`CREATED_AT_HEADER`].
+pub(crate) fn insert_created_at_header(
+    headers: &mut rusty_s3::Map<'_>,
+    created_at: OffsetDateTime,
+) {
+    insert_signed_header(
+        headers,
+        CREATED_AT_HEADER,
+        created_at.unix_timestamp().to_string(),
+    );
+}
+
+/// Insert the content-related headers of a put request.
+pub(crate) fn insert_content_headers(headers: &mut rusty_s3::Map<'_>, put: &Put) {
+    insert_header_values(
+        headers,
+        [
+            (http::header::CONTENT_TYPE, &put.mime_type),
+            (http::header::CACHE_CONTROL, &put.cache_control),
+            (http::header::CONTENT_DISPOSITION, &put.content_disposition),
+            (http::header::CONTENT_ENCODING, &put.content_encoding),
+        ],
+    );
+    if let Some(expires_at) = put.expires_at {
+        insert_signed_header(
+            headers,
+            EXPIRES_AT_HEADER,
+            expires_at.unix_timestamp().to_string(),
+        );
+    }
+}
+
+/// Insert the headers replacing the destination metadata of a copy request.
+pub(crate) fn insert_copy_metadata_headers(
+    headers: &mut rusty_s3::Map<'_>,
+    metadata: &CopyMetadata,
+) {
+    insert_signed_header(headers, "x-amz-metadata-directive", "REPLACE");
+    insert_header_values(
+        headers,
+        [
+            (http::header::CONTENT_TYPE, &metadata.mime_type),
+            (http::header::CACHE_CONTROL, &metadata.cache_control),
+            (
+                http::header::CONTENT_DISPOSITION,
+                &metadata.content_disposition,
+            ),
+            (http::header::CONTENT_ENCODING, &metadata.content_encoding),
+        ],
+    );
+}
+
+fn insert_header_values(
+    headers: &mut rusty_s3::Map<'_>,
+    values: [(http::HeaderName, &Option<String>); 4],
+) {
+    for (name, value) in values {
+        if let Some(value) = value {
+            insert_signed_header(headers, name.as_str(), value.clone());
+        }
+    }
+}
+
 /// See <https://docs.aws.amazon.com/AmazonS3/latest/API/API_HeadObject.html>
 pub fn parse_object_headers(key: String, headers: &HeaderMap) -> Result<ObjectMeta> {
     let last_modified = if let Some(v) = headers.get(http::header::LAST_MODIFIED) {
@@ -97,6 +208,37 @@ pub fn parse_object_headers(key: String, headers: &HeaderMap) -> Result<ObjectMe
             .to_string();
         meta.mime_type = Some(ct);
     }
+    for (name, field) in [
+        (http::header::CACHE_CONTROL, &mut meta.cache_control),
+        (
+            http::header::CONTENT_DISPOSITION,
+            &mut meta.content_disposition,
+        ),
+        (http::header::CONTENT_ENCODING, &mut meta.content_encoding),
+        (
+            http::HeaderName::from_static("x-amz-storage-class"),
+            &mut meta.attributes.storage_class,
+        ),
+        (
+            http::HeaderName::from_static("x-amz-version-id"),
+            &mut meta.attributes.version_id,
+        ),
+    ] {
+        if let Some(v) = headers.get(&name) {
+            let value = v
+                .to_str()
+                .map_err(|source| ObjStoreError::InvalidMetadata {
+                    key: key.clone(),
+                    message: format!("invalid {name} header"),
+                    source: Some(source.into()),
+                })?
+                .to_string();
+            *field = Some(value);
+        }
+    }
+    meta.encryption = parse_encryption_headers(headers);
+    meta.created_at = parse_timestamp_header(&key, headers, CREATED_AT_HEADER);
+    meta.expires_at = parse_timestamp_header(&key, headers, EXPIRES_AT_HEADER);
     // Extract MD5 hash from Content-MD5 header (base64-encoded)
     if let Some(v) = headers.get("Content-MD5") {
         let raw = v
@@ -150,6 +292,23 @@ pub fn parse_object_headers(key: String, headers: &HeaderMap) -> Result<ObjectMe
     Ok(meta)
 }
 
+/// Parse the server-side encryption headers of an object response.
+fn parse_encryption_headers(headers: &HeaderMap) -> Option<Encryption> {
+    let header = |name: &str| headers.get(name).and_then(|v| v.to_str().ok());
+
+    if header("x-amz-server-side-encryption-customer-algorithm").is_some() {
+        let mut encryption = Encryption::new(EncryptionScheme::CustomerProvided);
+        encryption.key_id = header("x-amz-server-side-encryption-customer-key-md5").map(Into::into);
+        return Some(encryption);
+    }
+
+    let mut encryption = Encryption::new(EncryptionScheme::parse(header(
+        "x-amz-server-side-encryption",
+    )?));
+    encryption.key_id = header("x-amz-server-side-encryption-aws-kms-key-id").map(Into::into);
+    Some(encryption)
+}
+
 #[derive(Debug, Deserialize, Clone, PartialEq, Eq)]
 #[serde(rename = "Error")]
 pub struct S3ErrorResponse {
@@ -235,6 +394,82 @@ pub fn parse_copy_object_result(key: String, body: &[u8]) -> Result<Option<Objec
     Ok(Some(meta))
 }
 
+/// An incomplete multipart upload, see [`parse_list_multipart_uploads`].
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) struct MultipartUpload {
+    pub key: String,
+    pub upload_id: String,
+    pub initiated: Option<OffsetDateTime>,
+}
+
+/// A page of a `ListMultipartUploads` response.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub(crate) struct MultipartUploadPage {
+    pub uploads: Vec<MultipartUpload>,
+    /// Markers for the next page, if the listing is truncated.
+    pub next_markers: Option<(String, String)>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename = "ListMultipartUploadsResult")]
+struct ListMultipartUploadsResult {
+    #[serde(rename = "IsTruncated", default)]
+    is_truncated: bool,
+    #[serde(rename = "NextKeyMarker")]
+    next_key_marker: Option<String>,
+    #[serde(rename = "NextUploadIdMarker")]
+    next_upload_id_marker: Option<String>,
+    #[serde(rename = "Upload", default)]
+    uploads: Vec<ListMultipartUploadsEntry>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ListMultipartUploadsEntry {
+    #[serde(rename = "Key")]
+    key: String,
+    #[serde(rename = "UploadId")]
+    upload_id: String,
+    #[serde(rename = "Initiated")]
+    initiated: Option<String>,
+}
+
+/// Parse a `ListMultipartUploads` response, with url-encoded keys.
+pub(crate) fn parse_list_multipart_uploads(
+    body: &[u8],
+) -> Result<MultipartUploadPage, quick_xml::DeError> {
+    let result = from_reader::<_, ListMultipartUploadsResult>(body)?;
+    let uploads = result
+        .uploads
+        .into_iter()
+        .map(|upload| MultipartUpload {
+            key: percent_encoding::percent_decode_str(&upload.key)
+                .decode_utf8()
+                .map(Cow::into_owned)
+                .unwrap_or(upload.key),
+            upload_id: upload.upload_id,
+            initiated: upload.initiated.and_then(|raw| {
+                OffsetDateTime::parse(
+                    &raw,
+                    &time::format_description::well_known::Iso8601::DEFAULT,
+                )
+                .ok()
+            }),
+        })
+        .collect();
+    let next_markers = match (
+        result.is_truncated,
+        result.next_key_marker,
+        result.next_upload_id_marker,
+    ) {
+        (true, Some(key), Some(upload_id)) => Some((key, upload_id)),
+        _ => None,
+    };
+    Ok(MultipartUploadPage {
+        uploads,
+        next_markers,
+    })
+}
+
 pub fn apply_condition_headers(
     headers: &mut rusty_s3::Map,
     mut conditions: Conditions,
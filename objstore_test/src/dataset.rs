@@ -0,0 +1,248 @@
+//! Reproducible random datasets for tests, benchmarks and demos.
+
+use bytes::Bytes;
+use futures::{StreamExt as _, TryStreamExt as _};
+use objstore::{ObjStore, ObjStoreError, ObjStoreExt as _};
+use sha2::Digest as _;
+
+/// Distribution of object sizes in a generated dataset.
+#[derive(Clone, Debug, PartialEq)]
+pub enum SizeDistribution {
+    /// Every object has the same size.
+    Fixed(usize),
+    /// Sizes are uniformly distributed in `min..=max`.
+    Uniform { min: usize, max: usize },
+    /// Sizes are distributed uniformly on a log scale in `min..=max`.
+    ///
+    /// Produces many small and few large objects, like most real buckets.
+    LogUniform { min: usize, max: usize },
+}
+
+/// Shape of a dataset generated by [`seed_dataset`].
+///
+/// The same spec always produces the same keys and contents.
+#[derive(Clone, Debug)]
+pub struct DatasetSpec {
+    /// Seed for the random generator.
+    pub seed: u64,
+    /// Prefix for all keys. A trailing `/` is added if missing.
+    pub prefix: String,
+    /// Number of directory levels below the prefix.
+    pub depth: usize,
+    /// Number of sub-directories per directory.
+    pub fanout: usize,
+    /// Number of objects in every directory, including the root.
+    pub files_per_dir: usize,
+    pub sizes: SizeDistribution,
+    /// Fraction of objects with random binary content, in `0.0..=1.0`.
+    ///
+    /// The other objects contain random text.
+    pub binary_ratio: f64,
+}
+
+impl Default for DatasetSpec {
+    fn default() -> Self {
+        Self {
+            seed: 0,
+            prefix: String::new(),
+            depth: 2,
+            fanout: 3,
+            files_per_dir: 4,
+            sizes: SizeDistribution::LogUniform {
+                min: 0,
+                max: 64 * 1024,
+            },
+            binary_ratio: 0.5,
+        }
+    }
+}
+
+/// An object written by [`seed_dataset`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DatasetEntry {
+    pub key: String,
+    pub size: u64,
+    pub binary: bool,
+    pub hash_sha256: [u8; 32],
+}
+
+/// The objects written by [`seed_dataset`], sorted by key.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct DatasetManifest {
+    pub entries: Vec<DatasetEntry>,
+}
+
+impl DatasetManifest {
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn keys(&self) -> impl Iterator<Item = &str> {
+        self.entries.iter().map(|entry| entry.key.as_str())
+    }
+
+    pub fn get(&self, key: &str) -> Option<&DatasetEntry> {
+        self.entries
+            .binary_search_by(|entry| entry.key.as_str().cmp(key))
+            .ok()
+            .map(|index| &self.entries[index])
+    }
+
+    pub fn total_size(&self) -> u64 {
+        self.entries.iter().map(|entry| entry.size).sum()
+    }
+
+    /// Assert that the store contains every object of the manifest with
+    /// the expected content.
+    pub async fn assert_matches(&self, store: &impl ObjStore) {
+        for entry in &self.entries {
+            let data = store
+                .get(&entry.key)
+                .await
+                .unwrap()
+                .unwrap_or_else(|| panic!("missing dataset object '{}'", entry.key));
+            assert_eq!(data.len() as u64, entry.size, "size of '{}'", entry.key);
+            let hash: [u8; 32] = sha2::Sha256::digest(&data).into();
+            assert!(hash == entry.hash_sha256, "content of '{}'", entry.key);
+        }
+    }
+}
+
+/// Number of concurrent uploads used by [`seed_dataset`].
+const SEED_CONCURRENCY: usize = 16;
+
+/// Generate the dataset described by `spec` without writing it.
+///
+/// Returns the key and content of every object, sorted by key.
+pub fn generate_dataset(spec: &DatasetSpec) -> Vec<(String, Bytes, bool)> {
+    let mut prefix = spec.prefix.clone();
+    if !prefix.is_empty() && !prefix.ends_with('/') {
+        prefix.push('/');
+    }
+
+    let mut dirs = vec![prefix];
+    let mut level = dirs.clone();
+    for _ in 0..spec.depth {
+        level = level
+            .iter()
+            .flat_map(|dir| (0..spec.fanout).map(move |index| format!("{dir}dir-{index:03}/")))
+            .collect();
+        dirs.extend(level.iter().cloned());
+    }
+    dirs.sort();
+
+    let mut rng = SplitMix64::new(spec.seed);
+    let mut objects = Vec::with_capacity(dirs.len() * spec.files_per_dir);
+    for dir in &dirs {
+        for index in 0..spec.files_per_dir {
+            let binary = rng.next_f64() < spec.binary_ratio;
+            let size = rng.size(&spec.sizes);
+            let (ext, data) = if binary {
+                ("bin", rng.binary(size))
+            } else {
+                ("txt", rng.text(size))
+            };
+            objects.push((format!("{dir}file-{index:03}.{ext}"), data, binary));
+        }
+    }
+    objects.sort_by(|a, b| a.0.cmp(&b.0));
+    objects
+}
+
+/// Write a reproducible tree of random objects to `store`.
+///
+/// Returns a manifest of the written objects for assertions.
+pub async fn seed_dataset(
+    store: &impl ObjStore,
+    spec: &DatasetSpec,
+) -> Result<DatasetManifest, ObjStoreError> {
+    let objects = generate_dataset(spec);
+    let entries = objects
+        .iter()
+        .map(|(key, data, binary)| DatasetEntry {
+            key: key.clone(),
+            size: data.len() as u64,
+            binary: *binary,
+            hash_sha256: sha2::Sha256::digest(data).into(),
+        })
+        .collect();
+
+    futures::stream::iter(objects)
+        .map(|(key, data, _)| async move { store.put(&key).bytes(data).await.map(|_| ()) })
+        .buffer_unordered(SEED_CONCURRENCY)
+        .try_collect::<()>()
+        .await?;
+
+    Ok(DatasetManifest { entries })
+}
+
+/// Small deterministic generator, so datasets are stable across versions
+/// without depending on an external crate.
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn new(seed: u64) -> Self {
+        Self(seed)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// Uniform value in `min..=max`.
+    fn range(&mut self, min: usize, max: usize) -> usize {
+        let (min, max) = (min.min(max), min.max(max));
+        let span = (max - min) as u64 + 1;
+        min + (self.next_u64() % span) as usize
+    }
+
+    fn size(&mut self, sizes: &SizeDistribution) -> usize {
+        match *sizes {
+            SizeDistribution::Fixed(size) => size,
+            SizeDistribution::Uniform { min, max } => self.range(min, max),
+            SizeDistribution::LogUniform { min, max } => {
+                let (min, max) = (min.min(max), min.max(max));
+                let low = (min as f64 + 1.0).ln();
+                let high = (max as f64 + 1.0).ln();
+                let size = (low + self.next_f64() * (high - low)).exp() - 1.0;
+                (size.round() as usize).clamp(min, max)
+            }
+        }
+    }
+
+    fn binary(&mut self, size: usize) -> Bytes {
+        let mut data = Vec::with_capacity(size + 8);
+        while data.len() < size {
+            data.extend_from_slice(&self.next_u64().to_le_bytes());
+        }
+        data.truncate(size);
+        data.into()
+    }
+
+    fn text(&mut self, size: usize) -> Bytes {
+        const WORDS: &[&str] = &[
+            "alpha", "bravo", "charlie", "delta", "echo", "foxtrot", "golf", "hotel", "india",
+            "juliett", "kilo", "lima", "mike", "november", "oscar", "papa",
+        ];
+        let mut text = String::with_capacity(size + 16);
+        while text.len() < size {
+            let word = WORDS[self.range(0, WORDS.len() - 1)];
+            text.push_str(word);
+            text.push(if self.range(0, 9) == 0 { '\n' } else { ' ' });
+        }
+        text.truncate(size);
+        text.into()
+    }
+}
@@ -3,11 +3,18 @@
 //! Allows for unified testing to make sure all implementations conform to the
 //! same behavior.
 
+mod dataset;
+
+pub use self::dataset::{
+    DatasetEntry, DatasetManifest, DatasetSpec, SizeDistribution, generate_dataset, seed_dataset,
+};
+
 use bytes::{Bytes, BytesMut};
 use futures::{StreamExt, TryStreamExt};
 use objstore::{
-    DataSource, ListArgs, ObjStore, ObjStoreError, ObjStoreExt, ObjectMeta, Put, SizedValueStream,
-    ValueStream,
+    Clock as _, Conditions, DataSource, DownloadUrlArgs, EventStream, GetManyOptions, KeyPath,
+    ListArgs, ManualClock, ObjStore, ObjStoreError, ObjStoreExt, ObjectEvent, ObjectMeta, Put,
+    SizedValueStream, Usage, ValueStream,
 };
 use pretty_assertions::assert_eq;
 use sha2::Digest as _;
@@ -24,6 +31,17 @@ pub async fn test_objstore(store: &impl ObjStore) {
     tracing::info!("running ObjStore::healthcheck()");
     store.healthcheck().await.expect("health check");
 
+    tracing::info!("running ObjStore::healthcheck_report()");
+    let report = store
+        .healthcheck_report()
+        .await
+        .expect("health check report");
+    assert!(
+        report.warnings.is_empty(),
+        "unexpected healthcheck warnings: {:?}",
+        report.warnings
+    );
+
     let prefix = Uuid::new_v4().to_string();
     store.delete_prefix(&prefix).await.unwrap();
 
@@ -42,6 +60,54 @@ pub async fn test_objstore(store: &impl ObjStore) {
     test_put_with_mime_type(store, &prefix).await;
     tracing::info!("finished test_put_with_mime_type()");
 
+    tracing::info!("running test_put_with_content_headers()");
+    test_put_with_content_headers(store, &prefix).await;
+    tracing::info!("finished test_put_with_content_headers()");
+
+    tracing::info!("running test_copy_replace_metadata()");
+    test_copy_replace_metadata(store, &prefix).await;
+    tracing::info!("finished test_copy_replace_metadata()");
+
+    tracing::info!("running test_usage()");
+    test_usage(store, &prefix).await;
+    tracing::info!("finished test_usage()");
+
+    tracing::info!("running test_append()");
+    test_append(store, &prefix).await;
+    tracing::info!("finished test_append()");
+
+    tracing::info!("running test_list_filters()");
+    test_list_filters(store, &prefix).await;
+    tracing::info!("finished test_list_filters()");
+
+    tracing::info!("running test_get_range()");
+    test_get_range(store, &prefix).await;
+    tracing::info!("finished test_get_range()");
+
+    tracing::info!("running test_download_url_overrides()");
+    test_download_url_overrides(store, &prefix).await;
+    tracing::info!("finished test_download_url_overrides()");
+
+    tracing::info!("running test_get_many()");
+    test_get_many(store, &prefix).await;
+    tracing::info!("finished test_get_many()");
+
+    tracing::info!("running test_watch()");
+    test_watch(store, &prefix).await;
+    tracing::info!("finished test_watch()");
+
+    tracing::info!("running test_key_normalization()");
+    test_key_normalization(store, &prefix).await;
+    tracing::info!("finished test_key_normalization()");
+
+    tracing::info!("running test_etag_semantics()");
+    test_etag_semantics(store, &prefix).await;
+    tracing::info!("finished test_etag_semantics()");
+
+    tracing::info!("running test_put_result()");
+    test_put_result(store, &prefix).await;
+    tracing::info!("finished test_put_result()");
+
     let keys = store.list_all_keys(&prefix).await.unwrap();
     assert!(keys.is_empty());
 
@@ -61,6 +127,158 @@ pub async fn test_objstore(store: &impl ObjStore) {
     assert_eq!(items.len(), 0);
 }
 
+/// Etags must be stable across reads, change when the content changes, and
+/// work with `if_match` on stores that support conditions.
+///
+/// See [`ObjectMeta::etag`].
+async fn test_etag_semantics(store: &impl ObjStore, prefix: &str) {
+    let key = format!("{prefix}/etag-{}", Uuid::new_v4());
+    let put = store.put(&key).text("v1").await.unwrap();
+    let Some(etag) = put.etag.clone() else {
+        // Stores without etags must not report them anywhere.
+        let meta = store.meta(&key).await.unwrap().unwrap();
+        assert_eq!(
+            meta.etag, None,
+            "etag missing from put but returned by meta"
+        );
+        store.delete(&key).await.unwrap();
+        return;
+    };
+
+    let meta = store.meta(&key).await.unwrap().unwrap();
+    assert_eq!(meta.etag.as_deref(), Some(etag.as_str()), "meta etag");
+    let again = store.meta(&key).await.unwrap().unwrap();
+    assert_eq!(again.etag, meta.etag, "etag must be stable across reads");
+    let (_, with_meta) = store.get_with_meta(&key).await.unwrap().unwrap();
+    assert_eq!(with_meta.etag, meta.etag, "get_with_meta etag");
+    let listed = store
+        .list(ListArgs::new().with_prefix(&key))
+        .await
+        .unwrap()
+        .items;
+    assert_eq!(listed.len(), 1);
+    if let Some(listed_etag) = &listed[0].etag {
+        assert_eq!(listed_etag, &etag, "listed etag");
+    }
+
+    let updated = store.put(&key).text("v2").await.unwrap();
+    let updated_etag = store.meta(&key).await.unwrap().unwrap().etag;
+    assert_eq!(updated.etag, updated_etag);
+    assert_ne!(
+        updated_etag.as_deref(),
+        Some(etag.as_str()),
+        "etag must change when the content changes"
+    );
+
+    if store.supports_conditions() {
+        let stale = store
+            .put(&key)
+            .conditions(Conditions::new().if_match_tags([etag.clone()]))
+            .text("v3")
+            .await;
+        match stale {
+            Err(ObjStoreError::PreconditionFailed { .. }) => {}
+            other => panic!("put with a stale if_match must fail, got {other:?}"),
+        }
+
+        let current = updated_etag.expect("updated object should have an etag");
+        store
+            .put(&key)
+            .conditions(Conditions::new().if_match_tags([current]))
+            .text("v3")
+            .await
+            .unwrap();
+        assert_eq!(store.get(&key).await.unwrap().unwrap(), "v3");
+    }
+
+    store.delete(&key).await.unwrap();
+}
+
+/// Puts replace existing objects and report whether they created one.
+async fn test_put_result(store: &impl ObjStore, prefix: &str) {
+    let key = format!("{prefix}/put-result-{}", Uuid::new_v4());
+    let first = store
+        .put(&key)
+        .send_with_result(Bytes::from_static(b"v1"))
+        .await
+        .unwrap();
+    assert!(first.created, "first put must create the object");
+    assert_eq!(first.meta.key, key);
+    assert_eq!(first.meta.size, Some(2));
+
+    let second = store
+        .put(&key)
+        .send_with_result(Bytes::from_static(b"v22"))
+        .await
+        .unwrap();
+    assert!(!second.created, "second put must replace the object");
+    assert_eq!(second.meta.size, Some(3));
+    assert_eq!(store.get(&key).await.unwrap().unwrap(), "v22");
+
+    store.delete(&key).await.unwrap();
+    let recreated = store
+        .put(&key)
+        .send_with_result(Bytes::from_static(b"v3"))
+        .await
+        .unwrap();
+    assert!(recreated.created, "put after delete must create the object");
+
+    store.delete(&key).await.unwrap();
+}
+
+/// Unusual keys must be rejected on write, see [`objstore::validate_key`].
+async fn test_key_normalization(store: &impl ObjStore, prefix: &str) {
+    let source = format!("{prefix}/normalization-source");
+    store.put(&source).text("value").await.unwrap();
+
+    let invalid = [
+        String::new(),
+        format!("{prefix}/"),
+        format!("{prefix}/dir/"),
+        format!("{prefix}//a"),
+        format!("{prefix}/a//b"),
+        format!("{prefix}/./a"),
+        format!("{prefix}/a/../b"),
+    ];
+    for key in &invalid {
+        let err = store
+            .put(key)
+            .text("value")
+            .await
+            .expect_err("put of an invalid key should fail");
+        assert!(
+            matches!(err, ObjStoreError::InvalidRequest { .. }),
+            "put '{key}': expected InvalidRequest, got {err:?}"
+        );
+
+        let err = store
+            .copy(&source, key)
+            .send()
+            .await
+            .expect_err("copy to an invalid key should fail");
+        assert!(
+            matches!(err, ObjStoreError::InvalidRequest { .. }),
+            "copy to '{key}': expected InvalidRequest, got {err:?}"
+        );
+
+        match store.append(key, Bytes::from_static(b"value")).await {
+            Err(ObjStoreError::InvalidRequest { .. } | ObjStoreError::Unsupported { .. }) => {}
+            other => panic!("append '{key}': expected InvalidRequest, got {other:?}"),
+        }
+    }
+
+    // Dots inside names are regular characters.
+    let dotted = format!("{prefix}/a/..b/.c");
+    store.put(&dotted).text("dots").await.unwrap();
+
+    let mut keys = store.list_all_keys(prefix).await.unwrap();
+    keys.sort();
+    assert_eq!(keys, vec![dotted.clone(), source.clone()]);
+
+    store.delete(&dotted).await.unwrap();
+    store.delete(&source).await.unwrap();
+}
+
 async fn test_error_variants(store: &impl ObjStore, prefix: &str) {
     let source = format!("{prefix}/missing-source-{}", Uuid::new_v4());
     let target = format!("{prefix}/missing-target-{}", Uuid::new_v4());
@@ -82,8 +300,9 @@ async fn test_copy_special_chars(store: &impl ObjStore, prefix: &str) {
     // in an x-amz-copy-source header: space, '#', '%', and a non-ASCII char.
     let unique = Uuid::new_v4().to_string();
     let special_segment = format!("space # % é {}", &unique[0..8]);
-    let key = format!("{}/{}", prefix, special_segment);
-    let dest = format!("{}/copy-{}", prefix, unique);
+    let dir = KeyPath::parse(prefix).unwrap();
+    let key = dir.join(&special_segment).unwrap().into_string();
+    let dest = dir.join(&format!("copy-{unique}")).unwrap().into_string();
 
     let payload = Bytes::from_static(b"copy-special-payload");
 
@@ -153,6 +372,330 @@ async fn test_put_with_mime_type(store: &impl ObjStore, prefix: &str) {
     store.delete(&key).await.unwrap();
 }
 
+async fn test_put_with_content_headers(store: &impl ObjStore, prefix: &str) {
+    let key = format!("{prefix}/content-headers-{}", Uuid::new_v4());
+    let value = Bytes::from_static(b"<html></html>");
+
+    let mut put = Put::new(&key, DataSource::Data(value.clone()));
+    put.cache_control = Some("public, max-age=300".to_string());
+    put.content_disposition = Some("attachment; filename=\"index.html\"".to_string());
+    put.content_encoding = Some("identity".to_string());
+    let put_meta = store.send_put(put).await.unwrap();
+
+    let meta = store
+        .meta(&key)
+        .await
+        .unwrap()
+        .expect("content header test object should exist");
+
+    // Not all backends persist these values, but if present they must match.
+    for meta in [&put_meta, &meta] {
+        if let Some(v) = &meta.cache_control {
+            assert_eq!(
+                v, "public, max-age=300",
+                "cache_control should be preserved"
+            );
+        }
+        if let Some(v) = &meta.content_disposition {
+            assert_eq!(
+                v, "attachment; filename=\"index.html\"",
+                "content_disposition should be preserved"
+            );
+        }
+        if let Some(v) = &meta.content_encoding {
+            assert_eq!(v, "identity", "content_encoding should be preserved");
+        }
+    }
+
+    let loaded = store.get(&key).await.unwrap().unwrap();
+    assert_eq!(loaded, value, "data should be stored as-is");
+
+    store.delete(&key).await.unwrap();
+}
+
+async fn test_copy_replace_metadata(store: &impl ObjStore, prefix: &str) {
+    let source = format!("{prefix}/copy-meta-{}/source.txt", Uuid::new_v4());
+    let target = format!("{source}.json");
+
+    let mut put = Put::new(&source, DataSource::Data(Bytes::from_static(b"{}")));
+    put.mime_type = Some("text/plain".to_string());
+    put.cache_control = Some("no-cache".to_string());
+    store.send_put(put).await.unwrap();
+
+    let copy_meta = store
+        .copy(&source, &target)
+        .mime_type("application/json")
+        .send()
+        .await
+        .unwrap();
+    let meta = store
+        .meta(&target)
+        .await
+        .unwrap()
+        .expect("copied object should exist");
+
+    // Not all backends persist these values, but if present they must be replaced.
+    for meta in [&copy_meta, &meta] {
+        if let Some(v) = &meta.mime_type {
+            assert_eq!(v, "application/json", "mime_type should be replaced");
+        }
+        if let Some(v) = &meta.cache_control {
+            panic!("cache_control should be cleared by a metadata replace, got {v}");
+        }
+    }
+
+    let loaded = store.get(&target).await.unwrap().unwrap();
+    assert_eq!(loaded, Bytes::from_static(b"{}"));
+
+    store.delete(&source).await.unwrap();
+    store.delete(&target).await.unwrap();
+}
+
+async fn test_usage(store: &impl ObjStore, prefix: &str) {
+    let usage_prefix = format!("{prefix}/usage-{}/", Uuid::new_v4());
+
+    let usage = store.usage(&usage_prefix).await.unwrap();
+    assert_eq!(
+        usage,
+        Usage::default(),
+        "usage of empty prefix should be zero"
+    );
+
+    let values = ["a", "bb", "cccc"];
+    for (index, value) in values.iter().enumerate() {
+        store
+            .put(&format!("{usage_prefix}{index}"))
+            .text(*value)
+            .await
+            .unwrap();
+    }
+
+    let usage = store.usage(&usage_prefix).await.unwrap();
+    assert_eq!(usage.objects, 3, "usage should count all objects");
+    assert_eq!(usage.bytes, 7, "usage should sum object sizes");
+
+    store.delete_prefix(&usage_prefix).await.unwrap();
+}
+
+async fn test_append(store: &impl ObjStore, prefix: &str) {
+    let key = format!("{prefix}/append-{}.log", Uuid::new_v4());
+
+    let meta = store.append(&key, Bytes::from("a\n")).await.unwrap();
+    assert_eq!(meta.size, Some(2), "append should create a missing object");
+
+    let meta = store.append(&key, Bytes::from("bc\n")).await.unwrap();
+    assert_eq!(meta.size, Some(5), "append should extend the object");
+    assert_eq!(store.get(&key).await.unwrap().unwrap(), "a\nbc\n");
+
+    store.delete(&key).await.unwrap();
+}
+
+async fn test_get_many(store: &impl ObjStore, prefix: &str) {
+    let many_prefix = format!("{prefix}/many-{}/", Uuid::new_v4());
+
+    let mut keys = Vec::new();
+    for index in 0..5 {
+        let key = format!("{many_prefix}{index}");
+        store.put(&key).text(index.to_string()).await.unwrap();
+        keys.push(key);
+    }
+    let missing = format!("{many_prefix}missing");
+    keys.push(missing.clone());
+
+    let results = store
+        .get_many(keys.clone(), GetManyOptions::new().with_concurrency(2))
+        .collect::<Vec<_>>()
+        .await;
+    assert_eq!(results.len(), keys.len(), "every key should yield a result");
+
+    let results = results
+        .into_iter()
+        .map(|(key, res)| (key, res.unwrap()))
+        .collect::<std::collections::HashMap<_, _>>();
+    for (index, key) in keys.iter().take(5).enumerate() {
+        assert_eq!(
+            results[key].as_deref(),
+            Some(index.to_string().as_bytes()),
+            "unexpected value for {key}"
+        );
+    }
+    assert_eq!(results[&missing], None, "missing key should yield None");
+
+    store.delete_prefix(&many_prefix).await.unwrap();
+}
+
+async fn test_watch(store: &impl ObjStore, prefix: &str) {
+    let watch_prefix = format!("{prefix}/watch-{}/", Uuid::new_v4());
+
+    // Create the prefix before watching, so backends that watch directories
+    // are already tracking it.
+    let seed_key = format!("{watch_prefix}seed");
+    store.put(&seed_key).text("seed").await.unwrap();
+
+    let mut events = match store.watch(&watch_prefix).await {
+        Ok(events) => events,
+        Err(ObjStoreError::Unsupported { .. }) => {
+            store.delete_prefix(&watch_prefix).await.unwrap();
+            return;
+        }
+        Err(err) => panic!("watch failed: {err}"),
+    };
+
+    // Backends may report additional events (e.g. a create followed by a
+    // modification), so wait for the first matching one.
+    async fn next_event(events: &mut EventStream, key: &str) -> ObjectEvent {
+        let wait = async {
+            loop {
+                let event = events
+                    .next()
+                    .await
+                    .expect("event stream ended")
+                    .expect("event stream failed");
+                if event.key() == key {
+                    return event;
+                }
+            }
+        };
+        tokio::time::timeout(std::time::Duration::from_secs(10), wait)
+            .await
+            .expect("timed out waiting for event")
+    }
+
+    let key = format!("{watch_prefix}item");
+    store.put(&key).text("value").await.unwrap();
+    let event = next_event(&mut events, &key).await;
+    assert!(
+        matches!(
+            event,
+            ObjectEvent::Created { .. } | ObjectEvent::Updated { .. }
+        ),
+        "expected a created event, got {event:?}"
+    );
+
+    store.delete(&key).await.unwrap();
+    loop {
+        if let ObjectEvent::Deleted { .. } = next_event(&mut events, &key).await {
+            break;
+        }
+    }
+
+    store.delete_prefix(&watch_prefix).await.unwrap();
+}
+
+async fn test_list_filters(store: &impl ObjStore, prefix: &str) {
+    let filter_prefix = format!("{prefix}/filters-{}/", Uuid::new_v4());
+    let small = format!("{filter_prefix}small");
+    let large = format!("{filter_prefix}large");
+    store.put(&small).text("s").await.unwrap();
+    store.put(&large).bytes(vec![b'l'; 100]).await.unwrap();
+
+    async fn list_filtered(store: &impl ObjStore, args: ListArgs) -> Vec<String> {
+        let page = store.list(args).await.unwrap();
+        let mut keys = page
+            .items
+            .into_iter()
+            .map(|item| item.key)
+            .collect::<Vec<_>>();
+        keys.sort();
+        keys
+    }
+
+    let args = ListArgs::new().with_prefix(&filter_prefix);
+    let day = time::Duration::days(1);
+    let now = OffsetDateTime::now_utc();
+
+    let keys = list_filtered(store, args.clone().with_min_size(10)).await;
+    assert_eq!(
+        keys,
+        vec![large.clone()],
+        "min_size should exclude small objects"
+    );
+
+    let keys = list_filtered(store, args.clone().with_max_size(10)).await;
+    assert_eq!(
+        keys,
+        vec![small.clone()],
+        "max_size should exclude large objects"
+    );
+
+    let keys = list_filtered(store, args.clone().with_modified_after(now - day)).await;
+    assert_eq!(keys, vec![large.clone(), small.clone()]);
+
+    let keys = list_filtered(store, args.clone().with_modified_before(now - day)).await;
+    assert!(
+        keys.is_empty(),
+        "modified_before should exclude new objects"
+    );
+
+    store.delete_prefix(&filter_prefix).await.unwrap();
+}
+
+/// Download URLs must carry the requested response overrides.
+///
+/// Stores without download URL support return `None` and are skipped.
+async fn test_download_url_overrides(store: &impl ObjStore, prefix: &str) {
+    let key = format!("{prefix}/download-url-{}", Uuid::new_v4());
+    store.put(&key).text("hello").await.unwrap();
+
+    let mut args = DownloadUrlArgs::new(&key, std::time::Duration::from_secs(60));
+    args.response_content_type = Some("text/x-override".to_string());
+    args.response_content_disposition = Some("attachment; filename=\"override.txt\"".to_string());
+    args.response_cache_control = Some("no-store".to_string());
+
+    if let Some(url) = store.generate_download_url(args).await.unwrap() {
+        let values = url
+            .query_pairs()
+            .map(|(_, value)| value.into_owned())
+            .collect::<Vec<_>>();
+        for expected in [
+            "text/x-override",
+            "attachment; filename=\"override.txt\"",
+            "no-store",
+        ] {
+            assert!(
+                values.iter().any(|value| value == expected),
+                "download URL should include the override '{expected}': {url}"
+            );
+        }
+    }
+
+    store.delete(&key).await.unwrap();
+}
+
+async fn test_get_range(store: &impl ObjStore, prefix: &str) {
+    let key = format!("{prefix}/range-{}", Uuid::new_v4());
+    let missing = format!("{prefix}/range-missing-{}", Uuid::new_v4());
+    let value = Bytes::from_static(b"0123456789");
+
+    store.put(&key).bytes(value.clone()).await.unwrap();
+
+    match store.get_range(&key, 2..5).await {
+        Err(ObjStoreError::Unsupported { .. }) => {
+            // Range reads are optional.
+            tracing::debug!("backend does not support range reads");
+            store.delete(&key).await.unwrap();
+            return;
+        }
+        other => {
+            let loaded = other.unwrap().expect("range of existing key should exist");
+            assert_eq!(loaded, value.slice(2..5));
+        }
+    }
+
+    let tail = store.get_range(&key, 8..100).await.unwrap().unwrap();
+    assert_eq!(tail, value.slice(8..), "range end should be clamped");
+
+    let after_end = store.get_range(&key, 20..30).await.unwrap().unwrap();
+    assert!(after_end.is_empty(), "range after the end should be empty");
+
+    let empty = store.get_range(&key, 3..3).await.unwrap().unwrap();
+    assert!(empty.is_empty(), "empty range should be empty");
+
+    assert!(store.get_range(&missing, 0..5).await.unwrap().is_none());
+
+    store.delete(&key).await.unwrap();
+}
+
 /// Test storing an empty stream.
 ///
 /// This is exposed separately from the shared suite because not all store
@@ -180,6 +723,95 @@ pub async fn test_empty_stream_put(store: &impl ObjStore, prefix: &str) {
     store.delete(&key).await.unwrap();
 }
 
+/// Test that presigned download URLs can actually be fetched.
+///
+/// Fetches the URL over HTTP and checks the status, the body and the
+/// response override headers. Signing bugs usually only show up here, since
+/// the URL itself always parses fine.
+///
+/// Stores that do not generate download URLs pass trivially.
+#[cfg(feature = "http-fetch")]
+pub async fn test_download_url_fetch(store: &impl ObjStore, prefix: &str) {
+    let key = format!("{prefix}/download-url-fetch {}.txt", Uuid::new_v4());
+    let value = "hello download";
+    store.put(&key).text(value).await.unwrap();
+
+    let mut args = DownloadUrlArgs::new(&key, std::time::Duration::from_secs(60));
+    args.response_content_type = Some("text/x-override".to_string());
+    args.response_content_disposition = Some("attachment; filename=\"override.txt\"".to_string());
+    args.response_cache_control = Some("no-store".to_string());
+
+    if let Some(url) = store.generate_download_url(args).await.unwrap() {
+        let response = reqwest::get(url.clone())
+            .await
+            .unwrap_or_else(|err| panic!("failed to fetch download URL {url}: {err}"));
+        assert_eq!(
+            response.status(),
+            reqwest::StatusCode::OK,
+            "download URL {url} returned an error"
+        );
+
+        let headers = response.headers().clone();
+        for (name, expected) in [
+            (reqwest::header::CONTENT_TYPE, "text/x-override"),
+            (
+                reqwest::header::CONTENT_DISPOSITION,
+                "attachment; filename=\"override.txt\"",
+            ),
+            (reqwest::header::CACHE_CONTROL, "no-store"),
+        ] {
+            assert_eq!(
+                headers.get(&name).and_then(|value| value.to_str().ok()),
+                Some(expected),
+                "download response header '{name}'"
+            );
+        }
+
+        let body = response.bytes().await.unwrap();
+        assert_eq!(body, value.as_bytes());
+    }
+
+    store.delete(&key).await.unwrap();
+}
+
+/// Test that timestamps come exactly from `clock`.
+///
+/// The shared suite only matches timestamps approximately, because most
+/// backends use the server time. Stores that take a [`objstore::Clock`],
+/// like the memory store, can be checked exactly with this test.
+pub async fn test_clock_timestamps(store: &impl ObjStore, clock: &ManualClock, prefix: &str) {
+    let dir = KeyPath::parse(prefix).unwrap();
+    let key = dir.join("clock").unwrap().into_string();
+    let copy = dir.join("clock-copy").unwrap().into_string();
+
+    let start = OffsetDateTime::from_unix_timestamp(1_700_000_000).unwrap();
+    clock.set(start);
+    let meta = store.put(&key).text("a").await.unwrap();
+    assert_eq!(meta.created_at, Some(start));
+    assert_eq!(meta.updated_at, Some(start));
+    assert_eq!(store.meta(&key).await.unwrap(), Some(meta.clone()));
+
+    clock.advance(std::time::Duration::from_secs(60));
+    let appended = store.append(&key, Bytes::from_static(b"b")).await.unwrap();
+    assert_eq!(appended.created_at, Some(start));
+    assert_eq!(appended.updated_at, Some(clock.now()));
+
+    clock.advance(std::time::Duration::from_secs(60));
+    let copied = store.copy(&key, &copy).send().await.unwrap();
+    assert_eq!(copied.created_at, Some(clock.now()));
+    assert_eq!(copied.updated_at, Some(clock.now()));
+
+    let items = store
+        .list(ListArgs::new().with_prefix(dir.as_prefix()))
+        .await
+        .unwrap()
+        .items;
+    assert_eq!(items, vec![appended, copied]);
+
+    store.delete(&key).await.unwrap();
+    store.delete(&copy).await.unwrap();
+}
+
 async fn test_full_flow(store: &impl ObjStore, prefix: &str) {
     let keys = store.list_all_keys(prefix).await.unwrap();
     assert!(keys.is_empty());
@@ -455,9 +1087,14 @@ where
     D: ObjStore + Sized,
 {
     let nested_prefix = Uuid::new_v4().to_string();
-    let prefix = format!("{base_prefix}/{nested_prefix}");
+    let dir = KeyPath::parse(base_prefix)
+        .unwrap()
+        .join(&nested_prefix)
+        .unwrap();
     let key_name = Uuid::new_v4().to_string();
-    let key = format!("{prefix}/{key_name}");
+    let key = dir.join(&key_name).unwrap().into_string();
+    let dest = dir.join(&format!("{key_name}_copy")).unwrap().into_string();
+    let prefix = dir.into_string();
 
     // Key does not exist.
     {
@@ -470,6 +1107,9 @@ where
 
         let m0 = store.meta(&key).await.unwrap();
         assert!(m0.is_none(), "meta should not exist before put");
+
+        let e0 = store.exists(&key).await.unwrap();
+        assert!(!e0, "exists should be false before put");
     }
 
     // List with prefix should be empty.
@@ -493,12 +1133,16 @@ where
             "list with prefix should contain just the expected key"
         );
 
+        assert!(
+            store.exists(&key).await.unwrap(),
+            "exists should be true after put"
+        );
+
         expect_key(store, &key, &value, expected_meta.clone()).await;
     }
 
     // Copy the key and verify the copy exists.
     {
-        let dest = format!("{prefix}/{key_name}_copy");
         store.copy(&key, &dest).send().await.unwrap();
         let value_copy = store.get(&dest).await.unwrap().unwrap();
         let expected_meta = new_keymeta(&dest, &value_copy);
@@ -509,7 +1153,6 @@ where
     {
         // Remove both original and copied keys.
         store.delete(&key).await.unwrap();
-        let dest = format!("{prefix}/{key_name}_copy");
         store.delete(&dest).await.unwrap();
 
         let keys = store.list_all_keys(&prefix).await.unwrap();
@@ -525,6 +1168,9 @@ where
         let m2 = store.meta(&key).await.unwrap();
         assert!(m2.is_none(), "meta should not exist after delete");
 
+        let e2 = store.exists(&key).await.unwrap();
+        assert!(!e2, "exists should be false after delete");
+
         let v3 = store.get_with_meta(&key).await.unwrap();
         assert_eq!(v3, None, "get_with_meta should return None after delete");
     }
@@ -590,3 +1236,27 @@ fn approximate_meta_match(a: &ObjectMeta, b: &ObjectMeta, msg: &str) {
 
     // todo: extra handling?
 }
+
+/// Assert that no credential material leaks through [`ObjStore::safe_uri`]
+/// or the `Debug` output of a store.
+///
+/// `secrets` are the credentials the store was configured with.
+pub fn assert_no_credentials(store: &impl ObjStore, secrets: &[&str]) {
+    let safe_uri = store.safe_uri();
+    assert!(
+        safe_uri.username().is_empty() && safe_uri.password().is_none(),
+        "safe_uri must not contain user info: {safe_uri}"
+    );
+
+    let debug = format!("{store:?}");
+    for secret in secrets {
+        assert!(
+            !safe_uri.as_str().contains(secret),
+            "safe_uri leaks credential material: {safe_uri}"
+        );
+        assert!(
+            !debug.contains(secret),
+            "Debug output leaks credential material: {debug}"
+        );
+    }
+}
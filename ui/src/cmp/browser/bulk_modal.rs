@@ -0,0 +1,209 @@
+use dioxus::prelude::*;
+use dioxus_bulma::{Modal, Notification};
+use objstore::DynObjStore;
+
+use crate::{
+    cmp::{object::helpers::human_size, util::loader::Spinner},
+    store::{
+        archive_file_name, default_download_dir, delete_objects, download_objects_zip,
+        jobs::{use_jobs, JobKind, JobOutput, JobSpec, JobUnit},
+        resolve_sources, TransferSource,
+    },
+};
+
+/// An action applied to all selected objects and prefixes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BulkAction {
+    Delete,
+    Download,
+}
+
+/// Confirmation for a bulk action, showing the number and total size of the
+/// affected objects.
+///
+/// Selected prefixes are expanded to all objects below them. The action runs
+/// as a background job.
+#[component]
+pub fn BulkActionModal(
+    store: ReadOnlySignal<DynObjStore>,
+    connection: ReadOnlySignal<String>,
+    /// Current browser path, archive entries are named relative to it.
+    base_path: ReadOnlySignal<String>,
+    action: BulkAction,
+    sources: ReadOnlySignal<Vec<TransferSource>>,
+    on_complete: EventHandler<()>,
+    on_cancel: EventHandler<()>,
+) -> Element {
+    let jobs = use_jobs();
+    let objects = use_resource(move || async move {
+        resolve_sources(&store.read(), &sources.read())
+            .await
+            .map_err(|err| format!("{err:#}"))
+    });
+    let mut local_path = use_signal(|| {
+        let filename = archive_file_name(&base_path.read_unchecked());
+        default_download_dir()
+            .map(|dir| dir.join(filename).display().to_string())
+            .unwrap_or_default()
+    });
+
+    let confirm = move |_| {
+        let Some(Ok(objects)) = objects.peek().clone() else {
+            return;
+        };
+        let store = store.peek().clone();
+        let count = objects.len() as u64;
+        let bytes = objects.iter().filter_map(|meta| meta.size).sum::<u64>();
+        let title = match sources.peek().as_slice() {
+            [source] => source.name().to_string(),
+            sources => format!("{} selected items", sources.len()),
+        };
+
+        match action {
+            BulkAction::Delete => {
+                let keys = objects.into_iter().map(|meta| meta.key).collect::<Vec<_>>();
+                let spec = JobSpec {
+                    kind: JobKind::Delete,
+                    connection: connection.peek().clone(),
+                    title,
+                    unit: JobUnit::Objects,
+                    total: Some(count),
+                };
+                jobs.submit(spec, move |job| {
+                    let store = store.clone();
+                    let keys = keys.clone();
+                    async move {
+                        delete_objects(&store, keys, move |n| job.progress(n)).await?;
+                        Ok(JobOutput::Changed)
+                    }
+                });
+            }
+            BulkAction::Download => {
+                let base_path = base_path.peek().clone();
+                let path = local_path.peek().clone();
+                let spec = JobSpec {
+                    kind: JobKind::Download,
+                    connection: connection.peek().clone(),
+                    title: format!("{title} as zip"),
+                    unit: JobUnit::Bytes,
+                    total: Some(bytes),
+                };
+                jobs.submit(spec, move |job| {
+                    let store = store.clone();
+                    let objects = objects.clone();
+                    let base_path = base_path.clone();
+                    let path = path.clone();
+                    async move {
+                        download_objects_zip(&store, &objects, &base_path, &path, move |p| {
+                            job.set_total(Some(p.bytes_total));
+                            job.progress(p.bytes_done);
+                        })
+                        .await?;
+                        Ok(JobOutput::None)
+                    }
+                });
+            }
+        }
+        on_complete.call(());
+    };
+
+    let (title, confirm_label, confirm_class) = match action {
+        BulkAction::Delete => ("Delete selection", "Delete", "button is-danger"),
+        BulkAction::Download => ("Download selection as zip", "Download", "button is-primary"),
+    };
+    let ready = matches!(&*objects.read(), Some(Ok(objects)) if !objects.is_empty());
+
+    rsx! {
+        Modal {
+            children: rsx! {
+                div {
+                    class: "box",
+
+                    h2 {
+                        class: "title is-5",
+                        "{title}"
+                    }
+
+                    ul {
+                        class: "mb-4",
+                        max_height: "12rem",
+                        overflow_y: "auto",
+                        for source in sources.read().iter() {
+                            li {
+                                match source {
+                                    TransferSource::Object(key) => rsx! { "{key}" },
+                                    TransferSource::Prefix(prefix) => rsx! { "{prefix} (folder)" },
+                                }
+                            }
+                        }
+                    }
+
+                    match &*objects.read() {
+                        None => rsx! { Spinner {} },
+                        Some(Err(err)) => rsx! {
+                            Notification {
+                                color: dioxus_bulma::Color::Danger,
+                                "Failed to load the selected objects: {err}"
+                            }
+                        },
+                        Some(Ok(objects)) => {
+                            let size = human_size(objects.iter().filter_map(|meta| meta.size).sum());
+                            let count = objects.len();
+                            match action {
+                                BulkAction::Delete if count > 0 => rsx! {
+                                    Notification {
+                                        color: dioxus_bulma::Color::Warning,
+                                        span {
+                                            "Really delete ",
+                                            strong { "{count} object(s)" },
+                                            " with a total size of {size}?"
+                                        }
+                                    }
+                                },
+                                _ => rsx! {
+                                    p {
+                                        class: "mb-4",
+                                        strong { "{count} object(s)" },
+                                        ", {size} in total"
+                                    }
+                                },
+                            }
+                        }
+                    }
+
+                    if action == BulkAction::Download {
+                        div {
+                            class: "field",
+                            label { class: "label", "Output Path" }
+                            div {
+                                class: "control",
+                                input {
+                                    class: "input",
+                                    r#type: "text",
+                                    value: "{local_path}",
+                                    onchange: move |e| local_path.set(e.value())
+                                }
+                            }
+                        }
+                    }
+
+                    div {
+                        class: "buttons",
+                        button {
+                            class: "{confirm_class}",
+                            disabled: !ready,
+                            onclick: confirm,
+                            "{confirm_label}"
+                        }
+                        button {
+                            class: "button",
+                            onclick: move |_| on_cancel.call(()),
+                            "Cancel"
+                        }
+                    }
+                }
+            },
+            on_close: move |_| on_cancel.call(())
+        }
+    }
+}
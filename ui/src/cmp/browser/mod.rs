@@ -1,38 +1,70 @@
 mod browser_settings_form;
+mod bulk_modal;
+mod search;
 mod table;
 
-use std::sync::Arc;
+use std::{collections::HashSet, sync::Arc};
 
 use dioxus::{core::Task, prelude::*};
 use dioxus_bulma::Modal;
 use futures::StreamExt as _;
-use objstore::{ListArgs, ObjectMeta};
+use objstore::{ListArgs, ObjectMeta, ObjectOrPrefix};
 
 use crate::{
     cmp::{
         object::{
-            download_modal::DownloadModal, object_creator::ObjectCreator, viewer::ObjectViewer,
+            details_panel::ObjectDetailsPanel, download_modal::DownloadModal,
+            object_creator::ObjectCreator, prefix_download_modal::PrefixDownloadModal,
+            transfer_modal::TransferModal, viewer::ObjectViewer,
         },
         object_delete_modal::ObjectDeleteModal,
         util::loader::{LoadState, Spinner},
     },
     context::ActiveStore,
+    router::Route,
+    store::{
+        jobs::{use_jobs, JobOutput, JobStatus},
+        TransferSource,
+    },
 };
 
 use browser_settings_form::BrowserSettingsForm;
+use bulk_modal::{BulkAction, BulkActionModal};
+use search::PrefixSearch;
 use table::ObjectsTable;
 
 #[derive(Clone, Debug, PartialEq, Eq)]
 enum ModalView {
-    DeleteObject { meta: Arc<ObjectMeta> },
-    DownloadObject { meta: Arc<ObjectMeta> },
-    ViewObject { meta: Arc<ObjectMeta> },
-    CreateObject { base_path: String },
+    DeleteObject {
+        meta: Arc<ObjectMeta>,
+    },
+    DownloadObject {
+        meta: Arc<ObjectMeta>,
+    },
+    DownloadPrefix {
+        prefix: String,
+    },
+    Transfer {
+        sources: Vec<TransferSource>,
+    },
+    Bulk {
+        action: BulkAction,
+        sources: Vec<TransferSource>,
+    },
+    ViewObject {
+        meta: Arc<ObjectMeta>,
+    },
+    CreateObject {
+        base_path: String,
+    },
 }
 
 enum Msg {
     GotoPath(String),
     Download(Arc<ObjectMeta>),
+    DownloadPrefix { prefix: String },
+    Transfer(Vec<TransferSource>),
+    Bulk(BulkAction),
     DeleteObject(Arc<ObjectMeta>),
     ViewObject(Arc<ObjectMeta>),
     ObjectDeleted { key: String },
@@ -44,27 +76,68 @@ enum Msg {
 #[derive(Default)]
 struct Page {
     objects: Vec<Arc<ObjectMeta>>,
-    prefixes: Option<Vec<String>>,
+    prefixes: Vec<String>,
     next_cursor: Option<String>,
 }
 
+impl Page {
+    /// Add the entries of a loaded page.
+    fn extend(&mut self, entries: Vec<ObjectOrPrefix>) {
+        for entry in entries {
+            match entry {
+                ObjectOrPrefix::Object(meta) => self.objects.push(Arc::from(meta)),
+                ObjectOrPrefix::Prefix(prefix) => {
+                    if !self.prefixes.contains(&prefix) {
+                        self.prefixes.push(prefix);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// List arguments for the objects directly below `prefix`.
+fn prefix_args(prefix: String) -> ListArgs {
+    if prefix.is_empty() {
+        ListArgs::new()
+    } else {
+        ListArgs::new().with_prefix(prefix)
+    }
+}
+
 /// Object store browser.
+///
+/// The current path is kept in sync with the `prefix` of the route, so
+/// navigation updates the URL, and back/forward navigation or links to a
+/// prefix open that prefix.
 #[component]
-pub fn Browser(store: ActiveStore) -> Element {
+pub fn Browser(store: ActiveStore, prefix: ReadOnlySignal<String>) -> Element {
     let mut next_cursor = use_signal::<Option<String>>(|| None);
+    let navigator = use_navigator();
 
-    let mut path = use_signal(|| String::new());
+    let mut path = use_signal(|| prefix.peek().clone());
     let mut load_state = use_signal::<LoadState<()>>(|| LoadState::Idle);
+    // Loading of further pages, which keeps the current rows visible.
+    let mut loading_more = use_signal(|| false);
     let mut page = use_signal::<Page>(|| Page::default());
     let mut modal_view = use_signal::<Option<ModalView>>(|| None);
     let mut manual_pagination = use_signal(|| true);
     let mut pagination_size = use_signal(|| 250u64);
     let mut show_settings = use_signal(|| false);
+    let mut show_uploader = use_signal(|| false);
+    let mut details = use_signal::<Option<Arc<ObjectMeta>>>(|| None);
+    // Checked object keys and prefixes of the current path.
+    let mut selected = use_signal(HashSet::<String>::new);
+    let jobs = use_jobs();
+    // Finished jobs whose output was already applied to the listing.
+    let mut applied_jobs = use_signal::<Option<HashSet<u64>>>(|| None);
 
     let tx = use_coroutine::<Msg, _, _>({
         let store = store.store.clone();
+        let connection = store.config.config.name.clone();
         move |mut rx| {
             let store = store.clone();
+            let connection = connection.clone();
             async move {
                 let mut task: Option<Task> = None;
 
@@ -74,17 +147,30 @@ pub fn Browser(store: ActiveStore) -> Element {
                         let args = args.with_limit(pagination_size());
                         if let Some(t) = task.take() {
                             t.cancel();
+                            loading_more.set(false);
                         }
 
-                        path.set(args.prefix().map(|p| p.to_owned()).unwrap_or_default());
+                        let new_path = args.prefix().map(|p| p.to_owned()).unwrap_or_default();
+                        if !extend && *prefix.peek() != new_path {
+                            navigator.push(Route::Browser {
+                                store: connection.clone(),
+                                prefix: new_path.clone(),
+                            });
+                        }
+                        path.set(new_path);
 
                         let store = store.clone();
                         let f = spawn(async move {
                             let args = args.with_delimiter('/');
 
                             tracing::info!("Loading page with args: {args:?}");
-                            load_state.set(LoadState::Loading);
+                            if extend {
+                                loading_more.set(true);
+                            } else {
+                                load_state.set(LoadState::Loading);
+                            }
                             let res = store.list(args).await;
+                            loading_more.set(false);
                             match res {
                                 Ok(new_page) => {
                                     tracing::info!(
@@ -94,19 +180,19 @@ pub fn Browser(store: ActiveStore) -> Element {
                                     );
 
                                     next_cursor.set(new_page.next_cursor.clone());
-                                    let objects =
-                                        new_page.items.into_iter().map(|meta| Arc::new(meta));
+                                    let cursor = new_page.next_cursor.clone();
+                                    let entries = new_page.into_entries();
 
                                     if extend {
                                         let mut old_page = page.write_unchecked();
-                                        old_page.objects.extend(objects);
-                                        old_page.next_cursor = new_page.next_cursor;
+                                        old_page.extend(entries);
+                                        old_page.next_cursor = cursor;
                                     } else {
-                                        let new_page = Page {
-                                            objects: objects.collect(),
-                                            prefixes: new_page.prefixes,
-                                            next_cursor: new_page.next_cursor.clone(),
+                                        let mut new_page = Page {
+                                            next_cursor: cursor,
+                                            ..Page::default()
                                         };
+                                        new_page.extend(entries);
                                         page.set(new_page);
                                     }
 
@@ -122,8 +208,7 @@ pub fn Browser(store: ActiveStore) -> Element {
                     }
                 };
 
-                // TODO: respect URL path!
-                load(ListArgs::new().with_limit(pagination_size()), false);
+                load(prefix_args(prefix.peek().clone()), false);
 
                 while let Some(msg) = rx.next().await {
                     match msg {
@@ -133,19 +218,33 @@ pub fn Browser(store: ActiveStore) -> Element {
                         Msg::ObjectDeleted { key } => {
                             let mut page = page.write_unchecked();
                             page.objects.retain(|item| item.key != key);
+                            selected.write().remove(&key);
+                            if details.peek().as_ref().is_some_and(|meta| meta.key == key) {
+                                details.set(None);
+                            }
                         }
                         Msg::GotoPath(mut path) => {
-                            if !path.ends_with('/') {
+                            selected.write().clear();
+                            if !path.is_empty() && !path.ends_with('/') {
                                 path.push('/');
                             }
-                            let args = ListArgs::new()
-                                .with_prefix(path)
-                                .with_limit(pagination_size());
-                            load(args, false)
+                            load(prefix_args(path), false)
                         }
                         Msg::Download(meta) => {
                             modal_view.set(Some(ModalView::DownloadObject { meta }));
                         }
+                        Msg::DownloadPrefix { prefix } => {
+                            modal_view.set(Some(ModalView::DownloadPrefix { prefix }));
+                        }
+                        Msg::Transfer(sources) => {
+                            modal_view.set(Some(ModalView::Transfer { sources }));
+                        }
+                        Msg::Bulk(action) => {
+                            let sources = selected_sources(&selected.peek());
+                            if !sources.is_empty() {
+                                modal_view.set(Some(ModalView::Bulk { action, sources }));
+                            }
+                        }
                         Msg::ViewObject(meta) => {
                             modal_view.set(Some(ModalView::ViewObject { meta }));
                         }
@@ -169,13 +268,16 @@ pub fn Browser(store: ActiveStore) -> Element {
                             if key.starts_with(&path) {
                                 let rest = &key[path.len()..];
                                 if !rest.contains('/') {
-                                    page.objects.push(meta.clone());
-                                } else if let Some(prefixes) = page.prefixes.as_mut() {
+                                    match page.objects.iter_mut().find(|item| item.key == key) {
+                                        Some(existing) => *existing = meta.clone(),
+                                        None => page.objects.push(meta.clone()),
+                                    }
+                                } else {
                                     let next = rest.split_once('/').unwrap().0;
                                     let full = format!("{}{}/", path, next);
-                                    if !prefixes.contains(&full) {
-                                        prefixes.push(full);
-                                        prefixes.sort();
+                                    if !page.prefixes.contains(&full) {
+                                        page.prefixes.push(full);
+                                        page.prefixes.sort();
                                     }
                                 }
                             }
@@ -186,6 +288,52 @@ pub fn Browser(store: ActiveStore) -> Element {
         }
     });
 
+    // Follow route changes, like back/forward navigation.
+    use_effect(move || {
+        let prefix = prefix();
+        if *path.peek() != prefix {
+            tx.send(Msg::GotoPath(prefix));
+        }
+    });
+
+    // Apply the results of finished jobs on this connection to the listing.
+    use_effect({
+        let connection = store.config.config.name.clone();
+        move || {
+            let finished = jobs
+                .list()
+                .into_iter()
+                .filter(|job| job.connection == connection && job.status == JobStatus::Done)
+                .collect::<Vec<_>>();
+            let mut state = applied_jobs.write();
+            // Jobs that finished before the browser was opened are already
+            // reflected in the first listing.
+            let Some(applied) = state.as_mut() else {
+                *state = Some(finished.iter().map(|job| job.id).collect());
+                return;
+            };
+            for job in finished {
+                if !applied.insert(job.id) {
+                    continue;
+                }
+                match job.output {
+                    Some(JobOutput::Created(meta)) => {
+                        tx.send(Msg::ObjectCreated {
+                            meta: Arc::new(meta),
+                        });
+                    }
+                    Some(JobOutput::Deleted(key)) => {
+                        tx.send(Msg::ObjectDeleted { key });
+                    }
+                    Some(JobOutput::Changed) => {
+                        tx.send(Msg::GotoPath(path.peek().clone()));
+                    }
+                    Some(JobOutput::None) | None => {}
+                }
+            }
+        }
+    });
+
     let modal = use_memo(move || {
         let Some(view) = modal_view() else {
             return VNode::empty();
@@ -196,15 +344,10 @@ pub fn Browser(store: ActiveStore) -> Element {
                 rsx! {
                     ObjectDeleteModal {
                         store: store.store.clone(),
+                        connection: store.config.config.name.clone(),
                         object_key: meta.key.clone(),
-                        on_complete: {
-                            let key = meta.key.clone();
-                            move || {
-                                modal_view.set(None);
-                                tx.send(Msg::ObjectDeleted {
-                                    key: key.clone(),
-                                });
-                            }
+                        on_complete: move || {
+                            modal_view.set(None);
                         },
                         on_cancel: move || {
                             modal_view.set(None);
@@ -216,6 +359,7 @@ pub fn Browser(store: ActiveStore) -> Element {
                 rsx! {
                     DownloadModal {
                         store: store.store.clone(),
+                        connection: store.config.config.name.clone(),
                         object_meta: meta.clone(),
                         on_complete: move || {
                             modal_view.set(None);
@@ -226,6 +370,55 @@ pub fn Browser(store: ActiveStore) -> Element {
                     }
                 }
             }
+            ModalView::DownloadPrefix { prefix } => {
+                rsx! {
+                    PrefixDownloadModal {
+                        store: store.store.clone(),
+                        connection: store.config.config.name.clone(),
+                        prefix: prefix.clone(),
+                        on_complete: move || {
+                            modal_view.set(None);
+                        },
+                        on_cancel: move || {
+                            modal_view.set(None);
+                        },
+                    }
+                }
+            }
+            ModalView::Transfer { sources } => {
+                rsx! {
+                    TransferModal {
+                        store: store.store.clone(),
+                        connection: store.config.config.name.clone(),
+                        sources: sources.clone(),
+                        on_complete: move || {
+                            modal_view.set(None);
+                            selected.write().clear();
+                        },
+                        on_cancel: move || {
+                            modal_view.set(None);
+                        },
+                    }
+                }
+            }
+            ModalView::Bulk { action, sources } => {
+                rsx! {
+                    BulkActionModal {
+                        store: store.store.clone(),
+                        connection: store.config.config.name.clone(),
+                        base_path: path.read_unchecked().clone(),
+                        action,
+                        sources: sources.clone(),
+                        on_complete: move || {
+                            modal_view.set(None);
+                            selected.write().clear();
+                        },
+                        on_cancel: move || {
+                            modal_view.set(None);
+                        },
+                    }
+                }
+            }
             ModalView::ViewObject { meta } => {
                 rsx! {
                     Modal {
@@ -233,7 +426,11 @@ pub fn Browser(store: ActiveStore) -> Element {
                             modal_view.set(None);
                         },
                         ObjectViewer {
+                            store: store.store.clone(),
                             meta: meta.clone(),
+                            on_saved: move |meta| {
+                                tx.send(Msg::ObjectCreated { meta });
+                            },
                         }
                     }
                 }
@@ -343,6 +540,70 @@ pub fn Browser(store: ActiveStore) -> Element {
                 }
             }
 
+            button {
+                class: "button mb-2",
+                title: "Download folder",
+                aria_label: "Download folder",
+                onclick: {
+                    let prefix = path.read_unchecked().clone();
+                    move |_| {
+                        tx.send(Msg::DownloadPrefix { prefix: prefix.clone() });
+                    }
+                },
+                dioxus_free_icons::Icon {
+                    fill: "black",
+                    width: 20,
+                    height: 20,
+                    icon: dioxus_free_icons::icons::fa_solid_icons::FaFileZipper,
+                }
+            }
+
+            button {
+                class: "button mb-2",
+                title: "Copy or move folder",
+                aria_label: "Transfer folder",
+                onclick: {
+                    let prefix = path.read_unchecked().clone();
+                    move |_| {
+                        tx.send(Msg::Transfer(vec![TransferSource::Prefix(prefix.clone())]));
+                    }
+                },
+                dioxus_free_icons::Icon {
+                    fill: "black",
+                    width: 20,
+                    height: 20,
+                    icon: dioxus_free_icons::icons::fa_solid_icons::FaRightLeft,
+                }
+            }
+
+            if cfg!(feature = "desktop") {
+                button {
+                    class: if show_uploader() { "button mb-2 is-active" } else { "button mb-2" },
+                    title: "Upload Files",
+                    aria_label: "Upload Files",
+                    onclick: move |_| show_uploader.set(!show_uploader()),
+                    dioxus_free_icons::Icon {
+                        fill: "black",
+                        width: 20,
+                        height: 20,
+                        icon: dioxus_free_icons::icons::fa_solid_icons::FaUpload,
+                    }
+                }
+            }
+
+            Link {
+                to: Route::Usage { store: store.config.config.name.clone() },
+                class: "button mb-2",
+                title: "Usage statistics",
+                aria_label: "Usage statistics",
+                dioxus_free_icons::Icon {
+                    fill: "black",
+                    width: 20,
+                    height: 20,
+                    icon: dioxus_free_icons::icons::fa_solid_icons::FaChartPie,
+                }
+            }
+
             button {
                 class: if show_settings() { "button mb-2 is-active" } else { "button mb-2" },
                 title: "Settings",
@@ -359,6 +620,45 @@ pub fn Browser(store: ActiveStore) -> Element {
 
     };
 
+    let selection_bar = {
+        let count = selected.read().len();
+        rsx! {
+            if count > 0 {
+                div {
+                    class: "notification is-info is-light is-flex is-align-items-center py-3",
+
+                    span { class: "mr-4", "{count} selected" }
+
+                    div {
+                        class: "buttons mb-0",
+                        button {
+                            class: "button is-small is-danger mb-0",
+                            onclick: move |_| tx.send(Msg::Bulk(BulkAction::Delete)),
+                            "Delete"
+                        }
+                        button {
+                            class: "button is-small mb-0",
+                            onclick: move |_| tx.send(Msg::Bulk(BulkAction::Download)),
+                            "Download"
+                        }
+                        button {
+                            class: "button is-small mb-0",
+                            onclick: move |_| {
+                                tx.send(Msg::Transfer(selected_sources(&selected.peek())));
+                            },
+                            "Copy or move"
+                        }
+                        button {
+                            class: "button is-small is-text mb-0",
+                            onclick: move |_| selected.write().clear(),
+                            "Clear selection"
+                        }
+                    }
+                }
+            }
+        }
+    };
+
     let contents = {
         match &*load_state.read() {
             LoadState::Loading => {
@@ -373,30 +673,46 @@ pub fn Browser(store: ActiveStore) -> Element {
                 let page_data = page.read();
 
                 rsx! {
-                    if let Some(prefixes) = page_data.prefixes.as_ref().filter(|p| !p.is_empty()) {
+                    if !page_data.prefixes.is_empty() {
                         div {
-                            for prefix in prefixes {
-                                button {
-                                    class: "button mb-1",
-                                    display: "block",
-                                    onclick: {
-                                        let prefix = prefix.clone();
-                                        move |_| {
-                                            tx.send(Msg::GotoPath(prefix.clone()));
+                            for prefix in page_data.prefixes.iter() {
+                                div {
+                                    class: "is-flex is-align-items-center mb-1",
+                                    input {
+                                        class: "mr-2",
+                                        r#type: "checkbox",
+                                        checked: selected.read().contains(prefix),
+                                        onchange: {
+                                            let prefix = prefix.clone();
+                                            move |e: Event<FormData>| {
+                                                if e.checked() {
+                                                    selected.write().insert(prefix.clone());
+                                                } else {
+                                                    selected.write().remove(&prefix);
+                                                }
+                                            }
+                                        },
+                                    }
+                                    button {
+                                        class: "button",
+                                        onclick: {
+                                            let prefix = prefix.clone();
+                                            move |_| {
+                                                tx.send(Msg::GotoPath(prefix.clone()));
+                                            }
+                                        },
+
+                                        {
+                                            let prefix = prefix.trim_end_matches('/');
+                                            let name = if let Some((_, name)) = prefix.rsplit_once('/') {
+                                                name
+                                            } else {
+                                                prefix
+                                            };
+                                            name.to_string()
                                         }
-                                    },
-
-                                    {
-                                        let prefix = prefix.trim_end_matches('/');
-                                        let name = if let Some((_, name)) = prefix.rsplit_once('/') {
-                                            name
-                                        } else {
-                                            prefix
-                                        };
-                                        name.to_string()
                                     }
                                 }
-
                             }
                         }
 
@@ -411,7 +727,13 @@ pub fn Browser(store: ActiveStore) -> Element {
                     } else {
                         ObjectsTable {
                             page,
+                            selected,
                             now: now,
+                            auto_load: !manual_pagination(),
+                            loading_more: loading_more(),
+                            on_load_more: move |_| {
+                                tx.send(Msg::LoadMore);
+                            },
                             on_view: move |item| {
                                 tx.send(Msg::ViewObject(item));
                             },
@@ -421,6 +743,12 @@ pub fn Browser(store: ActiveStore) -> Element {
                             on_delete: move |item| {
                                 tx.send(Msg::DeleteObject(item));
                             },
+                            on_details: move |item| {
+                                details.set(Some(item));
+                            },
+                            on_transfer: move |item: Arc<ObjectMeta>| {
+                                tx.send(Msg::Transfer(vec![TransferSource::Object(item.key.clone())]));
+                            },
                         }
                     }
                 }
@@ -436,6 +764,22 @@ pub fn Browser(store: ActiveStore) -> Element {
         }
     };
 
+    #[cfg(feature = "desktop")]
+    let uploader = rsx! {
+        if show_uploader() {
+            crate::cmp::object::uploader::Uploader {
+                store: store.store.clone(),
+                connection: store.config.config.name.clone(),
+                base_path: path(),
+                on_close: move |_| {
+                    show_uploader.set(false);
+                },
+            }
+        }
+    };
+    #[cfg(not(feature = "desktop"))]
+    let uploader = VNode::empty();
+
     rsx! {
         div {
             h1 {
@@ -451,6 +795,20 @@ pub fn Browser(store: ActiveStore) -> Element {
 
             {action_bar}
 
+            {selection_bar}
+
+            PrefixSearch {
+                key: "{path}",
+                store: store.store.clone(),
+                prefix: path(),
+                page_size: pagination_size(),
+                on_view: move |item| {
+                    tx.send(Msg::ViewObject(item));
+                },
+            }
+
+            {uploader}
+
             if show_settings() {
                 div { class: "box",
                     BrowserSettingsForm {
@@ -471,8 +829,29 @@ pub fn Browser(store: ActiveStore) -> Element {
 
             div {
                 div {
-                    class: "box",
-                    {contents}
+                    class: "columns",
+                    div {
+                        class: "column",
+                        div {
+                            class: "box",
+                            {contents}
+                        }
+                    }
+                    if let Some(meta) = details() {
+                        div {
+                            class: "column is-one-third",
+                            ObjectDetailsPanel {
+                                key: "{meta.key}",
+                                store: store.store.clone(),
+                                meta,
+                                on_updated: move |meta: Arc<ObjectMeta>| {
+                                    details.set(Some(meta.clone()));
+                                    tx.send(Msg::ObjectCreated { meta });
+                                },
+                                on_close: move |_| details.set(None),
+                            }
+                        }
+                    }
                 }
                 // Manual pagination: Load more button
                 if manual_pagination() && next_cursor().is_some() {
@@ -486,3 +865,19 @@ pub fn Browser(store: ActiveStore) -> Element {
         }
     }
 }
+
+/// Transfer sources for the selected keys, prefixes end with a `/`.
+fn selected_sources(selected: &HashSet<String>) -> Vec<TransferSource> {
+    let mut sources = selected
+        .iter()
+        .map(|key| {
+            if key.ends_with('/') {
+                TransferSource::Prefix(key.clone())
+            } else {
+                TransferSource::Object(key.clone())
+            }
+        })
+        .collect::<Vec<_>>();
+    sources.sort_by(|a, b| a.name().cmp(b.name()));
+    sources
+}
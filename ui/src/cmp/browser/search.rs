@@ -0,0 +1,210 @@
+use std::sync::Arc;
+
+use dioxus::{core::Task, prelude::*};
+use dioxus_bulma::{Color, Notification};
+use futures::{channel::mpsc, StreamExt as _};
+use objstore::{DynObjStore, Glob, ListArgs, ObjectMeta};
+
+use crate::cmp::object::helpers::object_size;
+
+/// How the search query is matched against keys.
+enum Matcher {
+    /// Case-insensitive substring match.
+    Substring(String),
+    Glob(Glob),
+}
+
+impl Matcher {
+    /// Queries with wildcards are treated as globs, everything else as substrings.
+    fn parse(query: &str) -> Result<Self, String> {
+        if query.contains(['*', '?', '[']) {
+            Glob::new(query)
+                .map(Self::Glob)
+                .map_err(|err| format!("Invalid pattern: {err}"))
+        } else {
+            Ok(Self::Substring(query.to_lowercase()))
+        }
+    }
+
+    /// Check a key relative to the searched prefix.
+    fn matches(&self, relative_key: &str) -> bool {
+        match self {
+            Self::Substring(needle) => relative_key.to_lowercase().contains(needle),
+            Self::Glob(glob) => glob.matches(relative_key),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+struct SearchState {
+    results: Vec<Arc<ObjectMeta>>,
+    scanned: u64,
+    done: bool,
+    error: Option<String>,
+}
+
+/// Recursive search for keys below a prefix.
+///
+/// Streams the listing and filters keys client-side. Results are shown as they
+/// arrive, in batches of `page_size`. Changing the query cancels the running
+/// search.
+#[component]
+pub fn PrefixSearch(
+    store: ReadOnlySignal<DynObjStore>,
+    prefix: ReadOnlySignal<String>,
+    page_size: ReadOnlySignal<u64>,
+    on_view: EventHandler<Arc<ObjectMeta>>,
+) -> Element {
+    let mut query = use_signal(String::new);
+    let mut state = use_signal(SearchState::default);
+    let mut task = use_signal::<Option<Task>>(|| None);
+    // Requests the next batch of results from the running search.
+    let mut more = use_signal::<Option<mpsc::UnboundedSender<()>>>(|| None);
+
+    let mut search = move |value: String| {
+        if let Some(handle) = task.take() {
+            handle.cancel();
+        }
+        more.set(None);
+        query.set(value.clone());
+        state.set(SearchState::default());
+
+        let value = value.trim().to_string();
+        if value.is_empty() {
+            return;
+        }
+        let matcher = match Matcher::parse(&value) {
+            Ok(matcher) => matcher,
+            Err(err) => {
+                state.write().error = Some(err);
+                return;
+            }
+        };
+
+        let (more_tx, mut more_rx) = mpsc::unbounded();
+        more.set(Some(more_tx));
+
+        let store = store.peek().clone();
+        let prefix = prefix.peek().clone();
+        let page_size = *page_size.peek();
+        let handle = spawn(async move {
+            let args = ListArgs::new()
+                .with_prefix(prefix.clone())
+                .with_limit(page_size);
+            let mut items = store.list_items_stream(args);
+            let mut limit = page_size as usize;
+
+            while let Some(item) = items.next().await {
+                let meta = match item {
+                    Ok(meta) => meta,
+                    Err(err) => {
+                        state.write().error = Some(err.to_string());
+                        break;
+                    }
+                };
+
+                let relative = meta.key.strip_prefix(&prefix).unwrap_or(&meta.key);
+                let is_match = matcher.matches(relative);
+                let mut current = state.write();
+                current.scanned += 1;
+                if !is_match {
+                    continue;
+                }
+                current.results.push(Arc::new(meta));
+                if current.results.len() >= limit {
+                    drop(current);
+                    // Wait until more results are requested.
+                    if more_rx.next().await.is_none() {
+                        return;
+                    }
+                    limit += page_size as usize;
+                }
+            }
+            state.write().done = true;
+        });
+        task.set(Some(handle));
+    };
+
+    let current = state.read();
+    let has_more = !current.done
+        && current.error.is_none()
+        && current.results.len() as u64 >= *page_size.read()
+        && current.results.len() as u64 % *page_size.read() == 0;
+
+    rsx! {
+        div {
+            class: "field",
+            div {
+                class: "control has-icons-left",
+                class: if task.read().is_some() && !current.done { "is-loading" } else { "" },
+                input {
+                    class: "input",
+                    r#type: "search",
+                    placeholder: "Search below /{prefix} (substring, or glob like **/*.json)",
+                    value: "{query}",
+                    oninput: move |e| search(e.value()),
+                }
+                span {
+                    class: "icon is-left",
+                    dioxus_free_icons::Icon {
+                        fill: "gray",
+                        width: 15,
+                        height: 15,
+                        icon: dioxus_free_icons::icons::fa_solid_icons::FaMagnifyingGlass,
+                    }
+                }
+            }
+        }
+
+        if let Some(err) = &current.error {
+            Notification {
+                color: Color::Danger,
+                "{err}"
+            }
+        }
+
+        if !query.read().trim().is_empty() {
+            div {
+                class: "box",
+                p {
+                    class: "help mb-2",
+                    "{current.results.len()} match(es) in {current.scanned} scanned object(s)"
+                    if !current.done { "…" }
+                }
+
+                table {
+                    class: "table is-fullwidth is-narrow",
+                    tbody {
+                        for meta in current.results.iter().cloned() {
+                            tr {
+                                key: "{meta.key}",
+                                td {
+                                    class: "has-text-link",
+                                    style: "cursor: pointer;",
+                                    onclick: {
+                                        let meta = meta.clone();
+                                        move |_| on_view.call(meta.clone())
+                                    },
+                                    "{meta.key}"
+                                }
+                                td { "{object_size(&meta)}" }
+                            }
+                        }
+                    }
+                }
+
+                if has_more {
+                    button {
+                        class: "button is-fullwidth is-link",
+                        onclick: move |_| {
+                            if let Some(tx) = &*more.read() {
+                                let _ = tx.unbounded_send(());
+                            }
+                        },
+                        "Load more results"
+                    }
+                }
+            }
+        }
+    }
+}
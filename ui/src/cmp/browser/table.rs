@@ -1,99 +1,241 @@
 use dioxus::prelude::*;
 use objstore::ObjectMeta;
-use std::sync::Arc;
+use std::{collections::HashSet, sync::Arc};
 use time::OffsetDateTime;
 
 use crate::cmp::object::helpers::{object_modified, object_size};
 
+/// Fixed height of a table row in pixels, required for virtualization.
+const ROW_HEIGHT: f64 = 41.0;
+/// Height of the scroll viewport in pixels.
+const VIEWPORT_HEIGHT: f64 = 600.0;
+/// Rows rendered above and below the visible area.
+const OVERSCAN_ROWS: usize = 10;
+/// Start loading the next page when scrolled this close to the end.
+const PREFETCH_ROWS: usize = 50;
+/// Pages are no longer loaded automatically beyond this many rows, to keep
+/// memory bounded.
+pub const MAX_AUTO_LOADED_ROWS: usize = 100_000;
+
+/// The rows to render for a scroll position.
+fn visible_range(scroll_top: f64, total: usize) -> std::ops::Range<usize> {
+    let first = (scroll_top / ROW_HEIGHT).floor().max(0.0) as usize;
+    let visible = (VIEWPORT_HEIGHT / ROW_HEIGHT).ceil() as usize;
+    let start = first.saturating_sub(OVERSCAN_ROWS).min(total);
+    let end = (first + visible + OVERSCAN_ROWS).min(total);
+    start..end
+}
+
+/// Table of objects.
+///
+/// Only the rows in the visible area are rendered, so large listings stay
+/// responsive. With `auto_load`, the next page is requested through
+/// `on_load_more` when scrolling close to the end.
+///
+/// Checked rows are tracked by key in `selected`.
 #[component]
 pub fn ObjectsTable(
     page: Signal<super::Page>,
+    mut selected: Signal<HashSet<String>>,
     now: OffsetDateTime,
+    auto_load: bool,
+    loading_more: bool,
+    on_load_more: EventHandler<()>,
     on_download: EventHandler<Arc<ObjectMeta>>,
     on_delete: EventHandler<Arc<ObjectMeta>>,
     on_view: EventHandler<Arc<ObjectMeta>>,
+    on_details: EventHandler<Arc<ObjectMeta>>,
+    on_transfer: EventHandler<Arc<ObjectMeta>>,
 ) -> Element {
+    let mut scroll_top = use_signal(|| 0.0f64);
+
+    let toggle_all = move |_| {
+        let page = page.peek();
+        let mut selected = selected.write();
+        if page.objects.iter().all(|item| selected.contains(&item.key)) {
+            for item in page.objects.iter() {
+                selected.remove(&item.key);
+            }
+        } else {
+            selected.extend(page.objects.iter().map(|item| item.key.clone()));
+        }
+    };
+
     let page = page.read();
+    let total = page.objects.len();
+    let range = visible_range(scroll_top(), total);
+    let padding_top = range.start as f64 * ROW_HEIGHT;
+    let padding_bottom = (total - range.end) as f64 * ROW_HEIGHT;
+    let can_load_more = page.next_cursor.is_some() && total < MAX_AUTO_LOADED_ROWS;
+    let all_selected = {
+        let selected = selected.read();
+        total > 0 && page.objects.iter().all(|item| selected.contains(&item.key))
+    };
+
+    let onscroll = move |e: Event<ScrollData>| {
+        let top = e.data().scroll_top() as f64;
+        scroll_top.set(top);
+
+        let last_visible = ((top + VIEWPORT_HEIGHT) / ROW_HEIGHT) as usize;
+        if auto_load && can_load_more && !loading_more && last_visible + PREFETCH_ROWS >= total {
+            on_load_more.call(());
+        }
+    };
 
     let content = rsx! {
-        table {
-            class: "table is-fullwidth",
-
-            thead {
-                tr {
-                    th {}
-                    th { "Name" }
-                    th { "Modified" }
-                    th { "Size" }
-                    th { "Actions" }
-                }
-            }
+        div {
+            max_height: "{VIEWPORT_HEIGHT}px",
+            overflow_y: "auto",
+            onscroll,
+
+            table {
+                class: "table is-fullwidth",
 
-            tbody {
-                for item in page.objects.iter().cloned() {
+                thead {
                     tr {
-                        td {
-                            // Selector
+                        th {
+                            input {
+                                r#type: "checkbox",
+                                title: "Select all loaded objects",
+                                checked: all_selected,
+                                onchange: toggle_all,
+                            }
                         }
+                        th { "Name" }
+                        th { "Modified" }
+                        th { "Size" }
+                        th { "Actions" }
+                    }
+                }
 
-                        {
-                            let name = item.key.trim_end_matches('/');
-                            let display_name = if let Some((_, name)) = name.rsplit_once('/') {
-                                name
-                            } else {
-                                name
-                            };
-                            rsx! {
-                                td {
-                                    class: "has-text-link",
-                                    style: "cursor: pointer;",
-                                    onclick: {
-                                        let item = item.clone();
-                                        move |_| on_view.call(item.clone())
+                tbody {
+                    if padding_top > 0.0 {
+                        tr { height: "{padding_top}px" }
+                    }
+                    for item in page.objects[range.clone()].iter().cloned() {
+                        tr {
+                            key: "{item.key}",
+                            height: "{ROW_HEIGHT}px",
+                            td {
+                                input {
+                                    r#type: "checkbox",
+                                    checked: selected.read().contains(&item.key),
+                                    onchange: {
+                                        let key = item.key.clone();
+                                        move |e: Event<FormData>| {
+                                            if e.checked() {
+                                                selected.write().insert(key.clone());
+                                            } else {
+                                                selected.write().remove(&key);
+                                            }
+                                        }
                                     },
-                                    "{display_name}"
                                 }
                             }
-                        }
 
-                        td { {object_modified(&item, now)} }
+                            {
+                                let name = item.key.trim_end_matches('/');
+                                let display_name = if let Some((_, name)) = name.rsplit_once('/') {
+                                    name
+                                } else {
+                                    name
+                                };
+                                rsx! {
+                                    td {
+                                        class: "has-text-link",
+                                        style: "cursor: pointer;",
+                                        onclick: {
+                                            let item = item.clone();
+                                            move |_| on_view.call(item.clone())
+                                        },
+                                        "{display_name}"
+                                    }
+                                }
+                            }
 
-                        td { "{object_size(&item)}" }
+                            td { {object_modified(&item, now)} }
 
-                        td {
-                            div {
-                                class: "buttons",
-                                button {
-                                    class: "button is-small",
-                                    onclick: {
-                                        let item = item.clone();
-                                        move |_| {
-                                            on_download.call(item.clone());
-                                        }
-                                    },
-                                    "Download"
-                                }
-                                button {
-                                    class: "button is-small",
-                                    onclick: {
-                                        let item = item.clone();
-                                        move |_| {
-                                            on_delete.call(item.clone());
-                                        }
-                                    },
-                                    dioxus_free_icons::Icon {
-                                        fill: "black",
-                                        width: 15,
-                                        height: 15,
-                                        icon: dioxus_free_icons::icons::fa_solid_icons::FaTrash,
-                                    },
+                            td { "{object_size(&item)}" }
+
+                            td {
+                                div {
+                                    class: "buttons",
+                                    button {
+                                        class: "button is-small",
+                                        onclick: {
+                                            let item = item.clone();
+                                            move |_| {
+                                                on_download.call(item.clone());
+                                            }
+                                        },
+                                        "Download"
+                                    }
+                                    button {
+                                        class: "button is-small",
+                                        title: "Details",
+                                        onclick: {
+                                            let item = item.clone();
+                                            move |_| {
+                                                on_details.call(item.clone());
+                                            }
+                                        },
+                                        dioxus_free_icons::Icon {
+                                            fill: "black",
+                                            width: 15,
+                                            height: 15,
+                                            icon: dioxus_free_icons::icons::fa_solid_icons::FaCircleInfo,
+                                        },
+                                    }
+                                    button {
+                                        class: "button is-small",
+                                        title: "Copy or move",
+                                        onclick: {
+                                            let item = item.clone();
+                                            move |_| {
+                                                on_transfer.call(item.clone());
+                                            }
+                                        },
+                                        dioxus_free_icons::Icon {
+                                            fill: "black",
+                                            width: 15,
+                                            height: 15,
+                                            icon: dioxus_free_icons::icons::fa_solid_icons::FaRightLeft,
+                                        },
+                                    }
+                                    button {
+                                        class: "button is-small",
+                                        onclick: {
+                                            let item = item.clone();
+                                            move |_| {
+                                                on_delete.call(item.clone());
+                                            }
+                                        },
+                                        dioxus_free_icons::Icon {
+                                            fill: "black",
+                                            width: 15,
+                                            height: 15,
+                                            icon: dioxus_free_icons::icons::fa_solid_icons::FaTrash,
+                                        },
+                                    }
                                 }
                             }
                         }
                     }
+                    if padding_bottom > 0.0 {
+                        tr { height: "{padding_bottom}px" }
+                    }
                 }
             }
         }
+
+        if loading_more {
+            progress { class: "progress is-small is-primary mt-2" }
+        } else if auto_load && page.next_cursor.is_some() && !can_load_more {
+            p {
+                class: "help mt-2",
+                "Showing the first {total} objects. Use search or a narrower prefix to find more."
+            }
+        }
     };
 
     content
@@ -1,10 +1,17 @@
 use dioxus::prelude::*;
 use dioxus_bulma::{Color, Notification};
+use objstore_config::LoadedConnection;
 
-use crate::{cmp::util::loader::Spinner, context::UiConfigStore, router::Route};
+use crate::{
+    cmp::{object::helpers::human_duration, util::loader::Spinner},
+    context::{use_providers, use_stores, UiConfigStore},
+    router::Route,
+    store::monitor::{use_connection_monitor, HealthStatus},
+};
 
 #[component]
 pub fn ConnectionManager(store: UiConfigStore) -> Element {
+    let config_store = store.clone();
     let mut connections = use_resource(move || {
         let store = store.clone();
         async move {
@@ -85,12 +92,10 @@ pub fn ConnectionManager(store: UiConfigStore) -> Element {
                                 }
                             } else {
                                 for conn in cons.connections.iter() {
-                                    div {
-                                        Link {
-                                            to: Route::Browser { store: conn.config.name.clone() },
-                                            class: "button is-link",
-                                            "{conn.config.name}"
-                                        }
+                                    ConnectionRow {
+                                        key: "{conn.config.name}",
+                                        store: config_store.clone(),
+                                        conn: conn.clone(),
                                     }
                                 }
                             }
@@ -126,3 +131,143 @@ pub fn ConnectionManager(store: UiConfigStore) -> Element {
         }
     }
 }
+
+/// A saved connection with its health status and a manual health check.
+#[component]
+fn ConnectionRow(store: UiConfigStore, conn: LoadedConnection) -> Element {
+    let monitor = use_connection_monitor();
+    let stores = use_stores();
+    let builder = use_providers();
+    let mut show_diagnostics = use_signal(|| false);
+
+    let name = conn.config.name.clone();
+    let health = monitor.get(&name);
+
+    let (dot_class, status_label) = match health.status() {
+        HealthStatus::Unknown => ("has-text-grey-light", "Not checked yet"),
+        HealthStatus::Healthy => ("has-text-success", "Healthy"),
+        HealthStatus::Degraded => ("has-text-warning", "Reachable with warnings"),
+        HealthStatus::Unreachable => ("has-text-danger", "Unreachable"),
+    };
+    let latency = match &health.result {
+        Some(Ok(report)) => Some(format!("{} ms", report.latency.as_millis())),
+        _ => None,
+    };
+    let checked_ago = health
+        .checked_at
+        .map(|at| format!("checked {} ago", human_duration(at.elapsed())));
+
+    let test = {
+        let name = name.clone();
+        move |_| {
+            let name = name.clone();
+            let store = store.clone();
+            let builder = builder.clone();
+            show_diagnostics.set(true);
+            spawn(async move {
+                let _ = monitor.check(&name, stores, &store, &builder).await;
+            });
+        }
+    };
+
+    rsx! {
+        div {
+            class: "mb-2",
+
+            div {
+                class: "is-flex is-align-items-center",
+
+                span {
+                    class: "mr-2 {dot_class}",
+                    title: "{status_label}",
+                    "●"
+                }
+
+                Link {
+                    to: Route::Browser {
+                        store: name.clone(),
+                        prefix: String::new(),
+                    },
+                    class: "button is-link",
+                    "{name}"
+                }
+
+                span {
+                    class: "help ml-3",
+                    if let Some(latency) = &latency {
+                        "{latency}"
+                    }
+                    if latency.is_some() && checked_ago.is_some() {
+                        ", "
+                    }
+                    if let Some(checked_ago) = &checked_ago {
+                        "{checked_ago}"
+                    }
+                }
+
+                button {
+                    class: "button is-small ml-3",
+                    class: if health.checking { "is-loading" } else { "" },
+                    title: "Test connection",
+                    onclick: test,
+                    "Test"
+                }
+            }
+
+            if show_diagnostics() && !health.checking {
+                match &health.result {
+                    Some(Ok(report)) => rsx! {
+                        div {
+                            class: "box mt-2",
+                            table {
+                                class: "table is-narrow",
+                                tbody {
+                                    tr {
+                                        th { "Status" }
+                                        td { "{status_label}" }
+                                    }
+                                    tr {
+                                        th { "Latency" }
+                                        td { "{report.latency.as_millis()} ms" }
+                                    }
+                                    if let Some(server) = &report.server {
+                                        tr {
+                                            th { "Server" }
+                                            td { "{server}" }
+                                        }
+                                    }
+                                    if let Some(region) = &report.region {
+                                        tr {
+                                            th { "Region" }
+                                            td { "{region}" }
+                                        }
+                                    }
+                                }
+                            }
+                            if report.has_warnings() {
+                                ul {
+                                    class: "help is-warning",
+                                    for warning in report.warnings.iter() {
+                                        li { "{warning}" }
+                                    }
+                                }
+                            }
+                            button {
+                                class: "button is-small",
+                                onclick: move |_| show_diagnostics.set(false),
+                                "Close"
+                            }
+                        }
+                    },
+                    Some(Err(err)) => rsx! {
+                        Notification {
+                            color: Color::Danger,
+                            "Connection check failed: {err}"
+                        }
+                    },
+                    None => rsx! {},
+                }
+            }
+        }
+    }
+}
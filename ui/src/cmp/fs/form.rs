@@ -0,0 +1,119 @@
+use std::path::PathBuf;
+
+use anyhow::bail;
+use dioxus::prelude::*;
+use objstore_config::ConnectionConfig;
+use objstore_fs::FsObjStoreConfig;
+
+use crate::cmp::util::form::{ConnectionFormActions, ConnectionPersistence, FormSubmit};
+
+#[component]
+pub fn FsForm(
+    on_submit: EventHandler<(ConnectionConfig, ConnectionPersistence)>,
+    on_cancel: EventHandler<()>,
+    status: ReadOnlySignal<FormSubmit>,
+) -> Element {
+    let mut errors = use_signal::<Option<Vec<String>>>(|| None);
+
+    let mut value_name = use_signal(|| String::new());
+    let mut value_path = use_signal(|| String::new());
+
+    let submit = Callback::<ConnectionPersistence>::new(move |persist: ConnectionPersistence| {
+        if status.read().is_loading() {
+            return;
+        }
+
+        let build_values = move || -> Result<ConnectionConfig, anyhow::Error> {
+            let name = value_name().trim().to_owned();
+            if name.is_empty() {
+                bail!("Name must not be empty");
+            }
+
+            let path = PathBuf::from(value_path().trim());
+            if path.as_os_str().is_empty() {
+                bail!("Path must not be empty");
+            }
+            if !path.is_absolute() {
+                bail!("Path must be absolute");
+            }
+
+            let config = ConnectionConfig {
+                name,
+                uri: FsObjStoreConfig::new(path).build_uri()?,
+                description: None,
+                wrappers: Default::default(),
+            };
+
+            Ok(config)
+        };
+
+        match build_values() {
+            Ok(config) => {
+                errors.set(None);
+                on_submit.call((config, persist));
+            }
+            Err(e) => {
+                errors.set(Some(vec![e.to_string()]));
+            }
+        }
+    });
+
+    rsx! {
+        form {
+            onsubmit: move |e| {
+                e.prevent_default();
+                submit.call(ConnectionPersistence::Persistent);
+            },
+            div {
+                class: "field",
+
+                label {
+                    class: "label",
+                    "Name"
+                }
+
+                div {
+                    class: "control",
+                    input {
+                        class: "input",
+                        r#type: "text",
+                        placeholder: "Enter connection name",
+                        value: "{value_name}",
+                        onchange: move |e| value_name.set(e.value()),
+                    }
+                }
+            }
+
+            div {
+                class: "field",
+
+                label {
+                    class: "label",
+                    "Directory"
+                }
+
+                div {
+                    class: "control",
+                    input {
+                        class: "input",
+                        r#type: "text",
+                        placeholder: "/path/to/directory",
+                        value: "{value_path}",
+                        onchange: move |e| value_path.set(e.value()),
+                    }
+                }
+                p {
+                    class: "help",
+                    "Absolute path of the directory. It is created if it does not exist."
+                }
+            }
+
+            ConnectionFormActions {
+                status,
+                errors,
+                submit,
+                on_cancel,
+            }
+        }
+    }
+}
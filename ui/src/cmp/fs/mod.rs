@@ -0,0 +1,2 @@
+mod form;
+pub use self::form::FsForm;
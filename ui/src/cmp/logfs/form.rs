@@ -0,0 +1,259 @@
+use std::{num::NonZeroU32, path::PathBuf};
+
+use anyhow::{bail, Context};
+use dioxus::prelude::*;
+use objstore_config::ConnectionConfig;
+use objstore_logfs::{LogFsCryptoConfig, LogFsObjStoreConfig};
+
+use crate::cmp::util::form::{ConnectionFormActions, ConnectionPersistence, FormSubmit};
+
+const DEFAULT_CRYPTO_ITERATIONS: &str = "100000";
+
+#[component]
+pub fn LogFsForm(
+    on_submit: EventHandler<(ConnectionConfig, ConnectionPersistence)>,
+    on_cancel: EventHandler<()>,
+    status: ReadOnlySignal<FormSubmit>,
+) -> Element {
+    let mut errors = use_signal::<Option<Vec<String>>>(|| None);
+
+    let mut value_name = use_signal(|| String::new());
+    let mut value_path = use_signal(|| String::new());
+    let mut value_allow_create = use_signal(|| true);
+    let mut value_readonly = use_signal(|| false);
+    let mut value_encrypted = use_signal(|| false);
+    let mut value_crypto_key = use_signal(|| String::new());
+    let mut value_crypto_salt = use_signal(|| String::new());
+    let mut value_crypto_iterations = use_signal(|| DEFAULT_CRYPTO_ITERATIONS.to_string());
+
+    let submit = Callback::<ConnectionPersistence>::new(move |persist: ConnectionPersistence| {
+        if status.read().is_loading() {
+            return;
+        }
+
+        let build_values = move || -> Result<ConnectionConfig, anyhow::Error> {
+            let name = value_name().trim().to_owned();
+            if name.is_empty() {
+                bail!("Name must not be empty");
+            }
+
+            let path = PathBuf::from(value_path().trim());
+            if path.as_os_str().is_empty() {
+                bail!("Path must not be empty");
+            }
+            if !path.is_absolute() {
+                bail!("Path must be absolute");
+            }
+
+            let mut s = LogFsObjStoreConfig::new(path)
+                .with_allow_create(value_allow_create())
+                .with_readonly(value_readonly());
+
+            if value_encrypted() {
+                let key = value_crypto_key();
+                if key.is_empty() {
+                    bail!("Encryption key must not be empty");
+                }
+                let salt = value_crypto_salt();
+                if salt.is_empty() {
+                    bail!("Encryption salt must not be empty");
+                }
+                let iterations_raw = value_crypto_iterations();
+                let iterations = iterations_raw
+                    .trim()
+                    .parse::<NonZeroU32>()
+                    .with_context(|| format!("invalid iteration count '{}'", iterations_raw))?;
+
+                s = s.with_crypto(LogFsCryptoConfig {
+                    key,
+                    salt: salt.into_bytes(),
+                    iterations,
+                });
+            }
+
+            let config = ConnectionConfig {
+                name,
+                uri: s.build_uri()?,
+                description: None,
+                wrappers: Default::default(),
+            };
+
+            Ok(config)
+        };
+
+        match build_values() {
+            Ok(config) => {
+                errors.set(None);
+                on_submit.call((config, persist));
+            }
+            Err(e) => {
+                errors.set(Some(vec![e.to_string()]));
+            }
+        }
+    });
+
+    rsx! {
+        form {
+            onsubmit: move |e| {
+                e.prevent_default();
+                submit.call(ConnectionPersistence::Persistent);
+            },
+            div {
+                class: "field",
+
+                label {
+                    class: "label",
+                    "Name"
+                }
+
+                div {
+                    class: "control",
+                    input {
+                        class: "input",
+                        r#type: "text",
+                        placeholder: "Enter connection name",
+                        value: "{value_name}",
+                        onchange: move |e| value_name.set(e.value()),
+                    }
+                }
+            }
+
+            div {
+                class: "field",
+
+                label {
+                    class: "label",
+                    "Log Path"
+                }
+
+                div {
+                    class: "control",
+                    input {
+                        class: "input",
+                        r#type: "text",
+                        placeholder: "/path/to/store.log",
+                        value: "{value_path}",
+                        onchange: move |e| value_path.set(e.value()),
+                    }
+                }
+            }
+
+            div {
+                class: "field",
+                div {
+                    class: "control",
+                    label {
+                        class: "checkbox",
+                        input {
+                            r#type: "checkbox",
+                            checked: value_allow_create(),
+                            onchange: move |e| value_allow_create.set(e.checked()),
+                        }
+                        " Create the log if it does not exist"
+                    }
+                }
+            }
+
+            div {
+                class: "field",
+                div {
+                    class: "control",
+                    label {
+                        class: "checkbox",
+                        input {
+                            r#type: "checkbox",
+                            checked: value_readonly(),
+                            onchange: move |e| value_readonly.set(e.checked()),
+                        }
+                        " Open read-only"
+                    }
+                }
+            }
+
+            div {
+                class: "field",
+                div {
+                    class: "control",
+                    label {
+                        class: "checkbox",
+                        input {
+                            r#type: "checkbox",
+                            checked: value_encrypted(),
+                            onchange: move |e| value_encrypted.set(e.checked()),
+                        }
+                        " Encrypted"
+                    }
+                }
+            }
+
+            if value_encrypted() {
+                div {
+                    class: "field",
+
+                    label {
+                        class: "label",
+                        "Encryption Key"
+                    }
+
+                    div {
+                        class: "control",
+                        input {
+                            class: "input",
+                            r#type: "password",
+                            placeholder: "Enter encryption key",
+                            value: "{value_crypto_key}",
+                            onchange: move |e| value_crypto_key.set(e.value()),
+                        }
+                    }
+                }
+
+                div {
+                    class: "field",
+
+                    label {
+                        class: "label",
+                        "Salt"
+                    }
+
+                    div {
+                        class: "control",
+                        input {
+                            class: "input",
+                            r#type: "password",
+                            placeholder: "Enter salt",
+                            value: "{value_crypto_salt}",
+                            onchange: move |e| value_crypto_salt.set(e.value()),
+                        }
+                    }
+                }
+
+                div {
+                    class: "field",
+
+                    label {
+                        class: "label",
+                        "Key Derivation Iterations"
+                    }
+
+                    div {
+                        class: "control",
+                        input {
+                            class: "input",
+                            r#type: "number",
+                            min: "1",
+                            value: "{value_crypto_iterations}",
+                            onchange: move |e| value_crypto_iterations.set(e.value()),
+                        }
+                    }
+                }
+            }
+
+            ConnectionFormActions {
+                status,
+                errors,
+                submit,
+                on_cancel,
+            }
+        }
+    }
+}
@@ -6,6 +6,11 @@ pub mod util;
 
 pub mod s3;
 
+#[cfg(feature = "desktop")]
+pub mod fs;
+#[cfg(feature = "desktop")]
+pub mod logfs;
+
 mod browser;
 pub use browser::Browser;
 
@@ -16,3 +21,9 @@ mod object_delete_modal;
 use object_delete_modal::ObjectDeleteModal;
 
 mod object;
+
+mod usage_dashboard;
+pub use usage_dashboard::UsageDashboard;
+
+mod transfer_panel;
+pub use transfer_panel::TransferPanel;
@@ -0,0 +1,205 @@
+use std::sync::Arc;
+
+use dioxus::prelude::*;
+use dioxus_bulma::{Color, Notification};
+use objstore::{Copy, CopyMetadata, DynObjStore, ObjectMeta};
+use time::format_description::well_known::Iso8601;
+
+use crate::cmp::{object::helpers::object_size, util::loader::LoadState};
+
+fn format_time(time: &Option<time::OffsetDateTime>) -> Option<String> {
+    time.map(|time| time.format(&Iso8601::DEFAULT).unwrap_or_default())
+}
+
+fn non_empty(value: String) -> Option<String> {
+    let value = value.trim();
+    (!value.is_empty()).then(|| value.to_string())
+}
+
+/// Side panel with the full metadata of an object.
+///
+/// The content headers can be edited. Changes are written by copying the
+/// object onto itself with replaced metadata, which fails on backends that do
+/// not support this.
+#[component]
+pub fn ObjectDetailsPanel(
+    store: ReadOnlySignal<DynObjStore>,
+    meta: ReadOnlySignal<Arc<ObjectMeta>>,
+    on_updated: EventHandler<Arc<ObjectMeta>>,
+    on_close: EventHandler<()>,
+) -> Element {
+    let mut editing = use_signal(|| false);
+    let mut state = use_signal::<LoadState<()>>(|| LoadState::Idle);
+    let mut value_mime_type = use_signal(String::new);
+    let mut value_cache_control = use_signal(String::new);
+    let mut value_content_disposition = use_signal(String::new);
+    let mut value_content_encoding = use_signal(String::new);
+
+    let mut start_editing = move || {
+        let meta = meta.peek();
+        value_mime_type.set(meta.mime_type.clone().unwrap_or_default());
+        value_cache_control.set(meta.cache_control.clone().unwrap_or_default());
+        value_content_disposition.set(meta.content_disposition.clone().unwrap_or_default());
+        value_content_encoding.set(meta.content_encoding.clone().unwrap_or_default());
+        state.set(LoadState::Idle);
+        editing.set(true);
+    };
+
+    let save = move |_| {
+        if let LoadState::Loading = &*state.peek() {
+            return;
+        }
+        state.set(LoadState::Loading);
+
+        let mut metadata = CopyMetadata::new();
+        metadata.mime_type = non_empty(value_mime_type());
+        metadata.cache_control = non_empty(value_cache_control());
+        metadata.content_disposition = non_empty(value_content_disposition());
+        metadata.content_encoding = non_empty(value_content_encoding());
+
+        let store = store.peek().clone();
+        let key = meta.peek().key.clone();
+        spawn(async move {
+            let mut copy = Copy::new(key.clone(), key);
+            copy.metadata = Some(metadata);
+            match store.send_copy(copy).await {
+                Ok(new_meta) => {
+                    state.set(LoadState::Loaded(Ok(())));
+                    editing.set(false);
+                    on_updated.call(Arc::new(new_meta));
+                }
+                Err(err) => {
+                    state.set(LoadState::Loaded(Err(format!(
+                        "Updating metadata failed (the backend may not support rewriting metadata): {err}"
+                    ))));
+                }
+            }
+        });
+    };
+
+    let meta = meta.read();
+    let mut rows = vec![
+        ("Key", Some(meta.key.clone())),
+        ("Size", Some(object_size(&meta))),
+        ("Created", format_time(&meta.created_at)),
+        ("Updated", format_time(&meta.updated_at)),
+        ("Expires", format_time(&meta.expires_at)),
+        ("ETag", meta.etag.clone()),
+        ("MD5", meta.hash_md5.map(hex::encode)),
+        ("SHA256", meta.hash_sha256.map(hex::encode)),
+        ("Mime-Type", meta.mime_type.clone()),
+        ("Cache-Control", meta.cache_control.clone()),
+        ("Content-Disposition", meta.content_disposition.clone()),
+        ("Content-Encoding", meta.content_encoding.clone()),
+        (
+            "Encryption",
+            meta.encryption.as_ref().map(|enc| match &enc.key_id {
+                Some(key_id) => format!("{} ({key_id})", enc.scheme.as_str()),
+                None => enc.scheme.as_str().to_string(),
+            }),
+        ),
+        ("Storage Class", meta.attributes.storage_class.clone()),
+        ("Version", meta.attributes.version_id.clone()),
+        ("Owner", meta.attributes.owner.clone()),
+    ];
+    rows.retain(|(_, value)| value.is_some());
+    let custom = meta
+        .attributes
+        .custom_iter()
+        .map(|(key, value)| (key.to_string(), value.to_string()))
+        .collect::<Vec<_>>();
+    let is_saving = matches!(&*state.read(), LoadState::Loading);
+
+    rsx! {
+        div {
+            class: "box",
+
+            div {
+                class: "is-flex is-justify-content-space-between is-align-items-center mb-3",
+                h2 { class: "title is-5 mb-0", "Details" }
+                button {
+                    class: "delete",
+                    aria_label: "Close",
+                    onclick: move |_| on_close.call(()),
+                }
+            }
+
+            table {
+                class: "table is-fullwidth is-narrow",
+                tbody {
+                    for (label, value) in rows {
+                        tr {
+                            th { "{label}" }
+                            td {
+                                style: "word-break: break-all;",
+                                "{value.unwrap_or_default()}"
+                            }
+                        }
+                    }
+                    for (key, value) in custom {
+                        tr {
+                            th { "{key}" }
+                            td {
+                                style: "word-break: break-all;",
+                                "{value}"
+                            }
+                        }
+                    }
+                }
+            }
+
+            if editing() {
+                for (label, mut signal) in [
+                    ("Mime-Type", value_mime_type),
+                    ("Cache-Control", value_cache_control),
+                    ("Content-Disposition", value_content_disposition),
+                    ("Content-Encoding", value_content_encoding),
+                ] {
+                    div {
+                        class: "field",
+                        label { class: "label is-small", "{label}" }
+                        div {
+                            class: "control",
+                            input {
+                                class: "input is-small",
+                                r#type: "text",
+                                value: "{signal}",
+                                onchange: move |e| signal.set(e.value()),
+                            }
+                        }
+                    }
+                }
+            }
+
+            if let LoadState::Loaded(Err(err)) = &*state.read() {
+                Notification {
+                    color: Color::Danger,
+                    "{err}"
+                }
+            }
+
+            div {
+                class: "buttons",
+                if editing() {
+                    button {
+                        class: "button is-primary is-small",
+                        class: if is_saving { "is-loading" } else { "" },
+                        onclick: save,
+                        "Save"
+                    }
+                    button {
+                        class: "button is-small",
+                        onclick: move |_| editing.set(false),
+                        "Cancel"
+                    }
+                } else {
+                    button {
+                        class: "button is-small",
+                        onclick: move |_| start_editing(),
+                        "Edit metadata"
+                    }
+                }
+            }
+        }
+    }
+}
@@ -1,23 +1,25 @@
 use dioxus::prelude::*;
-use dioxus_bulma::{Modal, Notification};
-use futures::StreamExt as _;
+use dioxus_bulma::Modal;
 use objstore::DynObjStore;
 use std::sync::Arc;
 
-use crate::cmp::util::loader::LoadState;
-use crate::store::{default_download_dir, download_object};
+use crate::store::{
+    default_download_dir, download_object,
+    jobs::{use_jobs, JobKind, JobOutput, JobSpec, JobUnit},
+};
 
-/// Modal for downloading objects with output path selection and progress bar.
+/// Modal for downloading objects with output path selection.
+///
+/// The download runs as a background job.
 #[component]
 pub fn DownloadModal(
     store: ReadOnlySignal<DynObjStore>,
+    connection: ReadOnlySignal<String>,
     object_meta: ReadOnlySignal<Arc<objstore::ObjectMeta>>,
     on_complete: EventHandler<()>,
     on_cancel: EventHandler<()>,
 ) -> Element {
-    let mut state = use_signal::<LoadState<()>>(|| LoadState::Idle);
-    let mut progress = use_signal(|| 0u64);
-    let size = { object_meta.read_unchecked().size };
+    let jobs = use_jobs();
     let mut local_path = use_signal(|| {
         let meta = object_meta.read_unchecked();
         let filename = meta
@@ -32,35 +34,28 @@ pub fn DownloadModal(
             .unwrap_or_default()
     });
 
-    let on_progress = use_callback(move |value: u64| {
-        progress.set(value);
-    });
-
-    let tx = use_coroutine::<(), _, _>(move |mut rx| {
-        let store = store.clone();
-        let object_meta = object_meta.clone();
-        let local_path = local_path.clone();
-        let on_complete = on_complete.clone();
-        async move {
-            while let Some(_) = rx.next().await {
-                state.set(LoadState::Loading);
-                let meta = object_meta.read_unchecked();
-                let path = local_path.read_unchecked();
-
-                match download_object(&store.read_unchecked(), &meta, Some(&path), on_progress)
-                    .await
-                {
-                    Ok(()) => {
-                        on_complete.call(());
-                        state.set(LoadState::Loaded(Ok(())));
-                    }
-                    Err(e) => {
-                        state.set(LoadState::Loaded(Err(e.to_string())));
-                    }
-                }
+    let start = move |_| {
+        let store = store.peek().clone();
+        let meta = object_meta.peek().clone();
+        let path = local_path.peek().clone();
+        let spec = JobSpec {
+            kind: JobKind::Download,
+            connection: connection.peek().clone(),
+            title: meta.key.clone(),
+            unit: JobUnit::Bytes,
+            total: meta.size,
+        };
+        jobs.submit(spec, move |job| {
+            let store = store.clone();
+            let meta = meta.clone();
+            let path = path.clone();
+            async move {
+                download_object(&store, &meta, Some(&path), move |n| job.progress(n)).await?;
+                Ok(JobOutput::None)
             }
-        }
-    });
+        });
+        on_complete.call(());
+    };
 
     rsx! {
         Modal {
@@ -82,38 +77,11 @@ pub fn DownloadModal(
                         }
                     }
 
-                    match &*state.read() {
-                        LoadState::Idle => rsx! {},
-                        LoadState::Loading => rsx! {
-                            div {
-                                class: "field",
-                                div {
-                                    class: "control",
-                                    progress {
-                                        class: "progress is-primary",
-                                        max:  if let Some(size) = size {
-                                            size.to_string()
-                                        },
-                                        value: "{progress()}"
-                                    }
-                                }
-                            }
-                        },
-                        LoadState::Loaded(Ok(())) => rsx! {},
-                        LoadState::Loaded(Err(err)) => rsx! {
-                            Notification {
-                                color: dioxus_bulma::Color::Danger,
-                                "{err}"
-                            }
-                        },
-                    }
-
                     div {
                         class: "buttons",
                         button {
                             class: "button is-primary",
-                            class: if let LoadState::Loading = &*state.read() { "is-loading" } else { "" },
-                            onclick: move |_| { tx.send(()); },
+                            onclick: start,
                             "Download"
                         }
                         button {
@@ -0,0 +1,248 @@
+use std::sync::Arc;
+
+use dioxus::prelude::*;
+use dioxus_bulma::{Color, Notification};
+use objstore::{
+    Conditions, DynObjStore, MimeInference, ObjStoreError, ObjStoreExt as _, ObjectMeta,
+};
+
+use crate::cmp::{object::highlight::HighlightedCode, util::loader::LoadState};
+
+/// Objects larger than this are not loaded into the editor.
+const MAX_EDITABLE_SIZE: u64 = 1024 * 1024;
+
+/// Mime types besides `text/*` that are edited as text.
+const TEXT_MIME_TYPES: &[&str] = &[
+    "application/json",
+    "application/ld+json",
+    "application/xml",
+    "application/yaml",
+    "application/x-yaml",
+    "application/toml",
+    "application/javascript",
+    "application/x-sh",
+    "application/sql",
+];
+
+/// Whether an object can be opened in the [`ObjectEditor`].
+pub fn is_editable(meta: &ObjectMeta) -> bool {
+    if meta.size.is_some_and(|size| size > MAX_EDITABLE_SIZE) {
+        return false;
+    }
+    let mime = meta
+        .mime_type
+        .clone()
+        .or_else(|| MimeInference::Extension.infer(&meta.key, None));
+    let Some(mime) = mime else {
+        return false;
+    };
+    let essence = mime.split(';').next().unwrap_or_default().trim();
+    essence.starts_with("text/")
+        || essence.ends_with("+json")
+        || essence.ends_with("+xml")
+        || TEXT_MIME_TYPES.contains(&essence)
+}
+
+/// Loaded object contents, with the etag used to guard the save.
+#[derive(Clone, Debug, PartialEq)]
+struct Loaded {
+    text: String,
+    meta: Arc<ObjectMeta>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum SaveState {
+    Idle,
+    Saving,
+    Saved,
+    /// The object was changed by someone else since it was loaded.
+    Conflict,
+    Failed(String),
+}
+
+/// Text editor for small text-like objects.
+///
+/// Saves are conditional on the etag of the loaded version, so concurrent
+/// changes are reported as conflicts instead of being overwritten.
+#[component]
+pub fn ObjectEditor(
+    store: ReadOnlySignal<DynObjStore>,
+    meta: ReadOnlySignal<Arc<ObjectMeta>>,
+    on_saved: EventHandler<Arc<ObjectMeta>>,
+) -> Element {
+    let mut loaded = use_signal::<LoadState<Loaded>>(|| LoadState::Idle);
+    let mut draft = use_signal(String::new);
+    let mut editing = use_signal(|| false);
+    let mut save_state = use_signal(|| SaveState::Idle);
+
+    let mut load = move || {
+        loaded.set(LoadState::Loading);
+        save_state.set(SaveState::Idle);
+        let store = store.peek().clone();
+        let key = meta.peek().key.clone();
+        spawn(async move {
+            let res = match store.get_with_meta(&key).await {
+                Ok(Some((data, meta))) => match String::from_utf8(data.to_vec()) {
+                    Ok(text) => Ok(Loaded {
+                        text,
+                        meta: Arc::new(meta),
+                    }),
+                    Err(_) => Err("Object is not valid UTF-8 text".to_string()),
+                },
+                Ok(None) => Err("Object no longer exists".to_string()),
+                Err(err) => Err(err.to_string()),
+            };
+            if let Ok(current) = &res {
+                draft.set(current.text.clone());
+            }
+            loaded.set(LoadState::Loaded(res));
+        });
+    };
+
+    use_hook(move || load());
+
+    let mut save = move |force: bool| {
+        let LoadState::Loaded(Ok(current)) = &*loaded.peek() else {
+            return;
+        };
+        if *save_state.peek() == SaveState::Saving {
+            return;
+        }
+        save_state.set(SaveState::Saving);
+
+        let store = store.peek().clone();
+        let current = current.clone();
+        let text = draft.peek().clone();
+        spawn(async move {
+            let mut put = store.put(&current.meta.key);
+            if let Some(mime) = &current.meta.mime_type {
+                put = put.mime_type(mime.clone());
+            }
+            if !force {
+                if let Some(etag) = &current.meta.etag {
+                    put = put.conditions(Conditions::new().if_match_tags([etag.clone()]));
+                }
+            }
+            match put.text(text.clone()).await {
+                Ok(new_meta) => {
+                    let new_meta = Arc::new(new_meta);
+                    loaded.set(LoadState::Loaded(Ok(Loaded {
+                        text,
+                        meta: new_meta.clone(),
+                    })));
+                    editing.set(false);
+                    save_state.set(SaveState::Saved);
+                    on_saved.call(new_meta);
+                }
+                Err(ObjStoreError::PreconditionFailed { .. }) => {
+                    save_state.set(SaveState::Conflict);
+                }
+                Err(err) => {
+                    save_state.set(SaveState::Failed(err.to_string()));
+                }
+            }
+        });
+    };
+
+    let content = match &*loaded.read() {
+        LoadState::Idle | LoadState::Loading => rsx! {
+            progress { class: "progress is-small is-primary" }
+        },
+        LoadState::Loaded(Err(err)) => rsx! {
+            Notification {
+                color: Color::Danger,
+                "{err}"
+            }
+        },
+        LoadState::Loaded(Ok(current)) => {
+            let dirty = *draft.read() != current.text;
+            let saving = *save_state.read() == SaveState::Saving;
+            rsx! {
+                if editing() {
+                    textarea {
+                        class: "textarea is-family-monospace is-size-7",
+                        rows: 20,
+                        spellcheck: false,
+                        value: "{draft}",
+                        oninput: move |e| draft.set(e.value()),
+                    }
+                } else {
+                    HighlightedCode { text: current.text.clone() }
+                }
+
+                match &*save_state.read() {
+                    SaveState::Conflict => rsx! {
+                        Notification {
+                            color: Color::Warning,
+                            p { "The object was modified since it was loaded." }
+                            div {
+                                class: "buttons mt-2",
+                                button {
+                                    class: "button is-small",
+                                    onclick: move |_| load(),
+                                    "Reload (discard my changes)"
+                                }
+                                button {
+                                    class: "button is-small is-danger",
+                                    onclick: move |_| save(true),
+                                    "Overwrite"
+                                }
+                            }
+                        }
+                    },
+                    SaveState::Failed(err) => rsx! {
+                        Notification {
+                            color: Color::Danger,
+                            "{err}"
+                        }
+                    },
+                    SaveState::Saved => rsx! {
+                        p { class: "help is-success", "Saved." }
+                    },
+                    SaveState::Idle | SaveState::Saving => rsx! {},
+                }
+
+                div {
+                    class: "buttons mt-2",
+                    if editing() {
+                        button {
+                            class: "button is-primary",
+                            class: if saving { "is-loading" } else { "" },
+                            disabled: !dirty,
+                            onclick: move |_| save(false),
+                            "Save"
+                        }
+                        button {
+                            class: "button",
+                            onclick: {
+                                let text = current.text.clone();
+                                move |_| {
+                                    draft.set(text.clone());
+                                    editing.set(false);
+                                    save_state.set(SaveState::Idle);
+                                }
+                            },
+                            "Discard"
+                        }
+                    } else {
+                        button {
+                            class: "button",
+                            onclick: move |_| {
+                                save_state.set(SaveState::Idle);
+                                editing.set(true);
+                            },
+                            "Edit"
+                        }
+                    }
+                }
+            }
+        }
+    };
+
+    rsx! {
+        div {
+            class: "box",
+            {content}
+        }
+    }
+}
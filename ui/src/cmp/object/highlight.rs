@@ -0,0 +1,117 @@
+//! Minimal syntax highlighting for text-like objects.
+//!
+//! Covers the common config formats (JSON, TOML, YAML, INI) with a single
+//! tokenizer: strings, numbers, literals, comments and punctuation.
+
+use dioxus::prelude::*;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum TokenKind {
+    Plain,
+    String,
+    Number,
+    Literal,
+    Comment,
+    Punctuation,
+}
+
+impl TokenKind {
+    fn color(self) -> Option<&'static str> {
+        match self {
+            Self::Plain => None,
+            Self::String => Some("#a31515"),
+            Self::Number => Some("#098658"),
+            Self::Literal => Some("#0000ff"),
+            Self::Comment => Some("#6a9955"),
+            Self::Punctuation => Some("#7a7a7a"),
+        }
+    }
+}
+
+const LITERALS: &[&str] = &["true", "false", "null", "yes", "no", "on", "off", "~"];
+
+fn tokenize(text: &str) -> Vec<(TokenKind, &str)> {
+    let mut tokens = Vec::new();
+    let mut rest = text;
+    let mut line_start = true;
+
+    while let Some(c) = rest.chars().next() {
+        let (kind, len) = match c {
+            '"' | '\'' => {
+                let mut escaped = false;
+                let end = rest[1..]
+                    .char_indices()
+                    .find(|&(_, ch)| {
+                        let done = ch == c && !escaped || ch == '\n';
+                        escaped = ch == '\\' && !escaped;
+                        done
+                    })
+                    .map_or(rest.len(), |(idx, ch)| 1 + idx + ch.len_utf8());
+                (TokenKind::String, end)
+            }
+            '#' => (TokenKind::Comment, rest.find('\n').unwrap_or(rest.len())),
+            '/' if rest.starts_with("//") => {
+                (TokenKind::Comment, rest.find('\n').unwrap_or(rest.len()))
+            }
+            ';' if line_start => (TokenKind::Comment, rest.find('\n').unwrap_or(rest.len())),
+            '{' | '}' | '[' | ']' | ':' | ',' | '=' => (TokenKind::Punctuation, 1),
+            c if c.is_ascii_digit() || c == '-' => {
+                let end = rest[1..]
+                    .find(|ch: char| {
+                        !(ch.is_ascii_alphanumeric() || matches!(ch, '.' | '_' | '+' | '-'))
+                    })
+                    .map_or(rest.len(), |idx| idx + 1);
+                if rest[..end].chars().any(|ch| ch.is_ascii_digit()) {
+                    (TokenKind::Number, end)
+                } else {
+                    (TokenKind::Plain, end)
+                }
+            }
+            c if c.is_alphanumeric() || c == '_' || c == '~' => {
+                let end = rest
+                    .find(|ch: char| !(ch.is_alphanumeric() || matches!(ch, '_' | '~')))
+                    .unwrap_or(rest.len());
+                let kind = if LITERALS.contains(&&rest[..end]) {
+                    TokenKind::Literal
+                } else {
+                    TokenKind::Plain
+                };
+                (kind, end)
+            }
+            c => (TokenKind::Plain, c.len_utf8()),
+        };
+
+        let (token, tail) = rest.split_at(len);
+        line_start = token.ends_with('\n') || (line_start && token.trim().is_empty());
+        match tokens.last_mut() {
+            // Merge plain runs to keep the number of nodes down.
+            Some((TokenKind::Plain, last)) if kind == TokenKind::Plain => {
+                let start = text.len() - rest.len() - last.len();
+                *last = &text[start..text.len() - tail.len()];
+            }
+            _ => tokens.push((kind, token)),
+        }
+        rest = tail;
+    }
+    tokens
+}
+
+/// Render `text` as a highlighted code block.
+#[component]
+pub fn HighlightedCode(text: String) -> Element {
+    rsx! {
+        pre {
+            class: "is-size-7",
+            max_height: "60vh",
+            overflow: "auto",
+            code {
+                for (kind, token) in tokenize(&text) {
+                    match kind.color() {
+                        Some(color) => rsx! { span { color, "{token}" } },
+                        None => rsx! { "{token}" },
+                    }
+                }
+            }
+        }
+    }
+}
@@ -1,4 +1,12 @@
+pub mod details_panel;
 pub mod download_modal;
+pub mod editor;
 pub mod helpers;
+pub mod highlight;
 pub mod object_creator;
+pub mod prefix_download_modal;
+pub mod preview;
+pub mod transfer_modal;
+#[cfg(feature = "desktop")]
+pub mod uploader;
 pub mod viewer;
@@ -0,0 +1,100 @@
+use dioxus::prelude::*;
+use dioxus_bulma::Modal;
+use objstore::DynObjStore;
+
+use crate::store::{
+    archive_file_name, default_download_dir, download_prefix_zip,
+    jobs::{use_jobs, JobKind, JobOutput, JobSpec, JobUnit},
+};
+
+/// Modal for downloading all objects below a prefix as a zip archive.
+///
+/// The download runs as a background job. Cancelling it removes the partial
+/// archive.
+#[component]
+pub fn PrefixDownloadModal(
+    store: ReadOnlySignal<DynObjStore>,
+    connection: ReadOnlySignal<String>,
+    prefix: ReadOnlySignal<String>,
+    on_complete: EventHandler<()>,
+    on_cancel: EventHandler<()>,
+) -> Element {
+    let jobs = use_jobs();
+    let mut local_path = use_signal(|| {
+        let filename = archive_file_name(&prefix.read_unchecked());
+        default_download_dir()
+            .map(|dir| dir.join(filename).display().to_string())
+            .unwrap_or_default()
+    });
+
+    let start = move |_| {
+        let store = store.peek().clone();
+        let prefix = prefix.peek().clone();
+        let path = local_path.peek().clone();
+        let spec = JobSpec {
+            kind: JobKind::Download,
+            connection: connection.peek().clone(),
+            title: format!("/{prefix} as zip"),
+            unit: JobUnit::Bytes,
+            total: None,
+        };
+        jobs.submit(spec, move |job| {
+            let store = store.clone();
+            let prefix = prefix.clone();
+            let path = path.clone();
+            async move {
+                download_prefix_zip(&store, &prefix, &path, move |p| {
+                    job.set_total(Some(p.bytes_total));
+                    job.progress(p.bytes_done);
+                })
+                .await?;
+                Ok(JobOutput::None)
+            }
+        });
+        on_complete.call(());
+    };
+
+    rsx! {
+        Modal {
+            children: rsx! {
+                div {
+                    class: "box",
+
+                    h2 {
+                        class: "title is-5",
+                        "Download /{prefix} as zip"
+                    }
+
+                    div {
+                        class: "field",
+                        label { class: "label", "Output Path" }
+                        div {
+                            class: "control",
+                            input {
+                                class: "input",
+                                r#type: "text",
+                                value: "{local_path}",
+                                onchange: move |e| local_path.set(e.value())
+                            }
+                        }
+                    }
+
+                    div {
+                        class: "buttons",
+                        button {
+                            class: "button is-primary",
+                            onclick: start,
+                            "Download"
+                        }
+                        button {
+                            class: "button",
+                            onclick: move |_| on_cancel.call(()),
+                            "Cancel"
+                        }
+                    }
+                }
+            },
+            on_close: move |_| on_cancel.call(())
+        }
+    }
+}
@@ -0,0 +1,240 @@
+use std::sync::Arc;
+
+use base64::Engine as _;
+use bytes::Bytes;
+use dioxus::prelude::*;
+use futures::TryStreamExt as _;
+use objstore::{DynObjStore, MimeInference, ObjectMeta};
+
+use crate::cmp::{object::helpers::human_size, util::loader::Spinner};
+
+/// Images and PDFs larger than this are only shown as a hex dump.
+const MAX_MEDIA_SIZE: u64 = 20 * 1024 * 1024;
+/// Number of bytes shown in the hex dump.
+const HEX_PREVIEW_SIZE: u64 = 64 * 1024;
+const HEX_LINE_WIDTH: usize = 16;
+
+#[derive(Clone, Debug, PartialEq)]
+enum PreviewKind {
+    Image { mime: String },
+    Pdf,
+    Hex,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct Preview {
+    kind: PreviewKind,
+    data: Bytes,
+    /// Whether `data` holds the full object.
+    complete: bool,
+}
+
+/// Mime type of an object, from its metadata or the key extension.
+fn object_mime(meta: &ObjectMeta) -> Option<String> {
+    let mime = meta
+        .mime_type
+        .clone()
+        .or_else(|| MimeInference::Extension.infer(&meta.key, None))?;
+    Some(
+        mime.split(';')
+            .next()
+            .unwrap_or_default()
+            .trim()
+            .to_string(),
+    )
+}
+
+/// Mime type detected from the leading bytes of the content.
+fn sniff_mime(data: &[u8]) -> Option<&'static str> {
+    if data.starts_with(b"\x89PNG\r\n\x1a\n") {
+        Some("image/png")
+    } else if data.starts_with(b"\xff\xd8\xff") {
+        Some("image/jpeg")
+    } else if data.starts_with(b"GIF87a") || data.starts_with(b"GIF89a") {
+        Some("image/gif")
+    } else if data.len() >= 12 && data.starts_with(b"RIFF") && &data[8..12] == b"WEBP" {
+        Some("image/webp")
+    } else if data.starts_with(b"BM") {
+        Some("image/bmp")
+    } else if data.starts_with(b"%PDF-") {
+        Some("application/pdf")
+    } else {
+        None
+    }
+}
+
+/// Choose the view for an object, preferring the detected content type over
+/// the declared mime type.
+fn preview_kind(declared: Option<&str>, data: &[u8]) -> PreviewKind {
+    match sniff_mime(data).or(declared) {
+        Some("application/pdf") => PreviewKind::Pdf,
+        Some(mime) if mime.starts_with("image/") => PreviewKind::Image {
+            mime: mime.to_string(),
+        },
+        _ => PreviewKind::Hex,
+    }
+}
+
+/// Read up to `limit` bytes from the start of an object.
+///
+/// Only the required part of the object is streamed.
+async fn read_head(
+    store: &DynObjStore,
+    key: &str,
+    limit: u64,
+) -> Result<(Bytes, bool), anyhow::Error> {
+    use anyhow::Context as _;
+
+    let mut stream = store.get_stream(key).await?.context("object not found")?;
+    let mut data = Vec::new();
+    while let Some(chunk) = stream.try_next().await? {
+        let remaining = limit as usize - data.len();
+        if chunk.len() >= remaining {
+            data.extend_from_slice(&chunk[..remaining]);
+            // Complete only if this was the last chunk.
+            let complete = chunk.len() == remaining && stream.try_next().await?.is_none();
+            return Ok((data.into(), complete));
+        }
+        data.extend_from_slice(&chunk);
+    }
+    Ok((data.into(), true))
+}
+
+async fn load_preview(store: &DynObjStore, meta: &ObjectMeta) -> Result<Preview, anyhow::Error> {
+    let declared = object_mime(meta);
+    let fits_media = meta.size.is_some_and(|size| size <= MAX_MEDIA_SIZE);
+    let declared_media = matches!(
+        preview_kind(declared.as_deref(), &[]),
+        PreviewKind::Image { .. } | PreviewKind::Pdf
+    );
+
+    let limit = if declared_media && fits_media {
+        MAX_MEDIA_SIZE
+    } else {
+        HEX_PREVIEW_SIZE
+    };
+    let (mut data, mut complete) = read_head(store, &meta.key, limit).await?;
+
+    let mut kind = preview_kind(declared.as_deref(), &data);
+    if kind != PreviewKind::Hex && !complete {
+        if limit < MAX_MEDIA_SIZE && fits_media {
+            // Detected from the content, load the full object.
+            (data, complete) = read_head(store, &meta.key, MAX_MEDIA_SIZE).await?;
+        }
+        if !complete {
+            kind = PreviewKind::Hex;
+        }
+    }
+    if kind == PreviewKind::Hex {
+        data.truncate(HEX_PREVIEW_SIZE as usize);
+    }
+
+    Ok(Preview {
+        kind,
+        data,
+        complete,
+    })
+}
+
+fn data_url(mime: &str, data: &[u8]) -> String {
+    let encoded = base64::engine::general_purpose::STANDARD.encode(data);
+    format!("data:{mime};base64,{encoded}")
+}
+
+/// Format a line of the hex dump: offset, hex bytes and printable characters.
+fn hex_line(offset: usize, bytes: &[u8]) -> String {
+    let mut hex = String::with_capacity(HEX_LINE_WIDTH * 3 + 1);
+    for index in 0..HEX_LINE_WIDTH {
+        match bytes.get(index) {
+            Some(byte) => hex.push_str(&format!("{byte:02x} ")),
+            None => hex.push_str("   "),
+        }
+        if index == HEX_LINE_WIDTH / 2 - 1 {
+            hex.push(' ');
+        }
+    }
+    let ascii = bytes
+        .iter()
+        .map(|byte| {
+            if byte.is_ascii_graphic() || *byte == b' ' {
+                *byte as char
+            } else {
+                '.'
+            }
+        })
+        .collect::<String>();
+    format!("{offset:08x}  {hex} |{ascii}|")
+}
+
+fn hex_dump(data: &[u8]) -> String {
+    data.chunks(HEX_LINE_WIDTH)
+        .enumerate()
+        .map(|(index, bytes)| hex_line(index * HEX_LINE_WIDTH, bytes))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Preview of non-text objects.
+///
+/// Images are rendered, PDFs embedded, and other content is shown as a hex
+/// dump of the first bytes. The view is chosen from the content's magic bytes,
+/// falling back to the mime type. Large objects are not fully downloaded.
+#[component]
+pub fn ObjectPreview(
+    store: ReadOnlySignal<DynObjStore>,
+    meta: ReadOnlySignal<Arc<ObjectMeta>>,
+) -> Element {
+    let preview = use_resource(move || async move {
+        load_preview(&store.read(), &meta.read())
+            .await
+            .map_err(|err| format!("{err:#}"))
+    });
+
+    let content = match &*preview.read() {
+        None => rsx! { Spinner {} },
+        Some(Err(err)) => rsx! {
+            p { class: "help is-danger", "Failed to load preview: {err}" }
+        },
+        Some(Ok(preview)) => match &preview.kind {
+            PreviewKind::Image { mime } => rsx! {
+                figure {
+                    class: "image",
+                    img {
+                        src: "{data_url(mime, &preview.data)}",
+                        alt: "{meta.read().key}",
+                        max_height: "70vh",
+                        object_fit: "contain",
+                    }
+                }
+            },
+            PreviewKind::Pdf => rsx! {
+                embed {
+                    src: "{data_url(\"application/pdf\", &preview.data)}",
+                    r#type: "application/pdf",
+                    width: "100%",
+                    height: "600px",
+                }
+            },
+            PreviewKind::Hex => rsx! {
+                if !preview.complete {
+                    p {
+                        class: "help mb-2",
+                        "Showing the first {human_size(preview.data.len() as u64)}."
+                    }
+                }
+                pre {
+                    max_height: "60vh",
+                    overflow: "auto",
+                    "{hex_dump(&preview.data)}"
+                }
+            },
+        },
+    };
+
+    rsx! {
+        div {
+            class: "box",
+            {content}
+        }
+    }
+}
@@ -0,0 +1,262 @@
+use dioxus::prelude::*;
+use dioxus_bulma::{Modal, Notification};
+use objstore::DynObjStore;
+
+use crate::{
+    context::{use_config_store, use_providers, use_stores},
+    store::{
+        jobs::{use_jobs, JobKind, JobOutput, JobSpec, JobUnit},
+        transfer_objects, TransferMode, TransferSource,
+    },
+};
+
+/// Modal for copying or moving objects and prefixes, within the connection or
+/// to another one.
+///
+/// The transfer runs as a background job.
+#[component]
+pub fn TransferModal(
+    store: ReadOnlySignal<DynObjStore>,
+    /// Name of the source connection.
+    connection: ReadOnlySignal<String>,
+    sources: ReadOnlySignal<Vec<TransferSource>>,
+    on_complete: EventHandler<()>,
+    on_cancel: EventHandler<()>,
+) -> Element {
+    let jobs = use_jobs();
+    let mut error = use_signal::<Option<String>>(|| None);
+    let mut target = use_signal(String::new);
+    let mut dest_prefix = use_signal(String::new);
+    let mut mode = use_signal(|| TransferMode::Copy);
+
+    let config_store = use_config_store();
+    let builder = use_providers();
+    let stores = use_stores();
+    let connections = use_resource({
+        let config_store = config_store.clone();
+        move || {
+            let config_store = config_store.clone();
+            async move {
+                let mut names = config_store
+                    .get()
+                    .load_connections()
+                    .await?
+                    .connections
+                    .into_iter()
+                    .map(|con| con.config.name)
+                    .collect::<Vec<_>>();
+                // Connections opened without saving.
+                for name in stores.stores.read_unchecked().keys() {
+                    if !names.contains(name) {
+                        names.push(name.clone());
+                    }
+                }
+                names.sort();
+                Ok::<_, anyhow::Error>(names)
+            }
+        }
+    });
+
+    let mut start = move || {
+        let name = target.peek().clone();
+        if name.is_empty() {
+            error.set(Some("Select a target connection".to_string()));
+            return;
+        }
+        let mut prefix = dest_prefix
+            .peek()
+            .trim()
+            .trim_start_matches('/')
+            .to_string();
+        if !prefix.is_empty() && !prefix.ends_with('/') {
+            prefix.push('/');
+        }
+
+        let src = store.peek().clone();
+        let sources = sources.peek().clone();
+        let mode = *mode.peek();
+        let title = match sources.as_slice() {
+            [source] => format!("{} to {name}", source.name()),
+            _ => format!("{} items to {name}", sources.len()),
+        };
+        let spec = JobSpec {
+            kind: match mode {
+                TransferMode::Copy => JobKind::Copy,
+                TransferMode::Move => JobKind::Move,
+            },
+            connection: connection.peek().clone(),
+            title,
+            unit: JobUnit::Objects,
+            total: None,
+        };
+        // Transfers within the connection use server-side copies.
+        let same_connection = name == *connection.peek();
+        let config_store = config_store.clone();
+        let builder = builder.clone();
+        jobs.submit(spec, move |job| {
+            let src = src.clone();
+            let sources = sources.clone();
+            let name = name.clone();
+            let prefix = prefix.clone();
+            let config_store = config_store.clone();
+            let builder = builder.clone();
+            async move {
+                let dest = if same_connection {
+                    None
+                } else {
+                    Some(
+                        stores
+                            .resolve(&name, config_store.get(), &builder)
+                            .await?
+                            .store,
+                    )
+                };
+                let progress =
+                    transfer_objects(&src, dest.as_ref(), &sources, &prefix, mode, move |p| {
+                        job.progress(p.transferred);
+                        if let Some(current) = &p.current {
+                            job.set_current(current.clone());
+                        }
+                    })
+                    .await?;
+                if let Some((key, err)) = progress.failed.first() {
+                    anyhow::bail!(
+                        "{} object(s) could not be transferred, first failure: {key}: {err}",
+                        progress.failed.len()
+                    );
+                }
+                // Copies to other connections leave the source unchanged.
+                Ok(match mode {
+                    TransferMode::Copy if !same_connection => JobOutput::None,
+                    _ => JobOutput::Changed,
+                })
+            }
+        });
+        on_complete.call(());
+    };
+
+    rsx! {
+        Modal {
+            children: rsx! {
+                div {
+                    class: "box",
+
+                    h2 {
+                        class: "title is-5",
+                        "Copy or move"
+                    }
+
+                    ul {
+                        class: "mb-4",
+                        for source in sources.read().iter() {
+                            li {
+                                match source {
+                                    TransferSource::Object(key) => rsx! { "{key}" },
+                                    TransferSource::Prefix(prefix) => rsx! { "{prefix} (folder)" },
+                                }
+                            }
+                        }
+                    }
+
+                    div {
+                        class: "field",
+                        label { class: "label", "Target Connection" }
+                        div {
+                            class: "control",
+                            div {
+                                class: "select is-fullwidth",
+                                select {
+                                    value: "{target}",
+                                    onchange: move |e| target.set(e.value()),
+                                    option { value: "", "Select a connection" }
+                                    match &*connections.read() {
+                                        Some(Ok(names)) => rsx! {
+                                            for name in names.iter() {
+                                                if *name == *connection.read() {
+                                                    option { value: "{name}", "{name} (this connection)" }
+                                                } else {
+                                                    option { value: "{name}", "{name}" }
+                                                }
+                                            }
+                                        },
+                                        _ => rsx! {},
+                                    }
+                                }
+                            }
+                        }
+                        if let Some(Err(err)) = &*connections.read() {
+                            p { class: "help is-danger", "Failed to load connections: {err}" }
+                        }
+                    }
+
+                    div {
+                        class: "field",
+                        label { class: "label", "Target Prefix" }
+                        div {
+                            class: "control",
+                            input {
+                                class: "input",
+                                r#type: "text",
+                                placeholder: "path/to/folder/",
+                                value: "{dest_prefix}",
+                                onchange: move |e| dest_prefix.set(e.value())
+                            }
+                        }
+                    }
+
+                    div {
+                        class: "field",
+                        div {
+                            class: "control",
+                            label {
+                                class: "radio",
+                                input {
+                                    r#type: "radio",
+                                    name: "transfer-mode",
+                                    checked: mode() == TransferMode::Copy,
+                                    onchange: move |_| mode.set(TransferMode::Copy),
+                                }
+                                " Copy"
+                            }
+                            label {
+                                class: "radio",
+                                input {
+                                    r#type: "radio",
+                                    name: "transfer-mode",
+                                    checked: mode() == TransferMode::Move,
+                                    onchange: move |_| mode.set(TransferMode::Move),
+                                }
+                                " Move"
+                            }
+                        }
+                    }
+
+                    if let Some(err) = error() {
+                        Notification {
+                            color: dioxus_bulma::Color::Danger,
+                            "{err}"
+                        }
+                    }
+
+                    div {
+                        class: "buttons",
+                        button {
+                            class: "button is-primary",
+                            onclick: move |_| start(),
+                            match mode() {
+                                TransferMode::Copy => "Copy",
+                                TransferMode::Move => "Move",
+                            }
+                        }
+                        button {
+                            class: "button",
+                            onclick: move |_| on_cancel.call(()),
+                            "Cancel"
+                        }
+                    }
+                }
+            },
+            on_close: move |_| on_cancel.call(())
+        }
+    }
+}
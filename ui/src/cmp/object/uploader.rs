@@ -0,0 +1,164 @@
+use std::{path::PathBuf, sync::Arc};
+
+use dioxus::prelude::*;
+use dioxus_bulma::Notification;
+use objstore::DynObjStore;
+
+use crate::store::{
+    collect_upload_files,
+    jobs::{use_jobs, JobKind, JobOutput, JobSpec, JobUnit},
+    upload_file,
+};
+
+/// Paths of the files selected in a file input or dropped onto a drop zone.
+fn event_paths(files: Option<Arc<dyn FileEngine>>) -> Vec<PathBuf> {
+    files
+        .map(|engine| engine.files().into_iter().map(PathBuf::from).collect())
+        .unwrap_or_default()
+}
+
+/// Drop zone and file pickers for uploading many files into the current prefix.
+///
+/// Each file is queued as an upload job and streamed into the store in the
+/// background; progress is shown in the transfers panel. Dropped or selected
+/// directories are uploaded recursively, keeping their folder structure as
+/// key prefixes.
+#[component]
+pub fn Uploader(
+    store: ReadOnlySignal<DynObjStore>,
+    connection: ReadOnlySignal<String>,
+    base_path: ReadOnlySignal<String>,
+    on_close: EventHandler<()>,
+) -> Element {
+    let jobs = use_jobs();
+    let mut error = use_signal::<Option<String>>(|| None);
+    let mut dragging = use_signal(|| false);
+    let mut queued = use_signal(|| 0usize);
+
+    let mut add_paths = move |paths: Vec<PathBuf>| {
+        if paths.is_empty() {
+            return;
+        }
+        let files = match collect_upload_files(&paths, &base_path.read()) {
+            Ok(files) => files,
+            Err(err) => {
+                error.set(Some(err.to_string()));
+                return;
+            }
+        };
+        error.set(None);
+        queued += files.len();
+
+        for file in files {
+            let store = store.peek().clone();
+            let spec = JobSpec {
+                kind: JobKind::Upload,
+                connection: connection.peek().clone(),
+                title: file.key.clone(),
+                unit: JobUnit::Bytes,
+                total: Some(file.size),
+            };
+            jobs.submit(spec, move |job| {
+                let store = store.clone();
+                let file = file.clone();
+                async move {
+                    let meta = upload_file(&store, &file, move |n| job.progress(n)).await?;
+                    Ok(JobOutput::Created(meta))
+                }
+            });
+        }
+    };
+
+    rsx! {
+        div {
+            class: "box",
+
+            h2 {
+                class: "title is-5",
+                "Upload to /{base_path}"
+            }
+
+            div {
+                class: "box has-text-centered",
+                class: if dragging() { "has-background-primary-light" } else { "" },
+                border: "2px dashed #dbdbdb",
+                ondragover: move |e| {
+                    e.prevent_default();
+                    dragging.set(true);
+                },
+                ondragleave: move |_| dragging.set(false),
+                ondrop: move |e| {
+                    e.prevent_default();
+                    dragging.set(false);
+                    add_paths(event_paths(e.files()));
+                },
+
+                p {
+                    class: "mb-3",
+                    "Drop files or folders here"
+                }
+
+                div {
+                    class: "buttons is-centered",
+
+                    div {
+                        class: "file",
+                        label {
+                            class: "file-label",
+                            input {
+                                class: "file-input",
+                                r#type: "file",
+                                multiple: true,
+                                onchange: move |e| add_paths(event_paths(e.files())),
+                            }
+                            span {
+                                class: "file-cta",
+                                span { class: "file-label", "Choose files…" }
+                            }
+                        }
+                    }
+
+                    div {
+                        class: "file",
+                        label {
+                            class: "file-label",
+                            input {
+                                class: "file-input",
+                                r#type: "file",
+                                directory: true,
+                                onchange: move |e| add_paths(event_paths(e.files())),
+                            }
+                            span {
+                                class: "file-cta",
+                                span { class: "file-label", "Choose folder…" }
+                            }
+                        }
+                    }
+                }
+            }
+
+            if let Some(err) = error() {
+                Notification {
+                    color: dioxus_bulma::Color::Danger,
+                    "{err}"
+                }
+            }
+
+            if queued() > 0 {
+                p {
+                    class: "help mb-3",
+                    "{queued} file(s) queued. Progress is shown in the transfers panel."
+                }
+            }
+
+            div {
+                class: "buttons",
+                button {
+                    class: "button",
+                    onclick: move |_| on_close.call(()),
+                    "Close"
+                }
+            }
+        }
+    }
+}
@@ -1,12 +1,23 @@
 use dioxus::prelude::*;
-use objstore::ObjectMeta;
+use objstore::{DynObjStore, ObjectMeta};
 use std::sync::Arc;
 
-use crate::cmp::object::helpers::{object_created, object_modified, object_size};
+use crate::cmp::object::{
+    editor::{is_editable, ObjectEditor},
+    helpers::{object_created, object_modified, object_size},
+    preview::ObjectPreview,
+};
 
 /// Component for displaying metadata of an object.
+///
+/// Text-like objects can also be viewed and edited, other objects are shown
+/// in a preview.
 #[component]
-pub fn ObjectViewer(meta: Arc<ObjectMeta>) -> Element {
+pub fn ObjectViewer(
+    store: DynObjStore,
+    meta: Arc<ObjectMeta>,
+    on_saved: EventHandler<Arc<ObjectMeta>>,
+) -> Element {
     let now = time::OffsetDateTime::now_utc();
 
     rsx! {
@@ -65,5 +76,18 @@ pub fn ObjectViewer(meta: Arc<ObjectMeta>) -> Element {
                 }
             }
         }
+
+        if is_editable(&meta) {
+            ObjectEditor {
+                store: store.clone(),
+                meta: meta.clone(),
+                on_saved,
+            }
+        } else {
+            ObjectPreview {
+                store: store.clone(),
+                meta: meta.clone(),
+            }
+        }
     }
 }
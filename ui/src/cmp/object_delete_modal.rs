@@ -1,33 +1,43 @@
 use dioxus::prelude::*;
 use dioxus_bulma::{Modal, Notification};
-use futures::StreamExt as _;
 use objstore::DynObjStore;
 
-use crate::cmp::util::loader::LoadState;
+use crate::store::jobs::{use_jobs, JobKind, JobOutput, JobSpec, JobUnit};
 
+/// Confirmation for deleting an object.
+///
+/// The deletion runs as a background job.
 #[component]
 pub fn ObjectDeleteModal(
     store: ReadOnlySignal<DynObjStore>,
+    connection: ReadOnlySignal<String>,
     object_key: ReadOnlySignal<String>,
     on_complete: EventHandler<()>,
     on_cancel: EventHandler<()>,
 ) -> Element {
-    let mut state = use_signal::<LoadState<()>>(|| LoadState::Idle);
+    let jobs = use_jobs();
 
-    let tx = use_coroutine::<(), _, _>(move |mut rx| async move {
-        while let Some(_) = rx.next().await {
-            state.set(LoadState::Loading);
-            let new_state = match store.read_unchecked().delete(&object_key()).await {
-                Ok(()) => {
-                    on_complete.call(());
-                    LoadState::Loaded(Ok(()))
-                }
-                Err(e) => LoadState::Loaded(Err(e.to_string())),
-            };
-
-            state.set(new_state);
-        }
-    });
+    let delete = move |_| {
+        let store = store.peek().clone();
+        let key = object_key.peek().clone();
+        let spec = JobSpec {
+            kind: JobKind::Delete,
+            connection: connection.peek().clone(),
+            title: key.clone(),
+            unit: JobUnit::Objects,
+            total: Some(1),
+        };
+        jobs.submit(spec, move |job| {
+            let store = store.clone();
+            let key = key.clone();
+            async move {
+                store.delete(&key).await?;
+                job.progress(1);
+                Ok(JobOutput::Deleted(key))
+            }
+        });
+        on_complete.call(());
+    };
 
     rsx! {
         Modal {
@@ -49,14 +59,7 @@ pub fn ObjectDeleteModal(
 
                         button {
                             class: "button is-danger",
-                            class: if let LoadState::Loading = &*state.read() {
-                                "is-loading"
-                            } else {
-                                ""
-                            },
-                            onclick: move |_| {
-                                tx.send(());
-                            },
+                            onclick: delete,
                             "Delete"
                         }
 
@@ -1,11 +1,10 @@
 use anyhow::{bail, Context};
 use dioxus::prelude::*;
-use dioxus_bulma::{Color, Notification};
 use objstore_config::ConnectionConfig;
 use objstore_s3_light::{S3ObjStoreConfig, UrlStyle};
 use url::Url;
 
-use crate::cmp::{s3::ConnectionPersistence, util::form::FormSubmit};
+use crate::cmp::util::form::{ConnectionFormActions, ConnectionPersistence, FormSubmit};
 
 #[component]
 pub fn S3Form(
@@ -54,6 +53,12 @@ pub fn S3Form(
             .and_then(|c| c.peek_unchecked().path_prefix.clone())
             .unwrap_or_default()
     });
+    let mut value_proxy = use_signal(|| {
+        initial_value
+            .as_ref()
+            .and_then(|c| c.peek_unchecked().proxy.as_ref().map(|url| url.to_string()))
+            .unwrap_or_default()
+    });
     let mut value_path_style = use_signal(|| {
         initial_value
             .as_ref()
@@ -97,6 +102,19 @@ pub fn S3Form(
                 }
             };
 
+            let proxy = {
+                let proxy = value_proxy().trim().to_string();
+                if proxy.is_empty() {
+                    None
+                } else {
+                    Some(
+                        proxy
+                            .parse()
+                            .with_context(|| format!("invalid proxy url '{}'", proxy))?,
+                    )
+                }
+            };
+
             let path_style = value_path_style();
 
             let s = objstore_s3_light::S3ObjStoreConfig {
@@ -104,6 +122,17 @@ pub fn S3Form(
                 bucket: value_bucket(),
                 region,
                 path_style,
+                fetch_metadata_after_put: initial_value
+                    .as_ref()
+                    .map_or(true, |v| v.peek_unchecked().fetch_metadata_after_put),
+                delete_concurrency: initial_value.as_ref().map_or(
+                    S3ObjStoreConfig::DEFAULT_DELETE_CONCURRENCY,
+                    |v| v.peek_unchecked().delete_concurrency,
+                ),
+                read_chunk_size: initial_value
+                    .as_ref()
+                    .and_then(|v| v.peek_unchecked().read_chunk_size),
+                proxy,
                 key: value_access_key_id(),
                 secret: value_secret_access_key(),
                 token: None,
@@ -115,6 +144,7 @@ pub fn S3Form(
                 name: value_name(),
                 uri: s.build_uri()?,
                 description: None,
+                wrappers: Default::default(),
             };
 
             Ok(config)
@@ -131,12 +161,6 @@ pub fn S3Form(
         }
     });
 
-    let (is_loading, submit_error) = match &*status.read() {
-        FormSubmit::Idle => (false, None),
-        FormSubmit::Loading => (true, None),
-        FormSubmit::Error(err) => (false, Some(err.clone())),
-    };
-
     rsx! {
         form {
             onsubmit: move |e| {
@@ -339,58 +363,36 @@ pub fn S3Form(
                 }
             }
 
-            if let Some(errors) = errors() {
-                Notification {
-                    color: Color::Danger,
-
-                    ul {
-                        class: "content",
+            div {
+                class: "field",
 
-                        for error in errors.iter() {
-                            li {
-                                "{error}"
-                            }
-                        }
-                    }
-                }
-            }
-            if let Some(err) = &submit_error {
-                Notification {
-                    color: Color::Danger,
-                    "{err:#?}"
+                label {
+                    class: "label",
+                    "Proxy (optional)"
                 }
-            }
 
-            div {
-                class: "buttons is-large",
-
-                button {
-                    class: "button is-primary",
-                    class: if is_loading { "is-loading" } else { "" },
-                    r#type: "submit",
-                    onclick: move |_| {
-                        submit.call(ConnectionPersistence::Persistent);
-                    },
-                    "Save and connect"
-                }
+                div {
+                    class: "control",
+                    input {
+                        class: "input",
+                        r#type: "text",
+                        placeholder: "http://proxy:3128 or socks5://proxy:1080",
+                        value: "{value_proxy}",
+                        onchange: move |e| value_proxy.set(e.value()),
+                    }
 
-                button {
-                    class: "button",
-                    class: if is_loading { "is-loading" } else { "" },
-                    r#type: "submit",
-                    onclick: move |_| {
-                        submit.call(ConnectionPersistence::Temporary);
-                    },
-                    "Open without saving"
+                    span {
+                        class: "help",
+                        "Defaults to the HTTPS_PROXY / HTTP_PROXY environment variables."
+                    }
                 }
+            }
 
-                button {
-                    class: "button",
-                    onclick: move |_| {
-                        on_cancel.call(());
-                    },
-                    "Cancel"
-                }
+            ConnectionFormActions {
+                status,
+                errors,
+                submit,
+                on_cancel,
             }
         }
     }
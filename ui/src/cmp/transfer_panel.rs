@@ -0,0 +1,174 @@
+use dioxus::prelude::*;
+
+use crate::{
+    cmp::object::helpers::human_size,
+    store::jobs::{use_jobs, Job, JobStatus, JobUnit},
+};
+
+/// Collapsible panel listing the background jobs and their history.
+#[component]
+pub fn TransferPanel() -> Element {
+    let jobs = use_jobs();
+    let mut expanded = use_signal(|| false);
+
+    let list = jobs.list();
+    if list.is_empty() {
+        return VNode::empty();
+    }
+    let active = list.iter().filter(|job| job.status.is_active()).count();
+    let failed = list
+        .iter()
+        .filter(|job| matches!(job.status, JobStatus::Failed(_)))
+        .count();
+
+    rsx! {
+        div {
+            class: "box p-0",
+            position: "fixed",
+            right: "1rem",
+            bottom: "1rem",
+            width: "28rem",
+            max_width: "calc(100vw - 2rem)",
+            z_index: "30",
+
+            div {
+                class: "is-flex is-align-items-center is-justify-content-space-between p-3",
+                cursor: "pointer",
+                onclick: move |_| expanded.toggle(),
+
+                strong {
+                    if active > 0 {
+                        "Transfers ({active} active)"
+                    } else {
+                        "Transfers"
+                    }
+                }
+                if failed > 0 {
+                    span { class: "tag is-danger", "{failed} failed" }
+                }
+            }
+
+            if expanded() {
+                div {
+                    max_height: "50vh",
+                    overflow_y: "auto",
+                    border_top: "1px solid #dbdbdb",
+
+                    for job in list.iter().rev() {
+                        JobRow { key: "{job.id}", job: job.clone() }
+                    }
+                }
+
+                div {
+                    class: "p-3",
+                    border_top: "1px solid #dbdbdb",
+                    button {
+                        class: "button is-small",
+                        disabled: active == list.len(),
+                        onclick: move |_| jobs.clear_finished(),
+                        "Clear finished"
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[component]
+fn JobRow(job: Job) -> Element {
+    let jobs = use_jobs();
+    let id = job.id;
+
+    let amount = |value: u64| match job.unit {
+        JobUnit::Bytes => human_size(value),
+        JobUnit::Objects => format!("{value} objects"),
+    };
+    let transferred = match job.total {
+        Some(total) => format!("{} / {}", amount(job.transferred), amount(total)),
+        None => amount(job.transferred),
+    };
+    let speed = job
+        .speed()
+        .filter(|_| job.status == JobStatus::Running)
+        .map(|speed| format!("{}/s", amount(speed as u64)));
+    let (status_class, status_label) = match &job.status {
+        JobStatus::Queued => ("is-light", "Queued"),
+        JobStatus::Running => ("is-info", "Running"),
+        JobStatus::Done => ("is-success", "Done"),
+        JobStatus::Failed(_) => ("is-danger", "Failed"),
+        JobStatus::Cancelled => ("is-warning", "Cancelled"),
+    };
+
+    rsx! {
+        div {
+            class: "p-3",
+            border_bottom: "1px solid #f5f5f5",
+
+            div {
+                class: "is-flex is-align-items-center is-justify-content-space-between",
+                div {
+                    overflow: "hidden",
+                    text_overflow: "ellipsis",
+                    white_space: "nowrap",
+                    title: "{job.title}",
+                    span { class: "tag is-light mr-2", "{job.kind.label()}" }
+                    "{job.title}"
+                }
+                span { class: "tag ml-2 {status_class}", "{status_label}" }
+            }
+
+            p {
+                class: "help",
+                "{job.connection}: {transferred}"
+                if let Some(speed) = speed {
+                    ", {speed}"
+                }
+                if job.attempts > 1 {
+                    ", attempt {job.attempts}"
+                }
+            }
+
+            if job.status == JobStatus::Running {
+                if let Some(total) = job.total.filter(|total| *total > 0) {
+                    progress {
+                        class: "progress is-small is-primary mb-1",
+                        value: "{job.transferred}",
+                        max: "{total}",
+                    }
+                } else {
+                    progress { class: "progress is-small is-primary mb-1" }
+                }
+                if let Some(current) = &job.current {
+                    p {
+                        class: "help",
+                        overflow: "hidden",
+                        text_overflow: "ellipsis",
+                        white_space: "nowrap",
+                        "{current}"
+                    }
+                }
+            }
+
+            if let JobStatus::Failed(err) = &job.status {
+                p { class: "help is-danger", "{err}" }
+            }
+
+            div {
+                class: "buttons are-small mt-1",
+                if job.status.is_active() {
+                    button {
+                        class: "button",
+                        onclick: move |_| jobs.cancel(id),
+                        "Cancel"
+                    }
+                } else if job.status != JobStatus::Done && jobs.can_retry(id) {
+                    button {
+                        class: "button",
+                        onclick: move |_| jobs.retry(id),
+                        "Retry"
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,271 @@
+use dioxus::prelude::*;
+use dioxus_bulma::{Color, Notification};
+use objstore::ObjectMeta;
+
+use crate::{
+    cmp::{
+        object::helpers::{human_size, object_modified, object_size},
+        util::loader::{LoadState, Spinner},
+    },
+    context::ActiveStore,
+    router::Route,
+    store::usage::{largest_objects, recent_objects, scan_usage, UsageReport},
+};
+
+/// Usage statistics of a connection.
+///
+/// Shows the object count and total size, broken down by top-level prefix,
+/// and the largest and most recently modified objects.
+#[component]
+pub fn UsageDashboard(store: ActiveStore) -> Element {
+    let mut report = use_signal::<Option<UsageReport>>(|| None);
+    let mut state = use_signal::<LoadState<()>>(|| LoadState::Idle);
+
+    let scan = use_coroutine::<(), _, _>({
+        let store = store.store.clone();
+        move |mut rx| {
+            let store = store.clone();
+            async move {
+                use futures::StreamExt as _;
+                while rx.next().await.is_some() {
+                    state.set(LoadState::Loading);
+                    report.set(None);
+                    let res =
+                        scan_usage(&store, |progress| report.set(Some(progress.clone()))).await;
+                    match res {
+                        Ok(done) => {
+                            report.set(Some(done));
+                            state.set(LoadState::Loaded(Ok(())));
+                        }
+                        Err(err) => state.set(LoadState::Loaded(Err(format!("{err:#}")))),
+                    }
+                }
+            }
+        }
+    });
+    use_hook(|| scan.send(()));
+
+    let mut largest = use_resource({
+        let store = store.store.clone();
+        move || {
+            let store = store.clone();
+            async move {
+                largest_objects(&store)
+                    .await
+                    .map_err(|err| format!("{err:#}"))
+            }
+        }
+    });
+    let mut recent = use_resource({
+        let store = store.store.clone();
+        move || {
+            let store = store.clone();
+            async move {
+                recent_objects(&store)
+                    .await
+                    .map_err(|err| format!("{err:#}"))
+            }
+        }
+    });
+
+    let scanning = matches!(&*state.read(), LoadState::Loading);
+    let now = time::OffsetDateTime::now_utc();
+    let name = store.config.config.name.clone();
+
+    rsx! {
+        div {
+            h1 {
+                class: "title is-3",
+                "{name}: usage"
+            }
+
+            div {
+                class: "buttons",
+                Link {
+                    to: Route::Browser {
+                        store: name.clone(),
+                        prefix: String::new(),
+                    },
+                    class: "button",
+                    "Browse"
+                }
+                button {
+                    class: "button",
+                    class: if scanning { "is-loading" } else { "" },
+                    onclick: move |_| {
+                        scan.send(());
+                        largest.restart();
+                        recent.restart();
+                    },
+                    "Rescan"
+                }
+            }
+
+            if let LoadState::Loaded(Err(err)) = &*state.read() {
+                Notification {
+                    color: Color::Danger,
+                    "Failed to compute usage: {err}"
+                }
+            }
+
+            match report() {
+                None => rsx! { Spinner {} },
+                Some(report) => rsx! {
+                    UsageSummary { report, scanning }
+                },
+            }
+
+            div {
+                class: "columns mt-4",
+                div {
+                    class: "column",
+                    div {
+                        class: "box",
+                        h2 { class: "title is-5", "Largest objects" }
+                        ObjectList { objects: largest.read().clone(), now }
+                    }
+                }
+                div {
+                    class: "column",
+                    div {
+                        class: "box",
+                        h2 { class: "title is-5", "Recently modified" }
+                        ObjectList { objects: recent.read().clone(), now }
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[component]
+fn UsageSummary(report: UsageReport, scanning: bool) -> Element {
+    let total_bytes = report.total.bytes.max(1) as f64;
+    let share = move |bytes: u64| format!("{:.1}", bytes as f64 / total_bytes * 100.0);
+
+    rsx! {
+        div {
+            class: "box",
+
+            nav {
+                class: "level",
+                div {
+                    class: "level-item has-text-centered",
+                    div {
+                        p { class: "heading", "Objects" }
+                        p { class: "title", "{report.total.objects}" }
+                    }
+                }
+                div {
+                    class: "level-item has-text-centered",
+                    div {
+                        p { class: "heading", "Total size" }
+                        p { class: "title", "{human_size(report.total.bytes)}" }
+                    }
+                }
+                div {
+                    class: "level-item has-text-centered",
+                    div {
+                        p { class: "heading", "Top-level prefixes" }
+                        p { class: "title", "{report.prefixes_total}" }
+                    }
+                }
+            }
+
+            if scanning {
+                p {
+                    class: "help",
+                    "Scanned {report.prefixes.len()} of {report.prefixes_total} prefixes"
+                }
+                progress {
+                    class: "progress is-small is-primary",
+                    value: "{report.progress()}",
+                    max: "1",
+                }
+            }
+
+            table {
+                class: "table is-fullwidth is-narrow",
+                thead {
+                    tr {
+                        th { "Prefix" }
+                        th { "Objects" }
+                        th { "Size" }
+                        th { "Share" }
+                    }
+                }
+                tbody {
+                    for (prefix, usage) in report.prefixes.iter() {
+                        tr {
+                            key: "{prefix}",
+                            td { "{prefix}" }
+                            td { "{usage.objects}" }
+                            td { "{human_size(usage.bytes)}" }
+                            td {
+                                progress {
+                                    class: "progress is-small is-info",
+                                    value: "{usage.bytes}",
+                                    max: "{total_bytes}",
+                                    title: "{share(usage.bytes)}%",
+                                }
+                            }
+                        }
+                    }
+                    if report.root.objects > 0 {
+                        tr {
+                            td { em { "(root)" } }
+                            td { "{report.root.objects}" }
+                            td { "{human_size(report.root.bytes)}" }
+                            td {
+                                progress {
+                                    class: "progress is-small is-info",
+                                    value: "{report.root.bytes}",
+                                    max: "{total_bytes}",
+                                    title: "{share(report.root.bytes)}%",
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[component]
+fn ObjectList(
+    objects: Option<Result<Vec<ObjectMeta>, String>>,
+    now: time::OffsetDateTime,
+) -> Element {
+    match objects {
+        None => rsx! { Spinner {} },
+        Some(Err(err)) => rsx! {
+            p { class: "help is-danger", "{err}" }
+        },
+        Some(Ok(objects)) if objects.is_empty() => rsx! {
+            p { "No objects found." }
+        },
+        Some(Ok(objects)) => rsx! {
+            table {
+                class: "table is-fullwidth is-narrow",
+                thead {
+                    tr {
+                        th { "Key" }
+                        th { "Size" }
+                        th { "Modified" }
+                    }
+                }
+                tbody {
+                    for item in objects.iter() {
+                        tr {
+                            key: "{item.key}",
+                            td { "{item.key}" }
+                            td { "{object_size(item)}" }
+                            td { {object_modified(item, now)} }
+                        }
+                    }
+                }
+            }
+        },
+    }
+}
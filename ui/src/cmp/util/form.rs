@@ -1,4 +1,11 @@
 use dioxus::prelude::*;
+use dioxus_bulma::{Color, Notification};
+
+/// Whether a new connection is saved to the config store.
+pub enum ConnectionPersistence {
+    Temporary,
+    Persistent,
+}
 
 pub enum FormSubmit {
     Idle,
@@ -29,3 +36,74 @@ pub fn FormField(label: Element, children: Element) -> Element {
         }
     }
 }
+
+/// Validation errors and the submit buttons shared by the connection forms.
+#[component]
+pub fn ConnectionFormActions(
+    status: ReadOnlySignal<FormSubmit>,
+    errors: ReadOnlySignal<Option<Vec<String>>>,
+    submit: Callback<ConnectionPersistence>,
+    on_cancel: EventHandler<()>,
+) -> Element {
+    let (is_loading, submit_error) = match &*status.read() {
+        FormSubmit::Idle => (false, None),
+        FormSubmit::Loading => (true, None),
+        FormSubmit::Error(err) => (false, Some(err.clone())),
+    };
+
+    rsx! {
+        if let Some(errors) = errors() {
+            Notification {
+                color: Color::Danger,
+
+                ul {
+                    class: "content",
+
+                    for error in errors.iter() {
+                        li {
+                            "{error}"
+                        }
+                    }
+                }
+            }
+        }
+        if let Some(err) = &submit_error {
+            Notification {
+                color: Color::Danger,
+                "{err:#?}"
+            }
+        }
+
+        div {
+            class: "buttons is-large",
+
+            button {
+                class: "button is-primary",
+                class: if is_loading { "is-loading" } else { "" },
+                r#type: "submit",
+                onclick: move |_| {
+                    submit.call(ConnectionPersistence::Persistent);
+                },
+                "Save and connect"
+            }
+
+            button {
+                class: "button",
+                class: if is_loading { "is-loading" } else { "" },
+                r#type: "submit",
+                onclick: move |_| {
+                    submit.call(ConnectionPersistence::Temporary);
+                },
+                "Open without saving"
+            }
+
+            button {
+                class: "button",
+                onclick: move |_| {
+                    on_cancel.call(());
+                },
+                "Cancel"
+            }
+        }
+    }
+}
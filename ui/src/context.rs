@@ -4,7 +4,10 @@ use dioxus::{
     hooks::use_context_provider,
     signals::{Readable, Signal, Writable as _},
 };
-use objstore::{DynObjStore, ObjStoreBuilder};
+use objstore::{
+    wrapper::policy::{PolicyObjStore, StorePolicy},
+    DynObjStore, ObjStoreBuilder,
+};
 use objstore_config::{DynConfigStore, LoadedConnection};
 
 #[derive(Clone)]
@@ -101,6 +104,37 @@ impl Stores {
     pub fn get(&self, name: &str) -> Option<ActiveStore> {
         self.stores.read_unchecked().get(name).cloned()
     }
+
+    /// Get an active store, or connect to it from the saved connections.
+    pub async fn resolve(
+        &self,
+        name: &str,
+        config_store: &DynConfigStore,
+        builder: &ObjStoreBuilder,
+    ) -> Result<ActiveStore, anyhow::Error> {
+        if let Some(store) = self.get(name) {
+            return Ok(store);
+        }
+
+        let configs = config_store.load_connections().await?;
+        let Some(config) = configs.get(name) else {
+            anyhow::bail!("Connection '{name}' not found in config store");
+        };
+
+        let store = with_policy(builder.build_cached(&config.config.uri)?);
+        self.register(config.config.name.clone(), config.clone(), store.clone());
+
+        Ok(ActiveStore {
+            config: config.clone(),
+            store,
+        })
+    }
+}
+
+/// Wrap a store with the default [`StorePolicy`], which prevents deleting
+/// all objects of a connection by accident.
+pub fn with_policy(store: DynObjStore) -> DynObjStore {
+    Arc::new(PolicyObjStore::new(store, StorePolicy::default()))
 }
 
 pub fn provide_stores() {
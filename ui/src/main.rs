@@ -33,7 +33,9 @@ fn main() -> Result<(), anyhow::Error> {
 
     #[cfg(feature = "desktop")]
     {
-        builder = builder.with_provider(Arc::new(objstore_s3_light::S3LightProvider::new()));
+        use objstore_all::ObjStoreBuilderExt as _;
+
+        builder = builder.with_default_providers();
     }
 
     #[cfg(feature = "desktop")]
@@ -57,6 +59,8 @@ fn main() -> Result<(), anyhow::Error> {
 #[component]
 fn App() -> Element {
     provide_stores();
+    store::jobs::provide_jobs();
+    store::monitor::provide_connection_monitor();
 
     rsx! {
         document::Link { rel: "icon", href: FAVICON }
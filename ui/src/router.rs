@@ -1,6 +1,8 @@
 use dioxus::prelude::*;
 
-use crate::views::{BrowserPage as Browser, Home, NavbarLayout, NewConnection, Settings};
+use crate::views::{
+    BrowserPage as Browser, Home, NavbarLayout, NewConnection, Settings, UsagePage as Usage,
+};
 
 #[derive(Debug, Clone, Routable, PartialEq)]
 #[rustfmt::skip]
@@ -15,8 +17,14 @@ pub enum Route {
         #[route("/connections/new")]
         NewConnection {},
 
-        #[route("/connections/:store/browser")]
+        #[route("/connections/:store/browser?:prefix")]
         Browser {
             store: String,
+            prefix: String,
+        },
+
+        #[route("/connections/:store/usage")]
+        Usage {
+            store: String,
         }
 }
@@ -0,0 +1,342 @@
+//! Background jobs for long-running store operations.
+//!
+//! Uploads, downloads, transfers and deletions are submitted to the global
+//! [`Jobs`] queue instead of being awaited by the component that started
+//! them. Jobs run in the root scope, so they keep running when modals close
+//! or the user navigates away, and are shown in the transfers panel.
+
+use std::{collections::HashMap, rc::Rc, time::Instant};
+
+use dioxus::{
+    core::{spawn_forever, Task},
+    hooks::use_context,
+    signals::{Readable as _, Signal, SyncStorage, Writable as _},
+};
+use futures::future::LocalBoxFuture;
+use objstore::ObjectMeta;
+
+/// Maximum number of jobs running at the same time.
+const MAX_CONCURRENT_JOBS: usize = 3;
+/// Number of attempts for jobs that fail with a transient error.
+const MAX_ATTEMPTS: u32 = 3;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum JobKind {
+    Upload,
+    Download,
+    Copy,
+    Move,
+    Delete,
+}
+
+impl JobKind {
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Upload => "Upload",
+            Self::Download => "Download",
+            Self::Copy => "Copy",
+            Self::Move => "Move",
+            Self::Delete => "Delete",
+        }
+    }
+}
+
+/// What the progress of a job counts.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum JobUnit {
+    Bytes,
+    Objects,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum JobStatus {
+    Queued,
+    Running,
+    Done,
+    Failed(String),
+    Cancelled,
+}
+
+impl JobStatus {
+    pub fn is_active(&self) -> bool {
+        matches!(self, Self::Queued | Self::Running)
+    }
+}
+
+/// Result of a successful job, used to update views of the affected store.
+#[derive(Clone, Debug, PartialEq)]
+pub enum JobOutput {
+    None,
+    /// An object was created or replaced.
+    Created(ObjectMeta),
+    /// An object was deleted.
+    Deleted(String),
+    /// Objects below a prefix changed.
+    Changed,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Job {
+    pub id: u64,
+    pub kind: JobKind,
+    /// Name of the connection the job operates on.
+    pub connection: String,
+    pub title: String,
+    pub status: JobStatus,
+    pub unit: JobUnit,
+    pub transferred: u64,
+    pub total: Option<u64>,
+    /// The item currently being processed, for jobs with many items.
+    pub current: Option<String>,
+    pub attempts: u32,
+    pub started_at: Option<Instant>,
+    pub finished_at: Option<Instant>,
+    pub output: Option<JobOutput>,
+}
+
+impl Job {
+    /// Average throughput of the current attempt, in units per second.
+    pub fn speed(&self) -> Option<f64> {
+        let started = self.started_at?;
+        let end = self.finished_at.unwrap_or_else(Instant::now);
+        let secs = end.duration_since(started).as_secs_f64();
+        (secs > 0.5).then(|| self.transferred as f64 / secs)
+    }
+}
+
+/// Progress reporting for a running job.
+///
+/// Backed by thread-safe storage, so it can be used in `Send` callbacks.
+#[derive(Clone, Copy)]
+pub struct JobHandle {
+    id: u64,
+    jobs: Signal<Vec<Job>, SyncStorage>,
+}
+
+impl JobHandle {
+    fn update(mut self, f: impl FnOnce(&mut Job)) {
+        if let Some(job) = self.jobs.write().iter_mut().find(|job| job.id == self.id) {
+            f(job);
+        }
+    }
+
+    pub fn progress(self, transferred: u64) {
+        self.update(|job| job.transferred = transferred);
+    }
+
+    pub fn set_total(self, total: Option<u64>) {
+        self.update(|job| job.total = total);
+    }
+
+    pub fn set_current(self, current: impl Into<String>) {
+        let current = current.into();
+        self.update(|job| job.current = Some(current));
+    }
+}
+
+type JobFn = Rc<dyn Fn(JobHandle) -> LocalBoxFuture<'static, Result<JobOutput, anyhow::Error>>>;
+
+/// A new job, see [`Jobs::submit`].
+pub struct JobSpec {
+    pub kind: JobKind,
+    pub connection: String,
+    pub title: String,
+    pub unit: JobUnit,
+    pub total: Option<u64>,
+}
+
+/// The global job queue.
+#[derive(Clone, Copy)]
+pub struct Jobs {
+    jobs: Signal<Vec<Job>, SyncStorage>,
+    runners: Signal<HashMap<u64, JobFn>>,
+    tasks: Signal<HashMap<u64, Task>>,
+    next_id: Signal<u64>,
+}
+
+impl Jobs {
+    fn new() -> Self {
+        Self {
+            jobs: Signal::new_maybe_sync(Vec::new()),
+            runners: Signal::new(HashMap::new()),
+            tasks: Signal::new(HashMap::new()),
+            next_id: Signal::new(0),
+        }
+    }
+
+    /// All jobs, including finished ones, in submission order.
+    pub fn list(&self) -> Vec<Job> {
+        self.jobs.read().clone()
+    }
+
+    /// Queue a job.
+    ///
+    /// `run` is called for each attempt, so it must be repeatable.
+    pub fn submit<F, Fut>(mut self, spec: JobSpec, run: F) -> u64
+    where
+        F: Fn(JobHandle) -> Fut + 'static,
+        Fut: std::future::Future<Output = Result<JobOutput, anyhow::Error>> + 'static,
+    {
+        let id = {
+            let mut next_id = self.next_id.write();
+            *next_id += 1;
+            *next_id
+        };
+        self.runners.write().insert(
+            id,
+            Rc::new(move |handle| Box::pin(run(handle)) as LocalBoxFuture<'static, _>),
+        );
+        self.jobs.write().push(Job {
+            id,
+            kind: spec.kind,
+            connection: spec.connection,
+            title: spec.title,
+            status: JobStatus::Queued,
+            unit: spec.unit,
+            transferred: 0,
+            total: spec.total,
+            current: None,
+            attempts: 0,
+            started_at: None,
+            finished_at: None,
+            output: None,
+        });
+        self.schedule();
+        id
+    }
+
+    fn handle(&self, id: u64) -> JobHandle {
+        JobHandle {
+            id,
+            jobs: self.jobs,
+        }
+    }
+
+    /// Start queued jobs until the concurrency limit is reached.
+    fn schedule(self) {
+        let next = {
+            let jobs = self.jobs.peek();
+            let running = jobs
+                .iter()
+                .filter(|job| job.status == JobStatus::Running)
+                .count();
+            jobs.iter()
+                .filter(|job| job.status == JobStatus::Queued)
+                .take(MAX_CONCURRENT_JOBS.saturating_sub(running))
+                .map(|job| job.id)
+                .collect::<Vec<_>>()
+        };
+        for id in next {
+            self.start(id);
+        }
+    }
+
+    fn start(mut self, id: u64) {
+        let Some(run) = self.runners.peek().get(&id).cloned() else {
+            return;
+        };
+        let handle = self.handle(id);
+        handle.update(|job| {
+            job.status = JobStatus::Running;
+            job.attempts += 1;
+            job.transferred = 0;
+            job.current = None;
+            job.started_at = Some(Instant::now());
+            job.finished_at = None;
+        });
+
+        let task = spawn_forever(async move {
+            let res = run(handle).await;
+            self.tasks.write().remove(&id);
+
+            let mut done = false;
+            handle.update(|job| {
+                job.finished_at = Some(Instant::now());
+                match res {
+                    Ok(output) => {
+                        job.status = JobStatus::Done;
+                        job.output = Some(output);
+                        done = true;
+                    }
+                    Err(err) => {
+                        let transient = err
+                            .downcast_ref::<objstore::ObjStoreError>()
+                            .is_some_and(|err| err.is_transient());
+                        if transient && job.attempts < MAX_ATTEMPTS {
+                            tracing::warn!("Job '{}' failed, retrying: {err:#}", job.title);
+                            job.status = JobStatus::Queued;
+                        } else {
+                            tracing::error!("Job '{}' failed: {err:#}", job.title);
+                            job.status = JobStatus::Failed(format!("{err:#}"));
+                        }
+                    }
+                }
+            });
+            if done {
+                self.runners.write().remove(&id);
+            }
+            self.schedule();
+        });
+        if let Some(task) = task {
+            self.tasks.write().insert(id, task);
+        }
+    }
+
+    pub fn cancel(mut self, id: u64) {
+        if let Some(task) = self.tasks.write().remove(&id) {
+            task.cancel();
+        }
+        self.handle(id).update(|job| {
+            if job.status.is_active() {
+                job.status = JobStatus::Cancelled;
+                job.finished_at = Some(Instant::now());
+            }
+        });
+        self.schedule();
+    }
+
+    /// Run a failed or cancelled job again.
+    ///
+    /// Only possible while the job is still known to the queue, which keeps
+    /// the job function until the job succeeded.
+    pub fn can_retry(&self, id: u64) -> bool {
+        self.runners.peek().contains_key(&id)
+    }
+
+    pub fn retry(self, id: u64) {
+        if !self.can_retry(id) {
+            return;
+        }
+        self.handle(id).update(|job| {
+            if !job.status.is_active() {
+                job.status = JobStatus::Queued;
+                job.attempts = 0;
+            }
+        });
+        self.schedule();
+    }
+
+    /// Remove finished jobs from the history.
+    pub fn clear_finished(mut self) {
+        let mut removed = Vec::new();
+        self.jobs.write().retain(|job| {
+            let keep = job.status.is_active();
+            if !keep {
+                removed.push(job.id);
+            }
+            keep
+        });
+        let mut runners = self.runners.write();
+        for id in removed {
+            runners.remove(&id);
+        }
+    }
+}
+
+pub fn provide_jobs() {
+    dioxus::hooks::use_context_provider(Jobs::new);
+}
+
+pub fn use_jobs() -> Jobs {
+    use_context::<Jobs>()
+}
@@ -0,0 +1,627 @@
+use anyhow::Context;
+use objstore::{DynObjStore, KeyPath, ObjectMeta};
+use std::path::PathBuf;
+
+pub mod jobs;
+pub mod monitor;
+pub mod usage;
+
+/// Returns the default directory for object downloads.
+pub fn default_download_dir() -> Result<PathBuf, anyhow::Error> {
+    #[cfg(feature = "desktop")]
+    {
+        let home_dir = std::env::home_dir().context("Failed to determine home directory")?;
+        let dir = home_dir.join("Downloads");
+        Ok(dir)
+    }
+    #[cfg(not(feature = "desktop"))]
+    {
+        bail!("Default download directory not implemented for this platform");
+    }
+}
+
+/// Wait for the given duration.
+pub async fn sleep(duration: std::time::Duration) {
+    #[cfg(feature = "desktop")]
+    {
+        tokio::time::sleep(duration).await;
+    }
+
+    #[cfg(not(feature = "desktop"))]
+    {
+        let _ = duration;
+        futures::future::pending::<()>().await;
+    }
+}
+
+pub async fn download_object(
+    store: &DynObjStore,
+    object: &ObjectMeta,
+    local_path: Option<&str>,
+    on_progress: impl Fn(u64),
+) -> Result<(), anyhow::Error> {
+    #[cfg(feature = "desktop")]
+    {
+        return download_object_desktop(store, object, local_path, on_progress).await;
+    }
+
+    #[cfg(not(feature = "desktop"))]
+    {
+        bail!("Download support not implemented for this platform");
+    }
+}
+
+#[cfg(any(feature = "desktop"))]
+async fn download_object_desktop(
+    store: &DynObjStore,
+    object: &ObjectMeta,
+    local_path: Option<&str>,
+    on_progress: impl Fn(u64),
+) -> Result<(), anyhow::Error> {
+    use anyhow::Context;
+    use futures::TryStreamExt;
+    use tokio::io::AsyncWriteExt as _;
+
+    let home_dir = std::env::home_dir().context("Failed to determine home directory")?;
+    let dir = home_dir.join("Downloads");
+
+    let filename = object
+        .key
+        .trim_end_matches('/')
+        .split('/')
+        .last()
+        .unwrap_or(&object.key)
+        .replace('/', "_");
+
+    let output_path = dir.join(&filename);
+    let tmp_path = format!("{filename}.tmp");
+
+    std::fs::create_dir_all(&dir)
+        .with_context(|| format!("Failed to create directory: {}", dir.display()))?;
+
+    let file = tokio::fs::File::create(&tmp_path)
+        .await
+        .with_context(|| format!("Failed to create file: {}", tmp_path))?;
+
+    let mut writer = tokio::io::BufWriter::new(file);
+
+    let mut stream = store
+        .get_stream(&object.key)
+        .await?
+        .context("object not found")?;
+
+    let mut progress = 0u64;
+    while let Some(chunk) = stream.try_next().await? {
+        progress += chunk.len() as u64;
+        writer.write_all(&chunk).await?;
+        on_progress(progress);
+    }
+    {
+        writer.flush().await?;
+        let file = writer.into_inner();
+        file.sync_all().await?;
+        let _ = file;
+    }
+
+    tokio::fs::rename(&tmp_path, &output_path)
+        .await
+        .with_context(|| format!("Failed to rename temp file to '{}'", output_path.display()))?;
+
+    Ok(())
+}
+
+/// A local file queued for upload.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UploadFile {
+    pub path: PathBuf,
+    pub key: String,
+    pub size: u64,
+}
+
+/// Resolve selected or dropped paths into the files to upload.
+///
+/// Directories are walked recursively, and their relative folder structure is
+/// kept as key prefixes below `base_path`, including the directory name itself.
+pub fn collect_upload_files(
+    paths: &[PathBuf],
+    base_path: &str,
+) -> Result<Vec<UploadFile>, anyhow::Error> {
+    let base_path = KeyPath::parse(base_path)?;
+    let mut files = Vec::new();
+    for path in paths {
+        let name = path
+            .file_name()
+            .with_context(|| format!("invalid path: '{}'", path.display()))?
+            .to_string_lossy();
+        collect_upload_path(path, base_path.join(&name)?, &mut files)?;
+    }
+    Ok(files)
+}
+
+fn collect_upload_path(
+    path: &std::path::Path,
+    key: KeyPath,
+    files: &mut Vec<UploadFile>,
+) -> Result<(), anyhow::Error> {
+    let meta = std::fs::metadata(path)
+        .with_context(|| format!("Failed to read metadata of '{}'", path.display()))?;
+    if meta.is_dir() {
+        let entries = std::fs::read_dir(path)
+            .with_context(|| format!("Failed to read directory '{}'", path.display()))?;
+        for entry in entries {
+            let entry = entry?;
+            let name = entry.file_name();
+            collect_upload_path(&entry.path(), key.join(&name.to_string_lossy())?, files)?;
+        }
+    } else {
+        files.push(UploadFile {
+            path: path.to_owned(),
+            key: key.into_string(),
+            size: meta.len(),
+        });
+    }
+    Ok(())
+}
+
+pub async fn upload_file(
+    store: &DynObjStore,
+    file: &UploadFile,
+    on_progress: impl Fn(u64),
+) -> Result<ObjectMeta, anyhow::Error> {
+    #[cfg(feature = "desktop")]
+    {
+        return upload_file_desktop(store, file, on_progress).await;
+    }
+
+    #[cfg(not(feature = "desktop"))]
+    {
+        bail!("Upload support not implemented for this platform");
+    }
+}
+
+/// Stream a local file into the store.
+///
+/// Chunks are read in this task and handed to the store through a bounded
+/// channel, so progress is reported as the store consumes the data.
+#[cfg(any(feature = "desktop"))]
+async fn upload_file_desktop(
+    store: &DynObjStore,
+    file: &UploadFile,
+    on_progress: impl Fn(u64),
+) -> Result<ObjectMeta, anyhow::Error> {
+    use futures::{SinkExt as _, StreamExt as _};
+    use objstore::{ObjStoreExt as _, SizedValueStream};
+    use tokio::io::AsyncReadExt as _;
+
+    const CHUNK_SIZE: usize = 256 * 1024;
+
+    let mut reader = tokio::fs::File::open(&file.path)
+        .await
+        .with_context(|| format!("Failed to open file: {}", file.path.display()))?;
+
+    let (mut sender, receiver) = futures::channel::mpsc::channel(2);
+
+    let feed = async move {
+        let mut progress = 0u64;
+        loop {
+            let mut buf = vec![0u8; CHUNK_SIZE];
+            let read = match reader.read(&mut buf).await {
+                Ok(0) => break,
+                Ok(read) => read,
+                Err(err) => {
+                    let err = objstore::ObjStoreError::Io {
+                        operation: objstore::Operation::Put,
+                        source: Some(Box::new(err)),
+                    };
+                    let _ = sender.send(Err(err)).await;
+                    break;
+                }
+            };
+            buf.truncate(read);
+            if sender.send(Ok(bytes::Bytes::from(buf))).await.is_err() {
+                // The store stopped reading, its error is returned by the put.
+                break;
+            }
+            progress += read as u64;
+            on_progress(progress);
+        }
+    };
+
+    let stream = SizedValueStream::new(receiver.boxed(), file.size);
+    let put = store.put(&file.key).stream(stream);
+
+    let ((), res) = futures::join!(feed, put);
+    Ok(res?)
+}
+
+/// Progress of a prefix download.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ArchiveProgress {
+    pub objects_done: u64,
+    pub objects_total: u64,
+    pub bytes_done: u64,
+    pub bytes_total: u64,
+}
+
+/// Suggested archive file name for a prefix.
+pub fn archive_file_name(prefix: &str) -> String {
+    let name = prefix
+        .trim_end_matches('/')
+        .rsplit('/')
+        .next()
+        .unwrap_or("");
+    if name.is_empty() {
+        "objects.zip".to_string()
+    } else {
+        format!("{name}.zip")
+    }
+}
+
+/// Download all objects below `prefix` into a zip archive at `local_path`.
+///
+/// Entry names are the object keys relative to the prefix.
+pub async fn download_prefix_zip(
+    store: &DynObjStore,
+    prefix: &str,
+    local_path: &str,
+    on_progress: impl Fn(ArchiveProgress),
+) -> Result<(), anyhow::Error> {
+    use futures::TryStreamExt;
+    use objstore::ListArgs;
+
+    let objects = store
+        .list_items_stream(ListArgs::new().with_prefix(prefix))
+        .try_collect::<Vec<_>>()
+        .await
+        .with_context(|| format!("Failed to list objects below '{prefix}'"))?;
+    download_objects_zip(store, &objects, prefix, local_path, on_progress).await
+}
+
+/// Download the given objects into a zip archive at `local_path`.
+///
+/// Entry names are the object keys relative to `base_path`.
+pub async fn download_objects_zip(
+    store: &DynObjStore,
+    objects: &[ObjectMeta],
+    base_path: &str,
+    local_path: &str,
+    on_progress: impl Fn(ArchiveProgress),
+) -> Result<(), anyhow::Error> {
+    #[cfg(feature = "desktop")]
+    {
+        return download_objects_zip_desktop(store, objects, base_path, local_path, on_progress)
+            .await;
+    }
+
+    #[cfg(not(feature = "desktop"))]
+    {
+        bail!("Download support not implemented for this platform");
+    }
+}
+
+/// Removes a temporary file unless it was committed.
+///
+/// Makes sure cancelled downloads (dropped futures) do not leave partial
+/// files behind.
+#[cfg(any(feature = "desktop"))]
+struct TempFileGuard {
+    path: PathBuf,
+    committed: bool,
+}
+
+#[cfg(any(feature = "desktop"))]
+impl Drop for TempFileGuard {
+    fn drop(&mut self) {
+        if !self.committed {
+            let _ = std::fs::remove_file(&self.path);
+        }
+    }
+}
+
+#[cfg(any(feature = "desktop"))]
+async fn download_objects_zip_desktop(
+    store: &DynObjStore,
+    objects: &[ObjectMeta],
+    base_path: &str,
+    local_path: &str,
+    on_progress: impl Fn(ArchiveProgress),
+) -> Result<(), anyhow::Error> {
+    use std::io::Write as _;
+
+    use futures::TryStreamExt;
+
+    let output_path = PathBuf::from(local_path);
+    if let Some(dir) = output_path.parent() {
+        std::fs::create_dir_all(dir)
+            .with_context(|| format!("Failed to create directory: {}", dir.display()))?;
+    }
+
+    let mut progress = ArchiveProgress {
+        objects_total: objects.len() as u64,
+        bytes_total: objects.iter().filter_map(|meta| meta.size).sum(),
+        ..Default::default()
+    };
+    on_progress(progress);
+
+    let mut tmp = TempFileGuard {
+        path: output_path.with_extension("zip.tmp"),
+        committed: false,
+    };
+    let file = std::fs::File::create(&tmp.path)
+        .with_context(|| format!("Failed to create file: {}", tmp.path.display()))?;
+    let mut zip = zip::ZipWriter::new(std::io::BufWriter::new(file));
+    let options = zip::write::SimpleFileOptions::default()
+        .compression_method(zip::CompressionMethod::Deflated)
+        .large_file(true);
+
+    for meta in objects {
+        let name = meta.key.strip_prefix(base_path).unwrap_or(&meta.key);
+        if name.is_empty() || name.ends_with('/') {
+            // Directory markers.
+            progress.objects_done += 1;
+            continue;
+        }
+
+        let Some(mut stream) = store.get_stream(&meta.key).await? else {
+            // Deleted since listing.
+            progress.objects_done += 1;
+            progress.bytes_total = progress.bytes_total.saturating_sub(meta.size.unwrap_or(0));
+            on_progress(progress);
+            continue;
+        };
+
+        zip.start_file(name, options)
+            .with_context(|| format!("Failed to add '{name}' to archive"))?;
+        while let Some(chunk) = stream.try_next().await? {
+            zip.write_all(&chunk)?;
+            progress.bytes_done += chunk.len() as u64;
+            on_progress(progress);
+        }
+        progress.objects_done += 1;
+        on_progress(progress);
+    }
+
+    let mut writer = zip.finish().context("Failed to finish archive")?;
+    writer.flush()?;
+    writer
+        .into_inner()
+        .map_err(|err| err.into_error())?
+        .sync_all()?;
+
+    std::fs::rename(&tmp.path, &output_path)
+        .with_context(|| format!("Failed to rename temp file to '{}'", output_path.display()))?;
+    tmp.committed = true;
+
+    Ok(())
+}
+
+/// An object or prefix selected for a transfer between connections.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TransferSource {
+    Object(String),
+    Prefix(String),
+}
+
+impl TransferSource {
+    /// The last path segment, which is kept below the destination prefix.
+    pub fn name(&self) -> &str {
+        let path = match self {
+            Self::Object(key) => key.as_str(),
+            Self::Prefix(prefix) => prefix.trim_end_matches('/'),
+        };
+        path.rsplit('/').next().unwrap_or(path)
+    }
+}
+
+/// Resolve selected objects and prefixes into the objects they contain.
+///
+/// Objects that no longer exist are skipped.
+pub async fn resolve_sources(
+    store: &DynObjStore,
+    sources: &[TransferSource],
+) -> Result<Vec<ObjectMeta>, anyhow::Error> {
+    use futures::TryStreamExt;
+    use objstore::ListArgs;
+
+    let mut objects = Vec::new();
+    for source in sources {
+        match source {
+            TransferSource::Object(key) => {
+                if let Some(meta) = store.meta(key).await? {
+                    objects.push(meta);
+                }
+            }
+            TransferSource::Prefix(prefix) => {
+                let items = store
+                    .list_items_stream(ListArgs::new().with_prefix(prefix.as_str()))
+                    .try_collect::<Vec<_>>()
+                    .await
+                    .with_context(|| format!("Failed to list objects below '{prefix}'"))?;
+                objects.extend(items);
+            }
+        }
+    }
+    objects.sort_by(|a, b| a.key.cmp(&b.key));
+    objects.dedup_by(|a, b| a.key == b.key);
+    Ok(objects)
+}
+
+/// Delete the given keys, continuing after failures.
+///
+/// Fails with a summary if any key could not be deleted.
+pub async fn delete_objects(
+    store: &DynObjStore,
+    keys: Vec<String>,
+    on_progress: impl Fn(u64),
+) -> Result<(), anyhow::Error> {
+    use futures::StreamExt as _;
+    use objstore::{DeleteManyOptions, ObjStoreExt as _};
+
+    let mut deleted = 0u64;
+    let mut failed = Vec::new();
+    let mut results = store.delete_many(keys, DeleteManyOptions::new());
+    while let Some((key, res)) = results.next().await {
+        match res {
+            Ok(()) => {
+                deleted += 1;
+                on_progress(deleted);
+            }
+            Err(err) => failed.push((key, err)),
+        }
+    }
+
+    if let Some((key, err)) = failed.first() {
+        anyhow::bail!(
+            "{} object(s) could not be deleted, first failure: {key}: {err}",
+            failed.len()
+        );
+    }
+    Ok(())
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TransferMode {
+    Copy,
+    Move,
+}
+
+/// Progress of a transfer between connections.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TransferProgress {
+    /// Key of the object that was transferred last.
+    pub current: Option<String>,
+    pub transferred: u64,
+    pub failed: Vec<(String, String)>,
+}
+
+/// Copy or move objects and prefixes from `src` to `dest_prefix` in `dest`.
+///
+/// Each source keeps its name below the destination prefix, so copying the
+/// prefix `a/b/` to `x/` results in keys below `x/b/`. Failures of individual
+/// objects are collected in the progress instead of aborting the transfer.
+///
+/// Without `dest`, the objects are copied within `src`, using server-side
+/// copies.
+pub async fn transfer_objects(
+    src: &DynObjStore,
+    dest: Option<&DynObjStore>,
+    sources: &[TransferSource],
+    dest_prefix: &str,
+    mode: TransferMode,
+    on_progress: impl Fn(&TransferProgress) + Send + Sync + Clone + 'static,
+) -> Result<TransferProgress, anyhow::Error> {
+    use std::sync::{Arc, Mutex};
+
+    use objstore::{FailurePolicy, ObjStoreExt as _, PrefixCopyOptions};
+
+    let progress = Arc::new(Mutex::new(TransferProgress::default()));
+    let update = {
+        let progress = progress.clone();
+        move |f: &dyn Fn(&mut TransferProgress)| {
+            let mut progress = progress.lock().unwrap_or_else(|err| err.into_inner());
+            f(&mut progress);
+            on_progress(&progress);
+        }
+    };
+
+    for source in sources {
+        let target = format!("{dest_prefix}{}", source.name());
+        match source {
+            TransferSource::Prefix(prefix) => {
+                let base = progress.lock().map(|p| p.transferred).unwrap_or_default();
+                let options = PrefixCopyOptions::new()
+                    .with_policy(FailurePolicy::BestEffort)
+                    .with_progress({
+                        let update = update.clone();
+                        move |p| {
+                            update(&|progress| {
+                                progress.current = Some(p.key.clone());
+                                progress.transferred = base + p.copied;
+                            })
+                        }
+                    });
+                let target = format!("{target}/");
+                let report = match (dest, mode) {
+                    (Some(dest), TransferMode::Copy) => {
+                        src.copy_prefix_to(prefix, &**dest, &target, options)
+                            .await?
+                    }
+                    (Some(dest), TransferMode::Move) => {
+                        src.move_prefix_to(prefix, &**dest, &target, options)
+                            .await?
+                    }
+                    (None, TransferMode::Copy) => src.copy_prefix(prefix, &target, options).await?,
+                    (None, TransferMode::Move) => src.move_prefix(prefix, &target, options).await?,
+                };
+                let failed = report
+                    .failed
+                    .into_iter()
+                    .map(|(key, err)| (key, err.to_string()))
+                    .collect::<Vec<_>>();
+                update(&|progress| {
+                    progress.transferred = base + report.copied;
+                    progress.failed.extend(failed.iter().cloned());
+                });
+            }
+            TransferSource::Object(key) => {
+                let res = transfer_object(src, dest, key, &target, mode)
+                    .await
+                    .map_err(|err| format!("{err:#}"));
+                update(&|progress| {
+                    progress.current = Some(key.clone());
+                    match &res {
+                        Ok(()) => progress.transferred += 1,
+                        Err(err) => progress.failed.push((key.clone(), err.clone())),
+                    }
+                });
+            }
+        }
+    }
+
+    let progress = progress
+        .lock()
+        .unwrap_or_else(|err| err.into_inner())
+        .clone();
+    Ok(progress)
+}
+
+async fn transfer_object(
+    src: &DynObjStore,
+    dest: Option<&DynObjStore>,
+    key: &str,
+    target: &str,
+    mode: TransferMode,
+) -> Result<(), anyhow::Error> {
+    use objstore::{ObjStoreExt as _, SizedValueStream};
+
+    let Some(dest) = dest else {
+        if key == target {
+            anyhow::bail!("source and target are the same object");
+        }
+        src.copy(key, target).send().await?;
+        if mode == TransferMode::Move {
+            src.delete(key).await?;
+        }
+        return Ok(());
+    };
+
+    let (meta, stream) = src
+        .get_stream_with_meta(key)
+        .await?
+        .with_context(|| format!("object '{key}' not found"))?;
+    let stream = match meta.size {
+        Some(size) => SizedValueStream::new(stream, size),
+        None => SizedValueStream::new_without_size(stream),
+    };
+
+    let mut put = dest.put(target);
+    if let Some(mime_type) = meta.mime_type {
+        put = put.mime_type(mime_type);
+    }
+    put.stream(stream).await?;
+
+    if mode == TransferMode::Move {
+        src.delete(key).await?;
+    }
+    Ok(())
+}
@@ -0,0 +1,143 @@
+//! Periodic health checks of the saved connections.
+//!
+//! The [`ConnectionMonitor`] runs [`objstore::ObjStore::healthcheck_report`]
+//! for every saved connection in the background, and keeps the latest result
+//! for status indicators.
+
+use std::{collections::HashMap, time::Duration, time::Instant};
+
+use dioxus::prelude::*;
+use objstore::{DynObjStore, HealthReport};
+
+use crate::context::{use_config_store, use_providers, use_stores, Stores, UiConfigStore};
+
+/// Interval between background checks.
+const CHECK_INTERVAL: Duration = Duration::from_secs(60);
+/// Checks taking longer than this are reported as failed.
+const CHECK_TIMEOUT: Duration = Duration::from_secs(15);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HealthStatus {
+    /// Not checked yet.
+    Unknown,
+    Healthy,
+    /// Reachable, but with warnings.
+    Degraded,
+    Unreachable,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ConnectionHealth {
+    /// A check is currently running.
+    pub checking: bool,
+    pub checked_at: Option<Instant>,
+    /// Result of the last completed check.
+    pub result: Option<Result<HealthReport, String>>,
+}
+
+impl ConnectionHealth {
+    pub fn status(&self) -> HealthStatus {
+        match &self.result {
+            None => HealthStatus::Unknown,
+            Some(Ok(report)) if report.has_warnings() => HealthStatus::Degraded,
+            Some(Ok(_)) => HealthStatus::Healthy,
+            Some(Err(_)) => HealthStatus::Unreachable,
+        }
+    }
+}
+
+/// Latest health of all saved connections, by connection name.
+#[derive(Clone, Copy)]
+pub struct ConnectionMonitor {
+    health: Signal<HashMap<String, ConnectionHealth>>,
+}
+
+impl ConnectionMonitor {
+    pub fn get(&self, name: &str) -> ConnectionHealth {
+        self.health.read().get(name).cloned().unwrap_or_default()
+    }
+
+    /// Check a single connection now.
+    pub async fn check(
+        mut self,
+        name: &str,
+        stores: Stores,
+        config_store: &UiConfigStore,
+        builder: &objstore::ObjStoreBuilder,
+    ) -> Result<HealthReport, String> {
+        self.health
+            .write()
+            .entry(name.to_string())
+            .or_default()
+            .checking = true;
+
+        let res = match stores.resolve(name, config_store.get(), builder).await {
+            Ok(active) => check_store(&active.store).await,
+            Err(err) => Err(format!("{err:#}")),
+        };
+
+        let mut health = self.health.write();
+        let entry = health.entry(name.to_string()).or_default();
+        entry.checking = false;
+        entry.checked_at = Some(Instant::now());
+        entry.result = Some(res.clone());
+        res
+    }
+
+    /// Check all saved connections concurrently.
+    async fn check_all(
+        self,
+        stores: Stores,
+        config_store: &UiConfigStore,
+        builder: &objstore::ObjStoreBuilder,
+    ) {
+        let connections = match config_store.get().load_connections().await {
+            Ok(connections) => connections.connections,
+            Err(err) => {
+                tracing::warn!("Failed to load connections for health checks: {err:#}");
+                return;
+            }
+        };
+        let checks = connections
+            .iter()
+            .map(|con| self.check(&con.config.name, stores, config_store, builder));
+        futures::future::join_all(checks).await;
+    }
+}
+
+async fn check_store(store: &DynObjStore) -> Result<HealthReport, String> {
+    let check = std::pin::pin!(store.healthcheck_report());
+    let timeout = std::pin::pin!(super::sleep(CHECK_TIMEOUT));
+    match futures::future::select(check, timeout).await {
+        futures::future::Either::Left((res, _)) => res.map_err(|err| err.to_string()),
+        futures::future::Either::Right(_) => Err(format!(
+            "no response within {} seconds",
+            CHECK_TIMEOUT.as_secs()
+        )),
+    }
+}
+
+/// Provide the [`ConnectionMonitor`] and start the periodic checks.
+pub fn provide_connection_monitor() {
+    let config_store = use_config_store();
+    let builder = use_providers();
+    let stores = use_stores();
+    let monitor = use_context_provider(|| ConnectionMonitor {
+        health: Signal::new(HashMap::new()),
+    });
+
+    use_future(move || {
+        let config_store = config_store.clone();
+        let builder = builder.clone();
+        async move {
+            loop {
+                monitor.check_all(stores, &config_store, &builder).await;
+                super::sleep(CHECK_INTERVAL).await;
+            }
+        }
+    });
+}
+
+pub fn use_connection_monitor() -> ConnectionMonitor {
+    use_context::<ConnectionMonitor>()
+}
@@ -0,0 +1,91 @@
+//! Storage usage statistics for a connection.
+
+use futures::TryStreamExt as _;
+use objstore::{
+    DynObjStore, ListArgs, ObjStoreExt as _, ObjectMeta, ObjectOrPrefix, SortOrder, Usage,
+};
+
+/// Number of objects shown in the largest and recently modified lists.
+pub const TOP_OBJECTS: u64 = 10;
+
+/// Usage of a store, broken down by top-level prefix.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct UsageReport {
+    pub total: Usage,
+    /// Objects directly at the root, outside of any prefix.
+    pub root: Usage,
+    /// Usage per top-level prefix, largest first once the scan is complete.
+    pub prefixes: Vec<(String, Usage)>,
+    /// Number of top-level prefixes, known after the root was listed.
+    pub prefixes_total: usize,
+}
+
+impl UsageReport {
+    /// Scan progress between 0 and 1.
+    pub fn progress(&self) -> f64 {
+        if self.prefixes_total == 0 {
+            return 0.0;
+        }
+        self.prefixes.len() as f64 / self.prefixes_total as f64
+    }
+}
+
+/// Compute the usage of all objects in the store.
+///
+/// The root is listed first, then [`objstore::ObjStore::usage`] is queried
+/// for each top-level prefix. `on_progress` is called after each prefix.
+pub async fn scan_usage(
+    store: &DynObjStore,
+    mut on_progress: impl FnMut(&UsageReport),
+) -> Result<UsageReport, anyhow::Error> {
+    let mut report = UsageReport::default();
+
+    let mut prefixes = Vec::new();
+    let mut entries = store.list_entries_stream(ListArgs::new().with_delimiter('/'));
+    while let Some(entry) = entries.try_next().await? {
+        match entry {
+            ObjectOrPrefix::Object(meta) => {
+                report.root.objects += 1;
+                report.root.bytes += meta.size.unwrap_or_default();
+            }
+            ObjectOrPrefix::Prefix(prefix) => prefixes.push(prefix),
+        }
+    }
+    prefixes.sort();
+    prefixes.dedup();
+
+    report.total = report.root;
+    report.prefixes_total = prefixes.len();
+    on_progress(&report);
+
+    for prefix in prefixes {
+        let usage = store.usage(&prefix).await?;
+        report.total.objects += usage.objects;
+        report.total.bytes += usage.bytes;
+        report.prefixes.push((prefix, usage));
+        on_progress(&report);
+    }
+
+    report
+        .prefixes
+        .sort_by(|a, b| b.1.bytes.cmp(&a.1.bytes).then_with(|| a.0.cmp(&b.0)));
+    Ok(report)
+}
+
+/// The largest objects in the store.
+pub async fn largest_objects(store: &DynObjStore) -> Result<Vec<ObjectMeta>, anyhow::Error> {
+    top_objects(store, SortOrder::SizeDescending).await
+}
+
+/// The most recently modified objects in the store.
+pub async fn recent_objects(store: &DynObjStore) -> Result<Vec<ObjectMeta>, anyhow::Error> {
+    top_objects(store, SortOrder::ModifiedDescending).await
+}
+
+async fn top_objects(
+    store: &DynObjStore,
+    sort: SortOrder,
+) -> Result<Vec<ObjectMeta>, anyhow::Error> {
+    let args = ListArgs::new().with_sort(sort).with_limit(TOP_OBJECTS);
+    Ok(store.list_sorted(args).await?)
+}
@@ -1,4 +1,3 @@
-use anyhow::bail;
 use dioxus::prelude::*;
 use dioxus_bulma::{Color, Notification};
 
@@ -8,32 +7,13 @@ use crate::{
 };
 
 #[component]
-pub fn BrowserPage(store: ReadOnlySignal<String>) -> Element {
+pub fn BrowserPage(store: ReadOnlySignal<String>, prefix: ReadOnlySignal<String>) -> Element {
     let active_store = use_resource::<Result<ActiveStore, anyhow::Error>, _>(move || async move {
         let stores = use_stores();
-
-        let name = store();
-
-        if let Some(store) = stores.get(&name) {
-            return Ok(store.clone());
-        }
-
         let config_store = use_config_store();
-        let configs = config_store.get().load_connections().await?;
-
-        let Some(config) = configs.get(&name) else {
-            bail!("Connection '{name}' not found in config store");
-        };
-
         let builder = crate::context::use_providers();
-        let store = builder.build(&config.config.uri)?;
-
-        stores.register(config.config.name.clone(), config.clone(), store.clone());
 
-        Ok::<_, anyhow::Error>(ActiveStore {
-            config: config.clone(),
-            store,
-        })
+        stores.resolve(&store(), config_store.get(), &builder).await
     });
 
     let out = match &*active_store.read() {
@@ -53,7 +33,9 @@ pub fn BrowserPage(store: ReadOnlySignal<String>) -> Element {
         Some(Ok(store)) => {
             rsx! {
                 Browser {
+                    key: "{store.config.config.name}",
                     store: store.clone(),
+                    prefix,
                 }
             }
         }
@@ -12,3 +12,6 @@ pub use settings::Settings;
 
 mod browser;
 pub use browser::BrowserPage;
+
+mod usage;
+pub use usage::UsagePage;
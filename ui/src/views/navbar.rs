@@ -1,6 +1,6 @@
 use dioxus::prelude::*;
 
-use crate::router::Route;
+use crate::{cmp::TransferPanel, router::Route};
 
 /// The Navbar component that will be rendered on all pages of our app since every page is under the layout.
 ///
@@ -20,6 +20,8 @@ pub fn NavbarLayout() -> Element {
             // The `Outlet` component is used to render the next component inside the layout.
             Outlet::<Route> {}
         }
+
+        TransferPanel {}
     }
 }
 
@@ -6,10 +6,10 @@ use objstore_config::{ConnectionConfig, DynConfigStore, LoadedConnection};
 
 use crate::{
     cmp::{
-        s3::{ConnectionPersistence, S3Form},
-        util::form::FormSubmit,
+        s3::S3Form,
+        util::form::{ConnectionPersistence, FormSubmit},
     },
-    context::{use_config_store, use_providers, use_stores},
+    context::{use_config_store, use_providers, use_stores, with_policy},
     router::Route,
 };
 
@@ -21,9 +21,40 @@ enum Msg {
     Cancel,
 }
 
+/// The backends that can be configured in the UI.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Backend {
+    S3,
+    #[cfg(feature = "desktop")]
+    Fs,
+    #[cfg(feature = "desktop")]
+    LogFs,
+}
+
+impl Backend {
+    const ALL: &'static [Self] = &[
+        Self::S3,
+        #[cfg(feature = "desktop")]
+        Self::Fs,
+        #[cfg(feature = "desktop")]
+        Self::LogFs,
+    ];
+
+    fn label(self) -> &'static str {
+        match self {
+            Self::S3 => "S3",
+            #[cfg(feature = "desktop")]
+            Self::Fs => "Filesystem",
+            #[cfg(feature = "desktop")]
+            Self::LogFs => "LogFS",
+        }
+    }
+}
+
 #[component]
 pub fn NewConnection() -> Element {
     let mut status = use_signal(|| FormSubmit::Idle);
+    let mut backend = use_signal(|| Backend::S3);
 
     let coro = use_coroutine::<Msg, _, _>(move |mut rx| async move {
         let mut task: Option<dioxus_core::Task> = None;
@@ -60,19 +91,54 @@ pub fn NewConnection() -> Element {
         }
     });
 
+    let on_submit = move |(config, persist)| {
+        coro.send(Msg::Submit { config, persist });
+    };
+    let on_cancel = move |_| {
+        use_navigator().push(Route::Home {});
+    };
+
     rsx! {
-        S3Form {
-            status,
-            on_submit: move |(config, persist)| {
-                coro.send(Msg::Submit{
-                    config,
-                    persist,
-                });
+        div {
+            class: "tabs",
+            ul {
+                for b in Backend::ALL.iter().copied() {
+                    li {
+                        class: if backend() == b { "is-active" } else { "" },
+                        a {
+                            onclick: move |_| backend.set(b),
+                            "{b.label()}"
+                        }
+                    }
+                }
+            }
+        }
+
+        match backend() {
+            Backend::S3 => rsx! {
+                S3Form {
+                    status,
+                    on_submit,
+                    on_cancel,
+                    initial_value: None,
+                }
             },
-            on_cancel: move |_| {
-                use_navigator().push(Route::Home {  });
+            #[cfg(feature = "desktop")]
+            Backend::Fs => rsx! {
+                crate::cmp::fs::FsForm {
+                    status,
+                    on_submit,
+                    on_cancel,
+                }
+            },
+            #[cfg(feature = "desktop")]
+            Backend::LogFs => rsx! {
+                crate::cmp::logfs::LogFsForm {
+                    status,
+                    on_submit,
+                    on_cancel,
+                }
             },
-            initial_value: None,
         }
     }
 }
@@ -85,6 +151,7 @@ async fn create_connection(
 ) -> Result<(LoadedConnection, DynObjStore), anyhow::Error> {
     let store = builder
         .build(&config.uri)
+        .map(with_policy)
         .with_context(|| format!("Failed to build store for URI: '{}'", config.uri))?;
     store.healthcheck().await?;
 
@@ -94,6 +161,7 @@ async fn create_connection(
         }
         ConnectionPersistence::Temporary => LoadedConnection {
             source: None,
+            profile: None,
             config: config.clone(),
         },
     };
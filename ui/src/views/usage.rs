@@ -0,0 +1,44 @@
+use dioxus::prelude::*;
+use dioxus_bulma::{Color, Notification};
+
+use crate::{
+    cmp::{util::loader::Spinner, UsageDashboard},
+    context::{use_config_store, use_stores, ActiveStore},
+};
+
+#[component]
+pub fn UsagePage(store: ReadOnlySignal<String>) -> Element {
+    let active_store = use_resource::<Result<ActiveStore, anyhow::Error>, _>(move || async move {
+        let stores = use_stores();
+        let config_store = use_config_store();
+        let builder = crate::context::use_providers();
+
+        stores.resolve(&store(), config_store.get(), &builder).await
+    });
+
+    let out = match &*active_store.read() {
+        None => {
+            rsx! {
+                Spinner {}
+            }
+        }
+        Some(Err(err)) => {
+            rsx! {
+                Notification {
+                    color: Color::Danger,
+                    "{err}"
+                }
+            }
+        }
+        Some(Ok(store)) => {
+            rsx! {
+                UsageDashboard {
+                    key: "{store.config.config.name}",
+                    store: store.clone(),
+                }
+            }
+        }
+    };
+
+    out
+}